@@ -0,0 +1,110 @@
+use crate::bid::{Bid, BidStatus, BidStorage};
+use crate::config_history::record_config_change;
+use crate::errors::QuickLendXError;
+use crate::events::{
+    emit_acceptance_confirmed, emit_acceptance_requested, emit_acceptance_window_set,
+};
+use crate::invoice::Invoice;
+use crate::storage::{self, DataKey};
+use crate::verification::require_admin;
+use soroban_sdk::{contracttype, symbol_short, Address, BytesN, Env};
+
+/// A business's acceptance of a bid, waiting for the investor to confirm it.
+/// Nothing is escrowed until the investor confirms before `expires_at`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingAcceptance {
+    pub bid_id: BytesN<32>,
+    pub invoice_id: BytesN<32>,
+    pub business: Address,
+    pub terms_hash: Option<BytesN<32>>, // Terms the business signed for, if any
+    pub requested_at: u64,
+    pub expires_at: u64,
+}
+
+pub struct AcceptanceStorage;
+
+impl AcceptanceStorage {
+    /// Get how long investors have to confirm an acceptance, in seconds; 0
+    /// when acceptances take effect without confirmation
+    pub fn get_confirmation_window(env: &Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("acc_win"))
+            .unwrap_or(0)
+    }
+
+    /// Get the acceptance of a bid waiting for confirmation, if any
+    pub fn get_pending(env: &Env, bid_id: &BytesN<32>) -> Option<PendingAcceptance> {
+        storage::get(env, &DataKey::PendingAcceptance(bid_id.clone()))
+    }
+}
+
+/// Set how long investors have to confirm that their bid may be accepted, in
+/// seconds; 0 lets the business's acceptance fund the invoice directly
+/// (admin only)
+pub fn set_confirmation_window(
+    env: &Env,
+    admin: &Address,
+    window: u64,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    env.storage()
+        .instance()
+        .set(&symbol_short!("acc_win"), &window);
+    record_config_change(env, "acceptance_window", window, admin);
+    emit_acceptance_window_set(env, window, admin);
+    Ok(())
+}
+
+/// Record the business's acceptance of a placed bid for the investor to
+/// confirm. Asking again replaces an earlier request and restarts its window.
+pub fn request_acceptance(
+    env: &Env,
+    business: &Address,
+    invoice: &Invoice,
+    bid: &Bid,
+    terms_hash: Option<BytesN<32>>,
+) -> Result<PendingAcceptance, QuickLendXError> {
+    if bid.status != BidStatus::Placed || bid.invoice_id != invoice.id {
+        return Err(QuickLendXError::InvalidStatus);
+    }
+    let now = env.ledger().timestamp();
+    let pending = PendingAcceptance {
+        bid_id: bid.bid_id.clone(),
+        invoice_id: invoice.id.clone(),
+        business: business.clone(),
+        terms_hash,
+        requested_at: now,
+        expires_at: now.saturating_add(AcceptanceStorage::get_confirmation_window(env)),
+    };
+    storage::set(
+        env,
+        &DataKey::PendingAcceptance(bid.bid_id.clone()),
+        &pending,
+    );
+    emit_acceptance_requested(env, &pending, &bid.investor);
+    Ok(pending)
+}
+
+/// Take the pending acceptance of the investor's bid for it to be carried
+/// out (investor only). Expired requests can no longer be confirmed.
+pub fn confirm_acceptance(
+    env: &Env,
+    investor: &Address,
+    bid_id: &BytesN<32>,
+) -> Result<(PendingAcceptance, Bid), QuickLendXError> {
+    investor.require_auth();
+    let bid = BidStorage::get_bid(env, bid_id).ok_or(QuickLendXError::StorageKeyNotFound)?;
+    if bid.investor != *investor {
+        return Err(QuickLendXError::NotInvestor);
+    }
+    let pending =
+        AcceptanceStorage::get_pending(env, bid_id).ok_or(QuickLendXError::AcceptanceNotFound)?;
+    if env.ledger().timestamp() > pending.expires_at {
+        return Err(QuickLendXError::AcceptanceExpired);
+    }
+    storage::remove(env, &DataKey::PendingAcceptance(bid_id.clone()));
+    emit_acceptance_confirmed(env, &pending, investor);
+    Ok((pending, bid))
+}
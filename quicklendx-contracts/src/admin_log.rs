@@ -0,0 +1,92 @@
+use crate::events::emit_admin_action_logged;
+use crate::storage::{self, DataKey};
+use soroban_sdk::{
+    contracttype, symbol_short, xdr::ToXdr, Address, BytesN, Env, IntoVal, Symbol, Val, Vec,
+};
+
+/// Most admin actions returned by one range query
+pub const MAX_ADMIN_ACTIONS_PAGE: u32 = 100;
+
+/// A privileged action, kept apart from the invoice-scoped audit trail.
+/// Only a hash of the parameters is stored; callers who know the
+/// parameters can check them against it.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AdminAction {
+    pub id: u64, // Sequential from 1, in the order actions were taken
+    pub action: Symbol,
+    pub actor: Address,
+    pub params_hash: BytesN<32>, // sha256 of the XDR of the parameters
+    pub timestamp: u64,
+}
+
+pub struct AdminLogStorage;
+
+impl AdminLogStorage {
+    /// Get how many admin actions have been logged
+    pub fn get_count(env: &Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("adm_cnt"))
+            .unwrap_or(0)
+    }
+
+    /// Get a logged admin action by id
+    pub fn get_action(env: &Env, id: u64) -> Option<AdminAction> {
+        storage::get(env, &DataKey::AdminAction(id))
+    }
+}
+
+/// Log a privileged action taken by `actor`. Called at the start of each
+/// privileged entrypoint; if the action then fails, the entry is rolled back
+/// with it.
+pub fn record_admin_action<P: IntoVal<Env, Val>>(
+    env: &Env,
+    actor: &Address,
+    action: &str,
+    params: P,
+) {
+    let id = AdminLogStorage::get_count(env) + 1;
+    let entry = AdminAction {
+        id,
+        action: Symbol::new(env, action),
+        actor: actor.clone(),
+        params_hash: env.crypto().sha256(&params.to_xdr(env)).into(),
+        timestamp: env.ledger().timestamp(),
+    };
+    storage::set(env, &DataKey::AdminAction(id), &entry);
+    env.storage().instance().set(&symbol_short!("adm_cnt"), &id);
+    emit_admin_action_logged(env, &entry);
+}
+
+/// Get the admin actions taken between `from` and `to` (inclusive), oldest
+/// first, up to `limit` of them
+pub fn get_admin_actions(env: &Env, from: u64, to: u64, limit: u32) -> Vec<AdminAction> {
+    let mut actions = Vec::new(env);
+    let count = AdminLogStorage::get_count(env);
+    let limit = limit.min(MAX_ADMIN_ACTIONS_PAGE);
+
+    // Timestamps never decrease with the id, so find the first action at or
+    // after `from` by binary search
+    let (mut low, mut high) = (1u64, count + 1);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        match AdminLogStorage::get_action(env, mid) {
+            Some(action) if action.timestamp < from => low = mid + 1,
+            _ => high = mid,
+        }
+    }
+
+    let mut id = low;
+    while id <= count && actions.len() < limit {
+        let Some(action) = AdminLogStorage::get_action(env, id) else {
+            break;
+        };
+        if action.timestamp > to {
+            break;
+        }
+        actions.push_back(action);
+        id += 1;
+    }
+    actions
+}
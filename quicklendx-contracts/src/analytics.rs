@@ -0,0 +1,254 @@
+use crate::errors::QuickLendXError;
+use crate::investment::Investment;
+use crate::invoice::{Invoice, InvoiceStatus};
+use crate::oracle::to_reference;
+use crate::profits::{calculate_realized_apr_bps, SECONDS_PER_DAY};
+use crate::storage::{self, DataKey};
+use soroban_sdk::{contracttype, symbol_short, Address, Env, Vec};
+
+/// Running volume totals for one currency, counted from when this tracking
+/// was deployed. Escrows created before then add nothing to `escrowed`, so
+/// releasing them never takes it below zero.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CurrencyStats {
+    pub funded_volume: i128,    // Accepted bid amounts
+    pub escrowed: i128,         // Value held in escrow now (TVL)
+    pub settled_volume: i128,   // Payments that fully settled an invoice
+    pub defaulted_volume: i128, // Funding of invoices that defaulted
+}
+
+/// Platform-wide totals, kept up to date as invoices move through their life
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PlatformStats {
+    pub invoices_created: u64,
+    pub currencies: Vec<Address>, // Every currency with volume, oldest first
+    pub by_currency: Vec<CurrencyStats>, // In the order of `currencies`
+}
+
+/// Running totals of one business's invoices, counted from when this
+/// tracking was deployed
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BusinessCounters {
+    pub invoices_created: u32,
+    pub funded: u32,
+    pub paid: u32,
+    pub paid_on_time: u32, // Settled by their due date
+    pub defaulted: u32,
+    pub total_settlement_delay: u64, // Seconds past due, summed over paid invoices
+}
+
+/// A business's track record, for investors' due diligence
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BusinessStats {
+    pub total_invoices: u32,
+    pub funded_count: u32,
+    pub paid_count: u32,
+    pub default_count: u32,
+    pub on_time_rate_bps: u32, // Share of paid invoices settled by their due date
+    pub average_settlement_delay: u64, // Mean seconds past due over paid invoices
+}
+
+impl BusinessCounters {
+    /// Get the track record these totals add up to
+    pub fn stats(&self) -> BusinessStats {
+        let (on_time_rate_bps, average_settlement_delay) = match self.paid {
+            0 => (0, 0),
+            paid => (
+                self.paid_on_time * 10_000 / paid,
+                self.total_settlement_delay / paid as u64,
+            ),
+        };
+        BusinessStats {
+            total_invoices: self.invoices_created,
+            funded_count: self.funded,
+            paid_count: self.paid,
+            default_count: self.defaulted,
+            on_time_rate_bps,
+            average_settlement_delay,
+        }
+    }
+}
+
+/// What an investor has put into and got back from investments repaid
+/// through settlement, counted from when this tracking was deployed
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct InvestorReturns {
+    pub invested: i128,
+    pub returned: i128,        // Net of platform fees and insurance levies
+    pub capital_seconds: i128, // Each amount invested times the seconds it was out
+    pub settled_investments: u32,
+}
+
+pub struct AnalyticsStorage;
+
+impl AnalyticsStorage {
+    /// Get the running totals for one currency
+    pub fn get_currency_stats(env: &Env, currency: &Address) -> CurrencyStats {
+        storage::get(env, &DataKey::CurrencyStats(currency.clone())).unwrap_or_default()
+    }
+
+    /// Get a business's running invoice totals
+    pub fn get_business_counters(env: &Env, business: &Address) -> BusinessCounters {
+        storage::get(env, &DataKey::BusinessCounters(business.clone())).unwrap_or_default()
+    }
+
+    /// Get an investor's running totals over settled investments
+    pub fn get_investor_returns(env: &Env, investor: &Address) -> InvestorReturns {
+        storage::get(env, &DataKey::InvestorReturns(investor.clone())).unwrap_or_default()
+    }
+
+    /// Get the number of invoices created
+    pub fn get_invoices_created(env: &Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("inv_made"))
+            .unwrap_or(0)
+    }
+
+    fn get_currencies(env: &Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("stat_cur"))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+}
+
+/// Get the platform-wide totals, with one entry per currency seen
+pub fn get_platform_stats(env: &Env) -> PlatformStats {
+    let currencies = AnalyticsStorage::get_currencies(env);
+    let mut by_currency = Vec::new(env);
+    for currency in currencies.iter() {
+        by_currency.push_back(AnalyticsStorage::get_currency_stats(env, &currency));
+    }
+    PlatformStats {
+        invoices_created: AnalyticsStorage::get_invoices_created(env),
+        currencies,
+        by_currency,
+    }
+}
+
+/// Get the platform-wide totals across every currency, valued in the
+/// reference currency
+pub fn get_reference_stats(env: &Env) -> Result<CurrencyStats, QuickLendXError> {
+    let mut total = CurrencyStats::default();
+    for currency in AnalyticsStorage::get_currencies(env).iter() {
+        let stats = AnalyticsStorage::get_currency_stats(env, &currency);
+        total.funded_volume += to_reference(env, &currency, stats.funded_volume)?;
+        total.escrowed += to_reference(env, &currency, stats.escrowed)?;
+        total.settled_volume += to_reference(env, &currency, stats.settled_volume)?;
+        total.defaulted_volume += to_reference(env, &currency, stats.defaulted_volume)?;
+    }
+    Ok(total)
+}
+
+/// Count a newly created invoice
+pub fn record_invoice_created(env: &Env, invoice: &Invoice) {
+    let created = AnalyticsStorage::get_invoices_created(env) + 1;
+    env.storage()
+        .instance()
+        .set(&symbol_short!("inv_made"), &created);
+    update_business(env, &invoice.business, |counters| {
+        counters.invoices_created += 1
+    });
+}
+
+/// Count a bid amount that funded an invoice, and the invoice itself once
+/// it is fully funded
+pub fn record_funded(env: &Env, invoice: &Invoice, amount: i128) {
+    update(env, &invoice.currency, |stats| {
+        stats.funded_volume += amount
+    });
+    if invoice.status == InvoiceStatus::Funded {
+        update_business(env, &invoice.business, |counters| counters.funded += 1);
+    }
+}
+
+/// Count value moving into (positive) or out of (negative) escrow
+pub fn record_escrow_change(env: &Env, currency: &Address, change: i128) {
+    update(env, currency, |stats| {
+        stats.escrowed = (stats.escrowed + change).max(0);
+    });
+}
+
+/// Count a payment that fully settled an invoice, and how late it was
+pub fn record_settled(env: &Env, invoice: &Invoice, amount: i128) {
+    update(env, &invoice.currency, |stats| {
+        stats.settled_volume += amount
+    });
+    let settled_at = invoice.settled_at.unwrap_or(env.ledger().timestamp());
+    let delay = settled_at.saturating_sub(invoice.due_date);
+    update_business(env, &invoice.business, |counters| {
+        counters.paid += 1;
+        if delay == 0 {
+            counters.paid_on_time += 1;
+        }
+        counters.total_settlement_delay += delay;
+    });
+}
+
+/// Count the funding of an invoice that defaulted
+pub fn record_defaulted(env: &Env, invoice: &Invoice) {
+    update(env, &invoice.currency, |stats| {
+        stats.defaulted_volume += invoice.funded_amount
+    });
+    update_business(env, &invoice.business, |counters| counters.defaulted += 1);
+}
+
+/// Count an investment repaid through settlement, weighting its amount by
+/// how long it was out
+pub fn record_settled_investment(env: &Env, investment: &Investment) {
+    let duration = env
+        .ledger()
+        .timestamp()
+        .saturating_sub(investment.funded_at)
+        .max(SECONDS_PER_DAY);
+    update_investor(env, &investment.investor, |returns| {
+        returns.invested += investment.amount;
+        returns.capital_seconds += investment.amount * duration as i128;
+        returns.settled_investments += 1;
+    });
+}
+
+/// Count a settlement's net return to an investor
+pub fn record_investor_return(env: &Env, investor: &Address, amount: i128) {
+    update_investor(env, investor, |returns| returns.returned += amount);
+}
+
+/// Get the annualized return in basis points an investor realized over their
+/// settled investments, weighting each by its amount and how long it was out
+pub fn get_realized_apr_bps(env: &Env, investor: &Address) -> i128 {
+    let returns = AnalyticsStorage::get_investor_returns(env, investor);
+    calculate_realized_apr_bps(returns.returned - returns.invested, returns.capital_seconds)
+}
+
+fn update_investor(env: &Env, investor: &Address, change: impl FnOnce(&mut InvestorReturns)) {
+    let mut returns = AnalyticsStorage::get_investor_returns(env, investor);
+    change(&mut returns);
+    storage::set(env, &DataKey::InvestorReturns(investor.clone()), &returns);
+}
+
+fn update_business(env: &Env, business: &Address, change: impl FnOnce(&mut BusinessCounters)) {
+    let mut counters = AnalyticsStorage::get_business_counters(env, business);
+    change(&mut counters);
+    storage::set(env, &DataKey::BusinessCounters(business.clone()), &counters);
+}
+
+fn update(env: &Env, currency: &Address, change: impl FnOnce(&mut CurrencyStats)) {
+    let key = DataKey::CurrencyStats(currency.clone());
+    let existing: Option<CurrencyStats> = storage::get(env, &key);
+    if existing.is_none() {
+        let mut currencies = AnalyticsStorage::get_currencies(env);
+        currencies.push_back(currency.clone());
+        env.storage()
+            .instance()
+            .set(&symbol_short!("stat_cur"), &currencies);
+    }
+    let mut stats = existing.unwrap_or_default();
+    change(&mut stats);
+    storage::set(env, &key, &stats);
+}
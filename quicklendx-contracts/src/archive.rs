@@ -0,0 +1,242 @@
+use crate::config_history::record_config_change;
+use crate::defaults::get_default_recovery;
+use crate::errors::QuickLendXError;
+use crate::events::{emit_archive_age_set, emit_invoice_archived, emit_invoice_unarchived};
+use crate::insurance::invoice_risk_points;
+use crate::invoice::{Invoice, InvoiceDocument, InvoiceStatus, InvoiceStorage};
+use crate::storage::{self, DataKey};
+use crate::verification::require_admin;
+use soroban_sdk::{contracttype, symbol_short, vec, Address, BytesN, Env, String, Vec};
+
+/// How long after closing a paid or written-off invoice becomes eligible for
+/// archiving, unless the admin sets another age
+pub const DEFAULT_ARCHIVE_AGE: u64 = 90 * 86_400;
+
+/// A closed invoice moved out of the live lists. Individual ratings are
+/// dropped, keeping only their average and count, and so are the category
+/// and tags, which only serve to filter the marketplace.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ArchivedInvoice {
+    pub id: BytesN<32>,
+    pub business: Address,
+    pub amount: i128,
+    pub currency: Address,
+    pub due_date: u64,
+    pub status: InvoiceStatus, // Paid or Defaulted
+    pub created_at: u64,
+    pub description: String,
+    pub invoice_hash: Option<BytesN<32>>,
+    pub documents: Vec<InvoiceDocument>,
+    pub funded_amount: i128,
+    pub funded_at: Option<u64>,
+    pub investors: Vec<Address>,
+    pub settled_at: Option<u64>,
+    pub average_rating: Option<u32>,
+    pub total_ratings: u32,
+    pub archived_at: u64,
+}
+
+impl ArchivedInvoice {
+    /// Rebuild the live invoice, without its individual ratings, category,
+    /// tags or due-date extension
+    pub fn to_invoice(&self, env: &Env) -> Invoice {
+        Invoice {
+            id: self.id.clone(),
+            business: self.business.clone(),
+            amount: self.amount,
+            currency: self.currency.clone(),
+            due_date: self.due_date,
+            extensions: vec![env],
+            status: self.status.clone(),
+            created_at: self.created_at,
+            description: self.description.clone(),
+            invoice_hash: self.invoice_hash.clone(),
+            documents: self.documents.clone(),
+            category: None,
+            tags: vec![env],
+            funded_amount: self.funded_amount,
+            funded_at: self.funded_at,
+            investor: self.investors.first(),
+            investors: self.investors.clone(),
+            settled_at: self.settled_at,
+            average_rating: self.average_rating,
+            total_ratings: self.total_ratings,
+            ratings: vec![env],
+        }
+    }
+}
+
+/// Outcomes of a business's archived invoices, which its risk score keeps
+/// counting once they leave the business's live invoice list
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ArchivedOutcomes {
+    pub paid: u32,
+    pub defaulted: u32,
+    pub risk_points: u32, // Summed history risk points of the archived invoices
+}
+
+pub struct ArchiveStorage;
+
+impl ArchiveStorage {
+    /// Get how long after closing an invoice may be archived
+    pub fn get_archive_age(env: &Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("arch_age"))
+            .unwrap_or(DEFAULT_ARCHIVE_AGE)
+    }
+
+    /// Get an archived invoice
+    pub fn get_archived_invoice(env: &Env, invoice_id: &BytesN<32>) -> Option<ArchivedInvoice> {
+        storage::get(env, &DataKey::ArchivedInvoice(invoice_id.clone()))
+    }
+
+    /// Get the ids of a business's archived invoices, oldest archived first
+    pub fn get_business_archive(env: &Env, business: &Address) -> Vec<BytesN<32>> {
+        storage::get(env, &DataKey::BusinessArchive(business.clone()))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    /// Get the outcomes of a business's archived invoices
+    pub fn get_archived_outcomes(env: &Env, business: &Address) -> ArchivedOutcomes {
+        storage::get(env, &DataKey::ArchivedOutcomes(business.clone())).unwrap_or_default()
+    }
+
+    fn set_archived_outcomes(env: &Env, business: &Address, outcomes: &ArchivedOutcomes) {
+        storage::set(env, &DataKey::ArchivedOutcomes(business.clone()), outcomes);
+    }
+
+    fn set_business_archive(env: &Env, business: &Address, invoice_ids: &Vec<BytesN<32>>) {
+        storage::set(
+            env,
+            &DataKey::BusinessArchive(business.clone()),
+            invoice_ids,
+        );
+    }
+}
+
+/// Set how long after closing a paid or defaulted invoice may be archived,
+/// in seconds (admin only)
+pub fn set_archive_age(env: &Env, admin: &Address, age: u64) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    env.storage()
+        .instance()
+        .set(&symbol_short!("arch_age"), &age);
+    record_config_change(env, "archive_age", age, admin);
+    emit_archive_age_set(env, age, admin);
+    Ok(())
+}
+
+/// When an invoice closed: its settlement for paid invoices, the write-off
+/// of its recovery for defaulted ones. Defaulted invoices still recovering
+/// have not closed.
+fn closed_at(env: &Env, invoice: &Invoice) -> Option<u64> {
+    match invoice.status {
+        InvoiceStatus::Paid => invoice.settled_at,
+        InvoiceStatus::Defaulted => {
+            get_default_recovery(env, &invoice.id).and_then(|recovery| recovery.written_off_at)
+        }
+        _ => None,
+    }
+}
+
+/// Archive up to `max_invoices` paid or written-off defaulted invoices that
+/// closed at least the archive age ago, moving them out of the status, business,
+/// category and tag lists. Anyone may call this. Returns the ids archived.
+pub fn archive_invoices(env: &Env, max_invoices: u32) -> Vec<BytesN<32>> {
+    let now = env.ledger().timestamp();
+    let age = ArchiveStorage::get_archive_age(env);
+    let mut archived = Vec::new(env);
+    for status in [InvoiceStatus::Paid, InvoiceStatus::Defaulted].iter() {
+        for invoice_id in InvoiceStorage::get_invoices_by_status(env, status).iter() {
+            if archived.len() >= max_invoices {
+                return archived;
+            }
+            let Some(invoice) = InvoiceStorage::get_invoice(env, &invoice_id) else {
+                continue;
+            };
+            let eligible = closed_at(env, &invoice).is_some_and(|at| now.saturating_sub(at) >= age);
+            if eligible {
+                archive_invoice(env, invoice);
+                archived.push_back(invoice_id);
+            }
+        }
+    }
+    archived
+}
+
+fn archive_invoice(env: &Env, invoice: Invoice) {
+    let record = ArchivedInvoice {
+        id: invoice.id.clone(),
+        business: invoice.business.clone(),
+        amount: invoice.amount,
+        currency: invoice.currency.clone(),
+        due_date: invoice.due_date,
+        status: invoice.status.clone(),
+        created_at: invoice.created_at,
+        description: invoice.description.clone(),
+        invoice_hash: invoice.invoice_hash.clone(),
+        documents: invoice.documents.clone(),
+        funded_amount: invoice.funded_amount,
+        funded_at: invoice.funded_at,
+        investors: invoice.investors.clone(),
+        settled_at: invoice.settled_at,
+        average_rating: invoice.average_rating,
+        total_ratings: invoice.total_ratings,
+        archived_at: env.ledger().timestamp(),
+    };
+    storage::set(env, &DataKey::ArchivedInvoice(invoice.id.clone()), &record);
+    let mut business_archive = ArchiveStorage::get_business_archive(env, &invoice.business);
+    business_archive.push_back(invoice.id.clone());
+    ArchiveStorage::set_business_archive(env, &invoice.business, &business_archive);
+    update_outcomes(env, &invoice, true);
+
+    InvoiceStorage::remove_from_status_invoices(env, &invoice.status, &invoice.id);
+    InvoiceStorage::remove_from_business_invoices(env, &invoice.business, &invoice.id);
+    InvoiceStorage::unindex_metadata(env, &invoice);
+    storage::remove(env, &DataKey::Invoice(invoice.id.clone()));
+    emit_invoice_archived(env, &record);
+}
+
+/// Bring an archived invoice back into the live lists (admin only)
+pub fn restore_archived_invoice(
+    env: &Env,
+    admin: &Address,
+    invoice_id: &BytesN<32>,
+) -> Result<Invoice, QuickLendXError> {
+    require_admin(env, admin)?;
+    let record = ArchiveStorage::get_archived_invoice(env, invoice_id)
+        .ok_or(QuickLendXError::InvoiceNotFound)?;
+    let invoice = record.to_invoice(env);
+    InvoiceStorage::store_invoice(env, &invoice);
+
+    storage::remove(env, &DataKey::ArchivedInvoice(invoice_id.clone()));
+    let mut business_archive = ArchiveStorage::get_business_archive(env, &record.business);
+    if let Some(index) = business_archive.first_index_of(invoice_id) {
+        business_archive.remove(index);
+    }
+    ArchiveStorage::set_business_archive(env, &record.business, &business_archive);
+    update_outcomes(env, &invoice, false);
+    emit_invoice_unarchived(env, invoice_id, admin);
+    Ok(invoice)
+}
+
+/// Count an invoice in, or back out of, its business's archived outcomes
+fn update_outcomes(env: &Env, invoice: &Invoice, archived: bool) {
+    let mut outcomes = ArchiveStorage::get_archived_outcomes(env, &invoice.business);
+    let points = invoice_risk_points(env, invoice).unwrap_or(0);
+    let count = match invoice.status {
+        InvoiceStatus::Paid => &mut outcomes.paid,
+        _ => &mut outcomes.defaulted,
+    };
+    if archived {
+        *count += 1;
+        outcomes.risk_points += points;
+    } else {
+        *count = count.saturating_sub(1);
+        outcomes.risk_points = outcomes.risk_points.saturating_sub(points);
+    }
+    ArchiveStorage::set_archived_outcomes(env, &invoice.business, &outcomes);
+}
@@ -0,0 +1,456 @@
+use crate::config_history::record_config_change;
+use crate::errors::QuickLendXError;
+use crate::events::{
+    emit_attestation_imported, emit_attestation_issuer_registered, emit_attestation_issuer_revoked,
+    emit_attestation_max_age_set, emit_attestation_published, emit_imported_attestation_revoked,
+};
+use crate::insurance::publish_risk_score;
+use crate::storage::{self, DataKey};
+use crate::verification::{require_admin, require_business_verification};
+use soroban_sdk::{contracttype, symbol_short, Address, BytesN, Env, Map, Vec};
+
+/// Most attestations, published or imported, returned by one query
+pub const MAX_ATTESTATIONS_PAGE: u32 = 50;
+/// Age after which an attestation is stale unless the admin sets another
+pub const DEFAULT_ATTESTATION_MAX_AGE: u64 = 90 * 86_400;
+/// Risk score points taken off a business with a fresh attestation
+pub const FRESH_ATTESTATION_CREDIT: u32 = 10;
+/// Risk score points taken off a business with imported settlement history
+pub const IMPORTED_ATTESTATION_CREDIT: u32 = 5;
+
+/// Financial figures a business reports about itself. Only hashes of the
+/// underlying documents go on chain; investors compare them off chain.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FinancialAttestation {
+    pub business: Address,
+    pub revenue_band: u32,         // Self-reported annual revenue band
+    pub ar_aging_hash: BytesN<32>, // Hash of the receivables aging summary
+    pub published_at: u64,
+}
+
+/// Latest attestation of a business and whether investors should still rely
+/// on it
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AttestationStatus {
+    pub business: Address,
+    pub count: u32, // Attestations published so far
+    pub last_published_at: Option<u64>,
+    pub stale_at: Option<u64>,
+    pub is_stale: bool, // Also true when the business never attested
+}
+
+/// Another contract whose attestations the admin trusts, and how much of the
+/// volume it attests to counts here
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AttestationIssuer {
+    pub issuer: Address,
+    pub weight_bps: u32, // Share of attested volume that counts, up to 10_000
+    pub registered_at: u64,
+    pub revoked: bool, // Its attestations stop counting once revoked
+}
+
+/// A claim an external issuer signed about a business's history elsewhere,
+/// such as the volume it settled on another protocol
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ImportedAttestation {
+    pub issuer: Address,
+    pub business: Address,
+    pub claim_hash: BytesN<32>, // Hash of the issuer's full claim
+    pub settled_volume: i128,
+    pub imported_at: u64,
+    pub revoked: bool,
+}
+
+pub struct AttestationStorage;
+
+impl AttestationStorage {
+    /// Get how many attestations a business has published
+    pub fn get_attestation_count(env: &Env, business: &Address) -> u32 {
+        storage::get(env, &DataKey::AttestationCount(business.clone())).unwrap_or(0)
+    }
+
+    /// Get a business's attestation by its number, counting from 1
+    pub fn get_attestation(
+        env: &Env,
+        business: &Address,
+        number: u32,
+    ) -> Option<FinancialAttestation> {
+        storage::get(env, &DataKey::Attestation(business.clone(), number))
+    }
+
+    /// Get a business's attestations, oldest first, skipping `offset` and
+    /// returning at most `limit`
+    pub fn get_attestations(
+        env: &Env,
+        business: &Address,
+        offset: u32,
+        limit: u32,
+    ) -> Vec<FinancialAttestation> {
+        let mut attestations = Vec::new(env);
+        let count = Self::get_attestation_count(env, business);
+        let limit = limit.min(MAX_ATTESTATIONS_PAGE);
+        let mut number = offset.saturating_add(1);
+        while number <= count && attestations.len() < limit {
+            if let Some(attestation) = Self::get_attestation(env, business, number) {
+                attestations.push_back(attestation);
+            }
+            number += 1;
+        }
+        attestations
+    }
+
+    /// Record a business's attestation under its next number
+    pub(crate) fn add_attestation(env: &Env, attestation: &FinancialAttestation) {
+        let business = &attestation.business;
+        let number = Self::get_attestation_count(env, business) + 1;
+        storage::set(
+            env,
+            &DataKey::Attestation(business.clone(), number),
+            attestation,
+        );
+        storage::set(env, &DataKey::AttestationCount(business.clone()), &number);
+    }
+
+    /// Get how long after publication an attestation stays fresh
+    pub fn get_max_age(env: &Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("att_age"))
+            .unwrap_or(DEFAULT_ATTESTATION_MAX_AGE)
+    }
+
+    /// Get every issuer the admin has registered, revoked ones included
+    pub fn get_issuers(env: &Env) -> Vec<AttestationIssuer> {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("att_iss"))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    /// Get a registered issuer
+    pub fn get_issuer(env: &Env, issuer: &Address) -> Option<AttestationIssuer> {
+        Self::get_issuers(env)
+            .iter()
+            .find(|entry| entry.issuer == *issuer)
+    }
+
+    fn set_issuer(env: &Env, issuer: &AttestationIssuer) {
+        let mut issuers = Self::get_issuers(env);
+        match issuers
+            .iter()
+            .position(|entry| entry.issuer == issuer.issuer)
+        {
+            Some(index) => issuers.set(index as u32, issuer.clone()),
+            None => issuers.push_back(issuer.clone()),
+        }
+        env.storage()
+            .instance()
+            .set(&symbol_short!("att_iss"), &issuers);
+    }
+
+    /// Get how many attestations have been imported for a business
+    pub fn get_imported_count(env: &Env, business: &Address) -> u32 {
+        storage::get(env, &DataKey::ImportedAttestationCount(business.clone())).unwrap_or(0)
+    }
+
+    /// Get an attestation imported for a business by its number, counting
+    /// from 1
+    pub fn get_imported_attestation(
+        env: &Env,
+        business: &Address,
+        number: u32,
+    ) -> Option<ImportedAttestation> {
+        storage::get(env, &DataKey::ImportedAttestation(business.clone(), number))
+    }
+
+    /// Get the attestations imported for a business, oldest first, skipping
+    /// `offset` and returning at most `limit`
+    pub fn get_imported(
+        env: &Env,
+        business: &Address,
+        offset: u32,
+        limit: u32,
+    ) -> Vec<ImportedAttestation> {
+        let mut imported = Vec::new(env);
+        let count = Self::get_imported_count(env, business);
+        let limit = limit.min(MAX_ATTESTATIONS_PAGE);
+        let mut number = offset.saturating_add(1);
+        while number <= count && imported.len() < limit {
+            if let Some(claim) = Self::get_imported_attestation(env, business, number) {
+                imported.push_back(claim);
+            }
+            number += 1;
+        }
+        imported
+    }
+
+    /// Get the number an issuer's claim was imported under for a business
+    fn get_imported_number(
+        env: &Env,
+        business: &Address,
+        issuer: &Address,
+        claim_hash: &BytesN<32>,
+    ) -> Option<u32> {
+        storage::get(
+            env,
+            &DataKey::ImportedClaim(business.clone(), issuer.clone(), claim_hash.clone()),
+        )
+    }
+
+    /// Get the settled volume of a business's live imported attestations,
+    /// per issuer
+    pub fn get_imported_volume(env: &Env, business: &Address) -> Map<Address, i128> {
+        storage::get(env, &DataKey::ImportedVolume(business.clone()))
+            .unwrap_or_else(|| Map::new(env))
+    }
+
+    /// Add to or take from the live settled volume an issuer vouches for
+    fn adjust_imported_volume(env: &Env, business: &Address, issuer: &Address, delta: i128) {
+        let mut volume = Self::get_imported_volume(env, business);
+        let total = volume
+            .get(issuer.clone())
+            .unwrap_or(0)
+            .saturating_add(delta);
+        if total > 0 {
+            volume.set(issuer.clone(), total);
+        } else {
+            volume.remove(issuer.clone());
+        }
+        storage::set(env, &DataKey::ImportedVolume(business.clone()), &volume);
+    }
+
+    /// Record an attestation imported for a business under its next number
+    pub(crate) fn add_imported(env: &Env, claim: &ImportedAttestation) {
+        let business = &claim.business;
+        let number = Self::get_imported_count(env, business) + 1;
+        storage::set(
+            env,
+            &DataKey::ImportedAttestation(business.clone(), number),
+            claim,
+        );
+        storage::set(
+            env,
+            &DataKey::ImportedAttestationCount(business.clone()),
+            &number,
+        );
+        storage::set(
+            env,
+            &DataKey::ImportedClaim(
+                business.clone(),
+                claim.issuer.clone(),
+                claim.claim_hash.clone(),
+            ),
+            &number,
+        );
+        if !claim.revoked {
+            Self::adjust_imported_volume(env, business, &claim.issuer, claim.settled_volume);
+        }
+    }
+}
+
+/// Set how long attestations stay fresh, in seconds (admin only)
+pub fn set_attestation_max_age(
+    env: &Env,
+    admin: &Address,
+    max_age: u64,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    if max_age == 0 {
+        return Err(QuickLendXError::InvalidTimestamp);
+    }
+    env.storage()
+        .instance()
+        .set(&symbol_short!("att_age"), &max_age);
+    record_config_change(env, "attestation_max_age", max_age, admin);
+    emit_attestation_max_age_set(env, max_age, admin);
+    Ok(())
+}
+
+/// Publish a financial attestation (verified businesses only)
+pub fn publish_attestation(
+    env: &Env,
+    business: &Address,
+    revenue_band: u32,
+    ar_aging_hash: &BytesN<32>,
+) -> Result<(), QuickLendXError> {
+    business.require_auth();
+    require_business_verification(env, business)?;
+
+    let attestation = FinancialAttestation {
+        business: business.clone(),
+        revenue_band,
+        ar_aging_hash: ar_aging_hash.clone(),
+        published_at: env.ledger().timestamp(),
+    };
+    AttestationStorage::add_attestation(env, &attestation);
+    emit_attestation_published(env, &attestation);
+    publish_risk_score(env, business, symbol_short!("attest"));
+    Ok(())
+}
+
+/// Get the latest attestation a business published
+pub fn get_latest_attestation(env: &Env, business: &Address) -> Option<FinancialAttestation> {
+    let count = AttestationStorage::get_attestation_count(env, business);
+    AttestationStorage::get_attestation(env, business, count)
+}
+
+/// Summarize how current a business's attestations are
+pub fn get_attestation_status(env: &Env, business: &Address) -> AttestationStatus {
+    let last_published_at = get_latest_attestation(env, business).map(|a| a.published_at);
+    let stale_at =
+        last_published_at.map(|at| at.saturating_add(AttestationStorage::get_max_age(env)));
+    AttestationStatus {
+        business: business.clone(),
+        count: AttestationStorage::get_attestation_count(env, business),
+        last_published_at,
+        stale_at,
+        is_stale: stale_at.is_none_or(|at| env.ledger().timestamp() >= at),
+    }
+}
+
+/// Trust an external issuer, or change the weight of one already trusted
+/// (admin only). Registering a revoked issuer trusts it again.
+pub fn register_attestation_issuer(
+    env: &Env,
+    admin: &Address,
+    issuer: &Address,
+    weight_bps: u32,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    if weight_bps == 0 || weight_bps > 10_000 {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    let registered_at = AttestationStorage::get_issuer(env, issuer)
+        .map(|existing| existing.registered_at)
+        .unwrap_or(env.ledger().timestamp());
+    AttestationStorage::set_issuer(
+        env,
+        &AttestationIssuer {
+            issuer: issuer.clone(),
+            weight_bps,
+            registered_at,
+            revoked: false,
+        },
+    );
+    emit_attestation_issuer_registered(env, issuer, weight_bps, admin);
+    Ok(())
+}
+
+/// Stop trusting an issuer (admin only). Attestations it already issued stay
+/// on record but no longer count towards reputation.
+pub fn revoke_attestation_issuer(
+    env: &Env,
+    admin: &Address,
+    issuer: &Address,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    let mut entry =
+        AttestationStorage::get_issuer(env, issuer).ok_or(QuickLendXError::IssuerNotTrusted)?;
+    entry.revoked = true;
+    AttestationStorage::set_issuer(env, &entry);
+    emit_attestation_issuer_revoked(env, issuer, admin);
+    Ok(())
+}
+
+/// Import an attestation about `business` from a trusted issuer. The issuer
+/// signs the claim by authorizing the call (a contract issuer does so by
+/// calling in), and the business consents to it being imported.
+pub fn import_attestation(
+    env: &Env,
+    issuer: &Address,
+    business: &Address,
+    settled_volume: i128,
+    claim_hash: &BytesN<32>,
+) -> Result<(), QuickLendXError> {
+    issuer.require_auth();
+    business.require_auth();
+    if AttestationStorage::get_issuer(env, issuer).is_none_or(|entry| entry.revoked) {
+        return Err(QuickLendXError::IssuerNotTrusted);
+    }
+    if settled_volume <= 0 {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    if AttestationStorage::get_imported_number(env, business, issuer, claim_hash).is_some() {
+        return Err(QuickLendXError::AttestationAlreadyImported);
+    }
+    let claim = ImportedAttestation {
+        issuer: issuer.clone(),
+        business: business.clone(),
+        claim_hash: claim_hash.clone(),
+        settled_volume,
+        imported_at: env.ledger().timestamp(),
+        revoked: false,
+    };
+    AttestationStorage::add_imported(env, &claim);
+    emit_attestation_imported(env, &claim);
+    publish_risk_score(env, business, symbol_short!("imported"));
+    Ok(())
+}
+
+/// Revoke one imported attestation. Its issuer or the admin may do so.
+pub fn revoke_imported_attestation(
+    env: &Env,
+    caller: &Address,
+    business: &Address,
+    issuer: &Address,
+    claim_hash: &BytesN<32>,
+) -> Result<(), QuickLendXError> {
+    if caller == issuer {
+        caller.require_auth();
+    } else {
+        require_admin(env, caller)?;
+    }
+    let number = AttestationStorage::get_imported_number(env, business, issuer, claim_hash)
+        .ok_or(QuickLendXError::AttestationNotFound)?;
+    let mut claim = AttestationStorage::get_imported_attestation(env, business, number)
+        .ok_or(QuickLendXError::AttestationNotFound)?;
+    if !claim.revoked {
+        claim.revoked = true;
+        storage::set(
+            env,
+            &DataKey::ImportedAttestation(business.clone(), number),
+            &claim,
+        );
+        AttestationStorage::adjust_imported_volume(env, business, issuer, -claim.settled_volume);
+    }
+    emit_imported_attestation_revoked(env, business, issuer, claim_hash, caller);
+    publish_risk_score(env, business, symbol_short!("revoked"));
+    Ok(())
+}
+
+/// Settled volume a business brings from elsewhere: the live imported
+/// attestations of each trusted issuer, scaled by that issuer's weight
+pub fn get_imported_reputation(env: &Env, business: &Address) -> i128 {
+    let issuers = AttestationStorage::get_issuers(env);
+    let mut reputation = 0i128;
+    for (issuer, volume) in AttestationStorage::get_imported_volume(env, business).iter() {
+        if let Some(entry) = issuers
+            .iter()
+            .find(|entry| entry.issuer == issuer && !entry.revoked)
+        {
+            reputation =
+                reputation.saturating_add(volume.saturating_mul(entry.weight_bps as i128) / 10_000);
+        }
+    }
+    reputation
+}
+
+/// Risk score credit a business earns by keeping its attestation fresh and
+/// by bringing settlement history from trusted issuers
+pub fn attestation_credit(env: &Env, business: &Address) -> u32 {
+    let fresh = if get_attestation_status(env, business).is_stale {
+        0
+    } else {
+        FRESH_ATTESTATION_CREDIT
+    };
+    let imported = if get_imported_reputation(env, business) > 0 {
+        IMPORTED_ATTESTATION_CREDIT
+    } else {
+        0
+    };
+    fresh + imported
+}
@@ -0,0 +1,319 @@
+use crate::bid::{Bid, BidStatus, BidStorage};
+use crate::compliance::{check_not_blacklisted, check_not_self_dealing};
+use crate::config::check_min_ticket;
+use crate::errors::QuickLendXError;
+use crate::events::{
+    emit_auction_closed, emit_auction_started, emit_bid_committed, emit_bid_placed,
+    emit_bid_revealed,
+};
+use crate::expiry::is_funding_expired;
+use crate::investor_kyc::require_investor_verification;
+use crate::invoice::{InvoiceStatus, InvoiceStorage};
+use crate::jurisdiction;
+use crate::notifications::{notify, NotificationKind};
+use crate::priority::check_priority_bid;
+use crate::stop_loss::check_bidding_allowed;
+use crate::storage::{self, DataKey};
+use crate::terms::TermsStorage;
+use soroban_sdk::{contracttype, xdr::ToXdr, Address, Bytes, BytesN, Env, Map, Vec};
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AuctionStatus {
+    Open,    // Collecting commitments, then reveals
+    Settled, // The best revealed bid was accepted
+    Failed,  // Closed without an acceptable revealed bid
+}
+
+/// A sealed-bid auction for the funding of an invoice. Investors commit to a
+/// hash of their bid before `commit_ends` and reveal it before `reveal_ends`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Auction {
+    pub invoice_id: BytesN<32>,
+    pub business: Address,
+    pub started_at: u64,
+    pub commit_ends: u64,
+    pub reveal_ends: u64,
+    pub status: AuctionStatus,
+    pub revealed_bids: Vec<BytesN<32>>,
+    pub winning_bid: Option<BytesN<32>>,
+}
+
+pub struct AuctionStorage;
+
+impl AuctionStorage {
+    /// Get the latest auction listed for an invoice
+    pub fn get_auction(env: &Env, invoice_id: &BytesN<32>) -> Option<Auction> {
+        storage::get(env, &DataKey::Auction(invoice_id.clone()))
+    }
+
+    fn set_auction(env: &Env, auction: &Auction) {
+        storage::set(env, &DataKey::Auction(auction.invoice_id.clone()), auction);
+    }
+
+    /// Check whether an invoice is in a sealed-bid auction, where open bids
+    /// and manual acceptance are not allowed
+    pub fn is_open(env: &Env, invoice_id: &BytesN<32>) -> bool {
+        matches!(
+            Self::get_auction(env, invoice_id),
+            Some(Auction {
+                status: AuctionStatus::Open,
+                ..
+            })
+        )
+    }
+
+    /// Get the unrevealed commitments of an auction by investor
+    pub fn get_commitments(env: &Env, invoice_id: &BytesN<32>) -> Map<Address, BytesN<32>> {
+        storage::get(env, &DataKey::AuctionCommitments(invoice_id.clone()))
+            .unwrap_or_else(|| Map::new(env))
+    }
+
+    fn set_commitments(env: &Env, invoice_id: &BytesN<32>, commitments: &Map<Address, BytesN<32>>) {
+        storage::set(
+            env,
+            &DataKey::AuctionCommitments(invoice_id.clone()),
+            commitments,
+        );
+    }
+}
+
+/// Hash an investor commits to: sha256 of the investor's XDR, the bid amount
+/// and expected return as big-endian i128, and a secret salt. Binding the
+/// investor stops others from copying a commitment.
+pub fn bid_commitment(
+    env: &Env,
+    investor: &Address,
+    bid_amount: i128,
+    expected_return: i128,
+    salt: &BytesN<32>,
+) -> BytesN<32> {
+    let mut preimage = investor.clone().to_xdr(env);
+    preimage.append(&Bytes::from_array(env, &bid_amount.to_be_bytes()));
+    preimage.append(&Bytes::from_array(env, &expected_return.to_be_bytes()));
+    preimage.append(&Bytes::from_array(env, &salt.to_array()));
+    env.crypto().sha256(&preimage).into()
+}
+
+/// List a verified, unfunded invoice as a sealed-bid auction (business only).
+/// The invoice must have no open bids or terms, and reveals must end by the
+/// due date.
+pub fn start_auction(
+    env: &Env,
+    business: &Address,
+    invoice_id: &BytesN<32>,
+    commit_period: u64,
+    reveal_period: u64,
+) -> Result<Auction, QuickLendXError> {
+    business.require_auth();
+    let invoice =
+        InvoiceStorage::get_invoice(env, invoice_id).ok_or(QuickLendXError::InvoiceNotFound)?;
+    if invoice.business != *business {
+        return Err(QuickLendXError::NotBusinessOwner);
+    }
+    if invoice.status != InvoiceStatus::Verified || is_funding_expired(env, &invoice) {
+        return Err(QuickLendXError::InvalidStatus);
+    }
+    if invoice.funded_amount > 0 {
+        return Err(QuickLendXError::InvoiceAlreadyFunded);
+    }
+    if AuctionStorage::is_open(env, invoice_id) {
+        return Err(QuickLendXError::AuctionInProgress);
+    }
+    // Invoices with terms are only funded by acceptances both parties sign
+    if TermsStorage::get_terms(env, invoice_id).is_some() {
+        return Err(QuickLendXError::OperationNotAllowed);
+    }
+    let has_open_bids = BidStorage::get_bids_for_invoice(env, invoice_id)
+        .iter()
+        .filter_map(|bid_id| BidStorage::get_bid(env, &bid_id))
+        .any(|bid| bid.is_open());
+    if has_open_bids {
+        return Err(QuickLendXError::OperationNotAllowed);
+    }
+
+    let now = env.ledger().timestamp();
+    let commit_ends = now.saturating_add(commit_period);
+    let reveal_ends = commit_ends.saturating_add(reveal_period);
+    if commit_period == 0 || reveal_period == 0 || reveal_ends > invoice.due_date {
+        return Err(QuickLendXError::InvalidTimestamp);
+    }
+
+    let auction = Auction {
+        invoice_id: invoice_id.clone(),
+        business: business.clone(),
+        started_at: now,
+        commit_ends,
+        reveal_ends,
+        status: AuctionStatus::Open,
+        revealed_bids: Vec::new(env),
+        winning_bid: None,
+    };
+    AuctionStorage::set_auction(env, &auction);
+    AuctionStorage::set_commitments(env, invoice_id, &Map::new(env));
+    emit_auction_started(env, &auction);
+    Ok(auction)
+}
+
+fn get_open_auction(env: &Env, invoice_id: &BytesN<32>) -> Result<Auction, QuickLendXError> {
+    let auction =
+        AuctionStorage::get_auction(env, invoice_id).ok_or(QuickLendXError::AuctionNotFound)?;
+    if auction.status != AuctionStatus::Open {
+        return Err(QuickLendXError::InvalidStatus);
+    }
+    Ok(auction)
+}
+
+/// Commit to a sealed bid during the commit phase. Committing again replaces
+/// the investor's earlier commitment.
+pub fn commit_bid(
+    env: &Env,
+    investor: &Address,
+    invoice_id: &BytesN<32>,
+    commitment: &BytesN<32>,
+) -> Result<(), QuickLendXError> {
+    investor.require_auth();
+    check_not_blacklisted(env, investor)?;
+    check_bidding_allowed(env, investor)?;
+    require_investor_verification(env, investor)?;
+    let auction = get_open_auction(env, invoice_id)?;
+    if env.ledger().timestamp() >= auction.commit_ends {
+        return Err(QuickLendXError::AuctionPhaseClosed);
+    }
+    let invoice =
+        InvoiceStorage::get_invoice(env, invoice_id).ok_or(QuickLendXError::InvoiceNotFound)?;
+    check_not_self_dealing(env, &invoice.business, investor)?;
+    check_priority_bid(env, &invoice, investor)?;
+    let mut commitments = AuctionStorage::get_commitments(env, invoice_id);
+    commitments.set(investor.clone(), commitment.clone());
+    AuctionStorage::set_commitments(env, invoice_id, &commitments);
+    emit_bid_committed(env, invoice_id, investor);
+    Ok(())
+}
+
+/// Reveal a committed bid during the reveal phase. A matching reveal becomes
+/// an ordinary placed bid on the invoice and is entered into the auction.
+pub fn reveal_bid(
+    env: &Env,
+    investor: &Address,
+    invoice_id: &BytesN<32>,
+    bid_amount: i128,
+    expected_return: i128,
+    salt: &BytesN<32>,
+) -> Result<BytesN<32>, QuickLendXError> {
+    investor.require_auth();
+    let mut auction = get_open_auction(env, invoice_id)?;
+    let now = env.ledger().timestamp();
+    if now < auction.commit_ends || now >= auction.reveal_ends {
+        return Err(QuickLendXError::AuctionPhaseClosed);
+    }
+    let mut commitments = AuctionStorage::get_commitments(env, invoice_id);
+    let commitment = commitments
+        .get(investor.clone())
+        .ok_or(QuickLendXError::CommitmentMismatch)?;
+    if commitment != bid_commitment(env, investor, bid_amount, expected_return, salt) {
+        return Err(QuickLendXError::CommitmentMismatch);
+    }
+
+    let invoice =
+        InvoiceStorage::get_invoice(env, invoice_id).ok_or(QuickLendXError::InvoiceNotFound)?;
+    if bid_amount <= 0 || bid_amount > invoice.amount {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    check_min_ticket(env, &invoice, bid_amount)?;
+    jurisdiction::check_bid(
+        env,
+        &invoice.business,
+        investor,
+        bid_amount,
+        expected_return,
+        invoice.due_date,
+    )?;
+
+    BidStorage::hold_funds(env, investor, &invoice.currency, bid_amount)?;
+    let bid = Bid {
+        bid_id: BidStorage::generate_unique_bid_id(env),
+        invoice_id: invoice_id.clone(),
+        investor: investor.clone(),
+        bid_amount,
+        expected_return,
+        timestamp: now,
+        status: BidStatus::Placed,
+    };
+    BidStorage::store_bid(env, &bid);
+    emit_bid_placed(env, &bid);
+    BidStorage::add_bid_to_invoice(env, invoice_id, &bid.bid_id);
+    notify(
+        env,
+        &invoice.business,
+        NotificationKind::BidReceived,
+        invoice_id,
+    );
+    // Each commitment can be revealed once
+    commitments.remove(investor.clone());
+    AuctionStorage::set_commitments(env, invoice_id, &commitments);
+    auction.revealed_bids.push_back(bid.bid_id.clone());
+    AuctionStorage::set_auction(env, &auction);
+
+    emit_bid_revealed(env, &bid);
+    Ok(bid.bid_id)
+}
+
+/// Whether `a` is cheaper financing than `b`: a lower expected return per
+/// unit funded, then the larger amount, then the earlier reveal
+fn is_better_bid(a: &Bid, b: &Bid) -> bool {
+    let (a_rate, b_rate) = (
+        a.expected_return * b.bid_amount,
+        b.expected_return * a.bid_amount,
+    );
+    if a_rate != b_rate {
+        return a_rate < b_rate;
+    }
+    if a.bid_amount != b.bid_amount {
+        return a.bid_amount > b.bid_amount;
+    }
+    a.timestamp < b.timestamp
+}
+
+/// Close an auction once the reveal phase is over and pick the best revealed
+/// bid that is still placed. The auction is marked settled with that bid, or
+/// failed when there is none or the invoice can no longer be funded. The
+/// caller accepts the returned bid.
+pub fn close_auction(env: &Env, invoice_id: &BytesN<32>) -> Result<Option<Bid>, QuickLendXError> {
+    let mut auction = get_open_auction(env, invoice_id)?;
+    if env.ledger().timestamp() < auction.reveal_ends {
+        return Err(QuickLendXError::AuctionPhaseClosed);
+    }
+    let invoice =
+        InvoiceStorage::get_invoice(env, invoice_id).ok_or(QuickLendXError::InvoiceNotFound)?;
+
+    let mut best: Option<Bid> = None;
+    if invoice.is_available_for_funding() && !is_funding_expired(env, &invoice) {
+        for bid_id in auction.revealed_bids.iter() {
+            let Some(bid) = BidStorage::get_bid(env, &bid_id) else {
+                continue;
+            };
+            if bid.status != BidStatus::Placed || bid.bid_amount > invoice.remaining_funding() {
+                continue;
+            }
+            if best
+                .as_ref()
+                .is_none_or(|current| is_better_bid(&bid, current))
+            {
+                best = Some(bid);
+            }
+        }
+    }
+
+    auction.winning_bid = best.as_ref().map(|bid| bid.bid_id.clone());
+    auction.status = if best.is_some() {
+        AuctionStatus::Settled
+    } else {
+        AuctionStatus::Failed
+    };
+    AuctionStorage::set_auction(env, &auction);
+    AuctionStorage::set_commitments(env, invoice_id, &Map::new(env));
+    emit_auction_closed(env, &auction);
+    Ok(best)
+}
@@ -1,6 +1,9 @@
 use soroban_sdk::{contracttype, symbol_short, Address, BytesN, Env, String, Vec};
 use crate::invoice::{Invoice, InvoiceStatus};
+use core::fmt;
 use crate::errors::QuickLendXError;
+use crate::events::emit_invoice_status_changed;
+use crate::storage::{self, DataKey};
 
 /// Audit operation types
 #[contracttype]
@@ -22,6 +25,10 @@ pub enum AuditOperation {
     EscrowRefunded,
     PaymentProcessed,
     SettlementCompleted,
+    FeesWithdrawn,
+    InvoiceAmended,
+    BalanceEscheated,
+    EscheatClaimed,
 }
 
 /// Audit log entry structure
@@ -41,12 +48,20 @@ pub struct AuditLogEntry {
     pub transaction_hash: Option<BytesN<32>>,
 }
 
+/// Operation criterion for audit queries
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AuditOperationFilter {
+    Any,
+    Specific(AuditOperation),
+}
+
 /// Audit query filters
 #[contracttype]
 #[derive(Clone, Debug)]
 pub struct AuditQueryFilter {
     pub invoice_id: Option<BytesN<32>>,
-    pub operation: Option<AuditOperation>,
+    pub operation: AuditOperationFilter,
     pub actor: Option<Address>,
     pub start_timestamp: Option<u64>,
     pub end_timestamp: Option<u64>,
@@ -132,7 +147,9 @@ impl AuditLogEntry {
         
         // Validate operation-specific data
         match self.operation {
-            AuditOperation::InvoiceFunded | AuditOperation::PaymentProcessed => {
+            AuditOperation::InvoiceFunded
+            | AuditOperation::PaymentProcessed
+            | AuditOperation::FeesWithdrawn => {
                 if self.amount.is_none() || self.amount.unwrap() <= 0 {
                     return Ok(false);
                 }
@@ -156,7 +173,7 @@ impl AuditStorage {
     /// Store an audit log entry
     pub fn store_audit_entry(env: &Env, entry: &AuditLogEntry) {
         // Store individual entry
-        env.storage().instance().set(&entry.audit_id, entry);
+        storage::set(env, &DataKey::AuditEntry(entry.audit_id.clone()), entry);
         
         // Add to invoice audit trail
         Self::add_to_invoice_audit_trail(env, &entry.invoice_id, &entry.audit_id);
@@ -169,17 +186,19 @@ impl AuditStorage {
         
         // Add to timestamp index
         Self::add_to_timestamp_index(env, entry.timestamp, &entry.audit_id);
+
+        // Add to global index
+        Self::add_to_all_entries(env, &entry.audit_id);
     }
 
     /// Get audit entry by ID
     pub fn get_audit_entry(env: &Env, audit_id: &BytesN<32>) -> Option<AuditLogEntry> {
-        env.storage().instance().get(audit_id)
+        storage::get(env, &DataKey::AuditEntry(audit_id.clone()))
     }
 
     /// Get audit trail for an invoice
     pub fn get_invoice_audit_trail(env: &Env, invoice_id: &BytesN<32>) -> Vec<BytesN<32>> {
-        let key = (symbol_short!("inv_aud"), invoice_id.clone());
-        env.storage().instance().get(&key).unwrap_or_else(|| Vec::new(env))
+        storage::get(env, &DataKey::AuditTrail(invoice_id.clone())).unwrap_or_else(|| Vec::new(env))
     }
 
     /// Get audit entries by operation type
@@ -202,7 +221,7 @@ impl AuditStorage {
         // Start with invoice-specific entries if invoice_id is provided
         let audit_ids = if let Some(invoice_id) = &filter.invoice_id {
             Self::get_invoice_audit_trail(env, invoice_id)
-        } else if let Some(operation) = &filter.operation {
+        } else if let AuditOperationFilter::Specific(operation) = &filter.operation {
             Self::get_audit_entries_by_operation(env, operation)
         } else if let Some(actor) = &filter.actor {
             Self::get_audit_entries_by_actor(env, actor)
@@ -240,8 +259,22 @@ impl AuditStorage {
         
         for audit_id in all_entries.iter() {
             if let Some(entry) = Self::get_audit_entry(env, &audit_id) {
+                // Count entries per operation
+                let mut counted = false;
+                for i in 0..operations_count.len() {
+                    let (operation, count): (AuditOperation, u32) = operations_count.get(i).unwrap();
+                    if operation == entry.operation {
+                        operations_count.set(i, (operation, count + 1));
+                        counted = true;
+                        break;
+                    }
+                }
+                if !counted {
+                    operations_count.push_back((entry.operation.clone(), 1u32));
+                }
+
                 // Track unique actors
-                if !unique_actors.iter().any(|a| *a == entry.actor) {
+                if !unique_actors.contains(&entry.actor) {
                     unique_actors.push_back(entry.actor.clone());
                 }
                 
@@ -282,10 +315,9 @@ impl AuditStorage {
 
     // Helper methods
     fn add_to_invoice_audit_trail(env: &Env, invoice_id: &BytesN<32>, audit_id: &BytesN<32>) {
-        let key = (symbol_short!("inv_aud"), invoice_id.clone());
         let mut trail = Self::get_invoice_audit_trail(env, invoice_id);
         trail.push_back(audit_id.clone());
-        env.storage().instance().set(&key, &trail);
+        storage::set(env, &DataKey::AuditTrail(invoice_id.clone()), &trail);
     }
 
     fn add_to_operation_index(env: &Env, operation: &AuditOperation, audit_id: &BytesN<32>) {
@@ -310,7 +342,14 @@ impl AuditStorage {
         env.storage().instance().set(&key, &entries);
     }
 
-    fn get_all_audit_entries(env: &Env) -> Vec<BytesN<32>> {
+    fn add_to_all_entries(env: &Env, audit_id: &BytesN<32>) {
+        let mut entries = Self::get_all_audit_entries(env);
+        entries.push_back(audit_id.clone());
+        env.storage().instance().set(&symbol_short!("all_aud"), &entries);
+    }
+
+    /// Get the ids of every audit entry, oldest first
+    pub fn get_all_audit_entries(env: &Env) -> Vec<BytesN<32>> {
         let key = symbol_short!("all_aud");
         env.storage().instance().get(&key).unwrap_or_else(|| Vec::new(env))
     }
@@ -322,7 +361,7 @@ impl AuditStorage {
             }
         }
         
-        if let Some(operation) = &filter.operation {
+        if let AuditOperationFilter::Specific(operation) = &filter.operation {
             if entry.operation != *operation {
                 return false;
             }
@@ -375,6 +414,51 @@ pub fn log_invoice_operation(
     AuditStorage::store_audit_entry(env, &entry);
 }
 
+/// Longest text an audit value is formatted to; the rest is cut off
+const AUDIT_TEXT_CAPACITY: usize = 128;
+
+/// Buffer audit values are formatted into, as the contract has no allocator
+/// for `format!`
+struct AuditText {
+    bytes: [u8; AUDIT_TEXT_CAPACITY],
+    len: usize,
+}
+
+impl fmt::Write for AuditText {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        let end = (self.len + text.len()).min(AUDIT_TEXT_CAPACITY);
+        self.bytes[self.len..end].copy_from_slice(&text.as_bytes()[..end - self.len]);
+        self.len = end;
+        Ok(())
+    }
+}
+
+/// Contract string shown as text in an audit value
+struct ContractText<'a>(&'a String);
+
+impl fmt::Display for ContractText<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let len = self.0.len() as usize;
+        // Text too long for an audit value is left out
+        if len > AUDIT_TEXT_CAPACITY {
+            return Ok(());
+        }
+        let mut bytes = [0u8; AUDIT_TEXT_CAPACITY];
+        self.0.copy_into_slice(&mut bytes[..len]);
+        f.write_str(core::str::from_utf8(&bytes[..len]).unwrap_or_default())
+    }
+}
+
+/// Format an audit value from `format_args!`
+fn audit_text(env: &Env, args: fmt::Arguments) -> String {
+    let mut text = AuditText {
+        bytes: [0; AUDIT_TEXT_CAPACITY],
+        len: 0,
+    };
+    let _ = fmt::write(&mut text, args);
+    String::from_bytes(env, &text.bytes[..text.len])
+}
+
 /// Log invoice creation
 pub fn log_invoice_created(env: &Env, invoice: &Invoice) {
     log_invoice_operation(
@@ -383,7 +467,7 @@ pub fn log_invoice_created(env: &Env, invoice: &Invoice) {
         AuditOperation::InvoiceCreated,
         invoice.business.clone(),
         None,
-        Some(format!("Amount: {}, Due: {}", invoice.amount, invoice.due_date)),
+        Some(audit_text(env, format_args!("Amount: {}, Due: {}", invoice.amount, invoice.due_date))),
         Some(invoice.amount),
         Some(invoice.description.clone()),
     );
@@ -397,9 +481,10 @@ pub fn log_invoice_status_change(
     old_status: InvoiceStatus,
     new_status: InvoiceStatus,
 ) {
-    let old_value = format!("{:?}", old_status);
-    let new_value = format!("{:?}", new_status);
-    
+    emit_invoice_status_changed(env, &invoice_id, &old_status, &new_status, &actor);
+    let old_value = audit_text(env, format_args!("{:?}", old_status));
+    let new_value = audit_text(env, format_args!("{:?}", new_status));
+
     log_invoice_operation(
         env,
         invoice_id,
@@ -425,7 +510,7 @@ pub fn log_invoice_funded(
         AuditOperation::InvoiceFunded,
         investor,
         None,
-        Some(format!("Funded with amount: {}", amount)),
+        Some(audit_text(env, format_args!("Funded with amount: {}", amount))),
         Some(amount),
         None,
     );
@@ -445,8 +530,91 @@ pub fn log_payment_processed(
         AuditOperation::PaymentProcessed,
         actor,
         None,
-        Some(format!("Payment type: {}, Amount: {}", payment_type, amount)),
+        Some(audit_text(
+            env,
+            format_args!("Payment type: {}, Amount: {}", ContractText(&payment_type), amount),
+        )),
         Some(amount),
         Some(payment_type),
     );
-}
\ No newline at end of file
+}
+
+/// Log an amendment to a pending invoice with the values it replaced: the old
+/// description as the old value, the old amount and the old due date, in
+/// decimal seconds, as additional data
+pub fn log_invoice_amended(env: &Env, old: &Invoice, new: &Invoice) {
+    log_invoice_operation(
+        env,
+        old.id.clone(),
+        AuditOperation::InvoiceAmended,
+        old.business.clone(),
+        Some(old.description.clone()),
+        Some(new.description.clone()),
+        Some(old.amount),
+        Some(u64_to_string(env, old.due_date)),
+    );
+}
+
+fn u64_to_string(env: &Env, mut value: u64) -> String {
+    let mut digits = [0u8; 20];
+    let mut start = digits.len();
+    loop {
+        start -= 1;
+        digits[start] = b'0' + (value % 10) as u8;
+        value /= 10;
+        if value == 0 {
+            break;
+        }
+    }
+    String::from_bytes(env, &digits[start..])
+}
+
+/// Log an abandoned balance swept to the custodian, recorded under the
+/// all-zero invoice id with the owner as old value and the custodian as new
+pub fn log_balance_escheated(
+    env: &Env,
+    actor: Address,
+    owner: &Address,
+    custodian: &Address,
+    amount: i128,
+) {
+    log_invoice_operation(
+        env,
+        BytesN::from_array(env, &[0u8; 32]),
+        AuditOperation::BalanceEscheated,
+        actor,
+        Some(owner.to_string()),
+        Some(custodian.to_string()),
+        Some(amount),
+        None,
+    );
+}
+
+/// Log an owner reclaiming a swept balance from the custodian
+pub fn log_escheat_claimed(env: &Env, owner: Address, custodian: &Address, amount: i128) {
+    log_invoice_operation(
+        env,
+        BytesN::from_array(env, &[0u8; 32]),
+        AuditOperation::EscheatClaimed,
+        owner.clone(),
+        Some(custodian.to_string()),
+        Some(owner.to_string()),
+        Some(amount),
+        None,
+    );
+}
+
+/// Log a withdrawal of collected platform fees. Withdrawals are not tied to
+/// an invoice, so they are recorded under the all-zero invoice id.
+pub fn log_fees_withdrawn(env: &Env, actor: Address, amount: i128) {
+    log_invoice_operation(
+        env,
+        BytesN::from_array(env, &[0u8; 32]),
+        AuditOperation::FeesWithdrawn,
+        actor,
+        None,
+        None,
+        Some(amount),
+        None,
+    );
+}
@@ -0,0 +1,116 @@
+use crate::bid::Bid;
+use crate::errors::QuickLendXError;
+use crate::events::{emit_auto_accept_cleared, emit_auto_accept_set};
+use crate::invoice::{Invoice, InvoiceStatus, InvoiceStorage};
+use crate::storage::{self, DataKey};
+use crate::terms::TermsStorage;
+use soroban_sdk::{contracttype, Address, BytesN, Env};
+
+/// Bids a business has accepted in advance. A bid placed for at least
+/// `min_amount` whose profit is at most `max_return_bps` of its amount is
+/// accepted in the same transaction it is placed in.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AutoAcceptRule {
+    pub invoice_id: BytesN<32>,
+    pub min_amount: i128,
+    pub max_return_bps: u32,
+    pub terms_hash: Option<BytesN<32>>, // Invoice terms the business signed for
+    pub set_at: u64,
+}
+
+pub struct AutoAcceptStorage;
+
+impl AutoAcceptStorage {
+    /// Get the bids a business accepts in advance on an invoice, if any
+    pub fn get_rule(env: &Env, invoice_id: &BytesN<32>) -> Option<AutoAcceptRule> {
+        storage::get(env, &DataKey::AutoAcceptRule(invoice_id.clone()))
+    }
+}
+
+fn get_owned_open_invoice(
+    env: &Env,
+    business: &Address,
+    invoice_id: &BytesN<32>,
+) -> Result<Invoice, QuickLendXError> {
+    let invoice =
+        InvoiceStorage::get_invoice(env, invoice_id).ok_or(QuickLendXError::InvoiceNotFound)?;
+    if invoice.business != *business {
+        return Err(QuickLendXError::NotBusinessOwner);
+    }
+    if !matches!(
+        invoice.status,
+        InvoiceStatus::Pending | InvoiceStatus::Verified
+    ) {
+        return Err(QuickLendXError::InvalidStatus);
+    }
+    Ok(invoice)
+}
+
+/// Accept in advance any bid on an invoice of at least `min_amount` with a
+/// profit of at most `max_return_bps` of its amount, replacing an earlier
+/// rule (business only). The rule covers the invoice's terms as they are
+/// now; it stops applying if they change.
+pub fn set_auto_accept(
+    env: &Env,
+    business: &Address,
+    invoice_id: &BytesN<32>,
+    min_amount: i128,
+    max_return_bps: u32,
+) -> Result<AutoAcceptRule, QuickLendXError> {
+    business.require_auth();
+    set_rule(env, business, invoice_id, min_amount, max_return_bps)
+}
+
+/// Store an auto-accept rule for set_auto_accept once the business has
+/// authorized it
+pub fn set_rule(
+    env: &Env,
+    business: &Address,
+    invoice_id: &BytesN<32>,
+    min_amount: i128,
+    max_return_bps: u32,
+) -> Result<AutoAcceptRule, QuickLendXError> {
+    let invoice = get_owned_open_invoice(env, business, invoice_id)?;
+    if min_amount <= 0 || min_amount > invoice.amount {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    let rule = AutoAcceptRule {
+        invoice_id: invoice_id.clone(),
+        min_amount,
+        max_return_bps,
+        terms_hash: TermsStorage::get_terms_hash(env, invoice_id),
+        set_at: env.ledger().timestamp(),
+    };
+    storage::set(env, &DataKey::AutoAcceptRule(invoice_id.clone()), &rule);
+    emit_auto_accept_set(env, &rule, business);
+    Ok(rule)
+}
+
+/// Stop accepting bids on an invoice in advance (business only)
+pub fn clear_auto_accept(
+    env: &Env,
+    business: &Address,
+    invoice_id: &BytesN<32>,
+) -> Result<(), QuickLendXError> {
+    business.require_auth();
+    get_owned_open_invoice(env, business, invoice_id)?;
+    if AutoAcceptStorage::get_rule(env, invoice_id).is_none() {
+        return Err(QuickLendXError::StorageKeyNotFound);
+    }
+    storage::remove(env, &DataKey::AutoAcceptRule(invoice_id.clone()));
+    emit_auto_accept_cleared(env, invoice_id, business);
+    Ok(())
+}
+
+/// Get the rule a newly placed bid is accepted under, if it meets one. The
+/// bid must also fit the funding still needed.
+pub fn matching_rule(env: &Env, invoice: &Invoice, bid: &Bid) -> Option<AutoAcceptRule> {
+    let rule = AutoAcceptStorage::get_rule(env, &invoice.id)?;
+    let profit = bid.expected_return - bid.bid_amount;
+    let qualifies = bid.bid_amount >= rule.min_amount
+        && bid.bid_amount <= invoice.remaining_funding()
+        && profit * 10_000 <= bid.bid_amount * rule.max_return_bps as i128
+        && rule.terms_hash == TermsStorage::get_terms_hash(env, &invoice.id);
+    qualifies.then_some(rule)
+}
@@ -0,0 +1,94 @@
+use crate::archive::ArchiveStorage;
+use crate::config_history::record_config_change;
+use crate::errors::QuickLendXError;
+use crate::events::emit_auto_verify_rules_set;
+use crate::invoice::{Invoice, InvoiceStatus, InvoiceStorage};
+use crate::verification::require_admin;
+use soroban_sdk::{contracttype, symbol_short, Address, Env, Vec};
+
+/// Most rules the auto-verification table may hold
+pub const MAX_AUTO_VERIFY_RULES: u32 = 10;
+
+/// Invoices verified as soon as they are uploaded: those of at most
+/// `max_amount`, due within `max_duration` seconds of upload, from a
+/// business with at least `min_paid_invoices` invoices paid
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AutoVerifyRule {
+    pub max_amount: i128,
+    pub max_duration: u64,
+    pub min_paid_invoices: u32,
+}
+
+pub struct AutoVerifyStorage;
+
+impl AutoVerifyStorage {
+    /// Get the auto-verification rules; none until the admin sets them
+    pub fn get_rules(env: &Env) -> Vec<AutoVerifyRule> {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("auto_vrf"))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+}
+
+/// Replace the auto-verification rules; an empty table turns
+/// auto-verification off (admin only)
+pub fn set_auto_verify_rules(
+    env: &Env,
+    admin: &Address,
+    rules: Vec<AutoVerifyRule>,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    if rules.len() > MAX_AUTO_VERIFY_RULES {
+        return Err(QuickLendXError::OperationNotAllowed);
+    }
+    for rule in rules.iter() {
+        if rule.max_amount <= 0 {
+            return Err(QuickLendXError::InvalidAmount);
+        }
+        if rule.max_duration == 0 {
+            return Err(QuickLendXError::InvalidTimestamp);
+        }
+    }
+    env.storage()
+        .instance()
+        .set(&symbol_short!("auto_vrf"), &rules);
+    record_config_change(env, "auto_verify_rules", rules.clone(), admin);
+    emit_auto_verify_rules_set(env, &rules, admin);
+    Ok(())
+}
+
+/// Count the business's invoices that were paid, archived ones included
+pub fn count_paid_invoices(env: &Env, business: &Address) -> u32 {
+    let live = InvoiceStorage::get_business_invoices(env, business)
+        .iter()
+        .filter_map(|invoice_id| InvoiceStorage::get_invoice(env, &invoice_id))
+        .filter(|invoice| invoice.status == InvoiceStatus::Paid)
+        .count() as u32;
+    let archived = ArchiveStorage::get_business_archive(env, business)
+        .iter()
+        .filter_map(|invoice_id| ArchiveStorage::get_archived_invoice(env, &invoice_id))
+        .filter(|invoice| invoice.status == InvoiceStatus::Paid)
+        .count() as u32;
+    live + archived
+}
+
+/// Get the index of the first rule a newly uploaded invoice falls under, if
+/// any
+pub fn matching_rule(env: &Env, invoice: &Invoice) -> Option<u32> {
+    let rules = AutoVerifyStorage::get_rules(env);
+    if rules.is_empty() {
+        return None;
+    }
+    let duration = invoice.due_date.saturating_sub(env.ledger().timestamp());
+    let paid = count_paid_invoices(env, &invoice.business);
+    rules
+        .iter()
+        .position(|rule| {
+            invoice.amount <= rule.max_amount
+                && duration <= rule.max_duration
+                && paid >= rule.min_paid_invoices
+        })
+        .map(|index| index as u32)
+}
@@ -0,0 +1,185 @@
+use crate::config_history::record_config_change;
+use crate::errors::QuickLendXError;
+use crate::events::{emit_backstop_drawn, emit_backstop_fee_set, emit_backstop_registered};
+use crate::payments::transfer_funds;
+use crate::storage::{self, DataKey};
+use crate::verification::require_admin;
+use soroban_sdk::{contracttype, symbol_short, Address, Env, Vec};
+
+/// Highest share of each premium that can be paid to backstop providers
+pub const MAX_BACKSTOP_FEE_BPS: u32 = 5_000;
+
+/// External capital committed to cover the insurance fund when it runs dry
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BackstopProvider {
+    pub provider: Address,
+    pub currency: Address,
+    pub committed: i128,   // Most the provider can be drawn for in total
+    pub drawn: i128,       // Drawn so far to pay claims
+    pub priority: u32,     // Lower numbers are drawn first
+    pub fees_earned: i128, // Standing fees paid from premiums so far
+    pub registered_at: u64,
+}
+
+impl BackstopProvider {
+    /// Commitment not yet drawn
+    pub fn available(&self) -> i128 {
+        self.committed - self.drawn
+    }
+}
+
+pub struct BackstopStorage;
+
+impl BackstopStorage {
+    /// Get the backstop providers for a currency in draw order
+    pub fn get_providers(env: &Env, currency: &Address) -> Vec<BackstopProvider> {
+        storage::get(env, &DataKey::BackstopProviders(currency.clone()))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    fn set_providers(env: &Env, currency: &Address, providers: &Vec<BackstopProvider>) {
+        storage::set(
+            env,
+            &DataKey::BackstopProviders(currency.clone()),
+            providers,
+        );
+    }
+
+    /// Get the share of each premium, in bps, paid to backstop providers
+    pub fn get_fee_bps(env: &Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("bs_fee"))
+            .unwrap_or(0)
+    }
+}
+
+/// Set the share of each premium paid to backstop providers (admin only)
+pub fn set_backstop_fee(env: &Env, admin: &Address, fee_bps: u32) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    if fee_bps > MAX_BACKSTOP_FEE_BPS {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    env.storage()
+        .instance()
+        .set(&symbol_short!("bs_fee"), &fee_bps);
+    record_config_change(env, "backstop_fee", fee_bps, admin);
+    emit_backstop_fee_set(env, fee_bps, admin);
+    Ok(())
+}
+
+/// Register, or update, a provider's commitment to backstop the insurance
+/// fund in a currency. A commitment cannot be lowered below what has already
+/// been drawn.
+pub fn register_backstop(
+    env: &Env,
+    provider: &Address,
+    currency: &Address,
+    committed: i128,
+    priority: u32,
+) -> Result<(), QuickLendXError> {
+    provider.require_auth();
+    if committed <= 0 {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+
+    let mut providers = BackstopStorage::get_providers(env, currency);
+    let mut entry = BackstopProvider {
+        provider: provider.clone(),
+        currency: currency.clone(),
+        committed,
+        drawn: 0,
+        priority,
+        fees_earned: 0,
+        registered_at: env.ledger().timestamp(),
+    };
+    if let Some(index) = providers.iter().position(|p| p.provider == *provider) {
+        let existing = providers.get(index as u32).unwrap();
+        if committed < existing.drawn {
+            return Err(QuickLendXError::InvalidAmount);
+        }
+        entry.drawn = existing.drawn;
+        entry.fees_earned = existing.fees_earned;
+        entry.registered_at = existing.registered_at;
+        providers.remove(index as u32);
+    }
+
+    // Keep draw order: by priority, then by registration
+    let position = providers
+        .iter()
+        .position(|p| p.priority > priority)
+        .unwrap_or(providers.len() as usize);
+    providers.insert(position as u32, entry);
+    BackstopStorage::set_providers(env, currency, &providers);
+    emit_backstop_registered(env, provider, currency, committed, priority);
+    Ok(())
+}
+
+/// Draw `amount` from backstop providers in priority order into the
+/// contract. Nothing is drawn unless the commitments cover the full amount.
+pub fn draw_backstop(env: &Env, currency: &Address, amount: i128) -> Result<(), QuickLendXError> {
+    let mut providers = BackstopStorage::get_providers(env, currency);
+    let available: i128 = providers.iter().map(|p| p.available()).sum();
+    if available < amount {
+        return Err(QuickLendXError::InsufficientFunds);
+    }
+
+    let mut remaining = amount;
+    for index in 0..providers.len() {
+        if remaining == 0 {
+            break;
+        }
+        let mut provider = providers.get(index).unwrap();
+        let draw = provider.available().min(remaining);
+        if draw <= 0 {
+            continue;
+        }
+        if !transfer_funds(
+            env,
+            &provider.provider,
+            &env.current_contract_address(),
+            draw,
+        ) {
+            return Err(QuickLendXError::InsufficientFunds);
+        }
+        provider.drawn += draw;
+        remaining -= draw;
+        emit_backstop_drawn(env, &provider.provider, currency, draw);
+        providers.set(index, provider);
+    }
+    BackstopStorage::set_providers(env, currency, &providers);
+    Ok(())
+}
+
+/// Pay the backstop providers' standing fee out of a premium, split by their
+/// undrawn commitments. Returns the part of the premium left for the fund.
+pub fn pay_backstop_fees(env: &Env, currency: &Address, premium: i128) -> i128 {
+    let mut providers = BackstopStorage::get_providers(env, currency);
+    let available: i128 = providers.iter().map(|p| p.available()).sum();
+    let fee = premium * BackstopStorage::get_fee_bps(env) as i128 / 10_000;
+    if fee <= 0 || available <= 0 {
+        return premium;
+    }
+
+    let mut paid = 0i128;
+    for index in 0..providers.len() {
+        let mut provider = providers.get(index).unwrap();
+        let share = fee * provider.available() / available;
+        if share <= 0 {
+            continue;
+        }
+        if transfer_funds(
+            env,
+            &env.current_contract_address(),
+            &provider.provider,
+            share,
+        ) {
+            provider.fees_earned += share;
+            paid += share;
+            providers.set(index, provider);
+        }
+    }
+    BackstopStorage::set_providers(env, currency, &providers);
+    premium - paid
+}
@@ -1,6 +1,7 @@
 use crate::errors::QuickLendXError;
 use crate::invoice::{Invoice, InvoiceStorage};
-use soroban_sdk::{contracttype, symbol_short, Address, BytesN, Env, String, Vec};
+use crate::storage::{self, DataKey};
+use soroban_sdk::{contracttype, symbol_short, xdr::ToXdr, BytesN, Env, String, Vec};
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -20,6 +21,27 @@ pub enum BackupStatus {
     Corrupted,
 }
 
+/// Invoices restored per `restore_backup_chunk` call
+pub const RESTORE_CHUNK_SIZE: u32 = 10;
+
+/// A restore that is staged chunk by chunk and swapped in once complete
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RestoreProgress {
+    pub backup_id: BytesN<32>,
+    pub total_chunks: u32,
+    pub restored_chunks: Vec<u32>,
+    pub staged_invoices: u32,
+    pub started_at: u64,
+}
+
+impl RestoreProgress {
+    /// Check whether every chunk has been staged
+    pub fn is_complete(&self) -> bool {
+        self.restored_chunks.len() == self.total_chunks
+    }
+}
+
 pub struct BackupStorage;
 
 impl BackupStorage {
@@ -48,17 +70,17 @@ impl BackupStorage {
 
     /// Store a backup record
     pub fn store_backup(env: &Env, backup: &Backup) {
-        env.storage().instance().set(&backup.backup_id, backup);
+        storage::set(env, &DataKey::Backup(backup.backup_id.clone()), backup);
     }
 
     /// Get a backup by ID
     pub fn get_backup(env: &Env, backup_id: &BytesN<32>) -> Option<Backup> {
-        env.storage().instance().get(backup_id)
+        storage::get(env, &DataKey::Backup(backup_id.clone()))
     }
 
     /// Update a backup record
     pub fn update_backup(env: &Env, backup: &Backup) {
-        env.storage().instance().set(&backup.backup_id, backup);
+        storage::set(env, &DataKey::Backup(backup.backup_id.clone()), backup);
     }
 
     /// Get all backup IDs
@@ -95,14 +117,27 @@ impl BackupStorage {
 
     /// Store invoice data for a backup
     pub fn store_backup_data(env: &Env, backup_id: &BytesN<32>, invoices: &Vec<Invoice>) {
-        let key = (symbol_short!("bkup_data"), backup_id.clone());
-        env.storage().instance().set(&key, invoices);
+        storage::set(env, &DataKey::BackupData(backup_id.clone()), invoices);
     }
 
     /// Get invoice data from a backup
     pub fn get_backup_data(env: &Env, backup_id: &BytesN<32>) -> Option<Vec<Invoice>> {
-        let key = (symbol_short!("bkup_data"), backup_id.clone());
-        env.storage().instance().get(&key)
+        storage::get(env, &DataKey::BackupData(backup_id.clone()))
+    }
+
+    /// Hash of a backup's invoice data: sha256 of its XDR serialization
+    pub fn hash_backup_data(env: &Env, invoices: &Vec<Invoice>) -> BytesN<32> {
+        env.crypto().sha256(&invoices.clone().to_xdr(env)).into()
+    }
+
+    /// Get the content hash recorded for a backup's data when it was taken
+    pub fn get_content_hash(env: &Env, backup_id: &BytesN<32>) -> Option<BytesN<32>> {
+        storage::get(env, &DataKey::BackupHash(backup_id.clone()))
+    }
+
+    /// Record the content hash of a backup's data
+    pub fn set_content_hash(env: &Env, backup_id: &BytesN<32>, content_hash: &BytesN<32>) {
+        storage::set(env, &DataKey::BackupHash(backup_id.clone()), content_hash);
     }
 
     /// Validate backup data integrity
@@ -112,6 +147,13 @@ impl BackupStorage {
         let data =
             Self::get_backup_data(env, backup_id).ok_or(QuickLendXError::StorageKeyNotFound)?;
 
+        // Check the data is exactly what was backed up
+        let content_hash =
+            Self::get_content_hash(env, backup_id).ok_or(QuickLendXError::StorageError)?;
+        if Self::hash_backup_data(env, &data) != content_hash {
+            return Err(QuickLendXError::StorageError);
+        }
+
         // Check if count matches
         if data.len() as u32 != backup.invoice_count {
             return Err(QuickLendXError::StorageError);
@@ -127,6 +169,131 @@ impl BackupStorage {
         Ok(())
     }
 
+    /// Get the progress of a chunked restore of a backup
+    pub fn get_restore_progress(env: &Env, backup_id: &BytesN<32>) -> Option<RestoreProgress> {
+        env.storage()
+            .instance()
+            .get(&(symbol_short!("bkup_rst"), backup_id.clone()))
+    }
+
+    fn set_restore_progress(env: &Env, progress: &RestoreProgress) {
+        env.storage().instance().set(
+            &(symbol_short!("bkup_rst"), progress.backup_id.clone()),
+            progress,
+        );
+    }
+
+    /// Get the backup currently being restored into the staging namespace
+    pub fn get_staged_backup(env: &Env) -> Option<BytesN<32>> {
+        env.storage().instance().get(&symbol_short!("stg_bkup"))
+    }
+
+    /// Get a staged invoice, as it will be once the restore is promoted
+    pub fn get_staged_invoice(env: &Env, invoice_id: &BytesN<32>) -> Option<Invoice> {
+        storage::get(env, &DataKey::StagedInvoice(invoice_id.clone()))
+    }
+
+    /// Stage one chunk of a backup for restoring, next to the live invoices.
+    /// Only one backup can be staged at a time. The backup is validated on
+    /// the first chunk; staging a chunk again changes nothing.
+    pub fn restore_chunk(
+        env: &Env,
+        backup_id: &BytesN<32>,
+        chunk_index: u32,
+    ) -> Result<(RestoreProgress, bool), QuickLendXError> {
+        let mut progress = match Self::get_restore_progress(env, backup_id) {
+            Some(progress) => progress,
+            None => {
+                if Self::get_staged_backup(env).is_some() {
+                    return Err(QuickLendXError::OperationNotAllowed);
+                }
+                Self::validate_backup(env, backup_id)?;
+                let backup =
+                    Self::get_backup(env, backup_id).ok_or(QuickLendXError::StorageKeyNotFound)?;
+                env.storage()
+                    .instance()
+                    .set(&symbol_short!("stg_bkup"), backup_id);
+                RestoreProgress {
+                    backup_id: backup_id.clone(),
+                    total_chunks: backup.invoice_count.div_ceil(RESTORE_CHUNK_SIZE).max(1),
+                    restored_chunks: Vec::new(env),
+                    staged_invoices: 0,
+                    started_at: env.ledger().timestamp(),
+                }
+            }
+        };
+        if chunk_index >= progress.total_chunks {
+            return Err(QuickLendXError::StorageKeyNotFound);
+        }
+        if progress.restored_chunks.contains(chunk_index) {
+            return Ok((progress, false));
+        }
+
+        let data =
+            Self::get_backup_data(env, backup_id).ok_or(QuickLendXError::StorageKeyNotFound)?;
+        let start = chunk_index * RESTORE_CHUNK_SIZE;
+        let end = (start + RESTORE_CHUNK_SIZE).min(data.len());
+        let chunk = data.slice(start..end);
+        for invoice in chunk.iter() {
+            if invoice.amount <= 0 {
+                return Err(QuickLendXError::StorageError);
+            }
+        }
+        for invoice in chunk.iter() {
+            storage::set(env, &DataKey::StagedInvoice(invoice.id.clone()), &invoice);
+        }
+        progress.restored_chunks.push_back(chunk_index);
+        progress.staged_invoices += chunk.len();
+        Self::set_restore_progress(env, &progress);
+        Ok((progress, true))
+    }
+
+    /// Clear a completed restore out of the staging namespace, returning the
+    /// staged invoices, in backup order, to promote to live
+    pub fn take_staged_invoices(
+        env: &Env,
+        backup_id: &BytesN<32>,
+    ) -> Result<Vec<Invoice>, QuickLendXError> {
+        let progress = Self::get_restore_progress(env, backup_id)
+            .ok_or(QuickLendXError::StorageKeyNotFound)?;
+        let backup = Self::get_backup(env, backup_id).ok_or(QuickLendXError::StorageKeyNotFound)?;
+        if !progress.is_complete() {
+            return Err(QuickLendXError::OperationNotAllowed);
+        }
+        if progress.staged_invoices != backup.invoice_count {
+            return Err(QuickLendXError::StorageError);
+        }
+        Self::validate_backup(env, backup_id)?;
+
+        let data =
+            Self::get_backup_data(env, backup_id).ok_or(QuickLendXError::StorageKeyNotFound)?;
+        let mut invoices = Vec::new(env);
+        for invoice in data.iter() {
+            let staged =
+                Self::get_staged_invoice(env, &invoice.id).ok_or(QuickLendXError::StorageError)?;
+            invoices.push_back(staged);
+        }
+        Self::discard_staged(env, backup_id)?;
+        Ok(invoices)
+    }
+
+    /// Drop everything staged for a restore of a backup
+    pub fn discard_staged(env: &Env, backup_id: &BytesN<32>) -> Result<(), QuickLendXError> {
+        if Self::get_staged_backup(env).as_ref() != Some(backup_id) {
+            return Err(QuickLendXError::StorageKeyNotFound);
+        }
+        if let Some(data) = Self::get_backup_data(env, backup_id) {
+            for invoice in data.iter() {
+                storage::remove(env, &DataKey::StagedInvoice(invoice.id));
+            }
+        }
+        env.storage()
+            .instance()
+            .remove(&(symbol_short!("bkup_rst"), backup_id.clone()));
+        env.storage().instance().remove(&symbol_short!("stg_bkup"));
+        Ok(())
+    }
+
     /// Clean up old backups (keep only the last N)
     pub fn cleanup_old_backups(env: &Env, max_backups: u32) -> Result<(), QuickLendXError> {
         let backups = Self::get_all_backups(env);
@@ -0,0 +1,338 @@
+use crate::compliance::check_not_blacklisted;
+use crate::errors::QuickLendXError;
+use crate::events::{
+    emit_balance_credited, emit_balance_deposited, emit_balance_withdrawn,
+    emit_withdrawal_cancelled, emit_withdrawal_policy_set, emit_withdrawal_requested,
+};
+use crate::storage::{self, DataKey};
+use soroban_sdk::{contracttype, token, Address, Env, Vec};
+
+/// Most destinations a withdrawal allowlist may hold
+pub const MAX_WITHDRAWAL_DESTINATIONS: u32 = 10;
+
+/// Longest delay an owner may put on their withdrawals
+pub const MAX_WITHDRAWAL_DELAY: u64 = 30 * 86_400;
+
+/// Where and how quickly an owner's balance may be withdrawn. With an empty
+/// allowlist funds may go anywhere; with a delay, withdrawals wait that long
+/// before they can be completed and may be cancelled until then.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WithdrawalPolicy {
+    pub allowlist: Vec<Address>,
+    pub delay: u64,
+    pub effective_at: u64, // When the policy took or takes effect
+}
+
+/// A delayed withdrawal, taken out of the balance and waiting to be paid out
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingWithdrawal {
+    pub owner: Address,
+    pub currency: Address,
+    pub amount: i128,
+    pub destination: Address,
+    pub requested_at: u64,
+    pub available_at: u64,
+}
+
+/// Funds the contract holds on behalf of an address, tracked per currency.
+/// Deposits, bid refunds and reinvested settlement returns are credited here
+/// instead of being transferred out, and bids are funded from here while the
+/// balance covers them, so a flow touches the token contract less often.
+pub struct BalanceStorage;
+
+impl BalanceStorage {
+    /// Get an address's balance held by the contract in a currency
+    pub fn get_balance(env: &Env, owner: &Address, currency: &Address) -> i128 {
+        storage::get(env, &DataKey::Balance(owner.clone(), currency.clone())).unwrap_or(0)
+    }
+
+    /// Get the withdrawal policy in effect for an owner; without one, funds
+    /// may be withdrawn anywhere at once
+    pub fn get_withdrawal_policy(env: &Env, owner: &Address) -> WithdrawalPolicy {
+        if let Some(queued) = Self::get_queued_withdrawal_policy(env, owner) {
+            if queued.effective_at <= env.ledger().timestamp() {
+                return queued;
+            }
+        }
+        storage::get(env, &DataKey::WithdrawalPolicy(owner.clone())).unwrap_or(WithdrawalPolicy {
+            allowlist: Vec::new(env),
+            delay: 0,
+            effective_at: 0,
+        })
+    }
+
+    /// Get a withdrawal policy change waiting to take effect, if any
+    pub fn get_queued_withdrawal_policy(env: &Env, owner: &Address) -> Option<WithdrawalPolicy> {
+        storage::get(env, &DataKey::QueuedWithdrawalPolicy(owner.clone()))
+    }
+
+    /// Get the owner's withdrawal in a currency waiting out the delay, if any
+    pub fn get_pending_withdrawal(
+        env: &Env,
+        owner: &Address,
+        currency: &Address,
+    ) -> Option<PendingWithdrawal> {
+        storage::get(
+            env,
+            &DataKey::PendingWithdrawal(owner.clone(), currency.clone()),
+        )
+    }
+
+    /// Get when an address's balance in a currency last moved, if it has
+    pub fn get_last_activity(env: &Env, owner: &Address, currency: &Address) -> Option<u64> {
+        storage::get(
+            env,
+            &DataKey::BalanceActivity(owner.clone(), currency.clone()),
+        )
+    }
+
+    /// Set an address's balance in a currency. Any movement shows the
+    /// balance is not abandoned, so it also withdraws a dormancy notice.
+    pub fn set_balance(env: &Env, owner: &Address, currency: &Address, balance: i128) {
+        storage::set(
+            env,
+            &DataKey::Balance(owner.clone(), currency.clone()),
+            &balance,
+        );
+        storage::set(
+            env,
+            &DataKey::BalanceActivity(owner.clone(), currency.clone()),
+            &env.ledger().timestamp(),
+        );
+        storage::remove(
+            env,
+            &DataKey::DormancyNotice(owner.clone(), currency.clone()),
+        );
+    }
+}
+
+/// Move `amount` of `currency` tokens from `from` to `to`. Balances are
+/// backed by the tokens the contract holds, so every flow in or out of one
+/// moves real tokens.
+pub fn transfer_tokens(
+    env: &Env,
+    currency: &Address,
+    from: &Address,
+    to: &Address,
+    amount: i128,
+) -> Result<(), QuickLendXError> {
+    let token = token::Client::new(env, currency);
+    if token.balance(from) < amount {
+        return Err(QuickLendXError::InsufficientFunds);
+    }
+    token.transfer(from, to, &amount);
+    Ok(())
+}
+
+/// Pay funds into the owner's balance (owner only). The tokens are pulled
+/// from the owner before the balance is credited. Returns the new balance.
+pub fn deposit(
+    env: &Env,
+    owner: &Address,
+    currency: &Address,
+    amount: i128,
+) -> Result<i128, QuickLendXError> {
+    owner.require_auth();
+    check_not_blacklisted(env, owner)?;
+    if amount <= 0 {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    transfer_tokens(
+        env,
+        currency,
+        owner,
+        &env.current_contract_address(),
+        amount,
+    )?;
+    let balance = BalanceStorage::get_balance(env, owner, currency) + amount;
+    BalanceStorage::set_balance(env, owner, currency, balance);
+    emit_balance_deposited(env, owner, currency, amount, balance);
+    Ok(balance)
+}
+
+/// Set where the owner's balance may be withdrawn to and how long
+/// withdrawals wait (owner only). The change takes effect once the delay
+/// already in force has passed, so a compromised key cannot lift the
+/// protection at once; setting a policy again replaces a queued change.
+pub fn set_withdrawal_policy(
+    env: &Env,
+    owner: &Address,
+    allowlist: Vec<Address>,
+    delay: u64,
+) -> Result<WithdrawalPolicy, QuickLendXError> {
+    owner.require_auth();
+    check_not_blacklisted(env, owner)?;
+    if allowlist.len() > MAX_WITHDRAWAL_DESTINATIONS {
+        return Err(QuickLendXError::OperationNotAllowed);
+    }
+    if delay > MAX_WITHDRAWAL_DELAY {
+        return Err(QuickLendXError::InvalidTimestamp);
+    }
+
+    let current = BalanceStorage::get_withdrawal_policy(env, owner);
+    let policy = WithdrawalPolicy {
+        allowlist,
+        delay,
+        effective_at: env.ledger().timestamp() + current.delay,
+    };
+    if current.delay == 0 {
+        storage::set(env, &DataKey::WithdrawalPolicy(owner.clone()), &policy);
+        storage::remove(env, &DataKey::QueuedWithdrawalPolicy(owner.clone()));
+    } else {
+        storage::set(env, &DataKey::WithdrawalPolicy(owner.clone()), &current);
+        storage::set(
+            env,
+            &DataKey::QueuedWithdrawalPolicy(owner.clone()),
+            &policy,
+        );
+    }
+    emit_withdrawal_policy_set(env, owner, &policy);
+    Ok(policy)
+}
+
+/// Pay funds out of the owner's balance to `destination` (owner only).
+/// Under a withdrawal delay the funds leave the balance now and are paid out
+/// by `complete_withdrawal` once the delay has passed. Returns the new
+/// balance.
+pub fn withdraw(
+    env: &Env,
+    owner: &Address,
+    currency: &Address,
+    amount: i128,
+    destination: &Address,
+) -> Result<i128, QuickLendXError> {
+    owner.require_auth();
+    check_not_blacklisted(env, owner)?;
+    if amount <= 0 {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    let policy = BalanceStorage::get_withdrawal_policy(env, owner);
+    check_destination(&policy, destination)?;
+    if policy.delay > 0 && BalanceStorage::get_pending_withdrawal(env, owner, currency).is_some() {
+        return Err(QuickLendXError::WithdrawalPending);
+    }
+    let balance = BalanceStorage::get_balance(env, owner, currency);
+    if amount > balance {
+        return Err(QuickLendXError::InsufficientFunds);
+    }
+    BalanceStorage::set_balance(env, owner, currency, balance - amount);
+
+    if policy.delay > 0 {
+        let now = env.ledger().timestamp();
+        let pending = PendingWithdrawal {
+            owner: owner.clone(),
+            currency: currency.clone(),
+            amount,
+            destination: destination.clone(),
+            requested_at: now,
+            available_at: now + policy.delay,
+        };
+        storage::set(
+            env,
+            &DataKey::PendingWithdrawal(owner.clone(), currency.clone()),
+            &pending,
+        );
+        emit_withdrawal_requested(env, &pending, balance - amount);
+        return Ok(balance - amount);
+    }
+    transfer_tokens(
+        env,
+        currency,
+        &env.current_contract_address(),
+        destination,
+        amount,
+    )?;
+    emit_balance_withdrawn(env, owner, currency, amount, balance - amount);
+    Ok(balance - amount)
+}
+
+/// Pay out the owner's delayed withdrawal in a currency once its delay has
+/// passed (owner only). The destination must still be allowed. Returns the
+/// amount paid out.
+pub fn complete_withdrawal(
+    env: &Env,
+    owner: &Address,
+    currency: &Address,
+) -> Result<i128, QuickLendXError> {
+    owner.require_auth();
+    check_not_blacklisted(env, owner)?;
+    let pending = BalanceStorage::get_pending_withdrawal(env, owner, currency)
+        .ok_or(QuickLendXError::WithdrawalNotFound)?;
+    if env.ledger().timestamp() < pending.available_at {
+        return Err(QuickLendXError::WithdrawalNotReady);
+    }
+    check_destination(
+        &BalanceStorage::get_withdrawal_policy(env, owner),
+        &pending.destination,
+    )?;
+
+    storage::remove(
+        env,
+        &DataKey::PendingWithdrawal(owner.clone(), currency.clone()),
+    );
+    transfer_tokens(
+        env,
+        currency,
+        &env.current_contract_address(),
+        &pending.destination,
+        pending.amount,
+    )?;
+    let balance = BalanceStorage::get_balance(env, owner, currency);
+    emit_balance_withdrawn(env, owner, currency, pending.amount, balance);
+    Ok(pending.amount)
+}
+
+/// Cancel the owner's delayed withdrawal in a currency before it is paid
+/// out, returning the funds to the balance (owner only). Returns the new
+/// balance.
+pub fn cancel_withdrawal(
+    env: &Env,
+    owner: &Address,
+    currency: &Address,
+) -> Result<i128, QuickLendXError> {
+    owner.require_auth();
+    let pending = BalanceStorage::get_pending_withdrawal(env, owner, currency)
+        .ok_or(QuickLendXError::WithdrawalNotFound)?;
+    storage::remove(
+        env,
+        &DataKey::PendingWithdrawal(owner.clone(), currency.clone()),
+    );
+    let balance = BalanceStorage::get_balance(env, owner, currency) + pending.amount;
+    BalanceStorage::set_balance(env, owner, currency, balance);
+    emit_withdrawal_cancelled(env, &pending, balance);
+    Ok(balance)
+}
+
+/// Check a withdrawal destination against the owner's allowlist, if any
+fn check_destination(
+    policy: &WithdrawalPolicy,
+    destination: &Address,
+) -> Result<(), QuickLendXError> {
+    if !policy.allowlist.is_empty() && !policy.allowlist.contains(destination) {
+        return Err(QuickLendXError::WithdrawalNotAllowed);
+    }
+    Ok(())
+}
+
+/// Add funds the contract holds for an address to its balance
+pub fn credit_balance(env: &Env, owner: &Address, currency: &Address, amount: i128) {
+    if amount <= 0 {
+        return;
+    }
+    let balance = BalanceStorage::get_balance(env, owner, currency) + amount;
+    BalanceStorage::set_balance(env, owner, currency, balance);
+    emit_balance_credited(env, owner, currency, amount, balance);
+}
+
+/// Take `amount` from the owner's balance if it covers it. Returns whether it
+/// did; otherwise the caller takes the funds from the owner's wallet.
+pub fn debit_balance(env: &Env, owner: &Address, currency: &Address, amount: i128) -> bool {
+    let balance = BalanceStorage::get_balance(env, owner, currency);
+    if amount <= 0 || balance < amount {
+        return false;
+    }
+    BalanceStorage::set_balance(env, owner, currency, balance - amount);
+    true
+}
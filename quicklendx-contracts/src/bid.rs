@@ -1,3 +1,9 @@
+use crate::balances::{credit_balance, debit_balance};
+use crate::errors::QuickLendXError;
+use crate::events::emit_bid_rejected;
+use crate::invoice::InvoiceStorage;
+use crate::payments::transfer_funds;
+use crate::storage::{self, DataKey};
 use soroban_sdk::{contracttype, Address, BytesN, Env, String, Vec, symbol_short};
 
 #[contracttype]
@@ -6,6 +12,8 @@ pub enum BidStatus {
     Placed,
     Withdrawn,
     Accepted,
+    Rejected,
+    Countered, // The business proposed another return; awaiting the investor
 }
 
 #[contracttype]
@@ -20,27 +28,117 @@ pub struct Bid {
     pub status: BidStatus,
 }
 
+impl Bid {
+    /// Whether the bid is still outstanding, placed or under negotiation
+    pub fn is_open(&self) -> bool {
+        self.status == BidStatus::Placed || self.status == BidStatus::Countered
+    }
+}
+
 pub struct BidStorage;
 
 impl BidStorage {
     pub fn store_bid(env: &Env, bid: &Bid) {
-        env.storage().instance().set(&bid.bid_id, bid);
+        storage::set(env, &DataKey::Bid(bid.bid_id.clone()), bid);
     }
     pub fn get_bid(env: &Env, bid_id: &BytesN<32>) -> Option<Bid> {
-        env.storage().instance().get(bid_id)
+        storage::get(env, &DataKey::Bid(bid_id.clone()))
     }
     pub fn update_bid(env: &Env, bid: &Bid) {
-        env.storage().instance().set(&bid.bid_id, bid);
+        storage::set(env, &DataKey::Bid(bid.bid_id.clone()), bid);
     }
     pub fn get_bids_for_invoice(env: &Env, invoice_id: &BytesN<32>) -> Vec<BytesN<32>> {
-        let key = (symbol_short!("bids"), invoice_id.clone());
-        env.storage().instance().get(&key).unwrap_or_else(|| Vec::new(env))
+        storage::get(env, &DataKey::BidList(invoice_id.clone())).unwrap_or_else(|| Vec::new(env))
     }
     pub fn add_bid_to_invoice(env: &Env, invoice_id: &BytesN<32>, bid_id: &BytesN<32>) {
         let mut bids = Self::get_bids_for_invoice(env, invoice_id);
         bids.push_back(bid_id.clone());
-        let key = (symbol_short!("bids"), invoice_id.clone());
-        env.storage().instance().set(&key, &bids);
+        storage::set(env, &DataKey::BidList(invoice_id.clone()), &bids);
+    }
+    /// Get the placed bid offering the cheapest financing on an invoice: the
+    /// lowest expected return among bids of at most `max_amount`, preferring
+    /// the larger and then the earlier bid on ties
+    pub fn get_best_bid(env: &Env, invoice_id: &BytesN<32>, max_amount: i128) -> Option<Bid> {
+        let mut best: Option<Bid> = None;
+        for bid_id in Self::get_bids_for_invoice(env, invoice_id).iter() {
+            let Some(bid) = Self::get_bid(env, &bid_id) else {
+                continue;
+            };
+            if bid.status != BidStatus::Placed || bid.bid_amount > max_amount {
+                continue;
+            }
+            let better = match &best {
+                None => true,
+                Some(current) => {
+                    (bid.expected_return, -bid.bid_amount, bid.timestamp)
+                        < (current.expected_return, -current.bid_amount, current.timestamp)
+                }
+            };
+            if better {
+                best = Some(bid);
+            }
+        }
+        best
+    }
+    /// Move a bid's amount from the investor into the contract, where it is
+    /// held until the bid is accepted or leaves the book
+    pub fn hold_funds(
+        env: &Env,
+        investor: &Address,
+        currency: &Address,
+        amount: i128,
+    ) -> Result<(), QuickLendXError> {
+        // Bids are funded from the investor's balance while it covers them
+        if debit_balance(env, investor, currency, amount) {
+            return Ok(());
+        }
+        if !transfer_funds(env, investor, &env.current_contract_address(), amount) {
+            return Err(QuickLendXError::InsufficientFunds);
+        }
+        Ok(())
+    }
+    /// Give an open bid's held funds back to its investor's balance
+    pub fn return_funds(env: &Env, bid: &Bid) -> Result<(), QuickLendXError> {
+        if let Some(invoice) = InvoiceStorage::get_invoice(env, &bid.invoice_id) {
+            credit_balance(env, &bid.investor, &invoice.currency, bid.bid_amount);
+            return Ok(());
+        }
+        if !transfer_funds(env, &env.current_contract_address(), &bid.investor, bid.bid_amount) {
+            return Err(QuickLendXError::InsufficientFunds);
+        }
+        Ok(())
+    }
+    /// Reject every bid still open on an invoice, returning their held funds.
+    /// Returns how many were rejected.
+    pub fn reject_open_bids(
+        env: &Env,
+        invoice_id: &BytesN<32>,
+        actor: &Address,
+    ) -> Result<u32, QuickLendXError> {
+        Self::reject_bids_over(env, invoice_id, 0, actor)
+    }
+    /// Reject the open bids on an invoice for more than `max_amount`, which
+    /// can no longer be accepted, returning their held funds. Rejected bids
+    /// stay in the invoice's bid list. Returns how many were rejected.
+    pub fn reject_bids_over(
+        env: &Env,
+        invoice_id: &BytesN<32>,
+        max_amount: i128,
+        actor: &Address,
+    ) -> Result<u32, QuickLendXError> {
+        let mut rejected = 0u32;
+        for bid_id in Self::get_bids_for_invoice(env, invoice_id).iter() {
+            if let Some(mut bid) = Self::get_bid(env, &bid_id) {
+                if bid.is_open() && bid.bid_amount > max_amount {
+                    Self::return_funds(env, &bid)?;
+                    bid.status = BidStatus::Rejected;
+                    Self::update_bid(env, &bid);
+                    emit_bid_rejected(env, &bid, actor);
+                    rejected += 1;
+                }
+            }
+        }
+        Ok(rejected)
     }
     /// Generates a unique 32-byte bid ID using timestamp and a simple counter.
     /// This approach avoids potential serialization issues with large counters.
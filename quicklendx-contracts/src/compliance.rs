@@ -0,0 +1,291 @@
+use crate::config::check_rejection_reason_length;
+use crate::errors::QuickLendXError;
+use crate::events::{
+    emit_affiliate_added, emit_affiliate_removed, emit_blacklist_added, emit_blacklist_removed,
+    emit_compliance_officer_set, emit_wash_flagged,
+};
+use crate::invoice::Invoice;
+use crate::storage::{self, DataKey};
+use crate::verification::{require_admin, BusinessVerificationStorage};
+use soroban_sdk::{contracttype, symbol_short, Address, BytesN, Env, String, Vec};
+
+/// How soon after receiving a settlement from a business an investor's new
+/// funding of that business is flagged as recycled
+pub const RECYCLE_WINDOW: u64 = 86_400;
+
+/// Most wash flags returned by one query
+pub const MAX_WASH_FLAGS_PAGE: u32 = 50;
+
+/// An investor funding a business with money that business just repaid them
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WashFlag {
+    pub business: Address,
+    pub investor: Address,
+    pub settled_invoice: BytesN<32>,
+    pub funded_invoice: BytesN<32>,
+    pub settled_at: u64,
+    pub funded_at: u64,
+}
+
+/// A sanctioned or fraudulent address barred from uploading, bidding,
+/// funding and settling
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BlacklistEntry {
+    pub address: Address,
+    pub reason: String,
+    pub added_by: Address,
+    pub added_at: u64,
+}
+
+pub struct ComplianceStorage;
+
+impl ComplianceStorage {
+    /// Get the compliance officer who maintains the affiliate registry
+    pub fn get_officer(env: &Env) -> Option<Address> {
+        env.storage().instance().get(&symbol_short!("cmpl_off"))
+    }
+
+    /// Get the addresses registered as affiliates of a business
+    pub fn get_affiliates(env: &Env, business: &Address) -> Vec<Address> {
+        storage::get(env, &DataKey::Affiliates(business.clone())).unwrap_or_else(|| Vec::new(env))
+    }
+
+    fn set_affiliates(env: &Env, business: &Address, affiliates: &Vec<Address>) {
+        storage::set(env, &DataKey::Affiliates(business.clone()), affiliates);
+    }
+
+    /// Get how many recycled-funding patterns have been flagged
+    pub fn get_wash_flag_count(env: &Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("wash_cnt"))
+            .unwrap_or(0)
+    }
+
+    /// Get a flagged recycled-funding pattern by its number, counting from 1
+    pub fn get_wash_flag(env: &Env, index: u32) -> Option<WashFlag> {
+        storage::get(env, &DataKey::WashFlag(index))
+    }
+
+    /// Get the recycled-funding patterns flagged so far, oldest first,
+    /// skipping `offset` flags and returning at most `limit`
+    pub fn get_wash_flags(env: &Env, offset: u32, limit: u32) -> Vec<WashFlag> {
+        let mut flags = Vec::new(env);
+        let count = Self::get_wash_flag_count(env);
+        let limit = limit.min(MAX_WASH_FLAGS_PAGE);
+        let mut index = offset.saturating_add(1);
+        while index <= count && flags.len() < limit {
+            if let Some(flag) = Self::get_wash_flag(env, index) {
+                flags.push_back(flag);
+            }
+            index += 1;
+        }
+        flags
+    }
+
+    /// Record a flagged recycled-funding pattern under the next number
+    pub(crate) fn add_wash_flag(env: &Env, flag: &WashFlag) {
+        let index = Self::get_wash_flag_count(env) + 1;
+        storage::set(env, &DataKey::WashFlag(index), flag);
+        env.storage()
+            .instance()
+            .set(&symbol_short!("wash_cnt"), &index);
+    }
+
+    /// Get the blacklisted addresses, in the order they were added
+    pub fn get_blacklist(env: &Env) -> Vec<Address> {
+        storage::get(env, &DataKey::Blacklist).unwrap_or_else(|| Vec::new(env))
+    }
+
+    fn set_blacklist(env: &Env, blacklist: &Vec<Address>) {
+        storage::set(env, &DataKey::Blacklist, blacklist);
+    }
+
+    /// Get why and by whom an address was blacklisted, if it is
+    pub fn get_blacklist_entry(env: &Env, address: &Address) -> Option<BlacklistEntry> {
+        storage::get(env, &DataKey::BlacklistEntry(address.clone()))
+    }
+
+    /// Check whether an address is blacklisted
+    pub fn is_blacklisted(env: &Env, address: &Address) -> bool {
+        Self::get_blacklist_entry(env, address).is_some()
+    }
+
+    /// Get the last invoice of a business that repaid an investor, and when
+    fn get_last_return(
+        env: &Env,
+        business: &Address,
+        investor: &Address,
+    ) -> Option<(BytesN<32>, u64)> {
+        storage::get(
+            env,
+            &DataKey::LastReturn(business.clone(), investor.clone()),
+        )
+    }
+}
+
+/// Appoint the compliance officer (admin only)
+pub fn set_compliance_officer(
+    env: &Env,
+    admin: &Address,
+    officer: &Address,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    env.storage()
+        .instance()
+        .set(&symbol_short!("cmpl_off"), officer);
+    emit_compliance_officer_set(env, officer, admin);
+    Ok(())
+}
+
+fn require_compliance(env: &Env, officer: &Address) -> Result<(), QuickLendXError> {
+    officer.require_auth();
+    let is_officer = ComplianceStorage::get_officer(env).as_ref() == Some(officer);
+    if !is_officer && !BusinessVerificationStorage::is_admin(env, officer) {
+        return Err(QuickLendXError::Unauthorized);
+    }
+    Ok(())
+}
+
+/// Register an address as an affiliate of a business, barring it from
+/// funding the business's invoices (compliance officer or admin only)
+pub fn add_affiliate(
+    env: &Env,
+    officer: &Address,
+    business: &Address,
+    affiliate: &Address,
+) -> Result<(), QuickLendXError> {
+    require_compliance(env, officer)?;
+    if affiliate == business {
+        return Err(QuickLendXError::InvalidAddress);
+    }
+    let mut affiliates = ComplianceStorage::get_affiliates(env, business);
+    if !affiliates.contains(affiliate) {
+        affiliates.push_back(affiliate.clone());
+        ComplianceStorage::set_affiliates(env, business, &affiliates);
+    }
+    emit_affiliate_added(env, business, affiliate, officer);
+    Ok(())
+}
+
+/// Remove an address from a business's affiliates (compliance officer or
+/// admin only)
+pub fn remove_affiliate(
+    env: &Env,
+    officer: &Address,
+    business: &Address,
+    affiliate: &Address,
+) -> Result<(), QuickLendXError> {
+    require_compliance(env, officer)?;
+    let mut affiliates = ComplianceStorage::get_affiliates(env, business);
+    let index = affiliates
+        .first_index_of(affiliate)
+        .ok_or(QuickLendXError::StorageKeyNotFound)?;
+    affiliates.remove(index);
+    ComplianceStorage::set_affiliates(env, business, &affiliates);
+    emit_affiliate_removed(env, business, affiliate, officer);
+    Ok(())
+}
+
+/// Blacklist a business or investor, replacing the reason if it already is
+/// (admin only)
+pub fn add_to_blacklist(
+    env: &Env,
+    admin: &Address,
+    address: &Address,
+    reason: String,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    check_rejection_reason_length(env, &reason)?;
+    if !ComplianceStorage::is_blacklisted(env, address) {
+        let mut blacklist = ComplianceStorage::get_blacklist(env);
+        blacklist.push_back(address.clone());
+        ComplianceStorage::set_blacklist(env, &blacklist);
+    }
+    let entry = BlacklistEntry {
+        address: address.clone(),
+        reason,
+        added_by: admin.clone(),
+        added_at: env.ledger().timestamp(),
+    };
+    storage::set(env, &DataKey::BlacklistEntry(address.clone()), &entry);
+    emit_blacklist_added(env, &entry);
+    Ok(())
+}
+
+/// Take an address off the blacklist (admin only)
+pub fn remove_from_blacklist(
+    env: &Env,
+    admin: &Address,
+    address: &Address,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    let mut blacklist = ComplianceStorage::get_blacklist(env);
+    let index = blacklist
+        .first_index_of(address)
+        .ok_or(QuickLendXError::StorageKeyNotFound)?;
+    blacklist.remove(index);
+    ComplianceStorage::set_blacklist(env, &blacklist);
+    storage::remove(env, &DataKey::BlacklistEntry(address.clone()));
+    emit_blacklist_removed(env, address, admin);
+    Ok(())
+}
+
+/// Reject an operation with a blacklisted party
+pub fn check_not_blacklisted(env: &Env, address: &Address) -> Result<(), QuickLendXError> {
+    if ComplianceStorage::is_blacklisted(env, address) {
+        return Err(QuickLendXError::Blacklisted);
+    }
+    Ok(())
+}
+
+/// Reject funding where the investor is the business itself or one of its
+/// registered affiliates
+pub fn check_not_self_dealing(
+    env: &Env,
+    business: &Address,
+    investor: &Address,
+) -> Result<(), QuickLendXError> {
+    if investor == business || ComplianceStorage::get_affiliates(env, business).contains(investor) {
+        return Err(QuickLendXError::SelfDealing);
+    }
+    Ok(())
+}
+
+/// Remember that a settled invoice repaid each of its investors
+pub fn record_settlement_returns(env: &Env, invoice: &Invoice) {
+    let settled_at = env.ledger().timestamp();
+    for investor in invoice.investors.iter() {
+        storage::set(
+            env,
+            &DataKey::LastReturn(invoice.business.clone(), investor),
+            &(invoice.id.clone(), settled_at),
+        );
+    }
+}
+
+/// Flag an investor funding a business within the recycle window of being
+/// repaid by it. The funding is not blocked; the flag is left for compliance.
+pub fn flag_recycled_funding(env: &Env, invoice: &Invoice, investor: &Address) {
+    let Some((settled_invoice, settled_at)) =
+        ComplianceStorage::get_last_return(env, &invoice.business, investor)
+    else {
+        return;
+    };
+    let now = env.ledger().timestamp();
+    if now.saturating_sub(settled_at) > RECYCLE_WINDOW {
+        return;
+    }
+    let flag = WashFlag {
+        business: invoice.business.clone(),
+        investor: investor.clone(),
+        settled_invoice,
+        funded_invoice: invoice.id.clone(),
+        settled_at,
+        funded_at: now,
+    };
+    ComplianceStorage::add_wash_flag(env, &flag);
+    emit_wash_flagged(env, &flag);
+}
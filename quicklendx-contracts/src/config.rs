@@ -0,0 +1,408 @@
+use crate::config_history::record_config_change;
+use crate::errors::QuickLendXError;
+use crate::events::{
+    emit_currency_added, emit_currency_removed, emit_default_grace_period_set,
+    emit_default_min_ticket_set, emit_invoice_min_ticket_set, emit_max_investors_set,
+    emit_platform_fee_set, emit_size_limits_set,
+};
+use crate::invoice::{Invoice, InvoiceStatus, InvoiceStorage};
+use crate::verification::require_admin;
+use soroban_sdk::{contracttype, symbol_short, Address, BytesN, Env, String, Vec};
+
+/// Hard upper bounds (in bytes) that admin-configured size limits cannot exceed
+pub const MAX_DESCRIPTION_LENGTH: u32 = 4096;
+pub const MAX_FEEDBACK_LENGTH: u32 = 1024;
+pub const MAX_KYC_DATA_LENGTH: u32 = 8192;
+pub const MAX_REJECTION_REASON_LENGTH: u32 = 1024;
+
+/// Highest platform fee the admin can configure, in bps of investor profit
+pub const MAX_PLATFORM_FEE_BPS: u32 = 2_000;
+
+/// Time after the due date before a funded invoice may be defaulted, used
+/// until the admin configures one
+pub const DEFAULT_GRACE_PERIOD: u64 = 7 * 86400;
+
+/// Longest default grace period the admin can configure
+pub const MAX_DEFAULT_GRACE_PERIOD: u64 = 90 * 86400;
+
+/// Distinct investors an invoice may have until the admin configures a cap
+pub const DEFAULT_MAX_INVESTORS: u32 = 20;
+
+/// Highest cap on distinct investors per invoice the admin can configure,
+/// bounding the transfers made at settlement
+pub const MAX_INVESTORS_LIMIT: u32 = 100;
+
+/// Where platform fees are paid and how much of investor profit they take
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PlatformFeeConfig {
+    pub recipient: Address,
+    pub fee_bps: u32,
+}
+
+/// Maximum lengths (in bytes) of user-supplied text fields
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SizeLimits {
+    pub description: u32,
+    pub feedback: u32,
+    pub kyc_data: u32,
+    pub rejection_reason: u32,
+}
+
+/// Protocol-wide configuration managed by the admin
+pub struct ConfigStorage;
+
+impl ConfigStorage {
+    /// Get the whitelist of currencies accepted for invoices
+    pub fn get_supported_currencies(env: &Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("currency"))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    fn set_supported_currencies(env: &Env, currencies: &Vec<Address>) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("currency"), currencies);
+    }
+
+    /// Get the text field size limits, falling back to the defaults
+    pub fn get_size_limits(env: &Env) -> SizeLimits {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("sz_limits"))
+            .unwrap_or(SizeLimits {
+                description: 512,
+                feedback: 256,
+                kyc_data: 2048,
+                rejection_reason: 256,
+            })
+    }
+
+    fn set_size_limits(env: &Env, limits: &SizeLimits) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("sz_limits"), limits);
+    }
+
+    /// Get the platform fee configuration. Until the admin sets one, no fee is
+    /// charged and the contract itself is the recipient.
+    pub fn get_platform_fee(env: &Env) -> PlatformFeeConfig {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("plat_fee"))
+            .unwrap_or_else(|| PlatformFeeConfig {
+                recipient: env.current_contract_address(),
+                fee_bps: 0,
+            })
+    }
+
+    fn set_platform_fee(env: &Env, config: &PlatformFeeConfig) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("plat_fee"), config);
+    }
+
+    /// Get the time after the due date before a funded invoice may be defaulted
+    pub fn get_default_grace_period(env: &Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("dflt_grc"))
+            .unwrap_or(DEFAULT_GRACE_PERIOD)
+    }
+
+    fn set_default_grace_period(env: &Env, grace_period: u64) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("dflt_grc"), &grace_period);
+    }
+
+    /// Get the most distinct investors an invoice may have
+    pub fn get_max_investors(env: &Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("max_inv"))
+            .unwrap_or(DEFAULT_MAX_INVESTORS)
+    }
+
+    fn set_max_investors(env: &Env, max_investors: u32) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("max_inv"), &max_investors);
+    }
+
+    /// Get the smallest bid accepted on invoices without their own minimum
+    pub fn get_default_min_ticket(env: &Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("min_tkt"))
+            .unwrap_or(0)
+    }
+
+    fn set_default_min_ticket(env: &Env, amount: i128) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("min_tkt"), &amount);
+    }
+
+    /// Get the minimum bid a business set on one of its invoices
+    pub fn get_invoice_min_ticket(env: &Env, invoice_id: &BytesN<32>) -> Option<i128> {
+        env.storage()
+            .instance()
+            .get(&(symbol_short!("inv_mtk"), invoice_id.clone()))
+    }
+
+    /// Get the smallest bid an invoice accepts: its own minimum if set,
+    /// otherwise the protocol default
+    pub fn get_min_ticket(env: &Env, invoice_id: &BytesN<32>) -> i128 {
+        Self::get_invoice_min_ticket(env, invoice_id)
+            .unwrap_or_else(|| Self::get_default_min_ticket(env))
+    }
+
+    /// Check whether a currency may be used for invoices.
+    /// Until the admin whitelists a first currency, any token is accepted.
+    pub fn is_currency_supported(env: &Env, currency: &Address) -> bool {
+        let currencies = Self::get_supported_currencies(env);
+        currencies.is_empty() || currencies.contains(currency)
+    }
+}
+
+/// Add a currency to the whitelist (admin only)
+pub fn add_supported_currency(
+    env: &Env,
+    admin: &Address,
+    currency: &Address,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+
+    let mut currencies = ConfigStorage::get_supported_currencies(env);
+    if currencies.contains(currency) {
+        return Ok(());
+    }
+    currencies.push_back(currency.clone());
+    ConfigStorage::set_supported_currencies(env, &currencies);
+    record_config_change(env, "currencies", &currencies, admin);
+    emit_currency_added(env, currency, admin);
+    Ok(())
+}
+
+/// Remove a currency from the whitelist (admin only). Once configured the
+/// whitelist cannot be emptied again, since an empty whitelist accepts any token.
+pub fn remove_supported_currency(
+    env: &Env,
+    admin: &Address,
+    currency: &Address,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+
+    let mut currencies = ConfigStorage::get_supported_currencies(env);
+    let index = currencies
+        .first_index_of(currency)
+        .ok_or(QuickLendXError::InvalidCurrency)?;
+    if currencies.len() == 1 {
+        return Err(QuickLendXError::LastCurrency);
+    }
+    currencies.remove(index);
+    ConfigStorage::set_supported_currencies(env, &currencies);
+    record_config_change(env, "currencies", &currencies, admin);
+    emit_currency_removed(env, currency, admin);
+    Ok(())
+}
+
+/// Reject invoices denominated in a currency that is not whitelisted
+pub fn require_supported_currency(env: &Env, currency: &Address) -> Result<(), QuickLendXError> {
+    if !ConfigStorage::is_currency_supported(env, currency) {
+        return Err(QuickLendXError::InvalidCurrency);
+    }
+    Ok(())
+}
+
+/// Set the platform fee recipient and rate (admin only)
+pub fn set_platform_fee(
+    env: &Env,
+    admin: &Address,
+    recipient: &Address,
+    fee_bps: u32,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    if fee_bps > MAX_PLATFORM_FEE_BPS {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+
+    let config = PlatformFeeConfig {
+        recipient: recipient.clone(),
+        fee_bps,
+    };
+    ConfigStorage::set_platform_fee(env, &config);
+    record_config_change(env, "platform_fee", &config, admin);
+    emit_platform_fee_set(env, &config, admin);
+    Ok(())
+}
+
+/// Set the default grace period in seconds (admin only)
+pub fn set_default_grace_period(
+    env: &Env,
+    admin: &Address,
+    grace_period: u64,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    if grace_period > MAX_DEFAULT_GRACE_PERIOD {
+        return Err(QuickLendXError::InvalidTimestamp);
+    }
+
+    ConfigStorage::set_default_grace_period(env, grace_period);
+    record_config_change(env, "default_grace_period", grace_period, admin);
+    emit_default_grace_period_set(env, grace_period, admin);
+    Ok(())
+}
+
+/// Set the most distinct investors an invoice may have (admin only)
+pub fn set_max_investors(
+    env: &Env,
+    admin: &Address,
+    max_investors: u32,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    if max_investors == 0 || max_investors > MAX_INVESTORS_LIMIT {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    ConfigStorage::set_max_investors(env, max_investors);
+    record_config_change(env, "max_investors", max_investors, admin);
+    emit_max_investors_set(env, max_investors, admin);
+    Ok(())
+}
+
+/// Reject funding from a new investor once the invoice has as many distinct
+/// investors as allowed; investors already in the invoice may add to it
+pub fn check_investor_cap(
+    env: &Env,
+    invoice: &Invoice,
+    investor: &Address,
+) -> Result<(), QuickLendXError> {
+    if !invoice.is_investor(investor)
+        && invoice.investors.len() >= ConfigStorage::get_max_investors(env)
+    {
+        return Err(QuickLendXError::TooManyInvestors);
+    }
+    Ok(())
+}
+
+/// Set the protocol-wide minimum bid size; 0 disables it (admin only)
+pub fn set_default_min_ticket(
+    env: &Env,
+    admin: &Address,
+    amount: i128,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    if amount < 0 {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    ConfigStorage::set_default_min_ticket(env, amount);
+    record_config_change(env, "default_min_ticket", amount, admin);
+    emit_default_min_ticket_set(env, amount, admin);
+    Ok(())
+}
+
+/// Set or clear the minimum bid size of an unfunded invoice, overriding the
+/// protocol default (business owner only)
+pub fn set_invoice_min_ticket(
+    env: &Env,
+    business: &Address,
+    invoice_id: &BytesN<32>,
+    amount: Option<i128>,
+) -> Result<(), QuickLendXError> {
+    business.require_auth();
+    let invoice =
+        InvoiceStorage::get_invoice(env, invoice_id).ok_or(QuickLendXError::InvoiceNotFound)?;
+    if invoice.business != *business {
+        return Err(QuickLendXError::NotBusinessOwner);
+    }
+    if invoice.status != InvoiceStatus::Pending && invoice.status != InvoiceStatus::Verified {
+        return Err(QuickLendXError::InvalidStatus);
+    }
+
+    let key = (symbol_short!("inv_mtk"), invoice_id.clone());
+    match amount {
+        Some(amount) => {
+            if amount <= 0 || amount > invoice.amount {
+                return Err(QuickLendXError::InvalidAmount);
+            }
+            env.storage().instance().set(&key, &amount);
+        }
+        None => env.storage().instance().remove(&key),
+    }
+    emit_invoice_min_ticket_set(env, invoice_id, amount, business);
+    Ok(())
+}
+
+/// Reject bids below the invoice's minimum ticket, unless the bid is exactly
+/// what is left to fund, so dust positions never build up
+pub fn check_min_ticket(
+    env: &Env,
+    invoice: &Invoice,
+    bid_amount: i128,
+) -> Result<(), QuickLendXError> {
+    let min_ticket = ConfigStorage::get_min_ticket(env, &invoice.id);
+    if bid_amount < min_ticket && bid_amount != invoice.remaining_funding() {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    Ok(())
+}
+
+/// Update the text field size limits (admin only). Each limit must be
+/// non-zero and within its hard bound.
+pub fn set_size_limits(
+    env: &Env,
+    admin: &Address,
+    limits: &SizeLimits,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+
+    let bounds = [
+        (limits.description, MAX_DESCRIPTION_LENGTH),
+        (limits.feedback, MAX_FEEDBACK_LENGTH),
+        (limits.kyc_data, MAX_KYC_DATA_LENGTH),
+        (limits.rejection_reason, MAX_REJECTION_REASON_LENGTH),
+    ];
+    if bounds.iter().any(|(limit, max)| *limit == 0 || limit > max) {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+
+    ConfigStorage::set_size_limits(env, limits);
+    record_config_change(env, "size_limits", limits, admin);
+    emit_size_limits_set(env, limits, admin);
+    Ok(())
+}
+
+fn check_length(value: &String, limit: u32, error: QuickLendXError) -> Result<(), QuickLendXError> {
+    if value.len() > limit {
+        return Err(error);
+    }
+    Ok(())
+}
+
+/// Reject invoice descriptions longer than the configured limit
+pub fn check_description_length(env: &Env, description: &String) -> Result<(), QuickLendXError> {
+    let limit = ConfigStorage::get_size_limits(env).description;
+    check_length(description, limit, QuickLendXError::DescriptionTooLong)
+}
+
+/// Reject rating feedback longer than the configured limit
+pub fn check_feedback_length(env: &Env, feedback: &String) -> Result<(), QuickLendXError> {
+    let limit = ConfigStorage::get_size_limits(env).feedback;
+    check_length(feedback, limit, QuickLendXError::FeedbackTooLong)
+}
+
+/// Reject KYC data longer than the configured limit
+pub fn check_kyc_data_length(env: &Env, kyc_data: &String) -> Result<(), QuickLendXError> {
+    let limit = ConfigStorage::get_size_limits(env).kyc_data;
+    check_length(kyc_data, limit, QuickLendXError::KycDataTooLong)
+}
+
+/// Reject KYC rejection reasons longer than the configured limit
+pub fn check_rejection_reason_length(env: &Env, reason: &String) -> Result<(), QuickLendXError> {
+    let limit = ConfigStorage::get_size_limits(env).rejection_reason;
+    check_length(reason, limit, QuickLendXError::RejectionReasonTooLong)
+}
@@ -0,0 +1,96 @@
+use crate::storage::{self, DataKey};
+use soroban_sdk::{
+    contracttype, symbol_short, xdr::ToXdr, Address, Bytes, Env, IntoVal, Symbol, Val, Vec,
+};
+
+/// Most history entries returned by one query
+pub const MAX_CONFIG_HISTORY_PAGE: u32 = 50;
+
+/// A protocol-wide configuration value as the admin set it. `value` is the
+/// XDR of the parameter's own type, as its getter returns it:
+///
+/// - `platform_fee`: PlatformFeeConfig
+/// - `default_grace_period`, `archive_age`, `attestation_max_age`,
+///   `acceptance_window`, `kyc_validity`, `extension_vote_window`: u64
+/// - `max_investors`, `backstop_fee`, `late_fee`, `max_extension_days`: u32
+/// - `default_min_ticket`: i128
+/// - `size_limits`: SizeLimits
+/// - `currencies`: Vec<Address>, the whole whitelist after the change
+/// - `maturity_buckets`: Vec<u64>
+/// - `premium_rates`: PremiumRates
+/// - `insurance_pool_config`: InsurancePoolConfig
+/// - `penalty_schedule`: PenaltySchedule
+/// - `basic_tier_limits`, `standard_tier_limits`, `enhanced_tier_limits`:
+///   TierLimits
+/// - `verification_quorum`: VerificationQuorum
+/// - `auto_verify_rules`: Vec<AutoVerifyRule>
+/// - `investor_kyc_required`: bool
+///
+/// Before a parameter's first entry its built-in default applied.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConfigChange {
+    pub param: Symbol,
+    pub version: u32, // Sequential per parameter, from 1
+    pub value: Bytes,
+    pub changed_by: Address,
+    pub changed_at: u64,
+    pub ledger: u32,
+}
+
+pub struct ConfigHistoryStorage;
+
+impl ConfigHistoryStorage {
+    /// Get how many times a parameter has been set
+    pub fn get_version(env: &Env, param: &Symbol) -> u32 {
+        env.storage()
+            .instance()
+            .get(&(symbol_short!("cfg_ver"), param.clone()))
+            .unwrap_or(0)
+    }
+
+    /// Get one version of a parameter
+    pub fn get_change(env: &Env, param: &Symbol, version: u32) -> Option<ConfigChange> {
+        storage::get(env, &DataKey::ConfigChange(param.clone(), version))
+    }
+}
+
+/// Record the new value of a configuration parameter. Called by each setter
+/// once the value is stored.
+pub fn record_config_change<V: IntoVal<Env, Val>>(
+    env: &Env,
+    param: &str,
+    value: V,
+    admin: &Address,
+) {
+    let param = Symbol::new(env, param);
+    let version = ConfigHistoryStorage::get_version(env, &param) + 1;
+    let change = ConfigChange {
+        param: param.clone(),
+        version,
+        value: value.into_val(env).to_xdr(env),
+        changed_by: admin.clone(),
+        changed_at: env.ledger().timestamp(),
+        ledger: env.ledger().sequence(),
+    };
+    storage::set(env, &DataKey::ConfigChange(param.clone(), version), &change);
+    env.storage()
+        .instance()
+        .set(&(symbol_short!("cfg_ver"), param), &version);
+}
+
+/// Get the values a parameter has had, oldest first, skipping `offset`
+/// entries and returning at most `limit`
+pub fn get_config_history(env: &Env, param: &Symbol, offset: u32, limit: u32) -> Vec<ConfigChange> {
+    let mut changes = Vec::new(env);
+    let latest = ConfigHistoryStorage::get_version(env, param);
+    let limit = limit.min(MAX_CONFIG_HISTORY_PAGE);
+    let mut version = offset.saturating_add(1);
+    while version <= latest && changes.len() < limit {
+        if let Some(change) = ConfigHistoryStorage::get_change(env, param, version) {
+            changes.push_back(change);
+        }
+        version += 1;
+    }
+    changes
+}
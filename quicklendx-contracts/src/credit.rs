@@ -0,0 +1,93 @@
+use crate::errors::QuickLendXError;
+use crate::events::emit_credit_limit_set;
+use crate::invoice::{Invoice, InvoiceStatus, InvoiceStorage};
+use crate::limits::{override_limit, LimitKind};
+use crate::oracle::to_reference;
+use crate::storage::{self, DataKey};
+use crate::verification::require_admin;
+use soroban_sdk::{Address, BytesN, Env};
+
+pub struct CreditStorage;
+
+impl CreditStorage {
+    /// Get the most a business may have funded and outstanding at once, if
+    /// it is limited
+    pub fn get_credit_limit(env: &Env, business: &Address) -> Option<i128> {
+        storage::get(env, &DataKey::CreditLimit(business.clone()))
+    }
+}
+
+/// Limit how much a business may have funded and outstanding at once; 0
+/// removes the limit (admin only). A limit below the current exposure blocks
+/// new funding until enough is repaid.
+pub fn set_credit_limit(
+    env: &Env,
+    admin: &Address,
+    business: &Address,
+    limit: i128,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    if limit < 0 {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    let key = DataKey::CreditLimit(business.clone());
+    if limit == 0 {
+        storage::remove(env, &key);
+    } else {
+        storage::set(env, &key, &limit);
+    }
+    emit_credit_limit_set(env, business, limit, admin);
+    Ok(())
+}
+
+/// Get the funded amount of a business's invoices not yet repaid or
+/// defaulted, including invoices still collecting funding
+pub fn get_business_exposure(env: &Env, business: &Address) -> i128 {
+    exposed_invoices(env, business)
+        .map(|invoice| invoice.funded_amount)
+        .sum()
+}
+
+/// Get a business's exposure valued in the reference currency
+pub fn get_reference_exposure(env: &Env, business: &Address) -> Result<i128, QuickLendXError> {
+    let mut exposure = 0i128;
+    for invoice in exposed_invoices(env, business) {
+        exposure += to_reference(env, &invoice.currency, invoice.funded_amount)?;
+    }
+    Ok(exposure)
+}
+
+fn exposed_invoices<'a>(env: &'a Env, business: &Address) -> impl Iterator<Item = Invoice> + 'a {
+    InvoiceStorage::get_business_invoices(env, business)
+        .into_iter()
+        .filter_map(move |invoice_id| InvoiceStorage::get_invoice(env, &invoice_id))
+        .filter(|invoice| {
+            matches!(
+                invoice.status,
+                InvoiceStatus::Verified | InvoiceStatus::Funded
+            )
+        })
+}
+
+/// Reject taking on `amount` more funding for an invoice if it would put the
+/// business over its credit limit, or over the limit an approved limit
+/// request sets for the invoice
+pub fn check_credit_limit(
+    env: &Env,
+    business: &Address,
+    currency: &Address,
+    amount: i128,
+    invoice_hash: &Option<BytesN<32>>,
+) -> Result<(), QuickLendXError> {
+    let limit = override_limit(env, business, invoice_hash, LimitKind::CreditExposure)
+        .or_else(|| CreditStorage::get_credit_limit(env, business));
+    let Some(limit) = limit else {
+        return Ok(());
+    };
+    // Limits are in the reference currency once a price oracle is set
+    let amount = to_reference(env, currency, amount)?;
+    if get_reference_exposure(env, business)?.saturating_add(amount) > limit {
+        return Err(QuickLendXError::CreditLimitExceeded);
+    }
+    Ok(())
+}
@@ -0,0 +1,185 @@
+use crate::compliance::check_not_blacklisted;
+use crate::defaults::get_default_recovery;
+use crate::errors::QuickLendXError;
+use crate::events::{emit_claim_listed, emit_claim_listing_cancelled, emit_claim_sold};
+use crate::insurance::InsuranceStorage;
+use crate::investment::{Investment, InvestmentStorage};
+use crate::investor_kyc::require_investor_verification;
+use crate::invoice::{Invoice, InvoiceStatus, InvoiceStorage};
+use crate::payments::transfer_funds;
+use crate::storage::{self, DataKey};
+use soroban_sdk::{contracttype, Address, BytesN, Env, Vec};
+
+/// A defaulted investment's claim on future recoveries, offered for sale
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ClaimListing {
+    pub investment_id: BytesN<32>,
+    pub invoice_id: BytesN<32>,
+    pub seller: Address,
+    pub price: i128, // In the invoice currency, below the investment amount
+    pub currency: Address,
+    pub listed_at: u64,
+}
+
+pub struct DebtMarketStorage;
+
+impl DebtMarketStorage {
+    /// Get who holds an investment's claim on recoveries: its investor until
+    /// the claim is sold
+    pub fn get_claim_owner(env: &Env, investment: &Investment) -> Address {
+        storage::get(env, &DataKey::ClaimOwner(investment.investment_id.clone()))
+            .unwrap_or_else(|| investment.investor.clone())
+    }
+
+    /// Get the claims an address has bought, in purchase order. Claims it
+    /// has since sold on are left out.
+    pub fn get_owned_claims(env: &Env, owner: &Address) -> Vec<BytesN<32>> {
+        storage::get(env, &DataKey::OwnedClaims(owner.clone())).unwrap_or_else(|| Vec::new(env))
+    }
+
+    fn set_owned_claims(env: &Env, owner: &Address, claims: &Vec<BytesN<32>>) {
+        storage::set(env, &DataKey::OwnedClaims(owner.clone()), claims);
+    }
+
+    /// Get the listing of an investment's claim, if it is for sale
+    pub fn get_listing(env: &Env, investment_id: &BytesN<32>) -> Option<ClaimListing> {
+        storage::get(env, &DataKey::ClaimListing(investment_id.clone()))
+    }
+
+    /// Get the ids of every investment whose claim is for sale, oldest first
+    pub fn get_listed(env: &Env) -> Vec<BytesN<32>> {
+        storage::get(env, &DataKey::ListedClaims).unwrap_or_else(|| Vec::new(env))
+    }
+
+    fn remove_listing(env: &Env, investment_id: &BytesN<32>) {
+        storage::remove(env, &DataKey::ClaimListing(investment_id.clone()));
+        let mut listed = Self::get_listed(env);
+        if let Some(index) = listed.first_index_of(investment_id) {
+            listed.remove(index);
+        }
+        storage::set(env, &DataKey::ListedClaims, &listed);
+    }
+}
+
+/// List a defaulted investment's claim for sale (claim owner only). The
+/// price must be a discount on the amount invested. The investment's
+/// insurance passes to the buyer with the claim, so claims whose insurance
+/// was already drawn on cannot be sold.
+pub fn list_claim(
+    env: &Env,
+    seller: &Address,
+    investment_id: &BytesN<32>,
+    price: i128,
+) -> Result<ClaimListing, QuickLendXError> {
+    seller.require_auth();
+    let (investment, invoice) = get_open_claim(env, investment_id)?;
+    if DebtMarketStorage::get_claim_owner(env, &investment) != *seller {
+        return Err(QuickLendXError::NotInvestor);
+    }
+    if InsuranceStorage::is_insurance_used(env, investment_id) {
+        return Err(QuickLendXError::OperationNotAllowed);
+    }
+    if price <= 0 || price >= investment.amount {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    if DebtMarketStorage::get_listing(env, investment_id).is_some() {
+        return Err(QuickLendXError::ClaimAlreadyListed);
+    }
+
+    let listing = ClaimListing {
+        investment_id: investment_id.clone(),
+        invoice_id: invoice.id.clone(),
+        seller: seller.clone(),
+        price,
+        currency: invoice.currency.clone(),
+        listed_at: env.ledger().timestamp(),
+    };
+    storage::set(env, &DataKey::ClaimListing(investment_id.clone()), &listing);
+    let mut listed = DebtMarketStorage::get_listed(env);
+    listed.push_back(investment_id.clone());
+    storage::set(env, &DataKey::ListedClaims, &listed);
+    emit_claim_listed(env, &listing);
+    Ok(listing)
+}
+
+/// Take a claim off the market (seller only)
+pub fn cancel_listing(
+    env: &Env,
+    seller: &Address,
+    investment_id: &BytesN<32>,
+) -> Result<(), QuickLendXError> {
+    seller.require_auth();
+    let listing = DebtMarketStorage::get_listing(env, investment_id)
+        .ok_or(QuickLendXError::ClaimListingNotFound)?;
+    if listing.seller != *seller {
+        return Err(QuickLendXError::Unauthorized);
+    }
+    DebtMarketStorage::remove_listing(env, investment_id);
+    emit_claim_listing_cancelled(env, &listing);
+    Ok(())
+}
+
+/// Buy a listed claim, paying the seller its price. Recoveries on the
+/// investment are paid to the buyer from then on.
+pub fn buy_claim(
+    env: &Env,
+    buyer: &Address,
+    investment_id: &BytesN<32>,
+) -> Result<ClaimListing, QuickLendXError> {
+    buyer.require_auth();
+    let listing = DebtMarketStorage::get_listing(env, investment_id)
+        .ok_or(QuickLendXError::ClaimListingNotFound)?;
+    if listing.seller == *buyer {
+        return Err(QuickLendXError::OperationNotAllowed);
+    }
+    get_open_claim(env, investment_id)?;
+    check_not_blacklisted(env, buyer)?;
+    check_not_blacklisted(env, &listing.seller)?;
+    require_investor_verification(env, buyer)?;
+    if !transfer_funds(env, buyer, &listing.seller, listing.price) {
+        return Err(QuickLendXError::InsufficientFunds);
+    }
+
+    storage::set(env, &DataKey::ClaimOwner(investment_id.clone()), buyer);
+    let mut sold = DebtMarketStorage::get_owned_claims(env, &listing.seller);
+    if let Some(index) = sold.first_index_of(investment_id) {
+        sold.remove(index);
+        DebtMarketStorage::set_owned_claims(env, &listing.seller, &sold);
+    }
+    let mut bought = DebtMarketStorage::get_owned_claims(env, buyer);
+    bought.push_back(investment_id.clone());
+    DebtMarketStorage::set_owned_claims(env, buyer, &bought);
+    DebtMarketStorage::remove_listing(env, investment_id);
+    emit_claim_sold(env, &listing, buyer);
+    Ok(listing)
+}
+
+/// Get every claim for sale, oldest listing first
+pub fn get_claim_listings(env: &Env) -> Vec<ClaimListing> {
+    let mut listings = Vec::new(env);
+    for investment_id in DebtMarketStorage::get_listed(env).iter() {
+        if let Some(listing) = DebtMarketStorage::get_listing(env, &investment_id) {
+            listings.push_back(listing);
+        }
+    }
+    listings
+}
+
+/// Load an investment whose invoice defaulted and is still in recovery
+fn get_open_claim(
+    env: &Env,
+    investment_id: &BytesN<32>,
+) -> Result<(Investment, Invoice), QuickLendXError> {
+    let investment = InvestmentStorage::get_investment(env, investment_id)
+        .ok_or(QuickLendXError::StorageKeyNotFound)?;
+    let invoice = InvoiceStorage::get_invoice(env, &investment.invoice_id)
+        .ok_or(QuickLendXError::InvoiceNotFound)?;
+    if invoice.status != InvoiceStatus::Defaulted {
+        return Err(QuickLendXError::InvalidStatus);
+    }
+    if get_default_recovery(env, &invoice.id).is_some_and(|recovery| recovery.written_off) {
+        return Err(QuickLendXError::InvoiceWrittenOff);
+    }
+    Ok((investment, invoice))
+}
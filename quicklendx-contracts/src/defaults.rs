@@ -1,21 +1,230 @@
+use crate::analytics::record_defaulted;
+use crate::audit::log_invoice_status_change;
+use crate::compliance::check_not_blacklisted;
+use crate::config::ConfigStorage;
+use crate::debt_market::DebtMarketStorage;
 use crate::errors::QuickLendXError;
-use crate::events::emit_invoice_defaulted;
-use crate::investment::{Investment, InvestmentStatus, InvestmentStorage};
+use crate::events::{emit_default_recovery, emit_default_written_off, emit_invoice_defaulted};
+use crate::insurance::publish_risk_score;
+use crate::investment::{pro_rata_shares, Investment, InvestmentStatus, InvestmentStorage};
 use crate::invoice::{Invoice, InvoiceStatus, InvoiceStorage};
-use soroban_sdk::{BytesN, Env};
+use crate::maturity::release_funding;
+use crate::notifications::{notify, NotificationKind};
+use crate::observers::{notify_observers, LifecycleEvent};
+use crate::payments::transfer_funds;
+use crate::settlement::get_settlement_progress;
+use crate::stop_loss::record_closed;
+use crate::storage::{self, DataKey};
+use crate::tranche::{recovery_shares, TrancheStorage};
+use crate::verification::require_admin;
+use soroban_sdk::{contracttype, symbol_short, Address, BytesN, Env, Vec};
 
-pub fn handle_default(env: &Env, invoice_id: &BytesN<32>) -> Result<(), QuickLendXError> {
-    let mut invoice =
+/// What has been recovered on a defaulted invoice since it defaulted
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DefaultRecovery {
+    pub invoice_id: BytesN<32>,
+    pub recovered: i128, // Paid out to the investors so far
+    pub recoveries: u32,
+    pub last_recovered_at: u64,
+    pub written_off: bool, // No more recoveries are recorded once written off
+    pub written_off_at: Option<u64>,
+}
+
+/// Get what has been recovered on a defaulted invoice, if anything has been
+/// recorded
+pub fn get_default_recovery(env: &Env, invoice_id: &BytesN<32>) -> Option<DefaultRecovery> {
+    storage::get(env, &DataKey::DefaultRecovery(invoice_id.clone()))
+}
+
+/// Whether the default grace period after an invoice's due date has passed
+pub fn is_overdue(env: &Env, invoice: &Invoice) -> bool {
+    let grace_period = ConfigStorage::get_default_grace_period(env);
+    env.ledger().timestamp() > invoice.due_date.saturating_add(grace_period)
+}
+
+pub fn handle_default(
+    env: &Env,
+    admin: &Address,
+    invoice_id: &BytesN<32>,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    let invoice =
         InvoiceStorage::get_invoice(env, invoice_id).ok_or(QuickLendXError::InvoiceNotFound)?;
     if invoice.status != InvoiceStatus::Funded {
         return Err(QuickLendXError::InvalidStatus);
     }
+    if !is_overdue(env, &invoice) {
+        return Err(QuickLendXError::InvoiceNotOverdue);
+    }
+    default_invoice(env, admin, invoice)
+}
+
+/// Default every funded invoice whose grace period has passed. Anyone may
+/// call this; the contract itself is recorded as the actor. Returns the ids
+/// of the invoices that were defaulted.
+pub fn check_overdue_invoices(env: &Env) -> Vec<BytesN<32>> {
+    let keeper = env.current_contract_address();
+    let mut defaulted = Vec::new(env);
+    for invoice_id in InvoiceStorage::get_invoices_by_status(env, &InvoiceStatus::Funded).iter() {
+        if let Some(invoice) = InvoiceStorage::get_invoice(env, &invoice_id) {
+            if is_overdue(env, &invoice) && default_invoice(env, &keeper, invoice).is_ok() {
+                defaulted.push_back(invoice_id);
+            }
+        }
+    }
+    defaulted
+}
+
+fn default_invoice(
+    env: &Env,
+    actor: &Address,
+    mut invoice: Invoice,
+) -> Result<(), QuickLendXError> {
+    let invoice_id = invoice.id.clone();
+    // The business already paid in full; the payouts only need finishing
+    if get_settlement_progress(env, &invoice_id).is_some() {
+        return Err(QuickLendXError::OperationNotAllowed);
+    }
+    let investment_ids = InvestmentStorage::get_investments_for_invoice(env, &invoice_id);
+    if investment_ids.is_empty() {
+        return Err(QuickLendXError::StorageKeyNotFound);
+    }
+    InvoiceStorage::remove_from_status_invoices(env, &InvoiceStatus::Funded, &invoice_id);
+    release_funding(env, &invoice);
     invoice.mark_as_defaulted();
     InvoiceStorage::update_invoice(env, &invoice);
-    let mut investment = InvestmentStorage::get_investment(env, invoice_id)
-        .ok_or(QuickLendXError::StorageKeyNotFound)?;
-    investment.status = InvestmentStatus::Withdrawn;
-    InvestmentStorage::update_investment(env, &investment);
-    emit_invoice_defaulted(env, &invoice);
+    InvoiceStorage::add_to_status_invoices(env, &InvoiceStatus::Defaulted, &invoice_id);
+    log_invoice_status_change(
+        env,
+        invoice_id.clone(),
+        actor.clone(),
+        InvoiceStatus::Funded,
+        InvoiceStatus::Defaulted,
+    );
+    for investment_id in investment_ids.iter() {
+        if let Some(mut investment) = InvestmentStorage::get_investment(env, &investment_id) {
+            investment.status = InvestmentStatus::Withdrawn;
+            InvestmentStorage::update_investment(env, &investment);
+            record_closed(env, &investment, true);
+            notify(
+                env,
+                &investment.investor,
+                NotificationKind::InvoiceDefaulted,
+                &invoice_id,
+            );
+        }
+    }
+    record_defaulted(env, &invoice);
+    emit_invoice_defaulted(env, &invoice, actor);
+    publish_risk_score(env, &invoice.business, symbol_short!("default"));
+    notify(
+        env,
+        &invoice.business,
+        NotificationKind::InvoiceDefaulted,
+        &invoice_id,
+    );
+    notify_observers(env, &invoice_id, LifecycleEvent::Defaulted);
     Ok(())
 }
+
+/// Record funds recovered on a defaulted invoice, paid by `payer` straight to
+/// the holders of the investors' claims in proportion to what each investment
+/// funded (admin only). Recoveries carry no platform fee.
+pub fn record_default_recovery(
+    env: &Env,
+    admin: &Address,
+    invoice_id: &BytesN<32>,
+    payer: &Address,
+    amount: i128,
+) -> Result<DefaultRecovery, QuickLendXError> {
+    require_admin(env, admin)?;
+    if payer != admin {
+        payer.require_auth();
+    }
+    if amount <= 0 {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    let invoice =
+        InvoiceStorage::get_invoice(env, invoice_id).ok_or(QuickLendXError::InvoiceNotFound)?;
+    if invoice.status != InvoiceStatus::Defaulted {
+        return Err(QuickLendXError::InvalidStatus);
+    }
+    let mut recovery = open_recovery(env, invoice_id)?;
+
+    let mut investments: Vec<Investment> = Vec::new(env);
+    for investment_id in InvestmentStorage::get_investments_for_invoice(env, invoice_id).iter() {
+        if let Some(investment) = InvestmentStorage::get_investment(env, &investment_id) {
+            check_not_blacklisted(env, &DebtMarketStorage::get_claim_owner(env, &investment))?;
+            investments.push_back(investment);
+        }
+    }
+    if investments.is_empty() {
+        return Err(QuickLendXError::NotInvestor);
+    }
+    // A tranched invoice's recoveries go down its waterfall, senior first
+    let shares = match TrancheStorage::get_structure(env, invoice_id) {
+        Some(structure) => {
+            recovery_shares(env, &structure, &investments, recovery.recovered, amount)
+        }
+        None => pro_rata_shares(env, &investments, amount),
+    };
+    for (investment, share) in investments.iter().zip(shares.iter()) {
+        let owner = DebtMarketStorage::get_claim_owner(env, &investment);
+        if share > 0 && !transfer_funds(env, payer, &owner, share) {
+            return Err(QuickLendXError::InsufficientFunds);
+        }
+    }
+
+    recovery.recovered += amount;
+    recovery.recoveries += 1;
+    recovery.last_recovered_at = env.ledger().timestamp();
+    storage::set(
+        env,
+        &DataKey::DefaultRecovery(invoice_id.clone()),
+        &recovery,
+    );
+    emit_default_recovery(env, &recovery, amount, payer);
+    Ok(recovery)
+}
+
+/// Close a defaulted invoice's recovery, writing off whatever was not
+/// recovered (admin only)
+pub fn write_off_default(
+    env: &Env,
+    admin: &Address,
+    invoice_id: &BytesN<32>,
+) -> Result<DefaultRecovery, QuickLendXError> {
+    require_admin(env, admin)?;
+    let invoice =
+        InvoiceStorage::get_invoice(env, invoice_id).ok_or(QuickLendXError::InvoiceNotFound)?;
+    if invoice.status != InvoiceStatus::Defaulted {
+        return Err(QuickLendXError::InvalidStatus);
+    }
+    let mut recovery = open_recovery(env, invoice_id)?;
+    recovery.written_off = true;
+    recovery.written_off_at = Some(env.ledger().timestamp());
+    storage::set(
+        env,
+        &DataKey::DefaultRecovery(invoice_id.clone()),
+        &recovery,
+    );
+    emit_default_written_off(env, &invoice, &recovery, admin);
+    Ok(recovery)
+}
+
+/// The invoice's recovery record, refusing one already written off
+fn open_recovery(env: &Env, invoice_id: &BytesN<32>) -> Result<DefaultRecovery, QuickLendXError> {
+    let recovery = get_default_recovery(env, invoice_id).unwrap_or(DefaultRecovery {
+        invoice_id: invoice_id.clone(),
+        recovered: 0,
+        recoveries: 0,
+        last_recovered_at: 0,
+        written_off: false,
+        written_off_at: None,
+    });
+    if recovery.written_off {
+        return Err(QuickLendXError::InvoiceWrittenOff);
+    }
+    Ok(recovery)
+}
@@ -0,0 +1,57 @@
+use crate::events::emit_delinquency_stage_changed;
+use crate::invoice::{Invoice, InvoiceStatus, InvoiceStorage};
+use crate::storage::{self, DataKey};
+use soroban_sdk::{contracttype, BytesN, Env, Vec};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// How far a funded invoice is past its due date, short of being defaulted
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord)]
+pub enum DelinquencyStage {
+    Current,      // Not yet due
+    GracePeriod,  // Due, but less than 30 days late
+    Delinquent30, // At least 30 days late
+    Delinquent60, // At least 60 days late
+}
+
+/// Get the stage an invoice has reached by now, going by its due date
+pub fn current_stage(env: &Env, invoice: &Invoice) -> DelinquencyStage {
+    let now = env.ledger().timestamp();
+    if now <= invoice.due_date {
+        return DelinquencyStage::Current;
+    }
+    match (now - invoice.due_date) / SECONDS_PER_DAY {
+        0..=29 => DelinquencyStage::GracePeriod,
+        30..=59 => DelinquencyStage::Delinquent30,
+        _ => DelinquencyStage::Delinquent60,
+    }
+}
+
+/// Get the stage keepers last moved an invoice to. It is kept once the
+/// invoice is paid or defaulted, as a record of how late it ran.
+pub fn get_recorded_stage(env: &Env, invoice_id: &BytesN<32>) -> DelinquencyStage {
+    storage::get(env, &DataKey::DelinquencyStage(invoice_id.clone()))
+        .unwrap_or(DelinquencyStage::Current)
+}
+
+/// Move every funded invoice to the delinquency stage it has reached.
+/// Anyone may call this. Returns the ids of the invoices whose stage changed.
+pub fn update_delinquency_stages(env: &Env) -> Vec<BytesN<32>> {
+    let mut changed = Vec::new(env);
+    for invoice_id in InvoiceStorage::get_invoices_by_status(env, &InvoiceStatus::Funded).iter() {
+        let Some(invoice) = InvoiceStorage::get_invoice(env, &invoice_id) else {
+            continue;
+        };
+        let previous = get_recorded_stage(env, &invoice_id);
+        // An approved due-date extension can bring an invoice back to Current
+        let stage = current_stage(env, &invoice);
+        if stage == previous {
+            continue;
+        }
+        storage::set(env, &DataKey::DelinquencyStage(invoice_id.clone()), &stage);
+        emit_delinquency_stage_changed(env, &invoice, previous, stage);
+        changed.push_back(invoice_id);
+    }
+    changed
+}
@@ -0,0 +1,249 @@
+use crate::config::check_description_length;
+use crate::errors::QuickLendXError;
+use crate::events::{emit_arbitrator_set, emit_dispute_opened, emit_dispute_resolved};
+use crate::insurance::{rule_on_appeal, InsuranceClaim};
+use crate::invoice::InvoiceStorage;
+use crate::payments::{refund_escrow, release_escrow, split_escrow, EscrowStorage};
+use crate::storage::{self, DataKey};
+use crate::verification::{require_admin, BusinessVerificationStorage};
+use soroban_sdk::{contracttype, symbol_short, Address, BytesN, Env, String, Vec};
+
+/// How an arbitrator rules: on the escrowed funds of a disputed invoice, or on
+/// an appealed insurance claim
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DisputeOutcome {
+    Release,    // Funds go to the business
+    Refund,     // Funds go back to the investors
+    Split(u32), // The business receives this many bps, investors the rest
+    Upheld,     // An appealed insurance claim is approved after all
+    Dismissed,  // An appealed insurance claim stays denied
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DisputeStatus {
+    Open,                     // Escrow is frozen until the dispute is resolved
+    Resolved(DisputeOutcome), // Escrow was settled by the arbitrator's ruling
+}
+
+/// A dispute raised over an invoice's escrowed funds
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Dispute {
+    pub invoice_id: BytesN<32>,
+    pub opened_by: Address,
+    pub reason: String,
+    pub status: DisputeStatus,
+    pub opened_at: u64,
+    pub resolved_by: Option<Address>,
+    pub resolved_at: Option<u64>,
+}
+
+pub struct DisputeStorage;
+
+impl DisputeStorage {
+    /// Get the latest dispute raised over an invoice
+    pub fn get_dispute(env: &Env, invoice_id: &BytesN<32>) -> Option<Dispute> {
+        storage::get(env, &DataKey::Dispute(invoice_id.clone()))
+    }
+
+    fn set_dispute(env: &Env, dispute: &Dispute) {
+        storage::set(env, &DataKey::Dispute(dispute.invoice_id.clone()), dispute);
+    }
+
+    /// Check whether an invoice's escrow is frozen by an open dispute
+    pub fn is_open(env: &Env, invoice_id: &BytesN<32>) -> bool {
+        matches!(
+            Self::get_dispute(env, invoice_id),
+            Some(Dispute {
+                status: DisputeStatus::Open,
+                ..
+            })
+        )
+    }
+
+    /// Get the ids of invoices with an open dispute
+    pub fn get_open_disputes(env: &Env) -> Vec<BytesN<32>> {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("disp_open"))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    fn set_open_disputes(env: &Env, invoice_ids: &Vec<BytesN<32>>) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("disp_open"), invoice_ids);
+    }
+
+    /// Get the appeal against an insurance claim's denial, if one was filed
+    pub fn get_claim_appeal(env: &Env, claim_id: &BytesN<32>) -> Option<Dispute> {
+        storage::get(env, &DataKey::ClaimAppeal(claim_id.clone()))
+    }
+
+    /// Get the arbitrator who resolves disputes
+    pub fn get_arbitrator(env: &Env) -> Option<Address> {
+        env.storage().instance().get(&symbol_short!("arbiter"))
+    }
+}
+
+/// Appoint the arbitrator who resolves disputes (admin only)
+pub fn set_arbitrator(
+    env: &Env,
+    admin: &Address,
+    arbitrator: &Address,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    env.storage()
+        .instance()
+        .set(&symbol_short!("arbiter"), arbitrator);
+    emit_arbitrator_set(env, arbitrator, admin);
+    Ok(())
+}
+
+/// Open a dispute over an invoice whose funds are still escrowed, freezing
+/// release and refund until it is resolved. Only the business or one of the
+/// invoice's investors may open a dispute.
+pub fn open_dispute(
+    env: &Env,
+    caller: &Address,
+    invoice_id: &BytesN<32>,
+    reason: &String,
+) -> Result<(), QuickLendXError> {
+    caller.require_auth();
+    let invoice =
+        InvoiceStorage::get_invoice(env, invoice_id).ok_or(QuickLendXError::InvoiceNotFound)?;
+    if invoice.business != *caller && !invoice.is_investor(caller) {
+        return Err(QuickLendXError::Unauthorized);
+    }
+    if DisputeStorage::is_open(env, invoice_id) {
+        return Err(QuickLendXError::DisputeAlreadyOpen);
+    }
+    check_description_length(env, reason)?;
+    // Nothing to dispute once the escrowed funds have moved
+    EscrowStorage::get_held_funding_escrows(env, invoice_id)?;
+
+    let dispute = Dispute {
+        invoice_id: invoice_id.clone(),
+        opened_by: caller.clone(),
+        reason: reason.clone(),
+        status: DisputeStatus::Open,
+        opened_at: env.ledger().timestamp(),
+        resolved_by: None,
+        resolved_at: None,
+    };
+    DisputeStorage::set_dispute(env, &dispute);
+    let mut open = DisputeStorage::get_open_disputes(env);
+    open.push_back(invoice_id.clone());
+    DisputeStorage::set_open_disputes(env, &open);
+
+    emit_dispute_opened(env, &dispute);
+    Ok(())
+}
+
+/// Resolve an open dispute and settle every held funding escrow of the
+/// invoice according to the outcome (arbitrator or admin only)
+pub fn resolve_dispute(
+    env: &Env,
+    arbitrator: &Address,
+    invoice_id: &BytesN<32>,
+    outcome: &DisputeOutcome,
+) -> Result<(), QuickLendXError> {
+    require_arbitrator(env, arbitrator)?;
+    let mut dispute =
+        DisputeStorage::get_dispute(env, invoice_id).ok_or(QuickLendXError::DisputeNotFound)?;
+    if dispute.status != DisputeStatus::Open {
+        return Err(QuickLendXError::InvalidStatus);
+    }
+    match outcome {
+        DisputeOutcome::Split(business_bps) if *business_bps > 10_000 => {
+            return Err(QuickLendXError::InvalidAmount)
+        }
+        DisputeOutcome::Upheld | DisputeOutcome::Dismissed => {
+            return Err(QuickLendXError::OperationNotAllowed)
+        }
+        _ => {}
+    }
+
+    // Lift the freeze, then let the ruling drive the escrow
+    dispute.status = DisputeStatus::Resolved(outcome.clone());
+    dispute.resolved_by = Some(arbitrator.clone());
+    dispute.resolved_at = Some(env.ledger().timestamp());
+    DisputeStorage::set_dispute(env, &dispute);
+    let mut open = DisputeStorage::get_open_disputes(env);
+    if let Some(index) = open.first_index_of(invoice_id) {
+        open.remove(index);
+    }
+    DisputeStorage::set_open_disputes(env, &open);
+
+    for escrow in EscrowStorage::get_held_funding_escrows(env, invoice_id)?.iter() {
+        match outcome {
+            DisputeOutcome::Release => release_escrow(env, &escrow.escrow_id, arbitrator)?,
+            DisputeOutcome::Refund => refund_escrow(env, &escrow.escrow_id, arbitrator)?,
+            DisputeOutcome::Split(business_bps) => {
+                split_escrow(env, &escrow.escrow_id, *business_bps, arbitrator)?
+            }
+            DisputeOutcome::Upheld | DisputeOutcome::Dismissed => continue,
+        };
+    }
+
+    emit_dispute_resolved(env, &dispute, outcome, arbitrator);
+    Ok(())
+}
+
+/// Check the caller may rule on disputes: the arbitrator, or the admin
+fn require_arbitrator(env: &Env, caller: &Address) -> Result<(), QuickLendXError> {
+    caller.require_auth();
+    let is_arbitrator = DisputeStorage::get_arbitrator(env).as_ref() == Some(caller);
+    if !is_arbitrator && !BusinessVerificationStorage::is_admin(env, caller) {
+        return Err(QuickLendXError::Unauthorized);
+    }
+    Ok(())
+}
+
+/// Put the appeal against an insurance claim's denial before the arbitrator
+pub fn open_claim_appeal(env: &Env, claim: &InsuranceClaim, grounds: &String) {
+    let dispute = Dispute {
+        invoice_id: claim.invoice_id.clone(),
+        opened_by: claim.investor.clone(),
+        reason: grounds.clone(),
+        status: DisputeStatus::Open,
+        opened_at: env.ledger().timestamp(),
+        resolved_by: None,
+        resolved_at: None,
+    };
+    storage::set(env, &DataKey::ClaimAppeal(claim.claim_id.clone()), &dispute);
+    emit_dispute_opened(env, &dispute);
+}
+
+/// Rule on an appealed insurance claim (arbitrator or admin only). Upholding
+/// the appeal approves the claim for payout; dismissing it leaves the claim
+/// denied for good.
+pub fn resolve_claim_appeal(
+    env: &Env,
+    arbitrator: &Address,
+    claim_id: &BytesN<32>,
+    upheld: bool,
+) -> Result<(), QuickLendXError> {
+    require_arbitrator(env, arbitrator)?;
+    let mut dispute =
+        DisputeStorage::get_claim_appeal(env, claim_id).ok_or(QuickLendXError::DisputeNotFound)?;
+    if dispute.status != DisputeStatus::Open {
+        return Err(QuickLendXError::InvalidStatus);
+    }
+    let outcome = if upheld {
+        DisputeOutcome::Upheld
+    } else {
+        DisputeOutcome::Dismissed
+    };
+
+    dispute.status = DisputeStatus::Resolved(outcome.clone());
+    dispute.resolved_by = Some(arbitrator.clone());
+    dispute.resolved_at = Some(env.ledger().timestamp());
+    storage::set(env, &DataKey::ClaimAppeal(claim_id.clone()), &dispute);
+    rule_on_appeal(env, arbitrator, claim_id, upheld)?;
+
+    emit_dispute_resolved(env, &dispute, &outcome, arbitrator);
+    Ok(())
+}
@@ -16,12 +16,16 @@ pub enum QuickLendXError {
  InvoiceNotFunded = 1007,
  InvoiceAlreadyPaid = 1008,
  InvoiceAlreadyDefaulted = 1009,
+ InvoiceNotOverdue = 1010,
+ TooManyInvestors = 1011,
+ CreditLimitExceeded = 1012,
 
  // Authorization errors (1100-1199)
  Unauthorized = 1100,
  NotBusinessOwner = 1101,
  NotInvestor = 1102,
  NotAdmin = 1103,
+ AlreadyInitialized = 1104,
 
  // Validation errors (1200-1299)
  InvalidAmount = 1200,
@@ -29,6 +33,12 @@ pub enum QuickLendXError {
  InvalidCurrency = 1202,
  InvalidTimestamp = 1203,
  InvalidDescription = 1204,
+ DescriptionTooLong = 1205,
+ FeedbackTooLong = 1206,
+ KycDataTooLong = 1207,
+ RejectionReasonTooLong = 1208,
+ TooManyTags = 1209,
+ LastCurrency = 1210,
 
  // Storage errors (1300-1399)
  StorageError = 1300,
@@ -38,6 +48,10 @@ pub enum QuickLendXError {
  InsufficientFunds = 1400,
  InvalidStatus = 1401,
  OperationNotAllowed = 1402,
+ PriorityWindowActive = 1403,
+ SelfDealing = 1404,
+ Blacklisted = 1405,
+ LateChargeConflict = 1406,
 
  // Rating errors (1500-1599, from feat-invoice_rating_system)
  InvalidRating = 1500,
@@ -51,12 +65,101 @@ pub enum QuickLendXError {
  KYCAlreadyVerified = 1602,
  KYCNotFound = 1603,
  InvalidKYCStatus = 1604,
+ TemplateNotApproved = 1605,
+ KYCExpired = 1606,
+ TierLimitExceeded = 1607,
+ NotVerifier = 1608,
+ AlreadyApproved = 1609,
+ InvestorNotVerified = 1610,
 
  // Audit errors (1700-1799)
  AuditLogNotFound = 1700,
  AuditValidationFailed = 1701,
  AuditIntegrityError = 1702,
  AuditQueryError = 1703,
+
+ // Jurisdiction errors (1800-1899)
+ JurisdictionNotAllowed = 1800,
+ AprLimitExceeded = 1801,
+
+ // Insurance errors (1900-1999)
+ ClaimNotFound = 1900,
+ ClaimAlreadyExists = 1901,
+ AppealAlreadyFiled = 1902,
+
+ // Dispute errors (2000-2099)
+ DisputeNotFound = 2000,
+ DisputeAlreadyOpen = 2001,
+ EscrowFrozen = 2002,
+
+ // Auction errors (2100-2199)
+ AuctionNotFound = 2100,
+ AuctionInProgress = 2101,
+ AuctionPhaseClosed = 2102,
+ CommitmentMismatch = 2103,
+
+ // Terms errors (2200-2299)
+ TermsMismatch = 2200,
+
+ // Acceptance errors (2300-2399)
+ AcceptanceNotFound = 2300,
+ AcceptanceExpired = 2301,
+
+ // Limit request errors (2400-2499)
+ LimitRequestNotFound = 2400,
+ LimitRequestDecided = 2401,
+
+ // Due-date extension errors (2500-2599)
+ ExtensionAlreadyRequested = 2500,
+ ExtensionNotFound = 2501,
+ ExtensionVoteClosed = 2502,
+ AlreadyVoted = 2503,
+
+ // Stop-loss errors (2600-2699)
+ BiddingPaused = 2600,
+
+ // Attestation import errors (2700-2799)
+ IssuerNotTrusted = 2700,
+ AttestationAlreadyImported = 2701,
+ AttestationNotFound = 2702,
+
+ // Reverse factoring errors (2800-2899)
+ ProgramNotFound = 2800,
+ ProgramClosed = 2801,
+ InvoiceAlreadyConfirmed = 2802,
+ InvitationNotFound = 2803,
+ InvitationExpired = 2804,
+
+ // Default recovery errors (2900-2999)
+ InvoiceWrittenOff = 2900,
+
+ // Debt marketplace errors (3000-3099)
+ ClaimListingNotFound = 3000,
+ ClaimAlreadyListed = 3001,
+
+ // Rollover errors (3100-3199)
+ RolloverNotFound = 3100,
+
+ // Price oracle errors (3200-3299)
+ OraclePriceUnavailable = 3200,
+ OraclePriceStale = 3201,
+
+ // Withdrawal errors (3300-3399)
+ WithdrawalNotAllowed = 3300,
+ WithdrawalPending = 3301,
+ WithdrawalNotFound = 3302,
+ WithdrawalNotReady = 3303,
+
+ // Tranche errors (3400-3499)
+ TrancheRequired = 3400,
+ TrancheNotFound = 3401,
+ TrancheFull = 3402,
+
+ // Abandoned balance errors (3500-3599)
+ BalanceNotDormant = 3500,
+ DormancyNoticePending = 3501,
+ EscheatNotFound = 3502,
+ CustodianNotSet = 3503,
 }
 
 impl From<QuickLendXError> for Symbol {
@@ -72,20 +175,34 @@ impl From<QuickLendXError> for Symbol {
  QuickLendXError::InvoiceNotFunded => symbol_short!("INV_NF"),
  QuickLendXError::InvoiceAlreadyPaid => symbol_short!("INV_PD"),
  QuickLendXError::InvoiceAlreadyDefaulted => symbol_short!("INV_DF"),
+ QuickLendXError::InvoiceNotOverdue => symbol_short!("INV_OD"),
+ QuickLendXError::TooManyInvestors => symbol_short!("INV_MAX"),
+ QuickLendXError::CreditLimitExceeded => symbol_short!("CRED_LIM"),
  QuickLendXError::Unauthorized => symbol_short!("UNAUTH"),
  QuickLendXError::NotBusinessOwner => symbol_short!("NOT_OWN"),
  QuickLendXError::NotInvestor => symbol_short!("NOT_INV"),
  QuickLendXError::NotAdmin => symbol_short!("NOT_ADM"),
+ QuickLendXError::AlreadyInitialized => symbol_short!("ALR_INI"),
  QuickLendXError::InvalidAmount => symbol_short!("INV_AMT"),
  QuickLendXError::InvalidAddress => symbol_short!("INV_ADR"),
  QuickLendXError::InvalidCurrency => symbol_short!("INV_CR"),
  QuickLendXError::InvalidTimestamp => symbol_short!("INV_TM"),
  QuickLendXError::InvalidDescription => symbol_short!("INV_DS"),
+ QuickLendXError::DescriptionTooLong => symbol_short!("DESC_TL"),
+ QuickLendXError::FeedbackTooLong => symbol_short!("FDBK_TL"),
+ QuickLendXError::KycDataTooLong => symbol_short!("KYC_TL"),
+ QuickLendXError::RejectionReasonTooLong => symbol_short!("RSN_TL"),
+ QuickLendXError::TooManyTags => symbol_short!("TAGS_TL"),
+ QuickLendXError::LastCurrency => symbol_short!("LAST_CR"),
  QuickLendXError::StorageError => symbol_short!("STORE"),
  QuickLendXError::StorageKeyNotFound => symbol_short!("KEY_NF"),
  QuickLendXError::InsufficientFunds => symbol_short!("INSUF"),
  QuickLendXError::InvalidStatus => symbol_short!("INV_ST"),
  QuickLendXError::OperationNotAllowed => symbol_short!("OP_NA"),
+ QuickLendXError::PriorityWindowActive => symbol_short!("PRI_WIN"),
+ QuickLendXError::SelfDealing => symbol_short!("SELF_DL"),
+ QuickLendXError::Blacklisted => symbol_short!("BLKLST"),
+ QuickLendXError::LateChargeConflict => symbol_short!("LATE_CF"),
  QuickLendXError::InvalidRating => symbol_short!("INV_RT"),
  QuickLendXError::NotFunded => symbol_short!("NOT_FD"),
  QuickLendXError::AlreadyRated => symbol_short!("ALR_RT"),
@@ -95,11 +212,64 @@ impl From<QuickLendXError> for Symbol {
  QuickLendXError::KYCAlreadyVerified => symbol_short!("KYC_VF"),
  QuickLendXError::KYCNotFound => symbol_short!("KYC_NF"),
  QuickLendXError::InvalidKYCStatus => symbol_short!("KYC_IS"),
+ QuickLendXError::TemplateNotApproved => symbol_short!("TPL_NA"),
+ QuickLendXError::KYCExpired => symbol_short!("KYC_EXP"),
+ QuickLendXError::TierLimitExceeded => symbol_short!("TIER_LIM"),
+ QuickLendXError::NotVerifier => symbol_short!("NOT_VRF"),
+ QuickLendXError::AlreadyApproved => symbol_short!("ALR_APPR"),
+ QuickLendXError::InvestorNotVerified => symbol_short!("INVS_NV"),
  // Add to Symbol conversion
  QuickLendXError::AuditLogNotFound => symbol_short!("AUD_NF"),
  QuickLendXError::AuditValidationFailed => symbol_short!("AUD_VF"),
  QuickLendXError::AuditIntegrityError => symbol_short!("AUD_IE"),
  QuickLendXError::AuditQueryError => symbol_short!("AUD_QE"),
+ QuickLendXError::JurisdictionNotAllowed => symbol_short!("JUR_NA"),
+ QuickLendXError::AprLimitExceeded => symbol_short!("APR_EX"),
+ QuickLendXError::ClaimNotFound => symbol_short!("CLM_NF"),
+ QuickLendXError::ClaimAlreadyExists => symbol_short!("CLM_EX"),
+ QuickLendXError::AppealAlreadyFiled => symbol_short!("APL_EX"),
+ QuickLendXError::DisputeNotFound => symbol_short!("DSP_NF"),
+ QuickLendXError::DisputeAlreadyOpen => symbol_short!("DSP_EX"),
+ QuickLendXError::EscrowFrozen => symbol_short!("ESC_FRZ"),
+ QuickLendXError::AuctionNotFound => symbol_short!("AUC_NF"),
+ QuickLendXError::AuctionInProgress => symbol_short!("AUC_OPEN"),
+ QuickLendXError::AuctionPhaseClosed => symbol_short!("AUC_PH"),
+ QuickLendXError::CommitmentMismatch => symbol_short!("CMT_MM"),
+ QuickLendXError::TermsMismatch => symbol_short!("TRM_MM"),
+ QuickLendXError::AcceptanceNotFound => symbol_short!("ACC_NF"),
+ QuickLendXError::AcceptanceExpired => symbol_short!("ACC_EXP"),
+ QuickLendXError::LimitRequestNotFound => symbol_short!("LIM_NF"),
+ QuickLendXError::LimitRequestDecided => symbol_short!("LIM_DEC"),
+ QuickLendXError::ExtensionAlreadyRequested => symbol_short!("EXT_REQ"),
+ QuickLendXError::ExtensionNotFound => symbol_short!("EXT_NF"),
+ QuickLendXError::ExtensionVoteClosed => symbol_short!("EXT_VCL"),
+ QuickLendXError::AlreadyVoted => symbol_short!("EXT_VTD"),
+ QuickLendXError::BiddingPaused => symbol_short!("BID_PAUS"),
+ QuickLendXError::IssuerNotTrusted => symbol_short!("ISS_NT"),
+ QuickLendXError::AttestationAlreadyImported => symbol_short!("ATT_EX"),
+ QuickLendXError::AttestationNotFound => symbol_short!("ATT_NF"),
+ QuickLendXError::ProgramNotFound => symbol_short!("PRG_NF"),
+ QuickLendXError::ProgramClosed => symbol_short!("PRG_CLS"),
+ QuickLendXError::InvoiceAlreadyConfirmed => symbol_short!("INV_CNF"),
+ QuickLendXError::InvitationNotFound => symbol_short!("INVT_NF"),
+ QuickLendXError::InvitationExpired => symbol_short!("INVT_EXP"),
+ QuickLendXError::InvoiceWrittenOff => symbol_short!("INV_WO"),
+ QuickLendXError::ClaimListingNotFound => symbol_short!("LST_NF"),
+ QuickLendXError::ClaimAlreadyListed => symbol_short!("LST_EX"),
+ QuickLendXError::RolloverNotFound => symbol_short!("ROL_NF"),
+ QuickLendXError::OraclePriceUnavailable => symbol_short!("ORC_NA"),
+ QuickLendXError::OraclePriceStale => symbol_short!("ORC_OLD"),
+ QuickLendXError::WithdrawalNotAllowed => symbol_short!("WD_DEST"),
+ QuickLendXError::WithdrawalPending => symbol_short!("WD_PEND"),
+ QuickLendXError::WithdrawalNotFound => symbol_short!("WD_NF"),
+ QuickLendXError::WithdrawalNotReady => symbol_short!("WD_WAIT"),
+ QuickLendXError::TrancheRequired => symbol_short!("TRN_REQ"),
+ QuickLendXError::TrancheNotFound => symbol_short!("TRN_NF"),
+ QuickLendXError::TrancheFull => symbol_short!("TRN_FULL"),
+ QuickLendXError::BalanceNotDormant => symbol_short!("DRM_ACT"),
+ QuickLendXError::DormancyNoticePending => symbol_short!("DRM_WAIT"),
+ QuickLendXError::EscheatNotFound => symbol_short!("ESCH_NF"),
+ QuickLendXError::CustodianNotSet => symbol_short!("CUST_NS"),
  }
  }
 }
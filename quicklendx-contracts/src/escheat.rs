@@ -0,0 +1,207 @@
+use crate::audit::{log_balance_escheated, log_escheat_claimed};
+use crate::balances::{credit_balance, transfer_tokens, BalanceStorage};
+use crate::compliance::check_not_blacklisted;
+use crate::config_history::record_config_change;
+use crate::errors::QuickLendXError;
+use crate::events::{
+    emit_balance_escheated, emit_dormancy_notice, emit_escheat_claimed, emit_escheat_custodian_set,
+};
+use crate::storage::{self, DataKey};
+use crate::verification::require_admin;
+use soroban_sdk::{contracttype, symbol_short, Address, Env};
+
+/// How long a balance must go untouched before it counts as abandoned
+pub const DORMANCY_PERIOD: u64 = 5 * 365 * 86_400;
+
+/// How long an owner has after a dormancy notice to touch the balance
+/// before it can be swept
+pub const DORMANCY_NOTICE_PERIOD: u64 = 90 * 86_400;
+
+/// Warning that a balance looks abandoned and will be swept to the custodian
+/// unless it moves before `sweepable_at`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DormancyNotice {
+    pub owner: Address,
+    pub currency: Address,
+    pub amount: i128,
+    pub last_activity: u64,
+    pub noticed_at: u64,
+    pub sweepable_at: u64,
+}
+
+/// A balance swept to the custodian, which its owner can still reclaim
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscheatedBalance {
+    pub owner: Address,
+    pub currency: Address,
+    pub amount: i128,
+    pub custodian: Address, // Holds the funds until the owner reclaims them
+    pub swept_at: u64,
+}
+
+pub struct EscheatStorage;
+
+impl EscheatStorage {
+    /// Get the account abandoned balances are swept to, if one is designated
+    pub fn get_custodian(env: &Env) -> Option<Address> {
+        env.storage().instance().get(&symbol_short!("custodian"))
+    }
+
+    /// Get the dormancy notice on an address's balance in a currency, if any
+    pub fn get_notice(env: &Env, owner: &Address, currency: &Address) -> Option<DormancyNotice> {
+        storage::get(
+            env,
+            &DataKey::DormancyNotice(owner.clone(), currency.clone()),
+        )
+    }
+
+    /// Get the address's swept balance in a currency awaiting reclaim, if any
+    pub fn get_escheated(
+        env: &Env,
+        owner: &Address,
+        currency: &Address,
+    ) -> Option<EscheatedBalance> {
+        storage::get(
+            env,
+            &DataKey::EscheatedBalance(owner.clone(), currency.clone()),
+        )
+    }
+}
+
+/// Designate the custodial account abandoned balances are swept to (admin
+/// only). Balances already swept stay with the custodian they went to.
+pub fn set_custodian(
+    env: &Env,
+    admin: &Address,
+    custodian: &Address,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    env.storage()
+        .instance()
+        .set(&symbol_short!("custodian"), custodian);
+    record_config_change(env, "escheat_custodian", custodian.clone(), admin);
+    emit_escheat_custodian_set(env, custodian, admin);
+    Ok(())
+}
+
+/// Warn that an address's balance in a currency has not moved for the
+/// dormancy period (admin only). Any movement of the balance withdraws the
+/// notice; otherwise the balance can be swept once the notice period ends.
+pub fn issue_dormancy_notice(
+    env: &Env,
+    admin: &Address,
+    owner: &Address,
+    currency: &Address,
+) -> Result<DormancyNotice, QuickLendXError> {
+    require_admin(env, admin)?;
+    if let Some(notice) = EscheatStorage::get_notice(env, owner, currency) {
+        return Ok(notice);
+    }
+    let amount = BalanceStorage::get_balance(env, owner, currency);
+    if amount <= 0 {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    let now = env.ledger().timestamp();
+    // Balances whose activity was never recorded cannot be shown abandoned
+    let last_activity = BalanceStorage::get_last_activity(env, owner, currency)
+        .filter(|last| now.saturating_sub(*last) >= DORMANCY_PERIOD)
+        .ok_or(QuickLendXError::BalanceNotDormant)?;
+
+    let notice = DormancyNotice {
+        owner: owner.clone(),
+        currency: currency.clone(),
+        amount,
+        last_activity,
+        noticed_at: now,
+        sweepable_at: now + DORMANCY_NOTICE_PERIOD,
+    };
+    storage::set(
+        env,
+        &DataKey::DormancyNotice(owner.clone(), currency.clone()),
+        &notice,
+    );
+    emit_dormancy_notice(env, &notice);
+    Ok(notice)
+}
+
+/// Sweep a balance whose dormancy notice has run out to the custodian
+/// (admin only). The owner keeps a claim on the swept funds.
+pub fn sweep_dormant_balance(
+    env: &Env,
+    admin: &Address,
+    owner: &Address,
+    currency: &Address,
+) -> Result<EscheatedBalance, QuickLendXError> {
+    require_admin(env, admin)?;
+    let custodian = EscheatStorage::get_custodian(env).ok_or(QuickLendXError::CustodianNotSet)?;
+    let notice = EscheatStorage::get_notice(env, owner, currency)
+        .ok_or(QuickLendXError::BalanceNotDormant)?;
+    if env.ledger().timestamp() < notice.sweepable_at {
+        return Err(QuickLendXError::DormancyNoticePending);
+    }
+    let mut escheated = match EscheatStorage::get_escheated(env, owner, currency) {
+        // Earlier sweeps are reclaimed from the custodian that holds them
+        Some(escheated) if escheated.custodian != custodian => {
+            return Err(QuickLendXError::OperationNotAllowed)
+        }
+        Some(escheated) => escheated,
+        None => EscheatedBalance {
+            owner: owner.clone(),
+            currency: currency.clone(),
+            amount: 0,
+            custodian: custodian.clone(),
+            swept_at: 0,
+        },
+    };
+
+    let amount = BalanceStorage::get_balance(env, owner, currency);
+    BalanceStorage::set_balance(env, owner, currency, 0);
+    transfer_tokens(
+        env,
+        currency,
+        &env.current_contract_address(),
+        &custodian,
+        amount,
+    )?;
+    escheated.amount += amount;
+    escheated.swept_at = env.ledger().timestamp();
+    storage::set(
+        env,
+        &DataKey::EscheatedBalance(owner.clone(), currency.clone()),
+        &escheated,
+    );
+    log_balance_escheated(env, admin.clone(), owner, &custodian, amount);
+    emit_balance_escheated(env, &escheated, amount);
+    Ok(escheated)
+}
+
+/// Reclaim a swept balance (owner only, released by the custodian holding
+/// it). The funds return to the owner's balance. Returns the new balance.
+pub fn claim_escheated_balance(
+    env: &Env,
+    owner: &Address,
+    currency: &Address,
+) -> Result<i128, QuickLendXError> {
+    owner.require_auth();
+    check_not_blacklisted(env, owner)?;
+    let escheated = EscheatStorage::get_escheated(env, owner, currency)
+        .ok_or(QuickLendXError::EscheatNotFound)?;
+    escheated.custodian.require_auth();
+    transfer_tokens(
+        env,
+        currency,
+        &escheated.custodian,
+        &env.current_contract_address(),
+        escheated.amount,
+    )?;
+    storage::remove(
+        env,
+        &DataKey::EscheatedBalance(owner.clone(), currency.clone()),
+    );
+    credit_balance(env, owner, currency, escheated.amount);
+    log_escheat_claimed(env, owner.clone(), &escheated.custodian, escheated.amount);
+    emit_escheat_claimed(env, &escheated);
+    Ok(BalanceStorage::get_balance(env, owner, currency))
+}
@@ -1,7 +1,38 @@
-use crate::invoice::Invoice;
+use crate::acceptance::PendingAcceptance;
+use crate::admin_log::AdminAction;
+use crate::archive::ArchivedInvoice;
+use crate::attestation::{FinancialAttestation, ImportedAttestation};
+use crate::auction::Auction;
+use crate::auto_accept::AutoAcceptRule;
+use crate::auto_verify::AutoVerifyRule;
+use crate::balances::{PendingWithdrawal, WithdrawalPolicy};
+use crate::bid::Bid;
+use crate::compliance::{BlacklistEntry, WashFlag};
+use crate::config::{PlatformFeeConfig, SizeLimits};
+use crate::debt_market::ClaimListing;
+use crate::defaults::DefaultRecovery;
+use crate::delinquency::DelinquencyStage;
+use crate::disputes::{Dispute, DisputeOutcome};
+use crate::escheat::{DormancyNotice, EscheatedBalance};
+use crate::invoice::{DueDateExtension, Invoice, InvoiceDocument, InvoiceStatus};
+use crate::investment::{FundingContribution, Investment};
 use crate::payments::{Escrow, EscrowStatus};
-use crate::audit::{AuditLogEntry, AuditOperation};
-use soroban_sdk::{symbol_short, Address, BytesN, Env};
+use crate::audit::AuditLogEntry;
+use crate::jurisdiction::JurisdictionRule;
+use crate::limits::LimitRequest;
+use crate::observers::LifecycleEvent;
+use crate::penalty::PenaltySchedule;
+use crate::oracle::OracleConfig;
+use crate::profile::BusinessProfile;
+use crate::reverse_factoring::{DebtorProgram, ProgramCreditLine, SupplierInvitation};
+use crate::rollover::RolloverProposal;
+use crate::settlement::SettlementProgress;
+use crate::stop_loss::{StopLossRule, StopLossTrigger};
+use crate::templates::{InvoiceTemplate, TemplateUsage};
+use crate::terms::InvoiceTerms;
+use crate::tranche::TrancheStructure;
+use crate::insurance::{InsuranceClaim, InsuranceCoverage, InsurancePoolConfig, PremiumRates};
+use soroban_sdk::{symbol_short, Address, BytesN, Env, IntoVal, String, Symbol, Val, Vec};
 
 pub fn emit_invoice_uploaded(env: &Env, invoice: &Invoice) {
     env.events().publish(
@@ -16,10 +47,10 @@ pub fn emit_invoice_uploaded(env: &Env, invoice: &Invoice) {
     );
 }
 
-pub fn emit_invoice_verified(env: &Env, invoice: &Invoice) {
+pub fn emit_invoice_verified(env: &Env, invoice: &Invoice, actor: &Address) {
     env.events().publish(
         (symbol_short!("inv_ver"),),
-        (invoice.id.clone(), invoice.business.clone()),
+        (invoice.id.clone(), invoice.business.clone(), actor.clone()),
     );
 }
 
@@ -28,6 +59,8 @@ pub fn emit_invoice_settled(
     invoice: &crate::invoice::Invoice,
     investor_return: i128,
     platform_fee: i128,
+    actor: &Address,
+    terms_hash: &Option<BytesN<32>>,
 ) {
     env.events().publish(
         (symbol_short!("inv_set"),),
@@ -36,14 +69,247 @@ pub fn emit_invoice_settled(
             invoice.business.clone(),
             investor_return,
             platform_fee,
+            actor.clone(),
+            terms_hash.clone(),
         ),
     );
 }
 
-pub fn emit_invoice_defaulted(env: &Env, invoice: &crate::invoice::Invoice) {
+/// Emit event when a business pays an installment on a funded invoice
+pub fn emit_partial_payment(
+    env: &Env,
+    invoice_id: &BytesN<32>,
+    amount: i128,
+    total_paid: i128,
+    outstanding: i128,
+) {
+    env.events().publish(
+        (symbol_short!("inv_pp"),),
+        (invoice_id.clone(), amount, total_paid, outstanding),
+    );
+}
+
+pub fn emit_invoice_defaulted(env: &Env, invoice: &crate::invoice::Invoice, actor: &Address) {
     env.events().publish(
         (symbol_short!("inv_def"),),
-        (invoice.id.clone(), invoice.business.clone()),
+        (invoice.id.clone(), invoice.business.clone(), actor.clone()),
+    );
+}
+
+/// Emit event when funds recovered on a defaulted invoice are paid out
+pub fn emit_default_recovery(
+    env: &Env,
+    recovery: &DefaultRecovery,
+    amount: i128,
+    payer: &Address,
+) {
+    env.events().publish(
+        (symbol_short!("def_rec"),),
+        (
+            recovery.invoice_id.clone(),
+            amount,
+            recovery.recovered,
+            payer.clone(),
+            recovery.last_recovered_at,
+        ),
+    );
+}
+
+/// Emit event when a defaulted invoice's unrecovered balance is written off
+pub fn emit_default_written_off(
+    env: &Env,
+    invoice: &Invoice,
+    recovery: &DefaultRecovery,
+    admin: &Address,
+) {
+    env.events().publish(
+        (symbol_short!("def_wo"),),
+        (
+            invoice.id.clone(),
+            (invoice.funded_amount - recovery.recovered).max(0), // Written off
+            recovery.recovered,
+            admin.clone(),
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when a defaulted investment's claim is listed for sale
+pub fn emit_claim_listed(env: &Env, listing: &ClaimListing) {
+    env.events().publish(
+        (symbol_short!("clm_list"),),
+        (
+            listing.investment_id.clone(),
+            listing.invoice_id.clone(),
+            listing.seller.clone(),
+            listing.price,
+            listing.listed_at,
+        ),
+    );
+}
+
+/// Emit event when a seller takes its claim off the market
+pub fn emit_claim_listing_cancelled(env: &Env, listing: &ClaimListing) {
+    env.events().publish(
+        (symbol_short!("clm_unlst"),),
+        (
+            listing.investment_id.clone(),
+            listing.seller.clone(),
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when a listed claim is bought, passing its recovery rights on
+pub fn emit_claim_sold(env: &Env, listing: &ClaimListing, buyer: &Address) {
+    env.events().publish(
+        (symbol_short!("clm_sold"),),
+        (
+            listing.investment_id.clone(),
+            listing.seller.clone(),
+            buyer.clone(),
+            listing.price,
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when a business proposes rolling a funded invoice over
+pub fn emit_rollover_proposed(env: &Env, proposal: &RolloverProposal) {
+    env.events().publish(
+        (symbol_short!("rol_prop"),),
+        (
+            proposal.invoice_id.clone(),
+            proposal.new_due_date,
+            proposal.new_amount,
+            proposal.proposed_at,
+        ),
+    );
+}
+
+/// Emit event when an investor approves a proposed rollover
+pub fn emit_rollover_approved(env: &Env, invoice_id: &BytesN<32>, investor: &Address) {
+    env.events().publish(
+        (symbol_short!("rol_appr"),),
+        (invoice_id.clone(), investor.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when an invoice is rolled over into a new one
+pub fn emit_invoice_rolled_over(env: &Env, old: &Invoice, new: &Invoice) {
+    env.events().publish(
+        (symbol_short!("inv_roll"),),
+        (
+            old.id.clone(),
+            new.id.clone(),
+            new.amount,
+            new.due_date,
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when a business cancels an unfunded invoice
+/// Emit event when a business amends a pending invoice
+pub fn emit_invoice_amended(env: &Env, old: &Invoice, new: &Invoice) {
+    env.events().publish(
+        (symbol_short!("inv_amd"),),
+        (
+            new.id.clone(),
+            new.business.clone(),
+            old.amount,
+            new.amount,
+            old.due_date,
+            new.due_date,
+        ),
+    );
+}
+
+pub fn emit_invoice_cancelled(env: &Env, invoice: &Invoice, rejected_bids: u32) {
+    env.events().publish(
+        (symbol_short!("inv_can"),),
+        (
+            invoice.id.clone(),
+            invoice.business.clone(),
+            rejected_bids,
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when the business anchors a supplementary document to an invoice
+pub fn emit_invoice_document_added(
+    env: &Env,
+    invoice_id: &BytesN<32>,
+    document: &InvoiceDocument,
+) {
+    env.events().publish(
+        (symbol_short!("inv_doc"),),
+        (
+            invoice_id.clone(),
+            document.hash.clone(),
+            document.name.clone(),
+            document.added_at,
+        ),
+    );
+}
+
+/// Emit event when the business sets an invoice's category and tags
+pub fn emit_invoice_metadata_set(env: &Env, invoice: &Invoice) {
+    env.events().publish(
+        (symbol_short!("inv_meta"),),
+        (
+            invoice.id.clone(),
+            invoice.category.clone(),
+            invoice.tags.clone(),
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when the business sets or clears an invoice's funding deadline
+pub fn emit_funding_deadline_set(
+    env: &Env,
+    invoice_id: &BytesN<32>,
+    deadline: Option<u64>,
+    business: &Address,
+) {
+    env.events().publish(
+        (symbol_short!("dl_set"),),
+        (invoice_id.clone(), deadline, business.clone()),
+    );
+}
+
+/// Emit event when an invoice expires unfunded after its funding deadline
+pub fn emit_invoice_expired(env: &Env, invoice: &Invoice, rejected_bids: u32) {
+    env.events().publish(
+        (symbol_short!("inv_exp"),),
+        (
+            invoice.id.clone(),
+            invoice.business.clone(),
+            rejected_bids,
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit a single event once an invoice is fully funded, listing every
+/// investor's contribution to the round
+pub fn emit_funding_completed(
+    env: &Env,
+    invoice: &Invoice,
+    contributions: &Vec<FundingContribution>,
+) {
+    env.events().publish(
+        (symbol_short!("fund_cmp"),),
+        (
+            invoice.id.clone(),
+            invoice.business.clone(),
+            invoice.currency.clone(),
+            invoice.funded_amount,
+            contributions.clone(),
+            env.ledger().timestamp(),
+        ),
     );
 }
 
@@ -68,6 +334,7 @@ pub fn emit_escrow_released(
     invoice_id: &BytesN<32>,
     business: &Address,
     amount: i128,
+    actor: &Address,
 ) {
     env.events().publish(
         (symbol_short!("esc_rel"),),
@@ -76,6 +343,7 @@ pub fn emit_escrow_released(
             invoice_id.clone(),
             business.clone(),
             amount,
+            actor.clone(),
         ),
     );
 }
@@ -87,6 +355,7 @@ pub fn emit_escrow_refunded(
     invoice_id: &BytesN<32>,
     investor: &Address,
     amount: i128,
+    actor: &Address,
 ) {
     env.events().publish(
         (symbol_short!("esc_ref"),),
@@ -95,6 +364,7 @@ pub fn emit_escrow_refunded(
             invoice_id.clone(),
             investor.clone(),
             amount,
+            actor.clone(),
         ),
     );
 }
@@ -112,19 +382,107 @@ pub fn emit_escrow_status_changed(
     );
 }
 
+/// Emit event when a currency is added to the whitelist
+pub fn emit_currency_added(env: &Env, currency: &Address, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("cur_add"),),
+        (currency.clone(), admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when a currency is removed from the whitelist
+pub fn emit_currency_removed(env: &Env, currency: &Address, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("cur_rem"),),
+        (currency.clone(), admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when an address is assigned a jurisdiction
+pub fn emit_jurisdiction_assigned(env: &Env, address: &Address, code: &Symbol) {
+    env.events().publish(
+        (symbol_short!("jur_asg"),),
+        (address.clone(), code.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when a jurisdiction rule is created or replaced
+pub fn emit_jurisdiction_rule_set(env: &Env, rule: &JurisdictionRule) {
+    env.events().publish(
+        (symbol_short!("jur_set"),),
+        (rule.code.clone(), rule.max_apr_bps, env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when a jurisdiction rule is removed
+pub fn emit_jurisdiction_rule_removed(env: &Env, code: &Symbol) {
+    env.events().publish(
+        (symbol_short!("jur_rem"),),
+        (code.clone(), env.ledger().timestamp()),
+    );
+}
+
 /// Emit event when backup is created
-pub fn emit_backup_created(env: &Env, backup_id: &BytesN<32>, invoice_count: u32) {
+pub fn emit_backup_created(
+    env: &Env,
+    backup_id: &BytesN<32>,
+    invoice_count: u32,
+    actor: &Address,
+) {
     env.events().publish(
         (symbol_short!("bkup_crt"),),
-        (backup_id.clone(), invoice_count, env.ledger().timestamp()),
+        (
+            backup_id.clone(),
+            invoice_count,
+            actor.clone(),
+            env.ledger().timestamp(),
+        ),
     );
 }
 
 /// Emit event when backup is restored
-pub fn emit_backup_restored(env: &Env, backup_id: &BytesN<32>, invoice_count: u32) {
+pub fn emit_backup_restored(
+    env: &Env,
+    backup_id: &BytesN<32>,
+    invoice_count: u32,
+    actor: &Address,
+) {
     env.events().publish(
         (symbol_short!("bkup_rstr"),),
-        (backup_id.clone(), invoice_count, env.ledger().timestamp()),
+        (
+            backup_id.clone(),
+            invoice_count,
+            actor.clone(),
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when a chunk of a backup is staged for restoring
+pub fn emit_backup_chunk_restored(
+    env: &Env,
+    backup_id: &BytesN<32>,
+    chunk_index: u32,
+    total_chunks: u32,
+    actor: &Address,
+) {
+    env.events().publish(
+        (symbol_short!("bkup_chk"),),
+        (
+            backup_id.clone(),
+            chunk_index,
+            total_chunks,
+            actor.clone(),
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when a staged restore is dropped
+pub fn emit_backup_restore_discarded(env: &Env, backup_id: &BytesN<32>, actor: &Address) {
+    env.events().publish(
+        (symbol_short!("bkup_dsc"),),
+        (backup_id.clone(), actor.clone(), env.ledger().timestamp()),
     );
 }
 
@@ -137,10 +495,10 @@ pub fn emit_backup_validated(env: &Env, backup_id: &BytesN<32>, success: bool) {
 }
 
 /// Emit event when backup is archived
-pub fn emit_backup_archived(env: &Env, backup_id: &BytesN<32>) {
+pub fn emit_backup_archived(env: &Env, backup_id: &BytesN<32>, actor: &Address) {
     env.events().publish(
         (symbol_short!("bkup_ar"),),
-        (backup_id.clone(), env.ledger().timestamp()),
+        (backup_id.clone(), actor.clone(), env.ledger().timestamp()),
     );
 }
 
@@ -174,3 +532,1528 @@ pub fn emit_audit_query(env: &Env, query_type: String, result_count: u32) {
         (query_type, result_count, env.ledger().timestamp()),
     );
 }
+
+/// Emit event when the late payment penalty schedule is replaced
+pub fn emit_penalty_schedule_set(env: &Env, schedule: &PenaltySchedule, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("pen_set"),),
+        (
+            schedule.grace_period,
+            schedule.tiers.len(),
+            admin.clone(),
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when the flat late fee changes
+pub fn emit_late_fee_set(env: &Env, bps_per_day: u32, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("late_set"),),
+        (bps_per_day, admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when the first admin is set
+pub fn emit_admin_initialized(env: &Env, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("adm_init"),),
+        (admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when the admin nominates a successor
+pub fn emit_admin_transfer_started(env: &Env, admin: &Address, new_admin: &Address) {
+    env.events().publish(
+        (symbol_short!("adm_xfer"),),
+        (admin.clone(), new_admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when the nominated admin accepts the role
+pub fn emit_admin_transfer_accepted(
+    env: &Env,
+    previous_admin: Option<Address>,
+    new_admin: &Address,
+) {
+    env.events().publish(
+        (symbol_short!("adm_acc"),),
+        (previous_admin, new_admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when the text field size limits change
+pub fn emit_size_limits_set(env: &Env, limits: &SizeLimits, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("lim_set"),),
+        (
+            limits.description,
+            limits.feedback,
+            limits.kyc_data,
+            limits.rejection_reason,
+            admin.clone(),
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when the default grace period changes
+pub fn emit_default_grace_period_set(env: &Env, grace_period: u64, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("grc_set"),),
+        (grace_period, admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when the contract code is replaced
+pub fn emit_contract_upgraded(env: &Env, new_wasm_hash: &BytesN<32>, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("upgraded"),),
+        (new_wasm_hash.clone(), admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when the maturity ladder's bucket boundaries change
+pub fn emit_maturity_buckets_set(env: &Env, bounds: &Vec<u64>, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("mat_set"),),
+        (bounds.clone(), admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when stored data is migrated to a newer schema
+pub fn emit_storage_migrated(env: &Env, from_version: u32, to_version: u32, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("migrated"),),
+        (from_version, to_version, admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when the platform fee configuration changes
+pub fn emit_platform_fee_set(env: &Env, config: &PlatformFeeConfig, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("fee_set"),),
+        (
+            config.recipient.clone(),
+            config.fee_bps,
+            admin.clone(),
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when an unbacked Funded invoice is returned to Verified
+pub fn emit_invoice_reconciled(env: &Env, invoice_id: &BytesN<32>, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("inv_rec"),),
+        (invoice_id.clone(), admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when platform fees are added to the treasury
+pub fn emit_fees_collected(env: &Env, currency: &Address, amount: i128, balance: i128) {
+    env.events().publish(
+        (symbol_short!("fee_col"),),
+        (currency.clone(), amount, balance, env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when collected fees are withdrawn from the treasury
+pub fn emit_fees_withdrawn(
+    env: &Env,
+    currency: &Address,
+    amount: i128,
+    to: &Address,
+    actor: &Address,
+) {
+    env.events().publish(
+        (symbol_short!("fee_wd"),),
+        (
+            currency.clone(),
+            amount,
+            to.clone(),
+            actor.clone(),
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when the treasurer is appointed
+pub fn emit_treasurer_set(env: &Env, treasurer: &Address, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("trs_set"),),
+        (treasurer.clone(), admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when the insurance admin is appointed
+pub fn emit_insurance_admin_set(env: &Env, insurance_admin: &Address, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("ins_adm"),),
+        (insurance_admin.clone(), admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when capital is added to the insurance fund
+pub fn emit_insurance_fund_deposit(
+    env: &Env,
+    currency: &Address,
+    amount: i128,
+    depositor: &Address,
+) {
+    env.events().publish(
+        (symbol_short!("ins_dep"),),
+        (
+            currency.clone(),
+            amount,
+            depositor.clone(),
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when an investor files an insurance claim
+pub fn emit_claim_submitted(env: &Env, claim: &InsuranceClaim) {
+    env.events().publish(
+        (symbol_short!("clm_sub"),),
+        (
+            claim.claim_id.clone(),
+            claim.investment_id.clone(),
+            claim.investor.clone(),
+            claim.amount,
+        ),
+    );
+}
+
+/// Emit event when a claim enters review or is approved or denied
+pub fn emit_claim_reviewed(env: &Env, claim: &InsuranceClaim, reviewer: &Address) {
+    env.events().publish(
+        (symbol_short!("clm_rev"),),
+        (
+            claim.claim_id.clone(),
+            claim.status.clone(),
+            reviewer.clone(),
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when a denied claim is appealed
+pub fn emit_claim_appealed(env: &Env, claim: &InsuranceClaim) {
+    env.events().publish(
+        (symbol_short!("clm_apl"),),
+        (
+            claim.claim_id.clone(),
+            claim.investor.clone(),
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when an approved claim is paid out
+pub fn emit_claim_paid(env: &Env, claim: &InsuranceClaim, reviewer: &Address) {
+    env.events().publish(
+        (symbol_short!("clm_paid"),),
+        (
+            claim.claim_id.clone(),
+            claim.investor.clone(),
+            claim.amount,
+            reviewer.clone(),
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when the premium pricing rates change
+pub fn emit_premium_rates_set(env: &Env, rates: &PremiumRates, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("prem_set"),),
+        (
+            rates.base_bps,
+            rates.risk_bps,
+            rates.annual_tenor_bps,
+            admin.clone(),
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when the insurance pool's levy or recovery share changes
+pub fn emit_insurance_pool_config_set(env: &Env, config: &InsurancePoolConfig, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("pool_set"),),
+        (
+            config.settlement_levy_bps,
+            config.recovery_bps,
+            admin.clone(),
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when a defaulted investor recovers principal from the pool
+pub fn emit_insurance_recovered(
+    env: &Env,
+    invoice_id: &BytesN<32>,
+    investor: &Address,
+    amount: i128,
+) {
+    env.events().publish(
+        (symbol_short!("ins_rcv"),),
+        (
+            invoice_id.clone(),
+            investor.clone(),
+            amount,
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when an investor pays the premium for coverage
+pub fn emit_premium_charged(
+    env: &Env,
+    invoice_id: &BytesN<32>,
+    investor: &Address,
+    coverage: &InsuranceCoverage,
+) {
+    env.events().publish(
+        (symbol_short!("ins_prem"),),
+        (
+            invoice_id.clone(),
+            investor.clone(),
+            coverage.premium_bps,
+            coverage.premium,
+            coverage.coverage_amount,
+        ),
+    );
+}
+
+/// Emit event when the backstop providers' share of premiums changes
+pub fn emit_backstop_fee_set(env: &Env, fee_bps: u32, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("bs_fee"),),
+        (fee_bps, admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when a provider registers or updates a backstop commitment
+pub fn emit_backstop_registered(
+    env: &Env,
+    provider: &Address,
+    currency: &Address,
+    committed: i128,
+    priority: u32,
+) {
+    env.events().publish(
+        (symbol_short!("bs_reg"),),
+        (provider.clone(), currency.clone(), committed, priority),
+    );
+}
+
+/// Emit event when a backstop provider is drawn on to pay claims
+pub fn emit_backstop_drawn(env: &Env, provider: &Address, currency: &Address, amount: i128) {
+    env.events().publish(
+        (symbol_short!("bs_draw"),),
+        (
+            provider.clone(),
+            currency.clone(),
+            amount,
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when the admin appoints the dispute arbitrator
+pub fn emit_arbitrator_set(env: &Env, arbitrator: &Address, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("arb_set"),),
+        (arbitrator.clone(), admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when a dispute freezes an invoice's escrow
+pub fn emit_dispute_opened(env: &Env, dispute: &Dispute) {
+    env.events().publish(
+        (symbol_short!("dsp_open"),),
+        (
+            dispute.invoice_id.clone(),
+            dispute.opened_by.clone(),
+            dispute.reason.clone(),
+            dispute.opened_at,
+        ),
+    );
+}
+
+/// Emit event when the arbitrator rules on a dispute
+pub fn emit_dispute_resolved(
+    env: &Env,
+    dispute: &Dispute,
+    outcome: &DisputeOutcome,
+    arbitrator: &Address,
+) {
+    env.events().publish(
+        (symbol_short!("dsp_res"),),
+        (
+            dispute.invoice_id.clone(),
+            outcome.clone(),
+            arbitrator.clone(),
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when a contract is registered to observe invoice lifecycles
+pub fn emit_observer_registered(env: &Env, observer: &Address, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("obs_reg"),),
+        (observer.clone(), admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when an observer contract is unregistered
+pub fn emit_observer_removed(env: &Env, observer: &Address, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("obs_rem"),),
+        (observer.clone(), admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when an observer contract fails to handle a lifecycle event
+pub fn emit_observer_failed(
+    env: &Env,
+    observer: &Address,
+    invoice_id: &BytesN<32>,
+    event: LifecycleEvent,
+) {
+    env.events().publish(
+        (symbol_short!("obs_fail"),),
+        (
+            observer.clone(),
+            invoice_id.clone(),
+            event,
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when a business publishes a financial attestation
+pub fn emit_attestation_published(env: &Env, attestation: &FinancialAttestation) {
+    env.events().publish(
+        (symbol_short!("att_pub"),),
+        (
+            attestation.business.clone(),
+            attestation.revenue_band,
+            attestation.ar_aging_hash.clone(),
+            attestation.published_at,
+        ),
+    );
+}
+
+/// Emit event when the attestation staleness period changes
+pub fn emit_attestation_max_age_set(env: &Env, max_age: u64, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("att_age"),),
+        (max_age, admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when the admin trusts an attestation issuer or reweights it
+pub fn emit_attestation_issuer_registered(
+    env: &Env,
+    issuer: &Address,
+    weight_bps: u32,
+    admin: &Address,
+) {
+    env.events().publish(
+        (symbol_short!("att_iss"),),
+        (issuer.clone(), weight_bps, admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when the admin stops trusting an attestation issuer
+pub fn emit_attestation_issuer_revoked(env: &Env, issuer: &Address, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("att_isrv"),),
+        (issuer.clone(), admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when an external attestation is imported for a business
+pub fn emit_attestation_imported(env: &Env, claim: &ImportedAttestation) {
+    env.events().publish(
+        (symbol_short!("att_imp"),),
+        (
+            claim.business.clone(),
+            claim.issuer.clone(),
+            claim.claim_hash.clone(),
+            claim.settled_volume,
+            claim.imported_at,
+        ),
+    );
+}
+
+/// Emit event when an imported attestation is revoked
+pub fn emit_imported_attestation_revoked(
+    env: &Env,
+    business: &Address,
+    issuer: &Address,
+    claim_hash: &BytesN<32>,
+    revoked_by: &Address,
+) {
+    env.events().publish(
+        (symbol_short!("att_rev"),),
+        (
+            business.clone(),
+            issuer.clone(),
+            claim_hash.clone(),
+            revoked_by.clone(),
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when a debtor opens or reprices its supply-chain finance program
+pub fn emit_debtor_program_opened(env: &Env, program: &DebtorProgram) {
+    env.events().publish(
+        (symbol_short!("rf_open"),),
+        (
+            program.debtor.clone(),
+            program.currency.clone(),
+            program.max_apr_bps,
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when a debtor closes its program to new invoices
+pub fn emit_debtor_program_closed(env: &Env, debtor: &Address) {
+    env.events().publish(
+        (symbol_short!("rf_close"),),
+        (debtor.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when a debtor confirms a supplier's invoice under its program
+pub fn emit_supplier_invoice_confirmed(
+    env: &Env,
+    debtor: &Address,
+    invoice_id: &BytesN<32>,
+    supplier: &Address,
+) {
+    env.events().publish(
+        (symbol_short!("rf_conf"),),
+        (
+            debtor.clone(),
+            invoice_id.clone(),
+            supplier.clone(),
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when a debtor invites a supplier to its program
+pub fn emit_supplier_invited(env: &Env, invitation: &SupplierInvitation) {
+    env.events().publish(
+        (symbol_short!("rf_invite"),),
+        (
+            invitation.debtor.clone(),
+            invitation.supplier.clone(),
+            invitation.invited_at,
+            invitation.expires_at,
+        ),
+    );
+}
+
+/// Emit event when a supplier accepts an invitation and joins a program
+pub fn emit_supplier_invitation_accepted(env: &Env, invitation: &SupplierInvitation) {
+    env.events().publish(
+        (symbol_short!("rf_join"),),
+        (
+            invitation.debtor.clone(),
+            invitation.supplier.clone(),
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when a debtor guarantees or resizes its program's credit line
+pub fn emit_program_credit_line_set(env: &Env, line: &ProgramCreditLine) {
+    env.events().publish(
+        (symbol_short!("rf_line"),),
+        (line.debtor.clone(), line.limit, line.drawn, line.updated_at),
+    );
+}
+
+/// Emit event when a program debtor settles a supplier's invoice
+pub fn emit_program_invoice_settled(
+    env: &Env,
+    invoice_id: &BytesN<32>,
+    debtor: &Address,
+    payment_amount: i128,
+    supplier_residual: i128,
+) {
+    env.events().publish(
+        (symbol_short!("rf_settle"),),
+        (invoice_id.clone(), debtor.clone(), payment_amount, supplier_residual),
+    );
+}
+
+/// Emit event when the admin sets the platform fee on program invoices
+pub fn emit_reverse_factoring_fee_set(env: &Env, fee_bps: u32, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("rf_fee"),),
+        (fee_bps, admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when an invoice is listed as a sealed-bid auction
+pub fn emit_auction_started(env: &Env, auction: &Auction) {
+    env.events().publish(
+        (symbol_short!("auc_start"),),
+        (
+            auction.invoice_id.clone(),
+            auction.business.clone(),
+            auction.commit_ends,
+            auction.reveal_ends,
+        ),
+    );
+}
+
+/// Emit event when an investor commits to a sealed bid
+pub fn emit_bid_committed(env: &Env, invoice_id: &BytesN<32>, investor: &Address) {
+    env.events().publish(
+        (symbol_short!("bid_cmt"),),
+        (
+            invoice_id.clone(),
+            investor.clone(),
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when a sealed bid is revealed
+pub fn emit_bid_revealed(env: &Env, bid: &Bid) {
+    env.events().publish(
+        (symbol_short!("bid_rev"),),
+        (
+            bid.invoice_id.clone(),
+            bid.bid_id.clone(),
+            bid.investor.clone(),
+            bid.bid_amount,
+            bid.expected_return,
+        ),
+    );
+}
+
+/// Emit event when a sealed-bid auction closes
+pub fn emit_auction_closed(env: &Env, auction: &Auction) {
+    env.events().publish(
+        (symbol_short!("auc_end"),),
+        (
+            auction.invoice_id.clone(),
+            auction.status.clone(),
+            auction.winning_bid.clone(),
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when a business changes its repeat-investor priority window
+pub fn emit_priority_window_set(env: &Env, business: &Address, window: u64) {
+    env.events().publish(
+        (symbol_short!("rofr_set"),),
+        (business.clone(), window, env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when the compliance officer is appointed
+pub fn emit_compliance_officer_set(env: &Env, officer: &Address, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("cmpl_set"),),
+        (officer.clone(), admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when an address is registered as a business's affiliate
+pub fn emit_affiliate_added(
+    env: &Env,
+    business: &Address,
+    affiliate: &Address,
+    officer: &Address,
+) {
+    env.events().publish(
+        (symbol_short!("affil_add"),),
+        (business.clone(), affiliate.clone(), officer.clone()),
+    );
+}
+
+/// Emit event when an address is no longer a business's affiliate
+pub fn emit_affiliate_removed(
+    env: &Env,
+    business: &Address,
+    affiliate: &Address,
+    officer: &Address,
+) {
+    env.events().publish(
+        (symbol_short!("affil_rem"),),
+        (business.clone(), affiliate.clone(), officer.clone()),
+    );
+}
+
+/// Emit event when an investor funds a business with a settlement it just
+/// received from that business
+pub fn emit_wash_flagged(env: &Env, flag: &WashFlag) {
+    env.events().publish(
+        (symbol_short!("wash_flg"),),
+        (
+            flag.business.clone(),
+            flag.investor.clone(),
+            flag.settled_invoice.clone(),
+            flag.funded_invoice.clone(),
+        ),
+    );
+}
+
+/// Emit event when the protocol-wide minimum bid size changes
+pub fn emit_default_min_ticket_set(env: &Env, amount: i128, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("min_tkt"),),
+        (amount, admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when a business sets or clears an invoice's minimum bid size
+pub fn emit_invoice_min_ticket_set(
+    env: &Env,
+    invoice_id: &BytesN<32>,
+    amount: Option<i128>,
+    business: &Address,
+) {
+    env.events().publish(
+        (symbol_short!("inv_mtk"),),
+        (
+            invoice_id.clone(),
+            amount,
+            business.clone(),
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when a business counters a bid with another expected return
+pub fn emit_bid_countered(env: &Env, bid: &Bid, new_return: i128, round: u32) {
+    env.events().publish(
+        (symbol_short!("bid_ctr"),),
+        (
+            bid.bid_id.clone(),
+            bid.invoice_id.clone(),
+            bid.expected_return,
+            new_return,
+            round,
+        ),
+    );
+}
+
+/// Emit event when an investor accepts a counter-offer
+pub fn emit_counter_accepted(env: &Env, bid: &Bid, round: u32) {
+    env.events().publish(
+        (symbol_short!("ctr_acc"),),
+        (
+            bid.bid_id.clone(),
+            bid.investor.clone(),
+            bid.expected_return,
+            round,
+        ),
+    );
+}
+
+/// Emit event when an investor declines a counter-offer
+pub fn emit_counter_declined(env: &Env, bid: &Bid, round: u32) {
+    env.events().publish(
+        (symbol_short!("ctr_dec"),),
+        (
+            bid.bid_id.clone(),
+            bid.investor.clone(),
+            bid.expected_return,
+            round,
+        ),
+    );
+}
+
+/// Emit event when a business rejects a bid on its invoice
+pub fn emit_bid_rejected(env: &Env, bid: &Bid, business: &Address) {
+    env.events().publish(
+        (symbol_short!("bid_rej"),),
+        (
+            bid.invoice_id.clone(),
+            bid.bid_id.clone(),
+            bid.investor.clone(),
+            business.clone(),
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when the cap on distinct investors per invoice changes
+pub fn emit_max_investors_set(env: &Env, max_investors: u32, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("max_inv"),),
+        (max_investors, admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when a batched settlement starts or makes more payouts
+pub fn emit_settlement_progress(env: &Env, progress: &SettlementProgress) {
+    env.events().publish(
+        (symbol_short!("stl_prog"),),
+        (
+            progress.plan.invoice_id.clone(),
+            progress.next_transfer,
+            progress.plan.transfers.len(),
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when a payee pulls its share of a settlement
+pub fn emit_settlement_claimed(env: &Env, invoice_id: &BytesN<32>, payee: &Address, amount: i128) {
+    env.events().publish(
+        (symbol_short!("stl_clm"),),
+        (
+            invoice_id.clone(),
+            payee.clone(),
+            amount,
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when a privileged action is logged
+pub fn emit_admin_action_logged(env: &Env, action: &AdminAction) {
+    env.events().publish(
+        (symbol_short!("adm_act"),),
+        (
+            action.id,
+            action.action.clone(),
+            action.actor.clone(),
+            action.params_hash.clone(),
+            action.timestamp,
+        ),
+    );
+}
+
+/// Emit event when the archive age changes
+pub fn emit_archive_age_set(env: &Env, age: u64, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("arch_age"),),
+        (age, admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when a closed invoice is archived
+pub fn emit_invoice_archived(env: &Env, record: &ArchivedInvoice) {
+    env.events().publish(
+        (symbol_short!("inv_arch"),),
+        (
+            record.id.clone(),
+            record.business.clone(),
+            record.status.clone(),
+            record.archived_at,
+        ),
+    );
+}
+
+/// Emit event when an archived invoice is brought back to the live lists
+pub fn emit_invoice_unarchived(env: &Env, invoice_id: &BytesN<32>, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("inv_unarc"),),
+        (invoice_id.clone(), admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when the business attaches the hash of an invoice's terms
+pub fn emit_invoice_terms_set(env: &Env, terms: &InvoiceTerms, business: &Address) {
+    env.events().publish(
+        (symbol_short!("inv_term"),),
+        (
+            terms.invoice_id.clone(),
+            terms.terms_hash.clone(),
+            business.clone(),
+            terms.set_at,
+        ),
+    );
+}
+
+/// Emit event when an investor signs for an invoice's terms at acceptance
+pub fn emit_invoice_terms_acknowledged(
+    env: &Env,
+    invoice_id: &BytesN<32>,
+    terms_hash: &BytesN<32>,
+    investor: &Address,
+) {
+    env.events().publish(
+        (symbol_short!("term_ack"),),
+        (
+            invoice_id.clone(),
+            terms_hash.clone(),
+            investor.clone(),
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when a business creates or updates its profile
+pub fn emit_business_profile_updated(env: &Env, profile: &BusinessProfile) {
+    env.events().publish(
+        (symbol_short!("bus_prof"),),
+        (
+            profile.business.clone(),
+            profile.name.clone(),
+            profile.country.clone(),
+            profile.updated_at,
+        ),
+    );
+}
+
+/// Emit event when the acceptance confirmation window changes
+pub fn emit_acceptance_window_set(env: &Env, window: u64, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("acc_win"),),
+        (window, admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when a business accepts a bid that its investor must confirm
+pub fn emit_acceptance_requested(env: &Env, pending: &PendingAcceptance, investor: &Address) {
+    env.events().publish(
+        (symbol_short!("acc_req"),),
+        (
+            pending.invoice_id.clone(),
+            pending.bid_id.clone(),
+            investor.clone(),
+            pending.expires_at,
+        ),
+    );
+}
+
+/// Emit event when an investor confirms the acceptance of its bid
+pub fn emit_acceptance_confirmed(env: &Env, pending: &PendingAcceptance, investor: &Address) {
+    env.events().publish(
+        (symbol_short!("acc_conf"),),
+        (
+            pending.invoice_id.clone(),
+            pending.bid_id.clone(),
+            investor.clone(),
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when a business accepts bids on an invoice in advance
+pub fn emit_auto_accept_set(env: &Env, rule: &AutoAcceptRule, business: &Address) {
+    env.events().publish(
+        (symbol_short!("auto_set"),),
+        (
+            rule.invoice_id.clone(),
+            business.clone(),
+            rule.min_amount,
+            rule.max_return_bps,
+            rule.set_at,
+        ),
+    );
+}
+
+/// Emit event when a business stops accepting bids on an invoice in advance
+pub fn emit_auto_accept_cleared(env: &Env, invoice_id: &BytesN<32>, business: &Address) {
+    env.events().publish(
+        (symbol_short!("auto_clr"),),
+        (invoice_id.clone(), business.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when a bid is accepted as it is placed
+pub fn emit_bid_auto_accepted(env: &Env, bid: &Bid) {
+    env.events().publish(
+        (symbol_short!("auto_acc"),),
+        (
+            bid.invoice_id.clone(),
+            bid.bid_id.clone(),
+            bid.investor.clone(),
+            bid.bid_amount,
+            bid.expected_return,
+        ),
+    );
+}
+
+/// Emit event when the admin sets or removes a business's credit limit
+pub fn emit_credit_limit_set(env: &Env, business: &Address, limit: i128, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("cred_lim"),),
+        (
+            business.clone(),
+            limit,
+            admin.clone(),
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when the admin approves an invoice template for a business
+pub fn emit_invoice_template_approved(env: &Env, template: &InvoiceTemplate) {
+    env.events().publish(
+        (symbol_short!("tpl_appr"),),
+        (
+            template.business.clone(),
+            template.template_id.clone(),
+            template.criteria.max_amount,
+            template.criteria.max_tenor,
+            template.approved_by.clone(),
+        ),
+    );
+}
+
+/// Emit event when the admin withdraws a business's invoice template
+pub fn emit_invoice_template_revoked(
+    env: &Env,
+    business: &Address,
+    template_id: &Symbol,
+    admin: &Address,
+) {
+    env.events().publish(
+        (symbol_short!("tpl_rev"),),
+        (
+            business.clone(),
+            template_id.clone(),
+            admin.clone(),
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emit event when an invoice is listed through a template without manual
+/// verification
+pub fn emit_invoice_template_used(env: &Env, template: &InvoiceTemplate, usage: &TemplateUsage) {
+    env.events().publish(
+        (symbol_short!("tpl_use"),),
+        (
+            template.business.clone(),
+            template.template_id.clone(),
+            usage.invoice_id.clone(),
+            usage.amount,
+            usage.used_at,
+        ),
+    );
+}
+
+/// Emit event when a business asks to go past one of its caps
+pub fn emit_limit_requested(env: &Env, request: &LimitRequest) {
+    env.events().publish(
+        (symbol_short!("lim_req"),),
+        (
+            request.id,
+            request.business.clone(),
+            request.kind,
+            request.requested_limit,
+            request.justification_hash.clone(),
+        ),
+    );
+}
+
+/// Emit event when the admin approves or denies a limit-increase request
+pub fn emit_limit_request_decided(env: &Env, request: &LimitRequest) {
+    env.events().publish(
+        (symbol_short!("lim_dec"),),
+        (
+            request.id,
+            request.business.clone(),
+            request.status,
+            request.decided_by.clone(),
+            request.decision_reason.clone(),
+        ),
+    );
+}
+
+/// Emit a reminder that a funded invoice is due within `window` seconds
+pub fn emit_due_reminder(env: &Env, invoice: &Invoice, window: u64) {
+    env.events().publish(
+        (symbol_short!("due_rem"),),
+        (
+            invoice.id.clone(),
+            invoice.business.clone(),
+            invoice.due_date,
+            window,
+        ),
+    );
+}
+
+/// Emit event when the admin replaces the auto-verification rules
+pub fn emit_auto_verify_rules_set(env: &Env, rules: &Vec<AutoVerifyRule>, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("avrf_set"),),
+        (rules.len(), admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when an invoice is verified on upload under an
+/// auto-verification rule
+pub fn emit_invoice_auto_verified(env: &Env, invoice: &Invoice, rule_index: u32) {
+    env.events().publish(
+        (symbol_short!("inv_auto"),),
+        (
+            invoice.id.clone(),
+            invoice.business.clone(),
+            invoice.amount,
+            rule_index,
+        ),
+    );
+}
+
+/// Emit event when a business asks to push back an invoice's due date
+pub fn emit_due_date_extension_requested(
+    env: &Env,
+    invoice_id: &BytesN<32>,
+    extension: &DueDateExtension,
+) {
+    env.events().publish(
+        (symbol_short!("ext_req"),),
+        (
+            invoice_id.clone(),
+            extension.original_due_date,
+            extension.new_due_date,
+            extension.evidence_hash.clone(),
+        ),
+    );
+}
+
+/// Emit event when a verifier approves or rejects a due-date extension
+pub fn emit_due_date_extension_decided(
+    env: &Env,
+    invoice_id: &BytesN<32>,
+    extension: &DueDateExtension,
+) {
+    env.events().publish(
+        (symbol_short!("ext_dec"),),
+        (
+            invoice_id.clone(),
+            extension.status,
+            extension.new_due_date,
+            extension.decided_by.clone(),
+        ),
+    );
+}
+
+/// Emit event when the admin changes the longest due-date extension
+pub fn emit_max_extension_days_set(env: &Env, days: u32, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("max_ext"),),
+        (days, admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when an investor applies for KYC
+pub fn emit_investor_kyc_submitted(env: &Env, investor: &Address) {
+    env.events().publish(
+        (symbol_short!("ikyc_sub"),),
+        (investor.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when a verifier verifies an investor
+pub fn emit_investor_verified(env: &Env, investor: &Address, accredited: bool, verifier: &Address) {
+    env.events().publish(
+        (symbol_short!("inv_vrf"),),
+        (investor.clone(), accredited, verifier.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when a verifier rejects an investor
+pub fn emit_investor_rejected(env: &Env, investor: &Address, verifier: &Address) {
+    env.events().publish(
+        (symbol_short!("inv_rej"),),
+        (investor.clone(), verifier.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when the admin turns the investor verification requirement
+/// for bidding on or off
+pub fn emit_investor_kyc_required_set(env: &Env, required: bool, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("inv_kyc"),),
+        (required, admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when an investor votes on a due-date extension, weighted by
+/// the amount it funded
+pub fn emit_due_date_extension_voted(
+    env: &Env,
+    invoice_id: &BytesN<32>,
+    investor: &Address,
+    approve: bool,
+    amount: i128,
+) {
+    env.events().publish(
+        (symbol_short!("ext_vote"),),
+        (invoice_id.clone(), investor.clone(), approve, amount),
+    );
+}
+
+/// Emit event when the admin changes how long investors have to vote on
+/// due-date extensions
+pub fn emit_extension_vote_window_set(env: &Env, window: u64, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("ext_vwin"),),
+        (window, admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when the admin blacklists an address
+pub fn emit_blacklist_added(env: &Env, entry: &BlacklistEntry) {
+    env.events().publish(
+        (symbol_short!("blk_add"),),
+        (entry.address.clone(), entry.reason.clone(), entry.added_by.clone(), entry.added_at),
+    );
+}
+
+/// Emit event when the admin takes an address off the blacklist
+pub fn emit_blacklist_removed(env: &Env, address: &Address, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("blk_rem"),),
+        (address.clone(), admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when a keeper moves a funded invoice to another delinquency
+/// stage
+pub fn emit_delinquency_stage_changed(
+    env: &Env,
+    invoice: &Invoice,
+    from: DelinquencyStage,
+    to: DelinquencyStage,
+) {
+    env.events().publish(
+        (symbol_short!("delinq"),),
+        (invoice.id.clone(), invoice.business.clone(), from, to, invoice.due_date),
+    );
+}
+
+/// Emit event when an investor replaces its stop-loss rules
+pub fn emit_stop_loss_rules_set(env: &Env, investor: &Address, rules: &Vec<StopLossRule>) {
+    env.events().publish(
+        (symbol_short!("stop_set"),),
+        (investor.clone(), rules.len(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when an investor's portfolio breaches a stop-loss rule
+pub fn emit_stop_loss_triggered(env: &Env, investor: &Address, trigger: &StopLossTrigger) {
+    env.events().publish(
+        (symbol_short!("stop_hit"),),
+        (
+            investor.clone(),
+            trigger.rule_index,
+            trigger.rule.action,
+            trigger.value,
+            trigger.invoice_id.clone(),
+        ),
+    );
+}
+
+/// Emit event when an investor paused by a stop-loss rule resumes bidding
+pub fn emit_bidding_resumed(env: &Env, investor: &Address) {
+    env.events().publish(
+        (symbol_short!("bid_rsm"),),
+        (investor.clone(), env.ledger().timestamp()),
+    );
+}
+
+// Schema v2 events. Unlike the events above, each has the topics
+// (domain, action, entity id) and data that starts with the schema version and
+// ledger time, so indexers can subscribe by domain and decode by version. The
+// earlier events are still published alongside them.
+
+/// Version of the data layout of the v2 events
+pub const EVENT_SCHEMA_VERSION: u32 = 2;
+
+fn publish_v2<D>(env: &Env, domain: Symbol, action: Symbol, entity_id: &BytesN<32>, data: D)
+where
+    D: IntoVal<Env, Val>,
+{
+    let data: Val = data.into_val(env);
+    env.events().publish(
+        (domain, action, entity_id.clone()),
+        (EVENT_SCHEMA_VERSION, env.ledger().timestamp(), data),
+    );
+}
+
+/// Emit event when an investor places a bid, sealed auction reveals included
+pub fn emit_bid_placed(env: &Env, bid: &Bid) {
+    publish_v2(
+        env,
+        symbol_short!("bid"),
+        symbol_short!("placed"),
+        &bid.bid_id,
+        (bid.invoice_id.clone(), bid.investor.clone(), bid.bid_amount, bid.expected_return),
+    );
+}
+
+/// Emit event when an investor withdraws its open bid
+pub fn emit_bid_withdrawn(env: &Env, bid: &Bid) {
+    publish_v2(
+        env,
+        symbol_short!("bid"),
+        symbol_short!("withdrawn"),
+        &bid.bid_id,
+        (bid.invoice_id.clone(), bid.investor.clone(), bid.bid_amount),
+    );
+}
+
+/// Emit event when a bid is accepted and funds its invoice
+pub fn emit_bid_accepted(env: &Env, bid: &Bid, business: &Address) {
+    publish_v2(
+        env,
+        symbol_short!("bid"),
+        symbol_short!("accepted"),
+        &bid.bid_id,
+        (bid.invoice_id.clone(), bid.investor.clone(), business.clone(), bid.bid_amount),
+    );
+}
+
+/// Emit event when an accepted bid becomes an investment
+pub fn emit_investment_created(env: &Env, investment: &Investment) {
+    publish_v2(
+        env,
+        symbol_short!("invest"),
+        symbol_short!("created"),
+        &investment.investment_id,
+        (investment.invoice_id.clone(), investment.investor.clone(), investment.amount),
+    );
+}
+
+/// Emit event when an investment is repaid as its invoice settles
+pub fn emit_investment_completed(env: &Env, investment: &Investment) {
+    publish_v2(
+        env,
+        symbol_short!("invest"),
+        symbol_short!("completed"),
+        &investment.investment_id,
+        (investment.invoice_id.clone(), investment.investor.clone(), investment.amount),
+    );
+}
+
+/// Emit event whenever an invoice changes status
+pub fn emit_invoice_status_changed(
+    env: &Env,
+    invoice_id: &BytesN<32>,
+    from: &InvoiceStatus,
+    to: &InvoiceStatus,
+    actor: &Address,
+) {
+    publish_v2(
+        env,
+        symbol_short!("invoice"),
+        symbol_short!("status"),
+        invoice_id,
+        (from.clone(), to.clone(), actor.clone()),
+    );
+}
+
+/// Emit event when an investor opts in to or out of reinvesting returns
+pub fn emit_auto_reinvest_set(env: &Env, investor: &Address, enabled: bool) {
+    env.events().publish(
+        (symbol_short!("reinvest"),),
+        (investor.clone(), enabled, env.ledger().timestamp()),
+    );
+}
+
+/// Emit event when an address pays funds into its balance
+pub fn emit_balance_deposited(
+    env: &Env,
+    owner: &Address,
+    currency: &Address,
+    amount: i128,
+    balance: i128,
+) {
+    env.events().publish(
+        (symbol_short!("bal_dep"),),
+        (owner.clone(), currency.clone(), amount, balance),
+    );
+}
+
+/// Emit event when an address withdraws funds from its balance
+pub fn emit_balance_withdrawn(
+    env: &Env,
+    owner: &Address,
+    currency: &Address,
+    amount: i128,
+    balance: i128,
+) {
+    env.events().publish(
+        (symbol_short!("bal_wd"),),
+        (owner.clone(), currency.clone(), amount, balance),
+    );
+}
+
+/// Emit event when funds the contract holds, such as bid refunds or
+/// reinvested returns, are credited to an address's balance
+pub fn emit_balance_credited(
+    env: &Env,
+    owner: &Address,
+    currency: &Address,
+    amount: i128,
+    balance: i128,
+) {
+    env.events().publish(
+        (symbol_short!("bal_cred"),),
+        (owner.clone(), currency.clone(), amount, balance),
+    );
+}
+
+/// Emit event when the admin sets the price oracle
+pub fn emit_price_oracle_set(env: &Env, config: &OracleConfig, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("oracle"),),
+        (
+            config.oracle.clone(),
+            config.reference_currency.clone(),
+            config.max_price_age,
+            admin.clone(),
+        ),
+    );
+}
+
+/// Emit event when an owner sets where and how quickly their balance may be
+/// withdrawn
+pub fn emit_withdrawal_policy_set(env: &Env, owner: &Address, policy: &WithdrawalPolicy) {
+    env.events().publish(
+        (symbol_short!("wd_pol"),),
+        (
+            owner.clone(),
+            policy.allowlist.len(),
+            policy.delay,
+            policy.effective_at,
+        ),
+    );
+}
+
+/// Emit event when a withdrawal starts waiting out the owner's delay
+pub fn emit_withdrawal_requested(env: &Env, pending: &PendingWithdrawal, balance: i128) {
+    env.events().publish(
+        (symbol_short!("wd_req"),),
+        (
+            pending.owner.clone(),
+            pending.currency.clone(),
+            pending.amount,
+            pending.destination.clone(),
+            pending.available_at,
+            balance,
+        ),
+    );
+}
+
+/// Emit event when a delayed withdrawal is cancelled and its funds return to
+/// the balance
+pub fn emit_withdrawal_cancelled(env: &Env, pending: &PendingWithdrawal, balance: i128) {
+    env.events().publish(
+        (symbol_short!("wd_cncl"),),
+        (
+            pending.owner.clone(),
+            pending.currency.clone(),
+            pending.amount,
+            balance,
+        ),
+    );
+}
+
+/// Emit event when a business splits an invoice's funding into tranches
+pub fn emit_tranches_set(env: &Env, structure: &TrancheStructure) {
+    env.events().publish(
+        (symbol_short!("tranches"),),
+        (
+            structure.invoice_id.clone(),
+            structure.senior_amount,
+            structure.junior_amount,
+            structure.senior_return_bps,
+            structure.junior_return_bps,
+        ),
+    );
+}
+
+/// Emit event when the admin designates the custodial account abandoned
+/// balances are swept to
+pub fn emit_escheat_custodian_set(env: &Env, custodian: &Address, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("custodian"),),
+        (custodian.clone(), admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+/// Emit event warning that a balance looks abandoned and will be swept
+pub fn emit_dormancy_notice(env: &Env, notice: &DormancyNotice) {
+    env.events().publish(
+        (symbol_short!("esch_wrn"),),
+        (
+            notice.owner.clone(),
+            notice.currency.clone(),
+            notice.amount,
+            notice.last_activity,
+            notice.sweepable_at,
+        ),
+    );
+}
+
+/// Emit event when an abandoned balance is swept to the custodian
+pub fn emit_balance_escheated(env: &Env, escheated: &EscheatedBalance, amount: i128) {
+    env.events().publish(
+        (symbol_short!("esch_swp"),),
+        (
+            escheated.owner.clone(),
+            escheated.currency.clone(),
+            amount,
+            escheated.custodian.clone(),
+        ),
+    );
+}
+
+/// Emit event when an owner reclaims a swept balance from the custodian
+pub fn emit_escheat_claimed(env: &Env, escheated: &EscheatedBalance) {
+    env.events().publish(
+        (symbol_short!("esch_clm"),),
+        (
+            escheated.owner.clone(),
+            escheated.currency.clone(),
+            escheated.amount,
+            escheated.custodian.clone(),
+        ),
+    );
+}
+
+/// Emit event when an invoice's average rating changes, naming what changed
+/// it, so listings can be refreshed without polling rating queries
+pub fn emit_invoice_rating_updated(
+    env: &Env,
+    invoice: &Invoice,
+    old: Option<u32>,
+    new: u32,
+    cause: Symbol,
+) {
+    env.events().publish(
+        (Symbol::new(env, "score_updated"), symbol_short!("rating")),
+        (invoice.id.clone(), invoice.business.clone(), old, new, cause),
+    );
+}
+
+/// Emit event when a business's composite risk score changes, naming what
+/// changed it; `old` is None the first time the score is published
+pub fn emit_risk_score_updated(
+    env: &Env,
+    business: &Address,
+    old: Option<u32>,
+    new: u32,
+    cause: Symbol,
+) {
+    env.events().publish(
+        (Symbol::new(env, "score_updated"), symbol_short!("risk")),
+        (business.clone(), old, new, cause),
+    );
+}
@@ -0,0 +1,90 @@
+use crate::audit::log_invoice_status_change;
+use crate::bid::BidStorage;
+use crate::errors::QuickLendXError;
+use crate::events::{emit_funding_deadline_set, emit_invoice_expired};
+use crate::invoice::{Invoice, InvoiceStatus, InvoiceStorage};
+use crate::storage::{self, DataKey};
+use soroban_sdk::{Address, BytesN, Env, Vec};
+
+/// Get the time by which a bid must be accepted on an invoice, if any
+pub fn get_funding_deadline(env: &Env, invoice_id: &BytesN<32>) -> Option<u64> {
+    storage::get(env, &DataKey::FundingDeadline(invoice_id.clone()))
+}
+
+/// Set or clear the funding deadline of an unfunded invoice (business owner
+/// only). The deadline must be in the future and no later than the due date.
+pub fn set_funding_deadline(
+    env: &Env,
+    business: &Address,
+    invoice_id: &BytesN<32>,
+    deadline: Option<u64>,
+) -> Result<(), QuickLendXError> {
+    business.require_auth();
+    let invoice =
+        InvoiceStorage::get_invoice(env, invoice_id).ok_or(QuickLendXError::InvoiceNotFound)?;
+    if invoice.business != *business {
+        return Err(QuickLendXError::NotBusinessOwner);
+    }
+    if invoice.status != InvoiceStatus::Pending && invoice.status != InvoiceStatus::Verified {
+        return Err(QuickLendXError::InvalidStatus);
+    }
+    if invoice.funded_amount > 0 {
+        return Err(QuickLendXError::InvoiceAlreadyFunded);
+    }
+
+    let key = DataKey::FundingDeadline(invoice_id.clone());
+    match deadline {
+        Some(deadline) => {
+            if deadline <= env.ledger().timestamp() || deadline > invoice.due_date {
+                return Err(QuickLendXError::InvalidTimestamp);
+            }
+            storage::set(env, &key, &deadline);
+        }
+        None => storage::remove(env, &key),
+    }
+    emit_funding_deadline_set(env, invoice_id, deadline, business);
+    Ok(())
+}
+
+/// Whether an invoice's funding deadline passed before any bid was accepted
+pub fn is_funding_expired(env: &Env, invoice: &Invoice) -> bool {
+    match get_funding_deadline(env, &invoice.id) {
+        Some(deadline) => invoice.funded_amount == 0 && env.ledger().timestamp() > deadline,
+        None => false,
+    }
+}
+
+/// Expire every pending or verified invoice whose funding deadline passed
+/// without an accepted bid, rejecting bids still open on it and returning
+/// their funds. Anyone may call this; the contract itself is recorded as the
+/// actor. Returns the ids of the invoices that expired.
+pub fn expire_invoices(env: &Env) -> Result<Vec<BytesN<32>>, QuickLendXError> {
+    let keeper = env.current_contract_address();
+    let mut expired = Vec::new(env);
+    for status in [InvoiceStatus::Pending, InvoiceStatus::Verified].iter() {
+        for invoice_id in InvoiceStorage::get_invoices_by_status(env, status).iter() {
+            let Some(mut invoice) = InvoiceStorage::get_invoice(env, &invoice_id) else {
+                continue;
+            };
+            if !is_funding_expired(env, &invoice) {
+                continue;
+            }
+
+            let rejected = BidStorage::reject_open_bids(env, &invoice_id, &keeper)?;
+            InvoiceStorage::remove_from_status_invoices(env, status, &invoice_id);
+            invoice.mark_as_expired();
+            InvoiceStorage::update_invoice(env, &invoice);
+            InvoiceStorage::add_to_status_invoices(env, &InvoiceStatus::Expired, &invoice_id);
+            log_invoice_status_change(
+                env,
+                invoice_id.clone(),
+                keeper.clone(),
+                status.clone(),
+                InvoiceStatus::Expired,
+            );
+            emit_invoice_expired(env, &invoice, rejected);
+            expired.push_back(invoice_id);
+        }
+    }
+    Ok(expired)
+}
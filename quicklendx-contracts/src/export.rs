@@ -0,0 +1,100 @@
+use crate::archive::ArchiveStorage;
+use crate::invoice::{Invoice, InvoiceStorage};
+use crate::payments::{EscrowLedgerEntry, EscrowStorage};
+use crate::profile::{BusinessProfile, ProfileStorage};
+use crate::settlement::get_amount_paid;
+use crate::verification::{BusinessVerification, BusinessVerificationStorage};
+use soroban_sdk::{contracttype, Address, BytesN, Env, Vec};
+
+/// Most invoices returned in one page of an export
+pub const MAX_EXPORT_PAGE: u32 = 10;
+
+/// An invoice as exported, with the record of the money that moved for it
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InvoiceExport {
+    pub invoice: Invoice, // Its ratings included, unless archived
+    pub archived: bool,
+    pub installments_paid: i128, // Repaid through installments so far
+    pub receipts: Vec<EscrowLedgerEntry>, // Escrow deposits, releases and refunds
+}
+
+/// One page of everything the contract holds about a business
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BusinessDataExport {
+    pub business: Address,
+    pub verification: Vec<BusinessVerification>, // Empty if it never applied
+    pub profile: Vec<BusinessProfile>,           // Empty if it has none
+    pub total_invoices: u32,                     // Live and archived
+    pub offset: u32,
+    pub invoices: Vec<InvoiceExport>, // Live invoices first, then archived
+    pub exported_at: u64,
+}
+
+/// Export a business's verification record, profile and a page of its
+/// invoices with their receipts, for data-portability requests and
+/// migrations. Walk `offset` up by the page size until it reaches
+/// `total_invoices`; pages hold at most `MAX_EXPORT_PAGE` invoices.
+pub fn export_business_data(
+    env: &Env,
+    business: &Address,
+    offset: u32,
+    limit: u32,
+) -> BusinessDataExport {
+    let live = InvoiceStorage::get_business_invoices(env, business);
+    let archived = ArchiveStorage::get_business_archive(env, business);
+    let total_invoices = live.len() + archived.len();
+    let end = offset
+        .saturating_add(limit.min(MAX_EXPORT_PAGE))
+        .min(total_invoices);
+
+    let mut invoices = Vec::new(env);
+    for index in offset..end {
+        let export = if index < live.len() {
+            InvoiceStorage::get_invoice(env, &live.get(index).unwrap())
+                .map(|invoice| export_invoice(env, invoice, false))
+        } else {
+            ArchiveStorage::get_archived_invoice(env, &archived.get(index - live.len()).unwrap())
+                .map(|record| export_invoice(env, record.to_invoice(env), true))
+        };
+        if let Some(export) = export {
+            invoices.push_back(export);
+        }
+    }
+
+    let mut verification = Vec::new(env);
+    if let Some(record) = BusinessVerificationStorage::get_verification(env, business) {
+        verification.push_back(record);
+    }
+    let mut profile = Vec::new(env);
+    if let Some(record) = ProfileStorage::get_profile(env, business) {
+        profile.push_back(record);
+    }
+    BusinessDataExport {
+        business: business.clone(),
+        verification,
+        profile,
+        total_invoices,
+        offset,
+        invoices,
+        exported_at: env.ledger().timestamp(),
+    }
+}
+
+fn export_invoice(env: &Env, invoice: Invoice, archived: bool) -> InvoiceExport {
+    InvoiceExport {
+        installments_paid: get_amount_paid(env, &invoice.id),
+        receipts: get_receipts(env, &invoice.id),
+        invoice,
+        archived,
+    }
+}
+
+fn get_receipts(env: &Env, invoice_id: &BytesN<32>) -> Vec<EscrowLedgerEntry> {
+    let mut receipts = Vec::new(env);
+    for escrow_id in EscrowStorage::get_escrows_for_invoice(env, invoice_id).iter() {
+        receipts.append(&EscrowStorage::get_ledger(env, &escrow_id));
+    }
+    receipts
+}
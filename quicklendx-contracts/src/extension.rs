@@ -0,0 +1,272 @@
+use crate::config_history::record_config_change;
+use crate::defaults::is_overdue;
+use crate::errors::QuickLendXError;
+use crate::events::{
+    emit_due_date_extension_decided, emit_due_date_extension_requested,
+    emit_due_date_extension_voted, emit_extension_vote_window_set, emit_max_extension_days_set,
+};
+use crate::investment::InvestmentStorage;
+use crate::invoice::{DueDateExtension, ExtensionStatus, Invoice, InvoiceStatus, InvoiceStorage};
+use crate::maturity::reschedule_funding;
+use crate::storage::{self, DataKey};
+use crate::verification::{require_admin, require_verifier};
+use soroban_sdk::{contracttype, symbol_short, Address, BytesN, Env, Vec};
+
+/// Longest due-date extension granted unless the admin sets another
+pub const DEFAULT_MAX_EXTENSION_DAYS: u32 = 30;
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// The investors' vote on a due-date extension a verifier has approved. Each
+/// investor's vote weighs as much as it funded; the extension takes effect
+/// once investors holding more than half the funding approve, and is
+/// rejected once that can no longer happen or the vote times out.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExtensionVote {
+    pub invoice_id: BytesN<32>,
+    pub vote_ends: u64,
+    pub total_amount: i128, // Funding held by the investors when the vote opened
+    pub approved_amount: i128,
+    pub rejected_amount: i128,
+    pub voters: Vec<Address>,
+}
+
+/// Get the investors' vote on an invoice's due-date extension, if one was
+/// held
+pub fn get_extension_vote(env: &Env, invoice_id: &BytesN<32>) -> Option<ExtensionVote> {
+    storage::get(env, &DataKey::ExtensionVote(invoice_id.clone()))
+}
+
+/// Get how long investors have to vote on an approved extension, in
+/// seconds; 0 means a verifier's approval alone moves the due date
+pub fn get_extension_vote_window(env: &Env) -> u64 {
+    env.storage()
+        .instance()
+        .get(&symbol_short!("ext_vote"))
+        .unwrap_or(0)
+}
+
+/// Set how long investors have to vote on an approved extension, in
+/// seconds; 0 stops asking them (admin only). Votes already open keep their
+/// deadline.
+pub fn set_extension_vote_window(
+    env: &Env,
+    admin: &Address,
+    window: u64,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    env.storage()
+        .instance()
+        .set(&symbol_short!("ext_vote"), &window);
+    record_config_change(env, "extension_vote_window", window, admin);
+    emit_extension_vote_window_set(env, window, admin);
+    Ok(())
+}
+
+/// Get the most days an invoice's due date may be pushed back by
+pub fn get_max_extension_days(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&symbol_short!("max_ext"))
+        .unwrap_or(DEFAULT_MAX_EXTENSION_DAYS)
+}
+
+/// Set the most days an invoice's due date may be pushed back by (admin
+/// only). Requests already made keep their date.
+pub fn set_max_extension_days(
+    env: &Env,
+    admin: &Address,
+    days: u32,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    env.storage()
+        .instance()
+        .set(&symbol_short!("max_ext"), &days);
+    record_config_change(env, "max_extension_days", days, admin);
+    emit_max_extension_days_set(env, days, admin);
+    Ok(())
+}
+
+fn get_funded_invoice(env: &Env, invoice_id: &BytesN<32>) -> Result<Invoice, QuickLendXError> {
+    let invoice =
+        InvoiceStorage::get_invoice(env, invoice_id).ok_or(QuickLendXError::InvoiceNotFound)?;
+    if invoice.status != InvoiceStatus::Funded {
+        return Err(QuickLendXError::InvalidStatus);
+    }
+    // Once the grace period is over the invoice can be defaulted instead
+    if is_overdue(env, &invoice) {
+        return Err(QuickLendXError::OperationNotAllowed);
+    }
+    Ok(invoice)
+}
+
+/// Ask to push back a funded invoice's due date to `new_due_date`, with the
+/// hash of evidence that the debtor is paying late (business only). Each
+/// invoice gets one request, made before it can be defaulted.
+pub fn request_extension(
+    env: &Env,
+    business: &Address,
+    invoice_id: &BytesN<32>,
+    new_due_date: u64,
+    evidence_hash: BytesN<32>,
+) -> Result<DueDateExtension, QuickLendXError> {
+    business.require_auth();
+    let mut invoice = get_funded_invoice(env, invoice_id)?;
+    if invoice.business != *business {
+        return Err(QuickLendXError::NotBusinessOwner);
+    }
+    if !invoice.extensions.is_empty() {
+        return Err(QuickLendXError::ExtensionAlreadyRequested);
+    }
+    let max_extension = get_max_extension_days(env) as u64 * SECONDS_PER_DAY;
+    if new_due_date <= invoice.due_date || new_due_date - invoice.due_date > max_extension {
+        return Err(QuickLendXError::InvalidTimestamp);
+    }
+
+    let extension = DueDateExtension {
+        original_due_date: invoice.due_date,
+        new_due_date,
+        evidence_hash,
+        status: ExtensionStatus::Requested,
+        requested_at: env.ledger().timestamp(),
+        decided_by: None,
+        decided_at: None,
+    };
+    invoice.extensions.push_back(extension.clone());
+    InvoiceStorage::update_invoice(env, &invoice);
+    emit_due_date_extension_requested(env, invoice_id, &extension);
+    Ok(extension)
+}
+
+fn find_extension(
+    invoice: &Invoice,
+    status: ExtensionStatus,
+) -> Result<(u32, DueDateExtension), QuickLendXError> {
+    let index = invoice
+        .extensions
+        .iter()
+        .position(|extension| extension.status == status)
+        .ok_or(QuickLendXError::ExtensionNotFound)? as u32;
+    Ok((index, invoice.extensions.get(index).unwrap()))
+}
+
+/// Settle an extension as approved or rejected, moving the due date on
+/// approval
+fn finish_extension(
+    env: &Env,
+    mut invoice: Invoice,
+    index: u32,
+    mut extension: DueDateExtension,
+    approve: bool,
+) -> DueDateExtension {
+    extension.decided_at = Some(env.ledger().timestamp());
+    if approve {
+        extension.status = ExtensionStatus::Approved;
+        invoice.due_date = extension.new_due_date;
+        reschedule_funding(env, &invoice, extension.original_due_date);
+    } else {
+        extension.status = ExtensionStatus::Rejected;
+    }
+    invoice.extensions.set(index, extension.clone());
+    InvoiceStorage::update_invoice(env, &invoice);
+    emit_due_date_extension_decided(env, &invoice.id, &extension);
+    extension
+}
+
+/// Approve or reject an invoice's extension request (verifier only). An
+/// approval moves the due date, provided the invoice still cannot be
+/// defaulted; while the admin has set a vote window, it instead opens the
+/// investors' vote on the extension.
+pub fn decide_extension(
+    env: &Env,
+    verifier: &Address,
+    invoice_id: &BytesN<32>,
+    approve: bool,
+) -> Result<DueDateExtension, QuickLendXError> {
+    require_verifier(env, verifier)?;
+    let mut invoice = get_funded_invoice(env, invoice_id)?;
+    let (index, mut extension) = find_extension(&invoice, ExtensionStatus::Requested)?;
+    extension.decided_by = Some(verifier.clone());
+
+    let window = get_extension_vote_window(env);
+    if !approve || window == 0 {
+        return Ok(finish_extension(env, invoice, index, extension, approve));
+    }
+    let vote = ExtensionVote {
+        invoice_id: invoice_id.clone(),
+        vote_ends: env.ledger().timestamp().saturating_add(window),
+        total_amount: InvestmentStorage::get_funding_contributions(env, invoice_id)
+            .iter()
+            .map(|contribution| contribution.amount)
+            .sum(),
+        approved_amount: 0,
+        rejected_amount: 0,
+        voters: Vec::new(env),
+    };
+    storage::set(env, &DataKey::ExtensionVote(invoice_id.clone()), &vote);
+    extension.status = ExtensionStatus::AwaitingInvestors;
+    invoice.extensions.set(index, extension.clone());
+    InvoiceStorage::update_invoice(env, &invoice);
+    emit_due_date_extension_decided(env, invoice_id, &extension);
+    Ok(extension)
+}
+
+/// Vote for or against an extension awaiting the investors (investor in the
+/// invoice only). The vote settles the extension as soon as the outcome is
+/// certain.
+pub fn vote_on_extension(
+    env: &Env,
+    investor: &Address,
+    invoice_id: &BytesN<32>,
+    approve: bool,
+) -> Result<DueDateExtension, QuickLendXError> {
+    investor.require_auth();
+    let invoice = get_funded_invoice(env, invoice_id)?;
+    let (index, extension) = find_extension(&invoice, ExtensionStatus::AwaitingInvestors)?;
+    let mut vote = get_extension_vote(env, invoice_id).ok_or(QuickLendXError::ExtensionNotFound)?;
+    if env.ledger().timestamp() >= vote.vote_ends {
+        return Err(QuickLendXError::ExtensionVoteClosed);
+    }
+    if vote.voters.contains(investor) {
+        return Err(QuickLendXError::AlreadyVoted);
+    }
+    let amount = InvestmentStorage::get_funding_contributions(env, invoice_id)
+        .iter()
+        .find(|contribution| contribution.investor == *investor)
+        .map(|contribution| contribution.amount)
+        .ok_or(QuickLendXError::NotInvestor)?;
+
+    vote.voters.push_back(investor.clone());
+    if approve {
+        vote.approved_amount += amount;
+    } else {
+        vote.rejected_amount += amount;
+    }
+    storage::set(env, &DataKey::ExtensionVote(invoice_id.clone()), &vote);
+    emit_due_date_extension_voted(env, invoice_id, investor, approve, amount);
+
+    if vote.approved_amount * 2 > vote.total_amount {
+        return Ok(finish_extension(env, invoice, index, extension, true));
+    }
+    if vote.rejected_amount * 2 >= vote.total_amount {
+        return Ok(finish_extension(env, invoice, index, extension, false));
+    }
+    Ok(extension)
+}
+
+/// Reject an extension whose investors' vote timed out without a majority
+/// for it. Callable by anyone once the vote has ended.
+pub fn close_extension_vote(
+    env: &Env,
+    invoice_id: &BytesN<32>,
+) -> Result<DueDateExtension, QuickLendXError> {
+    let invoice =
+        InvoiceStorage::get_invoice(env, invoice_id).ok_or(QuickLendXError::InvoiceNotFound)?;
+    let (index, extension) = find_extension(&invoice, ExtensionStatus::AwaitingInvestors)?;
+    let vote = get_extension_vote(env, invoice_id).ok_or(QuickLendXError::ExtensionNotFound)?;
+    if env.ledger().timestamp() < vote.vote_ends {
+        return Err(QuickLendXError::OperationNotAllowed);
+    }
+    Ok(finish_extension(env, invoice, index, extension, false))
+}
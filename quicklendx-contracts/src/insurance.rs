@@ -0,0 +1,746 @@
+use crate::archive::ArchiveStorage;
+use crate::attestation::attestation_credit;
+use crate::audit::log_payment_processed;
+use crate::backstop::{draw_backstop, pay_backstop_fees};
+use crate::config::{check_description_length, check_rejection_reason_length};
+use crate::config_history::record_config_change;
+use crate::debt_market::DebtMarketStorage;
+use crate::delinquency::{get_recorded_stage, DelinquencyStage};
+use crate::disputes::open_claim_appeal;
+use crate::errors::QuickLendXError;
+use crate::events::{
+    emit_claim_appealed, emit_claim_paid, emit_claim_reviewed, emit_claim_submitted,
+    emit_insurance_admin_set, emit_insurance_fund_deposit, emit_insurance_pool_config_set,
+    emit_insurance_recovered, emit_premium_charged, emit_premium_rates_set,
+    emit_risk_score_updated,
+};
+use crate::investment::InvestmentStorage;
+use crate::invoice::{Invoice, InvoiceStatus, InvoiceStorage};
+use crate::payments::transfer_funds;
+use crate::profits::SECONDS_PER_YEAR;
+use crate::storage::{self, DataKey};
+use crate::verification::{require_admin, BusinessVerificationStorage};
+use soroban_sdk::{contracttype, symbol_short, Address, BytesN, Env, String, Symbol, Vec};
+
+/// Highest premium, in bps of the covered amount, pricing may produce
+pub const MAX_PREMIUM_BPS: u32 = 2_000;
+/// Risk score given to businesses without any repayment history
+pub const NEW_BUSINESS_RISK_SCORE: u32 = 50;
+
+/// Highest share of investor returns the settlement levy may divert
+pub const MAX_SETTLEMENT_LEVY_BPS: u32 = 500;
+
+/// How the insurance pool is funded from settlements and what defaulted
+/// investors may recover from it without a reviewed claim
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InsurancePoolConfig {
+    pub settlement_levy_bps: u32, // Share of each settlement's investor returns
+    pub recovery_bps: u32,        // Share of principal paid by claim_insurance
+}
+
+/// Inputs to premium pricing. The premium is
+/// `base_bps + risk_bps * risk_score / 100 + annual_tenor_bps * tenor / year`,
+/// capped at `MAX_PREMIUM_BPS`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PremiumRates {
+    pub base_bps: u32,         // Charged on every covered investment
+    pub risk_bps: u32,         // Added in full at the highest risk score (100)
+    pub annual_tenor_bps: u32, // Added per year between funding and due date
+}
+
+/// Terms of the insurance bought with an investment
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InsuranceCoverage {
+    pub risk_score: u32, // Business risk score (0-100) at acceptance
+    pub tenor: u64,      // Seconds from acceptance to the invoice due date
+    pub premium_bps: u32,
+    pub premium: i128,         // Paid by the investor into the insurance fund
+    pub coverage_amount: i128, // Most a claim on this investment may pay
+}
+
+/// Whether an investment is covered by the insurance fund
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum InvestmentInsurance {
+    Uninsured,
+    Covered(InsuranceCoverage),
+}
+
+/// Where an insurance claim is in its review workflow
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ClaimStatus {
+    Submitted,   // Filed by the investor, awaiting review
+    UnderReview, // Picked up by a reviewer
+    Approved,    // Accepted, awaiting payout
+    Denied,      // Rejected; may be appealed once
+    Appealed,    // Denial contested, awaiting the arbitrator's ruling
+    Paid,        // Payout sent to the investor
+}
+
+/// A claim against the insurance fund for a defaulted investment
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InsuranceClaim {
+    pub claim_id: BytesN<32>,
+    pub investment_id: BytesN<32>,
+    pub invoice_id: BytesN<32>,
+    pub investor: Address,
+    pub amount: i128,
+    pub currency: Address,
+    pub status: ClaimStatus,
+    pub submitted_at: u64,
+    pub reviewed_by: Option<Address>,
+    pub reviewed_at: Option<u64>,
+    pub denial_reason: Option<String>,
+    pub appeal_grounds: Option<String>,
+}
+
+pub struct InsuranceStorage;
+
+impl InsuranceStorage {
+    /// Generate a unique claim ID using timestamp and counter
+    fn generate_unique_claim_id(env: &Env) -> BytesN<32> {
+        let timestamp = env.ledger().timestamp();
+        let counter_key = symbol_short!("clm_cnt");
+        let counter: u64 = env.storage().instance().get(&counter_key).unwrap_or(0u64);
+        env.storage().instance().set(&counter_key, &(counter + 1));
+
+        let mut id_bytes = [0u8; 32];
+        // Add claim prefix to distinguish from other entity types
+        id_bytes[0] = 0xC1; // 'C' for Claim
+        id_bytes[1] = 0xA1; // 'A' for clAim
+
+        // Embed timestamp in next 8 bytes
+        id_bytes[2..10].copy_from_slice(&timestamp.to_be_bytes());
+        // Embed counter in next 8 bytes
+        id_bytes[10..18].copy_from_slice(&counter.to_be_bytes());
+        // Fill remaining bytes with a pattern to ensure uniqueness
+        id_bytes[18..].fill(((timestamp + counter + 0xC1A1) % 256) as u8);
+
+        BytesN::from_array(env, &id_bytes)
+    }
+
+    pub fn get_claim(env: &Env, claim_id: &BytesN<32>) -> Option<InsuranceClaim> {
+        storage::get(env, &DataKey::InsuranceClaim(claim_id.clone()))
+    }
+
+    fn set_claim(env: &Env, claim: &InsuranceClaim) {
+        storage::set(env, &DataKey::InsuranceClaim(claim.claim_id.clone()), claim);
+    }
+
+    /// Get the claim filed for an investment, if any
+    pub fn get_claim_for_investment(env: &Env, investment_id: &BytesN<32>) -> Option<BytesN<32>> {
+        storage::get(env, &DataKey::InvestmentClaim(investment_id.clone()))
+    }
+
+    /// Get the ids of claims currently in a status
+    pub fn get_claims_by_status(env: &Env, status: &ClaimStatus) -> Vec<BytesN<32>> {
+        storage::get(env, &DataKey::ClaimsByStatus(status.clone())).unwrap_or_else(|| Vec::new(env))
+    }
+
+    /// Store a new claim and index it under its status and investment
+    fn store_claim(env: &Env, claim: &InsuranceClaim) {
+        Self::set_claim(env, claim);
+        storage::set(
+            env,
+            &DataKey::InvestmentClaim(claim.investment_id.clone()),
+            &claim.claim_id,
+        );
+        let mut ids = Self::get_claims_by_status(env, &claim.status);
+        ids.push_back(claim.claim_id.clone());
+        Self::set_claims_by_status(env, &claim.status, &ids);
+    }
+
+    fn set_claims_by_status(env: &Env, status: &ClaimStatus, ids: &Vec<BytesN<32>>) {
+        storage::set(env, &DataKey::ClaimsByStatus(status.clone()), ids);
+    }
+
+    /// Move a claim to a new status, keeping the status index in step
+    fn move_claim_status(env: &Env, claim: &mut InsuranceClaim, status: ClaimStatus) {
+        let mut old = Self::get_claims_by_status(env, &claim.status);
+        if let Some(index) = old.first_index_of(&claim.claim_id) {
+            old.remove(index);
+        }
+        Self::set_claims_by_status(env, &claim.status, &old);
+
+        let mut new = Self::get_claims_by_status(env, &status);
+        new.push_back(claim.claim_id.clone());
+        Self::set_claims_by_status(env, &status, &new);
+
+        claim.status = status;
+        Self::set_claim(env, claim);
+    }
+
+    /// Get the insurance fund balance in a currency
+    pub fn get_fund_balance(env: &Env, currency: &Address) -> i128 {
+        env.storage()
+            .instance()
+            .get(&(symbol_short!("ins_fund"), currency.clone()))
+            .unwrap_or(0)
+    }
+
+    fn set_fund_balance(env: &Env, currency: &Address, balance: i128) {
+        env.storage()
+            .instance()
+            .set(&(symbol_short!("ins_fund"), currency.clone()), &balance);
+    }
+
+    /// Get the premium pricing rates, falling back to the defaults
+    pub fn get_premium_rates(env: &Env) -> PremiumRates {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("prem_rate"))
+            .unwrap_or(PremiumRates {
+                base_bps: 100,
+                risk_bps: 400,
+                annual_tenor_bps: 200,
+            })
+    }
+
+    /// Get the insurance pool settings, falling back to no levy and a 50%
+    /// recovery
+    pub fn get_pool_config(env: &Env) -> InsurancePoolConfig {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("ins_pool"))
+            .unwrap_or(InsurancePoolConfig {
+                settlement_levy_bps: 0,
+                recovery_bps: 5_000,
+            })
+    }
+
+    /// Check whether principal on an investment was already recovered from
+    /// the insurance pool
+    pub fn has_recovered(env: &Env, investment_id: &BytesN<32>) -> bool {
+        storage::get::<i128>(env, &DataKey::InsuranceRecovery(investment_id.clone())).is_some()
+    }
+
+    /// Check whether an investment's insurance was already drawn on, through
+    /// a claim or a pool recovery
+    pub fn is_insurance_used(env: &Env, investment_id: &BytesN<32>) -> bool {
+        Self::get_claim_for_investment(env, investment_id).is_some()
+            || Self::has_recovered(env, investment_id)
+    }
+
+    /// Check whether investments in an invoice are insured
+    pub fn is_insurance_enabled(env: &Env, invoice_id: &BytesN<32>) -> bool {
+        storage::get(env, &DataKey::InsuranceEnabled(invoice_id.clone())).unwrap_or(false)
+    }
+
+    /// Get the insurance admin who reviews claims
+    pub fn get_insurance_admin(env: &Env) -> Option<Address> {
+        env.storage().instance().get(&symbol_short!("ins_admin"))
+    }
+}
+
+/// Appoint the insurance admin who reviews and pays claims (admin only)
+pub fn set_insurance_admin(
+    env: &Env,
+    admin: &Address,
+    insurance_admin: &Address,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    env.storage()
+        .instance()
+        .set(&symbol_short!("ins_admin"), insurance_admin);
+    emit_insurance_admin_set(env, insurance_admin, admin);
+    Ok(())
+}
+
+/// Update the premium pricing rates (admin only)
+pub fn set_premium_rates(
+    env: &Env,
+    admin: &Address,
+    rates: &PremiumRates,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    let parts = [rates.base_bps, rates.risk_bps, rates.annual_tenor_bps];
+    if parts.iter().any(|bps| *bps > MAX_PREMIUM_BPS) {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    env.storage()
+        .instance()
+        .set(&symbol_short!("prem_rate"), rates);
+    record_config_change(env, "premium_rates", rates, admin);
+    emit_premium_rates_set(env, rates, admin);
+    Ok(())
+}
+
+/// Update the settlement levy and recovery share (admin only)
+pub fn set_pool_config(
+    env: &Env,
+    admin: &Address,
+    config: &InsurancePoolConfig,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    if config.settlement_levy_bps > MAX_SETTLEMENT_LEVY_BPS || config.recovery_bps > 10_000 {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    env.storage()
+        .instance()
+        .set(&symbol_short!("ins_pool"), config);
+    record_config_change(env, "insurance_pool_config", config, admin);
+    emit_insurance_pool_config_set(env, config, admin);
+    Ok(())
+}
+
+/// Turn insurance on or off for investments in an invoice (business owner
+/// only, before funding starts)
+pub fn set_invoice_insurance(
+    env: &Env,
+    business: &Address,
+    invoice_id: &BytesN<32>,
+    enabled: bool,
+) -> Result<(), QuickLendXError> {
+    business.require_auth();
+    let invoice =
+        InvoiceStorage::get_invoice(env, invoice_id).ok_or(QuickLendXError::InvoiceNotFound)?;
+    if invoice.business != *business {
+        return Err(QuickLendXError::NotBusinessOwner);
+    }
+    if invoice.funded_amount > 0 {
+        return Err(QuickLendXError::InvoiceAlreadyFunded);
+    }
+    if invoice.status != InvoiceStatus::Pending && invoice.status != InvoiceStatus::Verified {
+        return Err(QuickLendXError::InvalidStatus);
+    }
+    storage::set(
+        env,
+        &DataKey::InsuranceEnabled(invoice_id.clone()),
+        &enabled,
+    );
+    Ok(())
+}
+
+/// Composite risk score of a business from 0 (no defaults) to 100 (only
+/// defaults), based on the share of its closed invoices that defaulted and
+/// lowered while its financial attestation is fresh. Invoices paid after
+/// keepers marked them 30 or 60 days delinquent count as a quarter or half
+/// of a default.
+pub fn business_risk_score(env: &Env, business: &Address) -> u32 {
+    history_risk_score(env, business).saturating_sub(attestation_credit(env, business))
+}
+
+/// Recompute a business's risk score and, if it moved since it was last
+/// published, publish it with a `score_updated` event naming the cause.
+/// Returns the score.
+pub fn publish_risk_score(env: &Env, business: &Address, cause: Symbol) -> u32 {
+    let score = business_risk_score(env, business);
+    let published: Option<u32> = storage::get(env, &DataKey::PublishedRiskScore(business.clone()));
+    if published != Some(score) {
+        storage::set(env, &DataKey::PublishedRiskScore(business.clone()), &score);
+        emit_risk_score_updated(env, business, published, score, cause);
+    }
+    score
+}
+
+fn history_risk_score(env: &Env, business: &Address) -> u32 {
+    // Archived invoices still count towards the history
+    let archived = ArchiveStorage::get_archived_outcomes(env, business);
+    let mut closed = archived.paid + archived.defaulted;
+    let mut points = archived.risk_points;
+    for invoice_id in InvoiceStorage::get_business_invoices(env, business).iter() {
+        if let Some(invoice) = InvoiceStorage::get_invoice(env, &invoice_id) {
+            if let Some(invoice_points) = invoice_risk_points(env, &invoice) {
+                closed += 1;
+                points += invoice_points;
+            }
+        }
+    }
+    if closed == 0 {
+        return NEW_BUSINESS_RISK_SCORE;
+    }
+    points / closed
+}
+
+/// Points a closed invoice adds to its business's history risk score, or
+/// `None` while it is still open
+pub fn invoice_risk_points(env: &Env, invoice: &Invoice) -> Option<u32> {
+    match invoice.status {
+        InvoiceStatus::Paid => Some(match get_recorded_stage(env, &invoice.id) {
+            DelinquencyStage::Delinquent30 => 25,
+            DelinquencyStage::Delinquent60 => 50,
+            _ => 0,
+        }),
+        InvoiceStatus::Defaulted => Some(100),
+        _ => None,
+    }
+}
+
+/// Premium in bps for a business's risk score and a tenor in seconds
+pub fn premium_bps(env: &Env, risk_score: u32, tenor: u64) -> u32 {
+    let rates = InsuranceStorage::get_premium_rates(env);
+    let risk = rates.risk_bps as u64 * risk_score.min(100) as u64 / 100;
+    let tenor = rates.annual_tenor_bps as u64 * tenor / SECONDS_PER_YEAR;
+    (rates.base_bps as u64 + risk + tenor).min(MAX_PREMIUM_BPS as u64) as u32
+}
+
+/// Price and charge coverage for an investment being accepted. When the
+/// invoice is insured, the investor pays the premium into the insurance
+/// fund on top of the funded amount.
+pub fn cover_investment(
+    env: &Env,
+    invoice: &Invoice,
+    investor: &Address,
+    amount: i128,
+) -> Result<InvestmentInsurance, QuickLendXError> {
+    if !InsuranceStorage::is_insurance_enabled(env, &invoice.id) {
+        return Ok(InvestmentInsurance::Uninsured);
+    }
+
+    let risk_score = business_risk_score(env, &invoice.business);
+    let tenor = invoice.due_date.saturating_sub(env.ledger().timestamp());
+    let premium_bps = premium_bps(env, risk_score, tenor);
+    let premium = amount * premium_bps as i128 / 10_000;
+    if premium > 0 {
+        if !transfer_funds(env, investor, &env.current_contract_address(), premium) {
+            return Err(QuickLendXError::InsufficientFunds);
+        }
+        // Backstop providers take their standing fee; the rest goes to the fund
+        let retained = pay_backstop_fees(env, &invoice.currency, premium);
+        let balance = InsuranceStorage::get_fund_balance(env, &invoice.currency) + retained;
+        InsuranceStorage::set_fund_balance(env, &invoice.currency, balance);
+    }
+
+    let coverage = InsuranceCoverage {
+        risk_score,
+        tenor,
+        premium_bps,
+        premium,
+        coverage_amount: amount,
+    };
+    emit_premium_charged(env, &invoice.id, investor, &coverage);
+    Ok(InvestmentInsurance::Covered(coverage))
+}
+
+/// Add capital to the insurance fund
+pub fn deposit_insurance_fund(
+    env: &Env,
+    depositor: &Address,
+    currency: &Address,
+    amount: i128,
+) -> Result<(), QuickLendXError> {
+    depositor.require_auth();
+    if amount <= 0 {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    if !transfer_funds(env, depositor, &env.current_contract_address(), amount) {
+        return Err(QuickLendXError::InsufficientFunds);
+    }
+    let balance = InsuranceStorage::get_fund_balance(env, currency) + amount;
+    InsuranceStorage::set_fund_balance(env, currency, balance);
+    emit_insurance_fund_deposit(env, currency, amount, depositor);
+    Ok(())
+}
+
+/// Add a settlement levy received by the contract to the insurance fund
+pub fn credit_settlement_levy(env: &Env, currency: &Address, amount: i128, payer: &Address) {
+    let balance = InsuranceStorage::get_fund_balance(env, currency) + amount;
+    InsuranceStorage::set_fund_balance(env, currency, balance);
+    emit_insurance_fund_deposit(env, currency, amount, payer);
+}
+
+/// File a claim for an insured investment in a defaulted invoice (claim
+/// owner only: its investor until the claim is sold). One claim may be
+/// filed per investment, for at most its coverage, and none while the claim
+/// is listed for sale, as its insurance goes to the buyer.
+pub fn submit_claim(
+    env: &Env,
+    investor: &Address,
+    investment_id: &BytesN<32>,
+    amount: i128,
+) -> Result<BytesN<32>, QuickLendXError> {
+    investor.require_auth();
+    let investment = InvestmentStorage::get_investment(env, investment_id)
+        .ok_or(QuickLendXError::StorageKeyNotFound)?;
+    if DebtMarketStorage::get_claim_owner(env, &investment) != *investor {
+        return Err(QuickLendXError::NotInvestor);
+    }
+    if DebtMarketStorage::get_listing(env, investment_id).is_some() {
+        return Err(QuickLendXError::ClaimAlreadyListed);
+    }
+    let invoice = InvoiceStorage::get_invoice(env, &investment.invoice_id)
+        .ok_or(QuickLendXError::InvoiceNotFound)?;
+    if invoice.status != InvoiceStatus::Defaulted {
+        return Err(QuickLendXError::InvalidStatus);
+    }
+    // Only covered investments can claim, up to their coverage
+    let coverage = match investment.insurance {
+        InvestmentInsurance::Covered(coverage) => coverage,
+        InvestmentInsurance::Uninsured => return Err(QuickLendXError::OperationNotAllowed),
+    };
+    if amount <= 0 || amount > coverage.coverage_amount {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    if InsuranceStorage::is_insurance_used(env, investment_id) {
+        return Err(QuickLendXError::ClaimAlreadyExists);
+    }
+
+    let claim = InsuranceClaim {
+        claim_id: InsuranceStorage::generate_unique_claim_id(env),
+        investment_id: investment_id.clone(),
+        invoice_id: invoice.id.clone(),
+        investor: investor.clone(),
+        amount,
+        currency: invoice.currency.clone(),
+        status: ClaimStatus::Submitted,
+        submitted_at: env.ledger().timestamp(),
+        reviewed_by: None,
+        reviewed_at: None,
+        denial_reason: None,
+        appeal_grounds: None,
+    };
+    InsuranceStorage::store_claim(env, &claim);
+
+    emit_claim_submitted(env, &claim);
+    Ok(claim.claim_id)
+}
+
+/// Claims are reviewed by the insurance admin or the protocol admin. Appeals
+/// go to the dispute arbitrator instead, so a denial is never re-judged by the
+/// reviewer who made it.
+fn require_reviewer(env: &Env, reviewer: &Address) -> Result<(), QuickLendXError> {
+    reviewer.require_auth();
+    let is_admin = BusinessVerificationStorage::is_admin(env, reviewer);
+    let is_insurance_admin = InsuranceStorage::get_insurance_admin(env).as_ref() == Some(reviewer);
+    if is_admin || is_insurance_admin {
+        Ok(())
+    } else {
+        Err(QuickLendXError::Unauthorized)
+    }
+}
+
+fn get_claim(env: &Env, claim_id: &BytesN<32>) -> Result<InsuranceClaim, QuickLendXError> {
+    InsuranceStorage::get_claim(env, claim_id).ok_or(QuickLendXError::ClaimNotFound)
+}
+
+/// Take a submitted claim into review
+pub fn start_claim_review(
+    env: &Env,
+    reviewer: &Address,
+    claim_id: &BytesN<32>,
+) -> Result<(), QuickLendXError> {
+    let mut claim = get_claim(env, claim_id)?;
+    require_reviewer(env, reviewer)?;
+    if claim.status != ClaimStatus::Submitted {
+        return Err(QuickLendXError::InvalidStatus);
+    }
+    InsuranceStorage::move_claim_status(env, &mut claim, ClaimStatus::UnderReview);
+    emit_claim_reviewed(env, &claim, reviewer);
+    Ok(())
+}
+
+/// Approve a claim under review for payout
+pub fn approve_claim(
+    env: &Env,
+    reviewer: &Address,
+    claim_id: &BytesN<32>,
+) -> Result<(), QuickLendXError> {
+    decide_claim(env, reviewer, claim_id, ClaimStatus::Approved, None)
+}
+
+/// Deny a claim under review, giving the reason
+pub fn deny_claim(
+    env: &Env,
+    reviewer: &Address,
+    claim_id: &BytesN<32>,
+    reason: &String,
+) -> Result<(), QuickLendXError> {
+    check_rejection_reason_length(env, reason)?;
+    decide_claim(
+        env,
+        reviewer,
+        claim_id,
+        ClaimStatus::Denied,
+        Some(reason.clone()),
+    )
+}
+
+fn decide_claim(
+    env: &Env,
+    reviewer: &Address,
+    claim_id: &BytesN<32>,
+    decision: ClaimStatus,
+    denial_reason: Option<String>,
+) -> Result<(), QuickLendXError> {
+    let mut claim = get_claim(env, claim_id)?;
+    require_reviewer(env, reviewer)?;
+    if claim.status != ClaimStatus::UnderReview {
+        return Err(QuickLendXError::InvalidStatus);
+    }
+
+    claim.reviewed_by = Some(reviewer.clone());
+    claim.reviewed_at = Some(env.ledger().timestamp());
+    claim.denial_reason = denial_reason;
+    InsuranceStorage::move_claim_status(env, &mut claim, decision);
+    emit_claim_reviewed(env, &claim, reviewer);
+    Ok(())
+}
+
+/// Contest a denied claim (claiming investor only, once per claim). The appeal
+/// is filed as a dispute for the arbitrator to rule on.
+pub fn appeal_claim(
+    env: &Env,
+    investor: &Address,
+    claim_id: &BytesN<32>,
+    grounds: &String,
+) -> Result<(), QuickLendXError> {
+    investor.require_auth();
+    check_description_length(env, grounds)?;
+    let mut claim = get_claim(env, claim_id)?;
+    if claim.investor != *investor {
+        return Err(QuickLendXError::NotInvestor);
+    }
+    if claim.status != ClaimStatus::Denied {
+        return Err(QuickLendXError::InvalidStatus);
+    }
+    if claim.appeal_grounds.is_some() {
+        return Err(QuickLendXError::AppealAlreadyFiled);
+    }
+
+    claim.appeal_grounds = Some(grounds.clone());
+    InsuranceStorage::move_claim_status(env, &mut claim, ClaimStatus::Appealed);
+    open_claim_appeal(env, &claim, grounds);
+    emit_claim_appealed(env, &claim);
+    Ok(())
+}
+
+/// Apply the arbitrator's ruling on an appealed claim
+pub fn rule_on_appeal(
+    env: &Env,
+    arbitrator: &Address,
+    claim_id: &BytesN<32>,
+    upheld: bool,
+) -> Result<(), QuickLendXError> {
+    let mut claim = get_claim(env, claim_id)?;
+    if claim.status != ClaimStatus::Appealed {
+        return Err(QuickLendXError::InvalidStatus);
+    }
+    let decision = if upheld {
+        claim.denial_reason = None;
+        ClaimStatus::Approved
+    } else {
+        ClaimStatus::Denied
+    };
+    claim.reviewed_by = Some(arbitrator.clone());
+    claim.reviewed_at = Some(env.ledger().timestamp());
+    InsuranceStorage::move_claim_status(env, &mut claim, decision);
+    emit_claim_reviewed(env, &claim, arbitrator);
+    Ok(())
+}
+
+/// Take `amount` out of the insurance fund and send it to `to`, drawing on
+/// backstop providers when the fund falls short
+fn pay_from_fund(
+    env: &Env,
+    currency: &Address,
+    to: &Address,
+    amount: i128,
+) -> Result<(), QuickLendXError> {
+    let mut balance = InsuranceStorage::get_fund_balance(env, currency);
+    if amount > balance {
+        draw_backstop(env, currency, amount - balance)?;
+        balance = amount;
+    }
+    if !transfer_funds(env, &env.current_contract_address(), to, amount) {
+        return Err(QuickLendXError::InsufficientFunds);
+    }
+    InsuranceStorage::set_fund_balance(env, currency, balance - amount);
+    Ok(())
+}
+
+/// Recover the configured share of the principal behind the claims an
+/// address owns in a defaulted invoice straight from the insurance pool.
+/// Each investment may recover once, and not alongside a reviewed claim.
+/// Claims listed for sale keep their insurance for the buyer.
+pub fn claim_insurance(
+    env: &Env,
+    investor: &Address,
+    invoice_id: &BytesN<32>,
+) -> Result<i128, QuickLendXError> {
+    investor.require_auth();
+    let invoice =
+        InvoiceStorage::get_invoice(env, invoice_id).ok_or(QuickLendXError::InvoiceNotFound)?;
+    if invoice.status != InvoiceStatus::Defaulted {
+        return Err(QuickLendXError::InvalidStatus);
+    }
+
+    let recovery_bps = InsuranceStorage::get_pool_config(env).recovery_bps as i128;
+    let mut owns_claim = false;
+    let mut listed = false;
+    let mut recoveries = Vec::new(env);
+    for investment_id in InvestmentStorage::get_investments_for_invoice(env, invoice_id).iter() {
+        let investment = match InvestmentStorage::get_investment(env, &investment_id) {
+            Some(investment)
+                if DebtMarketStorage::get_claim_owner(env, &investment) == *investor =>
+            {
+                investment
+            }
+            _ => continue,
+        };
+        owns_claim = true;
+        if DebtMarketStorage::get_listing(env, &investment_id).is_some() {
+            listed = true;
+        } else if !InsuranceStorage::is_insurance_used(env, &investment_id) {
+            recoveries.push_back((investment_id, investment.amount * recovery_bps / 10_000));
+        }
+    }
+    if !owns_claim {
+        return Err(QuickLendXError::NotInvestor);
+    }
+    if recoveries.is_empty() && listed {
+        return Err(QuickLendXError::ClaimAlreadyListed);
+    }
+    if recoveries.is_empty() {
+        return Err(QuickLendXError::ClaimAlreadyExists);
+    }
+
+    let recovery: i128 = recoveries.iter().map(|(_, amount)| amount).sum();
+    if recovery <= 0 {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    pay_from_fund(env, &invoice.currency, investor, recovery)?;
+    for (investment_id, amount) in recoveries.iter() {
+        storage::set(env, &DataKey::InsuranceRecovery(investment_id), &amount);
+    }
+
+    log_payment_processed(
+        env,
+        invoice_id.clone(),
+        investor.clone(),
+        recovery,
+        String::from_str(env, "insurance_recovery"),
+    );
+    emit_insurance_recovered(env, invoice_id, investor, recovery);
+    Ok(recovery)
+}
+
+/// Pay an approved claim out of the insurance fund, drawing on backstop
+/// providers when the fund falls short
+pub fn pay_claim(
+    env: &Env,
+    reviewer: &Address,
+    claim_id: &BytesN<32>,
+) -> Result<(), QuickLendXError> {
+    let mut claim = get_claim(env, claim_id)?;
+    require_reviewer(env, reviewer)?;
+    if claim.status != ClaimStatus::Approved {
+        return Err(QuickLendXError::InvalidStatus);
+    }
+    pay_from_fund(env, &claim.currency, &claim.investor, claim.amount)?;
+    InsuranceStorage::move_claim_status(env, &mut claim, ClaimStatus::Paid);
+
+    log_payment_processed(
+        env,
+        claim.invoice_id.clone(),
+        reviewer.clone(),
+        claim.amount,
+        String::from_str(env, "insurance_claim"),
+    );
+    emit_claim_paid(env, &claim, reviewer);
+    Ok(())
+}
@@ -1,4 +1,13 @@
-use soroban_sdk::{contracttype, Address, BytesN, Env};
+use soroban_sdk::{contracttype, symbol_short, Address, BytesN, Env, Vec};
+use crate::config::ConfigStorage;
+use crate::errors::QuickLendXError;
+use crate::insurance::{business_risk_score, InsuranceStorage, InvestmentInsurance};
+use crate::invoice::{Invoice, InvoiceStorage};
+use crate::penalty::{
+    expected_returns, get_late_fee_bps_per_day, get_penalty_schedule, PenaltySchedule,
+};
+use crate::profits::calculate_apr_bps;
+use crate::storage::{self, DataKey};
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -17,6 +26,93 @@ pub struct Investment {
     pub amount: i128,
     pub funded_at: u64,
     pub status: InvestmentStatus,
+    pub insurance: InvestmentInsurance,
+    pub risk: RiskSnapshot,
+    pub fees: FeeSnapshot,
+}
+
+/// Inputs to risk decisions as they stood when an investment was funded,
+/// kept so predicted risk can later be checked against realized defaults
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RiskInputs {
+    pub risk_score: u32,       // Business risk score (0-100)
+    pub tenor: u64,            // Seconds from funding to the invoice due date
+    pub advance_rate_bps: u32, // Investment amount as a share of the invoice amount
+    pub apr_bps: i128,         // Annualized return promised by the accepted bid
+}
+
+/// Risk inputs recorded for an investment
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RiskSnapshot {
+    Unrecorded, // Funded before risk inputs were recorded
+    Recorded(RiskInputs),
+}
+
+/// Fee and penalty parameters in force when an investment was funded. The
+/// investment settles under them even if the admin changes the configuration
+/// while it is outstanding.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeTerms {
+    pub platform_fee_bps: u32,    // Share of investor profit taken by the platform
+    pub settlement_levy_bps: u32, // Share of investor returns paid to the insurance pool
+    pub penalty_schedule: PenaltySchedule,
+    pub late_fee_bps_per_day: u32,
+}
+
+/// Fee terms recorded for an investment
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FeeSnapshot {
+    Unrecorded, // Funded before fee terms were recorded; current terms apply
+    Recorded(FeeTerms),
+}
+
+impl FeeTerms {
+    /// Fee and penalty parameters as currently configured
+    pub fn current(env: &Env) -> Self {
+        Self {
+            platform_fee_bps: ConfigStorage::get_platform_fee(env).fee_bps,
+            settlement_levy_bps: InsuranceStorage::get_pool_config(env).settlement_levy_bps,
+            penalty_schedule: get_penalty_schedule(env),
+            late_fee_bps_per_day: get_late_fee_bps_per_day(env),
+        }
+    }
+}
+
+impl Investment {
+    /// Fee terms the investment settles under
+    pub fn fee_terms(&self, env: &Env) -> FeeTerms {
+        match &self.fees {
+            FeeSnapshot::Recorded(terms) => terms.clone(),
+            FeeSnapshot::Unrecorded => FeeTerms::current(env),
+        }
+    }
+}
+
+/// Total contributed by one investor to an invoice's funding round
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FundingContribution {
+    pub investor: Address,
+    pub amount: i128,
+}
+
+/// Mark-to-model value of an active investment at a point in time. The
+/// promised profit accrues linearly from funding until the invoice due date.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PositionValue {
+    pub investment_id: BytesN<32>,
+    pub principal: i128,
+    pub expected_return: i128, // Principal plus profit promised at maturity
+    pub accrued_profit: i128,  // Profit accrued by `valued_at`
+    pub value: i128,           // Principal plus accrued profit
+    pub funded_at: u64,
+    pub maturity: u64, // Invoice due date
+    pub valued_at: u64,
 }
 
 pub struct InvestmentStorage;
@@ -24,8 +120,6 @@ pub struct InvestmentStorage;
 impl InvestmentStorage {
     /// Generate a unique investment ID using timestamp and counter
     pub fn generate_unique_investment_id(env: &Env) -> BytesN<32> {
-        use soroban_sdk::symbol_short;
-        
         let timestamp = env.ledger().timestamp();
         let counter_key = symbol_short!("inv_cnt");
         let counter = env.storage().instance().get(&counter_key).unwrap_or(0u64);
@@ -48,16 +142,163 @@ impl InvestmentStorage {
     }
     
     pub fn store_investment(env: &Env, investment: &Investment) {
-        env.storage()
-            .instance()
-            .set(&investment.investment_id, investment);
+        storage::set(env, &DataKey::Investment(investment.investment_id.clone()), investment);
+        Self::add_investment_to_invoice(env, &investment.invoice_id, &investment.investment_id);
     }
     pub fn get_investment(env: &Env, investment_id: &BytesN<32>) -> Option<Investment> {
-        env.storage().instance().get(investment_id)
+        storage::get(env, &DataKey::Investment(investment_id.clone()))
     }
     pub fn update_investment(env: &Env, investment: &Investment) {
-        env.storage()
-            .instance()
-            .set(&investment.investment_id, investment);
+        storage::set(env, &DataKey::Investment(investment.investment_id.clone()), investment);
+    }
+    pub fn get_investments_for_invoice(env: &Env, invoice_id: &BytesN<32>) -> Vec<BytesN<32>> {
+        storage::get(env, &DataKey::InvestmentList(invoice_id.clone()))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+    /// Get each investor's total contribution to an invoice, in the order
+    /// they first invested
+    pub fn get_funding_contributions(
+        env: &Env,
+        invoice_id: &BytesN<32>,
+    ) -> Vec<FundingContribution> {
+        let mut contributions: Vec<FundingContribution> = Vec::new(env);
+        for investment_id in Self::get_investments_for_invoice(env, invoice_id).iter() {
+            let investment = match Self::get_investment(env, &investment_id) {
+                Some(investment) if investment.status != InvestmentStatus::Withdrawn => investment,
+                _ => continue,
+            };
+            match contributions
+                .iter()
+                .position(|c| c.investor == investment.investor)
+            {
+                Some(index) => {
+                    let mut contribution = contributions.get(index as u32).unwrap();
+                    contribution.amount += investment.amount;
+                    contributions.set(index as u32, contribution);
+                }
+                None => contributions.push_back(FundingContribution {
+                    investor: investment.investor,
+                    amount: investment.amount,
+                }),
+            }
+        }
+        contributions
+    }
+    /// Move every investment in one invoice to another, as when an invoice
+    /// is rolled over into a new one
+    pub fn move_investments(env: &Env, from_invoice: &BytesN<32>, to_invoice: &BytesN<32>) {
+        let investment_ids = Self::get_investments_for_invoice(env, from_invoice);
+        for investment_id in investment_ids.iter() {
+            if let Some(mut investment) = Self::get_investment(env, &investment_id) {
+                investment.invoice_id = to_invoice.clone();
+                Self::update_investment(env, &investment);
+            }
+        }
+        storage::set(env, &DataKey::InvestmentList(to_invoice.clone()), &investment_ids);
+        storage::remove(env, &DataKey::InvestmentList(from_invoice.clone()));
+    }
+    fn add_investment_to_invoice(env: &Env, invoice_id: &BytesN<32>, investment_id: &BytesN<32>) {
+        let mut investments = Self::get_investments_for_invoice(env, invoice_id);
+        investments.push_back(investment_id.clone());
+        storage::set(env, &DataKey::InvestmentList(invoice_id.clone()), &investments);
     }
 }
+
+/// Split `total` across investments in proportion to their funded amounts.
+/// The last share absorbs rounding dust so the parts always sum to `total`.
+pub fn pro_rata_shares(env: &Env, investments: &Vec<Investment>, total: i128) -> Vec<i128> {
+    let mut shares = Vec::new(env);
+    let funded: i128 = investments.iter().map(|i| i.amount).sum();
+    if funded <= 0 {
+        return shares;
+    }
+    let mut allocated = 0i128;
+    let count = investments.len();
+    for (idx, investment) in investments.iter().enumerate() {
+        let share = if idx as u32 + 1 == count {
+            total - allocated
+        } else {
+            total * investment.amount / funded
+        };
+        allocated += share;
+        shares.push_back(share);
+    }
+    shares
+}
+
+/// Fee terms governing an invoice's penalties and late fees. These accrue on
+/// the invoice as a whole, so they follow the terms of its first investment.
+pub fn invoice_fee_terms(env: &Env, invoice_id: &BytesN<32>) -> FeeTerms {
+    InvestmentStorage::get_investments_for_invoice(env, invoice_id)
+        .first()
+        .and_then(|investment_id| InvestmentStorage::get_investment(env, &investment_id))
+        .map(|investment| investment.fee_terms(env))
+        .unwrap_or_else(|| FeeTerms::current(env))
+}
+
+/// Capture the risk inputs for investing `amount` in an invoice for a
+/// promised `expected_return`
+pub fn snapshot_risk_inputs(
+    env: &Env,
+    invoice: &Invoice,
+    amount: i128,
+    expected_return: i128,
+) -> RiskSnapshot {
+    let tenor = invoice.due_date.saturating_sub(env.ledger().timestamp());
+    let advance_rate_bps = if invoice.amount > 0 {
+        (amount * 10_000 / invoice.amount) as u32
+    } else {
+        0
+    };
+    RiskSnapshot::Recorded(RiskInputs {
+        risk_score: business_risk_score(env, &invoice.business),
+        tenor,
+        advance_rate_bps,
+        apr_bps: calculate_apr_bps(amount, expected_return, tenor),
+    })
+}
+
+/// Value an active investment at `at`: its pro rata share of the returns
+/// promised by the invoice's accepted bids, with the profit accrued linearly
+/// between funding and the due date.
+pub fn get_position_value(
+    env: &Env,
+    investment_id: &BytesN<32>,
+    at: u64,
+) -> Result<PositionValue, QuickLendXError> {
+    let investment = InvestmentStorage::get_investment(env, investment_id)
+        .ok_or(QuickLendXError::StorageKeyNotFound)?;
+    if investment.status != InvestmentStatus::Active {
+        return Err(QuickLendXError::InvalidStatus);
+    }
+    let invoice = InvoiceStorage::get_invoice(env, &investment.invoice_id)
+        .ok_or(QuickLendXError::InvoiceNotFound)?;
+
+    let expected_return = if invoice.funded_amount > 0 {
+        expected_returns(env, &invoice.id) * investment.amount / invoice.funded_amount
+    } else {
+        investment.amount
+    };
+    let profit = (expected_return - investment.amount).max(0);
+
+    let maturity = invoice.due_date;
+    let accrued_profit = if at <= investment.funded_at {
+        0
+    } else if at >= maturity {
+        profit
+    } else {
+        let elapsed = (at - investment.funded_at) as i128;
+        profit * elapsed / (maturity - investment.funded_at) as i128
+    };
+
+    Ok(PositionValue {
+        investment_id: investment_id.clone(),
+        principal: investment.amount,
+        expected_return,
+        accrued_profit,
+        value: investment.amount + accrued_profit,
+        funded_at: investment.funded_at,
+        maturity,
+        valued_at: at,
+    })
+}
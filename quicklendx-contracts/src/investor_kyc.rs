@@ -0,0 +1,192 @@
+use crate::config::{check_kyc_data_length, check_rejection_reason_length};
+use crate::config_history::record_config_change;
+use crate::errors::QuickLendXError;
+use crate::events::{
+    emit_investor_kyc_required_set, emit_investor_kyc_submitted, emit_investor_rejected,
+    emit_investor_verified,
+};
+use crate::storage::{self, DataKey};
+use crate::verification::{require_admin, require_verifier};
+use soroban_sdk::{contracttype, symbol_short, Address, Env, String, Vec};
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InvestorVerificationStatus {
+    Pending,
+    Verified,
+    Rejected,
+}
+
+/// An investor's KYC application and its outcome, mirroring business
+/// verification
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InvestorVerification {
+    pub investor: Address,
+    pub status: InvestorVerificationStatus,
+    pub accredited: bool, // Whether the verifier confirmed accredited-investor status
+    pub verified_at: Option<u64>,
+    pub verified_by: Option<Address>,
+    pub kyc_data: String, // Encrypted KYC data
+    pub submitted_at: u64,
+    pub rejection_reason: Option<String>,
+}
+
+pub struct InvestorVerificationStorage;
+
+impl InvestorVerificationStorage {
+    /// Get an investor's KYC record, if it has applied
+    pub fn get_verification(env: &Env, investor: &Address) -> Option<InvestorVerification> {
+        storage::get(env, &DataKey::InvestorVerification(investor.clone()))
+    }
+
+    /// Get the investors whose KYC is in a status, in the order they got there
+    pub fn get_investors_by_status(env: &Env, status: InvestorVerificationStatus) -> Vec<Address> {
+        storage::get(env, &DataKey::InvestorsByStatus(status)).unwrap_or_else(|| Vec::new(env))
+    }
+
+    /// Whether an investor's KYC has been verified
+    pub fn is_investor_verified(env: &Env, investor: &Address) -> bool {
+        Self::get_verification(env, investor)
+            .is_some_and(|verification| verification.status == InvestorVerificationStatus::Verified)
+    }
+
+    /// Whether investors must be verified before they bid
+    pub fn is_verification_required(env: &Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("inv_kyc"))
+            .unwrap_or(false)
+    }
+
+    /// Store a KYC record, moving the investor to the list of its new status
+    fn set_verification(env: &Env, verification: &InvestorVerification) {
+        let investor = &verification.investor;
+        if let Some(old) = Self::get_verification(env, investor) {
+            let mut investors = Self::get_investors_by_status(env, old.status);
+            if let Some(index) = investors.first_index_of(investor) {
+                investors.remove(index);
+                storage::set(env, &DataKey::InvestorsByStatus(old.status), &investors);
+            }
+        }
+        let mut investors = Self::get_investors_by_status(env, verification.status);
+        investors.push_back(investor.clone());
+        storage::set(
+            env,
+            &DataKey::InvestorsByStatus(verification.status),
+            &investors,
+        );
+        storage::set(
+            env,
+            &DataKey::InvestorVerification(investor.clone()),
+            verification,
+        );
+    }
+}
+
+/// Apply for investor KYC (investor only). Rejected investors may apply
+/// again.
+pub fn submit_investor_kyc(
+    env: &Env,
+    investor: &Address,
+    kyc_data: String,
+) -> Result<(), QuickLendXError> {
+    investor.require_auth();
+    check_kyc_data_length(env, &kyc_data)?;
+    if let Some(existing) = InvestorVerificationStorage::get_verification(env, investor) {
+        match existing.status {
+            InvestorVerificationStatus::Pending => return Err(QuickLendXError::KYCAlreadyPending),
+            InvestorVerificationStatus::Verified => {
+                return Err(QuickLendXError::KYCAlreadyVerified)
+            }
+            InvestorVerificationStatus::Rejected => {}
+        }
+    }
+
+    let verification = InvestorVerification {
+        investor: investor.clone(),
+        status: InvestorVerificationStatus::Pending,
+        accredited: false,
+        verified_at: None,
+        verified_by: None,
+        kyc_data,
+        submitted_at: env.ledger().timestamp(),
+        rejection_reason: None,
+    };
+    InvestorVerificationStorage::set_verification(env, &verification);
+    emit_investor_kyc_submitted(env, investor);
+    Ok(())
+}
+
+fn get_pending(env: &Env, investor: &Address) -> Result<InvestorVerification, QuickLendXError> {
+    let verification = InvestorVerificationStorage::get_verification(env, investor)
+        .ok_or(QuickLendXError::KYCNotFound)?;
+    if verification.status != InvestorVerificationStatus::Pending {
+        return Err(QuickLendXError::InvalidKYCStatus);
+    }
+    Ok(verification)
+}
+
+/// Verify a pending investor, recording whether it is accredited (admin or
+/// verifier only)
+pub fn verify_investor(
+    env: &Env,
+    verifier: &Address,
+    investor: &Address,
+    accredited: bool,
+) -> Result<(), QuickLendXError> {
+    require_verifier(env, verifier)?;
+    let mut verification = get_pending(env, investor)?;
+    verification.status = InvestorVerificationStatus::Verified;
+    verification.accredited = accredited;
+    verification.verified_at = Some(env.ledger().timestamp());
+    verification.verified_by = Some(verifier.clone());
+    InvestorVerificationStorage::set_verification(env, &verification);
+    emit_investor_verified(env, investor, accredited, verifier);
+    Ok(())
+}
+
+/// Reject a pending investor with a reason (admin or verifier only)
+pub fn reject_investor(
+    env: &Env,
+    verifier: &Address,
+    investor: &Address,
+    reason: String,
+) -> Result<(), QuickLendXError> {
+    require_verifier(env, verifier)?;
+    check_rejection_reason_length(env, &reason)?;
+    let mut verification = get_pending(env, investor)?;
+    verification.status = InvestorVerificationStatus::Rejected;
+    verification.rejection_reason = Some(reason);
+    InvestorVerificationStorage::set_verification(env, &verification);
+    emit_investor_rejected(env, investor, verifier);
+    Ok(())
+}
+
+/// Require investors to be verified before they bid, or stop requiring it
+/// (admin only). Off until the admin turns it on, so existing investors are
+/// not locked out by an upgrade.
+pub fn set_investor_kyc_required(
+    env: &Env,
+    admin: &Address,
+    required: bool,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    env.storage()
+        .instance()
+        .set(&symbol_short!("inv_kyc"), &required);
+    record_config_change(env, "investor_kyc_required", required, admin);
+    emit_investor_kyc_required_set(env, required, admin);
+    Ok(())
+}
+
+/// Reject bids from investors who are not verified, when verification is
+/// required
+pub fn require_investor_verification(env: &Env, investor: &Address) -> Result<(), QuickLendXError> {
+    if InvestorVerificationStorage::is_verification_required(env)
+        && !InvestorVerificationStorage::is_investor_verified(env, investor)
+    {
+        return Err(QuickLendXError::InvestorNotVerified);
+    }
+    Ok(())
+}
@@ -1,19 +1,28 @@
-use soroban_sdk::{contracttype, symbol_short, vec, Address, BytesN, Env, Map, String, Vec};
+use soroban_sdk::{contracttype, symbol_short, vec, Address, BytesN, Env, String, Symbol, Vec};
+
+/// Most tags an invoice may carry
+pub const MAX_INVOICE_TAGS: u32 = 5;
+
+/// Most supplementary documents an invoice may carry
+pub const MAX_INVOICE_DOCUMENTS: u32 = 10;
 
 /// Invoice status enumeration
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum InvoiceStatus {
-    Pending,   // Invoice uploaded, awaiting verification
-    Verified,  // Invoice verified and available for bidding
-    Funded,    // Invoice has been funded by an investor
-    Paid,      // Invoice has been paid and settled
-    Defaulted, // Invoice payment is overdue/defaulted
+    Pending,    // Invoice uploaded, awaiting verification
+    Verified,   // Invoice verified and available for bidding
+    Funded,     // Invoice has been funded by an investor
+    Paid,       // Invoice has been paid and settled
+    Defaulted,  // Invoice payment is overdue/defaulted
+    Cancelled,  // Invoice withdrawn by the business before funding
+    Expired,    // Funding deadline passed without an accepted bid
+    Refinanced, // Rolled over into a new invoice before it was paid
 }
 
 /// Invoice rating structure
 #[contracttype]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct InvoiceRating {
     pub rating: u32,       // 1-5 stars
     pub feedback: String,  // Feedback text
@@ -21,34 +30,72 @@ pub struct InvoiceRating {
     pub rated_at: u64,     // Timestamp of rating
 }
 
+/// A supplementary document anchored to an invoice by its hash
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InvoiceDocument {
+    pub hash: BytesN<32>, // SHA-256 of the document, or the digest of its IPFS CID
+    pub name: String,
+    pub added_at: u64,
+}
+
+/// Where a due-date extension request stands
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExtensionStatus {
+    Requested,
+    AwaitingInvestors, // Approved by a verifier, waiting on the investors' vote
+    Approved,
+    Rejected,
+}
+
+/// A business's one-time request to push back a funded invoice's due date
+/// because its debtor is documented to be paying late
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DueDateExtension {
+    pub original_due_date: u64,
+    pub new_due_date: u64,
+    pub evidence_hash: BytesN<32>, // Hash of the evidence of the debtor's delay
+    pub status: ExtensionStatus,
+    pub requested_at: u64,
+    pub decided_by: Option<Address>, // Verifier who approved or rejected it
+    pub decided_at: Option<u64>,
+}
+
 /// Core invoice data structure
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Invoice {
-    pub id: BytesN<32>,              // Unique invoice identifier
-    pub business: Address,           // Business that uploaded the invoice
-    pub amount: i128,                // Total invoice amount
-    pub currency: Address,           // Currency token address (XLM = Address::random())
-    pub due_date: u64,               // Due date timestamp
-    pub status: InvoiceStatus,       // Current status of the invoice
-    pub created_at: u64,             // Creation timestamp
-    pub description: String,         // Invoice description/metadata
-    pub funded_amount: i128,         // Amount funded by investors
-    pub funded_at: Option<u64>,      // When the invoice was funded
-    pub investor: Option<Address>,   // Address of the investor who funded
-    pub settled_at: Option<u64>,     // When the invoice was settled
-    pub average_rating: Option<u32>, // Average rating (1-5)
-    pub total_ratings: u32,          // Total number of ratings
-    pub ratings: Vec<InvoiceRating>, // List of all ratings
+    pub id: BytesN<32>,                   // Unique invoice identifier
+    pub business: Address,                // Business that uploaded the invoice
+    pub amount: i128,                     // Total invoice amount
+    pub currency: Address,                // Currency token address (XLM = Address::random())
+    pub due_date: u64,                    // Due date timestamp
+    pub extensions: Vec<DueDateExtension>, // Due-date extension requested, at most one
+    pub status: InvoiceStatus,            // Current status of the invoice
+    pub created_at: u64,                  // Creation timestamp
+    pub description: String,              // Invoice description/metadata
+    pub invoice_hash: Option<BytesN<32>>, // Hash of the invoice document, if given at upload
+    pub documents: Vec<InvoiceDocument>,  // Supplementary documents added since
+    pub category: Option<Symbol>,         // Sector, e.g. logistics or SaaS
+    pub tags: Vec<Symbol>,                // Free-form labels investors can filter by
+    pub funded_amount: i128,              // Amount funded by investors
+    pub funded_at: Option<u64>,           // When the invoice was fully funded
+    pub investor: Option<Address>,        // Address of the first (lead) investor
+    pub investors: Vec<Address>,          // All investors holding a share of the invoice
+    pub settled_at: Option<u64>,          // When the invoice was settled
+    pub average_rating: Option<u32>,      // Average rating (1-5)
+    pub total_ratings: u32,               // Total number of ratings
+    pub ratings: Vec<InvoiceRating>,      // List of all ratings
 }
 
 // Use the main error enum from errors.rs
 use crate::errors::QuickLendXError;
-
-use crate::audit::{log_invoice_created, log_invoice_status_change, log_invoice_funded};
+use crate::storage::{self, DataKey};
 
 impl Invoice {
-    /// Create a new invoice with audit logging
+    /// Create a new invoice
     pub fn new(
         env: &Env,
         business: Address,
@@ -56,37 +103,67 @@ impl Invoice {
         currency: Address,
         due_date: u64,
         description: String,
+        invoice_hash: Option<BytesN<32>>,
     ) -> Self {
         let id = Self::generate_unique_invoice_id(env);
         let created_at = env.ledger().timestamp();
 
-        let invoice = Self {
+        Self {
             id,
             business,
             amount,
             currency,
             due_date,
+            extensions: vec![env],
             status: InvoiceStatus::Pending,
             created_at,
             description,
+            invoice_hash,
+            documents: vec![env],
+            category: None,
+            tags: vec![env],
             funded_amount: 0,
             funded_at: None,
             investor: None,
+            investors: vec![env],
             settled_at: None,
             average_rating: None,
             total_ratings: 0,
             ratings: vec![env],
         }
-        
-        // Log invoice creation
-        log_invoice_created(env, &invoice);
-        
-        invoice
+    }
+
+    /// Generate a unique invoice ID using timestamp and counter
+    fn generate_unique_invoice_id(env: &Env) -> BytesN<32> {
+        let timestamp = env.ledger().timestamp();
+        let counter_key = symbol_short!("invc_cnt");
+        let counter: u64 = env.storage().instance().get(&counter_key).unwrap_or(0u64);
+        env.storage().instance().set(&counter_key, &(counter + 1));
+
+        let mut id_bytes = [0u8; 32];
+        // Add invoice prefix to distinguish from other entity types
+        id_bytes[0] = 0x1C; // 'I' for Invoice
+        id_bytes[1] = 0x0E; // 'O' for invOice
+        // Embed timestamp in next 8 bytes
+        id_bytes[2..10].copy_from_slice(&timestamp.to_be_bytes());
+        // Embed counter in next 8 bytes
+        id_bytes[10..18].copy_from_slice(&counter.to_be_bytes());
+        // Fill remaining bytes with a pattern to ensure uniqueness
+        for byte in id_bytes.iter_mut().skip(18) {
+            *byte = ((timestamp + counter + 0x1C0E) % 256) as u8;
+        }
+
+        BytesN::from_array(env, &id_bytes)
     }
 
     /// Check if invoice is available for funding
     pub fn is_available_for_funding(&self) -> bool {
-        self.status == InvoiceStatus::Verified && self.funded_amount == 0
+        self.status == InvoiceStatus::Verified && self.funded_amount < self.amount
+    }
+
+    /// Amount still open for funding by investors
+    pub fn remaining_funding(&self) -> i128 {
+        self.amount - self.funded_amount
     }
 
     /// Check if invoice is overdue
@@ -94,36 +171,109 @@ impl Invoice {
         current_timestamp > self.due_date
     }
 
-    /// Mark invoice as funded with audit logging
-    pub fn mark_as_funded(&mut self, env: &Env, investor: Address, funded_amount: i128, timestamp: u64) {
-        let old_status = self.status.clone();
-        self.status = InvoiceStatus::Funded;
-        self.funded_amount = funded_amount;
-        self.funded_at = Some(timestamp);
-        self.investor = Some(investor.clone());
-        
-        // Log status change and funding
-        log_invoice_status_change(env, self.id.clone(), investor.clone(), old_status, self.status.clone());
-        log_invoice_funded(env, self.id.clone(), investor, funded_amount);
+    /// Record a funding contribution from an investor.
+    /// The invoice only becomes Funded once the full amount is covered.
+    pub fn mark_as_funded(&mut self, investor: Address, funded_amount: i128, timestamp: u64) {
+        if self.investor.is_none() {
+            self.investor = Some(investor.clone());
+        }
+        if !self.investors.contains(&investor) {
+            self.investors.push_back(investor);
+        }
+        self.funded_amount += funded_amount;
+        if self.funded_amount >= self.amount {
+            self.status = InvoiceStatus::Funded;
+            self.funded_at = Some(timestamp);
+        }
+    }
+
+    /// Drop all funding and reopen the invoice for bids
+    pub fn reset_funding(&mut self, env: &Env) {
+        self.status = InvoiceStatus::Verified;
+        self.funded_amount = 0;
+        self.funded_at = None;
+        self.investor = None;
+        self.investors = Vec::new(env);
+    }
+
+    /// Check if an address holds a share of the invoice
+    pub fn is_investor(&self, address: &Address) -> bool {
+        self.investors.contains(address)
     }
 
-    /// Mark invoice as paid with audit logging
-    pub fn mark_as_paid(&mut self, env: &Env, actor: Address, timestamp: u64) {
-        let old_status = self.status.clone();
+    /// Mark invoice as paid
+    pub fn mark_as_paid(&mut self, timestamp: u64) {
         self.status = InvoiceStatus::Paid;
         self.settled_at = Some(timestamp);
-        
-        // Log status change
-        log_invoice_status_change(env, self.id.clone(), actor, old_status, self.status.clone());
     }
 
-    /// Verify the invoice with audit logging
-    pub fn verify(&mut self, env: &Env, actor: Address) {
-        let old_status = self.status.clone();
+    /// Mark invoice as defaulted
+    pub fn mark_as_defaulted(&mut self) {
+        self.status = InvoiceStatus::Defaulted;
+    }
+
+    /// Mark invoice as cancelled
+    pub fn mark_as_cancelled(&mut self) {
+        self.status = InvoiceStatus::Cancelled;
+    }
+
+    /// Mark invoice as expired
+    pub fn mark_as_expired(&mut self) {
+        self.status = InvoiceStatus::Expired;
+    }
+
+    /// Verify the invoice
+    pub fn verify(&mut self) {
         self.status = InvoiceStatus::Verified;
-        
-        // Log status change
-        log_invoice_status_change(env, self.id.clone(), actor, old_status, self.status.clone());
+    }
+
+    /// Add a rating from one of the invoice's investors
+    pub fn add_rating(
+        &mut self,
+        rating: u32,
+        feedback: String,
+        rater: Address,
+        timestamp: u64,
+    ) -> Result<(), QuickLendXError> {
+        if !(1..=5).contains(&rating) {
+            return Err(QuickLendXError::InvalidRating);
+        }
+        if self.status != InvoiceStatus::Funded && self.status != InvoiceStatus::Paid {
+            return Err(QuickLendXError::NotFunded);
+        }
+        if !self.is_investor(&rater) {
+            return Err(QuickLendXError::NotRater);
+        }
+        if self.ratings.iter().any(|r| r.rated_by == rater) {
+            return Err(QuickLendXError::AlreadyRated);
+        }
+
+        self.ratings.push_back(InvoiceRating {
+            rating,
+            feedback,
+            rated_by: rater,
+            rated_at: timestamp,
+        });
+        self.total_ratings += 1;
+
+        let sum: u32 = self.ratings.iter().map(|r| r.rating).sum();
+        self.average_rating = Some(sum / self.total_ratings);
+        Ok(())
+    }
+
+    /// Check if the invoice has any ratings
+    pub fn has_ratings(&self) -> bool {
+        self.total_ratings > 0
+    }
+
+    /// Get the highest rating received
+    pub fn get_highest_rating(&self) -> Option<u32> {
+        self.ratings.iter().map(|r| r.rating).max()
+    }
+
+    /// Get the lowest rating received
+    pub fn get_lowest_rating(&self) -> Option<u32> {
+        self.ratings.iter().map(|r| r.rating).min()
     }
 }
 
@@ -133,74 +283,65 @@ pub struct InvoiceStorage;
 impl InvoiceStorage {
     /// Store an invoice
     pub fn store_invoice(env: &Env, invoice: &Invoice) {
-        env.storage().instance().set(&invoice.id, invoice);
+        storage::set(env, &DataKey::Invoice(invoice.id.clone()), invoice);
 
         // Add to business invoices list
         Self::add_to_business_invoices(env, &invoice.business, &invoice.id);
 
         // Add to status invoices list
         Self::add_to_status_invoices(env, &invoice.status, &invoice.id);
+
+        // Add to category and tag lists
+        Self::index_metadata(env, invoice);
     }
 
     /// Get an invoice by ID
     pub fn get_invoice(env: &Env, invoice_id: &BytesN<32>) -> Option<Invoice> {
-        env.storage().instance().get(invoice_id)
+        storage::get(env, &DataKey::Invoice(invoice_id.clone()))
     }
 
     /// Update an invoice
     pub fn update_invoice(env: &Env, invoice: &Invoice) {
-        env.storage().instance().set(&invoice.id, invoice);
+        storage::set(env, &DataKey::Invoice(invoice.id.clone()), invoice);
     }
 
     /// Get all invoices for a business
     pub fn get_business_invoices(env: &Env, business: &Address) -> Vec<BytesN<32>> {
-        let key = (symbol_short!("business"), business.clone());
-        env.storage().instance().get(&key).unwrap_or_else(|| Vec::new(env))
+        storage::get(env, &DataKey::BusinessInvoices(business.clone()))
+            .unwrap_or_else(|| Vec::new(env))
     }
 
     /// Get all invoices by status
     pub fn get_invoices_by_status(env: &Env, status: &InvoiceStatus) -> Vec<BytesN<32>> {
-        let key = match status {
-            InvoiceStatus::Pending => symbol_short!("pending"),
-            InvoiceStatus::Verified => symbol_short!("verified"),
-            InvoiceStatus::Funded => symbol_short!("funded"),
-            InvoiceStatus::Paid => symbol_short!("paid"),
-            InvoiceStatus::Defaulted => symbol_short!("default"),
-        };
-        env.storage().instance().get(&key).unwrap_or_else(|| Vec::new(env))
+        storage::get(env, &DataKey::StatusInvoices(status.clone()))
+            .unwrap_or_else(|| Vec::new(env))
     }
 
     /// Add invoice to business invoices list
     fn add_to_business_invoices(env: &Env, business: &Address, invoice_id: &BytesN<32>) {
-        let key = (symbol_short!("business"), business.clone());
         let mut invoices = Self::get_business_invoices(env, business);
         invoices.push_back(invoice_id.clone());
-        env.storage().instance().set(&key, &invoices);
+        storage::set(env, &DataKey::BusinessInvoices(business.clone()), &invoices);
+    }
+
+    /// Remove invoice from business invoices list
+    pub fn remove_from_business_invoices(env: &Env, business: &Address, invoice_id: &BytesN<32>) {
+        let mut invoices = Self::get_business_invoices(env, business);
+        if let Some(index) = invoices.first_index_of(invoice_id) {
+            invoices.remove(index);
+            storage::set(env, &DataKey::BusinessInvoices(business.clone()), &invoices);
+        }
     }
 
     /// Add invoice to status invoices list
     pub fn add_to_status_invoices(env: &Env, status: &InvoiceStatus, invoice_id: &BytesN<32>) {
-        let key = match status {
-            InvoiceStatus::Pending => symbol_short!("pending"),
-            InvoiceStatus::Verified => symbol_short!("verified"),
-            InvoiceStatus::Funded => symbol_short!("funded"),
-            InvoiceStatus::Paid => symbol_short!("paid"),
-            InvoiceStatus::Defaulted => symbol_short!("default"),
-        };
-        let mut invoices = env.storage().instance().get(&key).unwrap_or_else(|| Vec::new(env));
+        let mut invoices = Self::get_invoices_by_status(env, status);
         invoices.push_back(invoice_id.clone());
-        env.storage().instance().set(&key, &invoices);
+        storage::set(env, &DataKey::StatusInvoices(status.clone()), &invoices);
     }
 
     /// Remove invoice from status invoices list
     pub fn remove_from_status_invoices(env: &Env, status: &InvoiceStatus, invoice_id: &BytesN<32>) {
-        let key = match status {
-            InvoiceStatus::Pending => symbol_short!("pending"),
-            InvoiceStatus::Verified => symbol_short!("verified"),
-            InvoiceStatus::Funded => symbol_short!("funded"),
-            InvoiceStatus::Paid => symbol_short!("paid"),
-            InvoiceStatus::Defaulted => symbol_short!("default"),
-        };
         let invoices = Self::get_invoices_by_status(env, status);
 
         // Find and remove the invoice ID
@@ -211,7 +352,75 @@ impl InvoiceStorage {
             }
         }
 
-        env.storage().instance().set(&key, &new_invoices);
+        storage::set(env, &DataKey::StatusInvoices(status.clone()), &new_invoices);
+    }
+
+    /// Get all invoices in a category
+    pub fn get_invoices_by_category(env: &Env, category: &Symbol) -> Vec<BytesN<32>> {
+        storage::get(env, &DataKey::CategoryInvoices(category.clone()))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    /// Get all invoices carrying a tag
+    pub fn get_invoices_by_tag(env: &Env, tag: &Symbol) -> Vec<BytesN<32>> {
+        storage::get(env, &DataKey::TagInvoices(tag.clone())).unwrap_or_else(|| Vec::new(env))
+    }
+
+    /// Replace an invoice's category and tags, moving it between the category
+    /// and tag lists
+    pub fn set_metadata(
+        env: &Env,
+        invoice: &mut Invoice,
+        category: Option<Symbol>,
+        tags: Vec<Symbol>,
+    ) {
+        Self::unindex_metadata(env, invoice);
+        invoice.category = category;
+        invoice.tags = tags;
+        Self::update_invoice(env, invoice);
+        Self::index_metadata(env, invoice);
+    }
+
+    /// Add invoice to the lists of its category and tags
+    pub fn index_metadata(env: &Env, invoice: &Invoice) {
+        if let Some(category) = &invoice.category {
+            let key = DataKey::CategoryInvoices(category.clone());
+            Self::add_to_index(env, &key, &invoice.id);
+        }
+        for tag in invoice.tags.iter() {
+            Self::add_to_index(env, &DataKey::TagInvoices(tag), &invoice.id);
+        }
+    }
+
+    /// Remove invoice from the lists of its category and tags
+    pub fn unindex_metadata(env: &Env, invoice: &Invoice) {
+        if let Some(category) = &invoice.category {
+            let key = DataKey::CategoryInvoices(category.clone());
+            Self::remove_from_index(env, &key, &invoice.id);
+        }
+        for tag in invoice.tags.iter() {
+            Self::remove_from_index(env, &DataKey::TagInvoices(tag), &invoice.id);
+        }
+    }
+
+    fn add_to_index(env: &Env, key: &DataKey, invoice_id: &BytesN<32>) {
+        let mut invoices: Vec<BytesN<32>> = storage::get(env, key).unwrap_or_else(|| Vec::new(env));
+        if !invoices.contains(invoice_id) {
+            invoices.push_back(invoice_id.clone());
+            storage::set(env, key, &invoices);
+        }
+    }
+
+    fn remove_from_index(env: &Env, key: &DataKey, invoice_id: &BytesN<32>) {
+        let mut invoices: Vec<BytesN<32>> = storage::get(env, key).unwrap_or_else(|| Vec::new(env));
+        if let Some(index) = invoices.first_index_of(invoice_id) {
+            invoices.remove(index);
+            if invoices.is_empty() {
+                storage::remove(env, key);
+            } else {
+                storage::set(env, key, &invoices);
+            }
+        }
     }
 
     /// Get invoices with ratings above a threshold
@@ -270,4 +479,4 @@ impl InvoiceStorage {
         }
         count
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,184 @@
+use crate::errors::QuickLendXError;
+use crate::events::{
+    emit_jurisdiction_assigned, emit_jurisdiction_rule_removed, emit_jurisdiction_rule_set,
+};
+use crate::invoice::Invoice;
+use crate::profits::{calculate_apr_bps, max_return_for_apr};
+use crate::storage::{self, DataKey};
+use crate::verification::require_admin;
+use soroban_sdk::{contracttype, symbol_short, Address, Env, Symbol, Vec};
+
+/// Rules applied to invoices of businesses registered in a jurisdiction
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JurisdictionRule {
+    pub code: Symbol,                        // Jurisdiction code (e.g. "US", "EU")
+    pub max_apr_bps: u32,                    // Maximum investor APR in bps, 0 = no cap
+    pub allowed_currencies: Vec<Address>,    // Currencies invoices may use, empty = any
+    pub investor_jurisdictions: Vec<Symbol>, // Investor jurisdictions allowed to bid, empty = any
+}
+
+pub struct JurisdictionStorage;
+
+impl JurisdictionStorage {
+    /// Get the jurisdiction code registered for a business or investor
+    pub fn get_jurisdiction(env: &Env, address: &Address) -> Option<Symbol> {
+        storage::get(env, &DataKey::Jurisdiction(address.clone()))
+    }
+
+    pub fn set_jurisdiction(env: &Env, address: &Address, code: &Symbol) {
+        storage::set(env, &DataKey::Jurisdiction(address.clone()), code);
+    }
+
+    /// Get the rule for a jurisdiction code
+    pub fn get_rule(env: &Env, code: &Symbol) -> Option<JurisdictionRule> {
+        storage::get(env, &DataKey::JurisdictionRule(code.clone()))
+    }
+
+    /// Get all jurisdiction codes that have a rule
+    pub fn get_rule_codes(env: &Env) -> Vec<Symbol> {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("jur_codes"))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    pub fn store_rule(env: &Env, rule: &JurisdictionRule) {
+        storage::set(env, &DataKey::JurisdictionRule(rule.code.clone()), rule);
+        let mut codes = Self::get_rule_codes(env);
+        if !codes.contains(&rule.code) {
+            codes.push_back(rule.code.clone());
+            env.storage()
+                .instance()
+                .set(&symbol_short!("jur_codes"), &codes);
+        }
+    }
+
+    pub fn remove_rule(env: &Env, code: &Symbol) -> bool {
+        let mut codes = Self::get_rule_codes(env);
+        match codes.first_index_of(code) {
+            Some(index) => {
+                codes.remove(index);
+                env.storage()
+                    .instance()
+                    .set(&symbol_short!("jur_codes"), &codes);
+                storage::remove(env, &DataKey::JurisdictionRule(code.clone()));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Get the rule that applies to a business, if its jurisdiction has one
+    pub fn get_rule_for(env: &Env, address: &Address) -> Option<JurisdictionRule> {
+        Self::get_jurisdiction(env, address).and_then(|code| Self::get_rule(env, &code))
+    }
+}
+
+/// Assign a jurisdiction code to a business or investor (admin only)
+pub fn set_jurisdiction(
+    env: &Env,
+    admin: &Address,
+    address: &Address,
+    code: &Symbol,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    JurisdictionStorage::set_jurisdiction(env, address, code);
+    emit_jurisdiction_assigned(env, address, code);
+    Ok(())
+}
+
+/// Create or replace the rule for a jurisdiction (admin only)
+pub fn set_jurisdiction_rule(
+    env: &Env,
+    admin: &Address,
+    rule: &JurisdictionRule,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    JurisdictionStorage::store_rule(env, rule);
+    emit_jurisdiction_rule_set(env, rule);
+    Ok(())
+}
+
+/// Remove the rule for a jurisdiction (admin only)
+pub fn remove_jurisdiction_rule(
+    env: &Env,
+    admin: &Address,
+    code: &Symbol,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    if !JurisdictionStorage::remove_rule(env, code) {
+        return Err(QuickLendXError::StorageKeyNotFound);
+    }
+    emit_jurisdiction_rule_removed(env, code);
+    Ok(())
+}
+
+/// Upload path: the business's jurisdiction must allow the invoice currency
+pub fn check_upload(
+    env: &Env,
+    business: &Address,
+    currency: &Address,
+) -> Result<(), QuickLendXError> {
+    if let Some(rule) = JurisdictionStorage::get_rule_for(env, business) {
+        if !rule.allowed_currencies.is_empty() && !rule.allowed_currencies.contains(currency) {
+            return Err(QuickLendXError::InvalidCurrency);
+        }
+    }
+    Ok(())
+}
+
+/// Bid path: the investor must be eligible and the bid's implied APR within the cap
+pub fn check_bid(
+    env: &Env,
+    business: &Address,
+    investor: &Address,
+    bid_amount: i128,
+    expected_return: i128,
+    due_date: u64,
+) -> Result<(), QuickLendXError> {
+    let rule = match JurisdictionStorage::get_rule_for(env, business) {
+        Some(rule) => rule,
+        None => return Ok(()),
+    };
+
+    if !rule.investor_jurisdictions.is_empty() {
+        let eligible = JurisdictionStorage::get_jurisdiction(env, investor)
+            .map(|code| rule.investor_jurisdictions.contains(&code))
+            .unwrap_or(false);
+        if !eligible {
+            return Err(QuickLendXError::JurisdictionNotAllowed);
+        }
+    }
+
+    if rule.max_apr_bps > 0 {
+        let duration = due_date.saturating_sub(env.ledger().timestamp());
+        let apr_bps = calculate_apr_bps(bid_amount, expected_return, duration);
+        if apr_bps > rule.max_apr_bps as i128 {
+            return Err(QuickLendXError::AprLimitExceeded);
+        }
+    }
+    Ok(())
+}
+
+/// Settlement path: cap what an investor who invested `invested` may receive, after
+/// platform fees, at the jurisdiction's maximum APR. The APR is taken over the
+/// invoice's tenor, from funding to the due date, as bids are checked, so paying
+/// early or late does not move the cap. Fees are not capped; any return above the
+/// cap is not collected.
+pub fn cap_investor_return(
+    env: &Env,
+    invoice: &Invoice,
+    invested: i128,
+    investor_return: i128,
+) -> i128 {
+    match JurisdictionStorage::get_rule_for(env, &invoice.business) {
+        Some(rule) if rule.max_apr_bps > 0 => {
+            let funded_at = invoice.funded_at.unwrap_or(invoice.created_at);
+            let tenor = invoice.due_date.saturating_sub(funded_at);
+            let cap = max_return_for_apr(invested, rule.max_apr_bps as i128, tenor);
+            investor_return.min(cap)
+        }
+        _ => investor_return,
+    }
+}
@@ -1,42 +1,177 @@
 #![no_std]
 use soroban_sdk::{
-    contract, contractimpl, contracttype, symbol_short, vec, Address, BytesN, Env, String, Vec,
+    contract, contractimpl, contracttype, symbol_short, vec, Address, BytesN, Env, String, Symbol,
+    Vec,
 };
 
+mod acceptance;
+mod admin_log;
+mod analytics;
+mod archive;
+mod attestation;
+mod auction;
+mod auto_accept;
+mod auto_verify;
+mod balances;
+mod backstop;
 mod backup;
 mod bid;
+mod compliance;
+mod config;
+mod config_history;
+mod credit;
+mod debt_market;
 mod defaults;
+mod delinquency;
+mod disputes;
 mod errors;
+mod escheat;
 mod events;
+mod expiry;
+mod export;
+mod extension;
+mod insurance;
 mod investment;
+mod investor_kyc;
 mod invoice;
+mod jurisdiction;
+mod limits;
+mod maturity;
+mod negotiation;
+mod notifications;
+mod observers;
+mod oracle;
 mod payments;
+mod penalty;
+mod priority;
+mod profile;
 mod profits;
+mod reinvest;
+mod reminders;
+mod reverse_factoring;
+mod rollover;
 mod settlement;
+mod stop_loss;
+mod storage;
+mod templates;
+mod terms;
+mod tranche;
+mod treasury;
+mod upgrade;
 mod verification;
+mod watchdog;
 mod audit;
 
+use backstop::{BackstopProvider, BackstopStorage};
 use bid::{Bid, BidStatus, BidStorage};
-use defaults::handle_default as do_handle_default;
+use acceptance::{AcceptanceStorage, PendingAcceptance};
+use analytics::{
+    AnalyticsStorage, BusinessStats, CurrencyStats, InvestorReturns, PlatformStats,
+};
+use admin_log::{record_admin_action, AdminAction, AdminLogStorage};
+use archive::{ArchiveStorage, ArchivedInvoice};
+use attestation::{
+    AttestationIssuer, AttestationStatus, AttestationStorage, FinancialAttestation,
+    ImportedAttestation,
+};
+use auction::{Auction, AuctionStorage};
+use auto_accept::{AutoAcceptRule, AutoAcceptStorage};
+use auto_verify::{AutoVerifyRule, AutoVerifyStorage};
+use balances::{BalanceStorage, PendingWithdrawal, WithdrawalPolicy};
+use compliance::{
+    check_not_blacklisted, check_not_self_dealing, flag_recycled_funding, BlacklistEntry,
+    ComplianceStorage, WashFlag,
+};
+use config::{
+    add_supported_currency, check_description_length, check_feedback_length, check_investor_cap,
+    check_min_ticket, remove_supported_currency, ConfigStorage,
+    PlatformFeeConfig, SizeLimits,
+};
+use config_history::{ConfigChange, ConfigHistoryStorage};
+use credit::{check_credit_limit, CreditStorage};
+use debt_market::{ClaimListing, DebtMarketStorage};
+use defaults::{
+    check_overdue_invoices as do_check_overdue_invoices, handle_default as do_handle_default,
+    DefaultRecovery,
+};
+use delinquency::DelinquencyStage;
+use disputes::{Dispute, DisputeOutcome, DisputeStorage};
 use errors::QuickLendXError;
+use escheat::{DormancyNotice, EscheatStorage, EscheatedBalance};
 use events::{
-    emit_escrow_created, emit_escrow_refunded, emit_escrow_released, emit_invoice_uploaded,
-    emit_invoice_verified,
+    emit_audit_query, emit_audit_validation, emit_bid_accepted, emit_bid_auto_accepted,
+    emit_bid_placed, emit_bid_rejected, emit_bid_withdrawn, emit_escrow_created,
+    emit_escrow_refunded, emit_escrow_released, emit_funding_completed,
+    emit_investment_created, emit_invoice_amended, emit_invoice_auto_verified,
+    emit_invoice_cancelled, emit_invoice_document_added, emit_invoice_metadata_set,
+    emit_invoice_rating_updated, emit_invoice_uploaded, emit_invoice_verified,
+    EVENT_SCHEMA_VERSION,
+};
+use expiry::is_funding_expired;
+use export::BusinessDataExport;
+use extension::ExtensionVote;
+use insurance::{
+    cover_investment, ClaimStatus, InsuranceClaim, InsurancePoolConfig, InsuranceStorage,
+    PremiumRates,
+};
+use investment::{
+    snapshot_risk_inputs, FeeSnapshot, FundingContribution, Investment, InvestmentStatus,
+    InvestmentStorage, PositionValue,
+};
+use investor_kyc::{
+    require_investor_verification, InvestorVerification, InvestorVerificationStatus,
+    InvestorVerificationStorage,
 };
-use investment::{Investment, InvestmentStatus, InvestmentStorage};
-use invoice::{Invoice, InvoiceStatus, InvoiceStorage};
-use payments::{create_escrow, refund_escrow, release_escrow, EscrowStorage};
+use invoice::{
+    DueDateExtension, Invoice, InvoiceDocument, InvoiceStatus, InvoiceStorage,
+    MAX_INVOICE_DOCUMENTS, MAX_INVOICE_TAGS,
+};
+use jurisdiction::{JurisdictionRule, JurisdictionStorage};
+use limits::{LimitKind, LimitRequest, LimitStorage};
+use maturity::{record_funding, release_funding, MaturityLadder, MaturityStorage};
+use negotiation::{BidNegotiation, NegotiationStorage};
+use notifications::{notify, InboxSummary, Notification, NotificationKind, NotificationStorage};
+use observers::{notify_observers, LifecycleEvent, ObserverStorage};
+use penalty::{AmountDue, PenaltySchedule};
+use payments::{
+    create_escrow, refund_escrow, release_escrow, Escrow, EscrowKind, EscrowLedgerEntry,
+    EscrowStorage,
+};
+use priority::{check_priority_bid, open_priority_window, PriorityStorage};
+use oracle::{OracleConfig, OracleStorage};
+use profile::{BusinessProfile, ProfileStorage};
 use profits::calculate_profit as do_calculate_profit;
-use settlement::settle_invoice as do_settle_invoice;
+use reverse_factoring::{
+    DebtorProgram, ProgramCreditLine, ReverseFactoringStorage, SupplierInvitation,
+};
+use reinvest::ReinvestStorage;
+use rollover::RolloverProposal;
+use settlement::{
+    record_partial_payment as do_record_partial_payment, settle_invoice as do_settle_invoice,
+    SettlementPlan, SettlementProgress,
+};
+use stop_loss::{
+    check_bidding_allowed, PortfolioStats, StopLossRule, StopLossState, StopLossStorage,
+};
+use storage::DataKey;
+use templates::{
+    InvoiceInput, InvoiceTemplate, ListingParams, TemplateCriteria, TemplateStorage, TemplateUsage,
+};
+use terms::{acknowledge_terms, InvoiceTerms, TermsStorage};
+use tranche::{Tranche, TrancheStorage, TrancheStructure};
+use treasury::TreasuryStorage;
 use verification::{
     get_business_verification_status, reject_business, submit_kyc_application, verify_business,
-    verify_invoice_data, BusinessVerificationStorage,
+    require_admin, require_verifier, verify_invoice_data, check_invoice_upload,
+    check_tier_amount, approve_invoice, BusinessVerificationStorage,
+    InvoiceInputValidation,
+    KYCTier, TierLimits, VerificationQuorum,
 };
 
-use crate::backup::{Backup, BackupStatus, BackupStorage};
+use crate::backup::{Backup, BackupStatus, BackupStorage, RestoreProgress};
 use audit::{
-    log_invoice_created, log_invoice_status_change, log_invoice_funded, log_payment_processed,
-    AuditStorage, AuditLogEntry, AuditQueryFilter, AuditStats, AuditOperation
+    log_invoice_amended, log_invoice_created, log_invoice_funded, log_invoice_status_change,
+    AuditLogEntry, AuditOperation, AuditQueryFilter, AuditStats, AuditStorage,
 };
 
 #[contract]
@@ -44,7 +179,8 @@ pub struct QuickLendXContract;
 
 #[contractimpl]
 impl QuickLendXContract {
-    /// Store an invoice in the contract
+    /// Store an invoice in the contract. Kept for existing integrations; it is
+    /// an alias of `upload_invoice` and runs the same checks.
     pub fn store_invoice(
         env: Env,
         business: Address,
@@ -53,100 +189,402 @@ impl QuickLendXContract {
         due_date: u64,
         description: String,
     ) -> Result<BytesN<32>, QuickLendXError> {
-        // Validate input parameters
-        if amount <= 0 {
-            return Err(QuickLendXError::InvalidAmount);
-        }
-
-        let current_timestamp = env.ledger().timestamp();
-        if due_date <= current_timestamp {
-            return Err(QuickLendXError::InvoiceDueDateInvalid);
-        }
+        Self::upload(&env, business, amount, currency, due_date, description, None)
+    }
 
-        if description.len() == 0 {
-            return Err(QuickLendXError::InvalidDescription);
-        }
+    /// Upload an invoice (business only)
+    pub fn upload_invoice(
+        env: Env,
+        business: Address,
+        amount: i128,
+        currency: Address,
+        due_date: u64,
+        description: String,
+    ) -> Result<BytesN<32>, QuickLendXError> {
+        Self::upload(&env, business, amount, currency, due_date, description, None)
+    }
 
-        // Create new invoice
-        let invoice = Invoice::new(
+    /// Upload an invoice anchored to its document by hash, such as the SHA-256
+    /// of the PDF or the digest of its IPFS CID (business only)
+    pub fn upload_invoice_with_hash(
+        env: Env,
+        business: Address,
+        amount: i128,
+        currency: Address,
+        due_date: u64,
+        description: String,
+        invoice_hash: BytesN<32>,
+    ) -> Result<BytesN<32>, QuickLendXError> {
+        Self::upload(
             &env,
-            business.clone(),
+            business,
             amount,
-            currency.clone(),
+            currency,
             due_date,
             description,
-        );
+            Some(invoice_hash),
+        )
+    }
 
-        // Store the invoice
-        InvoiceStorage::store_invoice(&env, &invoice);
+    /// Upload an invoice and list it in one call (business only): set its
+    /// category and tags, verify it straight away if it fits one of the
+    /// business's approved templates, and set an auto-accept rule. Returns the
+    /// invoice id.
+    pub fn upload_and_list(
+        env: Env,
+        business: Address,
+        invoice_input: InvoiceInput,
+        listing_params: ListingParams,
+    ) -> Result<BytesN<32>, QuickLendXError> {
+        let template = match &listing_params.template_id {
+            Some(template_id) => Some(templates::matching_template(
+                &env,
+                &business,
+                template_id,
+                &invoice_input,
+            )?),
+            None => None,
+        };
+        let invoice_id = Self::upload(
+            &env,
+            business.clone(),
+            invoice_input.amount,
+            invoice_input.currency.clone(),
+            invoice_input.due_date,
+            invoice_input.description.clone(),
+            invoice_input.invoice_hash.clone(),
+        )?;
+        if listing_params.category.is_some() || !listing_params.tags.is_empty() {
+            Self::apply_invoice_metadata(
+                &env,
+                &business,
+                &invoice_id,
+                listing_params.category,
+                listing_params.tags,
+            )?;
+        }
+        let invoice = InvoiceStorage::get_invoice(&env, &invoice_id)
+            .ok_or(QuickLendXError::InvoiceNotFound)?;
+        // An auto-verification rule may already have listed the invoice
+        if let Some(template) = template.filter(|_| invoice.status == InvoiceStatus::Pending) {
+            let approver = template.approved_by.clone();
+            templates::record_template_use(&env, template, &invoice_id, &invoice_input);
+            Self::list_verified_invoice(env.clone(), invoice, approver)?;
+        }
+        if listing_params.auto_accept_min_amount > 0 {
+            auto_accept::set_rule(
+                &env,
+                &business,
+                &invoice_id,
+                listing_params.auto_accept_min_amount,
+                listing_params.auto_accept_max_return_bps,
+            )?;
+        }
+        Ok(invoice_id)
+    }
 
-        // Emit event
-        env.events().publish(
-            (symbol_short!("created"),),
-            (invoice.id.clone(), business, amount, currency, due_date),
+    /// Approve a kind of invoice the business may list through
+    /// upload_and_list without manual verification (admin only)
+    pub fn approve_invoice_template(
+        env: Env,
+        admin: Address,
+        business: Address,
+        template_id: Symbol,
+        criteria: TemplateCriteria,
+    ) -> Result<InvoiceTemplate, QuickLendXError> {
+        record_admin_action(
+            &env,
+            &admin,
+            "approve_invoice_template",
+            (&business, &template_id, &criteria),
         );
+        templates::approve_template(&env, &admin, &business, &template_id, &criteria)
+    }
+
+    /// Withdraw a business's invoice template (admin only)
+    pub fn revoke_invoice_template(
+        env: Env,
+        admin: Address,
+        business: Address,
+        template_id: Symbol,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "revoke_invoice_template", (&business, &template_id));
+        templates::revoke_template(&env, &admin, &business, &template_id)
+    }
 
-        Ok(invoice.id)
+    /// Get one of a business's approved invoice templates
+    pub fn get_invoice_template(
+        env: Env,
+        business: Address,
+        template_id: Symbol,
+    ) -> Option<InvoiceTemplate> {
+        TemplateStorage::get_template(&env, &business, &template_id)
     }
 
-    /// Upload an invoice (business only)
-    pub fn upload_invoice(
+    /// Get the invoices listed through one of a business's templates, oldest
+    /// first, including after the template was revoked
+    pub fn get_template_usage(
         env: Env,
         business: Address,
-        amount: i128,
-        currency: Address,
-        due_date: u64,
-        description: String,
-    ) -> Result<BytesN<32>, QuickLendXError> {
-        // Only the business can upload their own invoice
-        business.require_auth();
+        template_id: Symbol,
+    ) -> Vec<TemplateUsage> {
+        TemplateStorage::get_usage(&env, &business, &template_id)
+    }
 
-        // Check if business is verified
-        let verification = get_business_verification_status(&env, &business);
-        if verification.is_none()
-            || !matches!(
-                verification.unwrap().status,
-                verification::BusinessVerificationStatus::Verified
-            )
-        {
-            return Err(QuickLendXError::BusinessNotVerified);
+    /// Anchor a supplementary document, such as a contract or delivery note,
+    /// to an invoice by its hash (business only)
+    pub fn add_invoice_document(
+        env: Env,
+        business: Address,
+        invoice_id: BytesN<32>,
+        hash: BytesN<32>,
+        name: String,
+    ) -> Result<(), QuickLendXError> {
+        business.require_auth();
+        let mut invoice = InvoiceStorage::get_invoice(&env, &invoice_id)
+            .ok_or(QuickLendXError::InvoiceNotFound)?;
+        if invoice.business != business {
+            return Err(QuickLendXError::NotBusinessOwner);
+        }
+        if name.is_empty() {
+            return Err(QuickLendXError::InvalidDescription);
+        }
+        check_description_length(&env, &name)?;
+        let known = invoice.invoice_hash.as_ref() == Some(&hash)
+            || invoice.documents.iter().any(|document| document.hash == hash);
+        if known || invoice.documents.len() >= MAX_INVOICE_DOCUMENTS {
+            return Err(QuickLendXError::OperationNotAllowed);
         }
 
-        // Basic validation
-        verify_invoice_data(&env, &business, amount, &currency, due_date, &description)?;
+        let document = InvoiceDocument {
+            hash,
+            name,
+            added_at: env.ledger().timestamp(),
+        };
+        invoice.documents.push_back(document.clone());
+        InvoiceStorage::update_invoice(&env, &invoice);
+        emit_invoice_document_added(&env, &invoice_id, &document);
+        Ok(())
+    }
+
+    /// Attach the hash of the off-chain terms, such as the assignment
+    /// agreement, that funding the invoice binds both parties to (business
+    /// only). Bids on the invoice must then be accepted with
+    /// accept_bid_with_terms.
+    pub fn set_invoice_terms(
+        env: Env,
+        business: Address,
+        invoice_id: BytesN<32>,
+        terms_hash: BytesN<32>,
+    ) -> Result<InvoiceTerms, QuickLendXError> {
+        terms::set_invoice_terms(&env, &business, &invoice_id, &terms_hash)
+    }
 
-        // Create and store invoice
-        let invoice = Invoice::new(
+    /// Get an invoice's terms and the investors who signed for them
+    pub fn get_invoice_terms(env: Env, invoice_id: BytesN<32>) -> Option<InvoiceTerms> {
+        TermsStorage::get_terms(&env, &invoice_id)
+    }
+
+    /// Dry-run the upload_invoice checks and report pass/fail per field.
+    /// Read-only, so dApps can validate input before asking for a signature.
+    pub fn validate_invoice_input(
+        env: Env,
+        business: Address,
+        amount: i128,
+        currency: Address,
+        due_date: u64,
+        description: String,
+    ) -> InvoiceInputValidation {
+        verification::validate_invoice_input(
             &env,
-            business.clone(),
+            &business,
             amount,
-            currency.clone(),
+            &currency,
             due_date,
-            description.clone(),
-        );
-        InvoiceStorage::store_invoice(&env, &invoice);
-        emit_invoice_uploaded(&env, &invoice);
-        Ok(invoice.id)
+            &description,
+        )
     }
 
-    /// Verify an invoice (admin or automated process)
-    pub fn verify_invoice(env: Env, invoice_id: BytesN<32>) -> Result<(), QuickLendXError> {
-        let mut invoice = InvoiceStorage::get_invoice(&env, &invoice_id)
+    /// Verify an invoice (admin or verifier only). Invoices above the quorum
+    /// threshold are verified once enough verifiers have called this.
+    pub fn verify_invoice(
+        env: Env,
+        verifier: Address,
+        invoice_id: BytesN<32>,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &verifier, "verify_invoice", (&invoice_id,));
+        require_verifier(&env, &verifier)?;
+        let invoice = InvoiceStorage::get_invoice(&env, &invoice_id)
             .ok_or(QuickLendXError::InvoiceNotFound)?;
         // Only allow verification if pending
         if invoice.status != InvoiceStatus::Pending {
             return Err(QuickLendXError::InvalidStatus);
         }
-        // (Optional: Only admin can verify, add check here if needed)
+        // High-value invoices wait for a quorum of verifiers
+        if !approve_invoice(&env, &verifier, &invoice)? {
+            return Ok(());
+        }
+        Self::list_verified_invoice(env, invoice, verifier)
+    }
+
+    /// Move a pending invoice to Verified, opening it to bids. `admin` is the
+    /// verifier who verified it or approved its template, or the contract
+    /// itself when an auto-verification rule did.
+    fn list_verified_invoice(
+        env: Env,
+        mut invoice: Invoice,
+        admin: Address,
+    ) -> Result<(), QuickLendXError> {
+        let invoice_id = invoice.id.clone();
+        InvoiceStorage::remove_from_status_invoices(&env, &InvoiceStatus::Pending, &invoice_id);
         invoice.verify();
         InvoiceStorage::update_invoice(&env, &invoice);
-        emit_invoice_verified(&env, &invoice);
+        InvoiceStorage::add_to_status_invoices(&env, &InvoiceStatus::Verified, &invoice_id);
+        open_priority_window(&env, &invoice);
+        log_invoice_status_change(
+            &env,
+            invoice_id.clone(),
+            admin.clone(),
+            InvoiceStatus::Pending,
+            InvoiceStatus::Verified,
+        );
+        emit_invoice_verified(&env, &invoice, &admin);
+        notify_observers(&env, &invoice_id, LifecycleEvent::Verified);
+        notify(&env, &invoice.business, NotificationKind::InvoiceVerified, &invoice_id);
 
         // If invoice is funded (has escrow), release escrow funds to business
         if invoice.status == InvoiceStatus::Funded {
-            Self::release_escrow_funds(env.clone(), invoice_id)?;
+            Self::release_escrow_funds(env.clone(), admin, invoice_id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Cancel an invoice that has not been funded (business only). Bids still
+    /// outstanding on it are rejected.
+    pub fn cancel_invoice(
+        env: Env,
+        business: Address,
+        invoice_id: BytesN<32>,
+    ) -> Result<(), QuickLendXError> {
+        let mut invoice = InvoiceStorage::get_invoice(&env, &invoice_id)
+            .ok_or(QuickLendXError::InvoiceNotFound)?;
+        business.require_auth();
+        if invoice.business != business {
+            return Err(QuickLendXError::NotBusinessOwner);
+        }
+        if invoice.status != InvoiceStatus::Pending && invoice.status != InvoiceStatus::Verified {
+            return Err(QuickLendXError::InvalidStatus);
+        }
+        // A partly funded invoice already has an accepted bid
+        if invoice.funded_amount > 0 {
+            return Err(QuickLendXError::InvoiceAlreadyFunded);
+        }
+
+        let rejected = BidStorage::reject_open_bids(&env, &invoice_id, &business)?;
+
+        let old_status = invoice.status.clone();
+        InvoiceStorage::remove_from_status_invoices(&env, &old_status, &invoice_id);
+        invoice.mark_as_cancelled();
+        InvoiceStorage::update_invoice(&env, &invoice);
+        InvoiceStorage::add_to_status_invoices(&env, &InvoiceStatus::Cancelled, &invoice_id);
+        log_invoice_status_change(
+            &env,
+            invoice_id.clone(),
+            business.clone(),
+            old_status,
+            InvoiceStatus::Cancelled,
+        );
+        emit_invoice_cancelled(&env, &invoice, rejected);
+
+        Ok(())
+    }
+
+    /// Correct the amount, due date and description of an invoice still
+    /// awaiting verification (business only). The new values are validated as
+    /// on upload, approvals already given are discarded and the old values are
+    /// kept in the audit trail.
+    pub fn amend_invoice(
+        env: Env,
+        business: Address,
+        invoice_id: BytesN<32>,
+        new_amount: i128,
+        new_due_date: u64,
+        new_description: String,
+    ) -> Result<Invoice, QuickLendXError> {
+        let old = InvoiceStorage::get_invoice(&env, &invoice_id)
+            .ok_or(QuickLendXError::InvoiceNotFound)?;
+        business.require_auth();
+        if old.business != business {
+            return Err(QuickLendXError::NotBusinessOwner);
+        }
+        if old.status != InvoiceStatus::Pending {
+            return Err(QuickLendXError::InvalidStatus);
+        }
+        check_not_blacklisted(&env, &business)?;
+        verify_invoice_data(
+            &env,
+            &business,
+            new_amount,
+            &old.currency,
+            new_due_date,
+            &new_description,
+        )?;
+        check_credit_limit(&env, &business, &old.currency, new_amount, &old.invoice_hash)?;
+        check_tier_amount(&env, &business, &old.currency, new_amount, &old.invoice_hash)?;
+
+        let mut invoice = old.clone();
+        invoice.amount = new_amount;
+        invoice.due_date = new_due_date;
+        invoice.description = new_description;
+        InvoiceStorage::update_invoice(&env, &invoice);
+        BusinessVerificationStorage::clear_invoice_approvals(&env, &invoice_id);
+        log_invoice_amended(&env, &old, &invoice);
+        emit_invoice_amended(&env, &old, &invoice);
+        Ok(invoice)
+    }
+
+    /// Set an invoice's category and tags, replacing any set before, while it
+    /// is still open for funding (business only). Repeated tags are kept once.
+    pub fn set_invoice_metadata(
+        env: Env,
+        business: Address,
+        invoice_id: BytesN<32>,
+        category: Option<Symbol>,
+        tags: Vec<Symbol>,
+    ) -> Result<(), QuickLendXError> {
+        business.require_auth();
+        Self::apply_invoice_metadata(&env, &business, &invoice_id, category, tags)
+    }
+
+    /// Set an invoice's category and tags once its business has authorized it
+    fn apply_invoice_metadata(
+        env: &Env,
+        business: &Address,
+        invoice_id: &BytesN<32>,
+        category: Option<Symbol>,
+        tags: Vec<Symbol>,
+    ) -> Result<(), QuickLendXError> {
+        let mut invoice = InvoiceStorage::get_invoice(env, invoice_id)
+            .ok_or(QuickLendXError::InvoiceNotFound)?;
+        if invoice.business != *business {
+            return Err(QuickLendXError::NotBusinessOwner);
+        }
+        if invoice.status != InvoiceStatus::Pending && invoice.status != InvoiceStatus::Verified {
+            return Err(QuickLendXError::InvalidStatus);
+        }
+        let mut unique_tags = Vec::new(env);
+        for tag in tags.iter() {
+            if !unique_tags.contains(&tag) {
+                unique_tags.push_back(tag);
+            }
+        }
+        if unique_tags.len() > MAX_INVOICE_TAGS {
+            return Err(QuickLendXError::TooManyTags);
         }
 
+        InvoiceStorage::set_metadata(env, &mut invoice, category, unique_tags);
+        emit_invoice_metadata_set(env, &invoice);
         Ok(())
     }
 
@@ -170,23 +608,43 @@ impl QuickLendXContract {
         InvoiceStorage::get_invoices_by_status(&env, &status)
     }
 
+    /// Get all invoices in a category, such as a sector
+    pub fn get_invoices_by_category(env: Env, category: Symbol) -> Vec<BytesN<32>> {
+        InvoiceStorage::get_invoices_by_category(&env, &category)
+    }
+
+    /// Get all invoices carrying a tag
+    pub fn get_invoices_by_tag(env: Env, tag: Symbol) -> Vec<BytesN<32>> {
+        InvoiceStorage::get_invoices_by_tag(&env, &tag)
+    }
+
     /// Get all available invoices (verified and not funded)
     pub fn get_available_invoices(env: Env) -> Vec<BytesN<32>> {
         InvoiceStorage::get_invoices_by_status(&env, &InvoiceStatus::Verified)
     }
 
-    /// Update invoice status (admin function)
+    /// Update invoice status (admin only)
     pub fn update_invoice_status(
         env: Env,
+        admin: Address,
         invoice_id: BytesN<32>,
         new_status: InvoiceStatus,
     ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "update_invoice_status", (&invoice_id, &new_status));
+        require_admin(&env, &admin)?;
         let mut invoice = InvoiceStorage::get_invoice(&env, &invoice_id)
             .ok_or(QuickLendXError::InvoiceNotFound)?;
+        let old_status = invoice.status.clone();
 
         // Remove from old status list
         InvoiceStorage::remove_from_status_invoices(&env, &invoice.status, &invoice_id);
 
+        // Funding stops counting towards maturity once the invoice is closed
+        let closes = matches!(new_status, InvoiceStatus::Paid | InvoiceStatus::Defaulted);
+        if closes && matches!(old_status, InvoiceStatus::Verified | InvoiceStatus::Funded) {
+            release_funding(&env, &invoice);
+        }
+
         // Update status
         match new_status {
             InvoiceStatus::Verified => invoice.verify(),
@@ -200,10 +658,17 @@ impl QuickLendXContract {
 
         // Add to new status list
         InvoiceStorage::add_to_status_invoices(&env, &invoice.status, &invoice_id);
+        log_invoice_status_change(
+            &env,
+            invoice_id.clone(),
+            admin.clone(),
+            old_status,
+            new_status.clone(),
+        );
 
         // Emit event
         env.events()
-            .publish((symbol_short!("updated"),), (invoice_id, new_status));
+            .publish((symbol_short!("updated"),), (invoice_id, new_status, admin));
 
         Ok(())
     }
@@ -221,8 +686,11 @@ impl QuickLendXContract {
         let funded = Self::get_invoice_count_by_status(env.clone(), InvoiceStatus::Funded);
         let paid = Self::get_invoice_count_by_status(env.clone(), InvoiceStatus::Paid);
         let defaulted = Self::get_invoice_count_by_status(env.clone(), InvoiceStatus::Defaulted);
+        let cancelled = Self::get_invoice_count_by_status(env.clone(), InvoiceStatus::Cancelled);
+        let expired = Self::get_invoice_count_by_status(env.clone(), InvoiceStatus::Expired);
+        let refinanced = Self::get_invoice_count_by_status(env.clone(), InvoiceStatus::Refinanced);
 
-        pending + verified + funded + paid + defaulted
+        pending + verified + funded + paid + defaulted + cancelled + expired + refinanced
     }
 
     /// Get a bid by ID
@@ -230,25 +698,77 @@ impl QuickLendXContract {
         BidStorage::get_bid(&env, &bid_id)
     }
 
-    /// Place a bid on an invoice
+    /// Place a bid on an invoice. A bid meeting the business's auto-accept
+    /// rule for the invoice is accepted straight away.
     pub fn place_bid(
         env: Env,
         investor: Address,
         invoice_id: BytesN<32>,
         bid_amount: i128,
         expected_return: i128,
+    ) -> Result<BytesN<32>, QuickLendXError> {
+        Self::place_bid_in(env, investor, invoice_id, bid_amount, expected_return, None)
+    }
+
+    /// Place a bid in a tranche of a tranched invoice. The bid's expected
+    /// return is the tranche's return on `bid_amount`.
+    pub fn place_tranche_bid(
+        env: Env,
+        investor: Address,
+        invoice_id: BytesN<32>,
+        tranche: Tranche,
+        bid_amount: i128,
+    ) -> Result<BytesN<32>, QuickLendXError> {
+        let structure = TrancheStorage::get_structure(&env, &invoice_id)
+            .ok_or(QuickLendXError::TrancheNotFound)?;
+        let expected_return = structure.target_return(&tranche, bid_amount);
+        Self::place_bid_in(env, investor, invoice_id, bid_amount, expected_return, Some(tranche))
+    }
+
+    /// Place a bid, in `tranche` when the invoice is tranched
+    fn place_bid_in(
+        env: Env,
+        investor: Address,
+        invoice_id: BytesN<32>,
+        bid_amount: i128,
+        expected_return: i128,
+        tranche: Option<Tranche>,
     ) -> Result<BytesN<32>, QuickLendXError> {
         // Only allow bids on verified invoices
         let invoice = InvoiceStorage::get_invoice(&env, &invoice_id)
             .ok_or(QuickLendXError::InvoiceNotFound)?;
-        if invoice.status != InvoiceStatus::Verified {
+        if invoice.status != InvoiceStatus::Verified || is_funding_expired(&env, &invoice) {
             return Err(QuickLendXError::InvalidStatus);
         }
+        // Bids on an auctioned invoice go through commit and reveal
+        if AuctionStorage::is_open(&env, &invoice_id) {
+            return Err(QuickLendXError::AuctionInProgress);
+        }
         if bid_amount <= 0 {
             return Err(QuickLendXError::InvalidAmount);
         }
+        check_min_ticket(&env, &invoice, bid_amount)?;
         // Only the investor can place their own bid
         investor.require_auth();
+        check_not_blacklisted(&env, &investor)?;
+        check_not_blacklisted(&env, &invoice.business)?;
+        check_bidding_allowed(&env, &investor)?;
+        require_investor_verification(&env, &investor)?;
+        check_investor_cap(&env, &invoice, &investor)?;
+        check_not_self_dealing(&env, &invoice.business, &investor)?;
+        check_priority_bid(&env, &invoice, &investor)?;
+        reverse_factoring::check_bid(&env, &invoice, bid_amount, expected_return)?;
+        tranche::check_bid(&env, &invoice, tranche.as_ref(), bid_amount)?;
+        jurisdiction::check_bid(
+            &env,
+            &invoice.business,
+            &investor,
+            bid_amount,
+            expected_return,
+            invoice.due_date,
+        )?;
+        // The bid is backed by funds held in the contract from the start
+        BidStorage::hold_funds(&env, &investor, &invoice.currency, bid_amount)?;
         // Create bid
         let bid_id = BidStorage::generate_unique_bid_id(&env);
         let bid = Bid {
@@ -261,48 +781,301 @@ impl QuickLendXContract {
             status: BidStatus::Placed,
         };
         BidStorage::store_bid(&env, &bid);
+        if let Some(tranche) = &tranche {
+            tranche::record_bid(&env, &bid_id, tranche);
+        }
+        emit_bid_placed(&env, &bid);
         // Track bid for this invoice
         BidStorage::add_bid_to_invoice(&env, &invoice_id, &bid_id);
+        notify(&env, &invoice.business, NotificationKind::BidReceived, &invoice_id);
+        // The business accepted bids like this one in advance
+        if let Some(rule) = auto_accept::matching_rule(&env, &invoice, &bid) {
+            emit_bid_auto_accepted(&env, &bid);
+            Self::accept_placed_bid(env, invoice.business.clone(), invoice, bid, rule.terms_hash)?;
+        }
         Ok(bid_id)
     }
 
+    /// Accept in advance any bid on the invoice of at least `min_amount`
+    /// whose profit is at most `max_return_bps` of its amount (business only)
+    pub fn set_auto_accept(
+        env: Env,
+        business: Address,
+        invoice_id: BytesN<32>,
+        min_amount: i128,
+        max_return_bps: u32,
+    ) -> Result<AutoAcceptRule, QuickLendXError> {
+        auto_accept::set_auto_accept(&env, &business, &invoice_id, min_amount, max_return_bps)
+    }
+
+    /// Stop accepting bids on the invoice in advance (business only)
+    pub fn clear_auto_accept(
+        env: Env,
+        business: Address,
+        invoice_id: BytesN<32>,
+    ) -> Result<(), QuickLendXError> {
+        auto_accept::clear_auto_accept(&env, &business, &invoice_id)
+    }
+
+    /// Get the bids the business accepts in advance on an invoice, if any
+    pub fn get_auto_accept(env: Env, invoice_id: BytesN<32>) -> Option<AutoAcceptRule> {
+        AutoAcceptStorage::get_rule(&env, &invoice_id)
+    }
+
+    /// Split an invoice's funding into a senior tranche of `senior_amount`
+    /// and a junior tranche taking the rest, each owed its own return on
+    /// principal (business only). Settlement pays the senior tranche first.
+    pub fn set_tranches(
+        env: Env,
+        business: Address,
+        invoice_id: BytesN<32>,
+        senior_amount: i128,
+        senior_return_bps: u32,
+        junior_return_bps: u32,
+    ) -> Result<TrancheStructure, QuickLendXError> {
+        tranche::set_tranches(
+            &env,
+            &business,
+            &invoice_id,
+            senior_amount,
+            senior_return_bps,
+            junior_return_bps,
+        )
+    }
+
+    /// Get an invoice's tranche structure, if it is tranched
+    pub fn get_tranches(env: Env, invoice_id: BytesN<32>) -> Option<TrancheStructure> {
+        TrancheStorage::get_structure(&env, &invoice_id)
+    }
+
+    /// Get the tranche an investment belongs to, if any
+    pub fn get_investment_tranche(env: Env, investment_id: BytesN<32>) -> Option<Tranche> {
+        TrancheStorage::get_investment_tranche(&env, &investment_id)
+    }
+
     /// Accept a bid (business only)
+    ///
+    /// Several bids can be accepted on the same invoice until their combined
+    /// amount covers the invoice; each accepted bid becomes a separate investment.
+    /// While an acceptance window is set, the investor must confirm the
+    /// acceptance with confirm_bid_acceptance before anything is escrowed.
     pub fn accept_bid(
         env: Env,
+        business: Address,
         invoice_id: BytesN<32>,
         bid_id: BytesN<32>,
     ) -> Result<(), QuickLendXError> {
-        let mut invoice = InvoiceStorage::get_invoice(&env, &invoice_id)
+        let invoice = InvoiceStorage::get_invoice(&env, &invoice_id)
             .ok_or(QuickLendXError::InvoiceNotFound)?;
-        let mut bid =
+        let bid =
             BidStorage::get_bid(&env, &bid_id).ok_or(QuickLendXError::StorageKeyNotFound)?;
         // Only the business owner can accept a bid
-        invoice.business.require_auth();
-        // Only allow accepting if invoice is verified and bid is placed
-        if invoice.status != InvoiceStatus::Verified || bid.status != BidStatus::Placed {
-            return Err(QuickLendXError::InvalidStatus);
+        business.require_auth();
+        if invoice.business != business {
+            return Err(QuickLendXError::NotBusinessOwner);
+        }
+        // An auctioned invoice is funded by the auction's winning bid
+        if AuctionStorage::is_open(&env, &invoice_id) {
+            return Err(QuickLendXError::AuctionInProgress);
         }
+        Self::accept_or_request(env, business, invoice, bid, None)
+    }
 
-        // Create escrow
-        let escrow_id = create_escrow(
-            &env,
-            &invoice_id,
-            &bid.investor,
-            &invoice.business,
-            bid.bid_amount,
+    /// Accept a bid on an invoice with terms, binding both parties to the
+    /// terms with this hash (business and investor). Invoices with terms can
+    /// only be funded this way.
+    pub fn accept_bid_with_terms(
+        env: Env,
+        business: Address,
+        invoice_id: BytesN<32>,
+        bid_id: BytesN<32>,
+        terms_hash: BytesN<32>,
+    ) -> Result<(), QuickLendXError> {
+        let invoice = InvoiceStorage::get_invoice(&env, &invoice_id)
+            .ok_or(QuickLendXError::InvoiceNotFound)?;
+        let bid =
+            BidStorage::get_bid(&env, &bid_id).ok_or(QuickLendXError::StorageKeyNotFound)?;
+        business.require_auth();
+        if invoice.business != business {
+            return Err(QuickLendXError::NotBusinessOwner);
+        }
+        if AuctionStorage::is_open(&env, &invoice_id) {
+            return Err(QuickLendXError::AuctionInProgress);
+        }
+        Self::accept_or_request(env, business, invoice, bid, Some(terms_hash))
+    }
+
+    /// Accept the placed bid offering the cheapest financing, the one with the
+    /// lowest expected return that fits the remaining funding (business only).
+    /// Returns the accepted bid.
+    pub fn accept_best_bid(
+        env: Env,
+        business: Address,
+        invoice_id: BytesN<32>,
+    ) -> Result<BytesN<32>, QuickLendXError> {
+        let invoice = InvoiceStorage::get_invoice(&env, &invoice_id)
+            .ok_or(QuickLendXError::InvoiceNotFound)?;
+        business.require_auth();
+        if invoice.business != business {
+            return Err(QuickLendXError::NotBusinessOwner);
+        }
+        if AuctionStorage::is_open(&env, &invoice_id) {
+            return Err(QuickLendXError::AuctionInProgress);
+        }
+        let bid = BidStorage::get_best_bid(&env, &invoice_id, invoice.remaining_funding())
+            .ok_or(QuickLendXError::StorageKeyNotFound)?;
+        let bid_id = bid.bid_id.clone();
+        Self::accept_or_request(env, business, invoice, bid, None)?;
+        Ok(bid_id)
+    }
+
+    /// Carry out a business's acceptance of a bid, or, when investors must
+    /// confirm acceptances, record it for the investor to confirm. Accepting
+    /// under terms directly needs the investor's signature as well.
+    fn accept_or_request(
+        env: Env,
+        business: Address,
+        invoice: Invoice,
+        bid: Bid,
+        terms_hash: Option<BytesN<32>>,
+    ) -> Result<(), QuickLendXError> {
+        if AcceptanceStorage::get_confirmation_window(&env) > 0 {
+            acceptance::request_acceptance(&env, &business, &invoice, &bid, terms_hash)?;
+            return Ok(());
+        }
+        // Terms bind the investor too, who signs for them alongside the business
+        if terms_hash.is_some() {
+            bid.investor.require_auth();
+        }
+        Self::accept_placed_bid(env, business, invoice, bid, terms_hash)
+    }
+
+    /// Confirm the business's acceptance of the investor's bid within the
+    /// confirmation window, funding the invoice (investor only)
+    pub fn confirm_bid_acceptance(
+        env: Env,
+        investor: Address,
+        bid_id: BytesN<32>,
+    ) -> Result<(), QuickLendXError> {
+        let (pending, bid) = acceptance::confirm_acceptance(&env, &investor, &bid_id)?;
+        let invoice = InvoiceStorage::get_invoice(&env, &pending.invoice_id)
+            .ok_or(QuickLendXError::InvoiceNotFound)?;
+        if AuctionStorage::is_open(&env, &pending.invoice_id) {
+            return Err(QuickLendXError::AuctionInProgress);
+        }
+        Self::accept_placed_bid(env, pending.business, invoice, bid, pending.terms_hash)
+    }
+
+    /// Get the acceptance of a bid waiting for its investor to confirm, if any
+    pub fn get_pending_acceptance(env: Env, bid_id: BytesN<32>) -> Option<PendingAcceptance> {
+        AcceptanceStorage::get_pending(&env, &bid_id)
+    }
+
+    /// Require investors to confirm within `window` seconds that their bid
+    /// may be accepted before the invoice is funded; 0 turns this off (admin
+    /// only)
+    pub fn set_acceptance_window(
+        env: Env,
+        admin: Address,
+        window: u64,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_acceptance_window", (&window,));
+        acceptance::set_confirmation_window(&env, &admin, window)
+    }
+
+    /// Get how long investors have to confirm an acceptance; 0 when none is
+    /// needed
+    pub fn get_acceptance_window(env: Env) -> u64 {
+        AcceptanceStorage::get_confirmation_window(&env)
+    }
+
+    /// Accept a placed bid on behalf of the invoice's business: escrow the
+    /// funds, record the investment and move the invoice to Funded once it is
+    /// fully covered. Callers check who may accept and pass the terms hash
+    /// the business signed for, if any.
+    fn accept_placed_bid(
+        env: Env,
+        business: Address,
+        mut invoice: Invoice,
+        mut bid: Bid,
+        terms_hash: Option<BytesN<32>>,
+    ) -> Result<(), QuickLendXError> {
+        let invoice_id = invoice.id.clone();
+        // Only allow accepting if invoice is open for funding and bid is placed
+        if !invoice.is_available_for_funding()
+            || is_funding_expired(&env, &invoice)
+            || bid.status != BidStatus::Placed
+            || bid.invoice_id != invoice_id
+        {
+            return Err(QuickLendXError::InvalidStatus);
+        }
+        // Accepted bids can never fund more than the invoice amount
+        if bid.bid_amount > invoice.remaining_funding() {
+            return Err(QuickLendXError::InvalidAmount);
+        }
+        // Either party may have been blacklisted since the bid was placed
+        check_not_blacklisted(&env, &business)?;
+        check_not_blacklisted(&env, &bid.investor)?;
+        // Affiliates and other investors may have come in since the bid was placed
+        check_not_self_dealing(&env, &business, &bid.investor)?;
+        check_investor_cap(&env, &invoice, &bid.investor)?;
+        check_credit_limit(
+            &env,
+            &business,
             &invoice.currency,
+            bid.bid_amount,
+            &invoice.invoice_hash,
+        )?;
+        reverse_factoring::check_credit_line(&env, &invoice, bid.bid_amount)?;
+        tranche::check_accept(&env, &invoice, &bid)?;
+        flag_recycled_funding(&env, &invoice, &bid.investor);
+        // Both parties have signed for the invoice's terms, if it has any
+        acknowledge_terms(&env, &invoice, &bid.investor, terms_hash)?;
+
+        // Charge the premium if investments in the invoice are insured
+        let insurance = cover_investment(&env, &invoice, &bid.investor, bid.bid_amount)?;
+
+        // The funds held since the bid was placed become the invoice escrow
+        let escrow_id = create_escrow(
+            &env,
+            &invoice,
+            &bid.investor,
+            bid.bid_amount,
+            EscrowKind::Funding,
+            &business,
         )?;
         // Mark bid as accepted
         bid.status = BidStatus::Accepted;
         BidStorage::update_bid(&env, &bid);
-        // Mark invoice as funded
+        emit_bid_accepted(&env, &bid, &business);
+        // Record the funding share; the invoice becomes Funded once fully covered
         invoice.mark_as_funded(
             bid.investor.clone(),
             bid.bid_amount,
             env.ledger().timestamp(),
         );
         InvoiceStorage::update_invoice(&env, &invoice);
-        // Track investment
+        record_funding(&env, &invoice, bid.bid_amount);
+        reverse_factoring::draw_credit_line(&env, &invoice, bid.bid_amount);
+        analytics::record_funded(&env, &invoice, bid.bid_amount);
+        log_invoice_funded(&env, invoice_id.clone(), bid.investor.clone(), bid.bid_amount);
+        // Competing bids that no longer fit the remaining funding are rejected
+        // and get their funds back; all of them once the invoice is covered
+        BidStorage::reject_bids_over(&env, &invoice_id, invoice.remaining_funding(), &business)?;
+        if invoice.status == InvoiceStatus::Funded {
+            InvoiceStorage::remove_from_status_invoices(&env, &InvoiceStatus::Verified, &invoice_id);
+            InvoiceStorage::add_to_status_invoices(&env, &InvoiceStatus::Funded, &invoice_id);
+            log_invoice_status_change(
+                &env,
+                invoice_id.clone(),
+                business.clone(),
+                InvoiceStatus::Verified,
+                InvoiceStatus::Funded,
+            );
+        }
+        // Track investment, with the risk inputs behind it and the fee terms it
+        // settles under
+        let risk = snapshot_risk_inputs(&env, &invoice, bid.bid_amount, bid.expected_return);
         let investment_id = InvestmentStorage::generate_unique_investment_id(&env);
         let investment = Investment {
             investment_id: investment_id.clone(),
@@ -311,228 +1084,2440 @@ impl QuickLendXContract {
             amount: bid.bid_amount,
             funded_at: env.ledger().timestamp(),
             status: InvestmentStatus::Active,
+            insurance,
+            risk,
+            fees: FeeSnapshot::Recorded(reverse_factoring::fee_terms(&env, &invoice_id)),
         };
         InvestmentStorage::store_investment(&env, &investment);
+        tranche::record_investment(&env, &bid, &investment_id, &business)?;
+        emit_investment_created(&env, &investment);
+        stop_loss::record_funded(&env, &investment);
+        notify(&env, &bid.investor, NotificationKind::BidAccepted, &invoice_id);
 
         let escrow = EscrowStorage::get_escrow(&env, &escrow_id)
             .expect("Escrow should exist after creation");
         emit_escrow_created(&env, &escrow);
+        if invoice.status == InvoiceStatus::Funded {
+            let contributions = InvestmentStorage::get_funding_contributions(&env, &invoice_id);
+            emit_funding_completed(&env, &invoice, &contributions);
+            notify_observers(&env, &invoice_id, LifecycleEvent::Funded);
+        }
 
         Ok(())
     }
 
-    /// Withdraw a bid (investor only, before acceptance)
-    pub fn withdraw_bid(env: Env, bid_id: BytesN<32>) -> Result<(), QuickLendXError> {
+    /// Get an investment by ID
+    pub fn get_investment(env: Env, investment_id: BytesN<32>) -> Option<Investment> {
+        InvestmentStorage::get_investment(&env, &investment_id)
+    }
+
+    /// Get all investments (per-investor shares) in an invoice
+    pub fn get_invoice_investments(env: Env, invoice_id: BytesN<32>) -> Vec<Investment> {
+        let mut investments = Vec::new(&env);
+        for investment_id in InvestmentStorage::get_investments_for_invoice(&env, &invoice_id).iter()
+        {
+            if let Some(investment) = InvestmentStorage::get_investment(&env, &investment_id) {
+                investments.push_back(investment);
+            }
+        }
+        investments
+    }
+
+    /// Value an active investment at a timestamp, accruing its promised
+    /// profit linearly between funding and the invoice due date
+    pub fn get_position_value(
+        env: Env,
+        investment_id: BytesN<32>,
+        at_timestamp: u64,
+    ) -> Result<PositionValue, QuickLendXError> {
+        investment::get_position_value(&env, &investment_id, at_timestamp)
+    }
+
+    /// Get outstanding funded amounts grouped by days to maturity
+    pub fn get_maturity_ladder(env: Env) -> MaturityLadder {
+        maturity::get_maturity_ladder(&env)
+    }
+
+    /// Set the maturity ladder's bucket boundaries in days (admin only)
+    pub fn set_maturity_buckets(
+        env: Env,
+        admin: Address,
+        bounds: Vec<u64>,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_maturity_buckets", (&bounds,));
+        maturity::set_maturity_buckets(&env, &admin, &bounds)
+    }
+
+    /// Get the maturity ladder's bucket boundaries in days
+    pub fn get_maturity_buckets(env: Env) -> Vec<u64> {
+        MaturityStorage::get_bucket_bounds(&env)
+    }
+
+    /// Get each investor's total contribution to an invoice's funding round
+    pub fn get_funding_contributions(
+        env: Env,
+        invoice_id: BytesN<32>,
+    ) -> Vec<FundingContribution> {
+        InvestmentStorage::get_funding_contributions(&env, &invoice_id)
+    }
+
+    /// Withdraw a bid and get its held funds back (investor only, before
+    /// acceptance)
+    pub fn withdraw_bid(
+        env: Env,
+        investor: Address,
+        bid_id: BytesN<32>,
+    ) -> Result<(), QuickLendXError> {
         let mut bid =
             BidStorage::get_bid(&env, &bid_id).ok_or(QuickLendXError::StorageKeyNotFound)?;
         // Only the investor can withdraw their own bid
-        bid.investor.require_auth();
-        // Only allow withdrawal if bid is placed (not accepted/withdrawn)
-        if bid.status != BidStatus::Placed {
+        investor.require_auth();
+        if bid.investor != investor {
+            return Err(QuickLendXError::NotInvestor);
+        }
+        // Only allow withdrawal if bid is open (not accepted/withdrawn)
+        if !bid.is_open() {
             return Err(QuickLendXError::OperationNotAllowed);
         }
+        BidStorage::return_funds(&env, &bid)?;
         bid.status = BidStatus::Withdrawn;
         BidStorage::update_bid(&env, &bid);
+        emit_bid_withdrawn(&env, &bid);
         Ok(())
     }
 
-    /// Settle an invoice (business or automated process)
-    pub fn settle_invoice(
+    /// Reject an open bid on the business's invoice, returning its held funds
+    /// to the investor (business only)
+    pub fn reject_bid(
         env: Env,
+        business: Address,
         invoice_id: BytesN<32>,
-        payment_amount: i128,
-        platform: Address,
-        platform_fee_bps: i128,
+        bid_id: BytesN<32>,
     ) -> Result<(), QuickLendXError> {
-        do_settle_invoice(
-            &env,
-            &invoice_id,
-            payment_amount,
-            &platform,
-            platform_fee_bps,
-        )
+        let invoice = InvoiceStorage::get_invoice(&env, &invoice_id)
+            .ok_or(QuickLendXError::InvoiceNotFound)?;
+        let mut bid =
+            BidStorage::get_bid(&env, &bid_id).ok_or(QuickLendXError::StorageKeyNotFound)?;
+        business.require_auth();
+        if invoice.business != business {
+            return Err(QuickLendXError::NotBusinessOwner);
+        }
+        if bid.invoice_id != invoice_id {
+            return Err(QuickLendXError::InvalidStatus);
+        }
+        if !bid.is_open() {
+            return Err(QuickLendXError::OperationNotAllowed);
+        }
+        BidStorage::return_funds(&env, &bid)?;
+        bid.status = BidStatus::Rejected;
+        BidStorage::update_bid(&env, &bid);
+        emit_bid_rejected(&env, &bid, &business);
+        Ok(())
     }
 
-    /// Handle invoice default (admin or automated process)
-    pub fn handle_default(env: Env, invoice_id: BytesN<32>) -> Result<(), QuickLendXError> {
-        do_handle_default(&env, &invoice_id)
+    /// Counter a placed bid with a different expected return (business only).
+    /// A bid can be countered a limited number of times.
+    pub fn counter_bid(
+        env: Env,
+        business: Address,
+        bid_id: BytesN<32>,
+        new_return: i128,
+    ) -> Result<BidNegotiation, QuickLendXError> {
+        negotiation::counter_bid(&env, &business, &bid_id, new_return)
     }
 
-    /// Calculate profit and platform fee
-    pub fn calculate_profit(
-        _env: Env,
-        investment_amount: i128,
-        payment_amount: i128,
-        platform_fee_bps: i128,
-    ) -> (i128, i128) {
-        do_calculate_profit(investment_amount, payment_amount, platform_fee_bps)
+    /// Accept the business's counter-offer, which funds the invoice at the
+    /// countered return (investor only)
+    pub fn accept_counter(
+        env: Env,
+        investor: Address,
+        bid_id: BytesN<32>,
+    ) -> Result<(), QuickLendXError> {
+        let (invoice, bid) = negotiation::accept_counter(&env, &investor, &bid_id)?;
+        Self::accept_placed_bid(env, invoice.business.clone(), invoice, bid, None)
     }
 
-    // Rating Functions (from feat-invoice_rating_system)
+    /// Decline the business's counter-offer; the bid stays placed at its
+    /// current return (investor only)
+    pub fn decline_counter(
+        env: Env,
+        investor: Address,
+        bid_id: BytesN<32>,
+    ) -> Result<(), QuickLendXError> {
+        negotiation::decline_counter(&env, &investor, &bid_id)
+    }
 
-    /// Add a rating to an invoice (investor only)
-    pub fn add_invoice_rating(
+    /// Get the negotiation over a bid, if it was ever countered
+    pub fn get_bid_negotiation(env: Env, bid_id: BytesN<32>) -> Option<BidNegotiation> {
+        NegotiationStorage::get_negotiation(&env, &bid_id)
+    }
+
+    /// List an invoice as a sealed-bid auction with commit and reveal phases
+    /// of the given lengths in seconds (business only)
+    pub fn start_auction(
         env: Env,
+        business: Address,
         invoice_id: BytesN<32>,
-        rating: u32,
-        feedback: String,
-        rater: Address,
+        commit_period: u64,
+        reveal_period: u64,
+    ) -> Result<Auction, QuickLendXError> {
+        auction::start_auction(&env, &business, &invoice_id, commit_period, reveal_period)
+    }
+
+    /// Compute the commitment for a sealed bid; keep the salt secret until reveal
+    pub fn compute_bid_commitment(
+        env: Env,
+        investor: Address,
+        bid_amount: i128,
+        expected_return: i128,
+        salt: BytesN<32>,
+    ) -> BytesN<32> {
+        auction::bid_commitment(&env, &investor, bid_amount, expected_return, &salt)
+    }
+
+    /// Commit to a sealed bid during an auction's commit phase
+    pub fn commit_bid(
+        env: Env,
+        investor: Address,
+        invoice_id: BytesN<32>,
+        commitment: BytesN<32>,
     ) -> Result<(), QuickLendXError> {
-        let mut invoice = InvoiceStorage::get_invoice(&env, &invoice_id)
+        auction::commit_bid(&env, &investor, &invoice_id, &commitment)
+    }
+
+    /// Reveal a committed bid during an auction's reveal phase, placing it
+    pub fn reveal_bid(
+        env: Env,
+        investor: Address,
+        invoice_id: BytesN<32>,
+        bid_amount: i128,
+        expected_return: i128,
+        salt: BytesN<32>,
+    ) -> Result<BytesN<32>, QuickLendXError> {
+        auction::reveal_bid(&env, &investor, &invoice_id, bid_amount, expected_return, &salt)
+    }
+
+    /// Close an auction after its reveal phase and accept the best revealed
+    /// bid. Anyone may call this. Returns the winning bid, if any.
+    pub fn finalize_auction(
+        env: Env,
+        invoice_id: BytesN<32>,
+    ) -> Result<Option<BytesN<32>>, QuickLendXError> {
+        let Some(bid) = auction::close_auction(&env, &invoice_id)? else {
+            return Ok(None);
+        };
+        let invoice = InvoiceStorage::get_invoice(&env, &invoice_id)
             .ok_or(QuickLendXError::InvoiceNotFound)?;
+        let bid_id = bid.bid_id.clone();
+        Self::accept_placed_bid(env, invoice.business.clone(), invoice, bid, None)?;
+        Ok(Some(bid_id))
+    }
 
-        // Only the investor who funded the invoice can rate it
-        rater.require_auth();
+    /// Reserve the business's newly verified invoices for investors who funded
+    /// it before, for `window` seconds; 0 turns this off (business only)
+    pub fn set_priority_window(
+        env: Env,
+        business: Address,
+        window: u64,
+    ) -> Result<(), QuickLendXError> {
+        priority::set_priority_window(&env, &business, window)
+    }
 
-        invoice.add_rating(rating, feedback, rater.clone(), env.ledger().timestamp())?;
-        InvoiceStorage::update_invoice(&env, &invoice);
+    /// Get a business's repeat-investor priority window in seconds
+    pub fn get_priority_window(env: Env, business: Address) -> u64 {
+        PriorityStorage::get_priority_window(&env, &business)
+    }
 
-        // Emit rating event
-        env.events()
-            .publish((symbol_short!("rated"),), (invoice_id, rating, rater));
+    /// Get when an invoice opens to all investors, if it has a priority window
+    pub fn get_priority_window_end(env: Env, invoice_id: BytesN<32>) -> Option<u64> {
+        PriorityStorage::get_window_end(&env, &invoice_id)
+    }
 
-        Ok(())
+    /// Appoint the compliance officer who maintains the affiliate registry
+    /// (admin only)
+    pub fn set_compliance_officer(
+        env: Env,
+        admin: Address,
+        officer: Address,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_compliance_officer", (&officer,));
+        compliance::set_compliance_officer(&env, &admin, &officer)
+    }
+
+    /// Get the compliance officer
+    pub fn get_compliance_officer(env: Env) -> Option<Address> {
+        ComplianceStorage::get_officer(&env)
+    }
+
+    /// Bar an affiliate from funding a business's invoices (compliance only)
+    pub fn add_affiliate(
+        env: Env,
+        officer: Address,
+        business: Address,
+        affiliate: Address,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &officer, "add_affiliate", (&business, &affiliate));
+        compliance::add_affiliate(&env, &officer, &business, &affiliate)
+    }
+
+    /// Remove an address from a business's affiliates (compliance only)
+    pub fn remove_affiliate(
+        env: Env,
+        officer: Address,
+        business: Address,
+        affiliate: Address,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &officer, "remove_affiliate", (&business, &affiliate));
+        compliance::remove_affiliate(&env, &officer, &business, &affiliate)
+    }
+
+    /// Get the registered affiliates of a business
+    pub fn get_affiliates(env: Env, business: Address) -> Vec<Address> {
+        ComplianceStorage::get_affiliates(&env, &business)
+    }
+
+    /// Blacklist a sanctioned or fraudulent business or investor, barring it
+    /// from uploading, bidding, funding and settling (admin only)
+    pub fn add_to_blacklist(
+        env: Env,
+        admin: Address,
+        address: Address,
+        reason: String,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "add_to_blacklist", (&address, &reason));
+        compliance::add_to_blacklist(&env, &admin, &address, reason)
+    }
+
+    /// Take an address off the blacklist (admin only)
+    pub fn remove_from_blacklist(
+        env: Env,
+        admin: Address,
+        address: Address,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "remove_from_blacklist", (&address,));
+        compliance::remove_from_blacklist(&env, &admin, &address)
+    }
+
+    /// Check whether an address is blacklisted
+    pub fn is_blacklisted(env: Env, address: Address) -> bool {
+        ComplianceStorage::is_blacklisted(&env, &address)
+    }
+
+    /// Get why and by whom an address was blacklisted, if it is
+    pub fn get_blacklist_entry(env: Env, address: Address) -> Option<BlacklistEntry> {
+        ComplianceStorage::get_blacklist_entry(&env, &address)
+    }
+
+    /// Get the blacklisted addresses
+    pub fn get_blacklist(env: Env) -> Vec<Address> {
+        ComplianceStorage::get_blacklist(&env)
+    }
+
+    /// Get fundings flagged as recycling a just-received settlement, oldest
+    /// first, skipping `offset` flags and returning at most `limit`
+    pub fn get_wash_flags(env: Env, offset: u32, limit: u32) -> Vec<WashFlag> {
+        ComplianceStorage::get_wash_flags(&env, offset, limit)
+    }
+
+    /// Get how many fundings have been flagged as recycling a settlement
+    pub fn get_wash_flag_count(env: Env) -> u32 {
+        ComplianceStorage::get_wash_flag_count(&env)
+    }
+
+    /// Get the latest sealed-bid auction of an invoice
+    pub fn get_auction(env: Env, invoice_id: BytesN<32>) -> Option<Auction> {
+        AuctionStorage::get_auction(&env, &invoice_id)
+    }
+
+    /// Settle an invoice (business only). Platform fees follow the admin's
+    /// fee configuration.
+    pub fn settle_invoice(
+        env: Env,
+        business: Address,
+        invoice_id: BytesN<32>,
+        payment_amount: i128,
+    ) -> Result<(), QuickLendXError> {
+        do_settle_invoice(&env, &business, &invoice_id, payment_amount)
+    }
+
+    /// Pay an installment on a funded invoice (business only). The invoice
+    /// becomes Paid once installments cover the amount due.
+    pub fn record_partial_payment(
+        env: Env,
+        business: Address,
+        invoice_id: BytesN<32>,
+        amount: i128,
+    ) -> Result<(), QuickLendXError> {
+        do_record_partial_payment(&env, &business, &invoice_id, amount)
+    }
+
+    /// Pay a settlement into the contract so its payouts can be made in
+    /// batches with process_settlement (business only)
+    pub fn begin_settlement(
+        env: Env,
+        business: Address,
+        invoice_id: BytesN<32>,
+        payment_amount: i128,
+    ) -> Result<SettlementProgress, QuickLendXError> {
+        settlement::begin_settlement(&env, &business, &invoice_id, payment_amount)
+    }
+
+    /// Make up to `max_payouts` payouts of a started settlement; the invoice
+    /// becomes Paid once all are made. Callable by anyone.
+    pub fn process_settlement(
+        env: Env,
+        invoice_id: BytesN<32>,
+        max_payouts: u32,
+    ) -> Result<SettlementProgress, QuickLendXError> {
+        settlement::process_settlement(&env, &invoice_id, max_payouts)
+    }
+
+    /// Get how far a batched settlement has got, while it is in progress
+    pub fn get_settlement_progress(env: Env, invoice_id: BytesN<32>) -> Option<SettlementProgress> {
+        settlement::get_settlement_progress(&env, &invoice_id)
+    }
+
+    /// Settle an invoice by crediting each payee a balance to claim instead
+    /// of paying them directly (business only)
+    pub fn settle_invoice_with_claims(
+        env: Env,
+        business: Address,
+        invoice_id: BytesN<32>,
+        payment_amount: i128,
+    ) -> Result<(), QuickLendXError> {
+        settlement::settle_invoice_with_claims(&env, &business, &invoice_id, payment_amount)
+    }
+
+    /// Settle a program invoice on the debtor's payment (program debtor
+    /// only). Investors are paid what they are owed and the supplier the rest.
+    pub fn settle_program_invoice(
+        env: Env,
+        debtor: Address,
+        invoice_id: BytesN<32>,
+        payment_amount: i128,
+    ) -> Result<SettlementPlan, QuickLendXError> {
+        settlement::settle_program_invoice(&env, &debtor, &invoice_id, payment_amount)
+    }
+
+    /// Preview how a program debtor's payment would be routed
+    pub fn build_program_settlement_plan(
+        env: Env,
+        invoice_id: BytesN<32>,
+        payment_amount: i128,
+    ) -> Result<SettlementPlan, QuickLendXError> {
+        settlement::build_program_settlement_plan(&env, &invoice_id, payment_amount)
+    }
+
+    /// Pull the caller's balance from an invoice settled with claims
+    pub fn claim_settlement(
+        env: Env,
+        payee: Address,
+        invoice_id: BytesN<32>,
+    ) -> Result<i128, QuickLendXError> {
+        settlement::claim_settlement(&env, &payee, &invoice_id)
+    }
+
+    /// Get what a payee can still claim from an invoice's settlement
+    pub fn get_claimable_settlement(env: Env, invoice_id: BytesN<32>, payee: Address) -> i128 {
+        settlement::get_claimable_settlement(&env, &invoice_id, &payee)
+    }
+
+    /// Opt in to or out of having settlement returns credited to the
+    /// investor's balance and bids funded from it (investor only)
+    pub fn set_auto_reinvest(
+        env: Env,
+        investor: Address,
+        enabled: bool,
+    ) -> Result<(), QuickLendXError> {
+        reinvest::set_auto_reinvest(&env, &investor, enabled)
+    }
+
+    /// Check whether an investor reinvests settlement returns
+    pub fn is_auto_reinvest(env: Env, investor: Address) -> bool {
+        ReinvestStorage::is_enabled(&env, &investor)
+    }
+
+    /// Pay funds into the owner's balance, from which its bids are funded
+    /// (owner only). Returns the new balance.
+    pub fn deposit_balance(
+        env: Env,
+        owner: Address,
+        currency: Address,
+        amount: i128,
+    ) -> Result<i128, QuickLendXError> {
+        balances::deposit(&env, &owner, &currency, amount)
+    }
+
+    /// Withdraw funds from the owner's balance to `destination` (owner only).
+    /// Under the owner's withdrawal delay the funds are held until
+    /// `complete_withdrawal`. Returns the new balance.
+    pub fn withdraw_balance(
+        env: Env,
+        owner: Address,
+        currency: Address,
+        amount: i128,
+        destination: Address,
+    ) -> Result<i128, QuickLendXError> {
+        balances::withdraw(&env, &owner, &currency, amount, &destination)
+    }
+
+    /// Restrict where the owner's balance may be withdrawn to and delay its
+    /// withdrawals (owner only). Takes effect once the current delay passes.
+    pub fn set_withdrawal_policy(
+        env: Env,
+        owner: Address,
+        allowlist: Vec<Address>,
+        delay: u64,
+    ) -> Result<WithdrawalPolicy, QuickLendXError> {
+        balances::set_withdrawal_policy(&env, &owner, allowlist, delay)
+    }
+
+    /// Get the withdrawal policy in effect for an owner
+    pub fn get_withdrawal_policy(env: Env, owner: Address) -> WithdrawalPolicy {
+        BalanceStorage::get_withdrawal_policy(&env, &owner)
+    }
+
+    /// Get an owner's withdrawal policy change waiting to take effect, if any
+    pub fn get_queued_withdrawal_policy(env: Env, owner: Address) -> Option<WithdrawalPolicy> {
+        BalanceStorage::get_queued_withdrawal_policy(&env, &owner)
+    }
+
+    /// Get the owner's delayed withdrawal in a currency, if any
+    pub fn get_pending_withdrawal(
+        env: Env,
+        owner: Address,
+        currency: Address,
+    ) -> Option<PendingWithdrawal> {
+        BalanceStorage::get_pending_withdrawal(&env, &owner, &currency)
+    }
+
+    /// Pay out a delayed withdrawal once its delay has passed (owner only).
+    /// Returns the amount paid out.
+    pub fn complete_withdrawal(
+        env: Env,
+        owner: Address,
+        currency: Address,
+    ) -> Result<i128, QuickLendXError> {
+        balances::complete_withdrawal(&env, &owner, &currency)
+    }
+
+    /// Cancel a delayed withdrawal, returning its funds to the balance (owner
+    /// only). Returns the new balance.
+    pub fn cancel_withdrawal(
+        env: Env,
+        owner: Address,
+        currency: Address,
+    ) -> Result<i128, QuickLendXError> {
+        balances::cancel_withdrawal(&env, &owner, &currency)
+    }
+
+    /// Get an address's balance held by the contract in a currency
+    pub fn get_balance(env: Env, owner: Address, currency: Address) -> i128 {
+        BalanceStorage::get_balance(&env, &owner, &currency)
+    }
+
+    /// Get when an address's balance in a currency last moved, if it has
+    pub fn get_balance_last_activity(env: Env, owner: Address, currency: Address) -> Option<u64> {
+        BalanceStorage::get_last_activity(&env, &owner, &currency)
+    }
+
+    /// Designate the custodial account abandoned balances are swept to
+    /// (admin only)
+    pub fn set_escheat_custodian(
+        env: Env,
+        admin: Address,
+        custodian: Address,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_escheat_custodian", (&custodian,));
+        escheat::set_custodian(&env, &admin, &custodian)
+    }
+
+    /// Get the account abandoned balances are swept to, if one is designated
+    pub fn get_escheat_custodian(env: Env) -> Option<Address> {
+        EscheatStorage::get_custodian(&env)
+    }
+
+    /// Warn that a balance untouched for the dormancy period will be swept to
+    /// the custodian unless it moves within the notice period (admin only)
+    pub fn issue_dormancy_notice(
+        env: Env,
+        admin: Address,
+        owner: Address,
+        currency: Address,
+    ) -> Result<DormancyNotice, QuickLendXError> {
+        record_admin_action(&env, &admin, "issue_dormancy_notice", (&owner, &currency));
+        escheat::issue_dormancy_notice(&env, &admin, &owner, &currency)
+    }
+
+    /// Get the dormancy notice on an address's balance in a currency, if any
+    pub fn get_dormancy_notice(
+        env: Env,
+        owner: Address,
+        currency: Address,
+    ) -> Option<DormancyNotice> {
+        EscheatStorage::get_notice(&env, &owner, &currency)
+    }
+
+    /// Sweep a balance whose dormancy notice has run out to the custodian
+    /// (admin only). The owner can reclaim it with claim_escheated_balance.
+    pub fn sweep_dormant_balance(
+        env: Env,
+        admin: Address,
+        owner: Address,
+        currency: Address,
+    ) -> Result<EscheatedBalance, QuickLendXError> {
+        record_admin_action(&env, &admin, "sweep_dormant_balance", (&owner, &currency));
+        escheat::sweep_dormant_balance(&env, &admin, &owner, &currency)
+    }
+
+    /// Get an address's swept balance in a currency awaiting reclaim, if any
+    pub fn get_escheated_balance(
+        env: Env,
+        owner: Address,
+        currency: Address,
+    ) -> Option<EscheatedBalance> {
+        EscheatStorage::get_escheated(&env, &owner, &currency)
+    }
+
+    /// Reclaim a swept balance into the owner's balance (owner only, released
+    /// by the custodian). Returns the new balance.
+    pub fn claim_escheated_balance(
+        env: Env,
+        owner: Address,
+        currency: Address,
+    ) -> Result<i128, QuickLendXError> {
+        escheat::claim_escheated_balance(&env, &owner, &currency)
+    }
+
+    /// Get how much of an invoice has been repaid through installments
+    pub fn get_amount_paid(env: Env, invoice_id: BytesN<32>) -> i128 {
+        settlement::get_amount_paid(&env, &invoice_id)
+    }
+
+    /// Preview the token transfers settle_invoice would perform for this
+    /// payment, so wallets can display exact approvals before signing
+    pub fn build_settlement_plan(
+        env: Env,
+        invoice_id: BytesN<32>,
+        payment_amount: i128,
+    ) -> Result<SettlementPlan, QuickLendXError> {
+        settlement::build_settlement_plan(&env, &invoice_id, payment_amount)
+    }
+
+    /// Handle invoice default (admin only, once the grace period has passed)
+    pub fn handle_default(
+        env: Env,
+        admin: Address,
+        invoice_id: BytesN<32>,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "handle_default", (&invoice_id,));
+        do_handle_default(&env, &admin, &invoice_id)
+    }
+
+    /// Pay funds recovered on a defaulted invoice from `payer` to its
+    /// investors, pro rata (admin only)
+    pub fn record_default_recovery(
+        env: Env,
+        admin: Address,
+        invoice_id: BytesN<32>,
+        payer: Address,
+        amount: i128,
+    ) -> Result<DefaultRecovery, QuickLendXError> {
+        record_admin_action(&env, &admin, "record_default_recovery", (&invoice_id, &amount));
+        defaults::record_default_recovery(&env, &admin, &invoice_id, &payer, amount)
+    }
+
+    /// Write off what was not recovered on a defaulted invoice, ending its
+    /// recovery (admin only)
+    pub fn write_off_default(
+        env: Env,
+        admin: Address,
+        invoice_id: BytesN<32>,
+    ) -> Result<DefaultRecovery, QuickLendXError> {
+        record_admin_action(&env, &admin, "write_off_default", (&invoice_id,));
+        defaults::write_off_default(&env, &admin, &invoice_id)
+    }
+
+    /// Get what has been recovered on a defaulted invoice
+    pub fn get_default_recovery(env: Env, invoice_id: BytesN<32>) -> Option<DefaultRecovery> {
+        defaults::get_default_recovery(&env, &invoice_id)
+    }
+
+    /// List a defaulted investment's claim on future recoveries for sale
+    /// below the amount invested (claim owner only)
+    pub fn list_defaulted_claim(
+        env: Env,
+        seller: Address,
+        investment_id: BytesN<32>,
+        price: i128,
+    ) -> Result<ClaimListing, QuickLendXError> {
+        debt_market::list_claim(&env, &seller, &investment_id, price)
+    }
+
+    /// Take a listed claim off the market (seller only)
+    pub fn cancel_claim_listing(
+        env: Env,
+        seller: Address,
+        investment_id: BytesN<32>,
+    ) -> Result<(), QuickLendXError> {
+        debt_market::cancel_listing(&env, &seller, &investment_id)
+    }
+
+    /// Buy a listed claim, taking over its recovery rights (investor only)
+    pub fn buy_defaulted_claim(
+        env: Env,
+        buyer: Address,
+        investment_id: BytesN<32>,
+    ) -> Result<ClaimListing, QuickLendXError> {
+        debt_market::buy_claim(&env, &buyer, &investment_id)
+    }
+
+    /// Get every claim for sale, oldest listing first
+    pub fn get_claim_listings(env: Env) -> Vec<ClaimListing> {
+        debt_market::get_claim_listings(&env)
+    }
+
+    /// Get who holds an investment's claim on recoveries
+    pub fn get_claim_owner(
+        env: Env,
+        investment_id: BytesN<32>,
+    ) -> Result<Address, QuickLendXError> {
+        let investment = InvestmentStorage::get_investment(&env, &investment_id)
+            .ok_or(QuickLendXError::StorageKeyNotFound)?;
+        Ok(DebtMarketStorage::get_claim_owner(&env, &investment))
+    }
+
+    /// Get the claims an address has bought and still holds
+    pub fn get_owned_claims(env: Env, owner: Address) -> Vec<BytesN<32>> {
+        DebtMarketStorage::get_owned_claims(&env, &owner)
+    }
+
+    /// Propose rolling an unpaid funded invoice into a new invoice with a
+    /// later due date and adjusted amount (business only)
+    pub fn propose_rollover(
+        env: Env,
+        business: Address,
+        invoice_id: BytesN<32>,
+        new_due_date: u64,
+        new_amount: i128,
+    ) -> Result<RolloverProposal, QuickLendXError> {
+        rollover::propose_rollover(&env, &business, &invoice_id, new_due_date, new_amount)
+    }
+
+    /// Approve a proposed rollover (investor in the invoice only). Once all
+    /// investors approve, the invoice is closed and its replacement created.
+    pub fn approve_rollover(
+        env: Env,
+        investor: Address,
+        invoice_id: BytesN<32>,
+    ) -> Result<RolloverProposal, QuickLendXError> {
+        rollover::approve_rollover(&env, &investor, &invoice_id)
+    }
+
+    /// Get the rollover proposed for an invoice
+    pub fn get_rollover(env: Env, invoice_id: BytesN<32>) -> Option<RolloverProposal> {
+        rollover::get_rollover(&env, &invoice_id)
+    }
+
+    /// Set or clear the time by which a bid must be accepted on an unfunded
+    /// invoice (business only)
+    pub fn set_funding_deadline(
+        env: Env,
+        business: Address,
+        invoice_id: BytesN<32>,
+        deadline: Option<u64>,
+    ) -> Result<(), QuickLendXError> {
+        expiry::set_funding_deadline(&env, &business, &invoice_id, deadline)
+    }
+
+    /// Get an invoice's funding deadline, if it has one
+    pub fn get_funding_deadline(env: Env, invoice_id: BytesN<32>) -> Option<u64> {
+        expiry::get_funding_deadline(&env, &invoice_id)
+    }
+
+    /// Expire all invoices whose funding deadline passed without an accepted
+    /// bid, returning the funds of bids still open on them. Callable by
+    /// anyone; returns the expired invoice ids.
+    pub fn expire_invoices(env: Env) -> Result<Vec<BytesN<32>>, QuickLendXError> {
+        expiry::expire_invoices(&env)
+    }
+
+    /// Archive up to `max_invoices` paid or written-off defaulted invoices
+    /// closed for at least the archive age, moving them out of the live
+    /// lists. Callable by anyone; returns the archived invoice ids.
+    pub fn archive_invoices(env: Env, max_invoices: u32) -> Vec<BytesN<32>> {
+        archive::archive_invoices(&env, max_invoices)
+    }
+
+    /// Get an archived invoice
+    pub fn get_archived_invoice(env: Env, invoice_id: BytesN<32>) -> Option<ArchivedInvoice> {
+        ArchiveStorage::get_archived_invoice(&env, &invoice_id)
+    }
+
+    /// Get the ids of a business's archived invoices
+    pub fn get_business_archive(env: Env, business: Address) -> Vec<BytesN<32>> {
+        ArchiveStorage::get_business_archive(&env, &business)
+    }
+
+    /// Bring an archived invoice back into the live lists (admin only)
+    pub fn restore_archived_invoice(
+        env: Env,
+        admin: Address,
+        invoice_id: BytesN<32>,
+    ) -> Result<Invoice, QuickLendXError> {
+        record_admin_action(&env, &admin, "restore_archived_invoice", (&invoice_id,));
+        archive::restore_archived_invoice(&env, &admin, &invoice_id)
+    }
+
+    /// Set how long after closing an invoice may be archived (admin only)
+    pub fn set_archive_age(env: Env, admin: Address, age: u64) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_archive_age", (&age,));
+        archive::set_archive_age(&env, &admin, age)
+    }
+
+    /// Get how long after closing an invoice may be archived
+    pub fn get_archive_age(env: Env) -> u64 {
+        ArchiveStorage::get_archive_age(&env)
+    }
+
+    /// Default all funded invoices past their due date and grace period.
+    /// Callable by anyone; returns the defaulted invoice ids.
+    pub fn check_overdue_invoices(env: Env) -> Vec<BytesN<32>> {
+        do_check_overdue_invoices(&env)
+    }
+
+    /// Move funded invoices to the delinquency stage they have reached past
+    /// their due date. Callable by anyone; returns the ids of the invoices
+    /// whose stage changed.
+    pub fn update_delinquency_stages(env: Env) -> Vec<BytesN<32>> {
+        delinquency::update_delinquency_stages(&env)
+    }
+
+    /// Get the delinquency stage keepers last moved an invoice to
+    pub fn get_delinquency_stage(env: Env, invoice_id: BytesN<32>) -> DelinquencyStage {
+        delinquency::get_recorded_stage(&env, &invoice_id)
+    }
+
+    /// Ask to push back a funded invoice's due date once, with the hash of
+    /// evidence that its debtor is paying late (business only)
+    pub fn request_due_date_extension(
+        env: Env,
+        business: Address,
+        invoice_id: BytesN<32>,
+        new_due_date: u64,
+        evidence_hash: BytesN<32>,
+    ) -> Result<DueDateExtension, QuickLendXError> {
+        extension::request_extension(&env, &business, &invoice_id, new_due_date, evidence_hash)
+    }
+
+    /// Approve an invoice's due-date extension, moving its due date or, while
+    /// a vote window is set, putting it to the investors (admin or verifier
+    /// only)
+    pub fn approve_due_date_extension(
+        env: Env,
+        verifier: Address,
+        invoice_id: BytesN<32>,
+    ) -> Result<DueDateExtension, QuickLendXError> {
+        record_admin_action(&env, &verifier, "approve_due_date_extension", (&invoice_id,));
+        extension::decide_extension(&env, &verifier, &invoice_id, true)
+    }
+
+    /// Reject an invoice's due-date extension (admin or verifier only)
+    pub fn reject_due_date_extension(
+        env: Env,
+        verifier: Address,
+        invoice_id: BytesN<32>,
+    ) -> Result<DueDateExtension, QuickLendXError> {
+        record_admin_action(&env, &verifier, "reject_due_date_extension", (&invoice_id,));
+        extension::decide_extension(&env, &verifier, &invoice_id, false)
+    }
+
+    /// Set the most days a due date may be pushed back by (admin only)
+    pub fn set_max_extension_days(
+        env: Env,
+        admin: Address,
+        days: u32,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_max_extension_days", (&days,));
+        extension::set_max_extension_days(&env, &admin, days)
+    }
+
+    /// Get the most days a due date may be pushed back by
+    pub fn get_max_extension_days(env: Env) -> u32 {
+        extension::get_max_extension_days(&env)
+    }
+
+    /// Vote for or against a due-date extension put to the investors,
+    /// weighted by the amount funded (investor in the invoice only)
+    pub fn vote_on_due_date_extension(
+        env: Env,
+        investor: Address,
+        invoice_id: BytesN<32>,
+        approve: bool,
+    ) -> Result<DueDateExtension, QuickLendXError> {
+        extension::vote_on_extension(&env, &investor, &invoice_id, approve)
+    }
+
+    /// Reject a due-date extension whose investor vote timed out. Callable by
+    /// anyone.
+    pub fn close_due_date_extension_vote(
+        env: Env,
+        invoice_id: BytesN<32>,
+    ) -> Result<DueDateExtension, QuickLendXError> {
+        extension::close_extension_vote(&env, &invoice_id)
+    }
+
+    /// Get the investors' vote on an invoice's due-date extension, if one was
+    /// held
+    pub fn get_due_date_extension_vote(env: Env, invoice_id: BytesN<32>) -> Option<ExtensionVote> {
+        extension::get_extension_vote(&env, &invoice_id)
+    }
+
+    /// Set how long investors have to vote on an approved due-date
+    /// extension, in seconds; 0 lets a verifier's approval stand alone (admin
+    /// only)
+    pub fn set_extension_vote_window(
+        env: Env,
+        admin: Address,
+        window: u64,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_extension_vote_window", (&window,));
+        extension::set_extension_vote_window(&env, &admin, window)
+    }
+
+    /// Get how long investors have to vote on an approved due-date extension
+    pub fn get_extension_vote_window(env: Env) -> u64 {
+        extension::get_extension_vote_window(&env)
+    }
+
+    /// Publish a reminder event for each funded invoice due within `window`
+    /// seconds, once per invoice and window. Callable by anyone; returns the
+    /// ids of the invoices reminded of.
+    pub fn emit_due_reminders(env: Env, window: u64) -> Result<Vec<BytesN<32>>, QuickLendXError> {
+        reminders::emit_due_reminders(&env, window)
+    }
+
+    /// Get when an invoice was last reminded of for a reminder window
+    pub fn get_last_due_reminder(env: Env, invoice_id: BytesN<32>, window: u64) -> Option<u64> {
+        reminders::get_last_reminder(&env, &invoice_id, window)
+    }
+
+    /// Set the time after the due date before an invoice may be defaulted (admin only)
+    pub fn set_default_grace_period(
+        env: Env,
+        admin: Address,
+        grace_period: u64,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_default_grace_period", (&grace_period,));
+        config::set_default_grace_period(&env, &admin, grace_period)
+    }
+
+    /// Get the time after the due date before an invoice may be defaulted
+    pub fn get_default_grace_period(env: Env) -> u64 {
+        ConfigStorage::get_default_grace_period(&env)
+    }
+
+    /// Set the most distinct investors an invoice may have (admin only)
+    pub fn set_max_investors(
+        env: Env,
+        admin: Address,
+        max_investors: u32,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_max_investors", (&max_investors,));
+        config::set_max_investors(&env, &admin, max_investors)
+    }
+
+    /// Limit how much a business may have funded and outstanding at once; 0
+    /// removes the limit (admin only)
+    pub fn set_credit_limit(
+        env: Env,
+        admin: Address,
+        business: Address,
+        limit: i128,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_credit_limit", (&business, &limit));
+        credit::set_credit_limit(&env, &admin, &business, limit)
+    }
+
+    /// Get a business's credit limit, if it has one
+    pub fn get_credit_limit(env: Env, business: Address) -> Option<i128> {
+        CreditStorage::get_credit_limit(&env, &business)
+    }
+
+    /// Get the funded amount of a business's invoices not yet repaid or
+    /// defaulted
+    pub fn get_business_exposure(env: Env, business: Address) -> i128 {
+        credit::get_business_exposure(&env, &business)
+    }
+
+    /// Get a business's exposure valued in the reference currency
+    pub fn get_reference_exposure(env: Env, business: Address) -> Result<i128, QuickLendXError> {
+        credit::get_reference_exposure(&env, &business)
+    }
+
+    /// Ask to go past a credit limit or KYC tier cap for the invoices with the
+    /// given document hashes, backed by the hash of an off-chain
+    /// justification (business only). Returns the request id.
+    pub fn request_limit_increase(
+        env: Env,
+        business: Address,
+        kind: LimitKind,
+        requested_limit: i128,
+        invoice_hashes: Vec<BytesN<32>>,
+        justification_hash: BytesN<32>,
+    ) -> Result<u64, QuickLendXError> {
+        limits::request_limit_increase(
+            &env,
+            &business,
+            kind,
+            requested_limit,
+            invoice_hashes,
+            justification_hash,
+        )
+    }
+
+    /// Approve a limit-increase request, raising the cap for its invoices
+    /// only (admin only)
+    pub fn approve_limit_request(
+        env: Env,
+        admin: Address,
+        request_id: u64,
+        reason: String,
+    ) -> Result<LimitRequest, QuickLendXError> {
+        record_admin_action(&env, &admin, "approve_limit_request", (&request_id, &reason));
+        limits::decide_limit_request(&env, &admin, request_id, true, reason)
+    }
+
+    /// Deny a limit-increase request (admin only)
+    pub fn deny_limit_request(
+        env: Env,
+        admin: Address,
+        request_id: u64,
+        reason: String,
+    ) -> Result<LimitRequest, QuickLendXError> {
+        record_admin_action(&env, &admin, "deny_limit_request", (&request_id, &reason));
+        limits::decide_limit_request(&env, &admin, request_id, false, reason)
+    }
+
+    /// Get a limit-increase request by id
+    pub fn get_limit_request(env: Env, request_id: u64) -> Option<LimitRequest> {
+        LimitStorage::get_request(&env, request_id)
+    }
+
+    /// Get the ids of a business's limit-increase requests, oldest first
+    pub fn get_business_limit_requests(env: Env, business: Address) -> Vec<u64> {
+        LimitStorage::get_business_requests(&env, &business)
+    }
+
+    /// Get the most distinct investors an invoice may have
+    pub fn get_max_investors(env: Env) -> u32 {
+        ConfigStorage::get_max_investors(&env)
+    }
+
+    /// Set the protocol-wide minimum bid size; 0 disables it (admin only)
+    pub fn set_default_min_ticket(
+        env: Env,
+        admin: Address,
+        amount: i128,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_default_min_ticket", (&amount,));
+        config::set_default_min_ticket(&env, &admin, amount)
+    }
+
+    /// Get the protocol-wide minimum bid size
+    pub fn get_default_min_ticket(env: Env) -> i128 {
+        ConfigStorage::get_default_min_ticket(&env)
+    }
+
+    /// Set or clear an unfunded invoice's own minimum bid size (business only)
+    pub fn set_invoice_min_ticket(
+        env: Env,
+        business: Address,
+        invoice_id: BytesN<32>,
+        amount: Option<i128>,
+    ) -> Result<(), QuickLendXError> {
+        config::set_invoice_min_ticket(&env, &business, &invoice_id, amount)
+    }
+
+    /// Get the smallest bid an invoice accepts. A smaller bid is only allowed
+    /// when it covers exactly the remaining funding.
+    pub fn get_min_ticket(env: Env, invoice_id: BytesN<32>) -> i128 {
+        ConfigStorage::get_min_ticket(&env, &invoice_id)
+    }
+
+    /// Calculate profit and platform fee
+    pub fn calculate_profit(
+        _env: Env,
+        investment_amount: i128,
+        payment_amount: i128,
+        platform_fee_bps: i128,
+    ) -> (i128, i128) {
+        do_calculate_profit(investment_amount, payment_amount, platform_fee_bps)
+    }
+
+    // Rating Functions (from feat-invoice_rating_system)
+
+    /// Add a rating to an invoice (investor only)
+    pub fn add_invoice_rating(
+        env: Env,
+        invoice_id: BytesN<32>,
+        rating: u32,
+        feedback: String,
+        rater: Address,
+    ) -> Result<(), QuickLendXError> {
+        let mut invoice = InvoiceStorage::get_invoice(&env, &invoice_id)
+            .ok_or(QuickLendXError::InvoiceNotFound)?;
+
+        // Only the investor who funded the invoice can rate it
+        rater.require_auth();
+        check_feedback_length(&env, &feedback)?;
+
+        let old_average = invoice.average_rating;
+        invoice.add_rating(rating, feedback, rater.clone(), env.ledger().timestamp())?;
+        InvoiceStorage::update_invoice(&env, &invoice);
+
+        // Emit rating event
+        env.events()
+            .publish((symbol_short!("rated"),), (invoice_id, rating, rater));
+        if let Some(new_average) = invoice.average_rating.filter(|new| Some(*new) != old_average) {
+            emit_invoice_rating_updated(
+                &env,
+                &invoice,
+                old_average,
+                new_average,
+                symbol_short!("rated"),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Get invoices with ratings above a threshold
+    pub fn get_invoices_with_rating_above(env: Env, threshold: u32) -> Vec<BytesN<32>> {
+        InvoiceStorage::get_invoices_with_rating_above(&env, threshold)
+    }
+
+    /// Get business invoices with ratings above a threshold
+    pub fn get_business_rated_invoices(
+        env: Env,
+        business: Address,
+        threshold: u32,
+    ) -> Vec<BytesN<32>> {
+        InvoiceStorage::get_business_invoices_with_rating_above(&env, &business, threshold)
+    }
+
+    /// Get count of invoices with ratings
+    pub fn get_invoices_with_ratings_count(env: Env) -> u32 {
+        InvoiceStorage::get_invoices_with_ratings_count(&env)
+    }
+
+    /// Get invoice rating statistics
+    pub fn get_invoice_rating_stats(
+        env: Env,
+        invoice_id: BytesN<32>,
+    ) -> Result<(Option<u32>, u32, Option<u32>, Option<u32>), QuickLendXError> {
+        let invoice = InvoiceStorage::get_invoice(&env, &invoice_id)
+            .ok_or(QuickLendXError::InvoiceNotFound)?;
+
+        Ok((
+            invoice.average_rating,
+            invoice.total_ratings,
+            invoice.get_highest_rating(),
+            invoice.get_lowest_rating(),
+        ))
+    }
+
+    // Business KYC/Verification Functions (from main)
+
+    /// Submit KYC application (business only)
+    pub fn submit_kyc_application(
+        env: Env,
+        business: Address,
+        kyc_data: String,
+    ) -> Result<(), QuickLendXError> {
+        submit_kyc_application(&env, &business, kyc_data)
+    }
+
+    /// Verify business (admin or verifier only)
+    pub fn verify_business(
+        env: Env,
+        verifier: Address,
+        business: Address,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &verifier, "verify_business", (&business,));
+        verify_business(&env, &verifier, &business)
+    }
+
+    /// Reject business (admin or verifier only)
+    pub fn reject_business(
+        env: Env,
+        verifier: Address,
+        business: Address,
+        reason: String,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &verifier, "reject_business", (&business, &reason));
+        reject_business(&env, &verifier, &business, reason)
+    }
+
+    /// Allow an address to verify and reject businesses and invoices (admin
+    /// only)
+    pub fn add_verifier(
+        env: Env,
+        admin: Address,
+        verifier: Address,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "add_verifier", (&verifier,));
+        verification::add_verifier(&env, &admin, &verifier)
+    }
+
+    /// Withdraw a verifier's role (admin only)
+    pub fn remove_verifier(
+        env: Env,
+        admin: Address,
+        verifier: Address,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "remove_verifier", (&verifier,));
+        verification::remove_verifier(&env, &admin, &verifier)
+    }
+
+    /// Get the verifiers the admin has added
+    pub fn get_verifiers(env: Env) -> Vec<Address> {
+        BusinessVerificationStorage::get_verifiers(&env)
+    }
+
+    /// Replace the rules under which uploaded invoices are verified straight
+    /// away; an empty table turns auto-verification off (admin only)
+    pub fn set_auto_verify_rules(
+        env: Env,
+        admin: Address,
+        rules: Vec<AutoVerifyRule>,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_auto_verify_rules", (&rules,));
+        auto_verify::set_auto_verify_rules(&env, &admin, rules)
+    }
+
+    /// Get the rules under which uploaded invoices are verified straight away
+    pub fn get_auto_verify_rules(env: Env) -> Vec<AutoVerifyRule> {
+        AutoVerifyStorage::get_rules(&env)
+    }
+
+    /// Require invoices above an amount to be verified by several verifiers
+    /// (admin only)
+    pub fn set_verification_quorum(
+        env: Env,
+        admin: Address,
+        quorum: VerificationQuorum,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_verification_quorum", (&quorum,));
+        verification::set_verification_quorum(&env, &admin, quorum)
+    }
+
+    /// Get how many verifiers must approve high-value invoices
+    pub fn get_verification_quorum(env: Env) -> VerificationQuorum {
+        BusinessVerificationStorage::get_verification_quorum(&env)
+    }
+
+    /// Get the verifiers who have approved an invoice under the quorum rule
+    pub fn get_invoice_approvals(env: Env, invoice_id: BytesN<32>) -> Vec<Address> {
+        BusinessVerificationStorage::get_invoice_approvals(&env, &invoice_id)
+    }
+
+    /// Get business verification status
+    pub fn get_business_verification_status(
+        env: Env,
+        business: Address,
+    ) -> Option<verification::BusinessVerification> {
+        get_business_verification_status(&env, &business)
+    }
+
+    /// Renew a verified business's KYC after a fresh review, for another
+    /// validity period from now (admin only)
+    pub fn renew_kyc(env: Env, admin: Address, business: Address) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "renew_kyc", (&business,));
+        verification::renew_kyc(&env, &admin, &business)
+    }
+
+    /// Set how long new and renewed business verifications last, in seconds;
+    /// 0 makes them permanent (admin only)
+    pub fn set_kyc_validity_period(
+        env: Env,
+        admin: Address,
+        validity: u64,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_kyc_validity_period", (&validity,));
+        verification::set_kyc_validity(&env, &admin, validity)
+    }
+
+    /// Get how long new and renewed business verifications last, in seconds
+    pub fn get_kyc_validity_period(env: Env) -> u64 {
+        BusinessVerificationStorage::get_kyc_validity(&env)
+    }
+
+    /// Place a business in a KYC tier, which sets the caps on the invoices it
+    /// may upload (admin only)
+    pub fn set_kyc_tier(
+        env: Env,
+        admin: Address,
+        business: Address,
+        tier: KYCTier,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_kyc_tier", (&business, &tier));
+        verification::set_kyc_tier(&env, &admin, &business, tier)
+    }
+
+    /// Get the KYC tier of a business
+    pub fn get_kyc_tier(env: Env, business: Address) -> KYCTier {
+        BusinessVerificationStorage::get_kyc_tier(&env, &business)
+    }
+
+    /// Set the most a business in a tier may invoice at once and how many of
+    /// its invoices may be open at a time; 0 leaves a cap off (admin only)
+    pub fn set_tier_limits(
+        env: Env,
+        admin: Address,
+        tier: KYCTier,
+        limits: TierLimits,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_tier_limits", (&tier, &limits));
+        verification::set_tier_limits(&env, &admin, tier, limits)
+    }
+
+    /// Get the caps on businesses in a KYC tier
+    pub fn get_tier_limits(env: Env, tier: KYCTier) -> TierLimits {
+        BusinessVerificationStorage::get_tier_limits(&env, tier)
+    }
+
+    /// Submit KYC application (investor only)
+    pub fn submit_investor_kyc(
+        env: Env,
+        investor: Address,
+        kyc_data: String,
+    ) -> Result<(), QuickLendXError> {
+        investor_kyc::submit_investor_kyc(&env, &investor, kyc_data)
+    }
+
+    /// Verify an investor, recording whether it is accredited (admin or
+    /// verifier only)
+    pub fn verify_investor(
+        env: Env,
+        verifier: Address,
+        investor: Address,
+        accredited: bool,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &verifier, "verify_investor", (&investor, &accredited));
+        investor_kyc::verify_investor(&env, &verifier, &investor, accredited)
+    }
+
+    /// Reject an investor (admin or verifier only)
+    pub fn reject_investor(
+        env: Env,
+        verifier: Address,
+        investor: Address,
+        reason: String,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &verifier, "reject_investor", (&investor, &reason));
+        investor_kyc::reject_investor(&env, &verifier, &investor, reason)
+    }
+
+    /// Get investor verification status
+    pub fn get_investor_verification_status(
+        env: Env,
+        investor: Address,
+    ) -> Option<InvestorVerification> {
+        InvestorVerificationStorage::get_verification(&env, &investor)
+    }
+
+    /// Get the investors whose KYC is in a status
+    pub fn get_investors_by_status(env: Env, status: InvestorVerificationStatus) -> Vec<Address> {
+        InvestorVerificationStorage::get_investors_by_status(&env, status)
+    }
+
+    /// Require investors to be verified before they bid, or stop requiring it
+    /// (admin only)
+    pub fn set_investor_kyc_required(
+        env: Env,
+        admin: Address,
+        required: bool,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_investor_kyc_required", (&required,));
+        investor_kyc::set_investor_kyc_required(&env, &admin, required)
+    }
+
+    /// Get whether investors must be verified before they bid
+    pub fn is_investor_kyc_required(env: Env) -> bool {
+        InvestorVerificationStorage::is_verification_required(&env)
+    }
+
+    /// Replace the investor's stop-loss rules, which act when its portfolio
+    /// breaches their thresholds (investor only)
+    pub fn set_stop_loss_rules(
+        env: Env,
+        investor: Address,
+        rules: Vec<StopLossRule>,
+    ) -> Result<(), QuickLendXError> {
+        stop_loss::set_stop_loss_rules(&env, &investor, rules)
+    }
+
+    /// Let an investor paused by a stop-loss rule bid again (investor only)
+    pub fn resume_bidding(env: Env, investor: Address) -> Result<(), QuickLendXError> {
+        stop_loss::resume_bidding(&env, &investor)
+    }
+
+    /// Get an investor's stop-loss rules, whether its bidding is paused and
+    /// the rules' trigger history
+    pub fn get_stop_loss_state(env: Env, investor: Address) -> StopLossState {
+        StopLossStorage::get_state(&env, &investor)
+    }
+
+    /// Get the running totals stop-loss rules are checked against
+    pub fn get_portfolio_stats(env: Env, investor: Address) -> PortfolioStats {
+        StopLossStorage::get_stats(&env, &investor)
+    }
+
+    /// Get a page of an address's notifications, newest first, at most 50
+    pub fn get_notifications(
+        env: Env,
+        address: Address,
+        offset: u64,
+        limit: u32,
+    ) -> Vec<Notification> {
+        notifications::get_notifications(&env, &address, offset, limit)
+    }
+
+    /// Mark one of the address's notifications as read (address only)
+    pub fn mark_read(
+        env: Env,
+        address: Address,
+        notification_id: u64,
+    ) -> Result<(), QuickLendXError> {
+        notifications::mark_read(&env, &address, notification_id)
+    }
+
+    /// Get how many notifications an address has, and how many are unread
+    pub fn get_inbox_summary(env: Env, address: Address) -> InboxSummary {
+        NotificationStorage::get_summary(&env, &address)
+    }
+
+    /// Get the platform-wide totals: invoices created and, per currency, the
+    /// funded, escrowed, settled and defaulted volume
+    pub fn get_platform_stats(env: Env) -> PlatformStats {
+        analytics::get_platform_stats(&env)
+    }
+
+    /// Get the funded, escrowed, settled and defaulted volume across every
+    /// currency, valued in the reference currency
+    pub fn get_reference_stats(env: Env) -> Result<CurrencyStats, QuickLendXError> {
+        analytics::get_reference_stats(&env)
+    }
+
+    /// Set the price oracle used to value amounts in different currencies in
+    /// one reference currency (admin only)
+    pub fn set_price_oracle(
+        env: Env,
+        admin: Address,
+        config: OracleConfig,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_price_oracle", (&config,));
+        oracle::set_price_oracle(&env, &admin, config)
+    }
+
+    /// Get the price oracle configuration, if one is set
+    pub fn get_price_oracle(env: Env) -> Option<OracleConfig> {
+        OracleStorage::get_config(&env)
+    }
+
+    /// Value an amount of a currency in the reference currency
+    pub fn get_reference_value(
+        env: Env,
+        currency: Address,
+        amount: i128,
+    ) -> Result<i128, QuickLendXError> {
+        oracle::to_reference(&env, &currency, amount)
+    }
+
+    /// Get the funded, escrowed, settled and defaulted volume in a currency
+    pub fn get_currency_stats(env: Env, currency: Address) -> CurrencyStats {
+        AnalyticsStorage::get_currency_stats(&env, &currency)
+    }
+
+    /// Get a business's track record: invoices created, funded, paid and
+    /// defaulted, its on-time payment rate and average settlement delay
+    pub fn get_business_stats(env: Env, business: Address) -> BusinessStats {
+        AnalyticsStorage::get_business_counters(&env, &business).stats()
+    }
+
+    /// Get the annualized return in basis points implied by a bid of
+    /// `bid_amount` for `expected_return` on an invoice due in `days_to_due`
+    /// days (actual days over a 365-day year)
+    pub fn calculate_bid_apr(
+        _env: Env,
+        bid_amount: i128,
+        expected_return: i128,
+        days_to_due: u32,
+    ) -> i128 {
+        profits::calculate_bid_apr_bps(bid_amount, expected_return, days_to_due)
+    }
+
+    /// Get the annualized return in basis points an invoice's accepted bids
+    /// promise on its funding, from when it was fully funded to its due date
+    pub fn get_invoice_apr(env: Env, invoice_id: BytesN<32>) -> Result<i128, QuickLendXError> {
+        let invoice = InvoiceStorage::get_invoice(&env, &invoice_id)
+            .ok_or(QuickLendXError::InvoiceNotFound)?;
+        let funded_at = invoice.funded_at.ok_or(QuickLendXError::InvoiceNotFunded)?;
+        Ok(profits::calculate_apr_bps(
+            invoice.funded_amount,
+            penalty::expected_returns(&env, &invoice_id),
+            invoice.due_date.saturating_sub(funded_at),
+        ))
+    }
+
+    /// Get what an investor has put into and got back from settled investments
+    pub fn get_investor_returns(env: Env, investor: Address) -> InvestorReturns {
+        AnalyticsStorage::get_investor_returns(&env, &investor)
+    }
+
+    /// Get the annualized return in basis points an investor realized over
+    /// their settled investments, net of fees and levies
+    pub fn get_realized_apr(env: Env, investor: Address) -> i128 {
+        analytics::get_realized_apr_bps(&env, &investor)
+    }
+
+    /// Get the version of the data layout of the contract's schema v2 events,
+    /// whose topics are (domain, action, entity id)
+    pub fn get_event_schema_version(_env: Env) -> u32 {
+        EVENT_SCHEMA_VERSION
+    }
+
+    /// Create or update the business's public profile: its name, a hash of
+    /// its contact details and its country of registration (business only)
+    pub fn update_business_profile(
+        env: Env,
+        business: Address,
+        name: String,
+        contact_hash: BytesN<32>,
+        country: Symbol,
+    ) -> Result<BusinessProfile, QuickLendXError> {
+        profile::update_business_profile(&env, &business, name, contact_hash, country)
+    }
+
+    /// Get a business's profile, if it has one
+    pub fn get_business_profile(env: Env, business: Address) -> Option<BusinessProfile> {
+        ProfileStorage::get_profile(&env, &business)
+    }
+
+    /// Export a business's verification record, profile and a page of its
+    /// invoices with their receipts and ratings, at most 10 per page
+    pub fn export_business_data(
+        env: Env,
+        business: Address,
+        offset: u32,
+        limit: u32,
+    ) -> BusinessDataExport {
+        export::export_business_data(&env, &business, offset, limit)
+    }
+
+    /// Publish a hashed financial attestation (verified business only)
+    pub fn publish_attestation(
+        env: Env,
+        business: Address,
+        revenue_band: u32,
+        ar_aging_hash: BytesN<32>,
+    ) -> Result<(), QuickLendXError> {
+        attestation::publish_attestation(&env, &business, revenue_band, &ar_aging_hash)
+    }
+
+    /// Get a business's attestations, oldest first, skipping `offset` and
+    /// returning at most `limit`
+    pub fn get_attestations(
+        env: Env,
+        business: Address,
+        offset: u32,
+        limit: u32,
+    ) -> Vec<FinancialAttestation> {
+        AttestationStorage::get_attestations(&env, &business, offset, limit)
+    }
+
+    /// Get the latest attestation a business published
+    pub fn get_latest_attestation(env: Env, business: Address) -> Option<FinancialAttestation> {
+        attestation::get_latest_attestation(&env, &business)
+    }
+
+    /// Get when a business last attested and whether that attestation is stale
+    pub fn get_attestation_status(env: Env, business: Address) -> AttestationStatus {
+        attestation::get_attestation_status(&env, &business)
+    }
+
+    /// Set how long attestations stay fresh, in seconds (admin only)
+    pub fn set_attestation_max_age(
+        env: Env,
+        admin: Address,
+        max_age: u64,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_attestation_max_age", (&max_age,));
+        attestation::set_attestation_max_age(&env, &admin, max_age)
+    }
+
+    /// Get how long attestations stay fresh, in seconds
+    pub fn get_attestation_max_age(env: Env) -> u64 {
+        AttestationStorage::get_max_age(&env)
+    }
+
+    /// Trust an external attestation issuer with a weight in basis points,
+    /// or reweight one already trusted (admin only)
+    pub fn register_attestation_issuer(
+        env: Env,
+        admin: Address,
+        issuer: Address,
+        weight_bps: u32,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "register_attestation_issuer", (&issuer, &weight_bps));
+        attestation::register_attestation_issuer(&env, &admin, &issuer, weight_bps)
+    }
+
+    /// Stop trusting an external attestation issuer (admin only)
+    pub fn revoke_attestation_issuer(
+        env: Env,
+        admin: Address,
+        issuer: Address,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "revoke_attestation_issuer", (&issuer,));
+        attestation::revoke_attestation_issuer(&env, &admin, &issuer)
+    }
+
+    /// Get every registered attestation issuer, revoked ones included
+    pub fn get_attestation_issuers(env: Env) -> Vec<AttestationIssuer> {
+        AttestationStorage::get_issuers(&env)
+    }
+
+    /// Import a trusted issuer's attestation of a business's settled volume
+    /// elsewhere (signed by the issuer and the business)
+    pub fn import_attestation(
+        env: Env,
+        issuer: Address,
+        business: Address,
+        settled_volume: i128,
+        claim_hash: BytesN<32>,
+    ) -> Result<(), QuickLendXError> {
+        attestation::import_attestation(&env, &issuer, &business, settled_volume, &claim_hash)
+    }
+
+    /// Revoke an imported attestation (its issuer or the admin)
+    pub fn revoke_imported_attestation(
+        env: Env,
+        caller: Address,
+        business: Address,
+        issuer: Address,
+        claim_hash: BytesN<32>,
+    ) -> Result<(), QuickLendXError> {
+        attestation::revoke_imported_attestation(&env, &caller, &business, &issuer, &claim_hash)
+    }
+
+    /// Get the attestations imported for a business, oldest first, skipping
+    /// `offset` and returning at most `limit`
+    pub fn get_imported_attestations(
+        env: Env,
+        business: Address,
+        offset: u32,
+        limit: u32,
+    ) -> Vec<ImportedAttestation> {
+        AttestationStorage::get_imported(&env, &business, offset, limit)
+    }
+
+    /// Get the weighted settled volume a business's imported attestations
+    /// vouch for
+    pub fn get_imported_reputation(env: Env, business: Address) -> i128 {
+        attestation::get_imported_reputation(&env, &business)
+    }
+
+    /// Open a supply-chain finance program, or change its rate cap and reopen
+    /// it (verified business only)
+    pub fn open_debtor_program(
+        env: Env,
+        debtor: Address,
+        currency: Address,
+        max_apr_bps: u32,
+    ) -> Result<DebtorProgram, QuickLendXError> {
+        reverse_factoring::open_debtor_program(&env, &debtor, &currency, max_apr_bps)
+    }
+
+    /// Close a debtor's program to new invoices (debtor only)
+    pub fn close_debtor_program(env: Env, debtor: Address) -> Result<(), QuickLendXError> {
+        reverse_factoring::close_debtor_program(&env, &debtor)
+    }
+
+    /// Confirm a supplier's pending invoice as owed under the debtor's
+    /// program, listing it for funding at the program's rate (debtor only)
+    pub fn confirm_supplier_invoice(
+        env: Env,
+        debtor: Address,
+        invoice_id: BytesN<32>,
+    ) -> Result<(), QuickLendXError> {
+        let invoice = InvoiceStorage::get_invoice(&env, &invoice_id)
+            .ok_or(QuickLendXError::InvoiceNotFound)?;
+        reverse_factoring::confirm_supplier_invoice(&env, &debtor, &invoice)?;
+        Self::list_verified_invoice(env, invoice, debtor)
+    }
+
+    /// Set the platform fee charged on program invoices instead of the
+    /// platform fee (admin only)
+    pub fn set_reverse_factoring_fee(
+        env: Env,
+        admin: Address,
+        fee_bps: u32,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_reverse_factoring_fee", (&fee_bps,));
+        reverse_factoring::set_reverse_factoring_fee(&env, &admin, fee_bps)
+    }
+
+    /// Get the platform fee charged on program invoices, if set
+    pub fn get_reverse_factoring_fee(env: Env) -> Option<u32> {
+        ReverseFactoringStorage::get_fee_bps(&env)
+    }
+
+    /// Get a debtor's program
+    pub fn get_debtor_program(env: Env, debtor: Address) -> Option<DebtorProgram> {
+        ReverseFactoringStorage::get_program(&env, &debtor)
+    }
+
+    /// Get every program, open or closed, oldest first
+    pub fn get_debtor_programs(env: Env) -> Vec<DebtorProgram> {
+        reverse_factoring::get_debtor_programs(&env)
+    }
+
+    /// Get the invoices a debtor has confirmed under its program
+    pub fn get_program_invoices(env: Env, debtor: Address) -> Vec<BytesN<32>> {
+        ReverseFactoringStorage::get_program_invoices(&env, &debtor)
+    }
+
+    /// Get a program's invoices in a status, e.g. Verified for those open to bids
+    pub fn get_program_invoices_by_status(
+        env: Env,
+        debtor: Address,
+        status: InvoiceStatus,
+    ) -> Vec<BytesN<32>> {
+        reverse_factoring::get_program_invoices_by_status(&env, &debtor, &status)
+    }
+
+    /// Get the debtor whose program an invoice was confirmed under
+    pub fn get_invoice_debtor(env: Env, invoice_id: BytesN<32>) -> Option<Address> {
+        ReverseFactoringStorage::get_invoice_debtor(&env, &invoice_id)
+    }
+
+    /// Guarantee a credit line for the debtor's program, or change its limit
+    /// (debtor only)
+    pub fn set_program_credit_line(
+        env: Env,
+        debtor: Address,
+        limit: i128,
+    ) -> Result<ProgramCreditLine, QuickLendXError> {
+        reverse_factoring::set_program_credit_line(&env, &debtor, limit)
+    }
+
+    /// Get the credit line a debtor guarantees for its program
+    pub fn get_program_credit_line(env: Env, debtor: Address) -> Option<ProgramCreditLine> {
+        ReverseFactoringStorage::get_credit_line(&env, &debtor)
+    }
+
+    /// Get the share of a program's credit line in use, in basis points
+    pub fn get_program_line_utilization(env: Env, debtor: Address) -> u32 {
+        reverse_factoring::get_credit_line_utilization_bps(&env, &debtor)
+    }
+
+    /// Invite a supplier to the debtor's program for `valid_for` seconds
+    /// (debtor only)
+    pub fn invite_supplier(
+        env: Env,
+        debtor: Address,
+        supplier: Address,
+        valid_for: u64,
+    ) -> Result<SupplierInvitation, QuickLendXError> {
+        reverse_factoring::invite_supplier(&env, &debtor, &supplier, valid_for)
+    }
+
+    /// Accept a debtor's invitation, submitting KYC data for expedited
+    /// review if the supplier is not yet verified (supplier only)
+    pub fn accept_supplier_invitation(
+        env: Env,
+        supplier: Address,
+        debtor: Address,
+        kyc_data: String,
+    ) -> Result<SupplierInvitation, QuickLendXError> {
+        reverse_factoring::accept_invitation(&env, &supplier, &debtor, kyc_data)
+    }
+
+    /// Get a debtor's invitation to a supplier
+    pub fn get_supplier_invitation(
+        env: Env,
+        debtor: Address,
+        supplier: Address,
+    ) -> Option<SupplierInvitation> {
+        ReverseFactoringStorage::get_invitation(&env, &debtor, &supplier)
+    }
+
+    /// Get every invitation a supplier has received
+    pub fn get_supplier_invitations(env: Env, supplier: Address) -> Vec<SupplierInvitation> {
+        reverse_factoring::get_supplier_invitations(&env, &supplier)
+    }
+
+    /// Get the suppliers that have joined a debtor's program
+    pub fn get_program_suppliers(env: Env, debtor: Address) -> Vec<Address> {
+        ReverseFactoringStorage::get_program_suppliers(&env, &debtor)
+    }
+
+    /// Get invited suppliers whose KYC applications await expedited review
+    pub fn get_expedited_kyc_applications(env: Env) -> Vec<Address> {
+        reverse_factoring::get_expedited_kyc_applications(&env)
+    }
+
+    /// Set the initial admin address (can only be called once)
+    pub fn initialize(env: Env, admin: Address) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "initialize", ());
+        verification::initialize_admin(&env, &admin)?;
+        upgrade::set_schema_version(&env, upgrade::CURRENT_SCHEMA_VERSION);
+        Ok(())
+    }
+
+    /// Get admin address
+    pub fn get_admin(env: Env) -> Option<Address> {
+        BusinessVerificationStorage::get_admin(&env)
+    }
+
+    /// Nominate a new admin (admin only); the nominee must call accept_admin
+    pub fn transfer_admin(
+        env: Env,
+        admin: Address,
+        new_admin: Address,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "transfer_admin", (&new_admin,));
+        verification::transfer_admin(&env, &admin, &new_admin)
+    }
+
+    /// Accept a pending admin nomination (nominee only)
+    pub fn accept_admin(env: Env, new_admin: Address) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &new_admin, "accept_admin", ());
+        verification::accept_admin(&env, &new_admin)
+    }
+
+    /// Get the admin nominee awaiting acceptance, if any
+    pub fn get_pending_admin(env: Env) -> Option<Address> {
+        BusinessVerificationStorage::get_pending_admin(&env)
+    }
+
+    /// Replace the contract code with an uploaded WASM (admin only)
+    pub fn upgrade(
+        env: Env,
+        admin: Address,
+        new_wasm_hash: BytesN<32>,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "upgrade", (&new_wasm_hash,));
+        upgrade::upgrade(&env, &admin, &new_wasm_hash)
+    }
+
+    /// Migrate stored data to the schema expected by this code (admin only)
+    pub fn migrate(env: Env, admin: Address) -> Result<u32, QuickLendXError> {
+        record_admin_action(&env, &admin, "migrate", ());
+        upgrade::migrate(&env, &admin)
+    }
+
+    /// Extend the TTL of the contract instance and of the given invoices'
+    /// records (anyone). Returns how many records were found.
+    pub fn extend_ttl(env: Env, invoice_ids: Vec<BytesN<32>>) -> u32 {
+        storage::extend_ttl(&env, &invoice_ids)
+    }
+
+    /// Get the schema version of the data in storage
+    pub fn get_schema_version(env: Env) -> u32 {
+        upgrade::get_schema_version(&env)
+    }
+
+    /// Add a currency to the supported whitelist (admin only)
+    pub fn add_supported_currency(
+        env: Env,
+        admin: Address,
+        currency: Address,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "add_supported_currency", (&currency,));
+        add_supported_currency(&env, &admin, &currency)
+    }
+
+    /// Remove a currency from the supported whitelist (admin only)
+    pub fn remove_supported_currency(
+        env: Env,
+        admin: Address,
+        currency: Address,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "remove_supported_currency", (&currency,));
+        remove_supported_currency(&env, &admin, &currency)
+    }
+
+    /// Get the supported currency whitelist (empty means any currency is accepted)
+    pub fn get_supported_currencies(env: Env) -> Vec<Address> {
+        ConfigStorage::get_supported_currencies(&env)
+    }
+
+    /// Set the platform fee recipient and rate in bps of investor profit
+    /// (admin only, at most MAX_PLATFORM_FEE_BPS)
+    pub fn set_platform_fee(
+        env: Env,
+        admin: Address,
+        recipient: Address,
+        fee_bps: u32,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_platform_fee", (&recipient, &fee_bps));
+        config::set_platform_fee(&env, &admin, &recipient, fee_bps)
+    }
+
+    /// Get the platform fee configuration
+    pub fn get_platform_fee(env: Env) -> PlatformFeeConfig {
+        ConfigStorage::get_platform_fee(&env)
+    }
+
+    /// Get the platform fees held by the contract in a currency
+    pub fn get_treasury_balance(env: Env, currency: Address) -> i128 {
+        TreasuryStorage::get_balance(&env, &currency)
+    }
+
+    /// Appoint the treasurer, who may withdraw fees alongside the admin
+    pub fn set_treasurer(
+        env: Env,
+        admin: Address,
+        treasurer: Address,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_treasurer", (&treasurer,));
+        treasury::set_treasurer(&env, &admin, &treasurer)
+    }
+
+    /// Get the treasurer, if one has been appointed
+    pub fn get_treasurer(env: Env) -> Option<Address> {
+        TreasuryStorage::get_treasurer(&env)
+    }
+
+    /// Withdraw collected platform fees (admin or treasurer only)
+    pub fn withdraw_fees(
+        env: Env,
+        caller: Address,
+        currency: Address,
+        amount: i128,
+        to: Address,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &caller, "withdraw_fees", (&currency, &amount, &to));
+        treasury::withdraw_fees(&env, &caller, &currency, amount, &to)
+    }
+
+    /// Appoint the insurance admin who reviews and pays claims (admin only)
+    pub fn set_insurance_admin(
+        env: Env,
+        admin: Address,
+        insurance_admin: Address,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_insurance_admin", (&insurance_admin,));
+        insurance::set_insurance_admin(&env, &admin, &insurance_admin)
+    }
+
+    /// Update the rates used to price insurance premiums (admin only)
+    pub fn set_premium_rates(
+        env: Env,
+        admin: Address,
+        rates: PremiumRates,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_premium_rates", (&rates,));
+        insurance::set_premium_rates(&env, &admin, &rates)
+    }
+
+    /// Get the rates used to price insurance premiums
+    pub fn get_premium_rates(env: Env) -> PremiumRates {
+        InsuranceStorage::get_premium_rates(&env)
+    }
+
+    /// Turn insurance on or off for an invoice before it is funded (business only)
+    pub fn set_invoice_insurance(
+        env: Env,
+        business: Address,
+        invoice_id: BytesN<32>,
+        enabled: bool,
+    ) -> Result<(), QuickLendXError> {
+        insurance::set_invoice_insurance(&env, &business, &invoice_id, enabled)
+    }
+
+    /// Get a business's risk score (0-100) from its repayment history and
+    /// attestation freshness
+    pub fn get_business_risk_score(env: Env, business: Address) -> u32 {
+        insurance::business_risk_score(&env, &business)
+    }
+
+    /// Recompute a business's risk score, emitting `score_updated` if it
+    /// moved since it was last published. Anyone may call this, to catch
+    /// changes no transaction triggers, such as an attestation going stale or
+    /// an issuer losing trust. Returns the score.
+    pub fn refresh_risk_score(env: Env, business: Address) -> u32 {
+        insurance::publish_risk_score(&env, &business, symbol_short!("refresh"))
+    }
+
+    /// Quote the premium in bps for insuring a funding of an invoice now
+    pub fn quote_insurance_premium(
+        env: Env,
+        invoice_id: BytesN<32>,
+    ) -> Result<u32, QuickLendXError> {
+        let invoice = InvoiceStorage::get_invoice(&env, &invoice_id)
+            .ok_or(QuickLendXError::InvoiceNotFound)?;
+        let risk_score = insurance::business_risk_score(&env, &invoice.business);
+        let tenor = invoice.due_date.saturating_sub(env.ledger().timestamp());
+        Ok(insurance::premium_bps(&env, risk_score, tenor))
+    }
+
+    /// Add capital to the insurance fund
+    pub fn deposit_insurance_fund(
+        env: Env,
+        depositor: Address,
+        currency: Address,
+        amount: i128,
+    ) -> Result<(), QuickLendXError> {
+        insurance::deposit_insurance_fund(&env, &depositor, &currency, amount)
+    }
+
+    /// Get the insurance fund balance in a currency
+    pub fn get_insurance_fund_balance(env: Env, currency: Address) -> i128 {
+        InsuranceStorage::get_fund_balance(&env, &currency)
+    }
+
+    /// File an insurance claim for an investment in a defaulted invoice
+    /// (claim owner only)
+    pub fn submit_insurance_claim(
+        env: Env,
+        investor: Address,
+        investment_id: BytesN<32>,
+        amount: i128,
+    ) -> Result<BytesN<32>, QuickLendXError> {
+        insurance::submit_claim(&env, &investor, &investment_id, amount)
+    }
+
+    /// Take a submitted claim into review
+    pub fn start_claim_review(
+        env: Env,
+        reviewer: Address,
+        claim_id: BytesN<32>,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &reviewer, "start_claim_review", (&claim_id,));
+        insurance::start_claim_review(&env, &reviewer, &claim_id)
+    }
+
+    /// Approve a claim under review
+    pub fn approve_claim(
+        env: Env,
+        reviewer: Address,
+        claim_id: BytesN<32>,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &reviewer, "approve_claim", (&claim_id,));
+        insurance::approve_claim(&env, &reviewer, &claim_id)
+    }
+
+    /// Deny a claim under review
+    pub fn deny_claim(
+        env: Env,
+        reviewer: Address,
+        claim_id: BytesN<32>,
+        reason: String,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &reviewer, "deny_claim", (&claim_id, &reason));
+        insurance::deny_claim(&env, &reviewer, &claim_id, &reason)
+    }
+
+    /// Appeal a denied claim; appeals are ruled on by the dispute arbitrator
+    pub fn appeal_claim(
+        env: Env,
+        investor: Address,
+        claim_id: BytesN<32>,
+        grounds: String,
+    ) -> Result<(), QuickLendXError> {
+        insurance::appeal_claim(&env, &investor, &claim_id, &grounds)
+    }
+
+    /// Pay an approved claim from the insurance fund
+    pub fn pay_claim(
+        env: Env,
+        reviewer: Address,
+        claim_id: BytesN<32>,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &reviewer, "pay_claim", (&claim_id,));
+        insurance::pay_claim(&env, &reviewer, &claim_id)
+    }
+
+    /// Get an insurance claim by ID
+    pub fn get_insurance_claim(env: Env, claim_id: BytesN<32>) -> Option<InsuranceClaim> {
+        InsuranceStorage::get_claim(&env, &claim_id)
+    }
+
+    /// Commit capital to backstop the insurance fund in a currency, or update
+    /// an existing commitment. Lower priorities are drawn first.
+    pub fn register_backstop(
+        env: Env,
+        provider: Address,
+        currency: Address,
+        committed: i128,
+        priority: u32,
+    ) -> Result<(), QuickLendXError> {
+        backstop::register_backstop(&env, &provider, &currency, committed, priority)
+    }
+
+    /// Get the backstop providers for a currency in draw order
+    pub fn get_backstop_providers(env: Env, currency: Address) -> Vec<BackstopProvider> {
+        BackstopStorage::get_providers(&env, &currency)
+    }
+
+    /// Set the share of each premium paid to backstop providers (admin only)
+    pub fn set_backstop_fee(env: Env, admin: Address, fee_bps: u32) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_backstop_fee", (&fee_bps,));
+        backstop::set_backstop_fee(&env, &admin, fee_bps)
+    }
+
+    /// Get the share of each premium paid to backstop providers
+    pub fn get_backstop_fee(env: Env) -> u32 {
+        BackstopStorage::get_fee_bps(&env)
+    }
+
+    /// Set the settlement levy feeding the insurance pool and the share of
+    /// principal defaulted investors recover from it (admin only)
+    pub fn set_insurance_pool_config(
+        env: Env,
+        admin: Address,
+        config: InsurancePoolConfig,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_insurance_pool_config", (&config,));
+        insurance::set_pool_config(&env, &admin, &config)
+    }
+
+    /// Get the insurance pool's levy and recovery settings
+    pub fn get_insurance_pool_config(env: Env) -> InsurancePoolConfig {
+        InsuranceStorage::get_pool_config(&env)
+    }
+
+    /// Recover the configured share of principal behind the caller's claims
+    /// in a defaulted invoice from the insurance pool. Returns the amount paid.
+    pub fn claim_insurance(
+        env: Env,
+        investor: Address,
+        invoice_id: BytesN<32>,
+    ) -> Result<i128, QuickLendXError> {
+        insurance::claim_insurance(&env, &investor, &invoice_id)
+    }
+
+    /// Get the ids of insurance claims in a status
+    pub fn get_claims_by_status(env: Env, status: ClaimStatus) -> Vec<BytesN<32>> {
+        InsuranceStorage::get_claims_by_status(&env, &status)
+    }
+
+    /// Set maximum lengths for descriptions, feedback, KYC data and rejection
+    /// reasons (admin only, within hard bounds)
+    pub fn set_size_limits(
+        env: Env,
+        admin: Address,
+        limits: SizeLimits,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_size_limits", (&limits,));
+        config::set_size_limits(&env, &admin, &limits)
+    }
+
+    /// Get the current text field size limits
+    pub fn get_size_limits(env: Env) -> SizeLimits {
+        ConfigStorage::get_size_limits(&env)
+    }
+
+    /// Assign a jurisdiction code to a business or investor (admin only)
+    pub fn set_jurisdiction(
+        env: Env,
+        admin: Address,
+        address: Address,
+        code: Symbol,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_jurisdiction", (&address, &code));
+        jurisdiction::set_jurisdiction(&env, &admin, &address, &code)
+    }
+
+    /// Get the jurisdiction code of a business or investor
+    pub fn get_jurisdiction(env: Env, address: Address) -> Option<Symbol> {
+        JurisdictionStorage::get_jurisdiction(&env, &address)
+    }
+
+    /// Create or replace a jurisdiction rule (admin only)
+    pub fn set_jurisdiction_rule(
+        env: Env,
+        admin: Address,
+        rule: JurisdictionRule,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_jurisdiction_rule", (&rule,));
+        jurisdiction::set_jurisdiction_rule(&env, &admin, &rule)
+    }
+
+    /// Remove a jurisdiction rule (admin only)
+    pub fn remove_jurisdiction_rule(
+        env: Env,
+        admin: Address,
+        code: Symbol,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "remove_jurisdiction_rule", (&code,));
+        jurisdiction::remove_jurisdiction_rule(&env, &admin, &code)
+    }
+
+    /// Get the rule for a jurisdiction
+    pub fn get_jurisdiction_rule(env: Env, code: Symbol) -> Option<JurisdictionRule> {
+        JurisdictionStorage::get_rule(&env, &code)
+    }
+
+    /// Get all jurisdiction codes with a configured rule
+    pub fn get_jurisdiction_codes(env: Env) -> Vec<Symbol> {
+        JurisdictionStorage::get_rule_codes(&env)
+    }
+
+    /// Replace the late payment penalty schedule (admin only)
+    pub fn set_penalty_schedule(
+        env: Env,
+        admin: Address,
+        schedule: PenaltySchedule,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_penalty_schedule", (&schedule,));
+        penalty::set_penalty_schedule(&env, &admin, &schedule)
+    }
+
+    /// Get the late payment penalty schedule
+    pub fn get_penalty_schedule(env: Env) -> PenaltySchedule {
+        penalty::get_penalty_schedule(&env)
+    }
+
+    /// Set the flat late fee charged per full day past grace (admin only)
+    pub fn set_late_fee(
+        env: Env,
+        admin: Address,
+        bps_per_day: u32,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_late_fee", (&bps_per_day,));
+        penalty::set_late_fee_bps_per_day(&env, &admin, bps_per_day)
+    }
+
+    /// Get the flat late fee charged per full day past grace
+    pub fn get_late_fee(env: Env) -> u32 {
+        penalty::get_late_fee_bps_per_day(&env)
+    }
+
+    /// Get what is still owed on a funded invoice, late charges included and
+    /// installments deducted. Settlement must pay at least this amount.
+    pub fn get_amount_due(env: Env, invoice_id: BytesN<32>) -> Result<i128, QuickLendXError> {
+        Ok(penalty::get_amount_due(&env, &invoice_id)?.outstanding)
+    }
+
+    /// Get the principal, profit, late charges and per-tier accrual breakdown
+    /// of the amount due
+    pub fn get_amount_due_breakdown(
+        env: Env,
+        invoice_id: BytesN<32>,
+    ) -> Result<AmountDue, QuickLendXError> {
+        penalty::get_amount_due(&env, &invoice_id)
     }
 
-    /// Get invoices with ratings above a threshold
-    pub fn get_invoices_with_rating_above(env: Env, threshold: u32) -> Vec<BytesN<32>> {
-        InvoiceStorage::get_invoices_with_rating_above(&env, threshold)
+    /// Get all verified businesses
+    pub fn get_verified_businesses(env: Env) -> Vec<Address> {
+        BusinessVerificationStorage::get_verified_businesses(&env)
     }
 
-    /// Get business invoices with ratings above a threshold
-    pub fn get_business_rated_invoices(
-        env: Env,
-        business: Address,
-        threshold: u32,
-    ) -> Vec<BytesN<32>> {
-        InvoiceStorage::get_business_invoices_with_rating_above(&env, &business, threshold)
+    /// Get all pending businesses
+    pub fn get_pending_businesses(env: Env) -> Vec<Address> {
+        BusinessVerificationStorage::get_pending_businesses(&env)
     }
 
-    /// Get count of invoices with ratings
-    pub fn get_invoices_with_ratings_count(env: Env) -> u32 {
-        InvoiceStorage::get_invoices_with_ratings_count(&env)
+    /// Get all rejected businesses
+    pub fn get_rejected_businesses(env: Env) -> Vec<Address> {
+        BusinessVerificationStorage::get_rejected_businesses(&env)
     }
 
-    /// Get invoice rating statistics
-    pub fn get_invoice_rating_stats(
+    /// Release all held funding escrows of an invoice to the business (admin only)
+    pub fn release_escrow_funds(
         env: Env,
+        admin: Address,
         invoice_id: BytesN<32>,
-    ) -> Result<(Option<u32>, u32, Option<u32>, Option<u32>), QuickLendXError> {
-        let invoice = InvoiceStorage::get_invoice(&env, &invoice_id)
-            .ok_or(QuickLendXError::InvoiceNotFound)?;
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "release_escrow_funds", (&invoice_id,));
+        require_admin(&env, &admin)?;
+        let held = EscrowStorage::get_held_funding_escrows(&env, &invoice_id)?;
+
+        for escrow in held.iter() {
+            // Release escrow funds
+            let escrow = release_escrow(&env, &escrow.escrow_id, &admin)?;
+
+            // Emit event
+            emit_escrow_released(
+                &env,
+                &escrow.escrow_id,
+                &invoice_id,
+                &escrow.business,
+                escrow.amount,
+                &admin,
+            );
+        }
 
-        Ok((
-            invoice.average_rating,
-            invoice.total_ratings,
-            invoice.get_highest_rating(),
-            invoice.get_lowest_rating(),
-        ))
+        Ok(())
     }
 
-    // Business KYC/Verification Functions (from main)
-
-    /// Submit KYC application (business only)
-    pub fn submit_kyc_application(
+    /// Refund all held funding escrows of an invoice to their investors (admin only)
+    pub fn refund_escrow_funds(
         env: Env,
-        business: Address,
-        kyc_data: String,
+        admin: Address,
+        invoice_id: BytesN<32>,
     ) -> Result<(), QuickLendXError> {
-        submit_kyc_application(&env, &business, kyc_data)
+        record_admin_action(&env, &admin, "refund_escrow_funds", (&invoice_id,));
+        require_admin(&env, &admin)?;
+        let held = EscrowStorage::get_held_funding_escrows(&env, &invoice_id)?;
+
+        for escrow in held.iter() {
+            // Refund escrow funds
+            let escrow = refund_escrow(&env, &escrow.escrow_id, &admin)?;
+
+            // Emit event
+            emit_escrow_refunded(
+                &env,
+                &escrow.escrow_id,
+                &invoice_id,
+                &escrow.investor,
+                escrow.amount,
+                &admin,
+            );
+        }
+
+        Ok(())
     }
 
-    /// Verify business (admin only)
-    pub fn verify_business(
+    /// Register a contract whose `on_invoice_event(invoice_id, event)` is
+    /// called on invoice lifecycle events (admin only)
+    pub fn register_observer(
         env: Env,
         admin: Address,
-        business: Address,
+        observer: Address,
     ) -> Result<(), QuickLendXError> {
-        verify_business(&env, &admin, &business)
+        record_admin_action(&env, &admin, "register_observer", (&observer,));
+        observers::register_observer(&env, &admin, &observer)
     }
 
-    /// Reject business (admin only)
-    pub fn reject_business(
+    /// Stop notifying an observer contract (admin only)
+    pub fn remove_observer(
         env: Env,
         admin: Address,
-        business: Address,
-        reason: String,
+        observer: Address,
     ) -> Result<(), QuickLendXError> {
-        reject_business(&env, &admin, &business, reason)
+        record_admin_action(&env, &admin, "remove_observer", (&observer,));
+        observers::remove_observer(&env, &admin, &observer)
     }
 
-    /// Get business verification status
-    pub fn get_business_verification_status(
+    /// Get the registered observer contracts
+    pub fn get_observers(env: Env) -> Vec<Address> {
+        ObserverStorage::get_observers(&env)
+    }
+
+    /// Appoint the arbitrator who resolves disputes (admin only)
+    pub fn set_arbitrator(
         env: Env,
-        business: Address,
-    ) -> Option<verification::BusinessVerification> {
-        get_business_verification_status(&env, &business)
+        admin: Address,
+        arbitrator: Address,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "set_arbitrator", (&arbitrator,));
+        disputes::set_arbitrator(&env, &admin, &arbitrator)
     }
 
-    /// Set admin address (initialization function)
-    pub fn set_admin(env: Env, admin: Address) {
-        BusinessVerificationStorage::set_admin(&env, &admin);
+    /// Get the arbitrator who resolves disputes
+    pub fn get_arbitrator(env: Env) -> Option<Address> {
+        DisputeStorage::get_arbitrator(&env)
     }
 
-    /// Get admin address
-    pub fn get_admin(env: Env) -> Option<Address> {
-        BusinessVerificationStorage::get_admin(&env)
+    /// Open a dispute over an invoice's escrowed funds (business or investor
+    /// only). Release and refund are frozen until the dispute is resolved.
+    pub fn open_dispute(
+        env: Env,
+        caller: Address,
+        invoice_id: BytesN<32>,
+        reason: String,
+    ) -> Result<(), QuickLendXError> {
+        disputes::open_dispute(&env, &caller, &invoice_id, &reason)
     }
 
-    /// Get all verified businesses
-    pub fn get_verified_businesses(env: Env) -> Vec<Address> {
-        BusinessVerificationStorage::get_verified_businesses(&env)
+    /// Resolve a dispute, releasing, refunding or splitting the escrowed funds
+    /// (arbitrator or admin only)
+    pub fn resolve_dispute(
+        env: Env,
+        arbitrator: Address,
+        invoice_id: BytesN<32>,
+        outcome: DisputeOutcome,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &arbitrator, "resolve_dispute", (&invoice_id, &outcome));
+        disputes::resolve_dispute(&env, &arbitrator, &invoice_id, &outcome)
     }
 
-    /// Get all pending businesses
-    pub fn get_pending_businesses(env: Env) -> Vec<Address> {
-        BusinessVerificationStorage::get_pending_businesses(&env)
+    /// Rule on an appealed insurance claim, approving it if the appeal is
+    /// upheld (arbitrator or admin only)
+    pub fn resolve_claim_appeal(
+        env: Env,
+        arbitrator: Address,
+        claim_id: BytesN<32>,
+        upheld: bool,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &arbitrator, "resolve_claim_appeal", (&claim_id, &upheld));
+        disputes::resolve_claim_appeal(&env, &arbitrator, &claim_id, upheld)
     }
 
-    /// Get all rejected businesses
-    pub fn get_rejected_businesses(env: Env) -> Vec<Address> {
-        BusinessVerificationStorage::get_rejected_businesses(&env)
+    /// Get the appeal filed against an insurance claim's denial, if any
+    pub fn get_claim_appeal(env: Env, claim_id: BytesN<32>) -> Option<Dispute> {
+        DisputeStorage::get_claim_appeal(&env, &claim_id)
     }
 
-    /// Release escrow funds to business upon invoice verification
-    pub fn release_escrow_funds(env: Env, invoice_id: BytesN<32>) -> Result<(), QuickLendXError> {
-        let escrow = EscrowStorage::get_escrow_by_invoice(&env, &invoice_id)
-            .ok_or(QuickLendXError::StorageKeyNotFound)?;
+    /// Get the latest dispute raised over an invoice
+    pub fn get_dispute(env: Env, invoice_id: BytesN<32>) -> Option<Dispute> {
+        DisputeStorage::get_dispute(&env, &invoice_id)
+    }
 
-        // Release escrow funds
-        release_escrow(&env, &invoice_id)?;
+    /// Get the ids of invoices with an open dispute
+    pub fn get_open_disputes(env: Env) -> Vec<BytesN<32>> {
+        DisputeStorage::get_open_disputes(&env)
+    }
 
-        // Emit event
-        emit_escrow_released(
-            &env,
-            &escrow.escrow_id,
-            &invoice_id,
-            &escrow.business,
-            escrow.amount,
-        );
+    /// Get an escrow by ID
+    pub fn get_escrow(env: Env, escrow_id: BytesN<32>) -> Option<Escrow> {
+        EscrowStorage::get_escrow(&env, &escrow_id)
+    }
 
-        Ok(())
+    /// Get all escrows of an invoice, oldest first
+    pub fn get_invoice_escrows(env: Env, invoice_id: BytesN<32>) -> Vec<Escrow> {
+        let mut escrows = Vec::new(&env);
+        for escrow_id in EscrowStorage::get_escrows_for_invoice(&env, &invoice_id).iter() {
+            if let Some(escrow) = EscrowStorage::get_escrow(&env, &escrow_id) {
+                escrows.push_back(escrow);
+            }
+        }
+        escrows
     }
 
-    /// Refund escrow funds to investor if verification fails
-    pub fn refund_escrow_funds(env: Env, invoice_id: BytesN<32>) -> Result<(), QuickLendXError> {
-        let escrow = EscrowStorage::get_escrow_by_invoice(&env, &invoice_id)
-            .ok_or(QuickLendXError::StorageKeyNotFound)?;
+    /// Get the append-only ledger of fund movements for an escrow
+    pub fn get_escrow_ledger(env: Env, escrow_id: BytesN<32>) -> Vec<EscrowLedgerEntry> {
+        EscrowStorage::get_ledger(&env, &escrow_id)
+    }
 
-        // Refund escrow funds
-        refund_escrow(&env, &invoice_id)?;
+    /// Check that an escrow's ledger hash chain is intact
+    pub fn verify_escrow_ledger(env: Env, escrow_id: BytesN<32>) -> bool {
+        EscrowStorage::verify_ledger(&env, &escrow_id)
+    }
 
-        // Emit event
-        emit_escrow_refunded(
-            &env,
-            &escrow.escrow_id,
-            &invoice_id,
-            &escrow.investor,
-            escrow.amount,
-        );
+    /// List Funded invoices whose escrow is missing or refunded
+    pub fn get_unbacked_funded_invoices(env: Env) -> Vec<BytesN<32>> {
+        watchdog::get_unbacked_funded_invoices(&env)
+    }
 
-        Ok(())
+    /// Return an unbacked Funded invoice to Verified, withdrawing its
+    /// investments and accepted bids (admin only)
+    pub fn reconcile_funded_invoice(
+        env: Env,
+        admin: Address,
+        invoice_id: BytesN<32>,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "reconcile_funded_invoice", (&invoice_id,));
+        watchdog::reconcile_funded_invoice(&env, &admin, &invoice_id)
     }
 
-    /// Get escrow status for an invoice
+    /// Get the status of an invoice's most recent funding escrow
     pub fn get_escrow_status(
         env: Env,
         invoice_id: BytesN<32>,
@@ -542,7 +3527,7 @@ impl QuickLendXContract {
         Ok(escrow.status)
     }
 
-    /// Get escrow details for an invoice
+    /// Get an invoice's most recent funding escrow
     pub fn get_escrow_details(
         env: Env,
         invoice_id: BytesN<32>,
@@ -552,11 +3537,14 @@ impl QuickLendXContract {
     }
 
     /// Create a backup of all invoice data
-    pub fn create_backup(env: Env, description: String) -> Result<BytesN<32>, QuickLendXError> {
+    pub fn create_backup(
+        env: Env,
+        admin: Address,
+        description: String,
+    ) -> Result<BytesN<32>, QuickLendXError> {
+        record_admin_action(&env, &admin, "create_backup", (&description,));
         // Only admin can create backups
-        let admin =
-            BusinessVerificationStorage::get_admin(&env).ok_or(QuickLendXError::NotAdmin)?;
-        admin.require_auth();
+        require_admin(&env, &admin)?;
 
         // Get all invoices
         let pending = InvoiceStorage::get_invoices_by_status(&env, &InvoiceStatus::Pending);
@@ -564,10 +3552,15 @@ impl QuickLendXContract {
         let funded = InvoiceStorage::get_invoices_by_status(&env, &InvoiceStatus::Funded);
         let paid = InvoiceStorage::get_invoices_by_status(&env, &InvoiceStatus::Paid);
         let defaulted = InvoiceStorage::get_invoices_by_status(&env, &InvoiceStatus::Defaulted);
+        let cancelled = InvoiceStorage::get_invoices_by_status(&env, &InvoiceStatus::Cancelled);
+        let expired = InvoiceStorage::get_invoices_by_status(&env, &InvoiceStatus::Expired);
+        let refinanced = InvoiceStorage::get_invoices_by_status(&env, &InvoiceStatus::Refinanced);
 
         // Combine all invoices
         let mut all_invoices = Vec::new(&env);
-        for status_vec in [pending, verified, funded, paid, defaulted].iter() {
+        for status_vec in
+            [pending, verified, funded, paid, defaulted, cancelled, expired, refinanced].iter()
+        {
             for invoice_id in status_vec.iter() {
                 if let Some(invoice) = InvoiceStorage::get_invoice(&env, &invoice_id) {
                     all_invoices.push_back(invoice);
@@ -588,23 +3581,28 @@ impl QuickLendXContract {
         // Store backup and data
         BackupStorage::store_backup(&env, &backup);
         BackupStorage::store_backup_data(&env, &backup_id, &all_invoices);
+        let content_hash = BackupStorage::hash_backup_data(&env, &all_invoices);
+        BackupStorage::set_content_hash(&env, &backup_id, &content_hash);
         BackupStorage::add_to_backup_list(&env, &backup_id);
 
         // Clean up old backups (keep last 5)
         BackupStorage::cleanup_old_backups(&env, 5)?;
 
         // Emit event
-        events::emit_backup_created(&env, &backup_id, backup.invoice_count);
+        events::emit_backup_created(&env, &backup_id, backup.invoice_count, &admin);
 
         Ok(backup_id)
     }
 
     /// Restore invoice data from a backup
-    pub fn restore_backup(env: Env, backup_id: BytesN<32>) -> Result<(), QuickLendXError> {
+    pub fn restore_backup(
+        env: Env,
+        admin: Address,
+        backup_id: BytesN<32>,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "restore_backup", (&backup_id,));
         // Only admin can restore backups
-        let admin =
-            BusinessVerificationStorage::get_admin(&env).ok_or(QuickLendXError::NotAdmin)?;
-        admin.require_auth();
+        require_admin(&env, &admin)?;
 
         // Validate backup first
         BackupStorage::validate_backup(&env, &backup_id)?;
@@ -622,11 +3620,84 @@ impl QuickLendXContract {
         }
 
         // Emit event
-        events::emit_backup_restored(&env, &backup_id, invoices.len() as u32);
+        events::emit_backup_restored(&env, &backup_id, invoices.len(), &admin);
+
+        Ok(())
+    }
+
+    /// Stage one chunk of a backup for restoring (admin only). Staged invoices
+    /// sit in a separate namespace, where they can be inspected with
+    /// `get_staged_invoice`, until the restore is promoted or discarded.
+    /// Chunks can be staged in any order and staging one again is a no-op.
+    pub fn restore_backup_chunk(
+        env: Env,
+        admin: Address,
+        backup_id: BytesN<32>,
+        chunk_index: u32,
+    ) -> Result<RestoreProgress, QuickLendXError> {
+        record_admin_action(&env, &admin, "restore_backup_chunk", (&backup_id, &chunk_index));
+        require_admin(&env, &admin)?;
+        let (progress, staged) = BackupStorage::restore_chunk(&env, &backup_id, chunk_index)?;
+        if staged {
+            events::emit_backup_chunk_restored(
+                &env,
+                &backup_id,
+                chunk_index,
+                progress.total_chunks,
+                &admin,
+            );
+        }
+        Ok(progress)
+    }
+
+    /// Promote a backup whose chunks have all been staged to live, replacing
+    /// the current invoice data (admin only)
+    pub fn finalize_backup_restore(
+        env: Env,
+        admin: Address,
+        backup_id: BytesN<32>,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "finalize_backup_restore", (&backup_id,));
+        require_admin(&env, &admin)?;
+        let invoices = BackupStorage::take_staged_invoices(&env, &backup_id)?;
+
+        Self::clear_all_invoices(&env)?;
+        for invoice in invoices.iter() {
+            InvoiceStorage::store_invoice(&env, &invoice);
+        }
+
+        events::emit_backup_restored(&env, &backup_id, invoices.len(), &admin);
+        Ok(())
+    }
 
+    /// Drop a staged restore without touching live data (admin only)
+    pub fn discard_backup_restore(
+        env: Env,
+        admin: Address,
+        backup_id: BytesN<32>,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "discard_backup_restore", (&backup_id,));
+        require_admin(&env, &admin)?;
+        BackupStorage::discard_staged(&env, &backup_id)?;
+        events::emit_backup_restore_discarded(&env, &backup_id, &admin);
         Ok(())
     }
 
+    /// Get the backup currently staged for restoring, if any
+    pub fn get_staged_backup(env: Env) -> Option<BytesN<32>> {
+        BackupStorage::get_staged_backup(&env)
+    }
+
+    /// Get an invoice as the staged restore would make it
+    pub fn get_staged_invoice(env: Env, invoice_id: BytesN<32>) -> Option<Invoice> {
+        BackupStorage::get_staged_invoice(&env, &invoice_id)
+    }
+
+    /// Get the progress of a chunked restore of a backup
+    pub fn get_restore_progress(env: Env, backup_id: BytesN<32>) -> Option<RestoreProgress> {
+        BackupStorage::get_restore_progress(&env, &backup_id)
+    }
+
     /// Validate a backup's integrity
     pub fn validate_backup(env: Env, backup_id: BytesN<32>) -> Result<bool, QuickLendXError> {
         let result = BackupStorage::validate_backup(&env, &backup_id).is_ok();
@@ -635,11 +3706,14 @@ impl QuickLendXContract {
     }
 
     /// Archive a backup (mark as no longer active)
-    pub fn archive_backup(env: Env, backup_id: BytesN<32>) -> Result<(), QuickLendXError> {
+    pub fn archive_backup(
+        env: Env,
+        admin: Address,
+        backup_id: BytesN<32>,
+    ) -> Result<(), QuickLendXError> {
+        record_admin_action(&env, &admin, "archive_backup", (&backup_id,));
         // Only admin can archive backups
-        let admin =
-            BusinessVerificationStorage::get_admin(&env).ok_or(QuickLendXError::NotAdmin)?;
-        admin.require_auth();
+        require_admin(&env, &admin)?;
 
         let mut backup = BackupStorage::get_backup(&env, &backup_id)
             .ok_or(QuickLendXError::StorageKeyNotFound)?;
@@ -648,7 +3722,7 @@ impl QuickLendXContract {
         BackupStorage::update_backup(&env, &backup);
         BackupStorage::remove_from_backup_list(&env, &backup_id);
 
-        events::emit_backup_archived(&env, &backup_id);
+        events::emit_backup_archived(&env, &backup_id, &admin);
 
         Ok(())
     }
@@ -658,47 +3732,17 @@ impl QuickLendXContract {
         BackupStorage::get_all_backups(&env)
     }
 
+    /// Get the content hash recorded for a backup's data
+    pub fn get_backup_content_hash(env: Env, backup_id: BytesN<32>) -> Option<BytesN<32>> {
+        BackupStorage::get_content_hash(&env, &backup_id)
+    }
+
     /// Get backup details
     pub fn get_backup_details(env: Env, backup_id: BytesN<32>) -> Option<Backup> {
         BackupStorage::get_backup(&env, &backup_id)
     }
 
-    /// Internal function to clear all invoice data
-    fn clear_all_invoices(env: &Env) -> Result<(), QuickLendXError> {
-        // Clear all status lists
-        for status in [
-            InvoiceStatus::Pending,
-            InvoiceStatus::Verified,
-            InvoiceStatus::Funded,
-            InvoiceStatus::Paid,
-            InvoiceStatus::Defaulted,
-        ]
-        .iter()
-        {
-            let invoices = InvoiceStorage::get_invoices_by_status(env, status);
-            for invoice_id in invoices.iter() {
-                // Remove from status list
-                InvoiceStorage::remove_from_status_invoices(env, status, &invoice_id);
-                // Remove the invoice itself
-                env.storage().instance().remove(&invoice_id);
-            }
-        }
-
-        // Clear all business invoices
-        let verified_businesses = BusinessVerificationStorage::get_verified_businesses(env);
-        for business in verified_businesses.iter() {
-            let invoices = InvoiceStorage::get_business_invoices(env, &business);
-            let key = (symbol_short!("business"), business.clone());
-            env.storage().instance().remove(&key);
-        }
-
-        Ok(())
-    }
-}
-
-#[cfg(test)]
-mod test;
-
+    // Audit Functions
 
     /// Get audit trail for an invoice
     pub fn get_invoice_audit_trail(env: Env, invoice_id: BytesN<32>) -> Vec<BytesN<32>> {
@@ -706,19 +3750,53 @@ mod test;
     }
 
     /// Get audit entry by ID
-    pub fn get_audit_entry(env: Env, audit_id: BytesN<32>) -> Result<AuditLogEntry, QuickLendXError> {
-        AuditStorage::get_audit_entry(&env, &audit_id)
-            .ok_or(QuickLendXError::AuditLogNotFound)
+    pub fn get_audit_entry(
+        env: Env,
+        audit_id: BytesN<32>,
+    ) -> Result<AuditLogEntry, QuickLendXError> {
+        AuditStorage::get_audit_entry(&env, &audit_id).ok_or(QuickLendXError::AuditLogNotFound)
     }
 
-    /// Query audit logs with filters
-    pub fn query_audit_logs(
+    /// Get the privileged actions taken between two timestamps (inclusive),
+    /// oldest first
+    pub fn get_admin_actions(env: Env, from: u64, to: u64, limit: u32) -> Vec<AdminAction> {
+        admin_log::get_admin_actions(&env, from, to, limit)
+    }
+
+    /// Get a logged privileged action by id
+    pub fn get_admin_action(env: Env, id: u64) -> Option<AdminAction> {
+        AdminLogStorage::get_action(&env, id)
+    }
+
+    /// Get how many privileged actions have been logged
+    pub fn get_admin_action_count(env: Env) -> u64 {
+        AdminLogStorage::get_count(&env)
+    }
+
+    /// Get the values a configuration parameter has had, oldest first, so
+    /// the settings in force at any past time can be reconstructed
+    pub fn get_config_history(
         env: Env,
-        filter: AuditQueryFilter,
+        param: Symbol,
+        offset: u32,
         limit: u32,
-    ) -> Vec<AuditLogEntry> {
+    ) -> Vec<ConfigChange> {
+        config_history::get_config_history(&env, &param, offset, limit)
+    }
+
+    /// Get how many times a configuration parameter has been set
+    pub fn get_config_version(env: Env, param: Symbol) -> u32 {
+        ConfigHistoryStorage::get_version(&env, &param)
+    }
+
+    /// Query audit logs with filters
+    pub fn query_audit_logs(env: Env, filter: AuditQueryFilter, limit: u32) -> Vec<AuditLogEntry> {
         let results = AuditStorage::query_audit_logs(&env, &filter, limit);
-        emit_audit_query(&env, "query_audit_logs".to_string(), results.len() as u32);
+        emit_audit_query(
+            &env,
+            String::from_str(&env, "query_audit_logs"),
+            results.len(),
+        );
         results
     }
 
@@ -738,10 +3816,7 @@ mod test;
     }
 
     /// Get audit entries by operation type
-    pub fn get_audit_entries_by_operation(
-        env: Env,
-        operation: AuditOperation,
-    ) -> Vec<BytesN<32>> {
+    pub fn get_audit_entries_by_operation(env: Env, operation: AuditOperation) -> Vec<BytesN<32>> {
         AuditStorage::get_audit_entries_by_operation(&env, &operation)
     }
 
@@ -749,3 +3824,97 @@ mod test;
     pub fn get_audit_entries_by_actor(env: Env, actor: Address) -> Vec<BytesN<32>> {
         AuditStorage::get_audit_entries_by_actor(&env, &actor)
     }
+
+    /// Upload an invoice for a verified business
+    fn upload(
+        env: &Env,
+        business: Address,
+        amount: i128,
+        currency: Address,
+        due_date: u64,
+        description: String,
+        invoice_hash: Option<BytesN<32>>,
+    ) -> Result<BytesN<32>, QuickLendXError> {
+        // Only the business can upload their own invoice
+        business.require_auth();
+        // The same checks back validate_invoice_input
+        check_invoice_upload(
+            env,
+            &business,
+            amount,
+            &currency,
+            due_date,
+            &description,
+            &invoice_hash,
+        )?;
+
+        // Create and store invoice
+        let invoice = Invoice::new(
+            env,
+            business.clone(),
+            amount,
+            currency.clone(),
+            due_date,
+            description.clone(),
+            invoice_hash,
+        );
+        InvoiceStorage::store_invoice(env, &invoice);
+        log_invoice_created(env, &invoice);
+        analytics::record_invoice_created(env, &invoice);
+        emit_invoice_uploaded(env, &invoice);
+
+        // Invoices within an auto-verification rule skip manual verification
+        let invoice_id = invoice.id.clone();
+        if let Some(rule_index) = auto_verify::matching_rule(env, &invoice) {
+            let keeper = env.current_contract_address();
+            Self::list_verified_invoice(env.clone(), invoice.clone(), keeper)?;
+            emit_invoice_auto_verified(env, &invoice, rule_index);
+        }
+        Ok(invoice_id)
+    }
+
+    /// Internal function to clear all invoice data
+    fn clear_all_invoices(env: &Env) -> Result<(), QuickLendXError> {
+        // Clear all status lists
+        for status in [
+            InvoiceStatus::Pending,
+            InvoiceStatus::Verified,
+            InvoiceStatus::Funded,
+            InvoiceStatus::Paid,
+            InvoiceStatus::Defaulted,
+            InvoiceStatus::Cancelled,
+            InvoiceStatus::Expired,
+            InvoiceStatus::Refinanced,
+        ]
+        .iter()
+        {
+            let invoices = InvoiceStorage::get_invoices_by_status(env, status);
+            for invoice_id in invoices.iter() {
+                // Remove from status, category and tag lists
+                InvoiceStorage::remove_from_status_invoices(env, status, &invoice_id);
+                if let Some(invoice) = InvoiceStorage::get_invoice(env, &invoice_id) {
+                    InvoiceStorage::unindex_metadata(env, &invoice);
+                }
+                // Remove the invoice itself
+                storage::remove(env, &DataKey::Invoice(invoice_id.clone()));
+            }
+        }
+
+        // Clear all business invoices
+        let verified_businesses = BusinessVerificationStorage::get_verified_businesses(env);
+        for business in verified_businesses.iter() {
+            storage::remove(env, &DataKey::BusinessInvoices(business.clone()));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "proptest"))]
+mod proptest_harness;
+#[cfg(any(test, feature = "scenarios"))]
+pub mod scenarios;
+#[cfg(test)]
+mod storage_budget;
+#[cfg(test)]
+mod test;
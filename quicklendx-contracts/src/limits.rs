@@ -0,0 +1,166 @@
+use crate::config::check_rejection_reason_length;
+use crate::errors::QuickLendXError;
+use crate::events::{emit_limit_request_decided, emit_limit_requested};
+use crate::storage::{self, DataKey};
+use crate::verification::{require_admin, BusinessVerificationStorage};
+use soroban_sdk::{contracttype, symbol_short, Address, BytesN, Env, String, Vec};
+
+/// A cap a business can ask to have raised for particular invoices
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LimitKind {
+    InvoiceAmount,  // Its KYC tier's largest invoice
+    ActiveInvoices, // Its KYC tier's number of open invoices
+    CreditExposure, // Its credit limit
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LimitRequestStatus {
+    Pending,
+    Approved,
+    Denied,
+}
+
+/// A business's request to go past one of its caps. Invoices are named by
+/// the hash of their document, as given to upload_invoice_with_hash, so a
+/// request can be made before the invoice is uploaded. Once approved,
+/// `requested_limit` replaces the cap for those invoices only.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LimitRequest {
+    pub id: u64,
+    pub business: Address,
+    pub kind: LimitKind,
+    pub requested_limit: i128, // A count for ActiveInvoices
+    pub invoice_hashes: Vec<BytesN<32>>,
+    pub justification_hash: BytesN<32>, // Hash of the off-chain justification
+    pub status: LimitRequestStatus,
+    pub requested_at: u64,
+    pub decided_by: Option<Address>,
+    pub decided_at: Option<u64>,
+    pub decision_reason: Option<String>,
+}
+
+pub struct LimitStorage;
+
+impl LimitStorage {
+    /// Get a limit-increase request by id
+    pub fn get_request(env: &Env, id: u64) -> Option<LimitRequest> {
+        storage::get(env, &DataKey::LimitRequest(id))
+    }
+
+    fn set_request(env: &Env, request: &LimitRequest) {
+        storage::set(env, &DataKey::LimitRequest(request.id), request);
+    }
+
+    /// Get the ids of a business's limit-increase requests, oldest first
+    pub fn get_business_requests(env: &Env, business: &Address) -> Vec<u64> {
+        storage::get(env, &DataKey::BusinessLimitRequests(business.clone()))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+}
+
+/// Ask to go past one of the business's caps for the invoices with the given
+/// document hashes (business only). Returns the request id.
+pub fn request_limit_increase(
+    env: &Env,
+    business: &Address,
+    kind: LimitKind,
+    requested_limit: i128,
+    invoice_hashes: Vec<BytesN<32>>,
+    justification_hash: BytesN<32>,
+) -> Result<u64, QuickLendXError> {
+    business.require_auth();
+    if BusinessVerificationStorage::get_verification(env, business).is_none() {
+        return Err(QuickLendXError::KYCNotFound);
+    }
+    if requested_limit <= 0 {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    if invoice_hashes.is_empty() {
+        return Err(QuickLendXError::InvalidDescription);
+    }
+
+    let id = env
+        .storage()
+        .instance()
+        .get(&symbol_short!("lim_cnt"))
+        .unwrap_or(0u64)
+        + 1;
+    env.storage().instance().set(&symbol_short!("lim_cnt"), &id);
+    let request = LimitRequest {
+        id,
+        business: business.clone(),
+        kind,
+        requested_limit,
+        invoice_hashes,
+        justification_hash,
+        status: LimitRequestStatus::Pending,
+        requested_at: env.ledger().timestamp(),
+        decided_by: None,
+        decided_at: None,
+        decision_reason: None,
+    };
+    LimitStorage::set_request(env, &request);
+    let mut requests = LimitStorage::get_business_requests(env, business);
+    requests.push_back(id);
+    storage::set(
+        env,
+        &DataKey::BusinessLimitRequests(business.clone()),
+        &requests,
+    );
+    emit_limit_requested(env, &request);
+    Ok(id)
+}
+
+/// Approve or deny a pending limit-increase request, giving the reason
+/// (admin only). An approval replaces any earlier override of the same cap
+/// for those invoices.
+pub fn decide_limit_request(
+    env: &Env,
+    admin: &Address,
+    id: u64,
+    approve: bool,
+    reason: String,
+) -> Result<LimitRequest, QuickLendXError> {
+    require_admin(env, admin)?;
+    check_rejection_reason_length(env, &reason)?;
+    let mut request =
+        LimitStorage::get_request(env, id).ok_or(QuickLendXError::LimitRequestNotFound)?;
+    if request.status != LimitRequestStatus::Pending {
+        return Err(QuickLendXError::LimitRequestDecided);
+    }
+
+    request.status = if approve {
+        LimitRequestStatus::Approved
+    } else {
+        LimitRequestStatus::Denied
+    };
+    request.decided_by = Some(admin.clone());
+    request.decided_at = Some(env.ledger().timestamp());
+    request.decision_reason = Some(reason);
+    LimitStorage::set_request(env, &request);
+    if approve {
+        for invoice_hash in request.invoice_hashes.iter() {
+            let key = DataKey::LimitOverride(request.business.clone(), invoice_hash, request.kind);
+            storage::set(env, &key, &request.id);
+        }
+    }
+    emit_limit_request_decided(env, &request);
+    Ok(request)
+}
+
+/// Get the cap an approved request sets for an invoice in place of the
+/// business's own, if there is one
+pub fn override_limit(
+    env: &Env,
+    business: &Address,
+    invoice_hash: &Option<BytesN<32>>,
+    kind: LimitKind,
+) -> Option<i128> {
+    let invoice_hash = invoice_hash.clone()?;
+    let key = DataKey::LimitOverride(business.clone(), invoice_hash, kind);
+    let id: u64 = storage::get(env, &key)?;
+    LimitStorage::get_request(env, id).map(|request| request.requested_limit)
+}
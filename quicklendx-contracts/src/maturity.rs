@@ -0,0 +1,162 @@
+use crate::config_history::record_config_change;
+use crate::errors::QuickLendXError;
+use crate::events::emit_maturity_buckets_set;
+use crate::invoice::Invoice;
+use crate::verification::require_admin;
+use soroban_sdk::{contracttype, symbol_short, vec, Address, Env, Map, Vec};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Most bucket boundaries the admin may configure
+pub const MAX_MATURITY_BUCKETS: u32 = 12;
+
+/// Funded amount maturing within a range of days from now
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MaturityBucket {
+    pub min_days: u64,
+    pub max_days: Option<u64>, // None for the open-ended last bucket
+    pub amount: i128,
+}
+
+/// Outstanding funded amounts grouped by time to maturity
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MaturityLadder {
+    pub overdue: i128, // Funded amounts already past their due date
+    pub buckets: Vec<MaturityBucket>,
+    pub total: i128,
+    pub as_of: u64,
+}
+
+pub struct MaturityStorage;
+
+impl MaturityStorage {
+    /// Get the upper bounds, in days, of every bucket but the last
+    pub fn get_bucket_bounds(env: &Env) -> Vec<u64> {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("mat_bkts"))
+            .unwrap_or_else(|| vec![env, 7, 30, 60, 90, 180])
+    }
+
+    /// Outstanding funded amount per due day, kept up to date as invoices are
+    /// funded and closed so the ladder never scans invoices
+    fn get_by_due_day(env: &Env) -> Map<u64, i128> {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("mat_due"))
+            .unwrap_or_else(|| Map::new(env))
+    }
+
+    fn set_by_due_day(env: &Env, amounts: &Map<u64, i128>) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("mat_due"), amounts);
+    }
+}
+
+/// Set the bucket boundaries in days; they must be strictly increasing
+/// (admin only)
+pub fn set_maturity_buckets(
+    env: &Env,
+    admin: &Address,
+    bounds: &Vec<u64>,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    if bounds.is_empty() || bounds.len() > MAX_MATURITY_BUCKETS {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    for i in 1..bounds.len() {
+        if bounds.get(i).unwrap() <= bounds.get(i - 1).unwrap() {
+            return Err(QuickLendXError::InvalidAmount);
+        }
+    }
+    env.storage()
+        .instance()
+        .set(&symbol_short!("mat_bkts"), bounds);
+    record_config_change(env, "maturity_buckets", bounds, admin);
+    emit_maturity_buckets_set(env, bounds, admin);
+    Ok(())
+}
+
+/// Adjust the outstanding amount maturing on a due date
+fn adjust(env: &Env, due_date: u64, delta: i128) {
+    if delta == 0 {
+        return;
+    }
+    let day = due_date / SECONDS_PER_DAY;
+    let mut amounts = MaturityStorage::get_by_due_day(env);
+    let amount = amounts.get(day).unwrap_or(0) + delta;
+    if amount > 0 {
+        amounts.set(day, amount);
+    } else {
+        amounts.remove(day);
+    }
+    MaturityStorage::set_by_due_day(env, &amounts);
+}
+
+/// Count newly funded money towards the invoice's maturity
+pub fn record_funding(env: &Env, invoice: &Invoice, amount: i128) {
+    adjust(env, invoice.due_date, amount);
+}
+
+/// Move an invoice's funding to its new due date after the date changed
+pub fn reschedule_funding(env: &Env, invoice: &Invoice, old_due_date: u64) {
+    adjust(env, old_due_date, -invoice.funded_amount);
+    adjust(env, invoice.due_date, invoice.funded_amount);
+}
+
+/// Drop all of an invoice's funding from the ladder once it is repaid,
+/// defaulted or unwound. Call before the invoice's funded amount is reset.
+pub fn release_funding(env: &Env, invoice: &Invoice) {
+    adjust(env, invoice.due_date, -invoice.funded_amount);
+}
+
+/// Build the maturity ladder as of the current ledger time
+pub fn get_maturity_ladder(env: &Env) -> MaturityLadder {
+    let now = env.ledger().timestamp();
+    let today = now / SECONDS_PER_DAY;
+    let bounds = MaturityStorage::get_bucket_bounds(env);
+
+    let mut buckets = Vec::new(env);
+    let mut min_days = 0u64;
+    for bound in bounds.iter() {
+        buckets.push_back(MaturityBucket {
+            min_days,
+            max_days: Some(bound),
+            amount: 0,
+        });
+        min_days = bound + 1;
+    }
+    buckets.push_back(MaturityBucket {
+        min_days,
+        max_days: None,
+        amount: 0,
+    });
+
+    let mut overdue = 0i128;
+    let mut total = 0i128;
+    for (day, amount) in MaturityStorage::get_by_due_day(env).iter() {
+        total += amount;
+        if day < today {
+            overdue += amount;
+            continue;
+        }
+        let days = day - today;
+        let index = bounds
+            .iter()
+            .position(|bound| days <= bound)
+            .unwrap_or(bounds.len() as usize) as u32;
+        let mut bucket = buckets.get(index).unwrap();
+        bucket.amount += amount;
+        buckets.set(index, bucket);
+    }
+
+    MaturityLadder {
+        overdue,
+        buckets,
+        total,
+        as_of: now,
+    }
+}
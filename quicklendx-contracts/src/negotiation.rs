@@ -0,0 +1,153 @@
+use crate::auction::AuctionStorage;
+use crate::bid::{Bid, BidStatus, BidStorage};
+use crate::errors::QuickLendXError;
+use crate::events::{emit_bid_countered, emit_counter_accepted, emit_counter_declined};
+use crate::invoice::{Invoice, InvoiceStorage};
+use crate::jurisdiction;
+use crate::storage::{self, DataKey};
+use soroban_sdk::{contracttype, Address, BytesN, Env};
+
+/// Most counter-offers a business may make on a single bid
+pub const MAX_COUNTER_ROUNDS: u32 = 3;
+
+/// State of the negotiation over a bid's expected return
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BidNegotiation {
+    pub bid_id: BytesN<32>,
+    pub original_return: i128,
+    pub counter_return: Option<i128>, // Outstanding counter-offer, if any
+    pub rounds: u32,
+    pub updated_at: u64,
+}
+
+pub struct NegotiationStorage;
+
+impl NegotiationStorage {
+    /// Get the negotiation over a bid, if the business ever countered it
+    pub fn get_negotiation(env: &Env, bid_id: &BytesN<32>) -> Option<BidNegotiation> {
+        storage::get(env, &DataKey::BidNegotiation(bid_id.clone()))
+    }
+
+    fn set_negotiation(env: &Env, negotiation: &BidNegotiation) {
+        storage::set(
+            env,
+            &DataKey::BidNegotiation(negotiation.bid_id.clone()),
+            negotiation,
+        );
+    }
+}
+
+/// Propose a different expected return on a placed bid (business owner
+/// only). The bid waits for the investor to accept or decline, and can be
+/// countered at most `MAX_COUNTER_ROUNDS` times.
+pub fn counter_bid(
+    env: &Env,
+    business: &Address,
+    bid_id: &BytesN<32>,
+    new_return: i128,
+) -> Result<BidNegotiation, QuickLendXError> {
+    business.require_auth();
+    let mut bid = BidStorage::get_bid(env, bid_id).ok_or(QuickLendXError::StorageKeyNotFound)?;
+    let invoice = InvoiceStorage::get_invoice(env, &bid.invoice_id)
+        .ok_or(QuickLendXError::InvoiceNotFound)?;
+    if invoice.business != *business {
+        return Err(QuickLendXError::NotBusinessOwner);
+    }
+    if bid.status != BidStatus::Placed || !invoice.is_available_for_funding() {
+        return Err(QuickLendXError::InvalidStatus);
+    }
+    if AuctionStorage::is_open(env, &bid.invoice_id) {
+        return Err(QuickLendXError::AuctionInProgress);
+    }
+    if new_return <= 0 || new_return == bid.expected_return {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    jurisdiction::check_bid(
+        env,
+        &invoice.business,
+        &bid.investor,
+        bid.bid_amount,
+        new_return,
+        invoice.due_date,
+    )?;
+
+    let mut negotiation =
+        NegotiationStorage::get_negotiation(env, bid_id).unwrap_or(BidNegotiation {
+            bid_id: bid_id.clone(),
+            original_return: bid.expected_return,
+            counter_return: None,
+            rounds: 0,
+            updated_at: 0,
+        });
+    if negotiation.rounds >= MAX_COUNTER_ROUNDS {
+        return Err(QuickLendXError::OperationNotAllowed);
+    }
+    negotiation.rounds += 1;
+    negotiation.counter_return = Some(new_return);
+    negotiation.updated_at = env.ledger().timestamp();
+    NegotiationStorage::set_negotiation(env, &negotiation);
+    bid.status = BidStatus::Countered;
+    BidStorage::update_bid(env, &bid);
+
+    emit_bid_countered(env, &bid, new_return, negotiation.rounds);
+    Ok(negotiation)
+}
+
+/// Take up the outstanding counter-offer and return the bid, placed again at
+/// the new return, for the caller to accept on the business's behalf
+/// (investor only)
+pub fn accept_counter(
+    env: &Env,
+    investor: &Address,
+    bid_id: &BytesN<32>,
+) -> Result<(Invoice, Bid), QuickLendXError> {
+    let (mut bid, mut negotiation) = get_countered_bid(env, investor, bid_id)?;
+    let invoice = InvoiceStorage::get_invoice(env, &bid.invoice_id)
+        .ok_or(QuickLendXError::InvoiceNotFound)?;
+    bid.expected_return = negotiation.counter_return.unwrap_or(bid.expected_return);
+    bid.status = BidStatus::Placed;
+    BidStorage::update_bid(env, &bid);
+    negotiation.counter_return = None;
+    negotiation.updated_at = env.ledger().timestamp();
+    NegotiationStorage::set_negotiation(env, &negotiation);
+
+    emit_counter_accepted(env, &bid, negotiation.rounds);
+    Ok((invoice, bid))
+}
+
+/// Turn down the outstanding counter-offer, leaving the bid placed at its
+/// current return (investor only)
+pub fn decline_counter(
+    env: &Env,
+    investor: &Address,
+    bid_id: &BytesN<32>,
+) -> Result<(), QuickLendXError> {
+    let (mut bid, mut negotiation) = get_countered_bid(env, investor, bid_id)?;
+    bid.status = BidStatus::Placed;
+    BidStorage::update_bid(env, &bid);
+    negotiation.counter_return = None;
+    negotiation.updated_at = env.ledger().timestamp();
+    NegotiationStorage::set_negotiation(env, &negotiation);
+
+    emit_counter_declined(env, &bid, negotiation.rounds);
+    Ok(())
+}
+
+fn get_countered_bid(
+    env: &Env,
+    investor: &Address,
+    bid_id: &BytesN<32>,
+) -> Result<(Bid, BidNegotiation), QuickLendXError> {
+    investor.require_auth();
+    let bid = BidStorage::get_bid(env, bid_id).ok_or(QuickLendXError::StorageKeyNotFound)?;
+    if bid.investor != *investor {
+        return Err(QuickLendXError::NotInvestor);
+    }
+    if bid.status != BidStatus::Countered {
+        return Err(QuickLendXError::InvalidStatus);
+    }
+    let negotiation = NegotiationStorage::get_negotiation(env, bid_id)
+        .ok_or(QuickLendXError::StorageKeyNotFound)?;
+    Ok((bid, negotiation))
+}
@@ -0,0 +1,115 @@
+use crate::errors::QuickLendXError;
+use crate::storage::{self, DataKey};
+use soroban_sdk::{contracttype, Address, BytesN, Env, Vec};
+
+/// Most notifications returned by one query
+pub const MAX_NOTIFICATION_PAGE: u32 = 50;
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NotificationKind {
+    BidReceived,      // To the business, when a bid is placed on its invoice
+    BidAccepted,      // To the investor, when its bid funds an invoice
+    InvoiceVerified,  // To the business
+    PaymentDueSoon,   // To the business, with each due-date reminder
+    InvoiceDefaulted, // To the business and each investor
+}
+
+/// A record in an address's inbox, so dApps can show what happened to it
+/// without replaying events
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Notification {
+    pub id: u64, // Sequential per address, from 1
+    pub kind: NotificationKind,
+    pub invoice_id: BytesN<32>,
+    pub created_at: u64,
+    pub read: bool,
+}
+
+/// How many notifications an address has, and how many it has not read
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct InboxSummary {
+    pub total: u64,
+    pub unread: u64,
+}
+
+pub struct NotificationStorage;
+
+impl NotificationStorage {
+    /// Get how many notifications an address has, and how many are unread
+    pub fn get_summary(env: &Env, address: &Address) -> InboxSummary {
+        storage::get(env, &DataKey::Inbox(address.clone())).unwrap_or_default()
+    }
+
+    fn set_summary(env: &Env, address: &Address, summary: &InboxSummary) {
+        storage::set(env, &DataKey::Inbox(address.clone()), summary);
+    }
+
+    /// Get one of an address's notifications
+    pub fn get_notification(env: &Env, address: &Address, id: u64) -> Option<Notification> {
+        storage::get(env, &DataKey::Notification(address.clone(), id))
+    }
+
+    fn set_notification(env: &Env, address: &Address, notification: &Notification) {
+        storage::set(
+            env,
+            &DataKey::Notification(address.clone(), notification.id),
+            notification,
+        );
+    }
+}
+
+/// Add a notification to an address's inbox
+pub fn notify(env: &Env, address: &Address, kind: NotificationKind, invoice_id: &BytesN<32>) {
+    let mut summary = NotificationStorage::get_summary(env, address);
+    summary.total += 1;
+    summary.unread += 1;
+    let notification = Notification {
+        id: summary.total,
+        kind,
+        invoice_id: invoice_id.clone(),
+        created_at: env.ledger().timestamp(),
+        read: false,
+    };
+    NotificationStorage::set_notification(env, address, &notification);
+    NotificationStorage::set_summary(env, address, &summary);
+}
+
+/// Get a page of an address's notifications, newest first. `offset` skips
+/// that many of the newest.
+pub fn get_notifications(
+    env: &Env,
+    address: &Address,
+    offset: u64,
+    limit: u32,
+) -> Vec<Notification> {
+    let mut page = Vec::new(env);
+    let total = NotificationStorage::get_summary(env, address).total;
+    let limit = limit.min(MAX_NOTIFICATION_PAGE) as u64;
+    let newest = total.saturating_sub(offset);
+    let oldest = newest.saturating_sub(limit);
+    for id in (oldest + 1..=newest).rev() {
+        if let Some(notification) = NotificationStorage::get_notification(env, address, id) {
+            page.push_back(notification);
+        }
+    }
+    page
+}
+
+/// Mark one of the address's notifications as read (address only)
+pub fn mark_read(env: &Env, address: &Address, id: u64) -> Result<(), QuickLendXError> {
+    address.require_auth();
+    let mut notification = NotificationStorage::get_notification(env, address, id)
+        .ok_or(QuickLendXError::StorageKeyNotFound)?;
+    if notification.read {
+        return Ok(());
+    }
+    notification.read = true;
+    NotificationStorage::set_notification(env, address, &notification);
+    let mut summary = NotificationStorage::get_summary(env, address);
+    summary.unread -= 1;
+    NotificationStorage::set_summary(env, address, &summary);
+    Ok(())
+}
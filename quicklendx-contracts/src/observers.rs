@@ -0,0 +1,90 @@
+use crate::errors::QuickLendXError;
+use crate::events::{emit_observer_failed, emit_observer_registered, emit_observer_removed};
+use crate::verification::require_admin;
+use soroban_sdk::{
+    contracttype, symbol_short, Address, BytesN, Env, IntoVal, InvokeError, Symbol, Val, Vec,
+};
+
+/// Most observer contracts that may be registered at once, bounding the cost
+/// added to every lifecycle transition
+pub const MAX_OBSERVERS: u32 = 10;
+
+/// Invoice lifecycle transitions reported to observer contracts
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LifecycleEvent {
+    Verified,
+    Funded,
+    Settled,
+    Defaulted,
+}
+
+pub struct ObserverStorage;
+
+impl ObserverStorage {
+    /// Get the registered observer contracts, in registration order
+    pub fn get_observers(env: &Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("observers"))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    fn set_observers(env: &Env, observers: &Vec<Address>) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("observers"), observers);
+    }
+}
+
+/// Register a contract to be notified of invoice lifecycle events (admin only)
+pub fn register_observer(
+    env: &Env,
+    admin: &Address,
+    observer: &Address,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    let mut observers = ObserverStorage::get_observers(env);
+    if observers.contains(observer) || observers.len() >= MAX_OBSERVERS {
+        return Err(QuickLendXError::OperationNotAllowed);
+    }
+    observers.push_back(observer.clone());
+    ObserverStorage::set_observers(env, &observers);
+    emit_observer_registered(env, observer, admin);
+    Ok(())
+}
+
+/// Stop notifying an observer contract (admin only)
+pub fn remove_observer(
+    env: &Env,
+    admin: &Address,
+    observer: &Address,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    let mut observers = ObserverStorage::get_observers(env);
+    let index = observers
+        .first_index_of(observer)
+        .ok_or(QuickLendXError::StorageKeyNotFound)?;
+    observers.remove(index);
+    ObserverStorage::set_observers(env, &observers);
+    emit_observer_removed(env, observer, admin);
+    Ok(())
+}
+
+/// Call `on_invoice_event(invoice_id, event)` on every observer. Delivery is
+/// best-effort: an observer that fails or traps is reported in an event and
+/// never blocks the lifecycle transition or the other observers.
+pub fn notify_observers(env: &Env, invoice_id: &BytesN<32>, event: LifecycleEvent) {
+    let observers = ObserverStorage::get_observers(env);
+    if observers.is_empty() {
+        return;
+    }
+    let func = Symbol::new(env, "on_invoice_event");
+    let args: Vec<Val> = (invoice_id.clone(), event).into_val(env);
+    for observer in observers.iter() {
+        let result = env.try_invoke_contract::<Val, InvokeError>(&observer, &func, args.clone());
+        if !matches!(result, Ok(Ok(_))) {
+            emit_observer_failed(env, &observer, invoice_id, event);
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use crate::config_history::record_config_change;
+use crate::errors::QuickLendXError;
+use crate::events::emit_price_oracle_set;
+use crate::verification::require_admin;
+use soroban_sdk::{contractclient, contracttype, symbol_short, Address, Env, Symbol};
+
+/// Longest an oracle price may go without an update and still be used
+pub const MAX_PRICE_AGE_LIMIT: u64 = 7 * 86_400;
+
+/// An asset as SEP-40 price oracles identify it
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Asset {
+    Stellar(Address),
+    Other(Symbol),
+}
+
+/// A price as SEP-40 price oracles report it, in the oracle's base asset
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PriceData {
+    pub price: i128,
+    pub timestamp: u64,
+}
+
+/// The part of the SEP-40 price oracle interface the contract relies on
+#[allow(dead_code)]
+#[contractclient(name = "PriceOracleClient")]
+pub trait PriceOracle {
+    fn lastprice(env: Env, asset: Asset) -> Option<PriceData>;
+}
+
+/// The oracle amounts in different currencies are valued through, and the
+/// currency they are valued in
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OracleConfig {
+    pub oracle: Address,
+    pub reference_currency: Address,
+    pub max_price_age: u64, // Seconds after which an oracle price is stale
+}
+
+pub struct OracleStorage;
+
+impl OracleStorage {
+    /// Get the price oracle configuration, if one is set
+    pub fn get_config(env: &Env) -> Option<OracleConfig> {
+        env.storage().instance().get(&symbol_short!("oracle"))
+    }
+}
+
+/// Set the price oracle amounts are valued through and the reference
+/// currency they are valued in (admin only). Credit and tier limits are then
+/// denominated in the reference currency.
+pub fn set_price_oracle(
+    env: &Env,
+    admin: &Address,
+    config: OracleConfig,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    if config.max_price_age == 0 || config.max_price_age > MAX_PRICE_AGE_LIMIT {
+        return Err(QuickLendXError::InvalidTimestamp);
+    }
+    env.storage()
+        .instance()
+        .set(&symbol_short!("oracle"), &config);
+    record_config_change(env, "price_oracle", config.clone(), admin);
+    emit_price_oracle_set(env, &config, admin);
+    Ok(())
+}
+
+/// Get the oracle's price of a currency, refusing prices older than the
+/// configured maximum age
+pub fn get_price(
+    env: &Env,
+    config: &OracleConfig,
+    currency: &Address,
+) -> Result<PriceData, QuickLendXError> {
+    let client = PriceOracleClient::new(env, &config.oracle);
+    let price = match client.try_lastprice(&Asset::Stellar(currency.clone())) {
+        Ok(Ok(Some(price))) if price.price > 0 => price,
+        _ => return Err(QuickLendXError::OraclePriceUnavailable),
+    };
+    if price.timestamp.saturating_add(config.max_price_age) < env.ledger().timestamp() {
+        return Err(QuickLendXError::OraclePriceStale);
+    }
+    Ok(price)
+}
+
+/// Value `amount` of `currency` in the reference currency. Amounts are taken
+/// as they are while no oracle is set, so single-currency deployments work
+/// unchanged. Both currencies are assumed to use the same number of decimals.
+pub fn to_reference(env: &Env, currency: &Address, amount: i128) -> Result<i128, QuickLendXError> {
+    let Some(config) = OracleStorage::get_config(env) else {
+        return Ok(amount);
+    };
+    if amount == 0 || *currency == config.reference_currency {
+        return Ok(amount);
+    }
+    let price = get_price(env, &config, currency)?;
+    let reference_price = get_price(env, &config, &config.reference_currency)?;
+    Ok(amount * price.price / reference_price.price)
+}
@@ -1,5 +1,10 @@
-use soroban_sdk::{contracttype, Address, BytesN, Env, symbol_short};
+use soroban_sdk::{contracttype, xdr::ToXdr, Address, BytesN, Env, Vec, symbol_short};
+use crate::analytics::record_escrow_change;
+use crate::balances::credit_balance;
+use crate::disputes::DisputeStorage;
 use crate::errors::QuickLendXError;
+use crate::invoice::Invoice;
+use crate::storage::{self, DataKey};
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -7,6 +12,16 @@ pub enum EscrowStatus {
     Held,      // Funds are held in escrow
     Released,  // Funds released to business
     Refunded,  // Funds refunded to investor
+    Split,     // Funds divided between business and investor by a dispute ruling
+}
+
+/// What an escrow secures for its invoice
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EscrowKind {
+    Funding,     // Investor funds for an accepted bid
+    Settlement,  // Business payment awaiting distribution
+    Collateral,  // Security posted against the invoice
 }
 
 #[contracttype]
@@ -14,6 +29,7 @@ pub enum EscrowStatus {
 pub struct Escrow {
     pub escrow_id: BytesN<32>,
     pub invoice_id: BytesN<32>,
+    pub kind: EscrowKind,
     pub investor: Address,
     pub business: Address,
     pub amount: i128,
@@ -22,30 +38,174 @@ pub struct Escrow {
     pub status: EscrowStatus,
 }
 
+/// Direction of funds moved by an escrow state change
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EscrowDirection {
+    Deposit,  // Investor funds placed in escrow
+    Release,  // Escrow funds paid out to the business
+    Refund,   // Escrow funds returned to the investor
+}
+
+/// Append-only record of a single escrow state change.
+/// Each entry's hash covers its contents and the previous entry's hash, so
+/// rewriting any past entry breaks the chain.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowLedgerEntry {
+    pub escrow_id: BytesN<32>,
+    pub sequence: u32,
+    pub direction: EscrowDirection,
+    pub amount: i128,
+    pub from: Address,
+    pub to: Address,
+    pub actor: Address,
+    pub timestamp: u64,
+    pub prev_hash: BytesN<32>,
+    pub entry_hash: BytesN<32>,
+}
+
 pub struct EscrowStorage;
 
 impl EscrowStorage {
     pub fn store_escrow(env: &Env, escrow: &Escrow) {
-        env.storage().instance().set(&escrow.escrow_id, escrow);
-        // Also store by invoice_id for easy lookup
-        env.storage().instance().set(&(symbol_short!("escrow"), &escrow.invoice_id), &escrow.escrow_id);
+        storage::set(env, &DataKey::Escrow(escrow.escrow_id.clone()), escrow);
+        // Also index by invoice_id, in creation order
+        let mut escrow_ids = Self::get_escrows_for_invoice(env, &escrow.invoice_id);
+        escrow_ids.push_back(escrow.escrow_id.clone());
+        Self::set_escrows_for_invoice(env, &escrow.invoice_id, &escrow_ids);
     }
 
     pub fn get_escrow(env: &Env, escrow_id: &BytesN<32>) -> Option<Escrow> {
-        env.storage().instance().get(escrow_id)
+        storage::get(env, &DataKey::Escrow(escrow_id.clone()))
     }
 
-    pub fn get_escrow_by_invoice(env: &Env, invoice_id: &BytesN<32>) -> Option<Escrow> {
-        let escrow_id: Option<BytesN<32>> = env.storage().instance().get(&(symbol_short!("escrow"), invoice_id));
-        if let Some(id) = escrow_id {
-            Self::get_escrow(env, &id)
-        } else {
-            None
+    /// Get the ids of all escrows for an invoice, oldest first
+    pub fn get_escrows_for_invoice(env: &Env, invoice_id: &BytesN<32>) -> Vec<BytesN<32>> {
+        storage::get(env, &DataKey::EscrowList(invoice_id.clone()))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    pub fn set_escrows_for_invoice(
+        env: &Env,
+        invoice_id: &BytesN<32>,
+        escrow_ids: &Vec<BytesN<32>>,
+    ) {
+        storage::set(env, &DataKey::EscrowList(invoice_id.clone()), escrow_ids);
+    }
+
+    /// Get all escrows of one kind for an invoice, oldest first
+    pub fn get_escrows_by_kind(
+        env: &Env,
+        invoice_id: &BytesN<32>,
+        kind: &EscrowKind,
+    ) -> Vec<Escrow> {
+        let mut escrows = Vec::new(env);
+        for escrow_id in Self::get_escrows_for_invoice(env, invoice_id).iter() {
+            if let Some(escrow) = Self::get_escrow(env, &escrow_id) {
+                if escrow.kind == *kind {
+                    escrows.push_back(escrow);
+                }
+            }
+        }
+        escrows
+    }
+
+    /// Get the funding escrows still holding funds for an invoice.
+    /// Fails if the invoice has no funding escrow, or none is still held.
+    pub fn get_held_funding_escrows(
+        env: &Env,
+        invoice_id: &BytesN<32>,
+    ) -> Result<Vec<Escrow>, QuickLendXError> {
+        let funding = Self::get_escrows_by_kind(env, invoice_id, &EscrowKind::Funding);
+        if funding.is_empty() {
+            return Err(QuickLendXError::StorageKeyNotFound);
+        }
+        let mut held = Vec::new(env);
+        for escrow in funding.iter() {
+            if escrow.status == EscrowStatus::Held {
+                held.push_back(escrow);
+            }
+        }
+        if held.is_empty() {
+            return Err(QuickLendXError::InvalidStatus);
         }
+        Ok(held)
+    }
+
+    /// Get the most recent funding escrow for an invoice
+    pub fn get_escrow_by_invoice(env: &Env, invoice_id: &BytesN<32>) -> Option<Escrow> {
+        Self::get_escrows_by_kind(env, invoice_id, &EscrowKind::Funding).last()
     }
 
     pub fn update_escrow(env: &Env, escrow: &Escrow) {
-        env.storage().instance().set(&escrow.escrow_id, escrow);
+        storage::set(env, &DataKey::Escrow(escrow.escrow_id.clone()), escrow);
+    }
+
+    /// Move an invoice's escrows to the invoice it was rolled into
+    pub fn move_escrows(env: &Env, from_invoice: &BytesN<32>, to_invoice: &BytesN<32>) {
+        let escrow_ids = Self::get_escrows_for_invoice(env, from_invoice);
+        for escrow_id in escrow_ids.iter() {
+            if let Some(mut escrow) = Self::get_escrow(env, &escrow_id) {
+                escrow.invoice_id = to_invoice.clone();
+                Self::update_escrow(env, &escrow);
+            }
+        }
+        Self::set_escrows_for_invoice(env, to_invoice, &escrow_ids);
+        storage::remove(env, &DataKey::EscrowList(from_invoice.clone()));
+    }
+
+    /// Get the ledger entries of an escrow, oldest first
+    pub fn get_ledger(env: &Env, escrow_id: &BytesN<32>) -> Vec<EscrowLedgerEntry> {
+        storage::get(env, &DataKey::EscrowLedger(escrow_id.clone()))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    /// Append an entry to an escrow's ledger, chaining it to the previous entry
+    pub fn append_ledger_entry(
+        env: &Env,
+        escrow: &Escrow,
+        direction: EscrowDirection,
+        amount: i128,
+        from: &Address,
+        to: &Address,
+        actor: &Address,
+    ) {
+        let mut ledger = Self::get_ledger(env, &escrow.escrow_id);
+        let prev_hash = match ledger.last() {
+            Some(entry) => entry.entry_hash,
+            None => BytesN::from_array(env, &[0u8; 32]),
+        };
+        let mut entry = EscrowLedgerEntry {
+            escrow_id: escrow.escrow_id.clone(),
+            sequence: ledger.len(),
+            direction,
+            amount,
+            from: from.clone(),
+            to: to.clone(),
+            actor: actor.clone(),
+            timestamp: env.ledger().timestamp(),
+            prev_hash,
+            entry_hash: BytesN::from_array(env, &[0u8; 32]),
+        };
+        entry.entry_hash = hash_ledger_entry(env, &entry);
+        ledger.push_back(entry);
+        storage::set(env, &DataKey::EscrowLedger(escrow.escrow_id.clone()), &ledger);
+    }
+
+    /// Check that every ledger entry hashes correctly and links to its predecessor
+    pub fn verify_ledger(env: &Env, escrow_id: &BytesN<32>) -> bool {
+        let mut prev_hash = BytesN::from_array(env, &[0u8; 32]);
+        for (index, entry) in Self::get_ledger(env, escrow_id).iter().enumerate() {
+            if entry.sequence != index as u32
+                || entry.prev_hash != prev_hash
+                || entry.entry_hash != hash_ledger_entry(env, &entry)
+            {
+                return false;
+            }
+            prev_hash = entry.entry_hash;
+        }
+        true
     }
 
     pub fn generate_unique_escrow_id(env: &Env) -> BytesN<32> {
@@ -71,45 +231,87 @@ impl EscrowStorage {
     }
 }
 
-/// Create escrow when bid is accepted
+/// Hash of a ledger entry's contents and its link to the previous entry
+fn hash_ledger_entry(env: &Env, entry: &EscrowLedgerEntry) -> BytesN<32> {
+    let contents = (
+        entry.escrow_id.clone(),
+        entry.sequence,
+        entry.direction.clone(),
+        entry.amount,
+        entry.from.clone(),
+        entry.to.clone(),
+        entry.actor.clone(),
+        entry.timestamp,
+        entry.prev_hash.clone(),
+    );
+    env.crypto().sha256(&contents.to_xdr(env)).into()
+}
+
+/// Create an escrow for an invoice, e.g. when a bid is accepted
 pub fn create_escrow(
     env: &Env,
-    invoice_id: &BytesN<32>,
+    invoice: &Invoice,
     investor: &Address,
-    business: &Address,
     amount: i128,
-    currency: &Address,
+    kind: EscrowKind,
+    actor: &Address,
 ) -> Result<BytesN<32>, QuickLendXError> {
     let escrow_id = EscrowStorage::generate_unique_escrow_id(env);
     let escrow = Escrow {
         escrow_id: escrow_id.clone(),
-        invoice_id: invoice_id.clone(),
+        invoice_id: invoice.id.clone(),
+        kind,
         investor: investor.clone(),
-        business: business.clone(),
+        business: invoice.business.clone(),
         amount,
-        currency: currency.clone(),
+        currency: invoice.currency.clone(),
         created_at: env.ledger().timestamp(),
         status: EscrowStatus::Held,
     };
 
     EscrowStorage::store_escrow(env, &escrow);
+    record_escrow_change(env, &escrow.currency, amount);
+    EscrowStorage::append_ledger_entry(
+        env,
+        &escrow,
+        EscrowDirection::Deposit,
+        escrow.amount,
+        investor,
+        &env.current_contract_address(),
+        actor,
+    );
     Ok(escrow_id)
 }
 
-/// Release escrow funds to business upon invoice verification
-pub fn release_escrow(
-    env: &Env,
-    invoice_id: &BytesN<32>,
-) -> Result<(), QuickLendXError> {
-    let mut escrow = EscrowStorage::get_escrow_by_invoice(env, invoice_id)
+/// Load a held escrow whose invoice is not frozen by an open dispute
+fn get_movable_escrow(env: &Env, escrow_id: &BytesN<32>) -> Result<Escrow, QuickLendXError> {
+    let escrow = EscrowStorage::get_escrow(env, escrow_id)
         .ok_or(QuickLendXError::StorageKeyNotFound)?;
 
     if escrow.status != EscrowStatus::Held {
         return Err(QuickLendXError::InvalidStatus);
     }
+    if DisputeStorage::is_open(env, &escrow.invoice_id) {
+        return Err(QuickLendXError::EscrowFrozen);
+    }
+    Ok(escrow)
+}
+
+/// Release escrow funds to business upon invoice verification
+pub fn release_escrow(
+    env: &Env,
+    escrow_id: &BytesN<32>,
+    actor: &Address,
+) -> Result<Escrow, QuickLendXError> {
+    let mut escrow = get_movable_escrow(env, escrow_id)?;
 
     // Transfer funds from escrow to business
-    let transfer_success = transfer_funds(env, &escrow.investor, &escrow.business, escrow.amount);
+    let transfer_success = transfer_funds(
+        env,
+        &env.current_contract_address(),
+        &escrow.business,
+        escrow.amount,
+    );
     if !transfer_success {
         return Err(QuickLendXError::InsufficientFunds);
     }
@@ -117,33 +319,85 @@ pub fn release_escrow(
     // Update escrow status
     escrow.status = EscrowStatus::Released;
     EscrowStorage::update_escrow(env, &escrow);
+    record_escrow_change(env, &escrow.currency, -escrow.amount);
+    EscrowStorage::append_ledger_entry(
+        env,
+        &escrow,
+        EscrowDirection::Release,
+        escrow.amount,
+        &env.current_contract_address(),
+        &escrow.business,
+        actor,
+    );
 
-    Ok(())
+    Ok(escrow)
 }
 
 /// Refund escrow funds to investor if verification fails
 pub fn refund_escrow(
     env: &Env,
-    invoice_id: &BytesN<32>,
-) -> Result<(), QuickLendXError> {
-    let mut escrow = EscrowStorage::get_escrow_by_invoice(env, invoice_id)
-        .ok_or(QuickLendXError::StorageKeyNotFound)?;
+    escrow_id: &BytesN<32>,
+    actor: &Address,
+) -> Result<Escrow, QuickLendXError> {
+    let mut escrow = get_movable_escrow(env, escrow_id)?;
 
-    if escrow.status != EscrowStatus::Held {
-        return Err(QuickLendXError::InvalidStatus);
+    // Refund funds to the investor's balance
+    credit_balance(env, &escrow.investor, &escrow.currency, escrow.amount);
+
+    // Update escrow status
+    escrow.status = EscrowStatus::Refunded;
+    EscrowStorage::update_escrow(env, &escrow);
+    record_escrow_change(env, &escrow.currency, -escrow.amount);
+    EscrowStorage::append_ledger_entry(
+        env,
+        &escrow,
+        EscrowDirection::Refund,
+        escrow.amount,
+        &env.current_contract_address(),
+        &escrow.investor,
+        actor,
+    );
+
+    Ok(escrow)
+}
+
+/// Divide escrow funds between business and investor, giving the business
+/// `business_bps` of the amount
+pub fn split_escrow(
+    env: &Env,
+    escrow_id: &BytesN<32>,
+    business_bps: u32,
+    actor: &Address,
+) -> Result<Escrow, QuickLendXError> {
+    let mut escrow = get_movable_escrow(env, escrow_id)?;
+    if business_bps > 10_000 {
+        return Err(QuickLendXError::InvalidAmount);
     }
+    let business_amount = escrow.amount * business_bps as i128 / 10_000;
+    let investor_amount = escrow.amount - business_amount;
 
-    // Refund funds to investor
-    let transfer_success = transfer_funds(env, &escrow.business, &escrow.investor, escrow.amount);
-    if !transfer_success {
+    let contract = env.current_contract_address();
+    if business_amount > 0 && !transfer_funds(env, &contract, &escrow.business, business_amount) {
         return Err(QuickLendXError::InsufficientFunds);
     }
+    // The investor's part is refunded to their balance
+    credit_balance(env, &escrow.investor, &escrow.currency, investor_amount);
 
-    // Update escrow status
-    escrow.status = EscrowStatus::Refunded;
+    escrow.status = EscrowStatus::Split;
     EscrowStorage::update_escrow(env, &escrow);
+    record_escrow_change(env, &escrow.currency, -escrow.amount);
+    for (direction, amount, to) in [
+        (EscrowDirection::Release, business_amount, &escrow.business),
+        (EscrowDirection::Refund, investor_amount, &escrow.investor),
+    ] {
+        if amount > 0 {
+            EscrowStorage::append_ledger_entry(
+                env, &escrow, direction, amount, &contract, to, actor,
+            );
+        }
+    }
 
-    Ok(())
+    Ok(escrow)
 }
 
 /// Transfer funds between addresses
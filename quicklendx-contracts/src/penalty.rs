@@ -0,0 +1,247 @@
+use crate::bid::{BidStatus, BidStorage};
+use crate::config_history::record_config_change;
+use crate::delinquency::{current_stage, DelinquencyStage};
+use crate::errors::QuickLendXError;
+use crate::events::{emit_late_fee_set, emit_penalty_schedule_set};
+use crate::investment::invoice_fee_terms;
+use crate::invoice::{Invoice, InvoiceStatus, InvoiceStorage};
+use crate::profits::SECONDS_PER_YEAR;
+use crate::settlement::get_amount_paid;
+use crate::verification::require_admin;
+use soroban_sdk::{contracttype, symbol_short, Address, BytesN, Env, Vec};
+
+/// Highest penalty APR a schedule tier may charge (500%)
+pub const MAX_PENALTY_APR_BPS: u32 = 50_000;
+/// Maximum number of step-up tiers in a schedule
+pub const MAX_PENALTY_TIERS: u32 = 10;
+/// Highest flat late fee per full day past the due date (1% of the invoice
+/// amount)
+pub const MAX_LATE_FEE_BPS_PER_DAY: u32 = 100;
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// A penalty APR that applies from `starts_after` seconds past the end of grace
+/// until the next tier starts
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PenaltyTier {
+    pub starts_after: u64, // Seconds after the grace period ends
+    pub apr_bps: u32,      // Penalty APR on the invoice amount, in bps
+}
+
+/// Late payment schedule: interest-free grace after the due date, then
+/// step-up penalty tiers ordered by start offset
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PenaltySchedule {
+    pub grace_period: u64, // Seconds after due date without penalty
+    pub tiers: Vec<PenaltyTier>,
+}
+
+/// Penalty accrued under a single tier
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PenaltyAccrual {
+    pub apr_bps: u32,
+    pub from: u64, // Timestamp the tier started accruing
+    pub to: u64,   // Timestamp accrual under the tier stopped (or now)
+    pub amount: i128,
+}
+
+/// Breakdown of what a business currently owes on a funded invoice
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AmountDue {
+    pub invoice_id: BytesN<32>,
+    pub principal: i128, // Amount advanced by investors
+    pub profit: i128,    // Returns promised to investors on top of the principal
+    pub penalty: i128,   // Penalty interest under the schedule tiers
+    pub late_fee: i128,  // Flat late fee for each full day past the due date
+    pub total: i128,
+    pub paid: i128,        // Repaid so far through installments
+    pub outstanding: i128, // Total less what has been repaid
+    pub due_date: u64,
+    pub grace_ends_at: u64,
+    pub days_late: u64,       // Full days past the due date
+    pub current_apr_bps: u32, // 0 while the invoice is not yet due or within grace
+    pub accruals: Vec<PenaltyAccrual>,
+    pub delinquency_stage: DelinquencyStage, // As of now, even if keepers lag behind
+}
+
+/// Get the configured penalty schedule (no grace and no tiers by default)
+pub fn get_penalty_schedule(env: &Env) -> PenaltySchedule {
+    env.storage()
+        .instance()
+        .get(&symbol_short!("pen_sched"))
+        .unwrap_or_else(|| PenaltySchedule {
+            grace_period: 0,
+            tiers: Vec::new(env),
+        })
+}
+
+/// Replace the penalty schedule (admin only). Penalty tiers and the flat
+/// late fee are alternative late charges, so tiers cannot be set while a
+/// late fee is.
+pub fn set_penalty_schedule(
+    env: &Env,
+    admin: &Address,
+    schedule: &PenaltySchedule,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    validate_schedule(schedule)?;
+    if !schedule.tiers.is_empty() && get_late_fee_bps_per_day(env) > 0 {
+        return Err(QuickLendXError::LateChargeConflict);
+    }
+    env.storage()
+        .instance()
+        .set(&symbol_short!("pen_sched"), schedule);
+    record_config_change(env, "penalty_schedule", schedule, admin);
+    emit_penalty_schedule_set(env, schedule, admin);
+    Ok(())
+}
+
+/// Get the flat late fee charged per full day past the due date, in bps of
+/// the invoice amount (none by default). The grace period does not apply to
+/// it.
+pub fn get_late_fee_bps_per_day(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&symbol_short!("late_fee"))
+        .unwrap_or(0)
+}
+
+/// Set the flat late fee per day (admin only). Cannot be set while the
+/// penalty schedule has tiers.
+pub fn set_late_fee_bps_per_day(
+    env: &Env,
+    admin: &Address,
+    bps_per_day: u32,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    if bps_per_day > MAX_LATE_FEE_BPS_PER_DAY {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    if bps_per_day > 0 && !get_penalty_schedule(env).tiers.is_empty() {
+        return Err(QuickLendXError::LateChargeConflict);
+    }
+    env.storage()
+        .instance()
+        .set(&symbol_short!("late_fee"), &bps_per_day);
+    record_config_change(env, "late_fee", bps_per_day, admin);
+    emit_late_fee_set(env, bps_per_day, admin);
+    Ok(())
+}
+
+fn validate_schedule(schedule: &PenaltySchedule) -> Result<(), QuickLendXError> {
+    if schedule.tiers.len() > MAX_PENALTY_TIERS {
+        return Err(QuickLendXError::OperationNotAllowed);
+    }
+    let mut previous_start: Option<u64> = None;
+    for tier in schedule.tiers.iter() {
+        if tier.apr_bps > MAX_PENALTY_APR_BPS {
+            return Err(QuickLendXError::InvalidAmount);
+        }
+        if let Some(previous) = previous_start {
+            if tier.starts_after <= previous {
+                return Err(QuickLendXError::InvalidTimestamp);
+            }
+        }
+        previous_start = Some(tier.starts_after);
+    }
+    Ok(())
+}
+
+/// Compute the amount due on a funded invoice, including any penalty and late
+/// fee accrued up to the current ledger time under the terms in force when
+/// it was funded
+pub fn get_amount_due(env: &Env, invoice_id: &BytesN<32>) -> Result<AmountDue, QuickLendXError> {
+    let invoice =
+        InvoiceStorage::get_invoice(env, invoice_id).ok_or(QuickLendXError::InvoiceNotFound)?;
+    if invoice.status != InvoiceStatus::Funded {
+        return Err(QuickLendXError::InvoiceNotFunded);
+    }
+    let terms = invoice_fee_terms(env, invoice_id);
+    Ok(calculate_amount_due(
+        env,
+        &invoice,
+        &terms.penalty_schedule,
+        terms.late_fee_bps_per_day,
+        env.ledger().timestamp(),
+    ))
+}
+
+/// Total returns promised by the accepted bids funding an invoice
+pub fn expected_returns(env: &Env, invoice_id: &BytesN<32>) -> i128 {
+    let mut total = 0i128;
+    for bid_id in BidStorage::get_bids_for_invoice(env, invoice_id).iter() {
+        if let Some(bid) = BidStorage::get_bid(env, &bid_id) {
+            if bid.status == BidStatus::Accepted {
+                total += bid.expected_return;
+            }
+        }
+    }
+    total
+}
+
+fn calculate_amount_due(
+    env: &Env,
+    invoice: &Invoice,
+    schedule: &PenaltySchedule,
+    late_fee_bps_per_day: u32,
+    now: u64,
+) -> AmountDue {
+    let grace_ends_at = invoice.due_date.saturating_add(schedule.grace_period);
+    let elapsed = now.saturating_sub(grace_ends_at);
+
+    let mut accruals = Vec::new(env);
+    let mut penalty = 0i128;
+    let mut current_apr_bps = 0u32;
+    if elapsed > 0 {
+        let tier_count = schedule.tiers.len();
+        for i in 0..tier_count {
+            let tier = schedule.tiers.get(i).unwrap();
+            if tier.starts_after >= elapsed {
+                break;
+            }
+            let end = if i + 1 < tier_count {
+                schedule.tiers.get(i + 1).unwrap().starts_after.min(elapsed)
+            } else {
+                elapsed
+            };
+            let amount = invoice.amount * tier.apr_bps as i128 * (end - tier.starts_after) as i128
+                / (10_000 * SECONDS_PER_YEAR as i128);
+            accruals.push_back(PenaltyAccrual {
+                apr_bps: tier.apr_bps,
+                from: grace_ends_at + tier.starts_after,
+                to: grace_ends_at + end,
+                amount,
+            });
+            penalty += amount;
+            current_apr_bps = tier.apr_bps;
+        }
+    }
+
+    let days_late = now.saturating_sub(invoice.due_date) / SECONDS_PER_DAY;
+    let late_fee = invoice.amount * late_fee_bps_per_day as i128 * days_late as i128 / 10_000;
+
+    let principal = invoice.funded_amount;
+    let profit = (expected_returns(env, &invoice.id) - principal).max(0);
+    let total = principal + profit + penalty + late_fee;
+    let paid = get_amount_paid(env, &invoice.id);
+    AmountDue {
+        invoice_id: invoice.id.clone(),
+        principal,
+        profit,
+        penalty,
+        late_fee,
+        total,
+        paid,
+        outstanding: (total - paid).max(0),
+        due_date: invoice.due_date,
+        grace_ends_at,
+        days_late,
+        current_apr_bps,
+        accruals,
+        delinquency_stage: current_stage(env, invoice),
+    }
+}
@@ -0,0 +1,79 @@
+use crate::errors::QuickLendXError;
+use crate::events::emit_priority_window_set;
+use crate::invoice::{Invoice, InvoiceStorage};
+use crate::storage::{self, DataKey};
+use soroban_sdk::{Address, BytesN, Env};
+
+/// Longest right-of-first-refusal window a business may give repeat investors
+pub const MAX_PRIORITY_WINDOW: u64 = 7 * 86_400;
+
+pub struct PriorityStorage;
+
+impl PriorityStorage {
+    /// Get how long a business's newly verified invoices are reserved for
+    /// repeat investors, in seconds (0 when disabled)
+    pub fn get_priority_window(env: &Env, business: &Address) -> u64 {
+        storage::get(env, &DataKey::PriorityWindow(business.clone())).unwrap_or(0)
+    }
+
+    /// Get when an invoice's priority window closes, if it had one
+    pub fn get_window_end(env: &Env, invoice_id: &BytesN<32>) -> Option<u64> {
+        storage::get(env, &DataKey::PriorityWindowEnd(invoice_id.clone()))
+    }
+}
+
+/// Set how long the business's newly verified invoices are open only to
+/// investors who funded it before; 0 turns the priority window off
+/// (business only)
+pub fn set_priority_window(
+    env: &Env,
+    business: &Address,
+    window: u64,
+) -> Result<(), QuickLendXError> {
+    business.require_auth();
+    if window > MAX_PRIORITY_WINDOW {
+        return Err(QuickLendXError::InvalidTimestamp);
+    }
+    storage::set(env, &DataKey::PriorityWindow(business.clone()), &window);
+    emit_priority_window_set(env, business, window);
+    Ok(())
+}
+
+/// Start the invoice's priority window when it is verified for listing
+pub fn open_priority_window(env: &Env, invoice: &Invoice) {
+    let window = PriorityStorage::get_priority_window(env, &invoice.business);
+    if window == 0 {
+        return;
+    }
+    storage::set(
+        env,
+        &DataKey::PriorityWindowEnd(invoice.id.clone()),
+        &(env.ledger().timestamp() + window),
+    );
+}
+
+/// Whether the investor funded any invoice of the business before
+pub fn is_repeat_investor(env: &Env, business: &Address, investor: &Address) -> bool {
+    InvoiceStorage::get_business_invoices(env, business)
+        .iter()
+        .filter_map(|invoice_id| InvoiceStorage::get_invoice(env, &invoice_id))
+        .any(|invoice| invoice.is_investor(investor))
+}
+
+/// Reject bids from investors without priority while the invoice's priority
+/// window is still open
+pub fn check_priority_bid(
+    env: &Env,
+    invoice: &Invoice,
+    investor: &Address,
+) -> Result<(), QuickLendXError> {
+    let Some(window_end) = PriorityStorage::get_window_end(env, &invoice.id) else {
+        return Ok(());
+    };
+    if env.ledger().timestamp() < window_end
+        && !is_repeat_investor(env, &invoice.business, investor)
+    {
+        return Err(QuickLendXError::PriorityWindowActive);
+    }
+    Ok(())
+}
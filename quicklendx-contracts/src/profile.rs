@@ -0,0 +1,71 @@
+use crate::config::check_description_length;
+use crate::errors::QuickLendXError;
+use crate::events::emit_business_profile_updated;
+use crate::storage::{self, DataKey};
+use crate::verification::BusinessVerificationStorage;
+use soroban_sdk::{contracttype, Address, BytesN, Env, String, Symbol};
+
+/// Public details of a business, kept alongside its KYC record. Contact
+/// details stay off-chain; only their hash is stored.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BusinessProfile {
+    pub business: Address,
+    pub name: String,
+    pub contact_hash: BytesN<32>,
+    pub country: Symbol, // Country of registration (e.g. "US", "DE")
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+pub struct ProfileStorage;
+
+impl ProfileStorage {
+    /// Get a business's profile, if it has one
+    pub fn get_profile(env: &Env, business: &Address) -> Option<BusinessProfile> {
+        storage::get(env, &DataKey::BusinessProfile(business.clone()))
+    }
+
+    fn set_profile(env: &Env, profile: &BusinessProfile) {
+        storage::set(
+            env,
+            &DataKey::BusinessProfile(profile.business.clone()),
+            profile,
+        );
+    }
+}
+
+/// Create or replace a business's profile (business only). The business must
+/// have applied for KYC; the creation date is kept across updates.
+pub fn update_business_profile(
+    env: &Env,
+    business: &Address,
+    name: String,
+    contact_hash: BytesN<32>,
+    country: Symbol,
+) -> Result<BusinessProfile, QuickLendXError> {
+    business.require_auth();
+    if BusinessVerificationStorage::get_verification(env, business).is_none() {
+        return Err(QuickLendXError::KYCNotFound);
+    }
+    if name.is_empty() {
+        return Err(QuickLendXError::InvalidDescription);
+    }
+    check_description_length(env, &name)?;
+
+    let now = env.ledger().timestamp();
+    let created_at = ProfileStorage::get_profile(env, business)
+        .map(|profile| profile.created_at)
+        .unwrap_or(now);
+    let profile = BusinessProfile {
+        business: business.clone(),
+        name,
+        contact_hash,
+        country,
+        created_at,
+        updated_at: now,
+    };
+    ProfileStorage::set_profile(env, &profile);
+    emit_business_profile_updated(env, &profile);
+    Ok(profile)
+}
@@ -1,3 +1,7 @@
+/// Seconds in a 365-day year, used to annualize returns
+pub const SECONDS_PER_YEAR: u64 = 365 * 86_400;
+pub const SECONDS_PER_DAY: u64 = 86_400;
+
 pub fn calculate_profit(
     investment_amount: i128,
     payment_amount: i128,
@@ -8,3 +12,40 @@ pub fn calculate_profit(
     let investor_return = payment_amount - platform_fee;
     (investor_return, platform_fee)
 }
+
+/// Annualized return in basis points for investing `amount` and receiving
+/// `expected_return` after `duration_secs`. Periods under a day count as one day.
+pub fn calculate_apr_bps(amount: i128, expected_return: i128, duration_secs: u64) -> i128 {
+    if amount <= 0 {
+        return 0;
+    }
+    let duration = duration_secs.max(SECONDS_PER_DAY) as i128;
+    (expected_return - amount) * 10_000 * SECONDS_PER_YEAR as i128 / (amount * duration)
+}
+
+/// Annualized return in basis points implied by a bid of `amount` for
+/// `expected_return` on an invoice due in `days_to_due` days, counting
+/// actual days over a 365-day year
+pub fn calculate_bid_apr_bps(amount: i128, expected_return: i128, days_to_due: u32) -> i128 {
+    calculate_apr_bps(
+        amount,
+        expected_return,
+        days_to_due as u64 * SECONDS_PER_DAY,
+    )
+}
+
+/// Annualized return in basis points for capital that earned `profit` over
+/// `capital_seconds`, the sum of each amount invested times the seconds it
+/// was invested for
+pub fn calculate_realized_apr_bps(profit: i128, capital_seconds: i128) -> i128 {
+    if capital_seconds <= 0 {
+        return 0;
+    }
+    profit * 10_000 * SECONDS_PER_YEAR as i128 / capital_seconds
+}
+
+/// Largest total return on `amount` that stays within `apr_bps` over `duration_secs`
+pub fn max_return_for_apr(amount: i128, apr_bps: i128, duration_secs: u64) -> i128 {
+    let duration = duration_secs.max(SECONDS_PER_DAY) as i128;
+    amount + amount * apr_bps * duration / (10_000 * SECONDS_PER_YEAR as i128)
+}
@@ -0,0 +1,347 @@
+//! Property tests that drive random sequences of protocol operations against
+//! the contract and check its invariants after every step, to catch
+//! regressions that span modules. Run with `cargo test --features proptest`.
+
+extern crate std;
+
+use crate::invoice::InvoiceStatus;
+use crate::{QuickLendXContract, QuickLendXContractClient};
+use proptest::prelude::*;
+use proptest::sample::Index;
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::{Address, BytesN, Env, String};
+use std::vec;
+use std::vec::Vec as StdVec;
+
+const BUSINESSES: usize = 2;
+const INVESTORS: usize = 3;
+const SECONDS_PER_DAY: u64 = 86_400;
+
+const ALL_STATUSES: [InvoiceStatus; 8] = [
+    InvoiceStatus::Pending,
+    InvoiceStatus::Verified,
+    InvoiceStatus::Funded,
+    InvoiceStatus::Paid,
+    InvoiceStatus::Defaulted,
+    InvoiceStatus::Cancelled,
+    InvoiceStatus::Expired,
+    InvoiceStatus::Refinanced,
+];
+
+/// A protocol operation. Indexes pick among the actors, invoices and bids
+/// created so far, so most operations land on something that exists; the
+/// contract is free to refuse them.
+#[derive(Clone, Debug)]
+enum Op {
+    Upload {
+        business: Index,
+        amount: i128,
+        due_in_days: u64,
+    },
+    Verify {
+        invoice: Index,
+    },
+    Cancel {
+        invoice: Index,
+    },
+    SetFundingDeadline {
+        invoice: Index,
+        in_days: u64,
+    },
+    PlaceBid {
+        investor: Index,
+        invoice: Index,
+        percent: i128,         // Of the invoice amount
+        premium_percent: i128, // Of the bid amount
+    },
+    AcceptBid {
+        bid: Index,
+    },
+    WithdrawBid {
+        bid: Index,
+    },
+    Settle {
+        invoice: Index,
+        percent: i128, // Of the invoice amount
+    },
+    AdvanceTime {
+        days: u64,
+    },
+    ExpireInvoices,
+    CheckOverdue,
+}
+
+/// Mostly full bids, so that invoices get funded
+fn bid_percent() -> impl Strategy<Value = i128> {
+    prop_oneof![3 => Just(100), 1 => 1..100i128]
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        3 => (any::<Index>(), 1..5_000i128, 1..60u64).prop_map(|(business, amount, due_in_days)| {
+            Op::Upload {
+                business,
+                amount,
+                due_in_days,
+            }
+        }),
+        3 => any::<Index>().prop_map(|invoice| Op::Verify { invoice }),
+        1 => any::<Index>().prop_map(|invoice| Op::Cancel { invoice }),
+        1 => (any::<Index>(), 1..30u64)
+            .prop_map(|(invoice, in_days)| Op::SetFundingDeadline { invoice, in_days }),
+        4 => (any::<Index>(), any::<Index>(), bid_percent(), 0..=20i128).prop_map(
+            |(investor, invoice, percent, premium_percent)| Op::PlaceBid {
+                investor,
+                invoice,
+                percent,
+                premium_percent,
+            }
+        ),
+        3 => any::<Index>().prop_map(|bid| Op::AcceptBid { bid }),
+        1 => any::<Index>().prop_map(|bid| Op::WithdrawBid { bid }),
+        2 => (any::<Index>(), 50..=150i128)
+            .prop_map(|(invoice, percent)| Op::Settle { invoice, percent }),
+        2 => (1..90u64).prop_map(|days| Op::AdvanceTime { days }),
+        1 => Just(Op::ExpireInvoices),
+        1 => Just(Op::CheckOverdue),
+    ]
+}
+
+/// Whether an invoice may go straight from one status to another
+fn is_legal_transition(from: &InvoiceStatus, to: &InvoiceStatus) -> bool {
+    use InvoiceStatus::*;
+    from == to
+        || matches!(
+            (from, to),
+            (Pending, Verified)
+                | (Pending, Cancelled)
+                | (Pending, Expired)
+                | (Verified, Funded)
+                | (Verified, Cancelled)
+                | (Verified, Expired)
+                | (Funded, Paid)
+                | (Funded, Defaulted)
+                | (Funded, Refinanced)
+                // The watchdog unwinds funding that was never backed
+                | (Funded, Verified)
+        )
+}
+
+fn pick<T: Clone>(items: &[T], index: &Index) -> Option<T> {
+    (!items.is_empty()).then(|| items[index.index(items.len())].clone())
+}
+
+/// The contract under test and what the operations have created in it
+struct Harness {
+    env: Env,
+    client: QuickLendXContractClient<'static>,
+    admin: Address,
+    currency: Address,
+    businesses: StdVec<Address>,
+    investors: StdVec<Address>,
+    invoices: StdVec<BytesN<32>>,
+    statuses: StdVec<InvoiceStatus>, // Last seen status of each invoice
+    bids: StdVec<BytesN<32>>,
+}
+
+impl Harness {
+    fn new() -> Self {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register(QuickLendXContract, ());
+        let client = QuickLendXContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        client.initialize(&admin);
+        let businesses: StdVec<Address> = (0..BUSINESSES)
+            .map(|_| {
+                let business = Address::generate(&env);
+                client.submit_kyc_application(&business, &String::from_str(&env, "KYC data"));
+                client.verify_business(&admin, &business);
+                business
+            })
+            .collect();
+        let investors = (0..INVESTORS).map(|_| Address::generate(&env)).collect();
+        Harness {
+            currency: Address::generate(&env),
+            env,
+            client,
+            admin,
+            businesses,
+            investors,
+            invoices: StdVec::new(),
+            statuses: StdVec::new(),
+            bids: StdVec::new(),
+        }
+    }
+
+    fn owner(&self, invoice_id: &BytesN<32>) -> Address {
+        self.client.get_invoice(invoice_id).business
+    }
+
+    /// Apply an operation, ignoring whether the contract accepts it
+    fn apply(&mut self, op: &Op) {
+        let now = self.env.ledger().timestamp();
+        let invoice = |index: &Index| pick(&self.invoices, index);
+        match op {
+            Op::Upload {
+                business,
+                amount,
+                due_in_days,
+            } => {
+                let business = pick(&self.businesses, business).unwrap();
+                let uploaded = self.client.try_upload_invoice(
+                    &business,
+                    amount,
+                    &self.currency,
+                    &(now + due_in_days * SECONDS_PER_DAY),
+                    &String::from_str(&self.env, "Invoice"),
+                );
+                if let Ok(Ok(invoice_id)) = uploaded {
+                    self.invoices.push(invoice_id);
+                    self.statuses.push(InvoiceStatus::Pending);
+                }
+            }
+            Op::Verify { invoice: index } => {
+                if let Some(invoice_id) = invoice(index) {
+                    let _ = self.client.try_verify_invoice(&self.admin, &invoice_id);
+                }
+            }
+            Op::Cancel { invoice: index } => {
+                if let Some(invoice_id) = invoice(index) {
+                    let _ = self
+                        .client
+                        .try_cancel_invoice(&self.owner(&invoice_id), &invoice_id);
+                }
+            }
+            Op::SetFundingDeadline {
+                invoice: index,
+                in_days,
+            } => {
+                if let Some(invoice_id) = invoice(index) {
+                    let deadline = Some(now + in_days * SECONDS_PER_DAY);
+                    let _ = self.client.try_set_funding_deadline(
+                        &self.owner(&invoice_id),
+                        &invoice_id,
+                        &deadline,
+                    );
+                }
+            }
+            Op::PlaceBid {
+                investor,
+                invoice: index,
+                percent,
+                premium_percent,
+            } => {
+                let investor = pick(&self.investors, investor).unwrap();
+                if let Some(invoice_id) = invoice(index) {
+                    let amount =
+                        (self.client.get_invoice(&invoice_id).amount * percent / 100).max(1);
+                    let expected_return = amount + amount * premium_percent / 100;
+                    let placed = self.client.try_place_bid(
+                        &investor,
+                        &invoice_id,
+                        &amount,
+                        &expected_return,
+                    );
+                    if let Ok(Ok(bid_id)) = placed {
+                        self.bids.push(bid_id);
+                    }
+                }
+            }
+            Op::AcceptBid { bid } => {
+                if let Some(bid) = pick(&self.bids, bid).and_then(|id| self.client.get_bid(&id)) {
+                    let business = self.owner(&bid.invoice_id);
+                    let _ = self
+                        .client
+                        .try_accept_bid(&business, &bid.invoice_id, &bid.bid_id);
+                }
+            }
+            Op::WithdrawBid { bid } => {
+                if let Some(bid) = pick(&self.bids, bid).and_then(|id| self.client.get_bid(&id)) {
+                    let _ = self.client.try_withdraw_bid(&bid.investor, &bid.bid_id);
+                }
+            }
+            Op::Settle {
+                invoice: index,
+                percent,
+            } => {
+                if let Some(invoice_id) = invoice(index) {
+                    let invoice = self.client.get_invoice(&invoice_id);
+                    let payment = invoice.amount * percent / 100;
+                    let _ =
+                        self.client
+                            .try_settle_invoice(&invoice.business, &invoice_id, &payment);
+                }
+            }
+            Op::AdvanceTime { days } => {
+                self.env
+                    .ledger()
+                    .with_mut(|li| li.timestamp += days * SECONDS_PER_DAY);
+            }
+            Op::ExpireInvoices => {
+                let _ = self.client.try_expire_invoices();
+            }
+            Op::CheckOverdue => {
+                self.client.check_overdue_invoices();
+            }
+        }
+    }
+
+    /// Panic if an invariant no longer holds
+    fn check_invariants(&mut self) {
+        // Each status index lists only invoices in that status...
+        let mut listed = 0;
+        for status in ALL_STATUSES.iter() {
+            for invoice_id in self.client.get_invoices_by_status(status).iter() {
+                assert_eq!(&self.client.get_invoice(&invoice_id).status, status);
+                listed += 1;
+            }
+        }
+        // ...and every invoice appears exactly once, in its own status's
+        // index and its business's
+        assert_eq!(listed, self.invoices.len());
+        for (invoice_id, last_status) in self.invoices.iter().zip(self.statuses.iter_mut()) {
+            let invoice = self.client.get_invoice(invoice_id);
+            let by_status = self.client.get_invoices_by_status(&invoice.status);
+            assert_eq!(by_status.iter().filter(|id| id == invoice_id).count(), 1);
+            let by_business = self.client.get_business_invoices(&invoice.business);
+            assert_eq!(by_business.iter().filter(|id| id == invoice_id).count(), 1);
+
+            assert!(
+                is_legal_transition(last_status, &invoice.status),
+                "invoice went from {last_status:?} to {:?}",
+                invoice.status
+            );
+            *last_status = invoice.status;
+
+            assert!(invoice.funded_amount >= 0 && invoice.funded_amount <= invoice.amount);
+            assert!(self.client.get_amount_paid(invoice_id) >= 0);
+        }
+
+        // No balance the contract keeps goes negative
+        assert!(self.client.get_treasury_balance(&self.currency) >= 0);
+        assert!(self.client.get_insurance_fund_balance(&self.currency) >= 0);
+        for business in self.businesses.iter() {
+            assert!(self.client.get_business_exposure(business) >= 0);
+        }
+        for investor in self.investors.iter() {
+            assert!(self.client.get_portfolio_stats(investor).active_amount >= 0);
+        }
+        for bid_id in self.bids.iter() {
+            assert!(self.client.get_bid(bid_id).unwrap().bid_amount > 0);
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn random_operations_keep_protocol_invariants(ops in prop::collection::vec(op(), 1..60)) {
+        let mut harness = Harness::new();
+        for op in ops.iter() {
+            harness.apply(op);
+            harness.check_invariants();
+        }
+    }
+}
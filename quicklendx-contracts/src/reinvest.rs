@@ -0,0 +1,31 @@
+use crate::compliance::check_not_blacklisted;
+use crate::errors::QuickLendXError;
+use crate::events::emit_auto_reinvest_set;
+use crate::storage::{self, DataKey};
+use soroban_sdk::{Address, Env};
+
+/// Investors who opt in to reinvestment have their settlement returns
+/// credited to their balance instead of paid out, ready to fund their next
+/// bids.
+pub struct ReinvestStorage;
+
+impl ReinvestStorage {
+    /// Whether an investor has opted in to reinvesting settlement returns
+    pub fn is_enabled(env: &Env, investor: &Address) -> bool {
+        storage::get(env, &DataKey::AutoReinvest(investor.clone())).unwrap_or(false)
+    }
+}
+
+/// Opt in to or out of reinvesting settlement returns (investor only). The
+/// balance already held stays until withdrawn.
+pub fn set_auto_reinvest(
+    env: &Env,
+    investor: &Address,
+    enabled: bool,
+) -> Result<(), QuickLendXError> {
+    investor.require_auth();
+    check_not_blacklisted(env, investor)?;
+    storage::set(env, &DataKey::AutoReinvest(investor.clone()), &enabled);
+    emit_auto_reinvest_set(env, investor, enabled);
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use crate::errors::QuickLendXError;
+use crate::events::emit_due_reminder;
+use crate::invoice::{InvoiceStatus, InvoiceStorage};
+use crate::notifications::{notify, NotificationKind};
+use crate::storage::{self, DataKey};
+use soroban_sdk::{BytesN, Env, Vec};
+
+/// Get when an invoice was last reminded of for a reminder window, if it has
+/// been
+pub fn get_last_reminder(env: &Env, invoice_id: &BytesN<32>, window: u64) -> Option<u64> {
+    storage::get(env, &DataKey::DueReminder(invoice_id.clone(), window))
+}
+
+/// Publish a reminder for every funded invoice due within `window` seconds
+/// from now, for notification services to pick up. Each window is its own
+/// bucket: an invoice is reminded of once per window, so calling with 7 days
+/// and later with 1 day reminds twice. Anyone may call this; returns the ids
+/// of the invoices reminded of.
+pub fn emit_due_reminders(env: &Env, window: u64) -> Result<Vec<BytesN<32>>, QuickLendXError> {
+    if window == 0 {
+        return Err(QuickLendXError::InvalidTimestamp);
+    }
+    let now = env.ledger().timestamp();
+    let mut reminded = Vec::new(env);
+    for invoice_id in InvoiceStorage::get_invoices_by_status(env, &InvoiceStatus::Funded).iter() {
+        let Some(invoice) = InvoiceStorage::get_invoice(env, &invoice_id) else {
+            continue;
+        };
+        let due_soon = invoice.due_date >= now && invoice.due_date - now <= window;
+        if !due_soon || get_last_reminder(env, &invoice_id, window).is_some() {
+            continue;
+        }
+        storage::set(env, &DataKey::DueReminder(invoice_id.clone(), window), &now);
+        emit_due_reminder(env, &invoice, window);
+        notify(
+            env,
+            &invoice.business,
+            NotificationKind::PaymentDueSoon,
+            &invoice_id,
+        );
+        reminded.push_back(invoice_id);
+    }
+    Ok(reminded)
+}
@@ -0,0 +1,531 @@
+use crate::config::MAX_PLATFORM_FEE_BPS;
+use crate::config_history::record_config_change;
+use crate::errors::QuickLendXError;
+use crate::events::{
+    emit_debtor_program_closed, emit_debtor_program_opened, emit_program_credit_line_set,
+    emit_reverse_factoring_fee_set, emit_supplier_invitation_accepted, emit_supplier_invited,
+    emit_supplier_invoice_confirmed,
+};
+use crate::investment::FeeTerms;
+use crate::invoice::{Invoice, InvoiceStatus, InvoiceStorage};
+use crate::profits::calculate_apr_bps;
+use crate::storage::{self, DataKey};
+use crate::verification::{
+    is_kyc_expired, require_admin, require_business_verification, submit_kyc_application,
+    BusinessVerificationStatus, BusinessVerificationStorage,
+};
+use soroban_sdk::{contracttype, symbol_short, Address, BytesN, Env, String, Vec};
+
+/// Longest an invitation to join a program stays open
+pub const MAX_INVITATION_VALIDITY: u64 = 30 * 86_400;
+
+/// A large debtor's supply-chain finance program. The debtor confirms the
+/// invoices its suppliers raise against it, and investors fund them on the
+/// strength of the debtor's credit at no more than the program's rate.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DebtorProgram {
+    pub debtor: Address,
+    pub currency: Address,
+    pub max_apr_bps: u32, // Preferential rate cap on investor returns
+    pub active: bool,     // Closed programs confirm no more invoices
+    pub opened_at: u64,
+    pub invoice_count: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum InvitationStatus {
+    Pending, // Open until `expires_at`
+    Accepted,
+}
+
+/// An anchor debtor's invitation to a supplier to join its program
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SupplierInvitation {
+    pub debtor: Address,
+    pub supplier: Address,
+    pub status: InvitationStatus,
+    pub invited_at: u64,
+    pub expires_at: u64,
+    pub accepted_at: Option<u64>,
+}
+
+/// Funding a debtor guarantees across its program. Fundings of program
+/// invoices draw it down and their settlement restores it; funding of an
+/// invoice that defaults stays drawn, as the debtor's guarantee covers it.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProgramCreditLine {
+    pub debtor: Address,
+    pub limit: i128,
+    pub drawn: i128, // Funding of program invoices outstanding under the line
+    pub updated_at: u64,
+}
+
+pub struct ReverseFactoringStorage;
+
+impl ReverseFactoringStorage {
+    /// Get a debtor's program
+    pub fn get_program(env: &Env, debtor: &Address) -> Option<DebtorProgram> {
+        storage::get(env, &DataKey::DebtorProgram(debtor.clone()))
+    }
+
+    fn set_program(env: &Env, program: &DebtorProgram) {
+        storage::set(
+            env,
+            &DataKey::DebtorProgram(program.debtor.clone()),
+            program,
+        );
+    }
+
+    /// Get every debtor that has opened a program, oldest first
+    pub fn get_debtors(env: &Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("rf_progs"))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    /// Get the invoices a debtor has confirmed under its program
+    pub fn get_program_invoices(env: &Env, debtor: &Address) -> Vec<BytesN<32>> {
+        storage::get(env, &DataKey::ProgramInvoices(debtor.clone()))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    /// Get the debtor whose program an invoice was confirmed under
+    pub fn get_invoice_debtor(env: &Env, invoice_id: &BytesN<32>) -> Option<Address> {
+        storage::get(env, &DataKey::InvoiceDebtor(invoice_id.clone()))
+    }
+
+    /// Get a debtor's invitation to a supplier
+    pub fn get_invitation(
+        env: &Env,
+        debtor: &Address,
+        supplier: &Address,
+    ) -> Option<SupplierInvitation> {
+        storage::get(
+            env,
+            &DataKey::SupplierInvitation(debtor.clone(), supplier.clone()),
+        )
+    }
+
+    fn set_invitation(env: &Env, invitation: &SupplierInvitation) {
+        storage::set(
+            env,
+            &DataKey::SupplierInvitation(invitation.debtor.clone(), invitation.supplier.clone()),
+            invitation,
+        );
+    }
+
+    /// Get the debtors that have invited a supplier, in invitation order
+    pub fn get_inviting_debtors(env: &Env, supplier: &Address) -> Vec<Address> {
+        storage::get(env, &DataKey::SupplierInviters(supplier.clone()))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    /// Get the suppliers that have joined a debtor's program, in joining order
+    pub fn get_program_suppliers(env: &Env, debtor: &Address) -> Vec<Address> {
+        storage::get(env, &DataKey::ProgramSuppliers(debtor.clone()))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    fn get_expedited(env: &Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("rf_kycq"))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    /// Get the credit line a debtor guarantees for its program, if any
+    pub fn get_credit_line(env: &Env, debtor: &Address) -> Option<ProgramCreditLine> {
+        storage::get(env, &DataKey::ProgramCreditLine(debtor.clone()))
+    }
+
+    fn set_credit_line(env: &Env, line: &ProgramCreditLine) {
+        storage::set(env, &DataKey::ProgramCreditLine(line.debtor.clone()), line);
+    }
+
+    /// Get the platform fee charged on program invoices, if the admin set one
+    pub fn get_fee_bps(env: &Env) -> Option<u32> {
+        env.storage().instance().get(&symbol_short!("rf_fee"))
+    }
+}
+
+/// Open a program, or change the rate of the debtor's program and reopen it
+/// (verified business only)
+pub fn open_debtor_program(
+    env: &Env,
+    debtor: &Address,
+    currency: &Address,
+    max_apr_bps: u32,
+) -> Result<DebtorProgram, QuickLendXError> {
+    debtor.require_auth();
+    require_business_verification(env, debtor)?;
+    if max_apr_bps == 0 {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    let program = match ReverseFactoringStorage::get_program(env, debtor) {
+        Some(existing) => DebtorProgram {
+            currency: currency.clone(),
+            max_apr_bps,
+            active: true,
+            ..existing
+        },
+        None => {
+            let mut debtors = ReverseFactoringStorage::get_debtors(env);
+            debtors.push_back(debtor.clone());
+            env.storage()
+                .instance()
+                .set(&symbol_short!("rf_progs"), &debtors);
+            DebtorProgram {
+                debtor: debtor.clone(),
+                currency: currency.clone(),
+                max_apr_bps,
+                active: true,
+                opened_at: env.ledger().timestamp(),
+                invoice_count: 0,
+            }
+        }
+    };
+    ReverseFactoringStorage::set_program(env, &program);
+    emit_debtor_program_opened(env, &program);
+    Ok(program)
+}
+
+/// Close a debtor's program to new invoices. Invoices already confirmed stay
+/// under its rate and fee.
+pub fn close_debtor_program(env: &Env, debtor: &Address) -> Result<(), QuickLendXError> {
+    debtor.require_auth();
+    let mut program = ReverseFactoringStorage::get_program(env, debtor)
+        .ok_or(QuickLendXError::ProgramNotFound)?;
+    program.active = false;
+    ReverseFactoringStorage::set_program(env, &program);
+    emit_debtor_program_closed(env, debtor);
+    Ok(())
+}
+
+/// Record a debtor's confirmation that it owes a supplier's pending invoice,
+/// bringing the invoice under its program. The caller lists the invoice for
+/// funding: the debtor's confirmation stands in for a verifier's review.
+pub fn confirm_supplier_invoice(
+    env: &Env,
+    debtor: &Address,
+    invoice: &Invoice,
+) -> Result<(), QuickLendXError> {
+    debtor.require_auth();
+    let mut program = ReverseFactoringStorage::get_program(env, debtor)
+        .ok_or(QuickLendXError::ProgramNotFound)?;
+    if !program.active {
+        return Err(QuickLendXError::ProgramClosed);
+    }
+    if invoice.business == *debtor {
+        return Err(QuickLendXError::Unauthorized);
+    }
+    if invoice.currency != program.currency {
+        return Err(QuickLendXError::InvalidCurrency);
+    }
+    if ReverseFactoringStorage::get_invoice_debtor(env, &invoice.id).is_some() {
+        return Err(QuickLendXError::InvoiceAlreadyConfirmed);
+    }
+    if invoice.status != InvoiceStatus::Pending {
+        return Err(QuickLendXError::InvalidStatus);
+    }
+
+    storage::set(env, &DataKey::InvoiceDebtor(invoice.id.clone()), debtor);
+    let mut invoices = ReverseFactoringStorage::get_program_invoices(env, debtor);
+    invoices.push_back(invoice.id.clone());
+    storage::set(env, &DataKey::ProgramInvoices(debtor.clone()), &invoices);
+    program.invoice_count += 1;
+    ReverseFactoringStorage::set_program(env, &program);
+    emit_supplier_invoice_confirmed(env, debtor, &invoice.id, &invoice.business);
+    Ok(())
+}
+
+/// Refuse a bid on a program invoice that asks for more than the program's rate
+pub fn check_bid(
+    env: &Env,
+    invoice: &Invoice,
+    bid_amount: i128,
+    expected_return: i128,
+) -> Result<(), QuickLendXError> {
+    let program = match ReverseFactoringStorage::get_invoice_debtor(env, &invoice.id)
+        .and_then(|debtor| ReverseFactoringStorage::get_program(env, &debtor))
+    {
+        Some(program) => program,
+        None => return Ok(()),
+    };
+    let duration = invoice.due_date.saturating_sub(env.ledger().timestamp());
+    if calculate_apr_bps(bid_amount, expected_return, duration) > program.max_apr_bps as i128 {
+        return Err(QuickLendXError::AprLimitExceeded);
+    }
+    Ok(())
+}
+
+/// Fee terms an investment in an invoice is funded under: program invoices
+/// pay the reverse-factoring fee instead of the platform fee, once set
+pub fn fee_terms(env: &Env, invoice_id: &BytesN<32>) -> FeeTerms {
+    let mut terms = FeeTerms::current(env);
+    if ReverseFactoringStorage::get_invoice_debtor(env, invoice_id).is_some() {
+        if let Some(fee_bps) = ReverseFactoringStorage::get_fee_bps(env) {
+            terms.platform_fee_bps = fee_bps;
+        }
+    }
+    terms
+}
+
+/// Set the platform fee charged on program invoices (admin only)
+pub fn set_reverse_factoring_fee(
+    env: &Env,
+    admin: &Address,
+    fee_bps: u32,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    if fee_bps > MAX_PLATFORM_FEE_BPS {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    env.storage()
+        .instance()
+        .set(&symbol_short!("rf_fee"), &fee_bps);
+    record_config_change(env, "reverse_factoring_fee", fee_bps, admin);
+    emit_reverse_factoring_fee_set(env, fee_bps, admin);
+    Ok(())
+}
+
+/// Get every program, open or closed, oldest first
+pub fn get_debtor_programs(env: &Env) -> Vec<DebtorProgram> {
+    let mut programs = Vec::new(env);
+    for debtor in ReverseFactoringStorage::get_debtors(env).iter() {
+        if let Some(program) = ReverseFactoringStorage::get_program(env, &debtor) {
+            programs.push_back(program);
+        }
+    }
+    programs
+}
+
+/// Get a program's invoices that are in `status`, e.g. those open to bids
+pub fn get_program_invoices_by_status(
+    env: &Env,
+    debtor: &Address,
+    status: &InvoiceStatus,
+) -> Vec<BytesN<32>> {
+    let mut invoices = Vec::new(env);
+    for invoice_id in ReverseFactoringStorage::get_program_invoices(env, debtor).iter() {
+        if InvoiceStorage::get_invoice(env, &invoice_id).is_some_and(|inv| inv.status == *status) {
+            invoices.push_back(invoice_id);
+        }
+    }
+    invoices
+}
+
+/// Invite a supplier to join the debtor's open program. The invitation can
+/// be accepted for `valid_for` seconds; inviting again renews an invitation
+/// that has not been accepted.
+pub fn invite_supplier(
+    env: &Env,
+    debtor: &Address,
+    supplier: &Address,
+    valid_for: u64,
+) -> Result<SupplierInvitation, QuickLendXError> {
+    debtor.require_auth();
+    let program = ReverseFactoringStorage::get_program(env, debtor)
+        .ok_or(QuickLendXError::ProgramNotFound)?;
+    if !program.active {
+        return Err(QuickLendXError::ProgramClosed);
+    }
+    if supplier == debtor {
+        return Err(QuickLendXError::InvalidAddress);
+    }
+    if valid_for == 0 || valid_for > MAX_INVITATION_VALIDITY {
+        return Err(QuickLendXError::InvalidTimestamp);
+    }
+    match ReverseFactoringStorage::get_invitation(env, debtor, supplier) {
+        Some(existing) if existing.status == InvitationStatus::Accepted => {
+            return Err(QuickLendXError::InvalidStatus);
+        }
+        Some(_) => {}
+        None => {
+            let mut debtors = ReverseFactoringStorage::get_inviting_debtors(env, supplier);
+            debtors.push_back(debtor.clone());
+            storage::set(env, &DataKey::SupplierInviters(supplier.clone()), &debtors);
+        }
+    }
+
+    let now = env.ledger().timestamp();
+    let invitation = SupplierInvitation {
+        debtor: debtor.clone(),
+        supplier: supplier.clone(),
+        status: InvitationStatus::Pending,
+        invited_at: now,
+        expires_at: now.saturating_add(valid_for),
+        accepted_at: None,
+    };
+    ReverseFactoringStorage::set_invitation(env, &invitation);
+    emit_supplier_invited(env, &invitation);
+    Ok(invitation)
+}
+
+/// Accept a debtor's invitation, joining its program. A supplier without a
+/// current verification submits `kyc_data` with it, and its application is
+/// queued for expedited review.
+pub fn accept_invitation(
+    env: &Env,
+    supplier: &Address,
+    debtor: &Address,
+    kyc_data: String,
+) -> Result<SupplierInvitation, QuickLendXError> {
+    let mut invitation = ReverseFactoringStorage::get_invitation(env, debtor, supplier)
+        .ok_or(QuickLendXError::InvitationNotFound)?;
+    if invitation.status != InvitationStatus::Pending {
+        return Err(QuickLendXError::InvalidStatus);
+    }
+    if env.ledger().timestamp() >= invitation.expires_at {
+        return Err(QuickLendXError::InvitationExpired);
+    }
+
+    let needs_kyc = match BusinessVerificationStorage::get_verification(env, supplier) {
+        Some(verification) => match verification.status {
+            BusinessVerificationStatus::Pending => false,
+            BusinessVerificationStatus::Verified => is_kyc_expired(env, &verification),
+            BusinessVerificationStatus::Rejected => true,
+        },
+        None => true,
+    };
+    if needs_kyc {
+        submit_kyc_application(env, supplier, kyc_data)?;
+    } else {
+        supplier.require_auth();
+    }
+    let mut expedited = ReverseFactoringStorage::get_expedited(env);
+    if !expedited.contains(supplier) {
+        expedited.push_back(supplier.clone());
+        env.storage()
+            .instance()
+            .set(&symbol_short!("rf_kycq"), &expedited);
+    }
+
+    invitation.status = InvitationStatus::Accepted;
+    invitation.accepted_at = Some(env.ledger().timestamp());
+    ReverseFactoringStorage::set_invitation(env, &invitation);
+    let mut suppliers = ReverseFactoringStorage::get_program_suppliers(env, debtor);
+    suppliers.push_back(supplier.clone());
+    storage::set(env, &DataKey::ProgramSuppliers(debtor.clone()), &suppliers);
+    emit_supplier_invitation_accepted(env, &invitation);
+    Ok(invitation)
+}
+
+/// Get a supplier's invitations, in invitation order, expired ones included
+pub fn get_supplier_invitations(env: &Env, supplier: &Address) -> Vec<SupplierInvitation> {
+    let mut invitations = Vec::new(env);
+    for debtor in ReverseFactoringStorage::get_inviting_debtors(env, supplier).iter() {
+        if let Some(invitation) = ReverseFactoringStorage::get_invitation(env, &debtor, supplier) {
+            invitations.push_back(invitation);
+        }
+    }
+    invitations
+}
+
+/// Get the invited suppliers whose KYC applications await review, for
+/// verifiers to take ahead of other applications
+pub fn get_expedited_kyc_applications(env: &Env) -> Vec<Address> {
+    let mut pending = Vec::new(env);
+    for supplier in ReverseFactoringStorage::get_expedited(env).iter() {
+        let is_pending = BusinessVerificationStorage::get_verification(env, &supplier)
+            .is_some_and(|v| v.status == BusinessVerificationStatus::Pending);
+        if is_pending {
+            pending.push_back(supplier);
+        }
+    }
+    pending
+}
+
+/// Guarantee a credit line of `limit` for the debtor's program, or change
+/// its limit (debtor only). Lowering the limit below what is drawn stops
+/// further fundings until settlements bring the line back under it.
+pub fn set_program_credit_line(
+    env: &Env,
+    debtor: &Address,
+    limit: i128,
+) -> Result<ProgramCreditLine, QuickLendXError> {
+    debtor.require_auth();
+    ReverseFactoringStorage::get_program(env, debtor).ok_or(QuickLendXError::ProgramNotFound)?;
+    if limit <= 0 {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    let line = ProgramCreditLine {
+        debtor: debtor.clone(),
+        limit,
+        drawn: ReverseFactoringStorage::get_credit_line(env, debtor).map_or(0, |line| line.drawn),
+        updated_at: env.ledger().timestamp(),
+    };
+    ReverseFactoringStorage::set_credit_line(env, &line);
+    emit_program_credit_line_set(env, &line);
+    Ok(line)
+}
+
+/// The credit line a program invoice draws on, if the invoice is under a
+/// program whose debtor guarantees one
+fn credit_line_for(env: &Env, invoice: &Invoice) -> Option<ProgramCreditLine> {
+    ReverseFactoringStorage::get_invoice_debtor(env, &invoice.id)
+        .and_then(|debtor| ReverseFactoringStorage::get_credit_line(env, &debtor))
+}
+
+/// Refuse funding a program invoice by `amount` if it would overdraw the
+/// program's credit line
+pub fn check_credit_line(
+    env: &Env,
+    invoice: &Invoice,
+    amount: i128,
+) -> Result<(), QuickLendXError> {
+    match credit_line_for(env, invoice) {
+        Some(line) if line.drawn.saturating_add(amount) > line.limit => {
+            Err(QuickLendXError::CreditLimitExceeded)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Draw a program invoice's new funding down from its credit line
+pub fn draw_credit_line(env: &Env, invoice: &Invoice, amount: i128) {
+    if let Some(mut line) = credit_line_for(env, invoice) {
+        line.drawn += amount;
+        ReverseFactoringStorage::set_credit_line(env, &line);
+    }
+}
+
+/// Give a program invoice's funding back to its credit line once the
+/// invoice is repaid or its funding unwound. Call before the invoice's
+/// funded amount is reset.
+pub fn restore_credit_line(env: &Env, invoice: &Invoice) {
+    if let Some(mut line) = credit_line_for(env, invoice) {
+        line.drawn = (line.drawn - invoice.funded_amount).max(0);
+        ReverseFactoringStorage::set_credit_line(env, &line);
+    }
+}
+
+/// Share of a program's credit line in use, in basis points; 0 without a line
+pub fn get_credit_line_utilization_bps(env: &Env, debtor: &Address) -> u32 {
+    ReverseFactoringStorage::get_credit_line(env, debtor)
+        .map_or(0, |line| (line.drawn * 10_000 / line.limit) as u32)
+}
+
+/// Bring an invoice that replaces a program invoice under the same program,
+/// along with the funding it draws on the program's credit line
+pub fn carry_over(env: &Env, old_invoice_id: &BytesN<32>, new_invoice_id: &BytesN<32>) {
+    let Some(debtor) = ReverseFactoringStorage::get_invoice_debtor(env, old_invoice_id) else {
+        return;
+    };
+    storage::set(
+        env,
+        &DataKey::InvoiceDebtor(new_invoice_id.clone()),
+        &debtor,
+    );
+    let mut invoices = ReverseFactoringStorage::get_program_invoices(env, &debtor);
+    invoices.push_back(new_invoice_id.clone());
+    storage::set(env, &DataKey::ProgramInvoices(debtor.clone()), &invoices);
+    if let Some(mut program) = ReverseFactoringStorage::get_program(env, &debtor) {
+        program.invoice_count += 1;
+        ReverseFactoringStorage::set_program(env, &program);
+    }
+}
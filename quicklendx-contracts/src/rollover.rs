@@ -0,0 +1,175 @@
+use crate::audit::{log_invoice_created, log_invoice_status_change};
+use crate::errors::QuickLendXError;
+use crate::events::{emit_invoice_rolled_over, emit_rollover_approved, emit_rollover_proposed};
+use crate::investment::InvestmentStorage;
+use crate::invoice::{Invoice, InvoiceStatus, InvoiceStorage};
+use crate::maturity::{record_funding, release_funding};
+use crate::payments::EscrowStorage;
+use crate::reverse_factoring::carry_over;
+use crate::settlement::get_settlement_progress;
+use crate::storage::{self, DataKey};
+use crate::tranche;
+use crate::verification::check_tier_amount;
+use soroban_sdk::{contracttype, Address, BytesN, Env, Vec};
+
+/// A business's proposal to roll an unpaid funded invoice into a new one
+/// with a later due date and an adjusted amount. It goes through once every
+/// investor in the invoice has approved it.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RolloverProposal {
+    pub invoice_id: BytesN<32>,
+    pub new_due_date: u64,
+    pub new_amount: i128, // What the business will owe on the new invoice
+    pub proposed_at: u64,
+    pub approvals: Vec<Address>,            // Investors who approved
+    pub new_invoice_id: Option<BytesN<32>>, // Set once the rollover went through
+}
+
+/// Get the rollover proposed for an invoice, if any
+pub fn get_rollover(env: &Env, invoice_id: &BytesN<32>) -> Option<RolloverProposal> {
+    storage::get(env, &DataKey::RolloverProposal(invoice_id.clone()))
+}
+
+/// Propose rolling a funded invoice into a new invoice due at
+/// `new_due_date` for `new_amount` (business only). A new proposal replaces
+/// one still awaiting approval, clearing its approvals.
+pub fn propose_rollover(
+    env: &Env,
+    business: &Address,
+    invoice_id: &BytesN<32>,
+    new_due_date: u64,
+    new_amount: i128,
+) -> Result<RolloverProposal, QuickLendXError> {
+    business.require_auth();
+    let invoice = get_rollable_invoice(env, invoice_id)?;
+    if invoice.business != *business {
+        return Err(QuickLendXError::NotBusinessOwner);
+    }
+    if new_due_date <= invoice.due_date.max(env.ledger().timestamp()) {
+        return Err(QuickLendXError::InvalidTimestamp);
+    }
+    // Investors are owed at least what they put in
+    if new_amount < invoice.funded_amount {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    // The new invoice is held to the business's tier cap like any upload
+    check_tier_amount(
+        env,
+        business,
+        &invoice.currency,
+        new_amount,
+        &invoice.invoice_hash,
+    )?;
+
+    let proposal = RolloverProposal {
+        invoice_id: invoice_id.clone(),
+        new_due_date,
+        new_amount,
+        proposed_at: env.ledger().timestamp(),
+        approvals: Vec::new(env),
+        new_invoice_id: None,
+    };
+    storage::set(
+        env,
+        &DataKey::RolloverProposal(invoice_id.clone()),
+        &proposal,
+    );
+    emit_rollover_proposed(env, &proposal);
+    Ok(proposal)
+}
+
+/// Approve the rollover proposed for an invoice (investor in the invoice
+/// only). The last approval closes the invoice and creates the new one.
+pub fn approve_rollover(
+    env: &Env,
+    investor: &Address,
+    invoice_id: &BytesN<32>,
+) -> Result<RolloverProposal, QuickLendXError> {
+    investor.require_auth();
+    let invoice = get_rollable_invoice(env, invoice_id)?;
+    let mut proposal = get_rollover(env, invoice_id)
+        .filter(|proposal| proposal.new_invoice_id.is_none())
+        .ok_or(QuickLendXError::RolloverNotFound)?;
+    let contributions = InvestmentStorage::get_funding_contributions(env, invoice_id);
+    if !contributions
+        .iter()
+        .any(|contribution| contribution.investor == *investor)
+    {
+        return Err(QuickLendXError::NotInvestor);
+    }
+    if proposal.approvals.contains(investor) {
+        return Err(QuickLendXError::AlreadyVoted);
+    }
+
+    proposal.approvals.push_back(investor.clone());
+    emit_rollover_approved(env, invoice_id, investor);
+    let approved = contributions
+        .iter()
+        .all(|contribution| proposal.approvals.contains(&contribution.investor));
+    if approved {
+        proposal.new_invoice_id = Some(roll_over(env, invoice, &proposal));
+    }
+    storage::set(
+        env,
+        &DataKey::RolloverProposal(invoice_id.clone()),
+        &proposal,
+    );
+    Ok(proposal)
+}
+
+/// Close `old` as Refinanced and create its funded replacement, moving the
+/// investments and escrows across. Returns the new invoice's id.
+fn roll_over(env: &Env, mut old: Invoice, proposal: &RolloverProposal) -> BytesN<32> {
+    let mut new = Invoice::new(
+        env,
+        old.business.clone(),
+        proposal.new_amount,
+        old.currency.clone(),
+        proposal.new_due_date,
+        old.description.clone(),
+        None,
+    );
+    new.category = old.category.clone();
+    new.tags = old.tags.clone();
+    new.status = InvoiceStatus::Funded;
+    new.funded_amount = old.funded_amount;
+    new.funded_at = Some(env.ledger().timestamp());
+    new.investor = old.investor.clone();
+    new.investors = old.investors.clone();
+    InvoiceStorage::store_invoice(env, &new);
+    log_invoice_created(env, &new);
+    InvestmentStorage::move_investments(env, &old.id, &new.id);
+    EscrowStorage::move_escrows(env, &old.id, &new.id);
+    carry_over(env, &old.id, &new.id);
+    tranche::carry_over(env, &old.id, &new.id);
+    record_funding(env, &new, new.funded_amount);
+
+    InvoiceStorage::remove_from_status_invoices(env, &InvoiceStatus::Funded, &old.id);
+    release_funding(env, &old);
+    old.status = InvoiceStatus::Refinanced;
+    InvoiceStorage::update_invoice(env, &old);
+    InvoiceStorage::add_to_status_invoices(env, &InvoiceStatus::Refinanced, &old.id);
+    log_invoice_status_change(
+        env,
+        old.id.clone(),
+        old.business.clone(),
+        InvoiceStatus::Funded,
+        InvoiceStatus::Refinanced,
+    );
+    emit_invoice_rolled_over(env, &old, &new);
+    new.id
+}
+
+/// Load a funded invoice that is not part way through settlement
+fn get_rollable_invoice(env: &Env, invoice_id: &BytesN<32>) -> Result<Invoice, QuickLendXError> {
+    let invoice =
+        InvoiceStorage::get_invoice(env, invoice_id).ok_or(QuickLendXError::InvoiceNotFound)?;
+    if invoice.status != InvoiceStatus::Funded {
+        return Err(QuickLendXError::InvalidStatus);
+    }
+    if get_settlement_progress(env, invoice_id).is_some() {
+        return Err(QuickLendXError::OperationNotAllowed);
+    }
+    Ok(invoice)
+}
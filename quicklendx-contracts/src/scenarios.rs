@@ -0,0 +1,156 @@
+//! Canonical contract states for integration partners. With the `scenarios`
+//! feature, SDKs and frontends can seed a test environment with the same data
+//! on every run and test against known ids and statuses.
+
+use crate::QuickLendXContractClient;
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::{Address, BytesN, Env, String, Vec};
+
+/// Ledger time every scenario starts at
+pub const SCENARIO_START: u64 = 1_700_000_000;
+
+pub use crate::invoice::InvoiceStatus;
+
+const DAY: u64 = 86_400;
+const BUSINESSES: u32 = 5;
+const INVESTORS: u32 = 5;
+
+/// How many invoices the marketplace scenario leaves in each status, 50 in all
+pub const MARKETPLACE_INVOICES: [(InvoiceStatus, u32); 7] = [
+    (InvoiceStatus::Pending, 10),
+    (InvoiceStatus::Verified, 14),
+    (InvoiceStatus::Funded, 12),
+    (InvoiceStatus::Paid, 10),
+    (InvoiceStatus::Defaulted, 1),
+    (InvoiceStatus::Cancelled, 2),
+    (InvoiceStatus::Expired, 1),
+];
+
+/// The parties and invoices of a seeded marketplace
+#[derive(Clone, Debug)]
+pub struct Marketplace {
+    pub admin: Address,
+    pub currency: Address,
+    pub businesses: Vec<Address>,
+    pub investors: Vec<Address>,
+    pub invoices: Vec<BytesN<32>>,  // In upload order
+    pub open_bids: Vec<BytesN<32>>, // On verified invoices
+    pub defaulted: BytesN<32>,
+    pub disputed: BytesN<32>, // A funded invoice its investor disputes
+}
+
+/// Seed a freshly registered contract with a marketplace of 50 invoices
+/// across every status, as counted in `MARKETPLACE_INVOICES`. Half the
+/// verified invoices have an open bid, one funded invoice is disputed and one
+/// invoice has defaulted. Mocks all auths and leaves the ledger time at
+/// `SCENARIO_START` plus 30 days. Seeding two fresh environments gives the
+/// same ids.
+pub fn seed_marketplace(env: &Env, client: &QuickLendXContractClient) -> Marketplace {
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = SCENARIO_START);
+
+    let admin = Address::generate(env);
+    client.initialize(&admin);
+    let currency = Address::generate(env);
+    let mut businesses = Vec::new(env);
+    for _ in 0..BUSINESSES {
+        let business = Address::generate(env);
+        client.submit_kyc_application(&business, &String::from_str(env, "KYC data"));
+        client.verify_business(&admin, &business);
+        businesses.push_back(business);
+    }
+    let mut investors = Vec::new(env);
+    for _ in 0..INVESTORS {
+        investors.push_back(Address::generate(env));
+    }
+
+    let mut invoices = Vec::new(env);
+    let mut open_bids = Vec::new(env);
+    let mut funded = Vec::new(env);
+    let mut overdue = None;
+    for (status, count) in MARKETPLACE_INVOICES.iter() {
+        for _ in 0..*count {
+            let n = invoices.len();
+            let business = businesses.get(n % BUSINESSES).unwrap();
+            let investor = investors.get(n % INVESTORS).unwrap();
+            let amount = 1_000 + n as i128 * 250;
+            // The invoice that defaults falls due first; the rest stay current
+            let due_in = if *status == InvoiceStatus::Defaulted {
+                DAY
+            } else {
+                90 * DAY + n as u64 * DAY
+            };
+            let invoice_id = client.upload_invoice(
+                &business,
+                &amount,
+                &currency,
+                &(SCENARIO_START + due_in),
+                &String::from_str(env, "Scenario invoice"),
+            );
+            invoices.push_back(invoice_id.clone());
+            match status {
+                InvoiceStatus::Pending => continue,
+                InvoiceStatus::Cancelled => {
+                    client.cancel_invoice(&business, &invoice_id);
+                    continue;
+                }
+                InvoiceStatus::Expired => {
+                    client.set_funding_deadline(
+                        &business,
+                        &invoice_id,
+                        &Some(SCENARIO_START + DAY),
+                    );
+                }
+                _ => {}
+            }
+            client.verify_invoice(&admin, &invoice_id);
+            if *status == InvoiceStatus::Verified && n % 2 == 1 {
+                continue;
+            }
+            let expected_return = amount * 110 / 100;
+            let bid_id = client.place_bid(&investor, &invoice_id, &amount, &expected_return);
+            match status {
+                InvoiceStatus::Verified => {
+                    open_bids.push_back(bid_id);
+                    continue;
+                }
+                // Its bid is rejected and refunded when it expires
+                InvoiceStatus::Expired => continue,
+                _ => {}
+            }
+            client.accept_bid(&business, &invoice_id, &bid_id);
+            match status {
+                InvoiceStatus::Paid => {
+                    client.settle_invoice(&business, &invoice_id, &expected_return)
+                }
+                InvoiceStatus::Defaulted => overdue = Some(invoice_id.clone()),
+                _ => funded.push_back((invoice_id.clone(), investor)),
+            }
+        }
+    }
+    let (disputed, disputed_by) = funded.get(0).unwrap();
+    client.open_dispute(
+        &disputed_by,
+        &disputed,
+        &String::from_str(env, "Goods not delivered"),
+    );
+
+    // Past the overdue invoice's due date and grace period, and the other
+    // expiring invoice's funding deadline
+    let defaulted = overdue.unwrap();
+    env.ledger()
+        .with_mut(|li| li.timestamp = SCENARIO_START + 30 * DAY);
+    client.handle_default(&admin, &defaulted);
+    client.expire_invoices();
+
+    Marketplace {
+        admin,
+        currency,
+        businesses,
+        investors,
+        invoices,
+        open_bids,
+        defaulted,
+        disputed,
+    }
+}
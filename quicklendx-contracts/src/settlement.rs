@@ -1,59 +1,757 @@
-use soroban_sdk::{Address, BytesN, Env};
+use crate::analytics::{record_investor_return, record_settled, record_settled_investment};
+use crate::audit::{log_invoice_status_change, log_payment_processed};
+use crate::balances::credit_balance;
+use crate::compliance::{check_not_blacklisted, record_settlement_returns};
+use crate::config::ConfigStorage;
 use crate::errors::QuickLendXError;
-use crate::events::emit_invoice_settled;
-use crate::investment::{Investment, InvestmentStatus, InvestmentStorage};
+use crate::events::{
+    emit_investment_completed, emit_invoice_settled, emit_partial_payment,
+    emit_program_invoice_settled, emit_settlement_claimed, emit_settlement_progress,
+};
+use crate::insurance::{credit_settlement_levy, publish_risk_score};
+use crate::investment::{pro_rata_shares, Investment, InvestmentStatus, InvestmentStorage};
 use crate::invoice::{Invoice, InvoiceStatus, InvoiceStorage};
+use crate::jurisdiction::cap_investor_return;
+use crate::maturity::release_funding;
+use crate::observers::{notify_observers, LifecycleEvent};
 use crate::payments::transfer_funds;
+use crate::penalty::get_amount_due;
 use crate::profits::calculate_profit;
+use crate::reinvest::ReinvestStorage;
+use crate::reverse_factoring::{restore_credit_line, ReverseFactoringStorage};
+use crate::stop_loss::record_closed;
+use crate::storage::{self, DataKey};
+use crate::terms::TermsStorage;
+use crate::tranche::{by_seniority, waterfall_shares, TrancheStorage};
+use crate::treasury::credit_fees;
+use soroban_sdk::{contracttype, symbol_short, Address, BytesN, Env, String, Vec};
 
-pub fn settle_invoice(
+/// What a settlement transfer pays for
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SettlementTransferKind {
+    InvestorReturn,
+    PlatformFee,
+    InsuranceLevy,    // Share of investor returns diverted to the insurance fund
+    SupplierResidual, // What a program debtor pays beyond investors' dues
+}
+
+/// A single token transfer performed during settlement
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SettlementTransfer {
+    pub from: Address,
+    pub to: Address,
+    pub amount: i128,
+    pub currency: Address,
+    pub kind: SettlementTransferKind,
+}
+
+/// The exact transfers settling an invoice will perform, in execution order
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SettlementPlan {
+    pub invoice_id: BytesN<32>,
+    pub payment_amount: i128, // Payment less returns above jurisdiction caps
+    pub total_investor_return: i128,
+    pub total_platform_fee: i128,
+    pub total_insurance_levy: i128,
+    pub transfers: Vec<SettlementTransfer>,
+}
+
+/// A settlement paid out over several calls. The business pays the whole
+/// amount into the contract up front; the plan's transfers are then made
+/// from the contract, `next_transfer` marking how far payouts have got.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SettlementProgress {
+    pub business: Address,
+    pub plan: SettlementPlan,
+    pub next_transfer: u32,
+    pub started_at: u64,
+}
+
+impl SettlementProgress {
+    pub fn is_complete(&self) -> bool {
+        self.next_transfer >= self.plan.transfers.len()
+    }
+}
+
+/// Get the settlement being paid out for an invoice, if any
+pub fn get_settlement_progress(env: &Env, invoice_id: &BytesN<32>) -> Option<SettlementProgress> {
+    storage::get(env, &DataKey::SettlementProgress(invoice_id.clone()))
+}
+
+fn set_settlement_progress(env: &Env, progress: &SettlementProgress) {
+    storage::set(
+        env,
+        &DataKey::SettlementProgress(progress.plan.invoice_id.clone()),
+        progress,
+    );
+}
+
+fn clear_settlement_progress(env: &Env, invoice_id: &BytesN<32>) {
+    storage::remove(env, &DataKey::SettlementProgress(invoice_id.clone()));
+}
+
+/// Build the settlement plan for a funded invoice without changing any state
+pub fn build_settlement_plan(
     env: &Env,
     invoice_id: &BytesN<32>,
     payment_amount: i128,
-    platform: &Address,
-    platform_fee_bps: i128,
-) -> Result<(), QuickLendXError> {
+) -> Result<SettlementPlan, QuickLendXError> {
+    let (plan, _) = prepare_settlement(env, invoice_id, payment_amount)?;
+    Ok(plan)
+}
+
+fn prepare_settlement(
+    env: &Env,
+    invoice_id: &BytesN<32>,
+    payment_amount: i128,
+) -> Result<(SettlementPlan, Vec<Investment>), QuickLendXError> {
     // Get and validate invoice
-    let mut invoice = InvoiceStorage::get_invoice(env, invoice_id)
-        .ok_or(QuickLendXError::InvoiceNotFound)?;
-    
+    let invoice =
+        InvoiceStorage::get_invoice(env, invoice_id).ok_or(QuickLendXError::InvoiceNotFound)?;
+
     if invoice.status != InvoiceStatus::Funded {
         return Err(QuickLendXError::InvalidStatus);
     }
-    
-    // Get investor from invoice
-    let investor = invoice
-        .investor
-        .as_ref()
-        .ok_or(QuickLendXError::NotInvestor)?;
-    
-    // Get investment details
-    let investment = InvestmentStorage::get_investment(env, invoice_id)
-        .ok_or(QuickLendXError::StorageKeyNotFound)?;
-    
-    // Calculate profit and platform fee
-    let (investor_return, platform_fee) =
-        calculate_profit(investment.amount, payment_amount, platform_fee_bps);
-    
-    // Transfer funds to investor and platform
-    let investor_paid = transfer_funds(env, &invoice.business, investor, investor_return);
-    let platform_paid = transfer_funds(env, &invoice.business, platform, platform_fee);
-    
-    if !investor_paid || !platform_paid {
+
+    // Late charges are part of what is owed and cannot be skipped
+    if payment_amount < get_amount_due(env, invoice_id)?.total {
+        return Err(QuickLendXError::InsufficientFunds);
+    }
+
+    let structure = TrancheStorage::get_structure(env, invoice_id);
+    let mut investments = active_investments(env, invoice_id);
+    if investments.is_empty() {
+        return Err(QuickLendXError::NotInvestor);
+    }
+    if structure.is_some() {
+        investments = by_seniority(env, &investments);
+    }
+
+    // Split the payment pro rata by funded amount, or down the waterfall of a
+    // tranched invoice
+    let (shares, residual) = match &structure {
+        Some(structure) => waterfall_shares(env, structure, &investments, payment_amount),
+        None => (pro_rata_shares(env, &investments, payment_amount), 0),
+    };
+    // Fees are charged at the rates in force when each investment was funded;
+    // only the fee recipient is current
+    let fee_recipient = ConfigStorage::get_platform_fee(env).recipient;
+
+    let mut plan = SettlementPlan {
+        invoice_id: invoice_id.clone(),
+        payment_amount,
+        total_investor_return: 0,
+        total_platform_fee: 0,
+        total_insurance_levy: 0,
+        transfers: Vec::new(env),
+    };
+    for (investment, share) in investments.iter().zip(shares.iter()) {
+        let (gross_return, platform_fee, levy_bps) = if structure.is_some() {
+            // Tranches are owed their returns in full; the platform is paid
+            // last, from what is left
+            (share, 0, 0)
+        } else {
+            // Calculate profit and platform fee on this investor's share
+            let terms = investment.fee_terms(env);
+            let (investor_return, platform_fee) =
+                calculate_profit(investment.amount, share, terms.platform_fee_bps as i128);
+            (investor_return, platform_fee, terms.settlement_levy_bps)
+        };
+        // Investor returns may not exceed the business jurisdiction's APR
+        // cap; what is above it is not collected
+        let capped = cap_investor_return(env, &invoice, investment.amount, gross_return);
+        plan.payment_amount -= gross_return - capped;
+        // The insurance levy comes out of the investor's return
+        let levy = capped * levy_bps as i128 / 10_000;
+        let investor_return = capped - levy;
+
+        // Investor is paid first, then the platform, then the insurance fund;
+        // empty transfers are skipped
+        for (to, amount, kind) in [
+            (
+                investment.investor.clone(),
+                investor_return,
+                SettlementTransferKind::InvestorReturn,
+            ),
+            (
+                fee_recipient.clone(),
+                platform_fee,
+                SettlementTransferKind::PlatformFee,
+            ),
+            (
+                env.current_contract_address(),
+                levy,
+                SettlementTransferKind::InsuranceLevy,
+            ),
+        ] {
+            if amount > 0 {
+                plan.transfers.push_back(SettlementTransfer {
+                    from: invoice.business.clone(),
+                    to,
+                    amount,
+                    currency: invoice.currency.clone(),
+                    kind,
+                });
+            }
+        }
+
+        plan.total_investor_return += investor_return;
+        plan.total_platform_fee += platform_fee;
+        plan.total_insurance_levy += levy;
+    }
+    if residual > 0 {
+        plan.transfers.push_back(SettlementTransfer {
+            from: invoice.business.clone(),
+            to: fee_recipient,
+            amount: residual,
+            currency: invoice.currency.clone(),
+            kind: SettlementTransferKind::PlatformFee,
+        });
+        plan.total_platform_fee += residual;
+    }
+
+    Ok((plan, investments))
+}
+
+/// Collect every investor's open position in the invoice
+fn active_investments(env: &Env, invoice_id: &BytesN<32>) -> Vec<Investment> {
+    let mut investments: Vec<Investment> = Vec::new(env);
+    for investment_id in InvestmentStorage::get_investments_for_invoice(env, invoice_id).iter() {
+        if let Some(investment) = InvestmentStorage::get_investment(env, &investment_id) {
+            if investment.status == InvestmentStatus::Active {
+                investments.push_back(investment);
+            }
+        }
+    }
+    investments
+}
+
+/// Get how much of a funded invoice has been repaid through installments
+pub fn get_amount_paid(env: &Env, invoice_id: &BytesN<32>) -> i128 {
+    storage::get(env, &DataKey::AmountPaid(invoice_id.clone())).unwrap_or(0)
+}
+
+fn set_amount_paid(env: &Env, invoice_id: &BytesN<32>, amount: i128) {
+    storage::set(env, &DataKey::AmountPaid(invoice_id.clone()), &amount);
+}
+
+/// Get what installments have paid each settlement recipient of an invoice
+/// so far, one entry per recipient and kind of transfer
+fn get_installment_payouts(env: &Env, invoice_id: &BytesN<32>) -> Vec<SettlementTransfer> {
+    storage::get(env, &DataKey::InstallmentPayouts(invoice_id.clone()))
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+/// Merge transfers to the same recipient for the same purpose, such as the
+/// platform fees charged on each investment
+fn by_recipient(env: &Env, transfers: &Vec<SettlementTransfer>) -> Vec<SettlementTransfer> {
+    let mut merged: Vec<SettlementTransfer> = Vec::new(env);
+    for transfer in transfers.iter() {
+        match recipient_index(&merged, &transfer) {
+            Some(index) => {
+                let mut total = merged.get(index as u32).unwrap();
+                total.amount += transfer.amount;
+                merged.set(index as u32, total);
+            }
+            None => merged.push_back(transfer),
+        }
+    }
+    merged
+}
+
+/// Position of the entry paying the same recipient for the same purpose
+fn recipient_index(
+    transfers: &Vec<SettlementTransfer>,
+    transfer: &SettlementTransfer,
+) -> Option<usize> {
+    transfers
+        .iter()
+        .position(|other| other.to == transfer.to && other.kind == transfer.kind)
+}
+
+/// Load an invoice on behalf of the business that owns it
+fn get_owned_invoice(
+    env: &Env,
+    business: &Address,
+    invoice_id: &BytesN<32>,
+) -> Result<Invoice, QuickLendXError> {
+    // Only the business owner can pay their invoice
+    business.require_auth();
+    let invoice =
+        InvoiceStorage::get_invoice(env, invoice_id).ok_or(QuickLendXError::InvoiceNotFound)?;
+    if invoice.business != *business {
+        return Err(QuickLendXError::NotBusinessOwner);
+    }
+    // No settlement money moves to or from a blacklisted party
+    check_not_blacklisted(env, business)?;
+    for investor in invoice.investors.iter() {
+        check_not_blacklisted(env, &investor)?;
+    }
+    Ok(invoice)
+}
+
+fn execute_transfers(
+    env: &Env,
+    transfers: &Vec<SettlementTransfer>,
+) -> Result<(), QuickLendXError> {
+    for transfer in transfers.iter() {
+        execute_transfer(env, &transfer, &transfer.from)?;
+    }
+    Ok(())
+}
+
+/// Make one settlement transfer with funds taken from `payer`
+fn execute_transfer(
+    env: &Env,
+    transfer: &SettlementTransfer,
+    payer: &Address,
+) -> Result<(), QuickLendXError> {
+    // Returns of investors who reinvest stay in the contract as balance
+    let reinvested = transfer.kind == SettlementTransferKind::InvestorReturn
+        && ReinvestStorage::is_enabled(env, &transfer.to);
+    let to = if reinvested {
+        env.current_contract_address()
+    } else {
+        transfer.to.clone()
+    };
+    // Money already held by the recipient does not move
+    if *payer != to && !transfer_funds(env, payer, &to, transfer.amount) {
         return Err(QuickLendXError::InsufficientFunds);
     }
-    
+    match transfer.kind {
+        SettlementTransferKind::InvestorReturn if reinvested => {
+            credit_balance(env, &transfer.to, &transfer.currency, transfer.amount)
+        }
+        // Fees paid to the contract itself are held in the treasury
+        SettlementTransferKind::PlatformFee if transfer.to == env.current_contract_address() => {
+            credit_fees(env, &transfer.currency, transfer.amount)
+        }
+        SettlementTransferKind::InsuranceLevy => {
+            credit_settlement_levy(env, &transfer.currency, transfer.amount, &transfer.from)
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Close out investments and mark the invoice Paid once it is fully repaid
+fn complete_settlement(
+    env: &Env,
+    business: &Address,
+    mut invoice: Invoice,
+    investments: &Vec<Investment>,
+    plan: &SettlementPlan,
+) {
+    // Update investment status
+    for investment in investments.iter() {
+        let mut updated_investment = investment;
+        updated_investment.status = InvestmentStatus::Completed;
+        InvestmentStorage::update_investment(env, &updated_investment);
+        record_closed(env, &updated_investment, false);
+        record_settled_investment(env, &updated_investment);
+        emit_investment_completed(env, &updated_investment);
+    }
+    for transfer in plan.transfers.iter() {
+        if transfer.kind == SettlementTransferKind::InvestorReturn {
+            record_investor_return(env, &transfer.to, transfer.amount);
+        }
+    }
+
     // Update invoice status
+    InvoiceStorage::remove_from_status_invoices(env, &InvoiceStatus::Funded, &invoice.id);
+    release_funding(env, &invoice);
+    restore_credit_line(env, &invoice);
     invoice.mark_as_paid(env.ledger().timestamp());
     InvoiceStorage::update_invoice(env, &invoice);
-    
-    // Update investment status
-    let mut updated_investment = investment;
-    updated_investment.status = InvestmentStatus::Completed;
-    InvestmentStorage::update_investment(env, &updated_investment);
-    
-    // Emit settlement event
-    emit_invoice_settled(env, &invoice, investor_return, platform_fee);
-    
+    InvoiceStorage::add_to_status_invoices(env, &InvoiceStatus::Paid, &invoice.id);
+    record_settlement_returns(env, &invoice);
+    record_settled(env, &invoice, plan.payment_amount);
+    publish_risk_score(env, &invoice.business, symbol_short!("paid"));
+
+    log_invoice_status_change(
+        env,
+        invoice.id.clone(),
+        business.clone(),
+        InvoiceStatus::Funded,
+        InvoiceStatus::Paid,
+    );
+
+    // Emit settlement event, naming the terms the invoice was funded under
+    emit_invoice_settled(
+        env,
+        &invoice,
+        plan.total_investor_return,
+        plan.total_platform_fee,
+        business,
+        &TermsStorage::get_terms_hash(env, &invoice.id),
+    );
+    notify_observers(env, &invoice.id, LifecycleEvent::Settled);
+}
+
+pub fn settle_invoice(
+    env: &Env,
+    business: &Address,
+    invoice_id: &BytesN<32>,
+    payment_amount: i128,
+) -> Result<(), QuickLendXError> {
+    let invoice = get_owned_invoice(env, business, invoice_id)?;
+    // Invoices being repaid in installments or in batches are finished the
+    // same way
+    if get_amount_paid(env, invoice_id) > 0 || get_settlement_progress(env, invoice_id).is_some() {
+        return Err(QuickLendXError::OperationNotAllowed);
+    }
+
+    let (plan, investments) = prepare_settlement(env, invoice_id, payment_amount)?;
+
+    // Transfer funds to investors and platform
+    execute_transfers(env, &plan.transfers)?;
+
+    log_payment_processed(
+        env,
+        invoice_id.clone(),
+        business.clone(),
+        plan.payment_amount,
+        String::from_str(env, "settlement"),
+    );
+    complete_settlement(env, business, invoice, &investments, &plan);
+
+    Ok(())
+}
+
+/// Pay part of what is owed on a funded invoice. Each installment is split
+/// between investors and the platform in the same proportions as a full
+/// settlement; the invoice becomes Paid once the amount due is covered.
+pub fn record_partial_payment(
+    env: &Env,
+    business: &Address,
+    invoice_id: &BytesN<32>,
+    amount: i128,
+) -> Result<(), QuickLendXError> {
+    let invoice = get_owned_invoice(env, business, invoice_id)?;
+    if amount <= 0 {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    if get_settlement_progress(env, invoice_id).is_some() {
+        return Err(QuickLendXError::OperationNotAllowed);
+    }
+    let total_due = get_amount_due(env, invoice_id)?.total;
+    let paid_before = get_amount_paid(env, invoice_id);
+    if amount > total_due - paid_before {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    let paid_after = paid_before + amount;
+
+    // Each recipient receives its cumulative pro rata share of everything paid
+    // so far, less what it was actually paid by earlier installments. Late
+    // charges may have grown the amount due since then; rounding never
+    // accumulates, and the final installment completes every share exactly.
+    let (plan, investments) = prepare_settlement(env, invoice_id, total_due)?;
+    let mut payouts = get_installment_payouts(env, invoice_id);
+    let mut transfers = Vec::new(env);
+    for transfer in by_recipient(env, &plan.transfers).iter() {
+        let target = transfer.amount * paid_after / total_due;
+        let index = recipient_index(&payouts, &transfer);
+        let paid = index.map_or(0, |index| payouts.get(index as u32).unwrap().amount);
+        if target <= paid {
+            continue;
+        }
+        transfers.push_back(SettlementTransfer {
+            amount: target - paid,
+            ..transfer.clone()
+        });
+        let cumulative = SettlementTransfer {
+            amount: target,
+            ..transfer
+        };
+        match index {
+            Some(index) => payouts.set(index as u32, cumulative),
+            None => payouts.push_back(cumulative),
+        }
+    }
+    execute_transfers(env, &transfers)?;
+    set_amount_paid(env, invoice_id, paid_after);
+
+    log_payment_processed(
+        env,
+        invoice_id.clone(),
+        business.clone(),
+        amount,
+        String::from_str(env, "installment"),
+    );
+    emit_partial_payment(env, invoice_id, amount, paid_after, total_due - paid_after);
+
+    if paid_after >= total_due {
+        storage::remove(env, &DataKey::InstallmentPayouts(invoice_id.clone()));
+        complete_settlement(env, business, invoice, &investments, &plan);
+    } else {
+        storage::set(
+            env,
+            &DataKey::InstallmentPayouts(invoice_id.clone()),
+            &payouts,
+        );
+    }
+    Ok(())
+}
+
+/// Take everything a settlement plan pays out from the payer into the
+/// contract in a single transfer
+fn collect_settlement(
+    env: &Env,
+    payer: &Address,
+    plan: &SettlementPlan,
+) -> Result<(), QuickLendXError> {
+    let mut total = 0i128;
+    for transfer in plan.transfers.iter() {
+        total += transfer.amount;
+    }
+    if !transfer_funds(env, payer, &env.current_contract_address(), total) {
+        return Err(QuickLendXError::InsufficientFunds);
+    }
+    log_payment_processed(
+        env,
+        plan.invoice_id.clone(),
+        payer.clone(),
+        plan.payment_amount,
+        String::from_str(env, "settlement"),
+    );
+    Ok(())
+}
+
+/// Start settling an invoice whose payouts may not fit in one transaction
+/// (business owner only). The business pays the whole settlement into the
+/// contract now; `process_settlement` then pays the parties in batches.
+pub fn begin_settlement(
+    env: &Env,
+    business: &Address,
+    invoice_id: &BytesN<32>,
+    payment_amount: i128,
+) -> Result<SettlementProgress, QuickLendXError> {
+    get_owned_invoice(env, business, invoice_id)?;
+    if get_amount_paid(env, invoice_id) > 0 || get_settlement_progress(env, invoice_id).is_some() {
+        return Err(QuickLendXError::OperationNotAllowed);
+    }
+    let (plan, _) = prepare_settlement(env, invoice_id, payment_amount)?;
+    collect_settlement(env, business, &plan)?;
+
+    let progress = SettlementProgress {
+        business: business.clone(),
+        plan,
+        next_transfer: 0,
+        started_at: env.ledger().timestamp(),
+    };
+    set_settlement_progress(env, &progress);
+    emit_settlement_progress(env, &progress);
+    Ok(progress)
+}
+
+/// Make up to `max_payouts` of a started settlement's remaining transfers.
+/// Anyone may call this. The invoice is marked Paid by the call that makes
+/// the last transfer.
+pub fn process_settlement(
+    env: &Env,
+    invoice_id: &BytesN<32>,
+    max_payouts: u32,
+) -> Result<SettlementProgress, QuickLendXError> {
+    if max_payouts == 0 {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    let mut progress =
+        get_settlement_progress(env, invoice_id).ok_or(QuickLendXError::StorageKeyNotFound)?;
+    let contract = env.current_contract_address();
+    let end = progress
+        .next_transfer
+        .saturating_add(max_payouts)
+        .min(progress.plan.transfers.len());
+    for index in progress.next_transfer..end {
+        let transfer = progress.plan.transfers.get(index).unwrap();
+        execute_transfer(env, &transfer, &contract)?;
+    }
+    progress.next_transfer = end;
+    emit_settlement_progress(env, &progress);
+
+    if progress.is_complete() {
+        clear_settlement_progress(env, invoice_id);
+        let invoice =
+            InvoiceStorage::get_invoice(env, invoice_id).ok_or(QuickLendXError::InvoiceNotFound)?;
+        let investments = active_investments(env, invoice_id);
+        complete_settlement(
+            env,
+            &progress.business,
+            invoice,
+            &investments,
+            &progress.plan,
+        );
+    } else {
+        set_settlement_progress(env, &progress);
+    }
+    Ok(progress)
+}
+
+/// Get what a payee can still claim from an invoice settled with claims
+pub fn get_claimable_settlement(env: &Env, invoice_id: &BytesN<32>, payee: &Address) -> i128 {
+    storage::get(
+        env,
+        &DataKey::ClaimableSettlement(invoice_id.clone(), payee.clone()),
+    )
+    .unwrap_or(0)
+}
+
+fn set_claimable_settlement(env: &Env, invoice_id: &BytesN<32>, payee: &Address, amount: i128) {
+    let key = DataKey::ClaimableSettlement(invoice_id.clone(), payee.clone());
+    if amount > 0 {
+        storage::set(env, &key, &amount);
+    } else {
+        storage::remove(env, &key);
+    }
+}
+
+/// Settle an invoice without pushing payments (business owner only). The
+/// business pays the whole settlement into the contract, the invoice becomes
+/// Paid, and each payee is credited a balance to pull with
+/// `claim_settlement`. A payee that cannot receive funds never blocks the
+/// settlement.
+pub fn settle_invoice_with_claims(
+    env: &Env,
+    business: &Address,
+    invoice_id: &BytesN<32>,
+    payment_amount: i128,
+) -> Result<(), QuickLendXError> {
+    let invoice = get_owned_invoice(env, business, invoice_id)?;
+    if get_amount_paid(env, invoice_id) > 0 || get_settlement_progress(env, invoice_id).is_some() {
+        return Err(QuickLendXError::OperationNotAllowed);
+    }
+    let (plan, investments) = prepare_settlement(env, invoice_id, payment_amount)?;
+    collect_settlement(env, business, &plan)?;
+
+    let contract = env.current_contract_address();
+    for transfer in plan.transfers.iter() {
+        if transfer.to == contract {
+            // Fees and levies kept by the contract are credited right away
+            execute_transfer(env, &transfer, &contract)?;
+        } else {
+            let owed = get_claimable_settlement(env, invoice_id, &transfer.to);
+            set_claimable_settlement(env, invoice_id, &transfer.to, owed + transfer.amount);
+        }
+    }
+    complete_settlement(env, business, invoice, &investments, &plan);
     Ok(())
 }
+
+/// Pull the balance a settlement credited to the payee. Returns the amount
+/// paid out.
+pub fn claim_settlement(
+    env: &Env,
+    payee: &Address,
+    invoice_id: &BytesN<32>,
+) -> Result<i128, QuickLendXError> {
+    payee.require_auth();
+    check_not_blacklisted(env, payee)?;
+    let amount = get_claimable_settlement(env, invoice_id, payee);
+    if amount <= 0 {
+        return Err(QuickLendXError::StorageKeyNotFound);
+    }
+    set_claimable_settlement(env, invoice_id, payee, 0);
+    if !transfer_funds(env, &env.current_contract_address(), payee, amount) {
+        return Err(QuickLendXError::InsufficientFunds);
+    }
+    emit_settlement_claimed(env, invoice_id, payee, amount);
+    Ok(amount)
+}
+
+/// Build the plan for a program debtor paying `payment_amount` on a
+/// supplier's invoice, without changing any state. Investors are owed what
+/// the supplier would have paid them, late charges included; the rest of the
+/// payment is routed to the supplier.
+pub fn build_program_settlement_plan(
+    env: &Env,
+    invoice_id: &BytesN<32>,
+    payment_amount: i128,
+) -> Result<SettlementPlan, QuickLendXError> {
+    let (plan, _) = prepare_program_settlement(env, invoice_id, payment_amount)?;
+    Ok(plan)
+}
+
+fn prepare_program_settlement(
+    env: &Env,
+    invoice_id: &BytesN<32>,
+    payment_amount: i128,
+) -> Result<(SettlementPlan, Vec<Investment>), QuickLendXError> {
+    let invoice =
+        InvoiceStorage::get_invoice(env, invoice_id).ok_or(QuickLendXError::InvoiceNotFound)?;
+    let debtor = ReverseFactoringStorage::get_invoice_debtor(env, invoice_id)
+        .ok_or(QuickLendXError::ProgramNotFound)?;
+    let amount_due = get_amount_due(env, invoice_id)?.total;
+    if payment_amount < amount_due {
+        return Err(QuickLendXError::InsufficientFunds);
+    }
+
+    let (mut plan, investments) = prepare_settlement(env, invoice_id, amount_due)?;
+    let mut transfers = Vec::new(env);
+    for mut transfer in plan.transfers.iter() {
+        transfer.from = debtor.clone();
+        transfers.push_back(transfer);
+    }
+    // Anything investors may not take, jurisdiction caps included, is the
+    // supplier's
+    let residual = payment_amount - plan.payment_amount;
+    if residual > 0 {
+        transfers.push_back(SettlementTransfer {
+            from: debtor,
+            to: invoice.business.clone(),
+            amount: residual,
+            currency: invoice.currency.clone(),
+            kind: SettlementTransferKind::SupplierResidual,
+        });
+    }
+    plan.transfers = transfers;
+    plan.payment_amount = payment_amount;
+    Ok((plan, investments))
+}
+
+/// Settle a program invoice on the debtor's payment (program debtor only).
+/// The debtor pays the contract, which pays investors their principal and
+/// yield, the platform its fees and the supplier whatever is left over.
+pub fn settle_program_invoice(
+    env: &Env,
+    debtor: &Address,
+    invoice_id: &BytesN<32>,
+    payment_amount: i128,
+) -> Result<SettlementPlan, QuickLendXError> {
+    debtor.require_auth();
+    let invoice =
+        InvoiceStorage::get_invoice(env, invoice_id).ok_or(QuickLendXError::InvoiceNotFound)?;
+    match ReverseFactoringStorage::get_invoice_debtor(env, invoice_id) {
+        None => return Err(QuickLendXError::ProgramNotFound),
+        Some(program_debtor) if program_debtor != *debtor => {
+            return Err(QuickLendXError::Unauthorized)
+        }
+        Some(_) => {}
+    }
+    // No settlement money moves to or from a blacklisted party
+    check_not_blacklisted(env, debtor)?;
+    check_not_blacklisted(env, &invoice.business)?;
+    for investor in invoice.investors.iter() {
+        check_not_blacklisted(env, &investor)?;
+    }
+    if get_amount_paid(env, invoice_id) > 0 || get_settlement_progress(env, invoice_id).is_some() {
+        return Err(QuickLendXError::OperationNotAllowed);
+    }
+
+    let (plan, investments) = prepare_program_settlement(env, invoice_id, payment_amount)?;
+    collect_settlement(env, debtor, &plan)?;
+    let contract = env.current_contract_address();
+    let mut residual = 0;
+    for transfer in plan.transfers.iter() {
+        execute_transfer(env, &transfer, &contract)?;
+        if transfer.kind == SettlementTransferKind::SupplierResidual {
+            residual += transfer.amount;
+        }
+    }
+    complete_settlement(env, debtor, invoice, &investments, &plan);
+    emit_program_invoice_settled(env, invoice_id, debtor, payment_amount, residual);
+    Ok(plan)
+}
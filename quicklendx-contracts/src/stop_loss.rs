@@ -0,0 +1,234 @@
+use crate::errors::QuickLendXError;
+use crate::events::{emit_bidding_resumed, emit_stop_loss_rules_set, emit_stop_loss_triggered};
+use crate::investment::Investment;
+use crate::storage::{self, DataKey};
+use soroban_sdk::{contracttype, Address, BytesN, Env, Vec};
+
+/// Most stop-loss rules an investor may set
+pub const MAX_STOP_LOSS_RULES: u32 = 5;
+/// Most triggers kept per investor; older ones are dropped
+pub const MAX_STOP_LOSS_TRIGGERS: u32 = 50;
+
+/// A figure about an investor's portfolio that a stop-loss rule watches
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PortfolioMetric {
+    DefaultRateBps, // Share of closed investments that defaulted
+    ActiveExposure, // Amount in investments not yet repaid or defaulted
+}
+
+/// What happens when a rule's threshold is breached
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StopLossAction {
+    Alert,        // Only record the trigger and emit an event
+    PauseBidding, // Also refuse the investor's new bids until it resumes
+}
+
+/// Act once `metric` goes above `threshold`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StopLossRule {
+    pub metric: PortfolioMetric,
+    pub threshold: i128,
+    pub action: StopLossAction,
+}
+
+/// A rule firing, kept for the investor's history
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StopLossTrigger {
+    pub rule_index: u32,
+    pub rule: StopLossRule,
+    pub value: i128,                    // The metric when the rule fired
+    pub invoice_id: Option<BytesN<32>>, // Invoice whose funding or closing set it off
+    pub triggered_at: u64,
+}
+
+/// An investor's stop-loss rules and what they have done. A rule fires when
+/// its threshold is first breached and again only after its metric has come
+/// back within it.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StopLossState {
+    pub rules: Vec<StopLossRule>,
+    pub breached: Vec<bool>, // Whether each rule's threshold is breached now
+    pub bidding_paused: bool,
+    pub triggers: Vec<StopLossTrigger>, // Oldest first
+}
+
+/// Running totals of an investor's investments, counted from when this
+/// tracking was deployed. Investments made before then add nothing to the
+/// exposure, so closing them never takes it below zero.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PortfolioStats {
+    pub closed: u32,    // Investments repaid or defaulted
+    pub defaulted: u32, // Of those, the ones that defaulted
+    pub active_amount: i128,
+}
+
+impl PortfolioStats {
+    /// Get the current value of a metric
+    pub fn metric(&self, metric: PortfolioMetric) -> i128 {
+        match metric {
+            PortfolioMetric::DefaultRateBps if self.closed == 0 => 0,
+            PortfolioMetric::DefaultRateBps => {
+                self.defaulted as i128 * 10_000 / self.closed as i128
+            }
+            PortfolioMetric::ActiveExposure => self.active_amount,
+        }
+    }
+}
+
+pub struct StopLossStorage;
+
+impl StopLossStorage {
+    /// Get an investor's running portfolio totals
+    pub fn get_stats(env: &Env, investor: &Address) -> PortfolioStats {
+        storage::get(env, &DataKey::PortfolioStats(investor.clone())).unwrap_or_default()
+    }
+
+    /// Get an investor's stop-loss rules and trigger history
+    pub fn get_state(env: &Env, investor: &Address) -> StopLossState {
+        storage::get(env, &DataKey::StopLossState(investor.clone())).unwrap_or_else(|| {
+            StopLossState {
+                rules: Vec::new(env),
+                breached: Vec::new(env),
+                bidding_paused: false,
+                triggers: Vec::new(env),
+            }
+        })
+    }
+
+    fn set_state(env: &Env, investor: &Address, state: &StopLossState) {
+        storage::set(env, &DataKey::StopLossState(investor.clone()), state);
+    }
+}
+
+/// Replace the investor's stop-loss rules (investor only). The rules are
+/// checked against the portfolio straight away; a paused investor stays
+/// paused until it resumes bidding.
+pub fn set_stop_loss_rules(
+    env: &Env,
+    investor: &Address,
+    rules: Vec<StopLossRule>,
+) -> Result<(), QuickLendXError> {
+    investor.require_auth();
+    if rules.len() > MAX_STOP_LOSS_RULES {
+        return Err(QuickLendXError::OperationNotAllowed);
+    }
+    for rule in rules.iter() {
+        let in_range = match rule.metric {
+            PortfolioMetric::DefaultRateBps => (0..10_000).contains(&rule.threshold),
+            PortfolioMetric::ActiveExposure => rule.threshold > 0,
+        };
+        if !in_range {
+            return Err(QuickLendXError::InvalidAmount);
+        }
+    }
+
+    let mut state = StopLossStorage::get_state(env, investor);
+    state.breached = Vec::new(env);
+    for _ in rules.iter() {
+        state.breached.push_back(false);
+    }
+    state.rules = rules;
+    emit_stop_loss_rules_set(env, investor, &state.rules);
+    evaluate(env, investor, state, None);
+    Ok(())
+}
+
+/// Let an investor paused by a stop-loss rule bid again (investor only)
+pub fn resume_bidding(env: &Env, investor: &Address) -> Result<(), QuickLendXError> {
+    investor.require_auth();
+    let mut state = StopLossStorage::get_state(env, investor);
+    if !state.bidding_paused {
+        return Err(QuickLendXError::InvalidStatus);
+    }
+    state.bidding_paused = false;
+    StopLossStorage::set_state(env, investor, &state);
+    emit_bidding_resumed(env, investor);
+    Ok(())
+}
+
+/// Reject bids from an investor a stop-loss rule has paused
+pub fn check_bidding_allowed(env: &Env, investor: &Address) -> Result<(), QuickLendXError> {
+    if StopLossStorage::get_state(env, investor).bidding_paused {
+        return Err(QuickLendXError::BiddingPaused);
+    }
+    Ok(())
+}
+
+/// Count a new investment in its investor's portfolio
+pub fn record_funded(env: &Env, investment: &Investment) {
+    update_stats(env, investment, |stats| {
+        stats.active_amount += investment.amount;
+    });
+}
+
+/// Count an investment as repaid or defaulted
+pub fn record_closed(env: &Env, investment: &Investment, defaulted: bool) {
+    update_stats(env, investment, |stats| {
+        stats.active_amount = (stats.active_amount - investment.amount).max(0);
+        stats.closed += 1;
+        if defaulted {
+            stats.defaulted += 1;
+        }
+    });
+}
+
+/// Take back an investment that was unwound rather than closed
+pub fn record_unwound(env: &Env, investment: &Investment) {
+    update_stats(env, investment, |stats| {
+        stats.active_amount = (stats.active_amount - investment.amount).max(0);
+    });
+}
+
+fn update_stats(env: &Env, investment: &Investment, change: impl FnOnce(&mut PortfolioStats)) {
+    let investor = &investment.investor;
+    let mut stats = StopLossStorage::get_stats(env, investor);
+    change(&mut stats);
+    storage::set(env, &DataKey::PortfolioStats(investor.clone()), &stats);
+    let state = StopLossStorage::get_state(env, investor);
+    if !state.rules.is_empty() {
+        evaluate(env, investor, state, Some(investment.invoice_id.clone()));
+    }
+}
+
+/// Fire the rules whose thresholds have newly been breached, and store the
+/// state
+fn evaluate(
+    env: &Env,
+    investor: &Address,
+    mut state: StopLossState,
+    invoice_id: Option<BytesN<32>>,
+) {
+    let stats = StopLossStorage::get_stats(env, investor);
+    for (index, rule) in state.rules.iter().enumerate() {
+        let index = index as u32;
+        let value = stats.metric(rule.metric);
+        let breached = value > rule.threshold;
+        let newly_breached = breached && !state.breached.get(index).unwrap_or(false);
+        state.breached.set(index, breached);
+        if !newly_breached {
+            continue;
+        }
+        if rule.action == StopLossAction::PauseBidding {
+            state.bidding_paused = true;
+        }
+        let trigger = StopLossTrigger {
+            rule_index: index,
+            rule,
+            value,
+            invoice_id: invoice_id.clone(),
+            triggered_at: env.ledger().timestamp(),
+        };
+        if state.triggers.len() >= MAX_STOP_LOSS_TRIGGERS {
+            state.triggers.pop_front();
+        }
+        state.triggers.push_back(trigger.clone());
+        emit_stop_loss_triggered(env, investor, &trigger);
+    }
+    StopLossStorage::set_state(env, investor, &state);
+}
@@ -0,0 +1,407 @@
+use crate::audit::AuditStorage;
+use crate::bid::BidStorage;
+use crate::insurance::{ClaimStatus, InsuranceStorage};
+use crate::investment::InvestmentStorage;
+use crate::investor_kyc::InvestorVerificationStatus;
+use crate::invoice::{InvoiceStatus, InvoiceStorage};
+use crate::limits::LimitKind;
+use crate::payments::EscrowStorage;
+use soroban_sdk::{
+    contracttype, symbol_short, Address, BytesN, Env, IntoVal, Symbol, TryFromVal, Val, Vec,
+};
+
+/// Ledgers closed per day, at about five seconds a ledger
+pub const DAY_IN_LEDGERS: u32 = 17_280;
+/// Remaining TTL below which an entry is extended when accessed
+pub const TTL_THRESHOLD: u32 = 30 * DAY_IN_LEDGERS;
+/// TTL an entry is extended to when accessed
+pub const TTL_EXTEND_TO: u32 = 120 * DAY_IN_LEDGERS;
+
+/// Keys of the records and per-record indexes kept in persistent storage,
+/// each with its own TTL. Older deployments kept them in instance storage
+/// under ad hoc keys (bare ids before schema v6, symbol tuples before v8,
+/// or v14 and v15 for the records of later features),
+/// and they are still read from there until migrated or rewritten.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DataKey {
+    Invoice(BytesN<32>),
+    Bid(BytesN<32>),
+    Escrow(BytesN<32>),
+    AuditEntry(BytesN<32>),
+    StagedInvoice(BytesN<32>), // Restored from a backup, not yet live
+    Investment(BytesN<32>),
+    Backup(BytesN<32>),
+    BackupData(BytesN<32>),
+    BackupHash(BytesN<32>),
+    BidList(BytesN<32>),        // Bid ids placed on an invoice
+    EscrowList(BytesN<32>),     // Escrow ids of an invoice
+    EscrowLedger(BytesN<32>),   // Ledger entries of an escrow
+    InvestmentList(BytesN<32>), // Investment ids of an invoice
+    AuditTrail(BytesN<32>),     // Audit entry ids of an invoice
+    BusinessInvoices(Address),
+    StatusInvoices(InvoiceStatus),
+    AdminAction(u64),
+    ArchivedInvoice(BytesN<32>),
+    BusinessArchive(Address), // Archived invoice ids of a business
+    ArchivedOutcomes(Address),
+    CategoryInvoices(Symbol),
+    TagInvoices(Symbol),
+    ConfigChange(Symbol, u32), // A parameter's value at one version
+    InvoiceTerms(BytesN<32>),
+    BusinessProfile(Address),
+    PendingAcceptance(BytesN<32>), // Keyed by bid id
+    AutoAcceptRule(BytesN<32>),
+    CreditLimit(Address),
+    InvoiceTemplate(Address, Symbol),
+    TemplateUsage(Address, Symbol), // Invoices listed through a template
+    KYCTier(Address),
+    LimitRequest(u64),
+    BusinessLimitRequests(Address),
+    LimitOverride(Address, BytesN<32>, LimitKind), // Keyed by invoice document hash
+    InvoiceApprovals(BytesN<32>),                  // Verifiers who approved an invoice
+    DueReminder(BytesN<32>, u64),                  // Keyed by reminder window
+    InvestorVerification(Address),
+    InvestorsByStatus(InvestorVerificationStatus),
+    ExtensionVote(BytesN<32>),
+    BlacklistEntry(Address),
+    Blacklist,
+    Affiliates(Address),       // Keyed by business
+    WashFlag(u32),             // Numbered from 1, in the order flags were raised
+    Attestation(Address, u32), // (business, number from 1)
+    AttestationCount(Address),
+    ImportedAttestation(Address, u32), // (business, number from 1)
+    ImportedAttestationCount(Address),
+    ImportedClaim(Address, Address, BytesN<32>), // (business, issuer, claim hash)
+    ImportedVolume(Address),                     // Live volume per issuer
+    InsuranceEnabled(BytesN<32>),
+    Jurisdiction(Address),
+    JurisdictionRule(Symbol),
+    BackstopProviders(Address), // Keyed by currency, in draw order
+    DelinquencyStage(BytesN<32>),
+    PortfolioStats(Address),
+    StopLossState(Address),
+    Inbox(Address),
+    Notification(Address, u64),
+    CurrencyStats(Address),
+    BusinessCounters(Address),
+    DebtorProgram(Address),
+    ProgramInvoices(Address), // Invoices confirmed under a debtor's program
+    InvoiceDebtor(BytesN<32>),
+    DefaultRecovery(BytesN<32>),
+    ClaimOwner(BytesN<32>), // Keyed by investment id, once its claim is sold
+    ClaimListing(BytesN<32>),
+    OwnedClaims(Address),
+    SupplierInvitation(Address, Address), // Keyed by debtor, then supplier
+    SupplierInviters(Address),            // Debtors that invited a supplier
+    ProgramSuppliers(Address),
+    RolloverProposal(BytesN<32>),
+    ProgramCreditLine(Address),
+    Balance(Address, Address), // (owner, currency)
+    AutoReinvest(Address),
+    InvestorReturns(Address),
+    WithdrawalPolicy(Address),
+    QueuedWithdrawalPolicy(Address),
+    PendingWithdrawal(Address, Address), // (owner, currency)
+    TrancheStructure(BytesN<32>),
+    BidTranche(BytesN<32>),
+    InvestmentTranche(BytesN<32>),
+    BalanceActivity(Address, Address),  // (owner, currency)
+    DormancyNotice(Address, Address),   // (owner, currency)
+    EscheatedBalance(Address, Address), // (owner, currency)
+    PublishedRiskScore(Address),
+    SettlementProgress(BytesN<32>),
+    AmountPaid(BytesN<32>), // Installments repaid on an invoice
+    ClaimableSettlement(BytesN<32>, Address), // (invoice, payee)
+    Dispute(BytesN<32>),
+    Auction(BytesN<32>),
+    AuctionCommitments(BytesN<32>),
+    BidNegotiation(BytesN<32>),
+    InsuranceClaim(BytesN<32>),
+    InvestmentClaim(BytesN<32>), // Claim filed for an investment
+    ClaimsByStatus(ClaimStatus),
+    InsuranceRecovery(BytesN<32>), // Keyed by investment id
+    PriorityWindow(Address),
+    PriorityWindowEnd(BytesN<32>),
+    LastReturn(Address, Address), // (business, investor)
+    FundingDeadline(BytesN<32>),
+    TreasuryBalance(Address),
+    ListedClaims,                   // Investments whose claims are for sale
+    InstallmentPayouts(BytesN<32>), // Paid so far to each settlement recipient
+    ClaimAppeal(BytesN<32>),
+}
+
+impl DataKey {
+    /// Instance storage key the entry used before it moved to persistent
+    /// storage, if it ever had one
+    fn legacy_key(&self, env: &Env) -> Option<Val> {
+        let key = match self {
+            DataKey::Invoice(id)
+            | DataKey::Bid(id)
+            | DataKey::Escrow(id)
+            | DataKey::AuditEntry(id)
+            | DataKey::Investment(id)
+            | DataKey::Backup(id) => id.into_val(env),
+            DataKey::StagedInvoice(_)
+            | DataKey::AdminAction(_)
+            | DataKey::ArchivedInvoice(_)
+            | DataKey::BusinessArchive(_)
+            | DataKey::ArchivedOutcomes(_)
+            | DataKey::CategoryInvoices(_)
+            | DataKey::TagInvoices(_)
+            | DataKey::ConfigChange(..)
+            | DataKey::InvoiceTerms(_)
+            | DataKey::BusinessProfile(_)
+            | DataKey::PendingAcceptance(_)
+            | DataKey::AutoAcceptRule(_)
+            | DataKey::CreditLimit(_)
+            | DataKey::InvoiceTemplate(..)
+            | DataKey::TemplateUsage(..)
+            | DataKey::KYCTier(_)
+            | DataKey::LimitRequest(_)
+            | DataKey::BusinessLimitRequests(_)
+            | DataKey::LimitOverride(..)
+            | DataKey::InvoiceApprovals(_)
+            | DataKey::DueReminder(..)
+            | DataKey::InvestorVerification(_)
+            | DataKey::InvestorsByStatus(_)
+            | DataKey::ExtensionVote(_)
+            | DataKey::BlacklistEntry(_)
+            | DataKey::WashFlag(_)
+            | DataKey::Attestation(..)
+            | DataKey::AttestationCount(_)
+            | DataKey::ImportedAttestation(..)
+            | DataKey::ImportedAttestationCount(_)
+            | DataKey::ImportedClaim(..)
+            | DataKey::ImportedVolume(_)
+            | DataKey::DelinquencyStage(_)
+            | DataKey::PortfolioStats(_)
+            | DataKey::StopLossState(_)
+            | DataKey::Inbox(_)
+            | DataKey::Notification(..)
+            | DataKey::CurrencyStats(_)
+            | DataKey::BusinessCounters(_)
+            | DataKey::DebtorProgram(_)
+            | DataKey::ProgramInvoices(_)
+            | DataKey::InvoiceDebtor(_)
+            | DataKey::DefaultRecovery(_)
+            | DataKey::ClaimOwner(_)
+            | DataKey::ClaimListing(_)
+            | DataKey::OwnedClaims(_)
+            | DataKey::SupplierInvitation(..)
+            | DataKey::SupplierInviters(_)
+            | DataKey::ProgramSuppliers(_)
+            | DataKey::RolloverProposal(_)
+            | DataKey::ProgramCreditLine(_)
+            | DataKey::Balance(_, _)
+            | DataKey::AutoReinvest(_)
+            | DataKey::InvestorReturns(_)
+            | DataKey::WithdrawalPolicy(_)
+            | DataKey::QueuedWithdrawalPolicy(_)
+            | DataKey::PendingWithdrawal(_, _)
+            | DataKey::TrancheStructure(_)
+            | DataKey::BidTranche(_)
+            | DataKey::InvestmentTranche(_)
+            | DataKey::BalanceActivity(_, _)
+            | DataKey::DormancyNotice(_, _)
+            | DataKey::EscheatedBalance(_, _)
+            | DataKey::PublishedRiskScore(_)
+            | DataKey::InstallmentPayouts(_)
+            | DataKey::ClaimAppeal(_) => return None,
+            DataKey::BackupData(id) => (symbol_short!("bkup_data"), id.clone()).into_val(env),
+            DataKey::BackupHash(id) => (symbol_short!("bkup_hsh"), id.clone()).into_val(env),
+            DataKey::BidList(id) => (symbol_short!("bids"), id.clone()).into_val(env),
+            DataKey::EscrowList(id) => (symbol_short!("inv_escs"), id.clone()).into_val(env),
+            DataKey::EscrowLedger(id) => (symbol_short!("esc_led"), id.clone()).into_val(env),
+            DataKey::InvestmentList(id) => (symbol_short!("invests"), id.clone()).into_val(env),
+            DataKey::AuditTrail(id) => (symbol_short!("inv_aud"), id.clone()).into_val(env),
+            DataKey::BusinessInvoices(business) => {
+                (symbol_short!("business"), business.clone()).into_val(env)
+            }
+            DataKey::StatusInvoices(status) => match status {
+                InvoiceStatus::Pending => symbol_short!("pending"),
+                InvoiceStatus::Verified => symbol_short!("verified"),
+                InvoiceStatus::Funded => symbol_short!("funded"),
+                InvoiceStatus::Paid => symbol_short!("paid"),
+                InvoiceStatus::Defaulted => symbol_short!("default"),
+                InvoiceStatus::Cancelled => symbol_short!("cancelled"),
+                InvoiceStatus::Expired => symbol_short!("expired"),
+                // Introduced after status lists left instance storage
+                InvoiceStatus::Refinanced => return None,
+            }
+            .into_val(env),
+            DataKey::SettlementProgress(id) => {
+                (symbol_short!("stl_prog"), id.clone()).into_val(env)
+            }
+            DataKey::AmountPaid(id) => (symbol_short!("inv_paid"), id.clone()).into_val(env),
+            DataKey::ClaimableSettlement(id, payee) => {
+                (symbol_short!("stl_clm"), id.clone(), payee.clone()).into_val(env)
+            }
+            DataKey::Dispute(id) => (symbol_short!("dispute"), id.clone()).into_val(env),
+            DataKey::Auction(id) => (symbol_short!("auction"), id.clone()).into_val(env),
+            DataKey::AuctionCommitments(id) => (symbol_short!("auc_cmt"), id.clone()).into_val(env),
+            DataKey::BidNegotiation(id) => (symbol_short!("bid_neg"), id.clone()).into_val(env),
+            DataKey::InsuranceClaim(id) => (symbol_short!("claim"), id.clone()).into_val(env),
+            DataKey::InvestmentClaim(id) => (symbol_short!("inv_clm"), id.clone()).into_val(env),
+            DataKey::ClaimsByStatus(status) => {
+                (symbol_short!("clm_st"), status.clone()).into_val(env)
+            }
+            DataKey::InsuranceRecovery(id) => (symbol_short!("ins_rcv"), id.clone()).into_val(env),
+            DataKey::PriorityWindow(business) => {
+                (symbol_short!("rofr"), business.clone()).into_val(env)
+            }
+            DataKey::PriorityWindowEnd(id) => (symbol_short!("rofr_end"), id.clone()).into_val(env),
+            DataKey::LastReturn(business, investor) => (
+                symbol_short!("last_rtn"),
+                business.clone(),
+                investor.clone(),
+            )
+                .into_val(env),
+            DataKey::FundingDeadline(id) => (symbol_short!("fund_dl"), id.clone()).into_val(env),
+            DataKey::TreasuryBalance(currency) => {
+                (symbol_short!("treasury"), currency.clone()).into_val(env)
+            }
+            DataKey::ListedClaims => symbol_short!("clm_lst").into_val(env),
+            DataKey::Blacklist => symbol_short!("blacklist").into_val(env),
+            DataKey::Affiliates(business) => {
+                (symbol_short!("affil"), business.clone()).into_val(env)
+            }
+            DataKey::InsuranceEnabled(id) => (symbol_short!("ins_on"), id.clone()).into_val(env),
+            DataKey::Jurisdiction(address) => {
+                (symbol_short!("juris"), address.clone()).into_val(env)
+            }
+            DataKey::JurisdictionRule(code) => {
+                (symbol_short!("jur_rule"), code.clone()).into_val(env)
+            }
+            DataKey::BackstopProviders(currency) => {
+                (symbol_short!("backstop"), currency.clone()).into_val(env)
+            }
+        };
+        Some(key)
+    }
+}
+
+/// Read a record, extending its TTL when it is running low
+pub fn get<V: TryFromVal<Env, Val>>(env: &Env, key: &DataKey) -> Option<V> {
+    let value = env.storage().persistent().get(key);
+    if value.is_some() {
+        extend(env, key);
+        return value;
+    }
+    let legacy_key = key.legacy_key(env)?;
+    env.storage().instance().get(&legacy_key)
+}
+
+/// Write a record and give it a full TTL
+pub fn set<V: IntoVal<Env, Val>>(env: &Env, key: &DataKey, value: &V) {
+    env.storage().persistent().set(key, value);
+    env.storage()
+        .persistent()
+        .extend_ttl(key, TTL_EXTEND_TO, TTL_EXTEND_TO);
+    remove_legacy(env, key);
+}
+
+/// Delete a record
+pub fn remove(env: &Env, key: &DataKey) {
+    env.storage().persistent().remove(key);
+    remove_legacy(env, key);
+}
+
+fn remove_legacy(env: &Env, key: &DataKey) {
+    if let Some(legacy_key) = key.legacy_key(env) {
+        env.storage().instance().remove(&legacy_key);
+    }
+}
+
+/// Extend a record's TTL if it is stored and running low. Returns whether
+/// the record exists.
+pub fn extend(env: &Env, key: &DataKey) -> bool {
+    if !env.storage().persistent().has(key) {
+        return false;
+    }
+    env.storage()
+        .persistent()
+        .extend_ttl(key, TTL_THRESHOLD, TTL_EXTEND_TO);
+    true
+}
+
+/// Move a record written before schema v6 into persistent storage
+pub fn migrate_legacy(env: &Env, key: &DataKey) {
+    let Some(legacy_key) = key.legacy_key(env) else {
+        return;
+    };
+    let legacy: Option<Val> = env.storage().instance().get(&legacy_key);
+    if let Some(value) = legacy {
+        set(env, key, &value);
+    }
+}
+
+/// Keep the contract instance and the records of the given invoices alive:
+/// each invoice with its bids, escrows, investments and audit entries, their
+/// indexes, and the settlement, dispute, auction and insurance records kept
+/// alongside them. Anyone may pay for this. Returns how many records were
+/// found.
+pub fn extend_ttl(env: &Env, invoice_ids: &Vec<BytesN<32>>) -> u32 {
+    env.storage()
+        .instance()
+        .extend_ttl(TTL_THRESHOLD, TTL_EXTEND_TO);
+
+    let mut extended = 0u32;
+    for invoice_id in invoice_ids.iter() {
+        let mut keys = Vec::new(env);
+        keys.push_back(DataKey::Invoice(invoice_id.clone()));
+        keys.push_back(DataKey::BidList(invoice_id.clone()));
+        keys.push_back(DataKey::EscrowList(invoice_id.clone()));
+        keys.push_back(DataKey::InvestmentList(invoice_id.clone()));
+        keys.push_back(DataKey::AuditTrail(invoice_id.clone()));
+        keys.push_back(DataKey::InvoiceTerms(invoice_id.clone()));
+        keys.push_back(DataKey::SettlementProgress(invoice_id.clone()));
+        keys.push_back(DataKey::AmountPaid(invoice_id.clone()));
+        keys.push_back(DataKey::InstallmentPayouts(invoice_id.clone()));
+        keys.push_back(DataKey::Dispute(invoice_id.clone()));
+        keys.push_back(DataKey::Auction(invoice_id.clone()));
+        keys.push_back(DataKey::AuctionCommitments(invoice_id.clone()));
+        keys.push_back(DataKey::PriorityWindowEnd(invoice_id.clone()));
+        keys.push_back(DataKey::FundingDeadline(invoice_id.clone()));
+        keys.push_back(DataKey::InsuranceEnabled(invoice_id.clone()));
+        if let Some(invoice) = InvoiceStorage::get_invoice(env, &invoice_id) {
+            let mut payees = invoice.investors.clone();
+            payees.push_back(invoice.business.clone());
+            for payee in payees.iter() {
+                keys.push_back(DataKey::ClaimableSettlement(invoice_id.clone(), payee));
+            }
+            for investor in invoice.investors.iter() {
+                keys.push_back(DataKey::LastReturn(invoice.business.clone(), investor));
+            }
+            keys.push_back(DataKey::TreasuryBalance(invoice.currency));
+        }
+        for bid_id in BidStorage::get_bids_for_invoice(env, &invoice_id).iter() {
+            keys.push_back(DataKey::Bid(bid_id.clone()));
+            keys.push_back(DataKey::BidNegotiation(bid_id));
+        }
+        for escrow_id in EscrowStorage::get_escrows_for_invoice(env, &invoice_id).iter() {
+            keys.push_back(DataKey::Escrow(escrow_id.clone()));
+            keys.push_back(DataKey::EscrowLedger(escrow_id));
+        }
+        for investment_id in InvestmentStorage::get_investments_for_invoice(env, &invoice_id).iter()
+        {
+            keys.push_back(DataKey::Investment(investment_id.clone()));
+            keys.push_back(DataKey::InsuranceRecovery(investment_id.clone()));
+            if let Some(claim_id) = InsuranceStorage::get_claim_for_investment(env, &investment_id)
+            {
+                keys.push_back(DataKey::InsuranceClaim(claim_id.clone()));
+                keys.push_back(DataKey::ClaimAppeal(claim_id));
+            }
+            keys.push_back(DataKey::InvestmentClaim(investment_id));
+        }
+        for audit_id in AuditStorage::get_invoice_audit_trail(env, &invoice_id).iter() {
+            keys.push_back(DataKey::AuditEntry(audit_id));
+        }
+        for key in keys.iter() {
+            if extend(env, &key) {
+                extended += 1;
+            }
+        }
+    }
+    extended
+}
@@ -0,0 +1,99 @@
+//! Test-only measurement of the contract's ledger footprint, so tests can
+//! hold representative flows to a storage budget.
+
+use soroban_sdk::xdr::{
+    ContractDataDurability, LedgerEntryData, Limits, ScAddress, ScVal, WriteXdr,
+};
+use soroban_sdk::{Address, Env};
+
+/// The contract's storage entries at one point in time. Sizes are of the
+/// entries' XDR, which is what rent and write fees are charged on.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct StorageFootprint {
+    pub persistent_entries: u32,
+    pub persistent_bytes: u32,
+    pub temporary_entries: u32,
+    pub temporary_bytes: u32,
+    pub instance_bytes: u32, // The instance entry, which every call loads
+    pub largest_entry_bytes: u32, // Largest persistent or temporary entry
+}
+
+impl StorageFootprint {
+    /// Measure the storage a contract holds in the test ledger
+    pub fn measure(env: &Env, contract_id: &Address) -> Self {
+        let contract = ScAddress::from(contract_id);
+        let mut footprint = Self::default();
+        for (_, (entry, _)) in env.to_ledger_snapshot().ledger_entries {
+            let LedgerEntryData::ContractData(data) = &entry.data else {
+                continue;
+            };
+            if data.contract != contract {
+                continue;
+            }
+            let bytes = entry.to_xdr(Limits::none()).unwrap().len() as u32;
+            if data.key == ScVal::LedgerKeyContractInstance {
+                footprint.instance_bytes = bytes;
+                continue;
+            }
+            match data.durability {
+                ContractDataDurability::Persistent => {
+                    footprint.persistent_entries += 1;
+                    footprint.persistent_bytes += bytes;
+                }
+                ContractDataDurability::Temporary => {
+                    footprint.temporary_entries += 1;
+                    footprint.temporary_bytes += bytes;
+                }
+            }
+            footprint.largest_entry_bytes = footprint.largest_entry_bytes.max(bytes);
+        }
+        footprint
+    }
+
+    /// Entries other than the instance
+    pub fn entries(&self) -> u32 {
+        self.persistent_entries + self.temporary_entries
+    }
+
+    /// Bytes across all entries, the instance included
+    pub fn total_bytes(&self) -> u32 {
+        self.persistent_bytes + self.temporary_bytes + self.instance_bytes
+    }
+
+    /// What was added since an earlier measurement
+    pub fn growth_since(&self, earlier: &Self) -> StorageGrowth {
+        StorageGrowth {
+            entries: self.entries() as i64 - earlier.entries() as i64,
+            bytes: self.total_bytes() as i64 - earlier.total_bytes() as i64,
+            instance_bytes: self.instance_bytes as i64 - earlier.instance_bytes as i64,
+        }
+    }
+}
+
+/// Change in footprint between two measurements
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct StorageGrowth {
+    pub entries: i64,
+    pub bytes: i64,
+    pub instance_bytes: i64,
+}
+
+/// Most storage a step may add
+#[derive(Clone, Copy, Debug)]
+pub struct StorageBudget {
+    pub entries: i64,
+    pub bytes: i64,
+    pub instance_bytes: i64,
+}
+
+impl StorageBudget {
+    /// Panic naming the step if its growth is over budget
+    pub fn assert_within(&self, step: &str, growth: &StorageGrowth) {
+        assert!(
+            growth.entries <= self.entries
+                && growth.bytes <= self.bytes
+                && growth.instance_bytes <= self.instance_bytes,
+            "{step} exceeded its storage budget: grew by {growth:?}, budget is {self:?}"
+        );
+    }
+}
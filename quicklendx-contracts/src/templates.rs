@@ -0,0 +1,239 @@
+use crate::errors::QuickLendXError;
+use crate::events::{
+    emit_invoice_template_approved, emit_invoice_template_revoked, emit_invoice_template_used,
+};
+use crate::storage::{self, DataKey};
+use crate::verification::require_admin;
+use soroban_sdk::{contracttype, Address, BytesN, Env, Map, String, Symbol, TryFromVal, Val, Vec};
+
+/// The invoices a template covers. Tenors run from upload to due date, in
+/// seconds; a template without a debtor covers invoices to any debtor.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TemplateCriteria {
+    pub currency: Address,
+    pub debtor: Option<BytesN<32>>, // Hash identifying the debtor
+    pub min_amount: i128,
+    pub max_amount: i128,
+    pub min_tenor: u64,
+    pub max_tenor: u64,
+}
+
+/// A kind of recurring invoice the admin has approved a business to list
+/// without manual verification, such as monthly invoices to one debtor
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InvoiceTemplate {
+    pub business: Address,
+    pub template_id: Symbol,
+    pub criteria: TemplateCriteria,
+    pub approved_by: Address,
+    pub approved_at: u64,
+    pub uses: u32, // Invoices listed through the template
+}
+
+/// Template layout before debtors and amount and tenor ranges. Such
+/// templates are read as covering any debtor and any amount or tenor up to
+/// their maximum.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct InvoiceTemplateV1 {
+    pub business: Address,
+    pub template_id: Symbol,
+    pub currency: Address,
+    pub max_amount: i128,
+    pub max_tenor: u64,
+    pub approved_by: Address,
+    pub approved_at: u64,
+}
+
+/// An invoice listed through a template, kept for auditing its use
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TemplateUsage {
+    pub invoice_id: BytesN<32>,
+    pub debtor: Option<BytesN<32>>,
+    pub amount: i128,
+    pub used_at: u64,
+}
+
+/// The invoice being uploaded by upload_and_list
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InvoiceInput {
+    pub amount: i128,
+    pub currency: Address,
+    pub due_date: u64,
+    pub description: String,
+    pub invoice_hash: Option<BytesN<32>>,
+    pub debtor: Option<BytesN<32>>, // Hash identifying the debtor
+}
+
+/// How upload_and_list lists an invoice. Without a template the invoice
+/// waits for manual verification; an auto-accept minimum of 0 sets no rule.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ListingParams {
+    pub template_id: Option<Symbol>,
+    pub category: Option<Symbol>,
+    pub tags: Vec<Symbol>,
+    pub auto_accept_min_amount: i128,
+    pub auto_accept_max_return_bps: u32,
+}
+
+pub struct TemplateStorage;
+
+impl TemplateStorage {
+    /// Get one of a business's approved templates
+    pub fn get_template(
+        env: &Env,
+        business: &Address,
+        template_id: &Symbol,
+    ) -> Option<InvoiceTemplate> {
+        let key = DataKey::InvoiceTemplate(business.clone(), template_id.clone());
+        let raw: Val = storage::get(env, &key)?;
+        let is_v1 = Map::<Symbol, Val>::try_from_val(env, &raw)
+            .is_ok_and(|fields| !fields.contains_key(Symbol::new(env, "criteria")));
+        if !is_v1 {
+            return InvoiceTemplate::try_from_val(env, &raw).ok();
+        }
+        let old = InvoiceTemplateV1::try_from_val(env, &raw).ok()?;
+        Some(InvoiceTemplate {
+            business: old.business,
+            template_id: old.template_id,
+            criteria: TemplateCriteria {
+                currency: old.currency,
+                debtor: None,
+                min_amount: 0,
+                max_amount: old.max_amount,
+                min_tenor: 0,
+                max_tenor: old.max_tenor,
+            },
+            approved_by: old.approved_by,
+            approved_at: old.approved_at,
+            uses: 0,
+        })
+    }
+
+    fn set_template(env: &Env, template: &InvoiceTemplate) {
+        storage::set(
+            env,
+            &DataKey::InvoiceTemplate(template.business.clone(), template.template_id.clone()),
+            template,
+        );
+    }
+
+    /// Get the invoices listed through a template, oldest first
+    pub fn get_usage(env: &Env, business: &Address, template_id: &Symbol) -> Vec<TemplateUsage> {
+        storage::get(
+            env,
+            &DataKey::TemplateUsage(business.clone(), template_id.clone()),
+        )
+        .unwrap_or_else(|| Vec::new(env))
+    }
+}
+
+/// Approve a kind of invoice a business may list without manual
+/// verification, replacing any template with the same id (admin only). Its
+/// usage history is kept.
+pub fn approve_template(
+    env: &Env,
+    admin: &Address,
+    business: &Address,
+    template_id: &Symbol,
+    criteria: &TemplateCriteria,
+) -> Result<InvoiceTemplate, QuickLendXError> {
+    require_admin(env, admin)?;
+    if criteria.min_amount < 0
+        || criteria.max_amount <= 0
+        || criteria.min_amount > criteria.max_amount
+    {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    if criteria.max_tenor == 0 || criteria.min_tenor > criteria.max_tenor {
+        return Err(QuickLendXError::InvalidTimestamp);
+    }
+    let uses = TemplateStorage::get_template(env, business, template_id)
+        .map(|template| template.uses)
+        .unwrap_or(0);
+    let template = InvoiceTemplate {
+        business: business.clone(),
+        template_id: template_id.clone(),
+        criteria: criteria.clone(),
+        approved_by: admin.clone(),
+        approved_at: env.ledger().timestamp(),
+        uses,
+    };
+    TemplateStorage::set_template(env, &template);
+    emit_invoice_template_approved(env, &template);
+    Ok(template)
+}
+
+/// Withdraw a business's template (admin only). Its usage history is kept.
+pub fn revoke_template(
+    env: &Env,
+    admin: &Address,
+    business: &Address,
+    template_id: &Symbol,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    if TemplateStorage::get_template(env, business, template_id).is_none() {
+        return Err(QuickLendXError::StorageKeyNotFound);
+    }
+    storage::remove(
+        env,
+        &DataKey::InvoiceTemplate(business.clone(), template_id.clone()),
+    );
+    emit_invoice_template_revoked(env, business, template_id, admin);
+    Ok(())
+}
+
+/// Get the business's template an invoice fits, for it to be listed without
+/// manual verification
+pub fn matching_template(
+    env: &Env,
+    business: &Address,
+    template_id: &Symbol,
+    input: &InvoiceInput,
+) -> Result<InvoiceTemplate, QuickLendXError> {
+    let template = TemplateStorage::get_template(env, business, template_id)
+        .ok_or(QuickLendXError::TemplateNotApproved)?;
+    let criteria = &template.criteria;
+    let tenor = input.due_date.saturating_sub(env.ledger().timestamp());
+    let debtor_matches = criteria.debtor.is_none() || criteria.debtor == input.debtor;
+    if input.currency != criteria.currency
+        || !debtor_matches
+        || input.amount < criteria.min_amount
+        || input.amount > criteria.max_amount
+        || tenor < criteria.min_tenor
+        || tenor > criteria.max_tenor
+    {
+        return Err(QuickLendXError::TemplateNotApproved);
+    }
+    Ok(template)
+}
+
+/// Record that an invoice was listed through a template
+pub fn record_template_use(
+    env: &Env,
+    mut template: InvoiceTemplate,
+    invoice_id: &BytesN<32>,
+    input: &InvoiceInput,
+) {
+    let usage = TemplateUsage {
+        invoice_id: invoice_id.clone(),
+        debtor: input.debtor.clone(),
+        amount: input.amount,
+        used_at: env.ledger().timestamp(),
+    };
+    let mut history = TemplateStorage::get_usage(env, &template.business, &template.template_id);
+    history.push_back(usage.clone());
+    storage::set(
+        env,
+        &DataKey::TemplateUsage(template.business.clone(), template.template_id.clone()),
+        &history,
+    );
+    template.uses += 1;
+    TemplateStorage::set_template(env, &template);
+    emit_invoice_template_used(env, &template, &usage);
+}
@@ -0,0 +1,113 @@
+use crate::auction::AuctionStorage;
+use crate::errors::QuickLendXError;
+use crate::events::{emit_invoice_terms_acknowledged, emit_invoice_terms_set};
+use crate::invoice::{Invoice, InvoiceStatus, InvoiceStorage};
+use crate::storage::{self, DataKey};
+use soroban_sdk::{contracttype, Address, BytesN, Env, Vec};
+
+/// An investor's acknowledgement of an invoice's terms, given when its bid
+/// was accepted
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TermsAcknowledgement {
+    pub investor: Address,
+    pub acknowledged_at: u64,
+}
+
+/// Hash of the off-chain legal terms, such as the assignment agreement, that
+/// funding an invoice binds its business and investors to. Set before
+/// funding; each accepted bid is then signed for by both parties.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InvoiceTerms {
+    pub invoice_id: BytesN<32>,
+    pub terms_hash: BytesN<32>,
+    pub set_at: u64,
+    pub acknowledgements: Vec<TermsAcknowledgement>,
+}
+
+pub struct TermsStorage;
+
+impl TermsStorage {
+    /// Get the terms an invoice is funded under, if it has any
+    pub fn get_terms(env: &Env, invoice_id: &BytesN<32>) -> Option<InvoiceTerms> {
+        storage::get(env, &DataKey::InvoiceTerms(invoice_id.clone()))
+    }
+
+    /// Get the hash of an invoice's terms, if it has any
+    pub fn get_terms_hash(env: &Env, invoice_id: &BytesN<32>) -> Option<BytesN<32>> {
+        Self::get_terms(env, invoice_id).map(|terms| terms.terms_hash)
+    }
+
+    fn set_terms(env: &Env, terms: &InvoiceTerms) {
+        storage::set(env, &DataKey::InvoiceTerms(terms.invoice_id.clone()), terms);
+    }
+}
+
+/// Attach the hash of an invoice's legal terms, replacing any earlier one
+/// (business only). Terms can only change before the invoice is funded and
+/// while it is not being auctioned.
+pub fn set_invoice_terms(
+    env: &Env,
+    business: &Address,
+    invoice_id: &BytesN<32>,
+    terms_hash: &BytesN<32>,
+) -> Result<InvoiceTerms, QuickLendXError> {
+    business.require_auth();
+    let invoice =
+        InvoiceStorage::get_invoice(env, invoice_id).ok_or(QuickLendXError::InvoiceNotFound)?;
+    if invoice.business != *business {
+        return Err(QuickLendXError::NotBusinessOwner);
+    }
+    if !matches!(
+        invoice.status,
+        InvoiceStatus::Pending | InvoiceStatus::Verified
+    ) {
+        return Err(QuickLendXError::InvalidStatus);
+    }
+    if invoice.funded_amount > 0 {
+        return Err(QuickLendXError::InvoiceAlreadyFunded);
+    }
+    if AuctionStorage::is_open(env, invoice_id) {
+        return Err(QuickLendXError::AuctionInProgress);
+    }
+
+    let terms = InvoiceTerms {
+        invoice_id: invoice_id.clone(),
+        terms_hash: terms_hash.clone(),
+        set_at: env.ledger().timestamp(),
+        acknowledgements: Vec::new(env),
+    };
+    TermsStorage::set_terms(env, &terms);
+    emit_invoice_terms_set(env, &terms, business);
+    Ok(terms)
+}
+
+/// Check the terms a bid is being accepted under and record the investor's
+/// acknowledgement. An invoice with terms is only funded when the acceptance
+/// names their hash; one without terms only when no hash is given. Callers
+/// make sure the investor signed for the acceptance, by co-signing it,
+/// confirming it or placing the bid under an auto-accept rule.
+pub fn acknowledge_terms(
+    env: &Env,
+    invoice: &Invoice,
+    investor: &Address,
+    terms_hash: Option<BytesN<32>>,
+) -> Result<(), QuickLendXError> {
+    let Some(mut terms) = TermsStorage::get_terms(env, &invoice.id) else {
+        return match terms_hash {
+            Some(_) => Err(QuickLendXError::TermsMismatch),
+            None => Ok(()),
+        };
+    };
+    if terms_hash.as_ref() != Some(&terms.terms_hash) {
+        return Err(QuickLendXError::TermsMismatch);
+    }
+    terms.acknowledgements.push_back(TermsAcknowledgement {
+        investor: investor.clone(),
+        acknowledged_at: env.ledger().timestamp(),
+    });
+    TermsStorage::set_terms(env, &terms);
+    emit_invoice_terms_acknowledged(env, &terms.invoice_id, &terms.terms_hash, investor);
+    Ok(())
+}
@@ -1,16 +1,25 @@
 use super::*;
 use soroban_sdk::{
     symbol_short,
-    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation},
-    vec, Address, BytesN, Env, String, Symbol, Vec,
-};
-use crate::audit::{
-    AuditStorage, AuditQueryFilter, AuditOperation, log_invoice_operation
+    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation, Ledger},
+    token, vec, Address, BytesN, Env, String, Symbol, Vec,
 };
+use crate::analytics::BusinessStats;
+use crate::audit::{AuditOperation, AuditOperationFilter, AuditQueryFilter};
+use crate::investor_kyc::InvestorVerificationStatus;
+use crate::invoice::ExtensionStatus;
+use crate::limits::LimitRequestStatus;
+use crate::notifications::{InboxSummary, NotificationKind};
+use crate::reverse_factoring::InvitationStatus;
+use crate::settlement::SettlementTransferKind;
+use crate::tranche::Tranche;
+use crate::stop_loss::{PortfolioMetric, StopLossAction};
+use crate::storage_budget::{StorageBudget, StorageFootprint};
 
 #[test]
 fn test_store_invoice() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register_contract(None, QuickLendXContract);
     let client = QuickLendXContractClient::new(&env, &contract_id);
 
@@ -20,6 +29,9 @@ fn test_store_invoice() {
     let due_date = env.ledger().timestamp() + 86400; // 1 day from now
     let description = String::from_str(&env, "Test invoice for services");
 
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    verify_kyc(&env, &client, &admin, &business);
     let invoice_id = client.store_invoice(&business, &amount, &currency, &due_date, &description);
 
     // Verify invoice was stored
@@ -37,6 +49,7 @@ fn test_store_invoice() {
 #[test]
 fn test_store_invoice_validation() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register_contract(None, QuickLendXContract);
     let client = QuickLendXContractClient::new(&env, &contract_id);
 
@@ -45,6 +58,9 @@ fn test_store_invoice_validation() {
     let due_date = env.ledger().timestamp() + 86400;
 
     // Test valid invoice creation
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    verify_kyc(&env, &client, &admin, &business);
     let invoice_id = client.store_invoice(
         &business,
         &1000,
@@ -62,6 +78,7 @@ fn test_store_invoice_validation() {
 #[test]
 fn test_get_business_invoices() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register_contract(None, QuickLendXContract);
     let client = QuickLendXContractClient::new(&env, &contract_id);
 
@@ -71,6 +88,10 @@ fn test_get_business_invoices() {
     let due_date = env.ledger().timestamp() + 86400;
 
     // Create invoices for business1
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    verify_kyc(&env, &client, &admin, &business1);
+    verify_kyc(&env, &client, &admin, &business2);
     let invoice1_id = client.store_invoice(
         &business1,
         &1000,
@@ -111,6 +132,7 @@ fn test_get_business_invoices() {
 #[test]
 fn test_get_invoices_by_status() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register_contract(None, QuickLendXContract);
     let client = QuickLendXContractClient::new(&env, &contract_id);
 
@@ -119,6 +141,9 @@ fn test_get_invoices_by_status() {
     let due_date = env.ledger().timestamp() + 86400;
 
     // Create invoices
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    verify_kyc(&env, &client, &admin, &business);
     let invoice1_id = client.store_invoice(
         &business,
         &1000,
@@ -149,13 +174,17 @@ fn test_get_invoices_by_status() {
 #[test]
 fn test_update_invoice_status() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register_contract(None, QuickLendXContract);
     let client = QuickLendXContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
 
     let business = Address::generate(&env);
     let currency = Address::generate(&env);
     let due_date = env.ledger().timestamp() + 86400;
 
+    verify_kyc(&env, &client, &admin, &business);
     let invoice_id = client.store_invoice(
         &business,
         &1000,
@@ -169,7 +198,7 @@ fn test_update_invoice_status() {
     assert_eq!(invoice.status, InvoiceStatus::Pending);
 
     // Update to verified
-    client.update_invoice_status(&invoice_id, &InvoiceStatus::Verified);
+    client.update_invoice_status(&admin, &invoice_id, &InvoiceStatus::Verified);
 
     let invoice = client.get_invoice(&invoice_id);
     assert_eq!(invoice.status, InvoiceStatus::Verified);
@@ -186,14 +215,18 @@ fn test_update_invoice_status() {
 #[test]
 fn test_get_available_invoices() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register_contract(None, QuickLendXContract);
     let client = QuickLendXContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
 
     let business = Address::generate(&env);
     let currency = Address::generate(&env);
     let due_date = env.ledger().timestamp() + 86400;
 
     // Create invoices
+    verify_kyc(&env, &client, &admin, &business);
     let invoice1_id = client.store_invoice(
         &business,
         &1000,
@@ -215,7 +248,7 @@ fn test_get_available_invoices() {
     assert_eq!(available_invoices.len(), 0);
 
     // Verify one invoice
-    client.update_invoice_status(&invoice1_id, &InvoiceStatus::Verified);
+    client.update_invoice_status(&admin, &invoice1_id, &InvoiceStatus::Verified);
 
     // Now one available invoice
     let available_invoices = client.get_available_invoices();
@@ -226,6 +259,7 @@ fn test_get_available_invoices() {
 #[test]
 fn test_invoice_count_functions() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register_contract(None, QuickLendXContract);
     let client = QuickLendXContractClient::new(&env, &contract_id);
 
@@ -234,6 +268,9 @@ fn test_invoice_count_functions() {
     let due_date = env.ledger().timestamp() + 86400;
 
     // Create invoices
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    verify_kyc(&env, &client, &admin, &business);
     client.store_invoice(
         &business,
         &1000,
@@ -277,13 +314,17 @@ fn test_invoice_not_found() {
 #[test]
 fn test_invoice_lifecycle() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register_contract(None, QuickLendXContract);
     let client = QuickLendXContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
 
     let business = Address::generate(&env);
     let currency = Address::generate(&env);
     let due_date = env.ledger().timestamp() + 86400;
 
+    verify_kyc(&env, &client, &admin, &business);
     let invoice_id = client.store_invoice(
         &business,
         &1000,
@@ -296,11 +337,11 @@ fn test_invoice_lifecycle() {
     let mut invoice = client.get_invoice(&invoice_id);
     assert_eq!(invoice.status, InvoiceStatus::Pending);
 
-    client.update_invoice_status(&invoice_id, &InvoiceStatus::Verified);
+    client.update_invoice_status(&admin, &invoice_id, &InvoiceStatus::Verified);
     invoice = client.get_invoice(&invoice_id);
     assert_eq!(invoice.status, InvoiceStatus::Verified);
 
-    client.update_invoice_status(&invoice_id, &InvoiceStatus::Paid);
+    client.update_invoice_status(&admin, &invoice_id, &InvoiceStatus::Paid);
     invoice = client.get_invoice(&invoice_id);
     assert_eq!(invoice.status, InvoiceStatus::Paid);
     assert!(invoice.settled_at.is_some());
@@ -312,6 +353,8 @@ fn test_simple_bid_storage() {
     env.mock_all_auths();
     let contract_id = env.register(QuickLendXContract, ());
     let client = QuickLendXContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
 
     let business = Address::generate(&env);
     let investor = Address::generate(&env);
@@ -319,6 +362,7 @@ fn test_simple_bid_storage() {
     let due_date = env.ledger().timestamp() + 86400;
 
     // Create and verify invoice
+    verify_kyc(&env, &client, &admin, &business);
     let invoice_id = client.store_invoice(
         &business,
         &1000,
@@ -327,7 +371,7 @@ fn test_simple_bid_storage() {
         &String::from_str(&env, "Test invoice"),
     );
 
-    client.update_invoice_status(&invoice_id, &InvoiceStatus::Verified);
+    client.update_invoice_status(&admin, &invoice_id, &InvoiceStatus::Verified);
 
     // Place a single bid to test basic functionality
     let bid_id = client.place_bid(&investor, &invoice_id, &1001, &1100);
@@ -364,6 +408,8 @@ fn test_unique_bid_id_generation() {
     env.mock_all_auths();
     let contract_id = env.register(QuickLendXContract, ());
     let client = QuickLendXContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
 
     let business = Address::generate(&env);
     let investor = Address::generate(&env);
@@ -371,6 +417,7 @@ fn test_unique_bid_id_generation() {
     let due_date = env.ledger().timestamp() + 86400;
 
     // Create and verify invoice
+    verify_kyc(&env, &client, &admin, &business);
     let invoice_id = client.store_invoice(
         &business,
         &1000,
@@ -379,7 +426,7 @@ fn test_unique_bid_id_generation() {
         &String::from_str(&env, "Test invoice"),
     );
 
-    client.update_invoice_status(&invoice_id, &InvoiceStatus::Verified);
+    client.update_invoice_status(&admin, &invoice_id, &InvoiceStatus::Verified);
 
     // Place first bid
     let bid_id_1 = client.place_bid(&investor, &invoice_id, &1001, &1100);
@@ -401,6 +448,8 @@ fn test_escrow_creation_on_bid_acceptance() {
     env.mock_all_auths();
     let contract_id = env.register(QuickLendXContract, ());
     let client = QuickLendXContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
 
     let business = Address::generate(&env);
     let investor = Address::generate(&env);
@@ -409,6 +458,7 @@ fn test_escrow_creation_on_bid_acceptance() {
     let bid_amount = 1000i128;
 
     // Create and verify invoice
+    verify_kyc(&env, &client, &admin, &business);
     let invoice_id = client.store_invoice(
         &business,
         &bid_amount,
@@ -416,13 +466,13 @@ fn test_escrow_creation_on_bid_acceptance() {
         &due_date,
         &String::from_str(&env, "Test invoice"),
     );
-    client.update_invoice_status(&invoice_id, &InvoiceStatus::Verified);
+    client.update_invoice_status(&admin, &invoice_id, &InvoiceStatus::Verified);
 
     // Place bid
     let bid_id = client.place_bid(&investor, &invoice_id, &bid_amount, &1100);
 
     // Accept bid (should create escrow)
-    client.accept_bid(&invoice_id, &bid_id);
+    client.accept_bid(&business, &invoice_id, &bid_id);
 
     // Verify escrow was created
     let escrow_details = client.get_escrow_details(&invoice_id);
@@ -444,6 +494,8 @@ fn test_escrow_release_on_verification() {
     env.mock_all_auths();
     let contract_id = env.register(QuickLendXContract, ());
     let client = QuickLendXContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
 
     let business = Address::generate(&env);
     let investor = Address::generate(&env);
@@ -452,6 +504,7 @@ fn test_escrow_release_on_verification() {
     let bid_amount = 1000i128;
 
     // Create invoice
+    verify_kyc(&env, &client, &admin, &business);
     let invoice_id = client.store_invoice(
         &business,
         &bid_amount,
@@ -459,18 +512,18 @@ fn test_escrow_release_on_verification() {
         &due_date,
         &String::from_str(&env, "Test invoice"),
     );
-    client.update_invoice_status(&invoice_id, &InvoiceStatus::Verified);
+    client.update_invoice_status(&admin, &invoice_id, &InvoiceStatus::Verified);
 
     // Place and accept bid (creates escrow)
     let bid_id = client.place_bid(&investor, &invoice_id, &bid_amount, &1100);
-    client.accept_bid(&invoice_id, &bid_id);
+    client.accept_bid(&business, &invoice_id, &bid_id);
 
     // Verify escrow is held
     let escrow_status = client.get_escrow_status(&invoice_id);
     assert_eq!(escrow_status, crate::payments::EscrowStatus::Held);
 
     // Release escrow funds
-    client.release_escrow_funds(&invoice_id);
+    client.release_escrow_funds(&admin, &invoice_id);
 
     // Verify escrow is released
     let escrow_status = client.get_escrow_status(&invoice_id);
@@ -483,6 +536,8 @@ fn test_escrow_refund() {
     env.mock_all_auths();
     let contract_id = env.register(QuickLendXContract, ());
     let client = QuickLendXContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
 
     let business = Address::generate(&env);
     let investor = Address::generate(&env);
@@ -491,6 +546,7 @@ fn test_escrow_refund() {
     let bid_amount = 1000i128;
 
     // Create invoice
+    verify_kyc(&env, &client, &admin, &business);
     let invoice_id = client.store_invoice(
         &business,
         &bid_amount,
@@ -498,30 +554,96 @@ fn test_escrow_refund() {
         &due_date,
         &String::from_str(&env, "Test invoice"),
     );
-    client.update_invoice_status(&invoice_id, &InvoiceStatus::Verified);
+    client.update_invoice_status(&admin, &invoice_id, &InvoiceStatus::Verified);
 
     // Place and accept bid (creates escrow)
     let bid_id = client.place_bid(&investor, &invoice_id, &bid_amount, &1100);
-    client.accept_bid(&invoice_id, &bid_id);
+    client.accept_bid(&business, &invoice_id, &bid_id);
 
     // Verify escrow is held
     let escrow_status = client.get_escrow_status(&invoice_id);
     assert_eq!(escrow_status, crate::payments::EscrowStatus::Held);
 
     // Refund escrow funds
-    client.refund_escrow_funds(&invoice_id);
+    client.refund_escrow_funds(&admin, &invoice_id);
 
     // Verify escrow is refunded
     let escrow_status = client.get_escrow_status(&invoice_id);
     assert_eq!(escrow_status, crate::payments::EscrowStatus::Refunded);
 }
 
+#[test]
+fn test_escrow_ledger_records_fund_movements() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let business = Address::generate(&env);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86400;
+    let bid_amount = 1000i128;
+
+    verify_kyc(&env, &client, &admin, &business);
+    let invoice_id = client.store_invoice(
+        &business,
+        &bid_amount,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Test invoice"),
+    );
+    client.update_invoice_status(&admin, &invoice_id, &InvoiceStatus::Verified);
+    let bid_id = client.place_bid(&investor, &invoice_id, &bid_amount, &1100);
+    client.accept_bid(&business, &invoice_id, &bid_id);
+    let escrow_id = client.get_escrow_details(&invoice_id).escrow_id;
+
+    // Deposit is recorded when the escrow is created
+    let ledger = client.get_escrow_ledger(&escrow_id);
+    assert_eq!(ledger.len(), 1);
+    let deposit = ledger.get(0).unwrap();
+    assert_eq!(deposit.direction, crate::payments::EscrowDirection::Deposit);
+    assert_eq!(deposit.amount, bid_amount);
+    assert_eq!(deposit.from, investor);
+    assert_eq!(deposit.actor, business);
+
+    // Release appends a second entry chained to the first
+    client.release_escrow_funds(&admin, &invoice_id);
+    let ledger = client.get_escrow_ledger(&escrow_id);
+    assert_eq!(ledger.len(), 2);
+    let release = ledger.get(1).unwrap();
+    assert_eq!(release.direction, crate::payments::EscrowDirection::Release);
+    assert_eq!(release.sequence, 1);
+    assert_eq!(release.to, business);
+    assert_eq!(release.actor, admin);
+    assert_eq!(release.prev_hash, deposit.entry_hash);
+    assert!(client.verify_escrow_ledger(&escrow_id));
+
+    // Rewriting a past entry breaks the hash chain
+    env.as_contract(&contract_id, || {
+        let mut ledger = crate::payments::EscrowStorage::get_ledger(&env, &escrow_id);
+        let mut entry = ledger.get(0).unwrap();
+        entry.amount = 1;
+        ledger.set(0, entry);
+        crate::storage::set(
+            &env,
+            &crate::storage::DataKey::EscrowLedger(escrow_id.clone()),
+            &ledger,
+        );
+    });
+    assert!(!client.verify_escrow_ledger(&escrow_id));
+}
+
 #[test]
 fn test_escrow_status_tracking() {
     let env = Env::default();
     env.mock_all_auths();
     let contract_id = env.register(QuickLendXContract, ());
     let client = QuickLendXContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
 
     let business = Address::generate(&env);
     let investor = Address::generate(&env);
@@ -530,6 +652,7 @@ fn test_escrow_status_tracking() {
     let bid_amount = 1000i128;
 
     // Create and verify invoice
+    verify_kyc(&env, &client, &admin, &business);
     let invoice_id = client.store_invoice(
         &business,
         &bid_amount,
@@ -537,11 +660,11 @@ fn test_escrow_status_tracking() {
         &due_date,
         &String::from_str(&env, "Test invoice"),
     );
-    client.update_invoice_status(&invoice_id, &InvoiceStatus::Verified);
+    client.update_invoice_status(&admin, &invoice_id, &InvoiceStatus::Verified);
 
     // Place and accept bid
     let bid_id = client.place_bid(&investor, &invoice_id, &bid_amount, &1100);
-    client.accept_bid(&invoice_id, &bid_id);
+    client.accept_bid(&business, &invoice_id, &bid_id);
 
     // Test escrow details
     let escrow_details = client.get_escrow_details(&invoice_id);
@@ -550,7 +673,7 @@ fn test_escrow_status_tracking() {
     assert_eq!(escrow_details.amount, bid_amount);
 
     // Test status progression: Held -> Released
-    client.release_escrow_funds(&invoice_id);
+    client.release_escrow_funds(&admin, &invoice_id);
     let escrow_details = client.get_escrow_details(&invoice_id);
     assert_eq!(
         escrow_details.status,
@@ -564,6 +687,8 @@ fn test_escrow_error_cases() {
     env.mock_all_auths();
     let contract_id = env.register(QuickLendXContract, ());
     let client = QuickLendXContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
 
     let fake_invoice_id = BytesN::from_array(&env, &[1u8; 32]);
 
@@ -575,11 +700,11 @@ fn test_escrow_error_cases() {
     assert!(result.is_err());
 
     // Test releasing escrow for non-existent invoice
-    let result = client.try_release_escrow_funds(&fake_invoice_id);
+    let result = client.try_release_escrow_funds(&admin, &fake_invoice_id);
     assert!(result.is_err());
 
     // Test refunding escrow for non-existent invoice
-    let result = client.try_refund_escrow_funds(&fake_invoice_id);
+    let result = client.try_refund_escrow_funds(&admin, &fake_invoice_id);
     assert!(result.is_err());
 }
 
@@ -589,6 +714,8 @@ fn test_escrow_double_operation_prevention() {
     env.mock_all_auths();
     let contract_id = env.register(QuickLendXContract, ());
     let client = QuickLendXContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
 
     let business = Address::generate(&env);
     let investor = Address::generate(&env);
@@ -597,6 +724,7 @@ fn test_escrow_double_operation_prevention() {
     let bid_amount = 1000i128;
 
     // Create and verify invoice
+    verify_kyc(&env, &client, &admin, &business);
     let invoice_id = client.store_invoice(
         &business,
         &bid_amount,
@@ -604,21 +732,21 @@ fn test_escrow_double_operation_prevention() {
         &due_date,
         &String::from_str(&env, "Test invoice"),
     );
-    client.update_invoice_status(&invoice_id, &InvoiceStatus::Verified);
+    client.update_invoice_status(&admin, &invoice_id, &InvoiceStatus::Verified);
 
     // Place and accept bid
     let bid_id = client.place_bid(&investor, &invoice_id, &bid_amount, &1100);
-    client.accept_bid(&invoice_id, &bid_id);
+    client.accept_bid(&business, &invoice_id, &bid_id);
 
     // Release escrow funds
-    client.release_escrow_funds(&invoice_id);
+    client.release_escrow_funds(&admin, &invoice_id);
 
     // Try to release again (should fail)
-    let result = client.try_release_escrow_funds(&invoice_id);
+    let result = client.try_release_escrow_funds(&admin, &invoice_id);
     assert!(result.is_err());
 
     // Try to refund after release (should fail)
-    let result = client.try_refund_escrow_funds(&invoice_id);
+    let result = client.try_refund_escrow_funds(&admin, &invoice_id);
     assert!(result.is_err());
 }
 
@@ -650,8 +778,11 @@ fn test_unique_investment_id_generation() {
 #[test]
 fn test_add_invoice_rating() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register_contract(None, QuickLendXContract);
     let client = QuickLendXContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
 
     let business = Address::generate(&env);
     let investor = Address::generate(&env);
@@ -659,6 +790,7 @@ fn test_add_invoice_rating() {
     let due_date = env.ledger().timestamp() + 86400;
 
     // Create and fund an invoice
+    verify_kyc(&env, &client, &admin, &business);
     let invoice_id = client.store_invoice(
         &business,
         &1000,
@@ -668,7 +800,7 @@ fn test_add_invoice_rating() {
     );
 
     // Verify the invoice
-    client.update_invoice_status(&invoice_id, &InvoiceStatus::Verified);
+    client.update_invoice_status(&admin, &invoice_id, &InvoiceStatus::Verified);
 
     // Fund the invoice properly
     env.as_contract(&contract_id, || {
@@ -703,6 +835,7 @@ fn test_add_invoice_rating() {
 #[test]
 fn test_add_invoice_rating_validation() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register_contract(None, QuickLendXContract);
     let client = QuickLendXContractClient::new(&env, &contract_id);
 
@@ -712,6 +845,9 @@ fn test_add_invoice_rating_validation() {
     let due_date = env.ledger().timestamp() + 86400;
 
     // Create invoice
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    verify_kyc(&env, &client, &admin, &business);
     let invoice_id = client.store_invoice(
         &business,
         &1000,
@@ -767,6 +903,7 @@ fn test_add_invoice_rating_validation() {
 #[test]
 fn test_multiple_ratings() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register_contract(None, QuickLendXContract);
     let client = QuickLendXContractClient::new(&env, &contract_id);
 
@@ -776,6 +913,9 @@ fn test_multiple_ratings() {
     let due_date = env.ledger().timestamp() + 86400;
 
     // Create and fund invoice
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    verify_kyc(&env, &client, &admin, &business);
     let invoice_id = client.store_invoice(
         &business,
         &1000,
@@ -815,6 +955,7 @@ fn test_multiple_ratings() {
 #[test]
 fn test_duplicate_rating_prevention() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register_contract(None, QuickLendXContract);
     let client = QuickLendXContractClient::new(&env, &contract_id);
 
@@ -824,6 +965,9 @@ fn test_duplicate_rating_prevention() {
     let due_date = env.ledger().timestamp() + 86400;
 
     // Create and fund invoice
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    verify_kyc(&env, &client, &admin, &business);
     let invoice_id = client.store_invoice(
         &business,
         &1000,
@@ -873,6 +1017,7 @@ fn test_duplicate_rating_prevention() {
 #[test]
 fn test_rating_queries() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register_contract(None, QuickLendXContract);
     let client = QuickLendXContractClient::new(&env, &contract_id);
 
@@ -881,6 +1026,9 @@ fn test_rating_queries() {
     let due_date = env.ledger().timestamp() + 86400;
 
     // Create and fund a single invoice first
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    verify_kyc(&env, &client, &admin, &business1);
     let invoice1_id = client.store_invoice(
         &business1,
         &1000,
@@ -939,6 +1087,7 @@ fn test_rating_queries() {
 #[test]
 fn test_rating_statistics() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register_contract(None, QuickLendXContract);
     let client = QuickLendXContractClient::new(&env, &contract_id);
 
@@ -948,6 +1097,9 @@ fn test_rating_statistics() {
     let due_date = env.ledger().timestamp() + 86400;
 
     // Create and fund invoice
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    verify_kyc(&env, &client, &admin, &business);
     let invoice_id = client.store_invoice(
         &business,
         &1000,
@@ -988,6 +1140,7 @@ fn test_rating_statistics() {
 #[test]
 fn test_rating_on_unfunded_invoice() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register_contract(None, QuickLendXContract);
     let client = QuickLendXContractClient::new(&env, &contract_id);
 
@@ -996,6 +1149,9 @@ fn test_rating_on_unfunded_invoice() {
     let due_date = env.ledger().timestamp() + 86400;
 
     // Create invoice but don't fund it
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    verify_kyc(&env, &client, &admin, &business);
     let invoice_id = client.store_invoice(
         &business,
         &1000,
@@ -1054,10 +1210,10 @@ fn test_verify_business() {
     let kyc_data = String::from_str(&env, "Business registration documents");
 
     // Set admin
-    client.set_admin(&admin);
+    env.mock_all_auths();
+    client.initialize(&admin);
 
     // Submit KYC application
-    env.mock_all_auths();
     client.submit_kyc_application(&business, &kyc_data);
 
     // Verify business
@@ -1088,10 +1244,10 @@ fn test_reject_business() {
     let rejection_reason = String::from_str(&env, "Incomplete documentation");
 
     // Set admin
-    client.set_admin(&admin);
+    env.mock_all_auths();
+    client.initialize(&admin);
 
     // Submit KYC application
-    env.mock_all_auths();
     client.submit_kyc_application(&business, &kyc_data);
 
     // Reject business
@@ -1132,7 +1288,7 @@ fn test_upload_invoice_requires_verification() {
     let admin = Address::generate(&env);
     let kyc_data = String::from_str(&env, "Business registration documents");
 
-    client.set_admin(&admin);
+    client.initialize(&admin);
     env.mock_all_auths();
     client.submit_kyc_application(&business, &kyc_data);
 
@@ -1175,8 +1331,8 @@ fn test_kyc_already_verified() {
     let kyc_data = String::from_str(&env, "Business registration documents");
 
     // Set admin and submit KYC
-    client.set_admin(&admin);
     env.mock_all_auths();
+    client.initialize(&admin);
     client.submit_kyc_application(&business, &kyc_data);
 
     // Verify business
@@ -1200,8 +1356,8 @@ fn test_kyc_resubmission_after_rejection() {
     let rejection_reason = String::from_str(&env, "Incomplete documentation");
 
     // Set admin and submit KYC
-    client.set_admin(&admin);
     env.mock_all_auths();
+    client.initialize(&admin);
     client.submit_kyc_application(&business, &kyc_data);
 
     // Reject business
@@ -1235,10 +1391,10 @@ fn test_verification_unauthorized_access() {
     let unauthorized_admin = Address::generate(&env);
 
     // Set admin
-    client.set_admin(&admin);
+    env.mock_all_auths();
+    client.initialize(&admin);
 
     // Submit KYC application
-    env.mock_all_auths();
     let kyc_data = String::from_str(&env, "Business registration documents");
     client.submit_kyc_application(&business, &kyc_data);
 
@@ -1260,10 +1416,10 @@ fn test_get_verification_lists() {
     let business3 = Address::generate(&env);
 
     // Set admin
-    client.set_admin(&admin);
+    env.mock_all_auths();
+    client.initialize(&admin);
 
     // Submit KYC applications
-    env.mock_all_auths();
     let kyc_data = String::from_str(&env, "Business registration documents");
     client.submit_kyc_application(&business1, &kyc_data);
     client.submit_kyc_application(&business2, &kyc_data);
@@ -1291,18 +1447,20 @@ fn test_get_verification_lists() {
 #[test]
 fn test_create_and_restore_backup() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register_contract(None, QuickLendXContract);
     let client = QuickLendXContractClient::new(&env, &contract_id);
 
     // Set up admin
     let admin = Address::generate(&env);
-    client.set_admin(&admin);
+    client.initialize(&admin);
 
     // Create test invoices
     let business = Address::generate(&env);
     let currency = Address::generate(&env);
     let due_date = env.ledger().timestamp() + 86400;
 
+    verify_kyc(&env, &client, &admin, &business);
     let invoice1_id = client.store_invoice(
         &business,
         &1000,
@@ -1321,7 +1479,7 @@ fn test_create_and_restore_backup() {
 
     // Create backup
     env.mock_all_auths();
-    let backup_id = client.create_backup(&String::from_str(&env, "Initial backup"));
+    let backup_id = client.create_backup(&admin, &String::from_str(&env, "Initial backup"));
 
     // Verify backup was created
     let backup = client.get_backup_details(&backup_id);
@@ -1342,7 +1500,7 @@ fn test_create_and_restore_backup() {
 
     // Restore backup
     env.mock_all_auths();
-    client.restore_backup(&backup_id);
+    client.restore_backup(&admin, &backup_id);
 
     // Verify invoices are back
     let invoice1 = client.get_invoice(&invoice1_id);
@@ -1354,18 +1512,20 @@ fn test_create_and_restore_backup() {
 #[test]
 fn test_backup_validation() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register_contract(None, QuickLendXContract);
     let client = QuickLendXContractClient::new(&env, &contract_id);
 
     // Set up admin
     let admin = Address::generate(&env);
-    client.set_admin(&admin);
+    client.initialize(&admin);
 
     // Create test invoice
     let business = Address::generate(&env);
     let currency = Address::generate(&env);
     let due_date = env.ledger().timestamp() + 86400;
 
+    verify_kyc(&env, &client, &admin, &business);
     client.store_invoice(
         &business,
         &1000,
@@ -1376,7 +1536,7 @@ fn test_backup_validation() {
 
     // Create backup
     env.mock_all_auths();
-    let backup_id = client.create_backup(&String::from_str(&env, "Test backup"));
+    let backup_id = client.create_backup(&admin, &String::from_str(&env, "Test backup"));
 
     // Validate backup
     let is_valid = client.validate_backup(&backup_id);
@@ -1402,10 +1562,10 @@ fn test_backup_cleanup() {
 
     // Set up admin
     let admin = Address::generate(&env);
-    client.set_admin(&admin);
+    env.mock_all_auths();
+    client.initialize(&admin);
 
     // Create multiple backups with simple descriptions
-    env.mock_all_auths();
     for i in 0..10 {
         let description = if i == 0 {
             String::from_str(&env, "Backup 0")
@@ -1415,7 +1575,7 @@ fn test_backup_cleanup() {
             // Continue this pattern or just use a generic description
             String::from_str(&env, "Backup")
         };
-        client.create_backup(&description);
+        client.create_backup(&admin, &description);
     }
 
     // Verify only last 5 backups are kept
@@ -1431,14 +1591,14 @@ fn test_archive_backup() {
 
     // Set up admin
     let admin = Address::generate(&env);
-    client.set_admin(&admin);
+    env.mock_all_auths();
+    client.initialize(&admin);
 
     // Create backup
-    env.mock_all_auths();
-    let backup_id = client.create_backup(&String::from_str(&env, "Test backup"));
+    let backup_id = client.create_backup(&admin, &String::from_str(&env, "Test backup"));
 
     // Archive backup
-    client.archive_backup(&backup_id);
+    client.archive_backup(&admin, &backup_id);
 
     // Verify backup is archived
     let backup = client.get_backup_details(&backup_id);
@@ -1450,13 +1610,38 @@ fn test_archive_backup() {
     assert!(!backups.contains(&backup_id));
 }
 
+fn verified_business(env: &Env, client: &QuickLendXContractClient) -> (Address, Address) {
+    let admin = Address::generate(env);
+    let business = Address::generate(env);
+    client.initialize(&admin);
+    client.submit_kyc_application(&business, &String::from_str(env, "KYC data"));
+    client.verify_business(&admin, &business);
+    (admin, business)
+}
+
+/// Verify `business` so it can create invoices
+fn verify_kyc(env: &Env, client: &QuickLendXContractClient, admin: &Address, business: &Address) {
+    client.submit_kyc_application(business, &String::from_str(env, "KYC data"));
+    client.verify_business(admin, business);
+}
+
+/// Register a token and mint `amount` of it to `holder`
+fn funded_token(env: &Env, holder: &Address, amount: i128) -> Address {
+    let token = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    token::StellarAssetClient::new(env, &token).mint(holder, &amount);
+    token
+}
+
 #[test]
 fn test_audit_trail_creation() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register_contract(None, QuickLendXContract);
     let client = QuickLendXContractClient::new(&env, &contract_id);
     
-    let business = Address::generate(&env);
+    let (_, business) = verified_business(&env, &client);
     let amount = 1000i128;
     let currency = Address::generate(&env);
     let due_date = env.ledger().timestamp() + 86400;
@@ -1474,15 +1659,20 @@ fn test_audit_trail_creation() {
     assert_eq!(audit_entry.invoice_id, invoice_id);
     assert_eq!(audit_entry.operation, AuditOperation::InvoiceCreated);
     assert_eq!(audit_entry.actor, business);
+    assert_eq!(
+        audit_entry.new_value,
+        Some(String::from_str(&env, "Amount: 1000, Due: 86400"))
+    );
 }
 
 #[test]
 fn test_audit_integrity_validation() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register_contract(None, QuickLendXContract);
     let client = QuickLendXContractClient::new(&env, &contract_id);
     
-    let business = Address::generate(&env);
+    let (admin, business) = verified_business(&env, &client);
     let amount = 1000i128;
     let currency = Address::generate(&env);
     let due_date = env.ledger().timestamp() + 86400;
@@ -1490,7 +1680,7 @@ fn test_audit_integrity_validation() {
     
     // Upload and verify invoice
     let invoice_id = client.upload_invoice(&business, &amount, &currency, &due_date, &description);
-    client.verify_invoice(&invoice_id);
+    client.verify_invoice(&admin, &invoice_id);
     
     // Validate audit integrity
     let is_valid = client.validate_invoice_audit_integrity(&invoice_id);
@@ -1500,10 +1690,11 @@ fn test_audit_integrity_validation() {
 #[test]
 fn test_audit_query_functionality() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register_contract(None, QuickLendXContract);
     let client = QuickLendXContractClient::new(&env, &contract_id);
     
-    let business = Address::generate(&env);
+    let (_, business) = verified_business(&env, &client);
     let amount = 1000i128;
     let currency = Address::generate(&env);
     let due_date = env.ledger().timestamp() + 86400;
@@ -1511,30 +1702,31 @@ fn test_audit_query_functionality() {
     
     // Create multiple invoices
     let invoice_id1 = client.upload_invoice(&business, &amount, &currency, &due_date, &description);
-    let invoice_id2 = client.upload_invoice(&business, &amount * 2, &currency, &due_date, &description);
+    let _invoice_id2 =
+        client.upload_invoice(&business, &(amount * 2), &currency, &due_date, &description);
     
     // Query by operation type
     let filter = AuditQueryFilter {
         invoice_id: None,
-        operation: Some(AuditOperation::InvoiceCreated),
+        operation: AuditOperationFilter::Specific(AuditOperation::InvoiceCreated),
         actor: None,
         start_timestamp: None,
         end_timestamp: None,
     };
     
-    let results = client.query_audit_logs(&filter, 10);
+    let results = client.query_audit_logs(&filter, &10);
     assert_eq!(results.len(), 2);
     
     // Query by specific invoice
     let filter = AuditQueryFilter {
-        invoice_id: Some(invoice_id1),
-        operation: None,
+        invoice_id: Some(invoice_id1.clone()),
+        operation: AuditOperationFilter::Any,
         actor: None,
         start_timestamp: None,
         end_timestamp: None,
     };
     
-    let results = client.query_audit_logs(&filter, 10);
+    let results = client.query_audit_logs(&filter, &10);
     assert!(!results.is_empty());
     assert_eq!(results.get(0).unwrap().invoice_id, invoice_id1);
 }
@@ -1542,10 +1734,11 @@ fn test_audit_query_functionality() {
 #[test]
 fn test_audit_statistics() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register_contract(None, QuickLendXContract);
     let client = QuickLendXContractClient::new(&env, &contract_id);
     
-    let business = Address::generate(&env);
+    let (admin, business) = verified_business(&env, &client);
     let amount = 1000i128;
     let currency = Address::generate(&env);
     let due_date = env.ledger().timestamp() + 86400;
@@ -1553,10 +1746,7259 @@ fn test_audit_statistics() {
     
     // Create and process invoices
     let invoice_id = client.upload_invoice(&business, &amount, &currency, &due_date, &description);
-    client.verify_invoice(&invoice_id);
+    client.verify_invoice(&admin, &invoice_id);
     
     // Get audit statistics
     let stats = client.get_audit_stats();
     assert!(stats.total_entries > 0);
     assert!(stats.unique_actors > 0);
 }
+
+#[test]
+fn test_partial_funding_by_multiple_investors() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let business = Address::generate(&env);
+    let investor1 = Address::generate(&env);
+    let investor2 = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86400;
+
+    verify_kyc(&env, &client, &admin, &business);
+    let invoice_id = client.store_invoice(
+        &business,
+        &1000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Syndicated invoice"),
+    );
+    client.update_invoice_status(&admin, &invoice_id, &InvoiceStatus::Verified);
+
+    let bid1 = client.place_bid(&investor1, &invoice_id, &600, &660);
+    let bid2 = client.place_bid(&investor2, &invoice_id, &400, &440);
+    let oversized = client.place_bid(&investor2, &invoice_id, &500, &550);
+
+    // First acceptance only partially funds the invoice
+    client.accept_bid(&business, &invoice_id, &bid1);
+    let invoice = client.get_invoice(&invoice_id);
+    assert_eq!(invoice.status, InvoiceStatus::Verified);
+    assert_eq!(invoice.funded_amount, 600);
+    assert!(client.get_available_invoices().contains(&invoice_id));
+
+    // A bid larger than the remaining amount cannot be accepted
+    assert!(client.try_accept_bid(&business, &invoice_id, &oversized).is_err());
+
+    // Second acceptance completes the funding
+    client.accept_bid(&business, &invoice_id, &bid2);
+    let invoice = client.get_invoice(&invoice_id);
+    assert_eq!(invoice.status, InvoiceStatus::Funded);
+    assert_eq!(invoice.funded_amount, 1000);
+    assert_eq!(invoice.investor, Some(investor1.clone()));
+    assert_eq!(invoice.investors.len(), 2);
+    assert!(!client.get_available_invoices().contains(&invoice_id));
+    assert!(client
+        .get_invoices_by_status(&InvoiceStatus::Funded)
+        .contains(&invoice_id));
+
+    let investments = client.get_invoice_investments(&invoice_id);
+    assert_eq!(investments.len(), 2);
+    assert_eq!(investments.get(0).unwrap().investor, investor1);
+    assert_eq!(investments.get(0).unwrap().amount, 600);
+    assert_eq!(investments.get(1).unwrap().investor, investor2);
+    assert_eq!(investments.get(1).unwrap().amount, 400);
+}
+
+#[test]
+fn test_pro_rata_settlement() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let business = Address::generate(&env);
+    let investor1 = Address::generate(&env);
+    let investor2 = Address::generate(&env);
+    let platform = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86400;
+
+    verify_kyc(&env, &client, &admin, &business);
+    let invoice_id = client.store_invoice(
+        &business,
+        &1000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Syndicated invoice"),
+    );
+    client.update_invoice_status(&admin, &invoice_id, &InvoiceStatus::Verified);
+    let bid1 = client.place_bid(&investor1, &invoice_id, &700, &770);
+    let bid2 = client.place_bid(&investor2, &invoice_id, &300, &330);
+    client.accept_bid(&business, &invoice_id, &bid1);
+    client.accept_bid(&business, &invoice_id, &bid2);
+
+    client.set_platform_fee(&admin, &platform, &200);
+    client.settle_invoice(&business, &invoice_id, &1100);
+
+    let invoice = client.get_invoice(&invoice_id);
+    assert_eq!(invoice.status, InvoiceStatus::Paid);
+    assert!(client
+        .get_invoices_by_status(&InvoiceStatus::Paid)
+        .contains(&invoice_id));
+
+    for investment in client.get_invoice_investments(&invoice_id).iter() {
+        assert_eq!(investment.status, InvestmentStatus::Completed);
+    }
+
+    // Shares of the payment follow the funded amounts
+    env.as_contract(&contract_id, || {
+        let mut investments = Vec::new(&env);
+        for id in InvestmentStorage::get_investments_for_invoice(&env, &invoice_id).iter() {
+            investments.push_back(InvestmentStorage::get_investment(&env, &id).unwrap());
+        }
+        let shares = crate::investment::pro_rata_shares(&env, &investments, 1100);
+        assert_eq!(shares.get(0).unwrap(), 770);
+        assert_eq!(shares.get(1).unwrap(), 330);
+    });
+}
+
+#[test]
+fn test_currency_whitelist() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let business = Address::generate(&env);
+    let usdc = Address::generate(&env);
+    let other = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86400;
+    client.initialize(&admin);
+
+    // Without a whitelist any currency is accepted
+    assert!(client.get_supported_currencies().is_empty());
+    verify_kyc(&env, &client, &admin, &business);
+    client.store_invoice(
+        &business,
+        &1000,
+        &other,
+        &due_date,
+        &String::from_str(&env, "Any"),
+    );
+
+    client.add_supported_currency(&admin, &usdc);
+    client.add_supported_currency(&admin, &usdc);
+    assert_eq!(client.get_supported_currencies(), vec![&env, usdc.clone()]);
+
+    // Only whitelisted currencies are accepted once configured
+    client.store_invoice(
+        &business,
+        &1000,
+        &usdc,
+        &due_date,
+        &String::from_str(&env, "USDC"),
+    );
+    let result = client.try_store_invoice(
+        &business,
+        &1000,
+        &other,
+        &due_date,
+        &String::from_str(&env, "X"),
+    );
+    assert_eq!(result, Err(Ok(QuickLendXError::InvalidCurrency)));
+
+    // upload_invoice runs the same check
+    let result = client.try_upload_invoice(
+        &business,
+        &1000,
+        &other,
+        &due_date,
+        &String::from_str(&env, "X"),
+    );
+    assert_eq!(result, Err(Ok(QuickLendXError::InvalidCurrency)));
+
+    // Only the admin can manage the whitelist
+    let stranger = Address::generate(&env);
+    assert!(client
+        .try_add_supported_currency(&stranger, &other)
+        .is_err());
+
+    // The whitelist cannot be emptied, which would reopen it to any token
+    assert_eq!(
+        client.try_remove_supported_currency(&admin, &usdc),
+        Err(Ok(QuickLendXError::LastCurrency))
+    );
+    client.add_supported_currency(&admin, &other);
+    client.remove_supported_currency(&admin, &usdc);
+    assert_eq!(client.get_supported_currencies(), vec![&env, other.clone()]);
+    assert_eq!(
+        client.try_remove_supported_currency(&admin, &usdc),
+        Err(Ok(QuickLendXError::InvalidCurrency))
+    );
+}
+
+#[test]
+fn test_jurisdiction_rules() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let business = Address::generate(&env);
+    let local_investor = Address::generate(&env);
+    let foreign_investor = Address::generate(&env);
+    let usdc = Address::generate(&env);
+    let other = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 365 * 86400;
+    client.initialize(&admin);
+
+    let us = Symbol::new(&env, "US");
+    let eu = Symbol::new(&env, "EU");
+    client.set_jurisdiction(&admin, &business, &us);
+    client.set_jurisdiction(&admin, &local_investor, &us);
+    client.set_jurisdiction(&admin, &foreign_investor, &eu);
+    assert_eq!(client.get_jurisdiction(&business), Some(us.clone()));
+
+    let rule = crate::jurisdiction::JurisdictionRule {
+        code: us.clone(),
+        max_apr_bps: 1_000,
+        allowed_currencies: vec![&env, usdc.clone()],
+        investor_jurisdictions: vec![&env, us.clone()],
+    };
+    client.set_jurisdiction_rule(&admin, &rule);
+    assert_eq!(client.get_jurisdiction_rule(&us), Some(rule));
+    assert_eq!(client.get_jurisdiction_codes(), vec![&env, us.clone()]);
+
+    // Upload path: currency must be allowed in the business's jurisdiction
+    verify_kyc(&env, &client, &admin, &business);
+    let result = client.try_store_invoice(
+        &business,
+        &1000,
+        &other,
+        &due_date,
+        &String::from_str(&env, "Invoice"),
+    );
+    assert_eq!(result, Err(Ok(QuickLendXError::InvalidCurrency)));
+    let invoice_id = client.store_invoice(
+        &business,
+        &1000,
+        &usdc,
+        &due_date,
+        &String::from_str(&env, "Invoice"),
+    );
+    client.update_invoice_status(&admin, &invoice_id, &InvoiceStatus::Verified);
+
+    // Bid path: investor eligibility and APR cap
+    let result = client.try_place_bid(&foreign_investor, &invoice_id, &1000, &1050);
+    assert_eq!(result, Err(Ok(QuickLendXError::JurisdictionNotAllowed)));
+    let result = client.try_place_bid(&local_investor, &invoice_id, &1000, &1200);
+    assert_eq!(result, Err(Ok(QuickLendXError::AprLimitExceeded)));
+    let fee_recipient = Address::generate(&env);
+    client.set_platform_fee(&admin, &fee_recipient, &1_000);
+    let bid_id = client.place_bid(&local_investor, &invoice_id, &1000, &1090);
+    client.accept_bid(&business, &invoice_id, &bid_id);
+
+    // Settlement path: investor returns above the cap are not collected. The
+    // cap is priced over the tenor to the due date, so early repayment does
+    // not shrink it, and the platform fee is left alone.
+    env.ledger().with_mut(|l| l.timestamp += 30 * 86400);
+    let invoice = client.get_invoice(&invoice_id);
+    env.as_contract(&contract_id, || {
+        let capped = crate::jurisdiction::cap_investor_return(&env, &invoice, 1000, 1450);
+        assert_eq!(capped, 1100);
+    });
+    let plan = client.build_settlement_plan(&invoice_id, &1500);
+    assert_eq!(plan.total_investor_return, 1100);
+    assert_eq!(plan.total_platform_fee, 50);
+    assert_eq!(plan.payment_amount, 1150);
+    client.settle_invoice(&business, &invoice_id, &1500);
+    assert_eq!(client.get_invoice(&invoice_id).status, InvoiceStatus::Paid);
+
+    client.remove_jurisdiction_rule(&admin, &us);
+    assert!(client.get_jurisdiction_rule(&us).is_none());
+    assert_eq!(
+        client.try_remove_jurisdiction_rule(&admin, &us),
+        Err(Ok(QuickLendXError::StorageKeyNotFound))
+    );
+}
+
+#[test]
+fn test_audit_hooks_across_lifecycle() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let platform = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86400;
+    let description = String::from_str(&env, "Audited invoice");
+
+    let invoice_id = client.upload_invoice(&business, &1000, &currency, &due_date, &description);
+    client.verify_invoice(&admin, &invoice_id);
+    let bid_id = client.place_bid(&investor, &invoice_id, &1000, &1100);
+    client.accept_bid(&business, &invoice_id, &bid_id);
+    client.set_platform_fee(&admin, &platform, &200);
+    client.settle_invoice(&business, &invoice_id, &1100);
+
+    let operations: Vec<AuditOperation> = {
+        let mut ops = Vec::new(&env);
+        for audit_id in client.get_invoice_audit_trail(&invoice_id).iter() {
+            ops.push_back(client.get_audit_entry(&audit_id).operation);
+        }
+        ops
+    };
+    assert_eq!(
+        operations,
+        vec![
+            &env,
+            AuditOperation::InvoiceCreated,
+            AuditOperation::InvoiceStatusChanged,
+            AuditOperation::InvoiceFunded,
+            AuditOperation::InvoiceStatusChanged,
+            AuditOperation::PaymentProcessed,
+            AuditOperation::InvoiceStatusChanged,
+        ]
+    );
+    assert!(client.validate_invoice_audit_integrity(&invoice_id));
+
+    // Defaults are recorded as a status change as well
+    let invoice_id = client.upload_invoice(&business, &500, &currency, &due_date, &description);
+    client.verify_invoice(&admin, &invoice_id);
+    let bid_id = client.place_bid(&investor, &invoice_id, &500, &550);
+    client.accept_bid(&business, &invoice_id, &bid_id);
+    env.ledger()
+        .with_mut(|l| l.timestamp = due_date + crate::config::DEFAULT_GRACE_PERIOD + 1);
+    client.handle_default(&admin, &invoice_id);
+    let trail = client.get_invoice_audit_trail(&invoice_id);
+    let last = client.get_audit_entry(&trail.get(trail.len() - 1).unwrap());
+    assert_eq!(last.operation, AuditOperation::InvoiceStatusChanged);
+    assert_eq!(last.new_value, Some(String::from_str(&env, "Defaulted")));
+
+    let stats = client.get_audit_stats();
+    assert_eq!(stats.total_entries, 6 + 5);
+    assert!(stats
+        .operations_count
+        .contains(&(AuditOperation::InvoiceCreated, 2u32)));
+}
+
+#[test]
+fn test_penalty_schedule_amount_due() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let business = Address::generate(&env);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    client.initialize(&admin);
+
+    let day = 86_400u64;
+    let due_date = env.ledger().timestamp() + 30 * day;
+    verify_kyc(&env, &client, &admin, &business);
+    let invoice_id = client.store_invoice(
+        &business,
+        &3_650_000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Invoice"),
+    );
+    assert_eq!(
+        client.try_get_amount_due(&invoice_id),
+        Err(Ok(QuickLendXError::InvoiceNotFunded))
+    );
+
+    // Tiers must start in strictly increasing order
+    let invalid = crate::penalty::PenaltySchedule {
+        grace_period: 5 * day,
+        tiers: vec![
+            &env,
+            crate::penalty::PenaltyTier {
+                starts_after: 10 * day,
+                apr_bps: 1_000,
+            },
+            crate::penalty::PenaltyTier {
+                starts_after: 10 * day,
+                apr_bps: 3_650,
+            },
+        ],
+    };
+    assert_eq!(
+        client.try_set_penalty_schedule(&admin, &invalid),
+        Err(Ok(QuickLendXError::InvalidTimestamp))
+    );
+
+    // 5 days grace, then 10% APR for 10 days, then 36.5% APR
+    let schedule = crate::penalty::PenaltySchedule {
+        grace_period: 5 * day,
+        tiers: vec![
+            &env,
+            crate::penalty::PenaltyTier {
+                starts_after: 0,
+                apr_bps: 1_000,
+            },
+            crate::penalty::PenaltyTier {
+                starts_after: 10 * day,
+                apr_bps: 3_650,
+            },
+        ],
+    };
+    client.set_penalty_schedule(&admin, &schedule);
+    assert_eq!(client.get_penalty_schedule(), schedule);
+
+    client.update_invoice_status(&admin, &invoice_id, &InvoiceStatus::Verified);
+    let bid_id = client.place_bid(&investor, &invoice_id, &3_650_000, &3_700_000);
+    client.accept_bid(&business, &invoice_id, &bid_id);
+    assert_eq!(client.get_amount_due(&invoice_id), 3_700_000);
+
+    // Within grace no penalty accrues
+    env.ledger().with_mut(|l| l.timestamp = due_date + 3 * day);
+    let breakdown = client.get_amount_due_breakdown(&invoice_id);
+    assert_eq!(breakdown.penalty, 0);
+    assert_eq!(breakdown.current_apr_bps, 0);
+    assert_eq!(breakdown.grace_ends_at, due_date + 5 * day);
+
+    // 20 days past grace: 10 days in each tier
+    env.ledger().with_mut(|l| l.timestamp = due_date + 25 * day);
+    let breakdown = client.get_amount_due_breakdown(&invoice_id);
+    assert_eq!(breakdown.principal, 3_650_000);
+    assert_eq!(breakdown.profit, 50_000);
+    assert_eq!(breakdown.accruals.len(), 2);
+    assert_eq!(breakdown.accruals.get(0).unwrap().amount, 10_000);
+    assert_eq!(breakdown.accruals.get(1).unwrap().amount, 36_500);
+    assert_eq!(breakdown.accruals.get(1).unwrap().to, due_date + 25 * day);
+    assert_eq!(breakdown.penalty, 46_500);
+    assert_eq!(breakdown.current_apr_bps, 3_650);
+    assert_eq!(client.get_amount_due(&invoice_id), 3_746_500);
+}
+
+#[test]
+fn test_build_settlement_plan() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let business = Address::generate(&env);
+    let investor1 = Address::generate(&env);
+    let investor2 = Address::generate(&env);
+    let platform = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86400;
+
+    verify_kyc(&env, &client, &admin, &business);
+    let invoice_id = client.store_invoice(
+        &business,
+        &1000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Syndicated invoice"),
+    );
+    client.update_invoice_status(&admin, &invoice_id, &InvoiceStatus::Verified);
+    client.set_platform_fee(&admin, &platform, &200);
+    let bid1 = client.place_bid(&investor1, &invoice_id, &700, &770);
+    let bid2 = client.place_bid(&investor2, &invoice_id, &300, &330);
+    client.accept_bid(&business, &invoice_id, &bid1);
+    client.accept_bid(&business, &invoice_id, &bid2);
+
+    let plan = client.build_settlement_plan(&invoice_id, &1100);
+    assert_eq!(plan.payment_amount, 1100);
+    assert_eq!(plan.total_investor_return, 1099);
+    assert_eq!(plan.total_platform_fee, 1);
+
+    // Investor 2's fee rounds to zero, so no platform transfer is planned for it
+    let expected = [(&investor1, 769), (&platform, 1), (&investor2, 330)];
+    assert_eq!(plan.transfers.len(), expected.len() as u32);
+    for (transfer, (to, amount)) in plan.transfers.iter().zip(expected.iter()) {
+        assert_eq!(transfer.from, business);
+        assert_eq!(&transfer.to, *to);
+        assert_eq!(transfer.amount, *amount);
+        assert_eq!(transfer.currency, currency);
+    }
+
+    // Previewing does not settle the invoice
+    assert_eq!(
+        client.get_invoice(&invoice_id).status,
+        InvoiceStatus::Funded
+    );
+    client.settle_invoice(&business, &invoice_id, &1100);
+    assert_eq!(client.get_invoice(&invoice_id).status, InvoiceStatus::Paid);
+    assert_eq!(
+        client.try_build_settlement_plan(&invoice_id, &1100),
+        Err(Ok(QuickLendXError::InvalidStatus))
+    );
+}
+
+#[test]
+fn test_validate_invoice_input() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let business = Address::generate(&env);
+    let usdc = Address::generate(&env);
+    let other = Address::generate(&env);
+    client.initialize(&admin);
+    client.add_supported_currency(&admin, &usdc);
+    let description = String::from_str(&env, "Invoice");
+    let due_date = env.ledger().timestamp() + 86400;
+
+    // Every failing field is reported, not just the first
+    let result = client.validate_invoice_input(&business, &0, &other, &0, &description);
+    assert!(!result.valid);
+    let expected = [
+        ("blacklist", None),
+        ("business", Some(QuickLendXError::BusinessNotVerified)),
+        ("amount", Some(QuickLendXError::InvalidAmount)),
+        ("currency", Some(QuickLendXError::InvalidCurrency)),
+        ("due_date", Some(QuickLendXError::InvoiceDueDateInvalid)),
+        ("description", None),
+        ("credit_limit", None),
+        ("tier_limits", None),
+    ];
+    assert_eq!(result.fields.len(), expected.len() as u32);
+    for (field, (name, error)) in result.fields.iter().zip(expected.iter()) {
+        assert_eq!(field.field, Symbol::new(&env, name));
+        assert_eq!(field.valid, error.is_none());
+        assert_eq!(field.error, error.map(Symbol::from));
+    }
+
+    client.submit_kyc_application(&business, &String::from_str(&env, "KYC data"));
+    client.verify_business(&admin, &business);
+    let result = client.validate_invoice_input(&business, &1000, &usdc, &due_date, &description);
+    assert!(result.valid);
+    assert!(result
+        .fields
+        .iter()
+        .all(|field| field.valid && field.error.is_none()));
+
+    // The dry run applies the credit limit and blacklist like upload does
+    let failure = |result: InvoiceInputValidation| {
+        assert!(!result.valid);
+        let failed = result.fields.iter().find(|field| !field.valid).unwrap();
+        (failed.field, failed.error)
+    };
+    client.set_credit_limit(&admin, &business, &500);
+    assert_eq!(
+        failure(client.validate_invoice_input(&business, &1000, &usdc, &due_date, &description)),
+        (
+            Symbol::new(&env, "credit_limit"),
+            Some(Symbol::from(QuickLendXError::CreditLimitExceeded))
+        )
+    );
+    client.add_to_blacklist(&admin, &business, &String::from_str(&env, "Fraud"));
+    assert_eq!(
+        failure(client.validate_invoice_input(&business, &500, &usdc, &due_date, &description)),
+        (
+            Symbol::new(&env, "blacklist"),
+            Some(Symbol::from(QuickLendXError::Blacklisted))
+        )
+    );
+
+    // Validation is read-only
+    assert_eq!(client.get_total_invoice_count(), 0);
+}
+
+#[test]
+fn test_two_step_admin_transfer() {
+    let env = Env::default();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let new_admin = Address::generate(&env);
+    let intruder = Address::generate(&env);
+    env.mock_all_auths();
+    client.initialize(&admin);
+    assert_eq!(client.get_admin(), Some(admin.clone()));
+
+    // The admin cannot be overwritten by re-initializing
+    assert_eq!(
+        client.try_initialize(&intruder),
+        Err(Ok(QuickLendXError::AlreadyInitialized))
+    );
+
+    assert_eq!(
+        client.try_transfer_admin(&intruder, &intruder),
+        Err(Ok(QuickLendXError::NotAdmin))
+    );
+    client.transfer_admin(&admin, &new_admin);
+    assert_eq!(client.get_pending_admin(), Some(new_admin.clone()));
+    assert_eq!(client.get_admin(), Some(admin.clone()));
+
+    // Only the nominee can accept
+    assert_eq!(
+        client.try_accept_admin(&intruder),
+        Err(Ok(QuickLendXError::Unauthorized))
+    );
+    client.accept_admin(&new_admin);
+    assert_eq!(client.get_admin(), Some(new_admin.clone()));
+    assert_eq!(client.get_pending_admin(), None);
+
+    // The previous admin has lost its privileges
+    let currency = Address::generate(&env);
+    assert_eq!(
+        client.try_add_supported_currency(&admin, &currency),
+        Err(Ok(QuickLendXError::NotAdmin))
+    );
+    client.add_supported_currency(&new_admin, &currency);
+}
+
+#[test]
+fn test_size_limits() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let business = Address::generate(&env);
+    let rejected = Address::generate(&env);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86400;
+    client.initialize(&admin);
+
+    // Limits must be non-zero and within the hard bounds
+    let mut limits = client.get_size_limits();
+    limits.feedback = 0;
+    assert_eq!(
+        client.try_set_size_limits(&admin, &limits),
+        Err(Ok(QuickLendXError::InvalidAmount))
+    );
+    limits.feedback = crate::config::MAX_FEEDBACK_LENGTH + 1;
+    assert_eq!(
+        client.try_set_size_limits(&admin, &limits),
+        Err(Ok(QuickLendXError::InvalidAmount))
+    );
+
+    let limits = crate::config::SizeLimits {
+        description: 8,
+        feedback: 8,
+        kyc_data: 8,
+        rejection_reason: 8,
+    };
+    client.set_size_limits(&admin, &limits);
+    assert_eq!(client.get_size_limits(), limits);
+    let too_long = String::from_str(&env, "123456789");
+    let short = String::from_str(&env, "12345678");
+
+    assert_eq!(
+        client.try_submit_kyc_application(&business, &too_long),
+        Err(Ok(QuickLendXError::KycDataTooLong))
+    );
+    client.submit_kyc_application(&business, &short);
+    client.verify_business(&admin, &business);
+
+    client.submit_kyc_application(&rejected, &short);
+    assert_eq!(
+        client.try_reject_business(&admin, &rejected, &too_long),
+        Err(Ok(QuickLendXError::RejectionReasonTooLong))
+    );
+    client.reject_business(&admin, &rejected, &short);
+
+    assert_eq!(
+        client.try_upload_invoice(&business, &1000, &currency, &due_date, &too_long),
+        Err(Ok(QuickLendXError::DescriptionTooLong))
+    );
+    assert_eq!(
+        client.try_store_invoice(&business, &1000, &currency, &due_date, &too_long),
+        Err(Ok(QuickLendXError::DescriptionTooLong))
+    );
+    let invoice_id = client.upload_invoice(&business, &1000, &currency, &due_date, &short);
+
+    client.update_invoice_status(&admin, &invoice_id, &InvoiceStatus::Verified);
+    let bid_id = client.place_bid(&investor, &invoice_id, &1000, &1100);
+    client.accept_bid(&business, &invoice_id, &bid_id);
+    assert_eq!(
+        client.try_add_invoice_rating(&invoice_id, &5, &too_long, &investor),
+        Err(Ok(QuickLendXError::FeedbackTooLong))
+    );
+    client.add_invoice_rating(&invoice_id, &5, &short, &investor);
+}
+
+#[test]
+fn test_upgrade_and_migrate() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let intruder = Address::generate(&env);
+    client.initialize(&admin);
+
+    // Fresh deployments start at the current schema
+    let current = crate::upgrade::CURRENT_SCHEMA_VERSION;
+    assert_eq!(client.get_schema_version(), current);
+    assert_eq!(
+        client.try_migrate(&admin),
+        Err(Ok(QuickLendXError::OperationNotAllowed))
+    );
+
+    // Only the admin can replace the code
+    let wasm_hash = BytesN::from_array(&env, &[7u8; 32]);
+    assert_eq!(
+        client.try_upgrade(&intruder, &wasm_hash),
+        Err(Ok(QuickLendXError::NotAdmin))
+    );
+
+    // Deployments that predate versioning migrate up to the current schema
+    env.as_contract(&contract_id, || {
+        env.storage().instance().remove(&symbol_short!("schema_v"));
+    });
+    assert_eq!(client.get_schema_version(), 0);
+    assert_eq!(
+        client.try_migrate(&intruder),
+        Err(Ok(QuickLendXError::NotAdmin))
+    );
+    assert_eq!(client.migrate(&admin), current);
+    assert_eq!(client.get_schema_version(), current);
+}
+
+#[test]
+fn test_mutations_require_explicit_actor() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let business = Address::generate(&env);
+    let investor = Address::generate(&env);
+    let other = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86400;
+    client.initialize(&admin);
+
+    verify_kyc(&env, &client, &admin, &business);
+    let invoice_id = client.store_invoice(
+        &business,
+        &1000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Invoice"),
+    );
+
+    // Administrative actions reject anyone but the admin (or a verifier)
+    assert_eq!(
+        client.try_update_invoice_status(&other, &invoice_id, &InvoiceStatus::Verified),
+        Err(Ok(QuickLendXError::NotAdmin))
+    );
+    assert_eq!(
+        client.try_verify_invoice(&other, &invoice_id),
+        Err(Ok(QuickLendXError::NotVerifier))
+    );
+    client.verify_invoice(&admin, &invoice_id);
+
+    // The acting business or investor must own the record they change
+    let bid_id = client.place_bid(&investor, &invoice_id, &1000, &1100);
+    assert_eq!(
+        client.try_withdraw_bid(&other, &bid_id),
+        Err(Ok(QuickLendXError::NotInvestor))
+    );
+    assert_eq!(
+        client.try_accept_bid(&other, &invoice_id, &bid_id),
+        Err(Ok(QuickLendXError::NotBusinessOwner))
+    );
+    client.accept_bid(&business, &invoice_id, &bid_id);
+    assert_eq!(
+        client.try_handle_default(&other, &invoice_id),
+        Err(Ok(QuickLendXError::NotAdmin))
+    );
+    assert_eq!(
+        client.try_settle_invoice(&other, &invoice_id, &1100),
+        Err(Ok(QuickLendXError::NotBusinessOwner))
+    );
+    client.settle_invoice(&business, &invoice_id, &1100);
+
+    // Audit entries record who performed each step
+    assert_eq!(client.get_audit_entries_by_actor(&admin).len(), 1);
+    let verification =
+        client.get_audit_entry(&client.get_audit_entries_by_actor(&admin).get(0).unwrap());
+    assert_eq!(
+        verification.new_value,
+        Some(String::from_str(&env, "Verified"))
+    );
+    assert!(client.get_audit_entries_by_actor(&business).len() >= 3);
+}
+
+#[test]
+#[should_panic]
+fn test_store_invoice_requires_business_auth() {
+    let env = Env::default();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let business = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86400;
+    client.store_invoice(
+        &business,
+        &1000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Invoice"),
+    );
+}
+
+#[test]
+fn test_platform_fee_configuration() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let business = Address::generate(&env);
+    let investor = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86400;
+    client.initialize(&admin);
+
+    // No fee is charged until the admin configures one
+    let fee = client.get_platform_fee();
+    assert_eq!(fee.fee_bps, 0);
+    assert_eq!(fee.recipient, contract_id);
+
+    assert_eq!(
+        client.try_set_platform_fee(&investor, &treasury, &100),
+        Err(Ok(QuickLendXError::NotAdmin))
+    );
+    assert_eq!(
+        client.try_set_platform_fee(
+            &admin,
+            &treasury,
+            &(crate::config::MAX_PLATFORM_FEE_BPS + 1)
+        ),
+        Err(Ok(QuickLendXError::InvalidAmount))
+    );
+    client.set_platform_fee(&admin, &treasury, &1_000);
+    let fee = client.get_platform_fee();
+    assert_eq!(fee.recipient, treasury);
+    assert_eq!(fee.fee_bps, 1_000);
+
+    // Settlement takes the fee from config: 10% of the 100 profit
+    verify_kyc(&env, &client, &admin, &business);
+    let invoice_id = client.store_invoice(
+        &business,
+        &1000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    let bid_id = client.place_bid(&investor, &invoice_id, &1000, &1100);
+    client.accept_bid(&business, &invoice_id, &bid_id);
+    let plan = client.build_settlement_plan(&invoice_id, &1100);
+    assert_eq!(plan.total_platform_fee, 10);
+    assert_eq!(plan.transfers.get(1).unwrap().to, treasury);
+}
+
+#[test]
+fn test_unbacked_funded_invoice_watchdog() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let business = Address::generate(&env);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86400;
+    client.initialize(&admin);
+
+    verify_kyc(&env, &client, &admin, &business);
+    let invoice_id = client.store_invoice(
+        &business,
+        &1000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    let bid_id = client.place_bid(&investor, &invoice_id, &1000, &1100);
+    client.accept_bid(&business, &invoice_id, &bid_id);
+
+    // A held escrow backs the invoice
+    assert_eq!(client.get_unbacked_funded_invoices().len(), 0);
+    assert_eq!(
+        client.try_reconcile_funded_invoice(&admin, &invoice_id),
+        Err(Ok(QuickLendXError::OperationNotAllowed))
+    );
+
+    // Refunding the escrow leaves the invoice Funded without backing
+    client.refund_escrow_funds(&admin, &invoice_id);
+    assert_eq!(
+        client.get_unbacked_funded_invoices(),
+        vec![&env, invoice_id.clone()]
+    );
+    assert_eq!(
+        client.try_reconcile_funded_invoice(&investor, &invoice_id),
+        Err(Ok(QuickLendXError::NotAdmin))
+    );
+    client.reconcile_funded_invoice(&admin, &invoice_id);
+
+    let invoice = client.get_invoice(&invoice_id);
+    assert_eq!(invoice.status, InvoiceStatus::Verified);
+    assert_eq!(invoice.funded_amount, 0);
+    assert!(invoice.investors.is_empty());
+    assert_eq!(
+        client.get_bid(&bid_id).unwrap().status,
+        BidStatus::Withdrawn
+    );
+    let investments = client.get_invoice_investments(&invoice_id);
+    assert_eq!(
+        investments.get(0).unwrap().status,
+        InvestmentStatus::Withdrawn
+    );
+    assert_eq!(client.get_unbacked_funded_invoices().len(), 0);
+
+    // The invoice can be funded again
+    let bid_id = client.place_bid(&investor, &invoice_id, &1000, &1100);
+    client.accept_bid(&business, &invoice_id, &bid_id);
+    assert_eq!(
+        client.get_invoice(&invoice_id).status,
+        InvoiceStatus::Funded
+    );
+}
+
+#[test]
+fn test_treasury_collects_and_withdraws_fees() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let business = Address::generate(&env);
+    let investor = Address::generate(&env);
+    let treasurer = Address::generate(&env);
+    let payee = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86400;
+    client.initialize(&admin);
+
+    // Fees paid to the contract itself accumulate in the treasury
+    client.set_platform_fee(&admin, &contract_id, &1_000);
+    verify_kyc(&env, &client, &admin, &business);
+    let invoice_id = client.store_invoice(
+        &business,
+        &1000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    let bid_id = client.place_bid(&investor, &invoice_id, &1000, &1100);
+    client.accept_bid(&business, &invoice_id, &bid_id);
+    client.settle_invoice(&business, &invoice_id, &1100);
+    assert_eq!(client.get_treasury_balance(&currency), 10);
+    assert_eq!(client.get_treasury_balance(&Address::generate(&env)), 0);
+
+    // Only the admin or the treasurer may withdraw
+    assert_eq!(
+        client.try_withdraw_fees(&treasurer, &currency, &5, &payee),
+        Err(Ok(QuickLendXError::Unauthorized))
+    );
+    client.set_treasurer(&admin, &treasurer);
+    assert_eq!(client.get_treasurer(), Some(treasurer.clone()));
+    assert_eq!(
+        client.try_withdraw_fees(&treasurer, &currency, &11, &payee),
+        Err(Ok(QuickLendXError::InsufficientFunds))
+    );
+    client.withdraw_fees(&treasurer, &currency, &4, &payee);
+    client.withdraw_fees(&admin, &currency, &6, &payee);
+    assert_eq!(client.get_treasury_balance(&currency), 0);
+
+    let withdrawals = client.get_audit_entries_by_operation(&AuditOperation::FeesWithdrawn);
+    assert_eq!(withdrawals.len(), 2);
+    let first = client.get_audit_entry(&withdrawals.get(0).unwrap());
+    assert_eq!(first.actor, treasurer);
+    assert_eq!(first.amount, Some(4));
+}
+
+#[test]
+fn test_default_requires_grace_period_to_pass() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let day = 86400u64;
+    let due_date = env.ledger().timestamp() + 10 * day;
+
+    assert_eq!(
+        client.get_default_grace_period(),
+        crate::config::DEFAULT_GRACE_PERIOD
+    );
+    assert_eq!(
+        client.try_set_default_grace_period(&admin, &(crate::config::MAX_DEFAULT_GRACE_PERIOD + 1)),
+        Err(Ok(QuickLendXError::InvalidTimestamp))
+    );
+    client.set_default_grace_period(&admin, &(3 * day));
+
+    let mut funded = Vec::new(&env);
+    for due in [due_date, due_date + 5 * day] {
+        let invoice_id = client.upload_invoice(
+            &business,
+            &1000,
+            &currency,
+            &due,
+            &String::from_str(&env, "Invoice"),
+        );
+        client.verify_invoice(&admin, &invoice_id);
+        let bid_id = client.place_bid(&investor, &invoice_id, &1000, &1100);
+        client.accept_bid(&business, &invoice_id, &bid_id);
+        funded.push_back(invoice_id);
+    }
+    let first = funded.get(0).unwrap();
+    let second = funded.get(1).unwrap();
+
+    // Past the due date but still within grace: nothing can be defaulted
+    env.ledger().with_mut(|l| l.timestamp = due_date + 3 * day);
+    assert_eq!(
+        client.try_handle_default(&admin, &first),
+        Err(Ok(QuickLendXError::InvoiceNotOverdue))
+    );
+    assert_eq!(client.check_overdue_invoices().len(), 0);
+
+    // Once grace has passed, the keeper defaults only the overdue invoice
+    env.ledger()
+        .with_mut(|l| l.timestamp = due_date + 3 * day + 1);
+    assert_eq!(client.check_overdue_invoices(), vec![&env, first.clone()]);
+    assert_eq!(client.get_invoice(&first).status, InvoiceStatus::Defaulted);
+    assert_eq!(client.get_invoice(&second).status, InvoiceStatus::Funded);
+    let trail = client.get_invoice_audit_trail(&first);
+    let last = client.get_audit_entry(&trail.get(trail.len() - 1).unwrap());
+    assert_eq!(last.actor, contract_id);
+}
+
+#[test]
+fn test_multiple_escrows_per_invoice() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let business = Address::generate(&env);
+    let investor1 = Address::generate(&env);
+    let investor2 = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86400;
+
+    verify_kyc(&env, &client, &admin, &business);
+    let invoice_id = client.store_invoice(
+        &business,
+        &1000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Syndicated invoice"),
+    );
+    client.update_invoice_status(&admin, &invoice_id, &InvoiceStatus::Verified);
+    let bid1 = client.place_bid(&investor1, &invoice_id, &600, &660);
+    let bid2 = client.place_bid(&investor2, &invoice_id, &400, &440);
+    client.accept_bid(&business, &invoice_id, &bid1);
+    client.accept_bid(&business, &invoice_id, &bid2);
+
+    // Each accepted bid gets its own funding escrow
+    let escrows = client.get_invoice_escrows(&invoice_id);
+    assert_eq!(escrows.len(), 2);
+    let first = escrows.get(0).unwrap();
+    let second = escrows.get(1).unwrap();
+    assert_eq!(first.kind, crate::payments::EscrowKind::Funding);
+    assert_eq!((first.investor.clone(), first.amount), (investor1, 600));
+    assert_eq!((second.investor.clone(), second.amount), (investor2, 400));
+    assert_eq!(client.get_escrow(&first.escrow_id), Some(first.clone()));
+    assert_eq!(client.get_escrow_details(&invoice_id), second);
+    assert_eq!(client.get_unbacked_funded_invoices().len(), 0);
+
+    // Releasing the invoice releases every held funding escrow
+    client.release_escrow_funds(&admin, &invoice_id);
+    for escrow in client.get_invoice_escrows(&invoice_id).iter() {
+        assert_eq!(escrow.status, crate::payments::EscrowStatus::Released);
+        assert_eq!(client.get_escrow_ledger(&escrow.escrow_id).len(), 2);
+    }
+    assert_eq!(
+        client.try_release_escrow_funds(&admin, &invoice_id),
+        Err(Ok(QuickLendXError::InvalidStatus))
+    );
+}
+
+#[test]
+fn test_migrate_escrows_to_v2() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let business = Address::generate(&env);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86400;
+    verify_kyc(&env, &client, &admin, &business);
+    let invoice_id = client.store_invoice(
+        &business,
+        &1000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Invoice"),
+    );
+
+    // Rewrite storage as a v1 deployment: untagged escrow, single mapping
+    let escrow_id = BytesN::from_array(&env, &[9u8; 32]);
+    env.as_contract(&contract_id, || {
+        let legacy = crate::upgrade::EscrowV1 {
+            escrow_id: escrow_id.clone(),
+            invoice_id: invoice_id.clone(),
+            investor: investor.clone(),
+            business: business.clone(),
+            amount: 1000,
+            currency: currency.clone(),
+            created_at: 0,
+            status: crate::payments::EscrowStatus::Held,
+        };
+        env.storage().instance().set(&escrow_id, &legacy);
+        env.storage()
+            .instance()
+            .set(&(symbol_short!("escrow"), invoice_id.clone()), &escrow_id);
+        crate::upgrade::set_schema_version(&env, 1);
+    });
+
+    assert_eq!(
+        client.migrate(&admin),
+        crate::upgrade::CURRENT_SCHEMA_VERSION
+    );
+    let escrows = client.get_invoice_escrows(&invoice_id);
+    assert_eq!(escrows.len(), 1);
+    let escrow = escrows.get(0).unwrap();
+    assert_eq!(escrow.escrow_id, escrow_id);
+    assert_eq!(escrow.kind, crate::payments::EscrowKind::Funding);
+    assert_eq!(escrow.amount, 1000);
+    env.as_contract(&contract_id, || {
+        assert!(!env
+            .storage()
+            .instance()
+            .has(&(symbol_short!("escrow"), invoice_id.clone())));
+    });
+}
+
+#[test]
+fn test_insurance_claim_review_workflow() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let insurer = Address::generate(&env);
+    let investor = Address::generate(&env);
+    let outsider = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86400;
+
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    client.set_invoice_insurance(&business, &invoice_id, &true);
+    let bid_id = client.place_bid(&investor, &invoice_id, &1000, &1100);
+    client.accept_bid(&business, &invoice_id, &bid_id);
+    let investment = client.get_invoice_investments(&invoice_id).get(0).unwrap();
+    let investment_id = investment.investment_id;
+    let premium = match investment.insurance {
+        crate::insurance::InvestmentInsurance::Covered(coverage) => coverage.premium,
+        crate::insurance::InvestmentInsurance::Uninsured => panic!("investment is insured"),
+    };
+
+    // Claims are only accepted once the invoice has defaulted
+    assert_eq!(
+        client.try_submit_insurance_claim(&investor, &investment_id, &1000),
+        Err(Ok(QuickLendXError::InvalidStatus))
+    );
+    env.ledger()
+        .with_mut(|l| l.timestamp = due_date + crate::config::DEFAULT_GRACE_PERIOD + 1);
+    client.handle_default(&admin, &invoice_id);
+
+    assert_eq!(
+        client.try_submit_insurance_claim(&outsider, &investment_id, &1000),
+        Err(Ok(QuickLendXError::NotInvestor))
+    );
+    assert_eq!(
+        client.try_submit_insurance_claim(&investor, &investment_id, &1001),
+        Err(Ok(QuickLendXError::InvalidAmount))
+    );
+    let claim_id = client.submit_insurance_claim(&investor, &investment_id, &1000);
+    assert_eq!(
+        client.try_submit_insurance_claim(&investor, &investment_id, &1000),
+        Err(Ok(QuickLendXError::ClaimAlreadyExists))
+    );
+    assert_eq!(
+        client.get_claims_by_status(&ClaimStatus::Submitted),
+        vec![&env, claim_id.clone()]
+    );
+
+    // The insurance admin reviews and denies the claim
+    client.set_insurance_admin(&admin, &insurer);
+    assert_eq!(
+        client.try_start_claim_review(&outsider, &claim_id),
+        Err(Ok(QuickLendXError::Unauthorized))
+    );
+    assert_eq!(
+        client.try_approve_claim(&insurer, &claim_id),
+        Err(Ok(QuickLendXError::InvalidStatus))
+    );
+    client.start_claim_review(&insurer, &claim_id);
+    client.deny_claim(&insurer, &claim_id, &String::from_str(&env, "No coverage"));
+    let claim = client.get_insurance_claim(&claim_id).unwrap();
+    assert_eq!(claim.status, ClaimStatus::Denied);
+    assert_eq!(claim.reviewed_by, Some(insurer.clone()));
+
+    // An appeal is a dispute for the arbitrator, not the original reviewer
+    client.appeal_claim(&investor, &claim_id, &String::from_str(&env, "Covered"));
+    assert_eq!(
+        client.try_start_claim_review(&insurer, &claim_id),
+        Err(Ok(QuickLendXError::InvalidStatus))
+    );
+    let appeal = client.get_claim_appeal(&claim_id).unwrap();
+    assert_eq!(appeal.status, crate::disputes::DisputeStatus::Open);
+    assert_eq!(appeal.opened_by, investor);
+    let arbitrator = Address::generate(&env);
+    client.set_arbitrator(&admin, &arbitrator);
+    assert_eq!(
+        client.try_resolve_claim_appeal(&insurer, &claim_id, &true),
+        Err(Ok(QuickLendXError::Unauthorized))
+    );
+    client.resolve_claim_appeal(&arbitrator, &claim_id, &true);
+    assert_eq!(
+        client.get_claim_appeal(&claim_id).unwrap().status,
+        crate::disputes::DisputeStatus::Resolved(DisputeOutcome::Upheld)
+    );
+    let claim = client.get_insurance_claim(&claim_id).unwrap();
+    assert_eq!(claim.status, ClaimStatus::Approved);
+    assert_eq!(claim.reviewed_by, Some(arbitrator.clone()));
+    assert_eq!(
+        client.try_resolve_claim_appeal(&arbitrator, &claim_id, &false),
+        Err(Ok(QuickLendXError::InvalidStatus))
+    );
+
+    // Payout needs enough capital in the fund
+    assert_eq!(
+        client.try_pay_claim(&admin, &claim_id),
+        Err(Ok(QuickLendXError::InsufficientFunds))
+    );
+    client.deposit_insurance_fund(&outsider, &currency, &1500);
+    client.pay_claim(&admin, &claim_id);
+    assert_eq!(client.get_insurance_fund_balance(&currency), premium + 500);
+    let claim = client.get_insurance_claim(&claim_id).unwrap();
+    assert_eq!(claim.status, ClaimStatus::Paid);
+    assert_eq!(
+        client.try_appeal_claim(&investor, &claim_id, &String::from_str(&env, "Again")),
+        Err(Ok(QuickLendXError::InvalidStatus))
+    );
+    assert!(client
+        .get_claims_by_status(&ClaimStatus::Submitted)
+        .is_empty());
+}
+
+#[test]
+fn test_late_fee_added_to_settlement() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let day = 86_400u64;
+    let due_date = env.ledger().timestamp() + 30 * day;
+
+    assert_eq!(client.get_late_fee(), 0);
+    assert_eq!(
+        client.try_set_late_fee(&admin, &(crate::penalty::MAX_LATE_FEE_BPS_PER_DAY + 1)),
+        Err(Ok(QuickLendXError::InvalidAmount))
+    );
+    client.set_late_fee(&admin, &10);
+
+    let invoice_id = client.upload_invoice(
+        &business,
+        &10_000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    let bid_id = client.place_bid(&investor, &invoice_id, &10_000, &11_000);
+    client.accept_bid(&business, &invoice_id, &bid_id);
+
+    // On time the business owes principal plus the promised profit
+    let due = client.get_amount_due_breakdown(&invoice_id);
+    assert_eq!(
+        (due.principal, due.profit, due.late_fee),
+        (10_000, 1_000, 0)
+    );
+    assert_eq!(due.total, 11_000);
+
+    // 0.1% of the invoice amount per full day late
+    env.ledger()
+        .with_mut(|l| l.timestamp = due_date + 3 * day + day / 2);
+    let due = client.get_amount_due_breakdown(&invoice_id);
+    assert_eq!(due.days_late, 3);
+    assert_eq!(due.late_fee, 30);
+    assert_eq!(due.total, 11_030);
+
+    // Settlement must cover the late fee, which is passed on to the investor
+    assert_eq!(
+        client.try_settle_invoice(&business, &invoice_id, &11_000),
+        Err(Ok(QuickLendXError::InsufficientFunds))
+    );
+    let plan = client.build_settlement_plan(&invoice_id, &11_030);
+    assert_eq!(plan.total_investor_return, 11_030);
+    client.settle_invoice(&business, &invoice_id, &11_030);
+    assert_eq!(client.get_invoice(&invoice_id).status, InvoiceStatus::Paid);
+}
+
+#[test]
+fn test_partial_payments_complete_settlement() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let investor1 = Address::generate(&env);
+    let investor2 = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86400;
+    client.set_platform_fee(&admin, &contract_id, &1_000);
+
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Installments"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    let bid1 = client.place_bid(&investor1, &invoice_id, &600, &660);
+    let bid2 = client.place_bid(&investor2, &invoice_id, &400, &440);
+    client.accept_bid(&business, &invoice_id, &bid1);
+    client.accept_bid(&business, &invoice_id, &bid2);
+    assert_eq!(client.get_amount_due(&invoice_id), 1100);
+
+    // Installments accumulate without settling the invoice
+    client.record_partial_payment(&business, &invoice_id, &300);
+    client.record_partial_payment(&business, &invoice_id, &300);
+    assert_eq!(client.get_amount_paid(&invoice_id), 600);
+    assert_eq!(client.get_amount_due(&invoice_id), 500);
+    assert_eq!(
+        client.get_invoice(&invoice_id).status,
+        InvoiceStatus::Funded
+    );
+    assert!(client.get_treasury_balance(&currency) < 10);
+
+    // Overpaying, or switching to a one-off settlement, is refused
+    assert_eq!(
+        client.try_record_partial_payment(&business, &invoice_id, &501),
+        Err(Ok(QuickLendXError::InvalidAmount))
+    );
+    assert_eq!(
+        client.try_settle_invoice(&business, &invoice_id, &1100),
+        Err(Ok(QuickLendXError::OperationNotAllowed))
+    );
+
+    // The final installment pays out every share in full
+    client.record_partial_payment(&business, &invoice_id, &500);
+    assert_eq!(client.get_invoice(&invoice_id).status, InvoiceStatus::Paid);
+    assert_eq!(client.get_treasury_balance(&currency), 10);
+    for investment in client.get_invoice_investments(&invoice_id).iter() {
+        assert_eq!(investment.status, InvestmentStatus::Completed);
+    }
+    assert_eq!(
+        client.try_record_partial_payment(&business, &invoice_id, &1),
+        Err(Ok(QuickLendXError::InvoiceNotFunded))
+    );
+}
+
+#[test]
+fn test_insurance_premium_priced_from_risk_and_tenor() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let year = 365 * 86_400u64;
+
+    client.set_premium_rates(
+        &admin,
+        &crate::insurance::PremiumRates {
+            base_bps: 100,
+            risk_bps: 1_000,
+            annual_tenor_bps: 400,
+        },
+    );
+    assert_eq!(
+        client.try_set_premium_rates(
+            &admin,
+            &crate::insurance::PremiumRates {
+                base_bps: crate::insurance::MAX_PREMIUM_BPS + 1,
+                risk_bps: 0,
+                annual_tenor_bps: 0,
+            }
+        ),
+        Err(Ok(QuickLendXError::InvalidAmount))
+    );
+
+    let fund = |due: u64, insured: bool| {
+        let invoice_id = client.upload_invoice(
+            &business,
+            &10_000,
+            &currency,
+            &due,
+            &String::from_str(&env, "Invoice"),
+        );
+        client.verify_invoice(&admin, &invoice_id);
+        client.set_invoice_insurance(&business, &invoice_id, &insured);
+        let bid_id = client.place_bid(&investor, &invoice_id, &10_000, &11_000);
+        client.accept_bid(&business, &invoice_id, &bid_id);
+        invoice_id
+    };
+
+    // Businesses without history start at the neutral risk score
+    assert_eq!(
+        client.get_business_risk_score(&business),
+        crate::insurance::NEW_BUSINESS_RISK_SCORE
+    );
+    let now = env.ledger().timestamp();
+    let insured_id = fund(now + year / 2, true);
+    let investment = client.get_invoice_investments(&insured_id).get(0).unwrap();
+    let coverage = match investment.insurance {
+        crate::insurance::InvestmentInsurance::Covered(coverage) => coverage,
+        crate::insurance::InvestmentInsurance::Uninsured => panic!("investment is insured"),
+    };
+    // 100 base + 1000 * 50% risk + 400 * half a year
+    assert_eq!(coverage.risk_score, 50);
+    assert_eq!(coverage.premium_bps, 800);
+    assert_eq!(coverage.premium, 800);
+    assert_eq!(coverage.coverage_amount, 10_000);
+    assert_eq!(client.get_insurance_fund_balance(&currency), 800);
+
+    // Insurance cannot be switched once funding has started
+    assert_eq!(
+        client.try_set_invoice_insurance(&business, &insured_id, &false),
+        Err(Ok(QuickLendXError::InvoiceAlreadyFunded))
+    );
+
+    // A default raises the risk score and the quoted premium
+    env.ledger()
+        .with_mut(|l| l.timestamp = now + year / 2 + crate::config::DEFAULT_GRACE_PERIOD + 1);
+    client.handle_default(&admin, &insured_id);
+    assert_eq!(client.get_business_risk_score(&business), 100);
+    let now = env.ledger().timestamp();
+    let uninsured_id = fund(now + year, false);
+    // 100 base + 1000 * 100% risk + 400 * one year
+    assert_eq!(client.quote_insurance_premium(&uninsured_id), 1_500);
+    let investment = client
+        .get_invoice_investments(&uninsured_id)
+        .get(0)
+        .unwrap();
+    assert_eq!(
+        investment.insurance,
+        crate::insurance::InvestmentInsurance::Uninsured
+    );
+}
+
+#[test]
+fn test_backstop_providers_cover_fund_shortfall() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let first = Address::generate(&env);
+    let second = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86400;
+
+    assert_eq!(
+        client.try_register_backstop(&first, &currency, &0, &1),
+        Err(Ok(QuickLendXError::InvalidAmount))
+    );
+    client.register_backstop(&second, &currency, &3_000, &2);
+    client.register_backstop(&first, &currency, &1_000, &1);
+    let providers = client.get_backstop_providers(&currency);
+    assert_eq!(providers.get(0).unwrap().provider, first);
+    assert_eq!(providers.get(1).unwrap().provider, second);
+
+    assert_eq!(
+        client.try_set_backstop_fee(&admin, &(crate::backstop::MAX_BACKSTOP_FEE_BPS + 1)),
+        Err(Ok(QuickLendXError::InvalidAmount))
+    );
+    client.set_backstop_fee(&admin, &5_000);
+
+    let invoice_id = client.upload_invoice(
+        &business,
+        &10_000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    client.set_invoice_insurance(&business, &invoice_id, &true);
+    let bid_id = client.place_bid(&investor, &invoice_id, &10_000, &11_000);
+    client.accept_bid(&business, &invoice_id, &bid_id);
+    let investment = client.get_invoice_investments(&invoice_id).get(0).unwrap();
+    let premium = match investment.insurance {
+        crate::insurance::InvestmentInsurance::Covered(coverage) => coverage.premium,
+        crate::insurance::InvestmentInsurance::Uninsured => panic!("investment is insured"),
+    };
+
+    // Half the premium is split between providers by undrawn commitment
+    let providers = client.get_backstop_providers(&currency);
+    let first_fee = providers.get(0).unwrap().fees_earned;
+    let second_fee = providers.get(1).unwrap().fees_earned;
+    assert_eq!(first_fee, premium / 2 / 4);
+    assert_eq!(second_fee, premium / 2 * 3 / 4);
+    let fund = premium - first_fee - second_fee;
+    assert_eq!(client.get_insurance_fund_balance(&currency), fund);
+
+    env.ledger()
+        .with_mut(|l| l.timestamp = due_date + crate::config::DEFAULT_GRACE_PERIOD + 1);
+    client.handle_default(&admin, &invoice_id);
+    let claim_id =
+        client.submit_insurance_claim(&investor, &investment.investment_id, &(fund + 2_000));
+    client.start_claim_review(&admin, &claim_id);
+    client.approve_claim(&admin, &claim_id);
+    client.pay_claim(&admin, &claim_id);
+
+    // The shortfall is drawn from the highest-priority provider first
+    let providers = client.get_backstop_providers(&currency);
+    assert_eq!(providers.get(0).unwrap().drawn, 1_000);
+    assert_eq!(providers.get(1).unwrap().drawn, 1_000);
+    assert_eq!(client.get_insurance_fund_balance(&currency), 0);
+
+    // A commitment cannot drop below what has been drawn
+    assert_eq!(
+        client.try_register_backstop(&second, &currency, &500, &2),
+        Err(Ok(QuickLendXError::InvalidAmount))
+    );
+}
+
+#[test]
+fn test_funding_contributions_aggregated_per_investor() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let investor1 = Address::generate(&env);
+    let investor2 = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86400;
+
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    for (investor, amount) in [(&investor1, 300), (&investor2, 500), (&investor1, 200)] {
+        let bid_id = client.place_bid(investor, &invoice_id, &amount, &(amount + 10));
+        client.accept_bid(&business, &invoice_id, &bid_id);
+    }
+    assert_eq!(client.get_invoice(&invoice_id).status, InvoiceStatus::Funded);
+
+    // Each investor appears once with their total share of the round
+    assert_eq!(
+        client.get_funding_contributions(&invoice_id),
+        vec![
+            &env,
+            FundingContribution {
+                investor: investor1.clone(),
+                amount: 500,
+            },
+            FundingContribution {
+                investor: investor2.clone(),
+                amount: 500,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_settlement_levy_funds_default_recovery() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let outsider = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86400;
+
+    assert_eq!(
+        client.try_set_insurance_pool_config(
+            &admin,
+            &InsurancePoolConfig {
+                settlement_levy_bps: crate::insurance::MAX_SETTLEMENT_LEVY_BPS + 1,
+                recovery_bps: 5_000,
+            }
+        ),
+        Err(Ok(QuickLendXError::InvalidAmount))
+    );
+    client.set_insurance_pool_config(
+        &admin,
+        &InsurancePoolConfig {
+            settlement_levy_bps: 100,
+            recovery_bps: 2_000,
+        },
+    );
+
+    let fund = |amount: i128| {
+        let invoice_id = client.upload_invoice(
+            &business,
+            &amount,
+            &currency,
+            &due_date,
+            &String::from_str(&env, "Invoice"),
+        );
+        client.verify_invoice(&admin, &invoice_id);
+        let bid_id = client.place_bid(&investor, &invoice_id, &amount, &(amount + amount / 10));
+        client.accept_bid(&business, &invoice_id, &bid_id);
+        invoice_id
+    };
+
+    // 1% of the investor's return is diverted into the pool at settlement
+    let paid_id = fund(10_000);
+    let plan = client.build_settlement_plan(&paid_id, &11_000);
+    assert_eq!(plan.total_insurance_levy, 110);
+    assert_eq!(plan.total_investor_return, 10_890);
+    client.settle_invoice(&business, &paid_id, &11_000);
+    assert_eq!(client.get_insurance_fund_balance(&currency), 110);
+
+    // Defaulted investors recover 20% of principal, once
+    let defaulted_id = fund(500);
+    assert_eq!(
+        client.try_claim_insurance(&investor, &defaulted_id),
+        Err(Ok(QuickLendXError::InvalidStatus))
+    );
+    env.ledger()
+        .with_mut(|l| l.timestamp = due_date + crate::config::DEFAULT_GRACE_PERIOD + 1);
+    client.handle_default(&admin, &defaulted_id);
+    assert_eq!(
+        client.try_claim_insurance(&outsider, &defaulted_id),
+        Err(Ok(QuickLendXError::NotInvestor))
+    );
+    assert_eq!(client.claim_insurance(&investor, &defaulted_id), 100);
+    assert_eq!(client.get_insurance_fund_balance(&currency), 10);
+    assert_eq!(
+        client.try_claim_insurance(&investor, &defaulted_id),
+        Err(Ok(QuickLendXError::ClaimAlreadyExists))
+    );
+}
+
+#[test]
+fn test_dispute_freezes_and_drives_escrow() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let investor1 = Address::generate(&env);
+    let investor2 = Address::generate(&env);
+    let arbitrator = Address::generate(&env);
+    let outsider = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86400;
+    let reason = String::from_str(&env, "Goods never delivered");
+
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    assert_eq!(
+        client.try_open_dispute(&business, &invoice_id, &reason),
+        Err(Ok(QuickLendXError::StorageKeyNotFound))
+    );
+    for (investor, amount) in [(&investor1, 600), (&investor2, 400)] {
+        let bid_id = client.place_bid(investor, &invoice_id, &amount, &(amount + 10));
+        client.accept_bid(&business, &invoice_id, &bid_id);
+    }
+
+    assert_eq!(
+        client.try_open_dispute(&outsider, &invoice_id, &reason),
+        Err(Ok(QuickLendXError::Unauthorized))
+    );
+    client.open_dispute(&investor1, &invoice_id, &reason);
+    assert_eq!(
+        client.try_open_dispute(&business, &invoice_id, &reason),
+        Err(Ok(QuickLendXError::DisputeAlreadyOpen))
+    );
+    assert_eq!(client.get_open_disputes(), vec![&env, invoice_id.clone()]);
+
+    // Escrow cannot move while the dispute is open
+    assert_eq!(
+        client.try_release_escrow_funds(&admin, &invoice_id),
+        Err(Ok(QuickLendXError::EscrowFrozen))
+    );
+    assert_eq!(
+        client.try_refund_escrow_funds(&admin, &invoice_id),
+        Err(Ok(QuickLendXError::EscrowFrozen))
+    );
+
+    // The arbitrator splits the funds 25/75 between business and investors
+    client.set_arbitrator(&admin, &arbitrator);
+    assert_eq!(
+        client.try_resolve_dispute(&outsider, &invoice_id, &DisputeOutcome::Refund),
+        Err(Ok(QuickLendXError::Unauthorized))
+    );
+    assert_eq!(
+        client.try_resolve_dispute(&arbitrator, &invoice_id, &DisputeOutcome::Split(10_001)),
+        Err(Ok(QuickLendXError::InvalidAmount))
+    );
+    // Claim appeal rulings do not settle escrow
+    assert_eq!(
+        client.try_resolve_dispute(&arbitrator, &invoice_id, &DisputeOutcome::Upheld),
+        Err(Ok(QuickLendXError::OperationNotAllowed))
+    );
+    client.resolve_dispute(&arbitrator, &invoice_id, &DisputeOutcome::Split(2_500));
+
+    let dispute = client.get_dispute(&invoice_id).unwrap();
+    assert_eq!(
+        dispute.status,
+        crate::disputes::DisputeStatus::Resolved(DisputeOutcome::Split(2_500))
+    );
+    assert_eq!(dispute.resolved_by, Some(arbitrator.clone()));
+    assert!(client.get_open_disputes().is_empty());
+    for escrow in client.get_invoice_escrows(&invoice_id).iter() {
+        assert_eq!(escrow.status, crate::payments::EscrowStatus::Split);
+        let ledger = client.get_escrow_ledger(&escrow.escrow_id);
+        assert_eq!(ledger.get(1).unwrap().amount, escrow.amount / 4);
+        assert_eq!(ledger.get(2).unwrap().amount, escrow.amount * 3 / 4);
+    }
+    assert_eq!(
+        client.try_resolve_dispute(&arbitrator, &invoice_id, &DisputeOutcome::Release),
+        Err(Ok(QuickLendXError::InvalidStatus))
+    );
+}
+
+#[soroban_sdk::contract]
+pub struct RecordingObserver;
+
+#[soroban_sdk::contractimpl]
+impl RecordingObserver {
+    pub fn on_invoice_event(
+        env: Env,
+        invoice_id: BytesN<32>,
+        event: crate::observers::LifecycleEvent,
+    ) {
+        let mut seen: Vec<crate::observers::LifecycleEvent> = env
+            .storage()
+            .instance()
+            .get(&invoice_id)
+            .unwrap_or_else(|| Vec::new(&env));
+        seen.push_back(event);
+        env.storage().instance().set(&invoice_id, &seen);
+    }
+
+    pub fn seen(env: Env, invoice_id: BytesN<32>) -> Vec<crate::observers::LifecycleEvent> {
+        env.storage()
+            .instance()
+            .get(&invoice_id)
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+}
+
+#[soroban_sdk::contract]
+pub struct FailingObserver;
+
+#[soroban_sdk::contractimpl]
+impl FailingObserver {
+    pub fn on_invoice_event(
+        _env: Env,
+        _invoice_id: BytesN<32>,
+        _event: crate::observers::LifecycleEvent,
+    ) {
+        panic!("observer failure");
+    }
+}
+
+#[test]
+fn test_observers_notified_with_failure_isolation() {
+    use crate::observers::LifecycleEvent;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86400;
+
+    let failing = env.register(FailingObserver, ());
+    let recorder = env.register(RecordingObserver, ());
+    let recorder_client = RecordingObserverClient::new(&env, &recorder);
+    client.register_observer(&admin, &failing);
+    client.register_observer(&admin, &recorder);
+    assert_eq!(
+        client.try_register_observer(&admin, &recorder),
+        Err(Ok(QuickLendXError::OperationNotAllowed))
+    );
+
+    // A failing observer does not block the transition or later observers
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    let bid_id = client.place_bid(&investor, &invoice_id, &1000, &1100);
+    client.accept_bid(&business, &invoice_id, &bid_id);
+    client.settle_invoice(&business, &invoice_id, &1100);
+    assert_eq!(
+        recorder_client.seen(&invoice_id),
+        vec![
+            &env,
+            LifecycleEvent::Verified,
+            LifecycleEvent::Funded,
+            LifecycleEvent::Settled
+        ]
+    );
+
+    client.remove_observer(&admin, &recorder);
+    assert_eq!(client.get_observers(), vec![&env, failing.clone()]);
+    assert_eq!(
+        client.try_remove_observer(&admin, &recorder),
+        Err(Ok(QuickLendXError::StorageKeyNotFound))
+    );
+}
+
+#[test]
+fn test_business_cancels_unfunded_invoice() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86400;
+    let upload = || {
+        client.upload_invoice(
+            &business,
+            &1000,
+            &currency,
+            &due_date,
+            &String::from_str(&env, "Invoice"),
+        )
+    };
+
+    let invoice_id = upload();
+    client.verify_invoice(&admin, &invoice_id);
+    let bid_id = client.place_bid(&investor, &invoice_id, &500, &550);
+    assert_eq!(
+        client.try_cancel_invoice(&investor, &invoice_id),
+        Err(Ok(QuickLendXError::NotBusinessOwner))
+    );
+    client.cancel_invoice(&business, &invoice_id);
+    assert_eq!(
+        client.get_invoice(&invoice_id).status,
+        InvoiceStatus::Cancelled
+    );
+    assert_eq!(client.get_bid(&bid_id).unwrap().status, BidStatus::Rejected);
+    assert!(client.get_available_invoices().is_empty());
+    assert_eq!(
+        client.get_invoice_count_by_status(&InvoiceStatus::Cancelled),
+        1
+    );
+    assert_eq!(
+        client.try_place_bid(&investor, &invoice_id, &500, &550),
+        Err(Ok(QuickLendXError::InvalidStatus))
+    );
+
+    // Once a bid is accepted the invoice can no longer be cancelled
+    let funded_id = upload();
+    client.verify_invoice(&admin, &funded_id);
+    let bid_id = client.place_bid(&investor, &funded_id, &400, &440);
+    client.accept_bid(&business, &funded_id, &bid_id);
+    assert_eq!(
+        client.try_cancel_invoice(&business, &funded_id),
+        Err(Ok(QuickLendXError::InvoiceAlreadyFunded))
+    );
+}
+
+#[test]
+fn test_position_value_accrues_linearly_to_maturity() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let investor1 = Address::generate(&env);
+    let investor2 = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let start = env.ledger().timestamp();
+    let due_date = start + 100 * 86_400;
+
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    let bid1 = client.place_bid(&investor1, &invoice_id, &600, &700);
+    let bid2 = client.place_bid(&investor2, &invoice_id, &400, &500);
+    client.accept_bid(&business, &invoice_id, &bid1);
+    client.accept_bid(&business, &invoice_id, &bid2);
+    let investment_id = client
+        .get_invoice_investments(&invoice_id)
+        .get(0)
+        .unwrap()
+        .investment_id;
+
+    // 60% of the 200 promised profit, accrued linearly over the tenor
+    let value = client.get_position_value(&investment_id, &start);
+    assert_eq!((value.principal, value.expected_return), (600, 720));
+    assert_eq!(value.value, 600);
+    let value = client.get_position_value(&investment_id, &(start + 25 * 86_400));
+    assert_eq!(value.accrued_profit, 30);
+    assert_eq!(value.value, 630);
+    let value = client.get_position_value(&investment_id, &(due_date + 86_400));
+    assert_eq!(value.value, 720);
+
+    // Closed positions are no longer marked to model
+    client.settle_invoice(&business, &invoice_id, &1200);
+    assert_eq!(
+        client.try_get_position_value(&investment_id, &due_date),
+        Err(Ok(QuickLendXError::InvalidStatus))
+    );
+}
+
+#[test]
+fn test_maturity_ladder_buckets_outstanding_funding() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let day = 86_400u64;
+    let now = env.ledger().timestamp();
+
+    assert_eq!(
+        client.try_set_maturity_buckets(&admin, &vec![&env, 30, 7]),
+        Err(Ok(QuickLendXError::InvalidAmount))
+    );
+    client.set_maturity_buckets(&admin, &vec![&env, 7, 30, 60]);
+
+    let fund = |amount: i128, days: u64| {
+        let invoice_id = client.upload_invoice(
+            &business,
+            &amount,
+            &currency,
+            &(now + days * day),
+            &String::from_str(&env, "Invoice"),
+        );
+        client.verify_invoice(&admin, &invoice_id);
+        let bid_id = client.place_bid(&investor, &invoice_id, &amount, &(amount + 10));
+        client.accept_bid(&business, &invoice_id, &bid_id);
+        invoice_id
+    };
+    let soon = fund(100, 5);
+    fund(200, 20);
+    fund(300, 45);
+    fund(400, 90);
+
+    let amounts = |ladder: &crate::maturity::MaturityLadder| {
+        let mut amounts = Vec::new(&env);
+        for bucket in ladder.buckets.iter() {
+            amounts.push_back(bucket.amount);
+        }
+        amounts
+    };
+    let ladder = client.get_maturity_ladder();
+    assert_eq!(ladder.total, 1000);
+    assert_eq!(ladder.buckets.get(1).unwrap().min_days, 8);
+    assert_eq!(ladder.buckets.get(3).unwrap().max_days, None);
+    assert_eq!(amounts(&ladder), vec![&env, 100, 200, 300, 400]);
+
+    // Repaid funding leaves the ladder; the rest rolls towards maturity
+    client.settle_invoice(&business, &soon, &110);
+    env.ledger().with_mut(|l| l.timestamp = now + 21 * day);
+    let ladder = client.get_maturity_ladder();
+    assert_eq!(ladder.overdue, 200);
+    assert_eq!(amounts(&ladder), vec![&env, 0, 300, 0, 400]);
+    assert_eq!(ladder.total, 900);
+}
+
+#[test]
+fn test_invoices_expire_after_funding_deadline() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let now = env.ledger().timestamp();
+    let due_date = now + 30 * 86_400;
+    let deadline = now + 86_400;
+    let upload = || {
+        let invoice_id = client.upload_invoice(
+            &business,
+            &1000,
+            &currency,
+            &due_date,
+            &String::from_str(&env, "Invoice"),
+        );
+        client.verify_invoice(&admin, &invoice_id);
+        invoice_id
+    };
+
+    let stale_id = upload();
+    let funded_id = upload();
+    let open_id = upload();
+    assert_eq!(
+        client.try_set_funding_deadline(&business, &stale_id, &Some(due_date + 1)),
+        Err(Ok(QuickLendXError::InvalidTimestamp))
+    );
+    for invoice_id in [&stale_id, &funded_id] {
+        client.set_funding_deadline(&business, invoice_id, &Some(deadline));
+    }
+    let stale_bid = client.place_bid(&investor, &stale_id, &500, &550);
+    let funded_bid = client.place_bid(&investor, &funded_id, &500, &550);
+    client.accept_bid(&business, &funded_id, &funded_bid);
+
+    // Nothing expires before the deadline
+    assert!(client.expire_invoices().is_empty());
+
+    env.ledger().with_mut(|l| l.timestamp = deadline + 1);
+    assert_eq!(
+        client.try_accept_bid(&business, &stale_id, &stale_bid),
+        Err(Ok(QuickLendXError::InvalidStatus))
+    );
+    assert_eq!(client.expire_invoices(), vec![&env, stale_id.clone()]);
+    assert_eq!(client.get_invoice(&stale_id).status, InvoiceStatus::Expired);
+    assert_eq!(
+        client.get_bid(&stale_bid).unwrap().status,
+        BidStatus::Rejected
+    );
+    assert_eq!(
+        client.get_available_invoices(),
+        vec![&env, funded_id, open_id]
+    );
+}
+
+#[test]
+fn test_risk_inputs_snapshotted_at_funding() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let tenor = 100 * 86_400u64;
+
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1000,
+        &currency,
+        &(env.ledger().timestamp() + tenor),
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    let bid_id = client.place_bid(&investor, &invoice_id, &800, &880);
+    client.accept_bid(&business, &invoice_id, &bid_id);
+
+    let investment = client.get_invoice_investments(&invoice_id).get(0).unwrap();
+    assert_eq!(
+        investment.risk,
+        crate::investment::RiskSnapshot::Recorded(crate::investment::RiskInputs {
+            risk_score: crate::insurance::NEW_BUSINESS_RISK_SCORE,
+            tenor,
+            advance_rate_bps: 8_000,
+            apr_bps: 3_650,
+        })
+    );
+}
+
+#[test]
+fn test_financial_attestation_freshness_lowers_risk_score() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let stranger = Address::generate(&env);
+    let aging_hash = BytesN::from_array(&env, &[7u8; 32]);
+
+    assert!(client.get_attestation_status(&business).is_stale);
+    assert_eq!(
+        client.get_business_risk_score(&business),
+        crate::insurance::NEW_BUSINESS_RISK_SCORE
+    );
+    assert_eq!(
+        client.try_publish_attestation(&stranger, &3, &aging_hash),
+        Err(Ok(QuickLendXError::BusinessNotVerified))
+    );
+
+    client.set_attestation_max_age(&admin, &1_000);
+    let published_at = env.ledger().timestamp();
+    client.publish_attestation(&business, &3, &aging_hash);
+    let status = client.get_attestation_status(&business);
+    assert_eq!(status.count, 1);
+    assert_eq!(status.stale_at, Some(published_at + 1_000));
+    assert!(!status.is_stale);
+    assert_eq!(
+        client
+            .get_latest_attestation(&business)
+            .unwrap()
+            .ar_aging_hash,
+        aging_hash
+    );
+    assert_eq!(
+        client.get_business_risk_score(&business),
+        crate::insurance::NEW_BUSINESS_RISK_SCORE - crate::attestation::FRESH_ATTESTATION_CREDIT
+    );
+
+    env.ledger().set_timestamp(published_at + 1_000);
+    assert!(client.get_attestation_status(&business).is_stale);
+    assert_eq!(
+        client.get_business_risk_score(&business),
+        crate::insurance::NEW_BUSINESS_RISK_SCORE
+    );
+
+    // The whole history is kept and read a page at a time
+    for band in 4..16 {
+        client.publish_attestation(&business, &band, &aging_hash);
+    }
+    assert_eq!(client.get_attestation_status(&business).count, 13);
+    assert_eq!(client.get_latest_attestation(&business).unwrap().revenue_band, 15);
+    let attestations = client.get_attestations(&business, &0, &5);
+    assert_eq!(attestations.len(), 5);
+    assert_eq!(attestations.get(0).unwrap().revenue_band, 3);
+    let attestations = client.get_attestations(&business, &10, &5);
+    assert_eq!(attestations.len(), 3);
+    assert_eq!(attestations.get(2).unwrap().revenue_band, 15);
+}
+
+#[test]
+fn test_sealed_bid_auction_accepts_best_reveal() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let cheap = Address::generate(&env);
+    let pricey = Address::generate(&env);
+    let silent = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let start = env.ledger().timestamp();
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1000,
+        &currency,
+        &(start + 30 * 86_400),
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    client.start_auction(&business, &invoice_id, &1_000, &1_000);
+
+    // Open bids are closed while the auction runs
+    assert_eq!(
+        client.try_place_bid(&cheap, &invoice_id, &1000, &1100),
+        Err(Ok(QuickLendXError::AuctionInProgress))
+    );
+
+    let salt = BytesN::from_array(&env, &[1u8; 32]);
+    let cheap_commitment = client.compute_bid_commitment(&cheap, &1000, &1050, &salt);
+    let pricey_commitment = client.compute_bid_commitment(&pricey, &1000, &1100, &salt);
+    client.commit_bid(&cheap, &invoice_id, &cheap_commitment);
+    client.commit_bid(&pricey, &invoice_id, &pricey_commitment);
+    client.commit_bid(&silent, &invoice_id, &pricey_commitment);
+    assert_eq!(
+        client.try_reveal_bid(&cheap, &invoice_id, &1000, &1050, &salt),
+        Err(Ok(QuickLendXError::AuctionPhaseClosed))
+    );
+
+    env.ledger().set_timestamp(start + 1_000);
+    assert_eq!(
+        client.try_commit_bid(&silent, &invoice_id, &cheap_commitment),
+        Err(Ok(QuickLendXError::AuctionPhaseClosed))
+    );
+    // A reveal must match what was committed
+    assert_eq!(
+        client.try_reveal_bid(&cheap, &invoice_id, &1000, &1000, &salt),
+        Err(Ok(QuickLendXError::CommitmentMismatch))
+    );
+    client.reveal_bid(&pricey, &invoice_id, &1000, &1100, &salt);
+    let cheap_bid = client.reveal_bid(&cheap, &invoice_id, &1000, &1050, &salt);
+    assert_eq!(
+        client.try_finalize_auction(&invoice_id),
+        Err(Ok(QuickLendXError::AuctionPhaseClosed))
+    );
+
+    env.ledger().set_timestamp(start + 2_000);
+    assert_eq!(
+        client.finalize_auction(&invoice_id),
+        Some(cheap_bid.clone())
+    );
+    let auction = client.get_auction(&invoice_id).unwrap();
+    assert_eq!(auction.status, crate::auction::AuctionStatus::Settled);
+    assert_eq!(auction.revealed_bids.len(), 2);
+    assert_eq!(
+        client.get_bid(&cheap_bid).unwrap().status,
+        BidStatus::Accepted
+    );
+    assert_eq!(
+        client.get_invoice(&invoice_id).status,
+        InvoiceStatus::Funded
+    );
+}
+
+#[test]
+fn test_accept_best_bid_picks_lowest_expected_return() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let currency = Address::generate(&env);
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1000,
+        &currency,
+        &(env.ledger().timestamp() + 30 * 86_400),
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    assert_eq!(
+        client.try_accept_best_bid(&business, &invoice_id),
+        Err(Ok(QuickLendXError::StorageKeyNotFound))
+    );
+
+    let first = client.place_bid(&Address::generate(&env), &invoice_id, &600, &650);
+    let second = client.place_bid(&Address::generate(&env), &invoice_id, &400, &440);
+    let third = client.place_bid(&Address::generate(&env), &invoice_id, &500, &420);
+    client.withdraw_bid(&client.get_bid(&third).unwrap().investor, &third);
+
+    assert_eq!(client.accept_best_bid(&business, &invoice_id), second);
+    assert_eq!(client.accept_best_bid(&business, &invoice_id), first);
+    assert_eq!(
+        client.get_invoice(&invoice_id).status,
+        InvoiceStatus::Funded
+    );
+}
+
+#[test]
+fn test_repeat_investors_get_priority_window() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let repeat = Address::generate(&env);
+    let newcomer = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 30 * 86_400;
+    let description = String::from_str(&env, "Invoice");
+
+    let first = client.upload_invoice(&business, &1000, &currency, &due_date, &description);
+    client.verify_invoice(&admin, &first);
+    let bid_id = client.place_bid(&repeat, &first, &1000, &1050);
+    client.accept_bid(&business, &first, &bid_id);
+
+    assert_eq!(
+        client.try_set_priority_window(&business, &(crate::priority::MAX_PRIORITY_WINDOW + 1)),
+        Err(Ok(QuickLendXError::InvalidTimestamp))
+    );
+    client.set_priority_window(&business, &3_600);
+    let second = client.upload_invoice(&business, &1000, &currency, &due_date, &description);
+    client.verify_invoice(&admin, &second);
+    let opens_at = env.ledger().timestamp() + 3_600;
+    assert_eq!(client.get_priority_window_end(&second), Some(opens_at));
+
+    assert_eq!(
+        client.try_place_bid(&newcomer, &second, &500, &520),
+        Err(Ok(QuickLendXError::PriorityWindowActive))
+    );
+    client.place_bid(&repeat, &second, &500, &520);
+
+    env.ledger().set_timestamp(opens_at);
+    client.place_bid(&newcomer, &second, &500, &520);
+}
+
+#[test]
+fn test_self_dealing_blocked_and_recycled_funding_flagged() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let officer = Address::generate(&env);
+    let affiliate = Address::generate(&env);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 30 * 86_400;
+    let description = String::from_str(&env, "Invoice");
+
+    client.set_compliance_officer(&admin, &officer);
+    assert_eq!(
+        client.try_add_affiliate(&investor, &business, &affiliate),
+        Err(Ok(QuickLendXError::Unauthorized))
+    );
+    client.add_affiliate(&officer, &business, &affiliate);
+    assert_eq!(client.get_affiliates(&business).len(), 1);
+
+    let first = client.upload_invoice(&business, &1000, &currency, &due_date, &description);
+    client.verify_invoice(&admin, &first);
+    assert_eq!(
+        client.try_place_bid(&business, &first, &1000, &1050),
+        Err(Ok(QuickLendXError::SelfDealing))
+    );
+    assert_eq!(
+        client.try_place_bid(&affiliate, &first, &1000, &1050),
+        Err(Ok(QuickLendXError::SelfDealing))
+    );
+
+    let bid_id = client.place_bid(&investor, &first, &1000, &1050);
+    client.accept_bid(&business, &first, &bid_id);
+    client.settle_invoice(&business, &first, &1050);
+    assert!(client.get_wash_flags(&0, &10).is_empty());
+
+    // Funding the business again right after being repaid is flagged
+    let second = client.upload_invoice(&business, &1000, &currency, &due_date, &description);
+    client.verify_invoice(&admin, &second);
+    let bid_id = client.place_bid(&investor, &second, &1000, &1050);
+    client.accept_bid(&business, &second, &bid_id);
+    let flags = client.get_wash_flags(&0, &10);
+    assert_eq!(flags.len(), 1);
+    assert_eq!(client.get_wash_flag_count(), 1);
+    assert!(client.get_wash_flags(&1, &10).is_empty());
+    let flag = flags.get(0).unwrap();
+    assert_eq!(flag.investor, investor);
+    assert_eq!(flag.settled_invoice, first);
+    assert_eq!(flag.funded_invoice, second);
+}
+
+#[test]
+fn test_min_ticket_size_blocks_dust_bids() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1000,
+        &currency,
+        &(env.ledger().timestamp() + 30 * 86_400),
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+
+    client.set_default_min_ticket(&admin, &100);
+    assert_eq!(client.get_min_ticket(&invoice_id), 100);
+    assert_eq!(
+        client.try_place_bid(&investor, &invoice_id, &50, &55),
+        Err(Ok(QuickLendXError::InvalidAmount))
+    );
+
+    client.set_invoice_min_ticket(&business, &invoice_id, &Some(400));
+    assert_eq!(client.get_min_ticket(&invoice_id), 400);
+    assert_eq!(
+        client.try_place_bid(&investor, &invoice_id, &300, &330),
+        Err(Ok(QuickLendXError::InvalidAmount))
+    );
+    let bid_id = client.place_bid(&investor, &invoice_id, &800, &880);
+    client.accept_bid(&business, &invoice_id, &bid_id);
+
+    // The last slice may be smaller than the minimum to complete funding
+    client.place_bid(&Address::generate(&env), &invoice_id, &200, &220);
+
+    client.set_invoice_min_ticket(&business, &invoice_id, &None);
+    assert_eq!(client.get_min_ticket(&invoice_id), 100);
+}
+
+#[test]
+fn test_counter_offer_rounds_and_acceptance() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1000,
+        &currency,
+        &(env.ledger().timestamp() + 30 * 86_400),
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    let bid_id = client.place_bid(&investor, &invoice_id, &1000, &1100);
+
+    client.counter_bid(&business, &bid_id, &1040);
+    assert_eq!(
+        client.get_bid(&bid_id).unwrap().status,
+        BidStatus::Countered
+    );
+    // A countered bid cannot be accepted until the investor responds
+    assert_eq!(
+        client.try_accept_bid(&business, &invoice_id, &bid_id),
+        Err(Ok(QuickLendXError::InvalidStatus))
+    );
+    client.decline_counter(&investor, &bid_id);
+    assert_eq!(client.get_bid(&bid_id).unwrap().expected_return, 1100);
+
+    client.counter_bid(&business, &bid_id, &1060);
+    client.decline_counter(&investor, &bid_id);
+    let negotiation = client.counter_bid(&business, &bid_id, &1070);
+    assert_eq!(negotiation.rounds, crate::negotiation::MAX_COUNTER_ROUNDS);
+    assert_eq!(negotiation.original_return, 1100);
+
+    client.accept_counter(&investor, &bid_id);
+    let bid = client.get_bid(&bid_id).unwrap();
+    assert_eq!(bid.status, BidStatus::Accepted);
+    assert_eq!(bid.expected_return, 1070);
+    assert_eq!(
+        client.get_invoice(&invoice_id).status,
+        InvoiceStatus::Funded
+    );
+    assert_eq!(
+        client.try_counter_bid(&business, &bid_id, &1050),
+        Err(Ok(QuickLendXError::InvalidStatus))
+    );
+}
+
+#[test]
+fn test_business_rejects_bid() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1000,
+        &currency,
+        &(env.ledger().timestamp() + 30 * 86_400),
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    let bid_id = client.place_bid(&investor, &invoice_id, &1000, &1100);
+
+    assert_eq!(
+        client.try_reject_bid(&investor, &invoice_id, &bid_id),
+        Err(Ok(QuickLendXError::NotBusinessOwner))
+    );
+    client.reject_bid(&business, &invoice_id, &bid_id);
+    assert_eq!(client.get_bid(&bid_id).unwrap().status, BidStatus::Rejected);
+    assert_eq!(
+        client.try_reject_bid(&business, &invoice_id, &bid_id),
+        Err(Ok(QuickLendXError::OperationNotAllowed))
+    );
+    assert_eq!(
+        client.try_accept_bid(&business, &invoice_id, &bid_id),
+        Err(Ok(QuickLendXError::InvalidStatus))
+    );
+}
+
+#[test]
+fn test_investor_cap_per_invoice() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let first = Address::generate(&env);
+    let second = Address::generate(&env);
+    let third = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1000,
+        &currency,
+        &(env.ledger().timestamp() + 30 * 86_400),
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+
+    assert_eq!(
+        client.try_set_max_investors(&admin, &0),
+        Err(Ok(QuickLendXError::InvalidAmount))
+    );
+    client.set_max_investors(&admin, &2);
+
+    let late_bid = client.place_bid(&third, &invoice_id, &100, &110);
+    for investor in [&first, &second] {
+        let bid_id = client.place_bid(investor, &invoice_id, &300, &330);
+        client.accept_bid(&business, &invoice_id, &bid_id);
+    }
+    // A third investor is turned away, at bidding and at acceptance
+    assert_eq!(
+        client.try_place_bid(&third, &invoice_id, &400, &440),
+        Err(Ok(QuickLendXError::TooManyInvestors))
+    );
+    assert_eq!(
+        client.try_accept_bid(&business, &invoice_id, &late_bid),
+        Err(Ok(QuickLendXError::TooManyInvestors))
+    );
+    // Existing investors can still top up
+    let bid_id = client.place_bid(&first, &invoice_id, &400, &440);
+    client.accept_bid(&business, &invoice_id, &bid_id);
+    assert_eq!(client.get_invoice(&invoice_id).investors.len(), 2);
+}
+
+#[test]
+fn test_leftover_bids_released_once_invoice_funded() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let currency = Address::generate(&env);
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1000,
+        &currency,
+        &(env.ledger().timestamp() + 30 * 86_400),
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    let investor = Address::generate(&env);
+    let withdrawn = client.place_bid(&investor, &invoice_id, &200, &220);
+    let winner = client.place_bid(&Address::generate(&env), &invoice_id, &1000, &1080);
+    let leftover = client.place_bid(&Address::generate(&env), &invoice_id, &500, &540);
+
+    client.withdraw_bid(&investor, &withdrawn);
+    assert_eq!(
+        client.try_withdraw_bid(&investor, &withdrawn),
+        Err(Ok(QuickLendXError::OperationNotAllowed))
+    );
+    client.accept_bid(&business, &invoice_id, &winner);
+
+    // Held funds of bids that can no longer be accepted are returned
+    assert_eq!(
+        client.get_bid(&leftover).unwrap().status,
+        BidStatus::Rejected
+    );
+    assert_eq!(
+        client.get_bid(&withdrawn).unwrap().status,
+        BidStatus::Withdrawn
+    );
+    assert_eq!(client.get_bid(&winner).unwrap().status, BidStatus::Accepted);
+}
+
+#[test]
+fn test_batched_settlement_resumes_until_paid() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let currency = Address::generate(&env);
+    let invoice_id = client.upload_invoice(
+        &business,
+        &900,
+        &currency,
+        &(env.ledger().timestamp() + 30 * 86_400),
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    for _ in 0..3 {
+        let bid_id = client.place_bid(&Address::generate(&env), &invoice_id, &300, &330);
+        client.accept_bid(&business, &invoice_id, &bid_id);
+    }
+
+    let progress = client.begin_settlement(&business, &invoice_id, &990);
+    let total = progress.plan.transfers.len();
+    assert!(total >= 3);
+    assert_eq!(progress.next_transfer, 0);
+    assert_eq!(
+        client.try_settle_invoice(&business, &invoice_id, &990),
+        Err(Ok(QuickLendXError::OperationNotAllowed))
+    );
+
+    let progress = client.process_settlement(&invoice_id, &2);
+    assert_eq!(progress.next_transfer, 2);
+    assert_eq!(
+        client.get_invoice(&invoice_id).status,
+        InvoiceStatus::Funded
+    );
+    assert_eq!(
+        client
+            .get_settlement_progress(&invoice_id)
+            .unwrap()
+            .next_transfer,
+        2
+    );
+
+    let progress = client.process_settlement(&invoice_id, &100);
+    assert_eq!(progress.next_transfer, total);
+    assert_eq!(client.get_invoice(&invoice_id).status, InvoiceStatus::Paid);
+    assert!(client.get_settlement_progress(&invoice_id).is_none());
+    assert_eq!(
+        client.try_process_settlement(&invoice_id, &1),
+        Err(Ok(QuickLendXError::StorageKeyNotFound))
+    );
+}
+
+#[test]
+fn test_settlement_with_claims_pays_on_pull() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let currency = Address::generate(&env);
+    let invoice_id = client.upload_invoice(
+        &business,
+        &600,
+        &currency,
+        &(env.ledger().timestamp() + 30 * 86_400),
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    let first = Address::generate(&env);
+    let second = Address::generate(&env);
+    let bid_id = client.place_bid(&first, &invoice_id, &300, &330);
+    client.accept_bid(&business, &invoice_id, &bid_id);
+    let bid_id = client.place_bid(&second, &invoice_id, &300, &330);
+    client.accept_bid(&business, &invoice_id, &bid_id);
+
+    client.settle_invoice_with_claims(&business, &invoice_id, &660);
+    assert_eq!(client.get_invoice(&invoice_id).status, InvoiceStatus::Paid);
+    let owed = client.get_claimable_settlement(&invoice_id, &first);
+    assert!(owed > 0);
+    assert!(client.get_claimable_settlement(&invoice_id, &second) > 0);
+
+    assert_eq!(client.claim_settlement(&first, &invoice_id), owed);
+    assert_eq!(client.get_claimable_settlement(&invoice_id, &first), 0);
+    assert_eq!(
+        client.try_claim_settlement(&first, &invoice_id),
+        Err(Ok(QuickLendXError::StorageKeyNotFound))
+    );
+    assert_eq!(
+        client.try_claim_settlement(&Address::generate(&env), &invoice_id),
+        Err(Ok(QuickLendXError::StorageKeyNotFound))
+    );
+}
+
+#[test]
+fn test_accept_bid_rejects_bids_that_no_longer_fit() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let currency = Address::generate(&env);
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1000,
+        &currency,
+        &(env.ledger().timestamp() + 30 * 86_400),
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    let large = client.place_bid(&Address::generate(&env), &invoice_id, &600, &660);
+    let too_big = client.place_bid(&Address::generate(&env), &invoice_id, &500, &550);
+    let small = client.place_bid(&Address::generate(&env), &invoice_id, &300, &330);
+    let last = client.place_bid(&Address::generate(&env), &invoice_id, &100, &110);
+
+    client.accept_bid(&business, &invoice_id, &large);
+    assert_eq!(
+        client.get_bid(&too_big).unwrap().status,
+        BidStatus::Rejected
+    );
+    assert_eq!(client.get_bid(&small).unwrap().status, BidStatus::Placed);
+    assert_eq!(client.get_bid(&last).unwrap().status, BidStatus::Placed);
+
+    client.accept_bid(&business, &invoice_id, &small);
+    assert_eq!(client.get_bid(&last).unwrap().status, BidStatus::Placed);
+    client.accept_bid(&business, &invoice_id, &last);
+    assert_eq!(
+        client.get_invoice(&invoice_id).status,
+        InvoiceStatus::Funded
+    );
+    // Rejected bids stay listed on the invoice
+    env.as_contract(&contract_id, || {
+        assert_eq!(BidStorage::get_bids_for_invoice(&env, &invoice_id).len(), 4);
+    });
+}
+
+#[test]
+fn test_chunked_backup_restore() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 30 * 86_400;
+    let count = crate::backup::RESTORE_CHUNK_SIZE + 1;
+    let mut first = None;
+    for _ in 0..count {
+        let invoice_id = client.upload_invoice(
+            &business,
+            &1000,
+            &currency,
+            &due_date,
+            &String::from_str(&env, "Invoice"),
+        );
+        first.get_or_insert(invoice_id);
+    }
+    let first = first.unwrap();
+    let backup_id = client.create_backup(&admin, &String::from_str(&env, "Chunked"));
+    client.verify_invoice(&admin, &first);
+
+    let progress = client.restore_backup_chunk(&admin, &backup_id, &1);
+    assert_eq!(progress.total_chunks, 2);
+    assert_eq!(progress.staged_invoices, 1);
+    assert_eq!(
+        client.try_restore_backup_chunk(&admin, &backup_id, &2),
+        Err(Ok(QuickLendXError::StorageKeyNotFound))
+    );
+    assert_eq!(
+        client.try_finalize_backup_restore(&admin, &backup_id),
+        Err(Ok(QuickLendXError::OperationNotAllowed))
+    );
+    // Nothing is swapped in until the restore is finalized
+    assert_eq!(client.get_invoice(&first).status, InvoiceStatus::Verified);
+
+    client.restore_backup_chunk(&admin, &backup_id, &0);
+    let progress = client.restore_backup_chunk(&admin, &backup_id, &0);
+    assert_eq!(progress.staged_invoices, count);
+    assert!(progress.is_complete());
+
+    client.finalize_backup_restore(&admin, &backup_id);
+    assert_eq!(client.get_invoice(&first).status, InvoiceStatus::Pending);
+    assert_eq!(client.get_business_invoices(&business).len(), count);
+    assert!(client.get_restore_progress(&backup_id).is_none());
+}
+
+#[test]
+fn test_records_move_to_persistent_storage() {
+    use soroban_sdk::testutils::storage::Persistent as _;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (_, business) = verified_business(&env, &client);
+    let currency = Address::generate(&env);
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1000,
+        &currency,
+        &(env.ledger().timestamp() + 30 * 86_400),
+        &String::from_str(&env, "Invoice"),
+    );
+    let key = crate::storage::DataKey::Invoice(invoice_id.clone());
+
+    // Rewrite the invoice as a v5 deployment kept it, in instance storage
+    env.as_contract(&contract_id, || {
+        let invoice: Invoice = env.storage().persistent().get(&key).unwrap();
+        env.storage().persistent().remove(&key);
+        env.storage().instance().set(&invoice_id, &invoice);
+        crate::upgrade::set_schema_version(&env, 5);
+    });
+    // Records not yet migrated are still readable
+    assert_eq!(client.get_invoice(&invoice_id).amount, 1000);
+
+    let admin = client.get_admin().unwrap();
+    client.migrate(&admin);
+    env.as_contract(&contract_id, || {
+        assert!(env.storage().persistent().has(&key));
+        assert!(!env.storage().instance().has(&invoice_id));
+    });
+
+    env.ledger()
+        .with_mut(|li| li.sequence_number += 100 * crate::storage::DAY_IN_LEDGERS);
+    assert!(client.extend_ttl(&vec![&env, invoice_id.clone()]) >= 1);
+    env.as_contract(&contract_id, || {
+        assert_eq!(
+            env.storage().persistent().get_ttl(&key),
+            crate::storage::TTL_EXTEND_TO
+        );
+    });
+}
+
+#[test]
+fn test_staged_restore_can_be_inspected_and_discarded() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let currency = Address::generate(&env);
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1000,
+        &currency,
+        &(env.ledger().timestamp() + 30 * 86_400),
+        &String::from_str(&env, "Invoice"),
+    );
+    let backup_id = client.create_backup(&admin, &String::from_str(&env, "Before verify"));
+    let other_backup = client.create_backup(&admin, &String::from_str(&env, "Other"));
+    client.verify_invoice(&admin, &invoice_id);
+
+    client.restore_backup_chunk(&admin, &backup_id, &0);
+    assert_eq!(client.get_staged_backup(), Some(backup_id.clone()));
+    assert_eq!(
+        client.get_staged_invoice(&invoice_id).unwrap().status,
+        InvoiceStatus::Pending
+    );
+    assert_eq!(
+        client.get_invoice(&invoice_id).status,
+        InvoiceStatus::Verified
+    );
+    // Only one restore can be staged at a time
+    assert_eq!(
+        client.try_restore_backup_chunk(&admin, &other_backup, &0),
+        Err(Ok(QuickLendXError::OperationNotAllowed))
+    );
+
+    client.discard_backup_restore(&admin, &backup_id);
+    assert!(client.get_staged_backup().is_none());
+    assert!(client.get_staged_invoice(&invoice_id).is_none());
+    assert!(client.get_restore_progress(&backup_id).is_none());
+    assert_eq!(
+        client.get_invoice(&invoice_id).status,
+        InvoiceStatus::Verified
+    );
+
+    client.restore_backup_chunk(&admin, &other_backup, &0);
+    client.finalize_backup_restore(&admin, &other_backup);
+    assert_eq!(
+        client.get_invoice(&invoice_id).status,
+        InvoiceStatus::Pending
+    );
+    assert!(client.get_staged_invoice(&invoice_id).is_none());
+}
+
+#[test]
+fn test_backup_content_hash_detects_tampering() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let currency = Address::generate(&env);
+    client.upload_invoice(
+        &business,
+        &1000,
+        &currency,
+        &(env.ledger().timestamp() + 30 * 86_400),
+        &String::from_str(&env, "Invoice"),
+    );
+    let backup_id = client.create_backup(&admin, &String::from_str(&env, "Hashed"));
+    assert!(client.get_backup_content_hash(&backup_id).is_some());
+    assert!(client.validate_backup(&backup_id));
+
+    // Same count and valid amounts, but not the data that was backed up
+    env.as_contract(&contract_id, || {
+        let mut data = BackupStorage::get_backup_data(&env, &backup_id).unwrap();
+        let mut invoice = data.get(0).unwrap();
+        invoice.amount = 2000;
+        data.set(0, invoice);
+        BackupStorage::store_backup_data(&env, &backup_id, &data);
+    });
+    assert!(!client.validate_backup(&backup_id));
+    assert_eq!(
+        client.try_restore_backup(&admin, &backup_id),
+        Err(Ok(QuickLendXError::StorageError))
+    );
+    assert_eq!(
+        client.try_restore_backup_chunk(&admin, &backup_id, &0),
+        Err(Ok(QuickLendXError::StorageError))
+    );
+}
+
+#[test]
+fn test_migrate_indexes_to_typed_keys() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let currency = Address::generate(&env);
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1000,
+        &currency,
+        &(env.ledger().timestamp() + 30 * 86_400),
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    let bid_id = client.place_bid(&Address::generate(&env), &invoice_id, &500, &550);
+
+    // Rewrite the bid list and status index as a v7 deployment kept them
+    let bids_key = crate::storage::DataKey::BidList(invoice_id.clone());
+    let verified_key = crate::storage::DataKey::StatusInvoices(InvoiceStatus::Verified);
+    env.as_contract(&contract_id, || {
+        let bids: Vec<BytesN<32>> = env.storage().persistent().get(&bids_key).unwrap();
+        let verified: Vec<BytesN<32>> = env.storage().persistent().get(&verified_key).unwrap();
+        env.storage().persistent().remove(&bids_key);
+        env.storage().persistent().remove(&verified_key);
+        env.storage()
+            .instance()
+            .set(&(symbol_short!("bids"), invoice_id.clone()), &bids);
+        env.storage()
+            .instance()
+            .set(&symbol_short!("verified"), &verified);
+        crate::upgrade::set_schema_version(&env, 7);
+    });
+    assert_eq!(
+        client
+            .get_invoices_by_status(&InvoiceStatus::Verified)
+            .len(),
+        1
+    );
+
+    client.migrate(&admin);
+    env.as_contract(&contract_id, || {
+        assert!(env.storage().persistent().has(&bids_key));
+        assert!(env.storage().persistent().has(&verified_key));
+        assert!(!env
+            .storage()
+            .instance()
+            .has(&(symbol_short!("bids"), invoice_id.clone())));
+        assert!(!env.storage().instance().has(&symbol_short!("verified")));
+        assert_eq!(
+            BidStorage::get_bids_for_invoice(&env, &invoice_id),
+            vec![&env, bid_id.clone()]
+        );
+    });
+    assert_eq!(
+        client.get_invoices_by_status(&InvoiceStatus::Verified),
+        vec![&env, invoice_id]
+    );
+}
+
+#[test]
+fn test_admin_actions_logged_and_queryable_by_range() {
+    use soroban_sdk::xdr::ToXdr;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    env.ledger().set_timestamp(1_000);
+    let (admin, business) = verified_business(&env, &client);
+    let logged = client.get_admin_action_count();
+    assert!(logged >= 2);
+
+    env.ledger().set_timestamp(2_000);
+    client.set_max_investors(&admin, &5);
+    env.ledger().set_timestamp(3_000);
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1000,
+        &Address::generate(&env),
+        &(env.ledger().timestamp() + 30 * 86_400),
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    // Failed privileged calls leave no trace
+    assert!(client
+        .try_set_max_investors(&Address::generate(&env), &7)
+        .is_err());
+    assert_eq!(client.get_admin_action_count(), logged + 2);
+
+    let actions = client.get_admin_actions(&1_500, &2_500, &10);
+    assert_eq!(actions.len(), 1);
+    let action = actions.get(0).unwrap();
+    assert_eq!(action.action, Symbol::new(&env, "set_max_investors"));
+    assert_eq!(action.actor, admin);
+    assert_eq!(action.timestamp, 2_000);
+    let params: BytesN<32> = env.crypto().sha256(&(&5u32,).to_xdr(&env)).into();
+    assert_eq!(action.params_hash, params);
+    assert_eq!(client.get_admin_action(&action.id), Some(action));
+
+    let later = client.get_admin_actions(&2_000, &u64::MAX, &10);
+    assert_eq!(later.len(), 2);
+    assert_eq!(
+        later.get(1).unwrap().action,
+        Symbol::new(&env, "verify_invoice")
+    );
+    assert_eq!(client.get_admin_actions(&0, &u64::MAX, &1).len(), 1);
+    assert_eq!(client.get_admin_actions(&4_000, &u64::MAX, &10).len(), 0);
+}
+
+#[test]
+fn test_archive_and_restore_paid_invoice() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let currency = Address::generate(&env);
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1000,
+        &currency,
+        &(env.ledger().timestamp() + 30 * 86_400),
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    let bid_id = client.place_bid(&Address::generate(&env), &invoice_id, &1000, &1100);
+    client.accept_bid(&business, &invoice_id, &bid_id);
+    client.settle_invoice(&business, &invoice_id, &1100);
+    let paid = client.get_invoice(&invoice_id);
+
+    client.set_archive_age(&admin, &(10 * 86_400));
+    assert_eq!(client.archive_invoices(&10).len(), 0);
+    env.ledger().with_mut(|li| li.timestamp += 10 * 86_400);
+    assert_eq!(client.archive_invoices(&10), vec![&env, invoice_id.clone()]);
+
+    assert!(client.try_get_invoice(&invoice_id).is_err());
+    assert_eq!(client.get_invoices_by_status(&InvoiceStatus::Paid).len(), 0);
+    assert_eq!(client.get_business_invoices(&business).len(), 0);
+    assert_eq!(
+        client.get_business_archive(&business),
+        vec![&env, invoice_id.clone()]
+    );
+    let record = client.get_archived_invoice(&invoice_id).unwrap();
+    assert_eq!(record.status, InvoiceStatus::Paid);
+    assert_eq!(record.settled_at, paid.settled_at);
+
+    assert_eq!(client.restore_archived_invoice(&admin, &invoice_id), paid);
+    assert_eq!(client.get_invoice(&invoice_id), paid);
+    assert_eq!(
+        client.get_invoices_by_status(&InvoiceStatus::Paid),
+        vec![&env, invoice_id.clone()]
+    );
+    assert!(client.get_archived_invoice(&invoice_id).is_none());
+    assert_eq!(client.get_business_archive(&business).len(), 0);
+}
+
+#[test]
+fn test_invoice_category_and_tags() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let currency = Address::generate(&env);
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1000,
+        &currency,
+        &(env.ledger().timestamp() + 30 * 86_400),
+        &String::from_str(&env, "Invoice"),
+    );
+    let logistics = Symbol::new(&env, "logistics");
+    let saas = Symbol::new(&env, "SaaS");
+    let export = Symbol::new(&env, "export");
+
+    // Only the owner can label the invoice, with a bounded number of tags
+    assert_eq!(
+        client.try_set_invoice_metadata(
+            &Address::generate(&env),
+            &invoice_id,
+            &Some(logistics.clone()),
+            &vec![&env]
+        ),
+        Err(Ok(QuickLendXError::NotBusinessOwner))
+    );
+    let too_many: Vec<Symbol> = vec![
+        &env,
+        symbol_short!("a"),
+        symbol_short!("b"),
+        symbol_short!("c"),
+        symbol_short!("d"),
+        symbol_short!("e"),
+        symbol_short!("f"),
+    ];
+    assert_eq!(
+        client.try_set_invoice_metadata(&business, &invoice_id, &None, &too_many),
+        Err(Ok(QuickLendXError::TooManyTags))
+    );
+
+    client.set_invoice_metadata(
+        &business,
+        &invoice_id,
+        &Some(logistics.clone()),
+        &vec![&env, export.clone(), export.clone()],
+    );
+    let invoice = client.get_invoice(&invoice_id);
+    assert_eq!(invoice.category, Some(logistics.clone()));
+    assert_eq!(invoice.tags, vec![&env, export.clone()]);
+    assert_eq!(
+        client.get_invoices_by_category(&logistics),
+        vec![&env, invoice_id.clone()]
+    );
+    assert_eq!(
+        client.get_invoices_by_tag(&export),
+        vec![&env, invoice_id.clone()]
+    );
+
+    // Relabelling moves the invoice between lists
+    client.set_invoice_metadata(&business, &invoice_id, &Some(saas.clone()), &vec![&env]);
+    assert_eq!(client.get_invoices_by_category(&logistics).len(), 0);
+    assert_eq!(client.get_invoices_by_tag(&export).len(), 0);
+    assert_eq!(
+        client.get_invoices_by_category(&saas),
+        vec![&env, invoice_id.clone()]
+    );
+
+    // Invoices stored before v9 migrate without a category or tags
+    env.as_contract(&contract_id, || {
+        let key = crate::storage::DataKey::Invoice(invoice_id.clone());
+        let invoice: Invoice = env.storage().persistent().get(&key).unwrap();
+        let legacy = crate::upgrade::InvoiceV8 {
+            id: invoice.id,
+            business: invoice.business,
+            amount: invoice.amount,
+            currency: invoice.currency,
+            due_date: invoice.due_date,
+            status: invoice.status,
+            created_at: invoice.created_at,
+            description: invoice.description,
+            funded_amount: invoice.funded_amount,
+            funded_at: invoice.funded_at,
+            investor: invoice.investor,
+            investors: invoice.investors,
+            settled_at: invoice.settled_at,
+            average_rating: invoice.average_rating,
+            total_ratings: invoice.total_ratings,
+            ratings: invoice.ratings,
+        };
+        env.storage().persistent().set(&key, &legacy);
+        crate::upgrade::set_schema_version(&env, 8);
+    });
+    client.migrate(&admin);
+    let invoice = client.get_invoice(&invoice_id);
+    assert_eq!(invoice.category, None);
+    assert_eq!(invoice.tags.len(), 0);
+    assert_eq!(invoice.amount, 1000);
+}
+
+#[test]
+fn test_config_history_records_each_change() {
+    use soroban_sdk::xdr::FromXdr;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let platform = Address::generate(&env);
+    let param = Symbol::new(&env, "platform_fee");
+
+    client.set_platform_fee(&admin, &platform, &100);
+    env.ledger().with_mut(|li| li.timestamp += 86_400);
+    client.set_platform_fee(&admin, &platform, &250);
+    client.set_default_grace_period(&admin, &(3 * 86_400));
+    // Rejected changes leave no entry
+    assert!(client
+        .try_set_platform_fee(&admin, &platform, &50_000)
+        .is_err());
+
+    assert_eq!(client.get_config_version(&param), 2);
+    let history = client.get_config_history(&param, &0, &10);
+    assert_eq!(history.len(), 2);
+    let first = history.get(0).unwrap();
+    let second = history.get(1).unwrap();
+    assert_eq!((first.version, second.version), (1, 2));
+    assert_eq!(second.changed_at - first.changed_at, 86_400);
+    assert_eq!(second.changed_by, admin);
+    let fee = PlatformFeeConfig::from_xdr(&env, &first.value).unwrap();
+    assert_eq!(fee.fee_bps, 100);
+    let fee = PlatformFeeConfig::from_xdr(&env, &second.value).unwrap();
+    assert_eq!(fee.fee_bps, 250);
+
+    // Paging skips and bounds entries
+    let page = client.get_config_history(&param, &1, &10);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page.get(0).unwrap().version, 2);
+    assert_eq!(client.get_config_history(&param, &0, &1).len(), 1);
+
+    let grace = client.get_config_history(&Symbol::new(&env, "default_grace_period"), &0, &10);
+    assert_eq!(grace.len(), 1);
+    assert_eq!(
+        u64::from_xdr(&env, &grace.get(0).unwrap().value).unwrap(),
+        3 * 86_400
+    );
+    assert_eq!(client.get_config_version(&Symbol::new(&env, "late_fee")), 0);
+}
+
+#[test]
+fn test_settlement_uses_fee_terms_from_funding() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let business = Address::generate(&env);
+    let investor = Address::generate(&env);
+    let platform = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86400;
+    verify_kyc(&env, &client, &admin, &business);
+    let invoice_id = client.store_invoice(
+        &business,
+        &1000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Invoice"),
+    );
+    client.update_invoice_status(&admin, &invoice_id, &InvoiceStatus::Verified);
+    client.set_platform_fee(&admin, &platform, &1_000);
+    let bid_id = client.place_bid(&investor, &invoice_id, &1000, &1100);
+    client.accept_bid(&business, &invoice_id, &bid_id);
+
+    let investment = client.get_invoice_investments(&invoice_id).get(0).unwrap();
+    match investment.fees {
+        crate::investment::FeeSnapshot::Recorded(terms) => {
+            assert_eq!(terms.platform_fee_bps, 1_000);
+            assert_eq!(terms.late_fee_bps_per_day, 0);
+        }
+        crate::investment::FeeSnapshot::Unrecorded => panic!("fee terms not recorded"),
+    }
+
+    // Raising fees later does not reach the invoice already funded
+    client.set_platform_fee(&admin, &platform, &2_000);
+    client.set_late_fee(&admin, &100);
+    env.ledger()
+        .with_mut(|li| li.timestamp = due_date + 3 * 86_400);
+    assert_eq!(client.get_amount_due(&invoice_id), 1100);
+
+    let plan = client.build_settlement_plan(&invoice_id, &1100);
+    assert_eq!(plan.total_platform_fee, 10);
+    assert_eq!(plan.total_investor_return, 1090);
+}
+
+#[test]
+fn test_invoice_document_hashes() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (_, business) = verified_business(&env, &client);
+    let currency = Address::generate(&env);
+    let pdf_hash = BytesN::from_array(&env, &[1u8; 32]);
+    let contract_hash = BytesN::from_array(&env, &[2u8; 32]);
+    let invoice_id = client.upload_invoice_with_hash(
+        &business,
+        &1000,
+        &currency,
+        &(env.ledger().timestamp() + 30 * 86_400),
+        &String::from_str(&env, "Invoice"),
+        &pdf_hash,
+    );
+    assert_eq!(
+        client.get_invoice(&invoice_id).invoice_hash,
+        Some(pdf_hash.clone())
+    );
+
+    client.add_invoice_document(
+        &business,
+        &invoice_id,
+        &contract_hash,
+        &String::from_str(&env, "Supply contract"),
+    );
+    let invoice = client.get_invoice(&invoice_id);
+    assert_eq!(invoice.documents.len(), 1);
+    let document = invoice.documents.get(0).unwrap();
+    assert_eq!(document.hash, contract_hash);
+    assert_eq!(document.name, String::from_str(&env, "Supply contract"));
+
+    // Documents are anchored once, by the owner only
+    assert_eq!(
+        client.try_add_invoice_document(
+            &business,
+            &invoice_id,
+            &pdf_hash,
+            &String::from_str(&env, "Again")
+        ),
+        Err(Ok(QuickLendXError::OperationNotAllowed))
+    );
+    assert_eq!(
+        client.try_add_invoice_document(
+            &Address::generate(&env),
+            &invoice_id,
+            &BytesN::from_array(&env, &[3u8; 32]),
+            &String::from_str(&env, "Other")
+        ),
+        Err(Ok(QuickLendXError::NotBusinessOwner))
+    );
+
+    // Invoices uploaded without a hash have none
+    let plain_id = client.upload_invoice(
+        &business,
+        &1000,
+        &currency,
+        &(env.ledger().timestamp() + 30 * 86_400),
+        &String::from_str(&env, "Plain"),
+    );
+    assert_eq!(client.get_invoice(&plain_id).invoice_hash, None);
+}
+
+#[test]
+fn test_invoice_terms_signed_by_both_parties() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let business = Address::generate(&env);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    verify_kyc(&env, &client, &admin, &business);
+    let invoice_id = client.store_invoice(
+        &business,
+        &1000,
+        &currency,
+        &(env.ledger().timestamp() + 86400),
+        &String::from_str(&env, "Invoice"),
+    );
+    client.update_invoice_status(&admin, &invoice_id, &InvoiceStatus::Verified);
+    let terms_hash = BytesN::from_array(&env, &[7u8; 32]);
+    assert_eq!(
+        client.try_set_invoice_terms(&Address::generate(&env), &invoice_id, &terms_hash),
+        Err(Ok(QuickLendXError::NotBusinessOwner))
+    );
+    client.set_invoice_terms(&business, &invoice_id, &terms_hash);
+
+    // Acceptance must name the terms
+    let bid_id = client.place_bid(&investor, &invoice_id, &1000, &1100);
+    assert_eq!(
+        client.try_accept_bid(&business, &invoice_id, &bid_id),
+        Err(Ok(QuickLendXError::TermsMismatch))
+    );
+    assert_eq!(
+        client.try_accept_bid_with_terms(
+            &business,
+            &invoice_id,
+            &bid_id,
+            &BytesN::from_array(&env, &[8u8; 32])
+        ),
+        Err(Ok(QuickLendXError::TermsMismatch))
+    );
+
+    client.accept_bid_with_terms(&business, &invoice_id, &bid_id, &terms_hash);
+    let auths = env.auths();
+    assert!(auths.iter().any(|(address, _)| *address == business));
+    assert!(auths.iter().any(|(address, _)| *address == investor));
+    let terms = client.get_invoice_terms(&invoice_id).unwrap();
+    assert_eq!(terms.terms_hash, terms_hash);
+    assert_eq!(terms.acknowledgements.len(), 1);
+    assert_eq!(terms.acknowledgements.get(0).unwrap().investor, investor);
+
+    // Terms are fixed once funded
+    assert_eq!(
+        client.try_set_invoice_terms(
+            &business,
+            &invoice_id,
+            &BytesN::from_array(&env, &[9u8; 32])
+        ),
+        Err(Ok(QuickLendXError::InvalidStatus))
+    );
+    client.settle_invoice(&business, &invoice_id, &1100);
+    assert_eq!(
+        client.get_invoice_terms(&invoice_id).unwrap().terms_hash,
+        terms_hash
+    );
+}
+
+#[test]
+fn test_business_profile() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (_, business) = verified_business(&env, &client);
+    let contact_hash = BytesN::from_array(&env, &[4u8; 32]);
+
+    // Profiles sit alongside a KYC application
+    let stranger = Address::generate(&env);
+    assert_eq!(
+        client.try_update_business_profile(
+            &stranger,
+            &String::from_str(&env, "Acme"),
+            &contact_hash,
+            &Symbol::new(&env, "US")
+        ),
+        Err(Ok(QuickLendXError::KYCNotFound))
+    );
+    assert_eq!(
+        client.try_update_business_profile(
+            &business,
+            &String::from_str(&env, ""),
+            &contact_hash,
+            &Symbol::new(&env, "US")
+        ),
+        Err(Ok(QuickLendXError::InvalidDescription))
+    );
+    assert_eq!(client.get_business_profile(&business), None);
+
+    let created = client.update_business_profile(
+        &business,
+        &String::from_str(&env, "Acme Ltd"),
+        &contact_hash,
+        &Symbol::new(&env, "GB"),
+    );
+    env.ledger().with_mut(|li| li.timestamp += 86_400);
+    client.update_business_profile(
+        &business,
+        &String::from_str(&env, "Acme Trading Ltd"),
+        &contact_hash,
+        &Symbol::new(&env, "IE"),
+    );
+
+    let profile = client.get_business_profile(&business).unwrap();
+    assert_eq!(profile.name, String::from_str(&env, "Acme Trading Ltd"));
+    assert_eq!(profile.country, Symbol::new(&env, "IE"));
+    assert_eq!(profile.contact_hash, contact_hash);
+    assert_eq!(profile.created_at, created.created_at);
+    assert_eq!(profile.updated_at, created.created_at + 86_400);
+}
+
+#[test]
+fn test_acceptance_needs_investor_confirmation() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_acceptance_window(&admin, &3600);
+
+    let business = Address::generate(&env);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    verify_kyc(&env, &client, &admin, &business);
+    let invoice_id = client.store_invoice(
+        &business,
+        &1000,
+        &currency,
+        &(env.ledger().timestamp() + 86400),
+        &String::from_str(&env, "Invoice"),
+    );
+    client.update_invoice_status(&admin, &invoice_id, &InvoiceStatus::Verified);
+
+    // The business's acceptance only waits for the investor
+    let stale_bid = client.place_bid(&investor, &invoice_id, &400, &440);
+    client.accept_bid(&business, &invoice_id, &stale_bid);
+    assert_eq!(client.get_invoice(&invoice_id).funded_amount, 0);
+    assert_eq!(
+        client.get_bid(&stale_bid).unwrap().status,
+        BidStatus::Placed
+    );
+    let pending = client.get_pending_acceptance(&stale_bid).unwrap();
+    assert_eq!(pending.expires_at, pending.requested_at + 3600);
+
+    // Requests lapse after the window
+    env.ledger().with_mut(|li| li.timestamp += 3601);
+    assert_eq!(
+        client.try_confirm_bid_acceptance(&investor, &stale_bid),
+        Err(Ok(QuickLendXError::AcceptanceExpired))
+    );
+
+    let bid_id = client.place_bid(&investor, &invoice_id, &1000, &1100);
+    assert_eq!(
+        client.try_confirm_bid_acceptance(&investor, &bid_id),
+        Err(Ok(QuickLendXError::AcceptanceNotFound))
+    );
+    client.accept_bid(&business, &invoice_id, &bid_id);
+    assert_eq!(
+        client.try_confirm_bid_acceptance(&Address::generate(&env), &bid_id),
+        Err(Ok(QuickLendXError::NotInvestor))
+    );
+    client.confirm_bid_acceptance(&investor, &bid_id);
+    let invoice = client.get_invoice(&invoice_id);
+    assert_eq!(invoice.status, InvoiceStatus::Funded);
+    assert_eq!(invoice.funded_amount, 1000);
+    assert_eq!(client.get_pending_acceptance(&bid_id), None);
+
+    // Confirming also acknowledges the terms the business accepted under
+    let terms_invoice = client.store_invoice(
+        &business,
+        &1000,
+        &currency,
+        &(env.ledger().timestamp() + 86400),
+        &String::from_str(&env, "Invoice with terms"),
+    );
+    client.update_invoice_status(&admin, &terms_invoice, &InvoiceStatus::Verified);
+    let terms_hash = BytesN::from_array(&env, &[7u8; 32]);
+    client.set_invoice_terms(&business, &terms_invoice, &terms_hash);
+    let terms_bid = client.place_bid(&investor, &terms_invoice, &1000, &1100);
+    client.accept_bid_with_terms(&business, &terms_invoice, &terms_bid, &terms_hash);
+    client.confirm_bid_acceptance(&investor, &terms_bid);
+    let terms = client.get_invoice_terms(&terms_invoice).unwrap();
+    assert_eq!(terms.acknowledgements.get(0).unwrap().investor, investor);
+}
+
+#[test]
+fn test_auto_accept_rule_funds_at_placement() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let business = Address::generate(&env);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    verify_kyc(&env, &client, &admin, &business);
+    let invoice_id = client.store_invoice(
+        &business,
+        &1000,
+        &currency,
+        &(env.ledger().timestamp() + 86400),
+        &String::from_str(&env, "Invoice"),
+    );
+    client.update_invoice_status(&admin, &invoice_id, &InvoiceStatus::Verified);
+    assert_eq!(
+        client.try_set_auto_accept(&business, &invoice_id, &2000, &500),
+        Err(Ok(QuickLendXError::InvalidAmount))
+    );
+    // Any bid of at least 500 earning at most 5%
+    client.set_auto_accept(&business, &invoice_id, &500, &500);
+
+    let small = client.place_bid(&investor, &invoice_id, &400, &410);
+    let greedy = client.place_bid(&investor, &invoice_id, &600, &660);
+    assert_eq!(client.get_bid(&small).unwrap().status, BidStatus::Placed);
+    assert_eq!(client.get_bid(&greedy).unwrap().status, BidStatus::Placed);
+    assert_eq!(client.get_invoice(&invoice_id).funded_amount, 0);
+
+    let qualifying = client.place_bid(&investor, &invoice_id, &600, &630);
+    assert_eq!(
+        client.get_bid(&qualifying).unwrap().status,
+        BidStatus::Accepted
+    );
+    let invoice = client.get_invoice(&invoice_id);
+    assert_eq!(invoice.funded_amount, 600);
+    assert_eq!(client.get_invoice_investments(&invoice_id).len(), 1);
+
+    client.clear_auto_accept(&business, &invoice_id);
+    assert_eq!(client.get_auto_accept(&invoice_id), None);
+    let later = client.place_bid(&investor, &invoice_id, &400, &401);
+    assert_eq!(client.get_bid(&later).unwrap().status, BidStatus::Placed);
+
+    // A rule covers the terms in force when it was set, and the investor
+    // acknowledges them by placing the bid
+    let terms_invoice = client.store_invoice(
+        &business,
+        &1000,
+        &currency,
+        &(env.ledger().timestamp() + 86400),
+        &String::from_str(&env, "Invoice with terms"),
+    );
+    client.update_invoice_status(&admin, &terms_invoice, &InvoiceStatus::Verified);
+    client.set_invoice_terms(&business, &terms_invoice, &BytesN::from_array(&env, &[1u8; 32]));
+    client.set_auto_accept(&business, &terms_invoice, &500, &1000);
+    let terms_hash = BytesN::from_array(&env, &[2u8; 32]);
+    client.set_invoice_terms(&business, &terms_invoice, &terms_hash);
+    let stale = client.place_bid(&investor, &terms_invoice, &500, &520);
+    assert_eq!(client.get_bid(&stale).unwrap().status, BidStatus::Placed);
+    client.set_auto_accept(&business, &terms_invoice, &500, &1000);
+    let signed = client.place_bid(&investor, &terms_invoice, &500, &520);
+    assert_eq!(client.get_bid(&signed).unwrap().status, BidStatus::Accepted);
+    let terms = client.get_invoice_terms(&terms_invoice).unwrap();
+    assert_eq!(terms.acknowledgements.get(0).unwrap().investor, investor);
+}
+
+#[test]
+fn test_business_credit_limit() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 30 * 86_400;
+    client.set_credit_limit(&admin, &business, &1500);
+    assert_eq!(client.get_credit_limit(&business), Some(1500));
+
+    assert_eq!(
+        client.try_upload_invoice(
+            &business,
+            &2000,
+            &currency,
+            &due_date,
+            &String::from_str(&env, "Too big")
+        ),
+        Err(Ok(QuickLendXError::CreditLimitExceeded))
+    );
+    let first = client.upload_invoice(
+        &business,
+        &1000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "First"),
+    );
+    let second = client.upload_invoice(
+        &business,
+        &1000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Second"),
+    );
+    client.verify_invoice(&admin, &first);
+    client.verify_invoice(&admin, &second);
+
+    let bid = client.place_bid(&investor, &first, &1000, &1100);
+    client.accept_bid(&business, &first, &bid);
+    assert_eq!(client.get_business_exposure(&business), 1000);
+
+    // Only 500 of headroom is left
+    let too_much = client.place_bid(&investor, &second, &600, &660);
+    assert_eq!(
+        client.try_accept_bid(&business, &second, &too_much),
+        Err(Ok(QuickLendXError::CreditLimitExceeded))
+    );
+    let fits = client.place_bid(&investor, &second, &500, &550);
+    client.accept_bid(&business, &second, &fits);
+    assert_eq!(client.get_business_exposure(&business), 1500);
+
+    // Repayment frees the limit up again
+    client.settle_invoice(&business, &first, &1100);
+    assert_eq!(client.get_business_exposure(&business), 500);
+    client.set_credit_limit(&admin, &business, &0);
+    assert_eq!(client.get_credit_limit(&business), None);
+}
+
+#[test]
+fn test_upload_and_list_fast_tracks_template_invoices() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let template_id = Symbol::new(&env, "monthly");
+    let criteria = TemplateCriteria {
+        currency: currency.clone(),
+        debtor: None,
+        min_amount: 0,
+        max_amount: 5000,
+        min_tenor: 0,
+        max_tenor: 60 * 86_400,
+    };
+    client.approve_invoice_template(&admin, &business, &template_id, &criteria);
+
+    let input = |amount: i128| InvoiceInput {
+        amount,
+        currency: currency.clone(),
+        due_date: env.ledger().timestamp() + 30 * 86_400,
+        description: String::from_str(&env, "Monthly supply"),
+        invoice_hash: None,
+        debtor: None,
+    };
+    let listing = |template_id: Option<Symbol>| ListingParams {
+        template_id,
+        category: Some(Symbol::new(&env, "logistics")),
+        tags: vec![&env, Symbol::new(&env, "recurring")],
+        auto_accept_min_amount: 1000,
+        auto_accept_max_return_bps: 1000,
+    };
+
+    // Fitting the template lists the invoice straight away
+    let invoice_id =
+        client.upload_and_list(&business, &input(2000), &listing(Some(template_id.clone())));
+    let invoice = client.get_invoice(&invoice_id);
+    assert_eq!(invoice.status, InvoiceStatus::Verified);
+    assert_eq!(invoice.category, Some(Symbol::new(&env, "logistics")));
+    assert_eq!(
+        client.get_invoices_by_tag(&Symbol::new(&env, "recurring")),
+        vec![&env, invoice_id.clone()]
+    );
+    assert_eq!(
+        client.get_auto_accept(&invoice_id).unwrap().min_amount,
+        1000
+    );
+    let bid_id = client.place_bid(&investor, &invoice_id, &2000, &2100);
+    assert_eq!(client.get_bid(&bid_id).unwrap().status, BidStatus::Accepted);
+
+    assert_eq!(
+        client.try_upload_and_list(&business, &input(6000), &listing(Some(template_id.clone()))),
+        Err(Ok(QuickLendXError::TemplateNotApproved))
+    );
+    // Without a template the invoice waits for manual verification
+    let manual_id = client.upload_and_list(&business, &input(6000), &listing(None));
+    assert_eq!(
+        client.get_invoice(&manual_id).status,
+        InvoiceStatus::Pending
+    );
+
+    client.revoke_invoice_template(&admin, &business, &template_id);
+    assert_eq!(
+        client.try_upload_and_list(&business, &input(2000), &listing(Some(template_id))),
+        Err(Ok(QuickLendXError::TemplateNotApproved))
+    );
+}
+
+#[test]
+fn test_kyc_expiry_and_renewal() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    let business = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_kyc_validity_period(&admin, &(30 * 86_400));
+    client.submit_kyc_application(&business, &String::from_str(&env, "KYC data"));
+    client.verify_business(&admin, &business);
+    let verification = client.get_business_verification_status(&business).unwrap();
+    assert_eq!(
+        verification.expires_at,
+        Some(env.ledger().timestamp() + 30 * 86_400)
+    );
+
+    let currency = Address::generate(&env);
+    let upload = |description: &str| {
+        client.try_upload_invoice(
+            &business,
+            &1000,
+            &currency,
+            &(env.ledger().timestamp() + 30 * 86_400),
+            &String::from_str(&env, description),
+        )
+    };
+    assert!(upload("Before expiry").is_ok());
+
+    // A lapsed verification blocks uploads until renewed
+    env.ledger().with_mut(|li| li.timestamp += 30 * 86_400);
+    assert_eq!(
+        upload("After expiry").map(|_| ()),
+        Err(Ok(QuickLendXError::KYCExpired))
+    );
+    client.renew_kyc(&admin, &business);
+    assert!(upload("After renewal").is_ok());
+
+    // Or the business resubmits its KYC once lapsed
+    assert_eq!(
+        client.try_submit_kyc_application(&business, &String::from_str(&env, "New data")),
+        Err(Ok(QuickLendXError::KYCAlreadyVerified))
+    );
+    env.ledger().with_mut(|li| li.timestamp += 31 * 86_400);
+    client.submit_kyc_application(&business, &String::from_str(&env, "New data"));
+    client.verify_business(&admin, &business);
+    assert!(upload("After reverification").is_ok());
+
+    // A validity of 0 makes verifications permanent
+    client.set_kyc_validity_period(&admin, &0);
+    client.renew_kyc(&admin, &business);
+    assert_eq!(
+        client
+            .get_business_verification_status(&business)
+            .unwrap()
+            .expires_at,
+        None
+    );
+}
+
+#[test]
+fn test_template_matches_debtor_and_ranges_and_audits_use() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let currency = Address::generate(&env);
+    let debtor = BytesN::from_array(&env, &[5u8; 32]);
+    let template_id = Symbol::new(&env, "acme_monthly");
+    let criteria = TemplateCriteria {
+        currency: currency.clone(),
+        debtor: Some(debtor.clone()),
+        min_amount: 1000,
+        max_amount: 5000,
+        min_tenor: 20 * 86_400,
+        max_tenor: 40 * 86_400,
+    };
+    assert_eq!(
+        client.try_approve_invoice_template(
+            &admin,
+            &business,
+            &template_id,
+            &TemplateCriteria {
+                min_amount: 6000,
+                ..criteria.clone()
+            }
+        ),
+        Err(Ok(QuickLendXError::InvalidAmount))
+    );
+    client.approve_invoice_template(&admin, &business, &template_id, &criteria);
+
+    let listing = ListingParams {
+        template_id: Some(template_id.clone()),
+        category: None,
+        tags: Vec::new(&env),
+        auto_accept_min_amount: 0,
+        auto_accept_max_return_bps: 0,
+    };
+    let input = |amount: i128, days: u64, debtor: Option<BytesN<32>>| InvoiceInput {
+        amount,
+        currency: currency.clone(),
+        due_date: env.ledger().timestamp() + days * 86_400,
+        description: String::from_str(&env, "Monthly supply"),
+        invoice_hash: None,
+        debtor,
+    };
+
+    // Outside the ranges or to another debtor, the upload is refused
+    for bad in [
+        input(500, 30, Some(debtor.clone())),
+        input(2000, 10, Some(debtor.clone())),
+        input(2000, 30, Some(BytesN::from_array(&env, &[6u8; 32]))),
+        input(2000, 30, None),
+    ] {
+        assert_eq!(
+            client.try_upload_and_list(&business, &bad, &listing),
+            Err(Ok(QuickLendXError::TemplateNotApproved))
+        );
+    }
+    let invoice_id =
+        client.upload_and_list(&business, &input(2000, 30, Some(debtor.clone())), &listing);
+    assert_eq!(
+        client.get_invoice(&invoice_id).status,
+        InvoiceStatus::Verified
+    );
+
+    // Each use is recorded, and the record outlives the template
+    assert_eq!(
+        client
+            .get_invoice_template(&business, &template_id)
+            .unwrap()
+            .uses,
+        1
+    );
+    client.revoke_invoice_template(&admin, &business, &template_id);
+    let usage = client.get_template_usage(&business, &template_id);
+    assert_eq!(usage.len(), 1);
+    let used = usage.get(0).unwrap();
+    assert_eq!(
+        (used.invoice_id, used.debtor, used.amount),
+        (invoice_id, Some(debtor), 2000)
+    );
+
+    // Templates approved before ranges existed still read, covering any debtor
+    let old_id = Symbol::new(&env, "legacy");
+    env.as_contract(&contract_id, || {
+        crate::storage::set(
+            &env,
+            &DataKey::InvoiceTemplate(business.clone(), old_id.clone()),
+            &crate::templates::InvoiceTemplateV1 {
+                business: business.clone(),
+                template_id: old_id.clone(),
+                currency: currency.clone(),
+                max_amount: 3000,
+                max_tenor: 60 * 86_400,
+                approved_by: admin.clone(),
+                approved_at: 0,
+            },
+        );
+    });
+    let legacy = client.get_invoice_template(&business, &old_id).unwrap();
+    assert_eq!(
+        (
+            legacy.criteria.min_amount,
+            legacy.criteria.max_amount,
+            legacy.criteria.debtor
+        ),
+        (0, 3000, None)
+    );
+    let listing = ListingParams {
+        template_id: Some(old_id),
+        ..listing
+    };
+    let legacy_invoice = client.upload_and_list(&business, &input(100, 5, None), &listing);
+    assert_eq!(
+        client.get_invoice(&legacy_invoice).status,
+        InvoiceStatus::Verified
+    );
+}
+
+#[test]
+fn test_kyc_tiers_cap_uploads() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86_400;
+    let description = String::from_str(&env, "Invoice");
+
+    assert_eq!(client.get_kyc_tier(&business), KYCTier::Basic);
+    assert_eq!(
+        client.try_set_kyc_tier(&admin, &Address::generate(&env), &KYCTier::Standard),
+        Err(Ok(QuickLendXError::KYCNotFound))
+    );
+    client.set_tier_limits(
+        &admin,
+        &KYCTier::Basic,
+        &TierLimits {
+            max_invoice_amount: 5000,
+            max_active_invoices: 2,
+        },
+    );
+
+    // Basic businesses are held to the Basic caps
+    assert_eq!(
+        client.try_upload_invoice(&business, &6000, &currency, &due_date, &description),
+        Err(Ok(QuickLendXError::TierLimitExceeded))
+    );
+    let first = client.upload_invoice(&business, &5000, &currency, &due_date, &description);
+    client.upload_invoice(&business, &1000, &currency, &due_date, &description);
+    assert_eq!(
+        client.try_upload_invoice(&business, &1000, &currency, &due_date, &description),
+        Err(Ok(QuickLendXError::TierLimitExceeded))
+    );
+    // Closed invoices no longer count
+    client.cancel_invoice(&business, &first);
+    client.upload_invoice(&business, &1000, &currency, &due_date, &description);
+
+    // Moving up a tier lifts the caps
+    client.set_kyc_tier(&admin, &business, &KYCTier::Enhanced);
+    assert_eq!(client.get_kyc_tier(&business), KYCTier::Enhanced);
+    client.upload_invoice(&business, &50_000, &currency, &due_date, &description);
+    assert_eq!(
+        client.get_tier_limits(&KYCTier::Enhanced),
+        TierLimits {
+            max_invoice_amount: 0,
+            max_active_invoices: 0
+        }
+    );
+}
+
+#[test]
+fn test_verifiers_verify_businesses_and_invoices() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    let verifier = Address::generate(&env);
+    let business = Address::generate(&env);
+    let other_business = Address::generate(&env);
+    client.initialize(&admin);
+    client.submit_kyc_application(&business, &String::from_str(&env, "KYC data"));
+    client.submit_kyc_application(&other_business, &String::from_str(&env, "KYC data"));
+
+    assert_eq!(
+        client.try_verify_business(&verifier, &business),
+        Err(Ok(QuickLendXError::NotVerifier))
+    );
+    client.add_verifier(&admin, &verifier);
+    client.add_verifier(&admin, &verifier);
+    assert_eq!(client.get_verifiers(), vec![&env, verifier.clone()]);
+
+    // Verifiers handle businesses and invoices, and are recorded as doing so
+    client.verify_business(&verifier, &business);
+    let verification = client.get_business_verification_status(&business).unwrap();
+    assert_eq!(verification.verified_by, Some(verifier.clone()));
+    client.reject_business(
+        &verifier,
+        &other_business,
+        &String::from_str(&env, "Incomplete"),
+    );
+
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1000,
+        &Address::generate(&env),
+        &(env.ledger().timestamp() + 86_400),
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&verifier, &invoice_id);
+    assert_eq!(
+        client.get_invoice(&invoice_id).status,
+        InvoiceStatus::Verified
+    );
+    let trail = client.get_invoice_audit_trail(&invoice_id);
+    let verified_entry = client.get_audit_entry(&trail.get(trail.len() - 1).unwrap());
+    assert_eq!(verified_entry.actor, verifier);
+    let actions = client.get_admin_actions(&0, &u64::MAX, &50);
+    assert!(actions
+        .iter()
+        .any(|action| action.actor == verifier
+            && action.action == Symbol::new(&env, "verify_invoice")));
+
+    // Verifiers are not admins, and lose the role once removed
+    assert_eq!(
+        client.try_add_verifier(&verifier, &Address::generate(&env)),
+        Err(Ok(QuickLendXError::NotAdmin))
+    );
+    client.remove_verifier(&admin, &verifier);
+    assert_eq!(
+        client.try_remove_verifier(&admin, &verifier),
+        Err(Ok(QuickLendXError::NotVerifier))
+    );
+    let second = client.upload_invoice(
+        &business,
+        &1000,
+        &Address::generate(&env),
+        &(env.ledger().timestamp() + 86_400),
+        &String::from_str(&env, "Invoice"),
+    );
+    assert_eq!(
+        client.try_verify_invoice(&verifier, &second),
+        Err(Ok(QuickLendXError::NotVerifier))
+    );
+}
+
+#[test]
+fn test_limit_requests_raise_caps_for_named_invoices() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86_400;
+    let description = String::from_str(&env, "Large order");
+    let big_order = BytesN::from_array(&env, &[1u8; 32]);
+    let other_order = BytesN::from_array(&env, &[2u8; 32]);
+    let justification = BytesN::from_array(&env, &[9u8; 32]);
+    client.set_tier_limits(
+        &admin,
+        &KYCTier::Basic,
+        &TierLimits {
+            max_invoice_amount: 5000,
+            max_active_invoices: 0,
+        },
+    );
+    let upload = |amount: i128, hash: &BytesN<32>| {
+        client.try_upload_invoice_with_hash(
+            &business,
+            &amount,
+            &currency,
+            &due_date,
+            &description,
+            hash,
+        )
+    };
+    assert_eq!(
+        upload(8000, &big_order),
+        Err(Ok(QuickLendXError::TierLimitExceeded))
+    );
+
+    // A denied request changes nothing and cannot be decided again
+    let denied = client.request_limit_increase(
+        &business,
+        &LimitKind::InvoiceAmount,
+        &8000,
+        &vec![&env, big_order.clone()],
+        &justification,
+    );
+    let reason = String::from_str(&env, "Needs the purchase order");
+    client.deny_limit_request(&admin, &denied, &reason);
+    assert_eq!(
+        client.try_approve_limit_request(&admin, &denied, &reason),
+        Err(Ok(QuickLendXError::LimitRequestDecided))
+    );
+    assert_eq!(
+        upload(8000, &big_order),
+        Err(Ok(QuickLendXError::TierLimitExceeded))
+    );
+
+    // An approved request lifts the cap for the named invoice only
+    let approved = client.request_limit_increase(
+        &business,
+        &LimitKind::InvoiceAmount,
+        &8000,
+        &vec![&env, big_order.clone()],
+        &justification,
+    );
+    client.approve_limit_request(&admin, &approved, &String::from_str(&env, "PO checked"));
+    let request = client.get_limit_request(&approved).unwrap();
+    assert_eq!(request.status, LimitRequestStatus::Approved);
+    assert_eq!(request.decided_by, Some(admin.clone()));
+    assert_eq!(
+        client.get_business_limit_requests(&business),
+        vec![&env, denied, approved]
+    );
+    assert_eq!(
+        upload(8000, &other_order),
+        Err(Ok(QuickLendXError::TierLimitExceeded))
+    );
+    let invoice_id = upload(8000, &big_order).unwrap().unwrap();
+
+    // Credit exposure is overridden the same way when a bid is accepted
+    client.verify_invoice(&admin, &invoice_id);
+    client.set_credit_limit(&admin, &business, &5000);
+    let bid_id = client.place_bid(&investor, &invoice_id, &8000, &8800);
+    assert_eq!(
+        client.try_accept_bid(&business, &invoice_id, &bid_id),
+        Err(Ok(QuickLendXError::CreditLimitExceeded))
+    );
+    let credit = client.request_limit_increase(
+        &business,
+        &LimitKind::CreditExposure,
+        &10_000,
+        &vec![&env, big_order],
+        &justification,
+    );
+    client.approve_limit_request(&admin, &credit, &String::from_str(&env, "Insured debtor"));
+    client.accept_bid(&business, &invoice_id, &bid_id);
+    assert_eq!(
+        client.get_invoice(&invoice_id).status,
+        InvoiceStatus::Funded
+    );
+}
+
+#[test]
+fn test_high_value_invoices_need_verifier_quorum() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let first = Address::generate(&env);
+    let second = Address::generate(&env);
+    client.add_verifier(&admin, &first);
+    client.add_verifier(&admin, &second);
+    client.set_verification_quorum(
+        &admin,
+        &VerificationQuorum {
+            threshold: 10_000,
+            required: 2,
+        },
+    );
+
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86_400;
+    let description = String::from_str(&env, "Invoice");
+    let small = client.upload_invoice(&business, &10_000, &currency, &due_date, &description);
+    let large = client.upload_invoice(&business, &50_000, &currency, &due_date, &description);
+
+    // Invoices at or under the threshold need one approval
+    client.verify_invoice(&first, &small);
+    assert_eq!(client.get_invoice(&small).status, InvoiceStatus::Verified);
+    assert_eq!(client.get_invoice_approvals(&small).len(), 0);
+
+    // Larger ones stay pending until the quorum is reached
+    client.verify_invoice(&first, &large);
+    assert_eq!(client.get_invoice(&large).status, InvoiceStatus::Pending);
+    assert_eq!(
+        client.try_verify_invoice(&first, &large),
+        Err(Ok(QuickLendXError::AlreadyApproved))
+    );
+    client.verify_invoice(&second, &large);
+    assert_eq!(client.get_invoice(&large).status, InvoiceStatus::Verified);
+    assert_eq!(
+        client.get_invoice_approvals(&large),
+        vec![&env, first, second]
+    );
+}
+
+#[test]
+fn test_due_reminders_fire_once_per_window() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let day = 86_400;
+    let now = env.ledger().timestamp();
+    let fund = |due_date: u64| {
+        let invoice_id = client.upload_invoice(
+            &business,
+            &1000,
+            &currency,
+            &due_date,
+            &String::from_str(&env, "Invoice"),
+        );
+        client.verify_invoice(&admin, &invoice_id);
+        let bid_id = client.place_bid(&investor, &invoice_id, &1000, &1100);
+        client.accept_bid(&business, &invoice_id, &bid_id);
+        invoice_id
+    };
+    let soon = fund(now + 5 * day);
+    let later = fund(now + 30 * day);
+    // Unfunded invoices are not reminded of
+    client.upload_invoice(
+        &business,
+        &1000,
+        &currency,
+        &(now + day),
+        &String::from_str(&env, "Invoice"),
+    );
+
+    assert_eq!(
+        client.try_emit_due_reminders(&0),
+        Err(Ok(QuickLendXError::InvalidTimestamp))
+    );
+    assert_eq!(
+        client.emit_due_reminders(&(7 * day)),
+        vec![&env, soon.clone()]
+    );
+    assert_eq!(client.emit_due_reminders(&(7 * day)), Vec::new(&env));
+    assert_eq!(client.get_last_due_reminder(&soon, &(7 * day)), Some(now));
+
+    // A narrower window is a separate bucket, and later invoices come due in turn
+    env.ledger().with_mut(|li| li.timestamp += 24 * day);
+    assert_eq!(
+        client.emit_due_reminders(&(7 * day)),
+        vec![&env, later.clone()]
+    );
+    assert_eq!(client.emit_due_reminders(&day), Vec::new(&env));
+    env.ledger().with_mut(|li| li.timestamp += 5 * day);
+    assert_eq!(client.emit_due_reminders(&day), vec![&env, later]);
+}
+
+#[test]
+fn test_auto_verify_rules_verify_trusted_uploads() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let day = 86_400;
+    let description = String::from_str(&env, "Invoice");
+    let upload = |amount: i128, days: u64| {
+        client.upload_invoice(
+            &business,
+            &amount,
+            &currency,
+            &(env.ledger().timestamp() + days * day),
+            &description,
+        )
+    };
+
+    // New businesses qualify under small rules, established ones under larger
+    let rules = vec![
+        &env,
+        AutoVerifyRule {
+            max_amount: 1000,
+            max_duration: 30 * day,
+            min_paid_invoices: 0,
+        },
+        AutoVerifyRule {
+            max_amount: 20_000,
+            max_duration: 90 * day,
+            min_paid_invoices: 1,
+        },
+    ];
+    assert_eq!(
+        client.try_set_auto_verify_rules(
+            &admin,
+            &vec![
+                &env,
+                AutoVerifyRule {
+                    max_amount: 0,
+                    max_duration: day,
+                    min_paid_invoices: 0
+                }
+            ]
+        ),
+        Err(Ok(QuickLendXError::InvalidAmount))
+    );
+    client.set_auto_verify_rules(&admin, &rules);
+    assert_eq!(client.get_auto_verify_rules(), rules);
+
+    let small = upload(1000, 10);
+    assert_eq!(client.get_invoice(&small).status, InvoiceStatus::Verified);
+    assert_eq!(
+        client.get_invoice(&upload(1000, 60)).status,
+        InvoiceStatus::Pending
+    );
+    let large = upload(10_000, 60);
+    assert_eq!(client.get_invoice(&large).status, InvoiceStatus::Pending);
+
+    // Once an invoice is paid, the business earns the second rule
+    let bid_id = client.place_bid(&investor, &small, &1000, &1100);
+    client.accept_bid(&business, &small, &bid_id);
+    client.settle_invoice(&business, &small, &1100);
+    let trusted = upload(10_000, 60);
+    assert_eq!(client.get_invoice(&trusted).status, InvoiceStatus::Verified);
+    let trail = client.get_invoice_audit_trail(&trusted);
+    let entry = client.get_audit_entry(&trail.get(trail.len() - 1).unwrap());
+    assert_eq!(entry.actor, contract_id);
+
+    // Turning the rules off sends uploads back to manual verification
+    client.set_auto_verify_rules(&admin, &Vec::new(&env));
+    assert_eq!(
+        client.get_invoice(&upload(500, 5)).status,
+        InvoiceStatus::Pending
+    );
+}
+
+#[test]
+fn test_due_date_extension_postpones_default() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let verifier = Address::generate(&env);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    client.add_verifier(&admin, &verifier);
+    client.set_default_grace_period(&admin, &0);
+    let day = 86_400;
+    let due_date = env.ledger().timestamp() + 10 * day;
+    let fund = || {
+        let invoice_id = client.upload_invoice(
+            &business,
+            &1000,
+            &currency,
+            &due_date,
+            &String::from_str(&env, "Invoice"),
+        );
+        client.verify_invoice(&admin, &invoice_id);
+        let bid_id = client.place_bid(&investor, &invoice_id, &1000, &1100);
+        client.accept_bid(&business, &invoice_id, &bid_id);
+        invoice_id
+    };
+    let delayed = fund();
+    let refused = fund();
+    let evidence = BytesN::from_array(&env, &[4u8; 32]);
+
+    assert_eq!(
+        client.try_request_due_date_extension(
+            &business,
+            &delayed,
+            &(due_date + 31 * day),
+            &evidence
+        ),
+        Err(Ok(QuickLendXError::InvalidTimestamp))
+    );
+    client.request_due_date_extension(&business, &delayed, &(due_date + 20 * day), &evidence);
+    assert_eq!(
+        client.try_request_due_date_extension(
+            &business,
+            &delayed,
+            &(due_date + 5 * day),
+            &evidence
+        ),
+        Err(Ok(QuickLendXError::ExtensionAlreadyRequested))
+    );
+    assert_eq!(
+        client.try_approve_due_date_extension(&investor, &delayed),
+        Err(Ok(QuickLendXError::NotVerifier))
+    );
+    let extension = client.approve_due_date_extension(&verifier, &delayed);
+    assert_eq!(extension.status, ExtensionStatus::Approved);
+    assert_eq!(extension.decided_by, Some(verifier.clone()));
+    let invoice = client.get_invoice(&delayed);
+    assert_eq!(invoice.due_date, due_date + 20 * day);
+    assert_eq!(
+        invoice.extensions.get(0).unwrap().original_due_date,
+        due_date
+    );
+
+    // A rejected request is final and leaves the due date alone
+    client.request_due_date_extension(&business, &refused, &(due_date + 5 * day), &evidence);
+    client.reject_due_date_extension(&verifier, &refused);
+    assert_eq!(
+        client.try_approve_due_date_extension(&verifier, &refused),
+        Err(Ok(QuickLendXError::ExtensionNotFound))
+    );
+    assert_eq!(client.get_invoice(&refused).due_date, due_date);
+
+    // Only the invoice without an extension defaults at the original due date
+    env.ledger().with_mut(|li| li.timestamp = due_date + day);
+    assert_eq!(client.check_overdue_invoices(), vec![&env, refused]);
+    assert_eq!(client.get_invoice(&delayed).status, InvoiceStatus::Funded);
+    let ladder = client.get_maturity_ladder();
+    assert_eq!((ladder.overdue, ladder.total), (0, 1000));
+
+    // Invoices stored before v13 migrate without an extension
+    env.as_contract(&contract_id, || {
+        let key = crate::storage::DataKey::Invoice(delayed.clone());
+        let invoice: Invoice = env.storage().persistent().get(&key).unwrap();
+        let legacy = crate::upgrade::InvoiceV12 {
+            id: invoice.id,
+            business: invoice.business,
+            amount: invoice.amount,
+            currency: invoice.currency,
+            due_date: invoice.due_date,
+            status: invoice.status,
+            created_at: invoice.created_at,
+            description: invoice.description,
+            invoice_hash: invoice.invoice_hash,
+            documents: invoice.documents,
+            category: invoice.category,
+            tags: invoice.tags,
+            funded_amount: invoice.funded_amount,
+            funded_at: invoice.funded_at,
+            investor: invoice.investor,
+            investors: invoice.investors,
+            settled_at: invoice.settled_at,
+            average_rating: invoice.average_rating,
+            total_ratings: invoice.total_ratings,
+            ratings: invoice.ratings,
+        };
+        env.storage().persistent().set(&key, &legacy);
+        crate::upgrade::set_schema_version(&env, 12);
+    });
+    client.migrate(&admin);
+    let invoice = client.get_invoice(&delayed);
+    assert_eq!(invoice.extensions.len(), 0);
+    assert_eq!(invoice.due_date, due_date + 20 * day);
+}
+
+#[test]
+fn test_investor_kyc_gates_bidding_when_required() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let other_investor = Address::generate(&env);
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1000,
+        &Address::generate(&env),
+        &(env.ledger().timestamp() + 86_400),
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+
+    // Until the admin requires it, anyone may bid
+    assert!(!client.is_investor_kyc_required());
+    client.place_bid(&investor, &invoice_id, &100, &110);
+    client.set_investor_kyc_required(&admin, &true);
+    assert_eq!(
+        client.try_place_bid(&investor, &invoice_id, &100, &110),
+        Err(Ok(QuickLendXError::InvestorNotVerified))
+    );
+
+    // Investors apply and are verified or rejected like businesses
+    let kyc_data = String::from_str(&env, "KYC data");
+    client.submit_investor_kyc(&investor, &kyc_data);
+    client.submit_investor_kyc(&other_investor, &kyc_data);
+    assert_eq!(
+        client.try_submit_investor_kyc(&investor, &kyc_data),
+        Err(Ok(QuickLendXError::KYCAlreadyPending))
+    );
+    assert_eq!(
+        client.try_verify_investor(&Address::generate(&env), &investor, &true),
+        Err(Ok(QuickLendXError::NotVerifier))
+    );
+    assert_eq!(
+        client.try_place_bid(&investor, &invoice_id, &100, &110),
+        Err(Ok(QuickLendXError::InvestorNotVerified))
+    );
+    client.verify_investor(&admin, &investor, &true);
+    client.reject_investor(
+        &admin,
+        &other_investor,
+        &String::from_str(&env, "Incomplete"),
+    );
+    let verification = client.get_investor_verification_status(&investor).unwrap();
+    assert_eq!(verification.status, InvestorVerificationStatus::Verified);
+    assert!(verification.accredited);
+    assert_eq!(verification.verified_by, Some(admin.clone()));
+    assert_eq!(
+        client.get_investors_by_status(&InvestorVerificationStatus::Verified),
+        vec![&env, investor.clone()]
+    );
+    assert_eq!(
+        client.get_investors_by_status(&InvestorVerificationStatus::Pending),
+        Vec::new(&env)
+    );
+    assert_eq!(
+        client.try_verify_investor(&admin, &investor, &false),
+        Err(Ok(QuickLendXError::InvalidKYCStatus))
+    );
+
+    // Only verified investors may bid; rejected ones may apply again
+    client.place_bid(&investor, &invoice_id, &100, &110);
+    assert_eq!(
+        client.try_place_bid(&other_investor, &invoice_id, &100, &110),
+        Err(Ok(QuickLendXError::InvestorNotVerified))
+    );
+    client.submit_investor_kyc(&other_investor, &kyc_data);
+    assert_eq!(
+        client.get_investors_by_status(&InvestorVerificationStatus::Rejected),
+        Vec::new(&env)
+    );
+}
+
+#[test]
+fn test_due_date_extension_needs_investor_majority_by_amount() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let investor1 = Address::generate(&env);
+    let investor2 = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let day = 86_400;
+    let due_date = env.ledger().timestamp() + 10 * day;
+    let evidence = BytesN::from_array(&env, &[5u8; 32]);
+    let fund = || {
+        let invoice_id = client.upload_invoice(
+            &business,
+            &1000,
+            &currency,
+            &due_date,
+            &String::from_str(&env, "Syndicated invoice"),
+        );
+        client.verify_invoice(&admin, &invoice_id);
+        let bid1 = client.place_bid(&investor1, &invoice_id, &600, &660);
+        let bid2 = client.place_bid(&investor2, &invoice_id, &400, &440);
+        client.accept_bid(&business, &invoice_id, &bid1);
+        client.accept_bid(&business, &invoice_id, &bid2);
+        client.request_due_date_extension(&business, &invoice_id, &(due_date + 5 * day), &evidence);
+        invoice_id
+    };
+    client.set_extension_vote_window(&admin, &(2 * day));
+    assert_eq!(client.get_extension_vote_window(), 2 * day);
+
+    // A verifier's approval puts the extension to the investors
+    let agreed = fund();
+    let extension = client.approve_due_date_extension(&admin, &agreed);
+    assert_eq!(extension.status, ExtensionStatus::AwaitingInvestors);
+    assert_eq!(client.get_invoice(&agreed).due_date, due_date);
+    assert_eq!(
+        client.try_vote_on_due_date_extension(&Address::generate(&env), &agreed, &true),
+        Err(Ok(QuickLendXError::NotInvestor))
+    );
+
+    // The minority holder's approval is not enough; the majority's is
+    let extension = client.vote_on_due_date_extension(&investor2, &agreed, &true);
+    assert_eq!(extension.status, ExtensionStatus::AwaitingInvestors);
+    assert_eq!(
+        client.try_vote_on_due_date_extension(&investor2, &agreed, &true),
+        Err(Ok(QuickLendXError::AlreadyVoted))
+    );
+    let extension = client.vote_on_due_date_extension(&investor1, &agreed, &true);
+    assert_eq!(extension.status, ExtensionStatus::Approved);
+    assert_eq!(client.get_invoice(&agreed).due_date, due_date + 5 * day);
+    let vote = client.get_due_date_extension_vote(&agreed).unwrap();
+    assert_eq!((vote.approved_amount, vote.total_amount), (1000, 1000));
+
+    // The majority holder can refuse on its own
+    let refused = fund();
+    client.approve_due_date_extension(&admin, &refused);
+    let extension = client.vote_on_due_date_extension(&investor1, &refused, &false);
+    assert_eq!(extension.status, ExtensionStatus::Rejected);
+    assert_eq!(client.get_invoice(&refused).due_date, due_date);
+
+    // Without a majority by the deadline, the extension is rejected
+    let ignored = fund();
+    client.approve_due_date_extension(&admin, &ignored);
+    client.vote_on_due_date_extension(&investor2, &ignored, &true);
+    assert_eq!(
+        client.try_close_due_date_extension_vote(&ignored),
+        Err(Ok(QuickLendXError::OperationNotAllowed))
+    );
+    env.ledger().with_mut(|li| li.timestamp += 2 * day);
+    assert_eq!(
+        client.try_vote_on_due_date_extension(&investor1, &ignored, &true),
+        Err(Ok(QuickLendXError::ExtensionVoteClosed))
+    );
+    let extension = client.close_due_date_extension_vote(&ignored);
+    assert_eq!(extension.status, ExtensionStatus::Rejected);
+    assert_eq!(client.get_invoice(&ignored).due_date, due_date);
+}
+
+#[test]
+fn test_blacklisted_parties_cannot_upload_bid_fund_or_settle() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86_400;
+    let description = String::from_str(&env, "Invoice");
+    let reason = String::from_str(&env, "Sanctioned");
+    let invoice_id = client.upload_invoice(&business, &1000, &currency, &due_date, &description);
+    client.verify_invoice(&admin, &invoice_id);
+    let bid_id = client.place_bid(&investor, &invoice_id, &1000, &1100);
+
+    assert_eq!(
+        client.try_add_to_blacklist(&investor, &business, &reason),
+        Err(Ok(QuickLendXError::NotAdmin))
+    );
+    client.add_to_blacklist(&admin, &investor, &reason);
+    client.add_to_blacklist(&admin, &investor, &reason);
+    assert!(client.is_blacklisted(&investor));
+    assert_eq!(client.get_blacklist(), vec![&env, investor.clone()]);
+    assert_eq!(
+        client.get_blacklist_entry(&investor).unwrap().added_by,
+        admin
+    );
+
+    // A bid placed before blacklisting can no longer be accepted or repeated
+    assert_eq!(
+        client.try_accept_bid(&business, &invoice_id, &bid_id),
+        Err(Ok(QuickLendXError::Blacklisted))
+    );
+    assert_eq!(
+        client.try_place_bid(&investor, &invoice_id, &1000, &1100),
+        Err(Ok(QuickLendXError::Blacklisted))
+    );
+    client.remove_from_blacklist(&admin, &investor);
+    assert_eq!(
+        client.try_remove_from_blacklist(&admin, &investor),
+        Err(Ok(QuickLendXError::StorageKeyNotFound))
+    );
+    client.accept_bid(&business, &invoice_id, &bid_id);
+
+    // A blacklisted business can neither upload nor settle
+    client.add_to_blacklist(&admin, &business, &reason);
+    assert_eq!(
+        client.try_upload_invoice(&business, &1000, &currency, &due_date, &description),
+        Err(Ok(QuickLendXError::Blacklisted))
+    );
+    assert_eq!(
+        client.try_settle_invoice(&business, &invoice_id, &1100),
+        Err(Ok(QuickLendXError::Blacklisted))
+    );
+    client.remove_from_blacklist(&admin, &business);
+    client.settle_invoice(&business, &invoice_id, &1100);
+    assert_eq!(client.get_invoice(&invoice_id).status, InvoiceStatus::Paid);
+    assert_eq!(client.get_blacklist(), Vec::new(&env));
+}
+
+#[test]
+fn test_delinquency_stages_advance_and_raise_risk_score() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let day = 86_400;
+    let due_date = env.ledger().timestamp() + day;
+    client.set_default_grace_period(&admin, &(90 * day));
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1000,
+        &Address::generate(&env),
+        &due_date,
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    let bid_id = client.place_bid(&investor, &invoice_id, &1000, &1100);
+    client.accept_bid(&business, &invoice_id, &bid_id);
+
+    assert_eq!(client.update_delinquency_stages(), Vec::new(&env));
+    let stages = [
+        (due_date + 10 * day, DelinquencyStage::GracePeriod),
+        (due_date + 30 * day, DelinquencyStage::Delinquent30),
+        (due_date + 60 * day, DelinquencyStage::Delinquent60),
+    ];
+    for (timestamp, stage) in stages {
+        env.ledger().with_mut(|li| li.timestamp = timestamp);
+        // The amount due reports the stage before keepers record it
+        assert_eq!(
+            client
+                .get_amount_due_breakdown(&invoice_id)
+                .delinquency_stage,
+            stage
+        );
+        assert_ne!(client.get_delinquency_stage(&invoice_id), stage);
+        assert_eq!(
+            client.update_delinquency_stages(),
+            vec![&env, invoice_id.clone()]
+        );
+        assert_eq!(client.get_delinquency_stage(&invoice_id), stage);
+    }
+    assert_eq!(client.update_delinquency_stages(), Vec::new(&env));
+    assert_eq!(
+        client.get_invoice(&invoice_id).status,
+        InvoiceStatus::Funded
+    );
+
+    // Paying after 60 days late counts as half a default
+    client.settle_invoice(&business, &invoice_id, &1100);
+    assert_eq!(
+        client.get_delinquency_stage(&invoice_id),
+        DelinquencyStage::Delinquent60
+    );
+    assert_eq!(client.get_business_risk_score(&business), 50);
+}
+
+#[test]
+fn test_stop_loss_rules_pause_bidding_on_defaults() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    client.set_default_grace_period(&admin, &0);
+    let due_date = env.ledger().timestamp() + 86_400;
+    let upload = || {
+        let invoice_id = client.upload_invoice(
+            &business,
+            &1000,
+            &currency,
+            &due_date,
+            &String::from_str(&env, "Invoice"),
+        );
+        client.verify_invoice(&admin, &invoice_id);
+        invoice_id
+    };
+    let fund = |invoice_id: &BytesN<32>| {
+        let bid_id = client.place_bid(&investor, invoice_id, &1000, &1100);
+        client.accept_bid(&business, invoice_id, &bid_id);
+    };
+
+    let exposure_alert = StopLossRule {
+        metric: PortfolioMetric::ActiveExposure,
+        threshold: 1500,
+        action: StopLossAction::Alert,
+    };
+    let default_pause = StopLossRule {
+        metric: PortfolioMetric::DefaultRateBps,
+        threshold: 3000,
+        action: StopLossAction::PauseBidding,
+    };
+    assert_eq!(
+        client.try_set_stop_loss_rules(
+            &investor,
+            &vec![
+                &env,
+                StopLossRule {
+                    threshold: 10_000,
+                    ..default_pause.clone()
+                }
+            ]
+        ),
+        Err(Ok(QuickLendXError::InvalidAmount))
+    );
+    client.set_stop_loss_rules(
+        &investor,
+        &vec![&env, exposure_alert.clone(), default_pause.clone()],
+    );
+
+    // Going over the exposure threshold only raises an alert
+    let repaid = upload();
+    let defaulted = upload();
+    fund(&repaid);
+    assert!(client.get_stop_loss_state(&investor).triggers.is_empty());
+    fund(&defaulted);
+    let state = client.get_stop_loss_state(&investor);
+    assert_eq!(state.triggers.len(), 1);
+    assert_eq!(state.triggers.get(0).unwrap().rule, exposure_alert);
+    assert_eq!(state.triggers.get(0).unwrap().value, 2000);
+    assert!(!state.bidding_paused);
+
+    // One default in two closed investments pauses bidding
+    client.settle_invoice(&business, &repaid, &1100);
+    env.ledger().with_mut(|li| li.timestamp = due_date + 1);
+    client.check_overdue_invoices();
+    let stats = client.get_portfolio_stats(&investor);
+    assert_eq!(
+        (stats.closed, stats.defaulted, stats.active_amount),
+        (2, 1, 0)
+    );
+    let state = client.get_stop_loss_state(&investor);
+    assert!(state.bidding_paused);
+    assert_eq!(state.triggers.get(1).unwrap().rule, default_pause);
+    assert_eq!(state.triggers.get(1).unwrap().invoice_id, Some(defaulted));
+
+    let next = client.upload_invoice(
+        &business,
+        &1000,
+        &currency,
+        &(due_date + 86_400),
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &next);
+    assert_eq!(
+        client.try_place_bid(&investor, &next, &1000, &1100),
+        Err(Ok(QuickLendXError::BiddingPaused))
+    );
+    client.resume_bidding(&investor);
+    assert_eq!(
+        client.try_resume_bidding(&investor),
+        Err(Ok(QuickLendXError::InvalidStatus))
+    );
+    client.place_bid(&investor, &next, &1000, &1100);
+}
+
+#[test]
+fn test_storage_footprint_of_upload_fund_settle_stays_within_budget() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86_400;
+    let run_flow = |step: &mut dyn FnMut(&str)| {
+        let invoice_id = client.upload_invoice(
+            &business,
+            &1000,
+            &currency,
+            &due_date,
+            &String::from_str(&env, "Invoice"),
+        );
+        step("upload");
+        client.verify_invoice(&admin, &invoice_id);
+        step("verify");
+        let bid_id = client.place_bid(&investor, &invoice_id, &1000, &1100);
+        step("place_bid");
+        client.accept_bid(&business, &invoice_id, &bid_id);
+        step("accept_bid");
+        client.settle_invoice(&business, &invoice_id, &1100);
+        step("settle");
+    };
+
+    // The first invoice also creates entries shared by later ones
+    run_flow(&mut |_| {});
+
+    // Each step of a later invoice's life adds at most this much
+    let budget = |step: &str| match step {
+        "upload" => StorageBudget {
+            entries: 4,
+            bytes: 2_400,
+            instance_bytes: 256,
+        },
+        "verify" => StorageBudget {
+            entries: 4,
+            bytes: 1_800,
+            instance_bytes: 256,
+        },
+        "place_bid" => StorageBudget {
+            entries: 4,
+            bytes: 1_200,
+            instance_bytes: 64,
+        },
+        "accept_bid" => StorageBudget {
+            entries: 9,
+            bytes: 5_600,
+            instance_bytes: 900,
+        },
+        _ => StorageBudget {
+            entries: 3,
+            bytes: 2_000,
+            instance_bytes: 400,
+        },
+    };
+    let start = StorageFootprint::measure(&env, &contract_id);
+    let mut before = start;
+    run_flow(&mut |step| {
+        let after = StorageFootprint::measure(&env, &contract_id);
+        budget(step).assert_within(step, &after.growth_since(&before));
+        before = after;
+    });
+
+    // Every call loads the instance, so what it gains per invoice is capped
+    // as a whole, as is the largest single entry
+    let growth = before.growth_since(&start);
+    StorageBudget {
+        entries: 20,
+        bytes: 11_000,
+        instance_bytes: 1_600,
+    }
+    .assert_within("upload to settlement", &growth);
+    assert!(before.largest_entry_bytes <= 1_200);
+    assert_eq!(before.temporary_entries, 0);
+}
+
+#[test]
+fn test_notification_inbox_records_invoice_lifecycle() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86_400;
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    let bid_id = client.place_bid(&investor, &invoice_id, &1000, &1100);
+    client.accept_bid(&business, &invoice_id, &bid_id);
+    client.emit_due_reminders(&86_400);
+
+    let kinds = |address: &Address, offset: u64, limit: u32| {
+        let mut kinds = Vec::new(&env);
+        for notification in client.get_notifications(address, &offset, &limit).iter() {
+            assert_eq!(notification.invoice_id, invoice_id);
+            kinds.push_back(notification.kind);
+        }
+        kinds
+    };
+    // Newest first, and pages skip the newest `offset`
+    assert_eq!(
+        kinds(&business, 0, 10),
+        vec![
+            &env,
+            NotificationKind::PaymentDueSoon,
+            NotificationKind::BidReceived,
+            NotificationKind::InvoiceVerified,
+        ]
+    );
+    assert_eq!(
+        kinds(&business, 1, 1),
+        vec![&env, NotificationKind::BidReceived]
+    );
+    assert_eq!(kinds(&business, 3, 10), Vec::new(&env));
+    assert_eq!(
+        kinds(&investor, 0, 10),
+        vec![&env, NotificationKind::BidAccepted]
+    );
+
+    client.mark_read(&business, &2);
+    client.mark_read(&business, &2);
+    let inbox = client.get_notifications(&business, &0, &10);
+    assert!(inbox.get(1).unwrap().read);
+    assert!(!inbox.get(0).unwrap().read);
+    assert_eq!(
+        client.get_inbox_summary(&business),
+        InboxSummary {
+            total: 3,
+            unread: 2
+        }
+    );
+    assert_eq!(
+        client.try_mark_read(&business, &4),
+        Err(Ok(QuickLendXError::StorageKeyNotFound))
+    );
+
+    // Defaulting tells the business and the investor
+    env.ledger()
+        .with_mut(|li| li.timestamp = due_date + 31 * 86_400);
+    client.check_overdue_invoices();
+    assert_eq!(
+        kinds(&investor, 0, 1),
+        vec![&env, NotificationKind::InvoiceDefaulted]
+    );
+    assert_eq!(
+        kinds(&business, 0, 1),
+        vec![&env, NotificationKind::InvoiceDefaulted]
+    );
+}
+
+#[test]
+fn test_marketplace_scenario_is_seeded_deterministically() {
+    let seed = || {
+        let env = Env::default();
+        let contract_id = env.register(QuickLendXContract, ());
+        let client = QuickLendXContractClient::new(&env, &contract_id);
+        let marketplace = scenarios::seed_marketplace(&env, &client);
+        (env, contract_id, marketplace)
+    };
+    let (env, contract_id, marketplace) = seed();
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    assert_eq!(marketplace.invoices.len(), 50);
+    assert_eq!(client.get_total_invoice_count(), 50);
+    for (status, count) in scenarios::MARKETPLACE_INVOICES.iter() {
+        assert_eq!(client.get_invoice_count_by_status(status), *count);
+    }
+    assert_eq!(
+        client.get_invoice(&marketplace.defaulted).status,
+        InvoiceStatus::Defaulted
+    );
+    assert_eq!(
+        client.get_invoice(&marketplace.disputed).status,
+        InvoiceStatus::Funded
+    );
+    assert!(client.get_dispute(&marketplace.disputed).is_some());
+    assert_eq!(marketplace.open_bids.len(), 7);
+    for bid_id in marketplace.open_bids.iter() {
+        assert_eq!(client.get_bid(&bid_id).unwrap().status, BidStatus::Placed);
+    }
+
+    // A second environment gets the same ids
+    let (_, _, again) = seed();
+    assert_eq!(again.invoices, marketplace.invoices);
+    assert_eq!(again.open_bids, marketplace.open_bids);
+    assert_eq!(again.businesses, marketplace.businesses);
+}
+
+#[test]
+fn test_platform_stats_track_volume_and_tvl() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let usdc = Address::generate(&env);
+    let eurc = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86_400;
+    let fund = |currency: &Address, amount: i128| {
+        let invoice_id = client.upload_invoice(
+            &business,
+            &amount,
+            currency,
+            &due_date,
+            &String::from_str(&env, "Invoice"),
+        );
+        client.verify_invoice(&admin, &invoice_id);
+        let bid_id = client.place_bid(&investor, &invoice_id, &amount, &(amount + 100));
+        client.accept_bid(&business, &invoice_id, &bid_id);
+        invoice_id
+    };
+    let settled = fund(&usdc, 1000);
+    let defaulted = fund(&usdc, 2000);
+    fund(&eurc, 500);
+    // Unfunded invoices only count as created
+    client.upload_invoice(
+        &business,
+        &700,
+        &usdc,
+        &due_date,
+        &String::from_str(&env, "Invoice"),
+    );
+
+    let stats = client.get_platform_stats();
+    assert_eq!(stats.invoices_created, 4);
+    assert_eq!(stats.currencies, vec![&env, usdc.clone(), eurc.clone()]);
+    assert_eq!(stats.by_currency.get(0).unwrap().funded_volume, 3000);
+    assert_eq!(stats.by_currency.get(1).unwrap().funded_volume, 500);
+    let escrowed = client.get_currency_stats(&usdc).escrowed;
+    assert_eq!(escrowed, 3000);
+
+    client.settle_invoice(&business, &settled, &1100);
+    env.ledger()
+        .with_mut(|li| li.timestamp = due_date + 8 * 86_400);
+    client.handle_default(&admin, &defaulted);
+    let usdc_stats = client.get_currency_stats(&usdc);
+    assert_eq!(usdc_stats.funded_volume, 3000);
+    assert_eq!(usdc_stats.settled_volume, 1100);
+    assert_eq!(usdc_stats.defaulted_volume, 2000);
+    assert_eq!(client.get_currency_stats(&eurc).escrowed, 500);
+    // Escrowed funds stay locked until released
+    assert_eq!(usdc_stats.escrowed, 3000);
+    client.release_escrow_funds(&admin, &settled);
+    assert_eq!(client.get_currency_stats(&usdc).escrowed, 2000);
+}
+
+#[test]
+fn test_export_business_data_pages_live_then_archived_invoices() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86_400;
+    let upload = || {
+        client.upload_invoice(
+            &business,
+            &1000,
+            &currency,
+            &due_date,
+            &String::from_str(&env, "Invoice"),
+        )
+    };
+    let fund = |invoice_id: &BytesN<32>| {
+        client.verify_invoice(&admin, invoice_id);
+        let bid_id = client.place_bid(&investor, invoice_id, &1000, &1100);
+        client.accept_bid(&business, invoice_id, &bid_id);
+    };
+    let settled = upload();
+    fund(&settled);
+    client.settle_invoice(&business, &settled, &1100);
+    let funded = upload();
+    fund(&funded);
+    let pending = upload();
+    client.set_archive_age(&admin, &0);
+    env.ledger().with_mut(|li| li.timestamp += 1);
+    assert_eq!(client.archive_invoices(&10), vec![&env, settled.clone()]);
+
+    let first = client.export_business_data(&business, &0, &2);
+    assert_eq!(first.total_invoices, 3);
+    assert_eq!(first.verification.get(0).unwrap().business, business);
+    assert!(first.profile.is_empty());
+    assert_eq!(first.invoices.len(), 2);
+    let funded_export = first.invoices.get(0).unwrap();
+    assert_eq!(funded_export.invoice.id, funded);
+    assert!(!funded_export.archived);
+    assert_eq!(funded_export.receipts.len(), 1);
+    assert_eq!(funded_export.receipts.get(0).unwrap().amount, 1000);
+    assert_eq!(first.invoices.get(1).unwrap().invoice.id, pending);
+    assert!(first.invoices.get(1).unwrap().receipts.is_empty());
+
+    let rest = client.export_business_data(&business, &2, &2);
+    assert_eq!(rest.invoices.len(), 1);
+    let settled_export = rest.invoices.get(0).unwrap();
+    assert_eq!(settled_export.invoice.id, settled);
+    assert!(settled_export.archived);
+    assert_eq!(settled_export.invoice.status, InvoiceStatus::Paid);
+
+    // Pages are capped, and reading past the end returns no invoices
+    assert_eq!(
+        client
+            .export_business_data(&business, &0, &100)
+            .invoices
+            .len(),
+        3
+    );
+    assert!(client
+        .export_business_data(&business, &3, &10)
+        .invoices
+        .is_empty());
+}
+
+#[test]
+fn test_business_stats_track_payment_record() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let day = 86_400;
+    let start = env.ledger().timestamp();
+    let fund = |due_date: u64| {
+        let invoice_id = client.upload_invoice(
+            &business,
+            &1000,
+            &currency,
+            &due_date,
+            &String::from_str(&env, "Invoice"),
+        );
+        client.verify_invoice(&admin, &invoice_id);
+        let bid_id = client.place_bid(&investor, &invoice_id, &1000, &1100);
+        client.accept_bid(&business, &invoice_id, &bid_id);
+        invoice_id
+    };
+    let early = fund(start + 10 * day);
+    let late = fund(start + day);
+    let defaulted = fund(start + day);
+    client.upload_invoice(
+        &business,
+        &1000,
+        &currency,
+        &(start + day),
+        &String::from_str(&env, "Invoice"),
+    );
+    assert_eq!(
+        client.get_business_stats(&business),
+        BusinessStats {
+            total_invoices: 4,
+            funded_count: 3,
+            paid_count: 0,
+            default_count: 0,
+            on_time_rate_bps: 0,
+            average_settlement_delay: 0,
+        }
+    );
+
+    client.settle_invoice(&business, &early, &1100);
+    // Two days past due, within the grace period
+    env.ledger().with_mut(|li| li.timestamp = start + 3 * day);
+    client.settle_invoice(&business, &late, &1100);
+    env.ledger().with_mut(|li| li.timestamp = start + 9 * day);
+    client.handle_default(&admin, &defaulted);
+
+    assert_eq!(
+        client.get_business_stats(&business),
+        BusinessStats {
+            total_invoices: 4,
+            funded_count: 3,
+            paid_count: 2,
+            default_count: 1,
+            on_time_rate_bps: 5_000,
+            average_settlement_delay: day,
+        }
+    );
+}
+
+#[test]
+fn test_imported_attestations_count_only_from_trusted_issuers() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let issuer = Address::generate(&env);
+    let other_issuer = Address::generate(&env);
+    let claim = BytesN::from_array(&env, &[1u8; 32]);
+    let other_claim = BytesN::from_array(&env, &[2u8; 32]);
+
+    assert_eq!(
+        client.try_import_attestation(&issuer, &business, &1_000_000, &claim),
+        Err(Ok(QuickLendXError::IssuerNotTrusted))
+    );
+    assert_eq!(
+        client.try_register_attestation_issuer(&admin, &issuer, &10_001),
+        Err(Ok(QuickLendXError::InvalidAmount))
+    );
+    client.register_attestation_issuer(&admin, &issuer, &5_000);
+    client.register_attestation_issuer(&admin, &other_issuer, &10_000);
+    client.import_attestation(&issuer, &business, &1_000_000, &claim);
+    client.import_attestation(&other_issuer, &business, &200_000, &other_claim);
+    assert_eq!(
+        client.try_import_attestation(&issuer, &business, &1_000_000, &claim),
+        Err(Ok(QuickLendXError::AttestationAlreadyImported))
+    );
+    let imported = client.get_imported_attestations(&business, &0, &10);
+    assert_eq!(imported.len(), 2);
+    assert_eq!(imported.get(1).unwrap().claim_hash, other_claim);
+    assert_eq!(client.get_imported_reputation(&business), 700_000);
+    assert_eq!(
+        client.get_business_risk_score(&business),
+        crate::insurance::NEW_BUSINESS_RISK_SCORE - crate::attestation::IMPORTED_ATTESTATION_CREDIT
+    );
+
+    // Only the issuer or the admin may revoke a claim
+    assert_eq!(
+        client.try_revoke_imported_attestation(&business, &business, &other_issuer, &other_claim),
+        Err(Ok(QuickLendXError::NotAdmin))
+    );
+    client.revoke_imported_attestation(&other_issuer, &business, &other_issuer, &other_claim);
+    assert_eq!(client.get_imported_reputation(&business), 500_000);
+    assert!(
+        client
+            .get_imported_attestations(&business, &1, &10)
+            .get(0)
+            .unwrap()
+            .revoked
+    );
+
+    // Revoking the issuer discounts everything it attested
+    client.revoke_attestation_issuer(&admin, &issuer);
+    assert!(client.get_attestation_issuers().get(0).unwrap().revoked);
+    assert_eq!(client.get_imported_reputation(&business), 0);
+    assert_eq!(
+        client.get_business_risk_score(&business),
+        crate::insurance::NEW_BUSINESS_RISK_SCORE
+    );
+    assert_eq!(
+        client.try_import_attestation(&issuer, &business, &1, &other_claim),
+        Err(Ok(QuickLendXError::IssuerNotTrusted))
+    );
+}
+
+#[test]
+fn test_reverse_factoring_program_confirms_and_caps_supplier_invoices() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, debtor) = verified_business(&env, &client);
+    let supplier = Address::generate(&env);
+    client.submit_kyc_application(&supplier, &String::from_str(&env, "KYC data"));
+    client.verify_business(&admin, &supplier);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 365 * 86_400;
+    let invoice_id = client.upload_invoice(
+        &supplier,
+        &10_000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Parts delivered to debtor"),
+    );
+
+    assert_eq!(
+        client.try_confirm_supplier_invoice(&debtor, &invoice_id),
+        Err(Ok(QuickLendXError::ProgramNotFound))
+    );
+    client.open_debtor_program(&debtor, &currency, &500);
+    client.set_reverse_factoring_fee(&admin, &300);
+
+    // The debtor's confirmation lists the invoice for funding
+    client.confirm_supplier_invoice(&debtor, &invoice_id);
+    assert_eq!(
+        client.get_invoice(&invoice_id).status,
+        InvoiceStatus::Verified
+    );
+    assert_eq!(client.get_invoice_debtor(&invoice_id), Some(debtor.clone()));
+    assert_eq!(
+        client.get_program_invoices_by_status(&debtor, &InvoiceStatus::Verified),
+        vec![&env, invoice_id.clone()]
+    );
+    assert_eq!(
+        client.get_debtor_programs().get(0).unwrap().invoice_count,
+        1
+    );
+    assert_eq!(
+        client.try_confirm_supplier_invoice(&debtor, &invoice_id),
+        Err(Ok(QuickLendXError::InvoiceAlreadyConfirmed))
+    );
+
+    // Bids above the program's preferential rate are refused
+    assert_eq!(
+        client.try_place_bid(&investor, &invoice_id, &10_000, &11_000),
+        Err(Ok(QuickLendXError::AprLimitExceeded))
+    );
+    let bid_id = client.place_bid(&investor, &invoice_id, &10_000, &10_400);
+    client.accept_bid(&supplier, &invoice_id, &bid_id);
+    let investment = client.get_invoice_investments(&invoice_id).get(0).unwrap();
+    match investment.fees {
+        FeeSnapshot::Recorded(terms) => assert_eq!(terms.platform_fee_bps, 300),
+        FeeSnapshot::Unrecorded => panic!("fee terms should be recorded"),
+    }
+
+    // A closed program confirms no more invoices
+    client.close_debtor_program(&debtor);
+    let later = client.upload_invoice(
+        &supplier,
+        &5_000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "More parts"),
+    );
+    assert_eq!(
+        client.try_confirm_supplier_invoice(&debtor, &later),
+        Err(Ok(QuickLendXError::ProgramClosed))
+    );
+    assert_eq!(client.get_program_invoices(&debtor).len(), 1);
+}
+
+#[test]
+fn test_default_recovery_accumulates_until_written_off() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let lead = Address::generate(&env);
+    let follower = Address::generate(&env);
+    let collector = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 30 * 86_400;
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1_000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Defaulting invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    let lead_bid = client.place_bid(&lead, &invoice_id, &600, &660);
+    let follower_bid = client.place_bid(&follower, &invoice_id, &400, &440);
+    client.accept_bid(&business, &invoice_id, &lead_bid);
+    client.accept_bid(&business, &invoice_id, &follower_bid);
+
+    // Nothing can be recovered before the invoice defaults
+    assert_eq!(
+        client.try_record_default_recovery(&admin, &invoice_id, &collector, &100),
+        Err(Ok(QuickLendXError::InvalidStatus))
+    );
+    env.ledger()
+        .with_mut(|l| l.timestamp = due_date + crate::config::DEFAULT_GRACE_PERIOD + 1);
+    client.handle_default(&admin, &invoice_id);
+    assert_eq!(client.get_default_recovery(&invoice_id), None);
+
+    client.record_default_recovery(&admin, &invoice_id, &collector, &250);
+    let recovery = client.record_default_recovery(&admin, &invoice_id, &collector, &150);
+    assert_eq!((recovery.recovered, recovery.recoveries), (400, 2));
+    assert!(!recovery.written_off);
+    assert_eq!(
+        client.try_record_default_recovery(&admin, &invoice_id, &collector, &0),
+        Err(Ok(QuickLendXError::InvalidAmount))
+    );
+
+    let written_off = client.write_off_default(&admin, &invoice_id);
+    assert!(written_off.written_off);
+    assert_eq!(written_off.written_off_at, Some(env.ledger().timestamp()));
+    assert_eq!(client.get_default_recovery(&invoice_id), Some(written_off));
+    assert_eq!(
+        client.try_record_default_recovery(&admin, &invoice_id, &collector, &100),
+        Err(Ok(QuickLendXError::InvoiceWrittenOff))
+    );
+    assert_eq!(
+        client.try_write_off_default(&admin, &invoice_id),
+        Err(Ok(QuickLendXError::InvoiceWrittenOff))
+    );
+}
+
+#[test]
+fn test_defaulted_claim_sale_moves_recovery_rights() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let collector = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 30 * 86_400;
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1_000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Defaulting invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    let bid_id = client.place_bid(&investor, &invoice_id, &1_000, &1_100);
+    client.accept_bid(&business, &invoice_id, &bid_id);
+    let investment_id = client
+        .get_invoice_investments(&invoice_id)
+        .get(0)
+        .unwrap()
+        .investment_id;
+
+    // Only claims on defaulted invoices can be sold
+    assert_eq!(
+        client.try_list_defaulted_claim(&investor, &investment_id, &400),
+        Err(Ok(QuickLendXError::InvalidStatus))
+    );
+    env.ledger()
+        .with_mut(|l| l.timestamp = due_date + crate::config::DEFAULT_GRACE_PERIOD + 1);
+    client.handle_default(&admin, &invoice_id);
+    assert_eq!(
+        client.try_list_defaulted_claim(&buyer, &investment_id, &400),
+        Err(Ok(QuickLendXError::NotInvestor))
+    );
+    assert_eq!(
+        client.try_list_defaulted_claim(&investor, &investment_id, &1_000),
+        Err(Ok(QuickLendXError::InvalidAmount))
+    );
+
+    client.list_defaulted_claim(&investor, &investment_id, &400);
+    assert_eq!(client.get_claim_listings().len(), 1);
+    assert_eq!(
+        client.try_list_defaulted_claim(&investor, &investment_id, &300),
+        Err(Ok(QuickLendXError::ClaimAlreadyListed))
+    );
+    let sale = client.buy_defaulted_claim(&buyer, &investment_id);
+    assert_eq!((sale.seller, sale.price), (investor.clone(), 400));
+    assert_eq!(client.get_claim_owner(&investment_id), buyer);
+    assert_eq!(
+        client.get_owned_claims(&buyer),
+        vec![&env, investment_id.clone()]
+    );
+    assert_eq!(client.get_claim_listings().len(), 0);
+    assert_eq!(
+        client.try_buy_defaulted_claim(&buyer, &investment_id),
+        Err(Ok(QuickLendXError::ClaimListingNotFound))
+    );
+
+    // The original investor can no longer sell it; the buyer can
+    assert_eq!(
+        client.try_list_defaulted_claim(&investor, &investment_id, &300),
+        Err(Ok(QuickLendXError::NotInvestor))
+    );
+    client.record_default_recovery(&admin, &invoice_id, &collector, &500);
+    client.list_defaulted_claim(&buyer, &investment_id, &300);
+    client.write_off_default(&admin, &invoice_id);
+    assert_eq!(
+        client.try_buy_defaulted_claim(&investor, &investment_id),
+        Err(Ok(QuickLendXError::InvoiceWrittenOff))
+    );
+    client.cancel_claim_listing(&buyer, &investment_id);
+    assert_eq!(client.get_claim_listings().len(), 0);
+}
+
+#[test]
+fn test_supplier_invitation_expedites_kyc_and_joins_program() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, debtor) = verified_business(&env, &client);
+    let supplier = Address::generate(&env);
+    let late_supplier = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let kyc_data = String::from_str(&env, "KYC data");
+
+    assert_eq!(
+        client.try_invite_supplier(&debtor, &supplier, &86_400),
+        Err(Ok(QuickLendXError::ProgramNotFound))
+    );
+    client.open_debtor_program(&debtor, &currency, &500);
+    assert_eq!(
+        client.try_invite_supplier(&debtor, &supplier, &(31 * 86_400)),
+        Err(Ok(QuickLendXError::InvalidTimestamp))
+    );
+    let invitation = client.invite_supplier(&debtor, &supplier, &86_400);
+    assert_eq!(invitation.expires_at, env.ledger().timestamp() + 86_400);
+    client.invite_supplier(&debtor, &late_supplier, &86_400);
+    assert_eq!(client.get_supplier_invitations(&supplier).len(), 1);
+
+    // Accepting submits the supplier's KYC and queues it for expedited review
+    let accepted = client.accept_supplier_invitation(&supplier, &debtor, &kyc_data);
+    assert_eq!(accepted.status, InvitationStatus::Accepted);
+    assert_eq!(
+        client.get_program_suppliers(&debtor),
+        vec![&env, supplier.clone()]
+    );
+    assert_eq!(
+        client.get_expedited_kyc_applications(),
+        vec![&env, supplier.clone()]
+    );
+    assert_eq!(
+        client.try_accept_supplier_invitation(&supplier, &debtor, &kyc_data),
+        Err(Ok(QuickLendXError::InvalidStatus))
+    );
+    client.verify_business(&admin, &supplier);
+    assert_eq!(client.get_expedited_kyc_applications().len(), 0);
+
+    // Invitations lapse unless accepted in time
+    env.ledger().with_mut(|l| l.timestamp += 86_400);
+    assert_eq!(
+        client.try_accept_supplier_invitation(&late_supplier, &debtor, &kyc_data),
+        Err(Ok(QuickLendXError::InvitationExpired))
+    );
+    assert_eq!(
+        client.try_accept_supplier_invitation(&late_supplier, &admin, &kyc_data),
+        Err(Ok(QuickLendXError::InvitationNotFound))
+    );
+    client.invite_supplier(&debtor, &late_supplier, &86_400);
+    client.accept_supplier_invitation(&late_supplier, &debtor, &kyc_data);
+    assert_eq!(client.get_program_suppliers(&debtor).len(), 2);
+}
+
+#[test]
+fn test_rollover_replaces_invoice_once_all_investors_approve() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let lead = Address::generate(&env);
+    let follower = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 30 * 86_400;
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1_000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Rolled invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    let lead_bid = client.place_bid(&lead, &invoice_id, &600, &660);
+    let follower_bid = client.place_bid(&follower, &invoice_id, &400, &440);
+    client.accept_bid(&business, &invoice_id, &lead_bid);
+    client.accept_bid(&business, &invoice_id, &follower_bid);
+
+    let new_due_date = due_date + 30 * 86_400;
+    assert_eq!(
+        client.try_propose_rollover(&business, &invoice_id, &due_date, &1_200),
+        Err(Ok(QuickLendXError::InvalidTimestamp))
+    );
+    assert_eq!(
+        client.try_propose_rollover(&business, &invoice_id, &new_due_date, &900),
+        Err(Ok(QuickLendXError::InvalidAmount))
+    );
+    assert_eq!(
+        client.try_approve_rollover(&lead, &invoice_id),
+        Err(Ok(QuickLendXError::RolloverNotFound))
+    );
+    client.propose_rollover(&business, &invoice_id, &new_due_date, &1_200);
+
+    // Every investor must consent before anything changes
+    let proposal = client.approve_rollover(&lead, &invoice_id);
+    assert_eq!(proposal.new_invoice_id, None);
+    assert_eq!(
+        client.get_invoice(&invoice_id).status,
+        InvoiceStatus::Funded
+    );
+    assert_eq!(
+        client.try_approve_rollover(&lead, &invoice_id),
+        Err(Ok(QuickLendXError::AlreadyVoted))
+    );
+    assert_eq!(
+        client.try_approve_rollover(&business, &invoice_id),
+        Err(Ok(QuickLendXError::NotInvestor))
+    );
+
+    let proposal = client.approve_rollover(&follower, &invoice_id);
+    let new_id = proposal.new_invoice_id.clone().unwrap();
+    assert_eq!(client.get_rollover(&invoice_id), Some(proposal));
+    assert_eq!(
+        client.get_invoice(&invoice_id).status,
+        InvoiceStatus::Refinanced
+    );
+    assert_eq!(
+        client.get_invoices_by_status(&InvoiceStatus::Refinanced),
+        vec![&env, invoice_id.clone()]
+    );
+    let new_invoice = client.get_invoice(&new_id);
+    assert_eq!(new_invoice.status, InvoiceStatus::Funded);
+    assert_eq!(
+        (
+            new_invoice.amount,
+            new_invoice.due_date,
+            new_invoice.funded_amount
+        ),
+        (1_200, new_due_date, 1_000)
+    );
+    let investments = client.get_invoice_investments(&new_id);
+    assert_eq!(investments.len(), 2);
+    assert!(investments.iter().all(|i| i.invoice_id == new_id));
+    assert_eq!(client.get_invoice_investments(&invoice_id).len(), 0);
+    assert_eq!(client.get_total_invoice_count(), 2);
+
+    // The funding escrows back the new invoice, so the watchdog leaves it alone
+    let escrows = client.get_invoice_escrows(&new_id);
+    assert_eq!(escrows.len(), 2);
+    assert!(escrows.iter().all(|e| e.invoice_id == new_id));
+    assert!(client
+        .get_invoice_escrows(&invoice_id)
+        .iter()
+        .all(|e| e.status != crate::payments::EscrowStatus::Held));
+    assert!(client.get_unbacked_funded_invoices().is_empty());
+
+    // The new invoice settles like any other
+    client.settle_invoice(&business, &new_id, &1_200);
+    assert_eq!(client.get_invoice(&new_id).status, InvoiceStatus::Paid);
+}
+
+#[test]
+fn test_program_credit_line_is_drawn_by_funding_and_restored_by_settlement() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, debtor) = verified_business(&env, &client);
+    let supplier = Address::generate(&env);
+    client.submit_kyc_application(&supplier, &String::from_str(&env, "KYC data"));
+    client.verify_business(&admin, &supplier);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 365 * 86_400;
+    let upload = |amount: i128| {
+        let invoice_id = client.upload_invoice(
+            &supplier,
+            &amount,
+            &currency,
+            &due_date,
+            &String::from_str(&env, "Parts delivered to debtor"),
+        );
+        client.confirm_supplier_invoice(&debtor, &invoice_id);
+        invoice_id
+    };
+
+    assert_eq!(
+        client.try_set_program_credit_line(&debtor, &15_000),
+        Err(Ok(QuickLendXError::ProgramNotFound))
+    );
+    client.open_debtor_program(&debtor, &currency, &1_000);
+    client.set_program_credit_line(&debtor, &15_000);
+    assert_eq!(client.get_program_line_utilization(&debtor), 0);
+
+    // Funding a program invoice draws the line down
+    let first = upload(10_000);
+    let bid_id = client.place_bid(&investor, &first, &10_000, &10_400);
+    client.accept_bid(&supplier, &first, &bid_id);
+    let line = client.get_program_credit_line(&debtor).unwrap();
+    assert_eq!((line.limit, line.drawn), (15_000, 10_000));
+    assert_eq!(client.get_program_line_utilization(&debtor), 6_666);
+
+    // Funding beyond what is left of the line is refused
+    let second = upload(8_000);
+    let bid_id = client.place_bid(&investor, &second, &8_000, &8_300);
+    assert_eq!(
+        client.try_accept_bid(&supplier, &second, &bid_id),
+        Err(Ok(QuickLendXError::CreditLimitExceeded))
+    );
+
+    // Settlement restores the line, after which the second invoice fits
+    client.settle_invoice(&supplier, &first, &10_400);
+    assert_eq!(client.get_program_credit_line(&debtor).unwrap().drawn, 0);
+    client.accept_bid(&supplier, &second, &bid_id);
+    assert_eq!(
+        client.get_program_credit_line(&debtor).unwrap().drawn,
+        8_000
+    );
+}
+
+#[test]
+fn test_program_debtor_settlement_routes_residual_to_supplier() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, debtor) = verified_business(&env, &client);
+    let supplier = Address::generate(&env);
+    client.submit_kyc_application(&supplier, &String::from_str(&env, "KYC data"));
+    client.verify_business(&admin, &supplier);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 365 * 86_400;
+    let invoice_id = client.upload_invoice(
+        &supplier,
+        &10_000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Parts delivered to debtor"),
+    );
+    client.open_debtor_program(&debtor, &currency, &1_000);
+    client.confirm_supplier_invoice(&debtor, &invoice_id);
+    let bid_id = client.place_bid(&investor, &invoice_id, &10_000, &10_400);
+    client.accept_bid(&supplier, &invoice_id, &bid_id);
+
+    // Only the program's debtor settles, and for at least what investors are due
+    assert_eq!(
+        client.try_settle_program_invoice(&investor, &invoice_id, &10_500),
+        Err(Ok(QuickLendXError::Unauthorized))
+    );
+    assert_eq!(
+        client.try_settle_program_invoice(&debtor, &invoice_id, &10_300),
+        Err(Ok(QuickLendXError::InsufficientFunds))
+    );
+
+    let preview = client.build_program_settlement_plan(&invoice_id, &10_500);
+    let plan = client.settle_program_invoice(&debtor, &invoice_id, &10_500);
+    assert_eq!(plan, preview);
+    assert_eq!(plan.payment_amount, 10_500);
+    let mut routed = 0;
+    for transfer in plan.transfers.iter() {
+        assert_eq!(transfer.from, debtor);
+        routed += transfer.amount;
+    }
+    assert_eq!(routed, 10_500);
+    let residual = plan.transfers.get(plan.transfers.len() - 1).unwrap();
+    assert_eq!(residual.kind, SettlementTransferKind::SupplierResidual);
+    assert_eq!((residual.to, residual.amount), (supplier.clone(), 100));
+    assert_eq!(plan.total_investor_return + plan.total_platform_fee, 10_400);
+    assert_eq!(client.get_invoice(&invoice_id).status, InvoiceStatus::Paid);
+}
+
+#[test]
+fn test_amend_pending_invoice_resets_approvals_and_audits_old_values() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let verifier = Address::generate(&env);
+    client.add_verifier(&admin, &verifier);
+    client.set_verification_quorum(
+        &admin,
+        &VerificationQuorum {
+            threshold: 10_000,
+            required: 2,
+        },
+    );
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86_400;
+    let invoice_id = client.upload_invoice(
+        &business,
+        &50_000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Invoce"),
+    );
+    client.verify_invoice(&verifier, &invoice_id);
+    assert_eq!(client.get_invoice_approvals(&invoice_id).len(), 1);
+
+    // The new values are validated as on upload
+    assert_eq!(
+        client.try_amend_invoice(
+            &business,
+            &invoice_id,
+            &0,
+            &due_date,
+            &String::from_str(&env, "Invoice")
+        ),
+        Err(Ok(QuickLendXError::InvalidAmount))
+    );
+    let amended = client.amend_invoice(
+        &business,
+        &invoice_id,
+        &45_000,
+        &(due_date + 86_400),
+        &String::from_str(&env, "Invoice"),
+    );
+    assert_eq!(amended.amount, 45_000);
+    assert_eq!(amended.due_date, due_date + 86_400);
+    assert_eq!(client.get_invoice(&invoice_id), amended);
+    assert_eq!(client.get_invoice_approvals(&invoice_id).len(), 0);
+
+    let entries = client.get_audit_entries_by_operation(&AuditOperation::InvoiceAmended);
+    let entry = client.get_audit_entry(&entries.get(0).unwrap());
+    assert_eq!(entry.old_value, Some(String::from_str(&env, "Invoce")));
+    assert_eq!(entry.amount, Some(50_000));
+    assert_eq!(entry.additional_data, Some(String::from_str(&env, "86400")));
+
+    // Verified invoices can no longer be amended
+    client.verify_invoice(&verifier, &invoice_id);
+    client.verify_invoice(&admin, &invoice_id);
+    assert_eq!(
+        client.try_amend_invoice(
+            &business,
+            &invoice_id,
+            &40_000,
+            &due_date,
+            &String::from_str(&env, "Invoice")
+        ),
+        Err(Ok(QuickLendXError::InvalidStatus))
+    );
+}
+
+#[test]
+fn test_auto_reinvest_credits_returns_to_balance_and_funds_bids() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let currency = funded_token(&env, &investor, 1_000);
+    let due_date = env.ledger().timestamp() + 30 * 86_400;
+    let list = |amount: i128| {
+        let invoice_id = client.upload_invoice(
+            &business,
+            &amount,
+            &currency,
+            &due_date,
+            &String::from_str(&env, "Invoice"),
+        );
+        client.verify_invoice(&admin, &invoice_id);
+        invoice_id
+    };
+
+    client.set_auto_reinvest(&investor, &true);
+    assert!(client.is_auto_reinvest(&investor));
+    assert_eq!(client.deposit_balance(&investor, &currency, &1_000), 1_000);
+
+    // Bids are funded from the balance while it covers them
+    let first = list(1_000);
+    let bid_id = client.place_bid(&investor, &first, &1_000, &1_100);
+    assert_eq!(client.get_balance(&investor, &currency), 0);
+    client.accept_bid(&business, &first, &bid_id);
+
+    // Settlement returns are credited to the balance instead of paid out
+    client.settle_invoice(&business, &first, &1_100);
+    assert_eq!(client.get_balance(&investor, &currency), 1_100);
+
+    // Funds of a bid that leaves the book go back to the balance
+    let second = list(500);
+    let bid_id = client.place_bid(&investor, &second, &500, &550);
+    assert_eq!(client.get_balance(&investor, &currency), 600);
+    client.withdraw_bid(&investor, &bid_id);
+    assert_eq!(client.get_balance(&investor, &currency), 1_100);
+
+    assert_eq!(
+        client.try_withdraw_balance(&investor, &currency, &2_000, &investor),
+        Err(Ok(QuickLendXError::InsufficientFunds))
+    );
+    assert_eq!(client.withdraw_balance(&investor, &currency, &400, &investor), 700);
+}
+
+#[test]
+fn test_refunds_are_credited_to_balances_that_fund_later_bids() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let currency = funded_token(&env, &business, 300);
+    let due_date = env.ledger().timestamp() + 86_400;
+    let list = |amount: i128| {
+        let invoice_id = client.upload_invoice(
+            &business,
+            &amount,
+            &currency,
+            &due_date,
+            &String::from_str(&env, "Invoice"),
+        );
+        client.verify_invoice(&admin, &invoice_id);
+        invoice_id
+    };
+
+    // Refunded escrow funds stay in the contract as the investor's balance
+    let first = list(1_000);
+    let bid_id = client.place_bid(&investor, &first, &1_000, &1_100);
+    client.accept_bid(&business, &first, &bid_id);
+    client.refund_escrow_funds(&admin, &first);
+    assert_eq!(client.get_balance(&investor, &currency), 1_000);
+
+    // and fund the investor's next bid without opting in to anything
+    let second = list(800);
+    let bid_id = client.place_bid(&investor, &second, &800, &880);
+    assert_eq!(client.get_balance(&investor, &currency), 200);
+
+    // Open bids rejected when the business cancels are refunded the same way
+    client.cancel_invoice(&business, &second);
+    assert_eq!(client.get_bid(&bid_id).unwrap().status, BidStatus::Rejected);
+    assert_eq!(client.get_balance(&investor, &currency), 1_000);
+
+    // A bid the balance does not cover is funded from the wallet instead
+    let third = list(2_000);
+    client.place_bid(&investor, &third, &2_000, &2_200);
+    assert_eq!(client.get_balance(&investor, &currency), 1_000);
+
+    // Any address may hold a balance
+    assert_eq!(client.deposit_balance(&business, &currency, &300), 300);
+    assert_eq!(client.withdraw_balance(&business, &currency, &300, &business), 0);
+}
+
+#[contract]
+pub struct MockPriceOracle;
+
+#[contractimpl]
+impl MockPriceOracle {
+    pub fn set_price(env: Env, asset: Address, price: i128, timestamp: u64) {
+        env.storage()
+            .instance()
+            .set(&asset, &crate::oracle::PriceData { price, timestamp });
+    }
+
+    pub fn lastprice(env: Env, asset: crate::oracle::Asset) -> Option<crate::oracle::PriceData> {
+        match asset {
+            crate::oracle::Asset::Stellar(address) => env.storage().instance().get(&address),
+            crate::oracle::Asset::Other(_) => None,
+        }
+    }
+}
+
+#[test]
+fn test_price_oracle_values_limits_and_stats_in_reference_currency() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|l| l.timestamp = 1_000);
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let oracle = MockPriceOracleClient::new(&env, &env.register(MockPriceOracle, ()));
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let usd = Address::generate(&env);
+    let eur = Address::generate(&env);
+    oracle.set_price(&usd, &10_000_000, &1_000);
+    oracle.set_price(&eur, &11_000_000, &1_000);
+
+    // Without an oracle amounts are taken as they are
+    assert_eq!(client.get_reference_value(&eur, &1_000), 1_000);
+    assert_eq!(
+        client.try_set_price_oracle(
+            &admin,
+            &OracleConfig {
+                oracle: oracle.address.clone(),
+                reference_currency: usd.clone(),
+                max_price_age: 0,
+            }
+        ),
+        Err(Ok(QuickLendXError::InvalidTimestamp))
+    );
+    client.set_price_oracle(
+        &admin,
+        &OracleConfig {
+            oracle: oracle.address.clone(),
+            reference_currency: usd.clone(),
+            max_price_age: 3_600,
+        },
+    );
+    assert_eq!(client.get_reference_value(&eur, &1_000), 1_100);
+    assert_eq!(client.get_reference_value(&usd, &1_000), 1_000);
+    assert_eq!(
+        client.try_get_reference_value(&Address::generate(&env), &1_000),
+        Err(Ok(QuickLendXError::OraclePriceUnavailable))
+    );
+
+    // Credit limits count exposure in every currency at its reference value
+    client.set_credit_limit(&admin, &business, &2_000);
+    let due_date = env.ledger().timestamp() + 86_400;
+    let list = |currency: &Address| {
+        let invoice_id = client.upload_invoice(
+            &business,
+            &1_000,
+            currency,
+            &due_date,
+            &String::from_str(&env, "Invoice"),
+        );
+        client.verify_invoice(&admin, &invoice_id);
+        let bid_id = client.place_bid(&investor, &invoice_id, &1_000, &1_050);
+        (invoice_id, bid_id)
+    };
+    let (eur_invoice, eur_bid) = list(&eur);
+    let (usd_invoice, usd_bid) = list(&usd);
+    client.accept_bid(&business, &eur_invoice, &eur_bid);
+    assert_eq!(client.get_reference_exposure(&business), 1_100);
+    assert_eq!(
+        client.try_accept_bid(&business, &usd_invoice, &usd_bid),
+        Err(Ok(QuickLendXError::CreditLimitExceeded))
+    );
+    assert_eq!(client.get_reference_stats().funded_volume, 1_100);
+
+    // Prices older than the maximum age are refused
+    env.ledger().with_mut(|l| l.timestamp += 3_601);
+    assert_eq!(
+        client.try_get_reference_value(&eur, &1_000),
+        Err(Ok(QuickLendXError::OraclePriceStale))
+    );
+}
+
+#[test]
+fn test_bid_invoice_and_realized_apr_queries() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+
+    // 2% over 73 days, a fifth of a year, is 10% a year; a same-day bid
+    // counts as one day
+    assert_eq!(client.calculate_bid_apr(&1_000, &1_020, &73), 1_000);
+    assert_eq!(client.calculate_bid_apr(&10_000, &10_001, &0), 365);
+
+    let due_date = env.ledger().timestamp() + 90 * 86_400;
+    let invoice_id = client.upload_invoice(
+        &business,
+        &10_000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    let bid_id = client.place_bid(&investor, &invoice_id, &10_000, &10_300);
+    assert_eq!(
+        client.try_get_invoice_apr(&invoice_id),
+        Err(Ok(QuickLendXError::InvoiceNotFunded))
+    );
+    client.accept_bid(&business, &invoice_id, &bid_id);
+    // 3% over 90 days
+    assert_eq!(client.get_invoice_apr(&invoice_id), 1_216);
+
+    // Repaid after 73 days, a fifth of a year, the 3% realized is 15% a year
+    assert_eq!(client.get_realized_apr(&investor), 0);
+    env.ledger().with_mut(|l| l.timestamp += 73 * 86_400);
+    client.settle_invoice(&business, &invoice_id, &10_300);
+    let returns = client.get_investor_returns(&investor);
+    assert_eq!(
+        (
+            returns.invested,
+            returns.returned,
+            returns.settled_investments
+        ),
+        (10_000, 10_300, 1)
+    );
+    assert_eq!(client.get_realized_apr(&investor), 1_500);
+}
+
+#[test]
+fn test_withdrawal_allowlist_and_delay() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let owner = Address::generate(&env);
+    let safe = Address::generate(&env);
+    let elsewhere = Address::generate(&env);
+    let currency = funded_token(&env, &owner, 1_000);
+    let token = token::Client::new(&env, &currency);
+    let day = 86_400;
+    client.deposit_balance(&owner, &currency, &1_000);
+
+    // Without a delay in force the policy applies at once
+    let policy = client.set_withdrawal_policy(&owner, &vec![&env, safe.clone()], &day);
+    assert_eq!(client.get_withdrawal_policy(&owner), policy);
+    assert_eq!(
+        client.try_withdraw_balance(&owner, &currency, &100, &elsewhere),
+        Err(Ok(QuickLendXError::WithdrawalNotAllowed))
+    );
+
+    // A delayed withdrawal leaves the balance and can be cancelled
+    assert_eq!(client.withdraw_balance(&owner, &currency, &300, &safe), 700);
+    let pending = client.get_pending_withdrawal(&owner, &currency).unwrap();
+    assert_eq!(pending.available_at, env.ledger().timestamp() + day);
+    assert_eq!(
+        client.try_withdraw_balance(&owner, &currency, &100, &safe),
+        Err(Ok(QuickLendXError::WithdrawalPending))
+    );
+    assert_eq!(
+        client.try_complete_withdrawal(&owner, &currency),
+        Err(Ok(QuickLendXError::WithdrawalNotReady))
+    );
+    assert_eq!(token.balance(&contract_id), 1_000);
+    assert_eq!(client.cancel_withdrawal(&owner, &currency), 1_000);
+    assert_eq!(client.get_pending_withdrawal(&owner, &currency), None);
+
+    // Once the delay passes it is paid out
+    client.withdraw_balance(&owner, &currency, &300, &safe);
+    env.ledger().with_mut(|li| li.timestamp += day);
+    assert_eq!(client.complete_withdrawal(&owner, &currency), 300);
+    assert_eq!(client.get_balance(&owner, &currency), 700);
+    assert_eq!((token.balance(&safe), token.balance(&contract_id)), (300, 700));
+    assert_eq!(
+        client.try_cancel_withdrawal(&owner, &currency),
+        Err(Ok(QuickLendXError::WithdrawalNotFound))
+    );
+
+    // Lifting the protection waits out the delay in force
+    client.set_withdrawal_policy(&owner, &Vec::new(&env), &0);
+    assert_eq!(client.get_withdrawal_policy(&owner), policy);
+    assert_eq!(
+        client
+            .get_queued_withdrawal_policy(&owner)
+            .unwrap()
+            .effective_at,
+        env.ledger().timestamp() + day
+    );
+    env.ledger().with_mut(|li| li.timestamp += day);
+    assert_eq!(client.get_withdrawal_policy(&owner).delay, 0);
+    assert_eq!(
+        client.withdraw_balance(&owner, &currency, &700, &elsewhere),
+        0
+    );
+    assert_eq!(token.balance(&elsewhere), 700);
+    assert_eq!(token.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_tranched_invoice_settles_senior_first() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let senior = Address::generate(&env);
+    let junior = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let invoice_id = client.upload_invoice(
+        &business,
+        &10_000,
+        &currency,
+        &(env.ledger().timestamp() + 86_400),
+        &String::from_str(&env, "Invoice"),
+    );
+    // Pending invoices can still be amended, so they cannot be tranched yet
+    assert_eq!(
+        client.try_set_tranches(&business, &invoice_id, &6_000, &500, &1_500),
+        Err(Ok(QuickLendXError::InvalidStatus))
+    );
+    client.verify_invoice(&admin, &invoice_id);
+
+    // 6,000 senior at 5%, 4,000 junior at 15%
+    let structure = client.set_tranches(&business, &invoice_id, &6_000, &500, &1_500);
+    assert_eq!(structure.junior_amount, 4_000);
+    assert_eq!(
+        client.try_place_bid(&senior, &invoice_id, &6_000, &6_300),
+        Err(Ok(QuickLendXError::TrancheRequired))
+    );
+    assert_eq!(
+        client.try_place_tranche_bid(&senior, &invoice_id, &Tranche::Senior, &7_000),
+        Err(Ok(QuickLendXError::TrancheFull))
+    );
+    let senior_bid = client.place_tranche_bid(&senior, &invoice_id, &Tranche::Senior, &6_000);
+    assert_eq!(client.get_bid(&senior_bid).unwrap().expected_return, 6_300);
+    let junior_bid = client.place_tranche_bid(&junior, &invoice_id, &Tranche::Junior, &4_000);
+    client.accept_bid(&business, &invoice_id, &junior_bid);
+    client.accept_bid(&business, &invoice_id, &senior_bid);
+    let tranches = client.get_tranches(&invoice_id).unwrap();
+    assert_eq!(
+        (tranches.senior_funded, tranches.junior_funded),
+        (6_000, 4_000)
+    );
+
+    // Both tranches are paid their returns in full before the platform
+    let plan = client.build_settlement_plan(&invoice_id, &11_000);
+    let paid = |index: u32| {
+        let transfer = plan.transfers.get(index).unwrap();
+        (transfer.to, transfer.amount, transfer.kind)
+    };
+    assert_eq!(plan.transfers.len(), 3);
+    assert_eq!(
+        paid(0),
+        (
+            senior.clone(),
+            6_300,
+            SettlementTransferKind::InvestorReturn
+        )
+    );
+    assert_eq!(
+        paid(1),
+        (
+            junior.clone(),
+            4_600,
+            SettlementTransferKind::InvestorReturn
+        )
+    );
+    assert_eq!(plan.total_platform_fee, 100);
+
+    client.settle_invoice(&business, &invoice_id, &11_000);
+    assert_eq!(client.get_investor_returns(&senior).returned, 6_300);
+
+    // Recoveries on a default follow the same waterfall, so the junior
+    // tranche absorbs a shortfall first
+    let investments = client.get_invoice_investments(&invoice_id);
+    let shares = |recovered: i128, amount: i128| {
+        env.as_contract(&contract_id, || {
+            crate::tranche::recovery_shares(&env, &tranches, &investments, recovered, amount)
+        })
+    };
+    // Investments are listed junior first, in funding order
+    assert_eq!(shares(0, 5_000), vec![&env, 0, 5_000]);
+    assert_eq!(shares(5_000, 3_000), vec![&env, 1_700, 1_300]);
+
+    // Without junior funding, recoveries beyond the senior target still go
+    // to the senior tranche rather than being dropped
+    let seniors = vec![&env, investments.get(1).unwrap()];
+    let senior_shares = env.as_contract(&contract_id, || {
+        crate::tranche::recovery_shares(&env, &tranches, &seniors, 0, 8_000)
+    });
+    assert_eq!(senior_shares, vec![&env, 8_000]);
+}
+
+#[test]
+fn test_abandoned_balances_are_swept_and_can_be_reclaimed() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let owner = Address::generate(&env);
+    let custodian = Address::generate(&env);
+    let currency = funded_token(&env, &owner, 1_001);
+    client.deposit_balance(&owner, &currency, &1_000);
+
+    // Only balances untouched for the whole dormancy period get a notice
+    env.ledger()
+        .with_mut(|li| li.timestamp += crate::escheat::DORMANCY_PERIOD - 1);
+    assert_eq!(
+        client.try_issue_dormancy_notice(&admin, &owner, &currency),
+        Err(Ok(QuickLendXError::BalanceNotDormant))
+    );
+    env.ledger().with_mut(|li| li.timestamp += 1);
+    let notice = client.issue_dormancy_notice(&admin, &owner, &currency);
+    assert_eq!(notice.amount, 1_000);
+    assert_eq!(
+        notice.sweepable_at,
+        env.ledger().timestamp() + crate::escheat::DORMANCY_NOTICE_PERIOD
+    );
+
+    // The balance is swept once the notice runs out, to the custodian
+    assert_eq!(
+        client.try_sweep_dormant_balance(&admin, &owner, &currency),
+        Err(Ok(QuickLendXError::CustodianNotSet))
+    );
+    client.set_escheat_custodian(&admin, &custodian);
+    assert_eq!(
+        client.try_sweep_dormant_balance(&admin, &owner, &currency),
+        Err(Ok(QuickLendXError::DormancyNoticePending))
+    );
+    env.ledger()
+        .with_mut(|li| li.timestamp += crate::escheat::DORMANCY_NOTICE_PERIOD);
+    let escheated = client.sweep_dormant_balance(&admin, &owner, &currency);
+    assert_eq!((escheated.amount, escheated.custodian.clone()), (1_000, custodian.clone()));
+    assert_eq!(client.get_balance(&owner, &currency), 0);
+    let token = token::Client::new(&env, &currency);
+    assert_eq!((token.balance(&custodian), token.balance(&contract_id)), (1_000, 0));
+    assert_eq!(client.get_dormancy_notice(&owner, &currency), None);
+    assert_eq!(
+        client
+            .get_audit_entries_by_operation(&AuditOperation::BalanceEscheated)
+            .len(),
+        1
+    );
+
+    // The owner keeps a claim on the swept funds
+    assert_eq!(client.claim_escheated_balance(&owner, &currency), 1_000);
+    assert_eq!(client.get_escheated_balance(&owner, &currency), None);
+    assert_eq!((token.balance(&custodian), token.balance(&contract_id)), (0, 1_000));
+    assert_eq!(
+        client.try_claim_escheated_balance(&owner, &currency),
+        Err(Ok(QuickLendXError::EscheatNotFound))
+    );
+
+    // Touching the balance withdraws a notice
+    env.ledger()
+        .with_mut(|li| li.timestamp += crate::escheat::DORMANCY_PERIOD);
+    client.issue_dormancy_notice(&admin, &owner, &currency);
+    client.deposit_balance(&owner, &currency, &1);
+    assert_eq!(client.get_dormancy_notice(&owner, &currency), None);
+}
+
+#[test]
+fn test_score_updated_events_on_rating_and_risk_changes() {
+    use soroban_sdk::testutils::Events;
+    use soroban_sdk::{xdr, IntoVal, TryFromVal, Val};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1_000,
+        &currency,
+        &(env.ledger().timestamp() + 86_400),
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    let bid_id = client.place_bid(&investor, &invoice_id, &1_000, &1_100);
+    client.accept_bid(&business, &invoice_id, &bid_id);
+
+    // Whether the last call published `score_updated` of `kind` with `data`
+    let published = |kind: Symbol, data: Val| {
+        let to_xdr = |val: Val| xdr::ScVal::try_from_val(&env, &val).unwrap();
+        let topics = [
+            to_xdr(Symbol::new(&env, "score_updated").to_val()),
+            to_xdr(kind.to_val()),
+        ];
+        let data = to_xdr(data);
+        env.events().all().events().iter().any(|event| {
+            let xdr::ContractEventBody::V0(body) = &event.body;
+            body.topics.as_slice() == topics && body.data == data
+        })
+    };
+
+    // Settling gives the business a repayment history, moving its risk score
+    client.settle_invoice(&business, &invoice_id, &1_100);
+    assert!(published(
+        symbol_short!("risk"),
+        (business.clone(), None::<u32>, 0u32, symbol_short!("paid")).into_val(&env),
+    ));
+    // Nothing moved, so a refresh publishes nothing
+    assert_eq!(client.refresh_risk_score(&business), 0);
+    assert!(env.events().all().events().is_empty());
+
+    client.add_invoice_rating(
+        &invoice_id,
+        &4,
+        &String::from_str(&env, "Paid on time"),
+        &investor,
+    );
+    assert!(published(
+        symbol_short!("rating"),
+        (
+            invoice_id.clone(),
+            business.clone(),
+            None::<u32>,
+            4u32,
+            symbol_short!("rated"),
+        )
+            .into_val(&env),
+    ));
+}
+
+#[test]
+fn test_initialize_requires_admin_auth() {
+    let env = Env::default();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+
+    // Nobody can install an admin that has not signed for it
+    assert!(client.try_initialize(&admin).is_err());
+    assert_eq!(client.get_admin(), None);
+
+    env.mock_all_auths();
+    client.initialize(&admin);
+    assert_eq!(client.get_admin(), Some(admin));
+}
+
+#[test]
+fn test_deposit_pulls_tokens_from_the_owner() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let owner = Address::generate(&env);
+    let currency = funded_token(&env, &owner, 500);
+    let token = token::Client::new(&env, &currency);
+
+    // A deposit the owner cannot pay for credits nothing
+    assert_eq!(
+        client.try_deposit_balance(&owner, &currency, &501),
+        Err(Ok(QuickLendXError::InsufficientFunds))
+    );
+    assert_eq!(client.get_balance(&owner, &currency), 0);
+    assert_eq!(token.balance(&owner), 500);
+
+    assert_eq!(client.deposit_balance(&owner, &currency, &500), 500);
+    assert_eq!(token.balance(&owner), 0);
+    assert_eq!(token.balance(&contract_id), 500);
+}
+
+#[test]
+fn test_reconcile_refunds_held_escrows_and_refuses_released_funding() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let investor1 = Address::generate(&env);
+    let investor2 = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86_400;
+    let fund = || {
+        let invoice_id = client.upload_invoice(
+            &business,
+            &1_000,
+            &currency,
+            &due_date,
+            &String::from_str(&env, "Invoice"),
+        );
+        client.verify_invoice(&admin, &invoice_id);
+        for investor in [&investor1, &investor2] {
+            let bid_id = client.place_bid(investor, &invoice_id, &500, &550);
+            client.accept_bid(&business, &invoice_id, &bid_id);
+        }
+        let escrows = client.get_invoice_escrows(&invoice_id);
+        (invoice_id, escrows.get(0).unwrap(), escrows.get(1).unwrap())
+    };
+
+    // Half the funding was refunded; the other half is still held
+    let (invoice_id, first, second) = fund();
+    env.as_contract(&contract_id, || {
+        crate::payments::refund_escrow(&env, &first.escrow_id, &admin).unwrap();
+    });
+    assert!(client.get_unbacked_funded_invoices().contains(&invoice_id));
+    assert_eq!(client.get_currency_stats(&currency).escrowed, 500);
+
+    client.reconcile_funded_invoice(&admin, &invoice_id);
+    assert_eq!(
+        client.get_escrow(&second.escrow_id).unwrap().status,
+        crate::payments::EscrowStatus::Refunded
+    );
+    assert_eq!(client.get_balance(&investor2, &currency), 500);
+    assert_eq!(client.get_currency_stats(&currency).escrowed, 0);
+    assert_eq!(
+        client.get_invoice(&invoice_id).status,
+        InvoiceStatus::Verified
+    );
+
+    // Funding already released to the business cannot be unwound
+    let (invoice_id, first, second) = fund();
+    env.as_contract(&contract_id, || {
+        crate::payments::release_escrow(&env, &first.escrow_id, &admin).unwrap();
+        crate::payments::refund_escrow(&env, &second.escrow_id, &admin).unwrap();
+    });
+    assert!(client.get_unbacked_funded_invoices().contains(&invoice_id));
+    assert_eq!(
+        client.try_reconcile_funded_invoice(&admin, &invoice_id),
+        Err(Ok(QuickLendXError::OperationNotAllowed))
+    );
+    assert_eq!(
+        client.get_invoice(&invoice_id).status,
+        InvoiceStatus::Funded
+    );
+}
+
+#[test]
+fn test_store_invoice_runs_upload_checks() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    let business = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86_400;
+    let description = String::from_str(&env, "Invoice");
+    client.initialize(&admin);
+
+    // Unverified businesses cannot create invoices through the legacy entrypoint
+    assert_eq!(
+        client.try_store_invoice(&business, &1000, &currency, &due_date, &description),
+        Err(Ok(QuickLendXError::BusinessNotVerified))
+    );
+
+    verify_kyc(&env, &client, &admin, &business);
+    client.set_credit_limit(&admin, &business, &1500);
+    assert_eq!(
+        client.try_store_invoice(&business, &2000, &currency, &due_date, &description),
+        Err(Ok(QuickLendXError::CreditLimitExceeded))
+    );
+    let invoice_id = client.store_invoice(&business, &1000, &currency, &due_date, &description);
+    assert_eq!(
+        client.get_invoice(&invoice_id).status,
+        InvoiceStatus::Pending
+    );
+}
+
+#[test]
+fn test_tier_caps_apply_to_every_invoice_creation_path() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 86_400;
+    let description = String::from_str(&env, "Invoice");
+    client.set_tier_limits(
+        &admin,
+        &KYCTier::Basic,
+        &TierLimits {
+            max_invoice_amount: 5000,
+            max_active_invoices: 2,
+        },
+    );
+
+    // The legacy entrypoint is held to the same caps as upload_invoice
+    assert_eq!(
+        client.try_store_invoice(&business, &6000, &currency, &due_date, &description),
+        Err(Ok(QuickLendXError::TierLimitExceeded))
+    );
+    let invoice_id = client.store_invoice(&business, &5000, &currency, &due_date, &description);
+    client.store_invoice(&business, &1000, &currency, &due_date, &description);
+    assert_eq!(
+        client.try_store_invoice(&business, &1000, &currency, &due_date, &description),
+        Err(Ok(QuickLendXError::TierLimitExceeded))
+    );
+
+    // A rollover cannot create an invoice above the amount cap either
+    client.verify_invoice(&admin, &invoice_id);
+    let bid_id = client.place_bid(&investor, &invoice_id, &5000, &5500);
+    client.accept_bid(&business, &invoice_id, &bid_id);
+    let new_due_date = due_date + 30 * 86_400;
+    assert_eq!(
+        client.try_propose_rollover(&business, &invoice_id, &new_due_date, &5_500),
+        Err(Ok(QuickLendXError::TierLimitExceeded))
+    );
+    client.propose_rollover(&business, &invoice_id, &new_due_date, &5_000);
+}
+
+#[test]
+fn test_late_fee_and_penalty_tiers_are_exclusive() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let day = 86_400u64;
+    let due_date = env.ledger().timestamp() + 30 * day;
+    let tiered = crate::penalty::PenaltySchedule {
+        grace_period: 5 * day,
+        tiers: vec![
+            &env,
+            crate::penalty::PenaltyTier {
+                starts_after: 0,
+                apr_bps: 1_000,
+            },
+        ],
+    };
+
+    // Only one kind of late charge can be configured at a time
+    client.set_penalty_schedule(&admin, &tiered);
+    assert_eq!(
+        client.try_set_late_fee(&admin, &10),
+        Err(Ok(QuickLendXError::LateChargeConflict))
+    );
+    let grace_only = crate::penalty::PenaltySchedule {
+        grace_period: 5 * day,
+        tiers: Vec::new(&env),
+    };
+    client.set_penalty_schedule(&admin, &grace_only);
+    client.set_late_fee(&admin, &10);
+    assert_eq!(
+        client.try_set_penalty_schedule(&admin, &tiered),
+        Err(Ok(QuickLendXError::LateChargeConflict))
+    );
+
+    let invoice_id = client.upload_invoice(
+        &business,
+        &10_000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    let bid_id = client.place_bid(&investor, &invoice_id, &10_000, &11_000);
+    client.accept_bid(&business, &invoice_id, &bid_id);
+
+    // The late fee runs from the due date, not from the end of grace
+    env.ledger().with_mut(|l| l.timestamp = due_date + 3 * day);
+    let due = client.get_amount_due_breakdown(&invoice_id);
+    assert_eq!((due.days_late, due.late_fee, due.penalty), (3, 30, 0));
+    assert_eq!(due.total, 11_030);
+}
+
+#[test]
+fn test_insurance_follows_sold_claims() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 30 * 86_400;
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1_000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Insured invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    client.set_invoice_insurance(&business, &invoice_id, &true);
+    let bid_id = client.place_bid(&investor, &invoice_id, &1_000, &1_100);
+    client.accept_bid(&business, &invoice_id, &bid_id);
+    let investment_id = client
+        .get_invoice_investments(&invoice_id)
+        .get(0)
+        .unwrap()
+        .investment_id;
+    client.deposit_insurance_fund(&admin, &currency, &1_000);
+    env.ledger()
+        .with_mut(|l| l.timestamp = due_date + crate::config::DEFAULT_GRACE_PERIOD + 1);
+    client.handle_default(&admin, &invoice_id);
+
+    client.list_defaulted_claim(&investor, &investment_id, &400);
+    // A listed claim keeps its insurance for whoever buys it
+    assert_eq!(
+        client.try_claim_insurance(&investor, &invoice_id),
+        Err(Ok(QuickLendXError::ClaimAlreadyListed))
+    );
+    assert_eq!(
+        client.try_submit_insurance_claim(&investor, &investment_id, &100),
+        Err(Ok(QuickLendXError::ClaimAlreadyListed))
+    );
+    client.buy_defaulted_claim(&buyer, &investment_id);
+
+    // The seller gave up the investment's insurance along with the claim
+    assert_eq!(
+        client.try_submit_insurance_claim(&investor, &investment_id, &100),
+        Err(Ok(QuickLendXError::NotInvestor))
+    );
+    assert_eq!(
+        client.try_claim_insurance(&investor, &invoice_id),
+        Err(Ok(QuickLendXError::NotInvestor))
+    );
+    assert_eq!(client.claim_insurance(&buyer, &invoice_id), 500);
+    assert_eq!(
+        client.try_submit_insurance_claim(&buyer, &investment_id, &100),
+        Err(Ok(QuickLendXError::ClaimAlreadyExists))
+    );
+
+    // A claim whose insurance was drawn on cannot be sold on
+    assert_eq!(
+        client.try_list_defaulted_claim(&buyer, &investment_id, &300),
+        Err(Ok(QuickLendXError::OperationNotAllowed))
+    );
+}
+
+#[test]
+fn test_migrate_feature_records_to_typed_keys() {
+    use soroban_sdk::testutils::storage::Persistent as _;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let due_date = env.ledger().timestamp() + 30 * 86_400;
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1000,
+        &Address::generate(&env),
+        &due_date,
+        &String::from_str(&env, "Invoice"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    client.set_funding_deadline(&business, &invoice_id, &Some(due_date - 86_400));
+    let bid_id = client.place_bid(&Address::generate(&env), &invoice_id, &500, &550);
+    client.counter_bid(&business, &bid_id, &560);
+
+    // Rewrite the deadline and negotiation as a v13 deployment kept them
+    let deadline_key = crate::storage::DataKey::FundingDeadline(invoice_id.clone());
+    let negotiation_key = crate::storage::DataKey::BidNegotiation(bid_id.clone());
+    let legacy_deadline = (symbol_short!("fund_dl"), invoice_id.clone());
+    let legacy_negotiation = (symbol_short!("bid_neg"), bid_id.clone());
+    env.as_contract(&contract_id, || {
+        let deadline: u64 = env.storage().persistent().get(&deadline_key).unwrap();
+        let negotiation: crate::negotiation::BidNegotiation =
+            env.storage().persistent().get(&negotiation_key).unwrap();
+        env.storage().persistent().remove(&deadline_key);
+        env.storage().persistent().remove(&negotiation_key);
+        env.storage().instance().set(&legacy_deadline, &deadline);
+        env.storage()
+            .instance()
+            .set(&legacy_negotiation, &negotiation);
+        crate::upgrade::set_schema_version(&env, 13);
+    });
+    assert_eq!(
+        client.get_funding_deadline(&invoice_id),
+        Some(due_date - 86_400)
+    );
+    assert_eq!(
+        client.get_bid_negotiation(&bid_id).unwrap().counter_return,
+        Some(560)
+    );
+
+    client.migrate(&admin);
+    env.as_contract(&contract_id, || {
+        assert!(env.storage().persistent().has(&deadline_key));
+        assert!(env.storage().persistent().has(&negotiation_key));
+        assert!(!env.storage().instance().has(&legacy_deadline));
+        assert!(!env.storage().instance().has(&legacy_negotiation));
+    });
+
+    // Keeping the invoice alive keeps the records kept alongside it alive
+    env.ledger()
+        .with_mut(|li| li.sequence_number += 100 * crate::storage::DAY_IN_LEDGERS);
+    client.extend_ttl(&vec![&env, invoice_id.clone()]);
+    env.as_contract(&contract_id, || {
+        for key in [deadline_key, negotiation_key] {
+            assert_eq!(
+                env.storage().persistent().get_ttl(&key),
+                crate::storage::TTL_EXTEND_TO
+            );
+        }
+    });
+}
+
+#[test]
+fn test_installments_pay_recipients_what_they_are_owed_as_late_fees_accrue() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let day = 86_400u64;
+    let due_date = env.ledger().timestamp() + 30 * day;
+    client.set_platform_fee(&admin, &contract_id, &1_000);
+    client.set_late_fee(&admin, &10);
+
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1000,
+        &currency,
+        &due_date,
+        &String::from_str(&env, "Installments"),
+    );
+    client.verify_invoice(&admin, &invoice_id);
+    let bid_id = client.place_bid(&investor, &invoice_id, &1000, &1100);
+    client.accept_bid(&business, &invoice_id, &bid_id);
+    client.set_auto_reinvest(&investor, &true);
+
+    // Half is paid on time, out of a return of 1_090 after fees
+    client.record_partial_payment(&business, &invoice_id, &550);
+    assert_eq!(client.get_balance(&investor, &currency), 545);
+
+    // Ten days late the amount due has grown, and the rest settles it
+    env.ledger().with_mut(|l| l.timestamp = due_date + 10 * day);
+    assert_eq!(client.get_amount_due(&invoice_id), 560);
+    let plan = client.build_settlement_plan(&invoice_id, &1110);
+    client.record_partial_payment(&business, &invoice_id, &560);
+    assert_eq!(client.get_invoice(&invoice_id).status, InvoiceStatus::Paid);
+
+    // Each recipient ends up with exactly its share of the final amount due
+    assert_eq!(
+        client.get_balance(&investor, &currency),
+        plan.total_investor_return
+    );
+    assert_eq!(
+        client.get_treasury_balance(&currency),
+        plan.total_platform_fee
+    );
+}
+
+#[test]
+fn test_defaulted_invoices_archive_once_written_off_and_keep_counting_towards_risk() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let collector = Address::generate(&env);
+    let currency = Address::generate(&env);
+    let due_date = env.ledger().timestamp() + 30 * 86_400;
+    let fund = |description: &str| {
+        let invoice_id = client.upload_invoice(
+            &business,
+            &1_000,
+            &currency,
+            &due_date,
+            &String::from_str(&env, description),
+        );
+        client.verify_invoice(&admin, &invoice_id);
+        let bid_id = client.place_bid(&investor, &invoice_id, &1_000, &1_100);
+        client.accept_bid(&business, &invoice_id, &bid_id);
+        invoice_id
+    };
+    let paid = fund("Paid invoice");
+    let defaulted = fund("Defaulted invoice");
+    client.settle_invoice(&business, &paid, &1_100);
+    env.ledger()
+        .with_mut(|l| l.timestamp = due_date + crate::config::DEFAULT_GRACE_PERIOD + 1);
+    client.handle_default(&admin, &defaulted);
+    // One on-time payment and one default
+    assert_eq!(client.get_business_risk_score(&business), 50);
+
+    // A default still being recovered stays live
+    client.set_archive_age(&admin, &0);
+    env.ledger().with_mut(|l| l.timestamp += 1);
+    assert_eq!(client.archive_invoices(&10), vec![&env, paid.clone()]);
+    assert_eq!(client.get_business_risk_score(&business), 50);
+    client.record_default_recovery(&admin, &defaulted, &collector, &200);
+
+    client.write_off_default(&admin, &defaulted);
+    env.ledger().with_mut(|l| l.timestamp += 1);
+    assert_eq!(client.archive_invoices(&10), vec![&env, defaulted.clone()]);
+    assert_eq!(
+        client.get_archived_invoice(&defaulted).unwrap().status,
+        InvoiceStatus::Defaulted
+    );
+    assert_eq!(client.get_business_invoices(&business).len(), 0);
+    assert_eq!(client.get_business_risk_score(&business), 50);
+
+    // Restoring an invoice does not count it twice
+    client.restore_archived_invoice(&admin, &defaulted);
+    assert_eq!(client.get_business_risk_score(&business), 50);
+    client.restore_archived_invoice(&admin, &paid);
+    assert_eq!(client.get_business_risk_score(&business), 50);
+}
+
+#[test]
+fn test_migrate_compliance_records_to_typed_keys() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let affiliate = Address::generate(&env);
+    let sanctioned = Address::generate(&env);
+    let investor = Address::generate(&env);
+    let flag = |n: u8| crate::compliance::WashFlag {
+        business: business.clone(),
+        investor: investor.clone(),
+        settled_invoice: BytesN::from_array(&env, &[n; 32]),
+        funded_invoice: BytesN::from_array(&env, &[n + 1; 32]),
+        settled_at: 100,
+        funded_at: 200,
+    };
+
+    // Write the compliance records as a v14 deployment kept them
+    let legacy_affiliates = (symbol_short!("affil"), business.clone());
+    env.as_contract(&contract_id, || {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("blacklist"), &vec![&env, sanctioned.clone()]);
+        env.storage()
+            .instance()
+            .set(&legacy_affiliates, &vec![&env, affiliate.clone()]);
+        env.storage()
+            .instance()
+            .set(&symbol_short!("wash_flg"), &vec![&env, flag(1), flag(3)]);
+        crate::upgrade::set_schema_version(&env, 14);
+    });
+    assert_eq!(client.get_blacklist(), vec![&env, sanctioned.clone()]);
+    assert_eq!(
+        client.get_affiliates(&business),
+        vec![&env, affiliate.clone()]
+    );
+
+    client.migrate(&admin);
+    assert_eq!(client.get_blacklist(), vec![&env, sanctioned]);
+    assert_eq!(client.get_affiliates(&business), vec![&env, affiliate]);
+    assert_eq!(client.get_wash_flag_count(), 2);
+    assert_eq!(client.get_wash_flags(&0, &10), vec![&env, flag(1), flag(3)]);
+    assert_eq!(client.get_wash_flags(&1, &1), vec![&env, flag(3)]);
+    env.as_contract(&contract_id, || {
+        assert!(!env.storage().instance().has(&symbol_short!("blacklist")));
+        assert!(!env.storage().instance().has(&legacy_affiliates));
+        assert!(!env.storage().instance().has(&symbol_short!("wash_flg")));
+        assert!(env
+            .storage()
+            .persistent()
+            .has(&crate::storage::DataKey::WashFlag(2)));
+    });
+}
+
+#[test]
+fn test_migrate_attestation_history_to_one_record_each() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let issuer = Address::generate(&env);
+    client.register_attestation_issuer(&admin, &issuer, &5_000);
+    let attestation = |band: u32| crate::attestation::FinancialAttestation {
+        business: business.clone(),
+        revenue_band: band,
+        ar_aging_hash: BytesN::from_array(&env, &[7u8; 32]),
+        published_at: env.ledger().timestamp(),
+    };
+    let claim = |n: u8, revoked: bool| crate::attestation::ImportedAttestation {
+        issuer: issuer.clone(),
+        business: business.clone(),
+        claim_hash: BytesN::from_array(&env, &[n; 32]),
+        settled_volume: 100_000,
+        imported_at: env.ledger().timestamp(),
+        revoked,
+    };
+
+    // Write the histories as a v14 deployment kept them
+    let legacy_attestations = (symbol_short!("attest"), business.clone());
+    let legacy_imported = (symbol_short!("att_ext"), business.clone());
+    env.as_contract(&contract_id, || {
+        env.storage().instance().set(
+            &legacy_attestations,
+            &vec![&env, attestation(1), attestation(2)],
+        );
+        env.storage().instance().set(
+            &legacy_imported,
+            &vec![&env, claim(1, false), claim(2, true)],
+        );
+        crate::upgrade::set_schema_version(&env, 14);
+    });
+
+    client.migrate(&admin);
+    assert_eq!(
+        client.get_attestations(&business, &0, &10),
+        vec![&env, attestation(1), attestation(2)]
+    );
+    assert_eq!(
+        client.get_latest_attestation(&business),
+        Some(attestation(2))
+    );
+    assert_eq!(client.get_attestation_status(&business).count, 2);
+    assert_eq!(
+        client.get_imported_attestations(&business, &0, &10),
+        vec![&env, claim(1, false), claim(2, true)]
+    );
+    assert_eq!(client.get_imported_reputation(&business), 50_000);
+    assert_eq!(
+        client.try_import_attestation(&issuer, &business, &1, &claim(2, true).claim_hash),
+        Err(Ok(QuickLendXError::AttestationAlreadyImported))
+    );
+    env.as_contract(&contract_id, || {
+        assert!(!env.storage().instance().has(&legacy_attestations));
+        assert!(!env.storage().instance().has(&legacy_imported));
+    });
+}
+
+#[test]
+fn test_migrate_insurance_jurisdiction_and_backstop_records_to_typed_keys() {
+    use crate::storage::DataKey;
+    use soroban_sdk::{IntoVal, Val};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+
+    let (admin, business) = verified_business(&env, &client);
+    let currency = Address::generate(&env);
+    let provider = Address::generate(&env);
+    let code = symbol_short!("EU");
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1000,
+        &currency,
+        &(env.ledger().timestamp() + 30 * 86_400),
+        &String::from_str(&env, "Invoice"),
+    );
+    client.set_invoice_insurance(&business, &invoice_id, &true);
+    client.set_jurisdiction(&admin, &business, &code);
+    client.set_jurisdiction_rule(
+        &admin,
+        &crate::jurisdiction::JurisdictionRule {
+            code: code.clone(),
+            max_apr_bps: 2_000,
+            allowed_currencies: Vec::new(&env),
+            investor_jurisdictions: Vec::new(&env),
+        },
+    );
+    client.register_backstop(&provider, &currency, &5_000, &1);
+
+    // Move the records back where a v14 deployment kept them
+    let keys: [(DataKey, Val); 4] = [
+        (
+            DataKey::InsuranceEnabled(invoice_id.clone()),
+            (symbol_short!("ins_on"), invoice_id.clone()).into_val(&env),
+        ),
+        (
+            DataKey::Jurisdiction(business.clone()),
+            (symbol_short!("juris"), business.clone()).into_val(&env),
+        ),
+        (
+            DataKey::JurisdictionRule(code.clone()),
+            (symbol_short!("jur_rule"), code.clone()).into_val(&env),
+        ),
+        (
+            DataKey::BackstopProviders(currency.clone()),
+            (symbol_short!("backstop"), currency.clone()).into_val(&env),
+        ),
+    ];
+    env.as_contract(&contract_id, || {
+        for (key, legacy_key) in keys.iter() {
+            let value: Val = env.storage().persistent().get(key).unwrap();
+            env.storage().persistent().remove(key);
+            env.storage().instance().set(legacy_key, &value);
+        }
+        crate::upgrade::set_schema_version(&env, 14);
+    });
+    assert_eq!(client.get_jurisdiction(&business), Some(code.clone()));
+    assert_eq!(client.get_backstop_providers(&currency).len(), 1);
+
+    client.migrate(&admin);
+    env.as_contract(&contract_id, || {
+        for (key, legacy_key) in keys.iter() {
+            assert!(env.storage().persistent().has(key));
+            assert!(!env.storage().instance().has(legacy_key));
+        }
+        assert!(crate::insurance::InsuranceStorage::is_insurance_enabled(
+            &env,
+            &invoice_id
+        ));
+    });
+    assert_eq!(client.get_jurisdiction(&business), Some(code.clone()));
+    assert_eq!(
+        client.get_jurisdiction_rule(&code).unwrap().max_apr_bps,
+        2_000
+    );
+    assert_eq!(
+        client
+            .get_backstop_providers(&currency)
+            .get(0)
+            .unwrap()
+            .committed,
+        5_000
+    );
+}
+
+#[test]
+fn test_v2_events_carry_domain_topics_and_schema_version() {
+    use soroban_sdk::testutils::{ContractEvents, Events};
+    use soroban_sdk::{xdr, IntoVal, TryFromVal, Val};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickLendXContract, ());
+    let client = QuickLendXContractClient::new(&env, &contract_id);
+    assert_eq!(
+        client.get_event_schema_version(),
+        crate::events::EVENT_SCHEMA_VERSION
+    );
+
+    let (admin, business) = verified_business(&env, &client);
+    let investor = Address::generate(&env);
+    let currency = Address::generate(&env);
+    env.ledger().set_timestamp(1_000);
+    let invoice_id = client.upload_invoice(
+        &business,
+        &1_000,
+        &currency,
+        &(env.ledger().timestamp() + 86_400),
+        &String::from_str(&env, "Invoice"),
+    );
+
+    // Whether `events` include a v2 event with these topics, led by the
+    // schema version and ledger time before `data`
+    let published = |events: &ContractEvents,
+                     domain: Symbol,
+                     action: Symbol,
+                     entity_id: &BytesN<32>,
+                     data: Val| {
+        let to_xdr = |val: Val| xdr::ScVal::try_from_val(&env, &val).unwrap();
+        let topics = [
+            to_xdr(domain.to_val()),
+            to_xdr(action.to_val()),
+            to_xdr(entity_id.to_val()),
+        ];
+        let data = to_xdr(
+            (
+                crate::events::EVENT_SCHEMA_VERSION,
+                env.ledger().timestamp(),
+                data,
+            )
+                .into_val(&env),
+        );
+        events.events().iter().any(|event| {
+            let xdr::ContractEventBody::V0(body) = &event.body;
+            body.topics.as_slice() == topics && body.data == data
+        })
+    };
+
+    client.verify_invoice(&admin, &invoice_id);
+    assert!(published(
+        &env.events().all(),
+        symbol_short!("invoice"),
+        symbol_short!("status"),
+        &invoice_id,
+        (
+            InvoiceStatus::Pending,
+            InvoiceStatus::Verified,
+            admin.clone()
+        )
+            .into_val(&env),
+    ));
+
+    let withdrawn_id = client.place_bid(&investor, &invoice_id, &1_000, &1_050);
+    assert!(published(
+        &env.events().all(),
+        symbol_short!("bid"),
+        symbol_short!("placed"),
+        &withdrawn_id,
+        (invoice_id.clone(), investor.clone(), 1_000i128, 1_050i128).into_val(&env),
+    ));
+    client.withdraw_bid(&investor, &withdrawn_id);
+    assert!(published(
+        &env.events().all(),
+        symbol_short!("bid"),
+        symbol_short!("withdrawn"),
+        &withdrawn_id,
+        (invoice_id.clone(), investor.clone(), 1_000i128).into_val(&env),
+    ));
+
+    let bid_id = client.place_bid(&investor, &invoice_id, &1_000, &1_100);
+    client.accept_bid(&business, &invoice_id, &bid_id);
+    let events = env.events().all();
+    let investment_id = client
+        .get_invoice_investments(&invoice_id)
+        .get(0)
+        .unwrap()
+        .investment_id;
+    assert!(published(
+        &events,
+        symbol_short!("bid"),
+        symbol_short!("accepted"),
+        &bid_id,
+        (
+            invoice_id.clone(),
+            investor.clone(),
+            business.clone(),
+            1_000i128
+        )
+            .into_val(&env),
+    ));
+    assert!(published(
+        &events,
+        symbol_short!("invest"),
+        symbol_short!("created"),
+        &investment_id,
+        (invoice_id.clone(), investor.clone(), 1_000i128).into_val(&env),
+    ));
+    assert!(published(
+        &events,
+        symbol_short!("invoice"),
+        symbol_short!("status"),
+        &invoice_id,
+        (
+            InvoiceStatus::Verified,
+            InvoiceStatus::Funded,
+            business.clone()
+        )
+            .into_val(&env),
+    ));
+
+    env.ledger().set_timestamp(2_000);
+    client.settle_invoice(&business, &invoice_id, &1_100);
+    let events = env.events().all();
+    assert!(published(
+        &events,
+        symbol_short!("invest"),
+        symbol_short!("completed"),
+        &investment_id,
+        (invoice_id.clone(), investor.clone(), 1_000i128).into_val(&env),
+    ));
+    assert!(published(
+        &events,
+        symbol_short!("invoice"),
+        symbol_short!("status"),
+        &invoice_id,
+        (InvoiceStatus::Funded, InvoiceStatus::Paid, business.clone()).into_val(&env),
+    ));
+}
@@ -0,0 +1,315 @@
+use crate::auction::AuctionStorage;
+use crate::bid::{Bid, BidStatus, BidStorage};
+use crate::errors::QuickLendXError;
+use crate::events::{emit_bid_rejected, emit_tranches_set};
+use crate::investment::{pro_rata_shares, Investment};
+use crate::invoice::{Invoice, InvoiceStatus, InvoiceStorage};
+use crate::storage::{self, DataKey};
+use soroban_sdk::{contracttype, Address, BytesN, Env, Vec};
+
+/// Highest return a tranche may promise on its principal
+pub const MAX_TRANCHE_RETURN_BPS: u32 = 10_000;
+
+/// A slice of a tranched invoice's funding
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Tranche {
+    Senior, // Paid first, absorbs losses last
+    Junior, // Paid after the senior tranche, absorbs losses first
+}
+
+/// How a large invoice's funding is split between a senior and a junior
+/// tranche. Each tranche is owed its principal plus a fixed return on it;
+/// settlement pays the senior tranche in full before the junior tranche, and
+/// the platform takes whatever is left over.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TrancheStructure {
+    pub invoice_id: BytesN<32>,
+    pub senior_amount: i128, // Funding taken by the senior tranche; junior takes the rest
+    pub junior_amount: i128,
+    pub senior_return_bps: u32, // Return owed on senior principal
+    pub junior_return_bps: u32, // Return owed on junior principal
+    pub senior_funded: i128,
+    pub junior_funded: i128,
+}
+
+impl TrancheStructure {
+    /// Funding a tranche can still take
+    pub fn remaining(&self, tranche: &Tranche) -> i128 {
+        match tranche {
+            Tranche::Senior => self.senior_amount - self.senior_funded,
+            Tranche::Junior => self.junior_amount - self.junior_funded,
+        }
+    }
+
+    /// Principal plus the return a tranche is owed on it
+    pub fn target_return(&self, tranche: &Tranche, principal: i128) -> i128 {
+        let return_bps = match tranche {
+            Tranche::Senior => self.senior_return_bps,
+            Tranche::Junior => self.junior_return_bps,
+        };
+        principal + principal * return_bps as i128 / 10_000
+    }
+}
+
+pub struct TrancheStorage;
+
+impl TrancheStorage {
+    /// Get an invoice's tranche structure, if it is tranched
+    pub fn get_structure(env: &Env, invoice_id: &BytesN<32>) -> Option<TrancheStructure> {
+        storage::get(env, &DataKey::TrancheStructure(invoice_id.clone()))
+    }
+
+    /// Get the tranche a bid was placed in, if any
+    pub fn get_bid_tranche(env: &Env, bid_id: &BytesN<32>) -> Option<Tranche> {
+        storage::get(env, &DataKey::BidTranche(bid_id.clone()))
+    }
+
+    /// Get the tranche an investment belongs to, if any
+    pub fn get_investment_tranche(env: &Env, investment_id: &BytesN<32>) -> Option<Tranche> {
+        storage::get(env, &DataKey::InvestmentTranche(investment_id.clone()))
+    }
+
+    fn set_structure(env: &Env, structure: &TrancheStructure) {
+        storage::set(
+            env,
+            &DataKey::TrancheStructure(structure.invoice_id.clone()),
+            structure,
+        );
+    }
+}
+
+/// Split an invoice's funding into a senior tranche of `senior_amount` and a
+/// junior tranche taking the rest (business only). Only verified invoices no
+/// one has bid on yet can be tranched, as pending ones can still be amended;
+/// bids on them must then name a tranche.
+pub fn set_tranches(
+    env: &Env,
+    business: &Address,
+    invoice_id: &BytesN<32>,
+    senior_amount: i128,
+    senior_return_bps: u32,
+    junior_return_bps: u32,
+) -> Result<TrancheStructure, QuickLendXError> {
+    business.require_auth();
+    let invoice =
+        InvoiceStorage::get_invoice(env, invoice_id).ok_or(QuickLendXError::InvoiceNotFound)?;
+    if invoice.business != *business {
+        return Err(QuickLendXError::NotBusinessOwner);
+    }
+    if invoice.status != InvoiceStatus::Verified {
+        return Err(QuickLendXError::InvalidStatus);
+    }
+    if AuctionStorage::is_open(env, invoice_id) {
+        return Err(QuickLendXError::AuctionInProgress);
+    }
+    if invoice.funded_amount > 0 || !BidStorage::get_bids_for_invoice(env, invoice_id).is_empty() {
+        return Err(QuickLendXError::OperationNotAllowed);
+    }
+    if senior_amount <= 0 || senior_amount >= invoice.amount {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    // The junior tranche takes losses first and is paid for it
+    if junior_return_bps > MAX_TRANCHE_RETURN_BPS || senior_return_bps > junior_return_bps {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+
+    let structure = TrancheStructure {
+        invoice_id: invoice_id.clone(),
+        senior_amount,
+        junior_amount: invoice.amount - senior_amount,
+        senior_return_bps,
+        junior_return_bps,
+        senior_funded: 0,
+        junior_funded: 0,
+    };
+    TrancheStorage::set_structure(env, &structure);
+    emit_tranches_set(env, &structure);
+    Ok(structure)
+}
+
+/// Check a bid names a tranche exactly when the invoice is tranched, and
+/// fits what that tranche can still take
+pub fn check_bid(
+    env: &Env,
+    invoice: &Invoice,
+    tranche: Option<&Tranche>,
+    bid_amount: i128,
+) -> Result<(), QuickLendXError> {
+    match (TrancheStorage::get_structure(env, &invoice.id), tranche) {
+        (None, None) => Ok(()),
+        (None, Some(_)) => Err(QuickLendXError::TrancheNotFound),
+        (Some(_), None) => Err(QuickLendXError::TrancheRequired),
+        (Some(structure), Some(tranche)) if bid_amount > structure.remaining(tranche) => {
+            Err(QuickLendXError::TrancheFull)
+        }
+        (Some(_), Some(_)) => Ok(()),
+    }
+}
+
+/// Record the tranche a bid was placed in
+pub fn record_bid(env: &Env, bid_id: &BytesN<32>, tranche: &Tranche) {
+    storage::set(env, &DataKey::BidTranche(bid_id.clone()), tranche);
+}
+
+/// Check a bid being accepted still fits its tranche
+pub fn check_accept(env: &Env, invoice: &Invoice, bid: &Bid) -> Result<(), QuickLendXError> {
+    let tranche = TrancheStorage::get_bid_tranche(env, &bid.bid_id);
+    check_bid(env, invoice, tranche.as_ref(), bid.bid_amount)
+}
+
+/// Put the investment made from an accepted bid in the bid's tranche and
+/// reject the open bids its tranche can no longer take
+pub fn record_investment(
+    env: &Env,
+    bid: &Bid,
+    investment_id: &BytesN<32>,
+    actor: &Address,
+) -> Result<(), QuickLendXError> {
+    let Some(mut structure) = TrancheStorage::get_structure(env, &bid.invoice_id) else {
+        return Ok(());
+    };
+    let Some(tranche) = TrancheStorage::get_bid_tranche(env, &bid.bid_id) else {
+        return Ok(());
+    };
+    match tranche {
+        Tranche::Senior => structure.senior_funded += bid.bid_amount,
+        Tranche::Junior => structure.junior_funded += bid.bid_amount,
+    }
+    TrancheStorage::set_structure(env, &structure);
+    storage::set(
+        env,
+        &DataKey::InvestmentTranche(investment_id.clone()),
+        &tranche,
+    );
+
+    for bid_id in BidStorage::get_bids_for_invoice(env, &bid.invoice_id).iter() {
+        let (Some(mut open), Some(tranche)) = (
+            BidStorage::get_bid(env, &bid_id),
+            TrancheStorage::get_bid_tranche(env, &bid_id),
+        ) else {
+            continue;
+        };
+        if open.is_open() && open.bid_amount > structure.remaining(&tranche) {
+            BidStorage::return_funds(env, &open)?;
+            open.status = BidStatus::Rejected;
+            BidStorage::update_bid(env, &open);
+            emit_bid_rejected(env, &open, actor);
+        }
+    }
+    Ok(())
+}
+
+/// Move an invoice's tranche structure to the invoice it was rolled into
+pub fn carry_over(env: &Env, from_invoice: &BytesN<32>, to_invoice: &BytesN<32>) {
+    if let Some(mut structure) = TrancheStorage::get_structure(env, from_invoice) {
+        structure.invoice_id = to_invoice.clone();
+        TrancheStorage::set_structure(env, &structure);
+        storage::remove(env, &DataKey::TrancheStructure(from_invoice.clone()));
+    }
+}
+
+/// Order investments senior tranche first, keeping funding order within
+/// each tranche
+pub fn by_seniority(env: &Env, investments: &Vec<Investment>) -> Vec<Investment> {
+    let mut ordered = Vec::new(env);
+    for tranche in [Tranche::Senior, Tranche::Junior] {
+        for investment in investments.iter() {
+            if tranche_of(env, &investment) == tranche {
+                ordered.push_back(investment);
+            }
+        }
+    }
+    ordered
+}
+
+/// Split `total` across investments by the waterfall: the senior tranche up
+/// to its target return, then the junior tranche up to its own, each pro
+/// rata within the tranche. Returns the shares, in the investments' order,
+/// and what is left once both tranches are paid in full.
+pub fn waterfall_shares(
+    env: &Env,
+    structure: &TrancheStructure,
+    investments: &Vec<Investment>,
+    total: i128,
+) -> (Vec<i128>, i128) {
+    let mut shares = Vec::new(env);
+    for _ in investments.iter() {
+        shares.push_back(0);
+    }
+    let mut remaining = total;
+    for tranche in [Tranche::Senior, Tranche::Junior] {
+        let mut indexes = Vec::new(env);
+        let mut members = Vec::new(env);
+        for (index, investment) in investments.iter().enumerate() {
+            if tranche_of(env, &investment) == tranche {
+                indexes.push_back(index as u32);
+                members.push_back(investment);
+            }
+        }
+        let funded: i128 = members.iter().map(|i| i.amount).sum();
+        let paid = remaining.min(structure.target_return(&tranche, funded));
+        for (index, share) in indexes
+            .iter()
+            .zip(pro_rata_shares(env, &members, paid).iter())
+        {
+            shares.set(index, share);
+        }
+        remaining -= paid;
+    }
+    (shares, remaining)
+}
+
+/// Split a recovery of `amount` on a defaulted tranched invoice, after
+/// `recovered` was recovered earlier, so that recoveries taken together
+/// follow the waterfall. Anything beyond both tranches' target returns goes
+/// to the junior tranche, or to the senior tranche if no junior funding was
+/// taken.
+pub fn recovery_shares(
+    env: &Env,
+    structure: &TrancheStructure,
+    investments: &Vec<Investment>,
+    recovered: i128,
+    amount: i128,
+) -> Vec<i128> {
+    let (before, excess_before) = waterfall_shares(env, structure, investments, recovered);
+    let (after, excess_after) = waterfall_shares(env, structure, investments, recovered + amount);
+    let mut shares = Vec::new(env);
+    for (before, after) in before.iter().zip(after.iter()) {
+        shares.push_back(after - before);
+    }
+
+    let excess = excess_after - excess_before;
+    if excess > 0 {
+        let mut indexes = Vec::new(env);
+        let mut takers = Vec::new(env);
+        for (index, investment) in investments.iter().enumerate() {
+            if tranche_of(env, &investment) == Tranche::Junior {
+                indexes.push_back(index as u32);
+                takers.push_back(investment);
+            }
+        }
+        // Without junior funding the senior tranche takes the excess
+        if takers.is_empty() {
+            for index in 0..investments.len() {
+                indexes.push_back(index);
+            }
+            takers = investments.clone();
+        }
+        for (index, extra) in indexes
+            .iter()
+            .zip(pro_rata_shares(env, &takers, excess).iter())
+        {
+            shares.set(index, shares.get(index).unwrap() + extra);
+        }
+    }
+    shares
+}
+
+/// Tranche an investment in a tranched invoice belongs to. Investments
+/// without one are treated as junior.
+fn tranche_of(env: &Env, investment: &Investment) -> Tranche {
+    TrancheStorage::get_investment_tranche(env, &investment.investment_id)
+        .unwrap_or(Tranche::Junior)
+}
@@ -0,0 +1,82 @@
+use crate::audit::log_fees_withdrawn;
+use crate::errors::QuickLendXError;
+use crate::events::{emit_fees_collected, emit_fees_withdrawn, emit_treasurer_set};
+use crate::payments::transfer_funds;
+use crate::storage::{self, DataKey};
+use crate::verification::{require_admin, BusinessVerificationStorage};
+use soroban_sdk::{symbol_short, Address, Env};
+
+/// Platform fees held by the contract, tracked per currency
+pub struct TreasuryStorage;
+
+impl TreasuryStorage {
+    /// Get the fees held by the contract in a currency
+    pub fn get_balance(env: &Env, currency: &Address) -> i128 {
+        storage::get(env, &DataKey::TreasuryBalance(currency.clone())).unwrap_or(0)
+    }
+
+    fn set_balance(env: &Env, currency: &Address, balance: i128) {
+        storage::set(env, &DataKey::TreasuryBalance(currency.clone()), &balance);
+    }
+
+    /// Get the address allowed to withdraw fees besides the admin
+    pub fn get_treasurer(env: &Env) -> Option<Address> {
+        env.storage().instance().get(&symbol_short!("treasurer"))
+    }
+}
+
+/// Add platform fees received by the contract to the treasury
+pub fn credit_fees(env: &Env, currency: &Address, amount: i128) {
+    if amount <= 0 {
+        return;
+    }
+    let balance = TreasuryStorage::get_balance(env, currency) + amount;
+    TreasuryStorage::set_balance(env, currency, balance);
+    emit_fees_collected(env, currency, amount, balance);
+}
+
+/// Appoint the treasurer (admin only)
+pub fn set_treasurer(
+    env: &Env,
+    admin: &Address,
+    treasurer: &Address,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    env.storage()
+        .instance()
+        .set(&symbol_short!("treasurer"), treasurer);
+    emit_treasurer_set(env, treasurer, admin);
+    Ok(())
+}
+
+/// Withdraw collected fees to `to` (admin or treasurer only)
+pub fn withdraw_fees(
+    env: &Env,
+    caller: &Address,
+    currency: &Address,
+    amount: i128,
+    to: &Address,
+) -> Result<(), QuickLendXError> {
+    caller.require_auth();
+    let is_treasurer = TreasuryStorage::get_treasurer(env).as_ref() == Some(caller);
+    if !is_treasurer && !BusinessVerificationStorage::is_admin(env, caller) {
+        return Err(QuickLendXError::Unauthorized);
+    }
+
+    if amount <= 0 {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    let balance = TreasuryStorage::get_balance(env, currency);
+    if amount > balance {
+        return Err(QuickLendXError::InsufficientFunds);
+    }
+
+    if !transfer_funds(env, &env.current_contract_address(), to, amount) {
+        return Err(QuickLendXError::InsufficientFunds);
+    }
+    TreasuryStorage::set_balance(env, currency, balance - amount);
+
+    log_fees_withdrawn(env, caller.clone(), amount);
+    emit_fees_withdrawn(env, currency, amount, to, caller);
+    Ok(())
+}
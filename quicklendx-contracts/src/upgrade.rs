@@ -0,0 +1,888 @@
+use crate::archive::{ArchiveStorage, ArchivedInvoice};
+use crate::attestation::{AttestationStorage, FinancialAttestation, ImportedAttestation};
+use crate::audit::AuditStorage;
+use crate::backup::BackupStorage;
+use crate::bid::BidStorage;
+use crate::compliance::{ComplianceStorage, WashFlag};
+use crate::config::ConfigStorage;
+use crate::errors::QuickLendXError;
+use crate::events::{emit_contract_upgraded, emit_storage_migrated};
+use crate::insurance::{ClaimStatus, InsuranceStorage, InvestmentInsurance};
+use crate::investment::{
+    FeeSnapshot, Investment, InvestmentStatus, InvestmentStorage, RiskSnapshot,
+};
+use crate::investor_kyc::{InvestorVerificationStatus, InvestorVerificationStorage};
+use crate::invoice::{Invoice, InvoiceDocument, InvoiceRating, InvoiceStatus, InvoiceStorage};
+use crate::jurisdiction::JurisdictionStorage;
+use crate::maturity::record_funding;
+use crate::payments::{Escrow, EscrowKind, EscrowStatus, EscrowStorage};
+use crate::storage::{self, migrate_legacy, DataKey};
+use crate::verification::{
+    require_admin, BusinessVerification, BusinessVerificationStatus, BusinessVerificationStorage,
+};
+use soroban_sdk::{
+    contracttype, symbol_short, vec, xdr::ToXdr, Address, BytesN, Env, IntoVal, Map, String,
+    Symbol, TryFromVal, Val, Vec,
+};
+
+/// Storage schema version written by this build of the contract.
+/// Bump it together with a new step in `migrate_step` whenever the layout of
+/// stored invoices, bids, escrows or other records changes.
+pub const CURRENT_SCHEMA_VERSION: u32 = 15;
+
+/// Get the schema version of the data in storage. Deployments that predate
+/// schema versioning report 0.
+pub fn get_schema_version(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&symbol_short!("schema_v"))
+        .unwrap_or(0)
+}
+
+/// Record the schema version of the data in storage
+pub fn set_schema_version(env: &Env, version: u32) {
+    env.storage()
+        .instance()
+        .set(&symbol_short!("schema_v"), &version);
+}
+
+/// Replace the contract code (admin only). Storage is kept as is; call
+/// `migrate` afterwards if the new code expects a newer schema.
+pub fn upgrade(
+    env: &Env,
+    admin: &Address,
+    new_wasm_hash: &BytesN<32>,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    env.deployer()
+        .update_current_contract_wasm(new_wasm_hash.clone());
+    emit_contract_upgraded(env, new_wasm_hash, admin);
+    Ok(())
+}
+
+/// Bring stored data up to `CURRENT_SCHEMA_VERSION` one version at a time
+/// (admin only). Returns the new schema version.
+pub fn migrate(env: &Env, admin: &Address) -> Result<u32, QuickLendXError> {
+    require_admin(env, admin)?;
+
+    let from_version = get_schema_version(env);
+    if from_version >= CURRENT_SCHEMA_VERSION {
+        return Err(QuickLendXError::OperationNotAllowed);
+    }
+
+    let mut version = from_version;
+    while version < CURRENT_SCHEMA_VERSION {
+        migrate_step(env, version);
+        version += 1;
+        set_schema_version(env, version);
+    }
+
+    emit_storage_migrated(env, from_version, version, admin);
+    Ok(version)
+}
+
+/// Transform storage from `version` to `version + 1`.
+/// v0 -> v1 introduced versioning and leaves record layouts unchanged.
+/// v1 -> v2 tags escrows with a kind and indexes all escrows per invoice.
+/// v2 -> v3 records insurance coverage on investments.
+/// v3 -> v4 builds the maturity ladder from invoices already funded.
+/// v4 -> v5 adds risk input snapshots to investments.
+/// v5 -> v6 moves invoices, bids, escrows and audit entries to persistent
+/// storage.
+/// v6 -> v7 records content hashes for the backups still listed.
+/// v7 -> v8 moves investments, backups and per-record indexes to typed keys
+/// in persistent storage.
+/// v8 -> v9 adds a category and tags to invoices, including those in backups.
+/// v9 -> v10 adds fee term snapshots to investments.
+/// v10 -> v11 adds document hashes to invoices, live, backed up and archived.
+/// v11 -> v12 adds an expiry to business verifications.
+/// v12 -> v13 adds due-date extensions to invoices, live and backed up.
+/// v13 -> v14 moves settlement, dispute, auction, insurance, treasury and
+/// claim market records to typed keys in persistent storage.
+/// v14 -> v15 moves the blacklist, affiliates, wash flags, attestations,
+/// insurance switches, jurisdictions and backstop providers to typed keys in
+/// persistent storage, each wash flag and attestation under its own key.
+fn migrate_step(env: &Env, version: u32) {
+    if version == 1 {
+        migrate_escrows_to_v2(env);
+    } else if version == 2 {
+        migrate_investments_to_v3(env);
+    } else if version == 3 {
+        migrate_maturity_ladder_to_v4(env);
+    } else if version == 4 {
+        migrate_investments_to_v5(env);
+    } else if version == 5 {
+        migrate_records_to_v6(env);
+    } else if version == 6 {
+        migrate_backup_hashes_to_v7(env);
+    } else if version == 7 {
+        migrate_keys_to_v8(env);
+    } else if version == 8 {
+        migrate_invoices_to_v9(env);
+    } else if version == 9 {
+        migrate_investments_to_v10(env);
+    } else if version == 10 {
+        migrate_invoices_to_v11(env);
+    } else if version == 11 {
+        migrate_verifications_to_v12(env);
+    } else if version == 12 {
+        migrate_invoices_to_v13(env);
+    } else if version == 13 {
+        migrate_keys_to_v14(env);
+    } else if version == 14 {
+        migrate_keys_to_v15(env);
+    }
+}
+
+/// Every invoice status, each with its own index of invoices
+fn all_statuses() -> [InvoiceStatus; 8] {
+    [
+        InvoiceStatus::Pending,
+        InvoiceStatus::Verified,
+        InvoiceStatus::Funded,
+        InvoiceStatus::Paid,
+        InvoiceStatus::Defaulted,
+        InvoiceStatus::Cancelled,
+        InvoiceStatus::Expired,
+        InvoiceStatus::Refinanced,
+    ]
+}
+
+/// Ids of every stored invoice, whatever its status
+fn all_invoice_ids(env: &Env) -> Vec<BytesN<32>> {
+    let mut ids = Vec::new(env);
+    for status in all_statuses().iter() {
+        ids.append(&InvoiceStorage::get_invoices_by_status(env, status));
+    }
+    ids
+}
+
+/// Escrow layout before v2, without a kind
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct EscrowV1 {
+    pub escrow_id: BytesN<32>,
+    pub invoice_id: BytesN<32>,
+    pub investor: Address,
+    pub business: Address,
+    pub amount: i128,
+    pub currency: Address,
+    pub created_at: u64,
+    pub status: EscrowStatus,
+}
+
+/// v1 kept a single invoice -> escrow mapping, so only the escrow it pointed
+/// at can be carried over; it becomes the invoice's one funding escrow.
+fn migrate_escrows_to_v2(env: &Env) {
+    for invoice_id in all_invoice_ids(env).iter() {
+        let old_key = (symbol_short!("escrow"), invoice_id.clone());
+        let escrow_id: Option<BytesN<32>> = env.storage().instance().get(&old_key);
+        let Some(escrow_id) = escrow_id else {
+            continue;
+        };
+        let old: Option<EscrowV1> = env.storage().instance().get(&escrow_id);
+        if let Some(old) = old {
+            let escrow = Escrow {
+                escrow_id: old.escrow_id,
+                invoice_id: old.invoice_id,
+                kind: EscrowKind::Funding,
+                investor: old.investor,
+                business: old.business,
+                amount: old.amount,
+                currency: old.currency,
+                created_at: old.created_at,
+                status: old.status,
+            };
+            EscrowStorage::update_escrow(env, &escrow);
+            EscrowStorage::set_escrows_for_invoice(env, &invoice_id, &vec![env, escrow_id]);
+        }
+        env.storage().instance().remove(&old_key);
+    }
+}
+
+/// Investment layout before v3, without insurance coverage
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct InvestmentV2 {
+    pub investment_id: BytesN<32>,
+    pub invoice_id: BytesN<32>,
+    pub investor: Address,
+    pub amount: i128,
+    pub funded_at: u64,
+    pub status: InvestmentStatus,
+}
+
+/// Investments made before v3 were never insured
+fn migrate_investments_to_v3(env: &Env) {
+    for invoice_id in all_invoice_ids(env).iter() {
+        for investment_id in InvestmentStorage::get_investments_for_invoice(env, &invoice_id).iter()
+        {
+            let old: Option<InvestmentV2> = env.storage().instance().get(&investment_id);
+            if let Some(old) = old {
+                let investment = InvestmentV3 {
+                    investment_id: old.investment_id,
+                    invoice_id: old.invoice_id,
+                    investor: old.investor,
+                    amount: old.amount,
+                    funded_at: old.funded_at,
+                    status: old.status,
+                    insurance: InvestmentInsurance::Uninsured,
+                };
+                env.storage().instance().set(&investment_id, &investment);
+            }
+        }
+    }
+}
+
+/// Count the funding of every open invoice towards its maturity. Only
+/// funded and partly funded (still verified) invoices carry funding.
+fn migrate_maturity_ladder_to_v4(env: &Env) {
+    for status in [InvoiceStatus::Verified, InvoiceStatus::Funded].iter() {
+        for invoice_id in InvoiceStorage::get_invoices_by_status(env, status).iter() {
+            if let Some(invoice) = InvoiceStorage::get_invoice(env, &invoice_id) {
+                record_funding(env, &invoice, invoice.funded_amount);
+            }
+        }
+    }
+}
+
+/// Investment layout before v5, without a risk input snapshot
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct InvestmentV3 {
+    pub investment_id: BytesN<32>,
+    pub invoice_id: BytesN<32>,
+    pub investor: Address,
+    pub amount: i128,
+    pub funded_at: u64,
+    pub status: InvestmentStatus,
+    pub insurance: InvestmentInsurance,
+}
+
+/// Risk inputs were not recorded for investments made before v5
+fn migrate_investments_to_v5(env: &Env) {
+    for invoice_id in all_invoice_ids(env).iter() {
+        for investment_id in InvestmentStorage::get_investments_for_invoice(env, &invoice_id).iter()
+        {
+            let old: Option<InvestmentV3> = env.storage().instance().get(&investment_id);
+            if let Some(old) = old {
+                let investment = InvestmentV9 {
+                    investment_id: old.investment_id,
+                    invoice_id: old.invoice_id,
+                    investor: old.investor,
+                    amount: old.amount,
+                    funded_at: old.funded_at,
+                    status: old.status,
+                    insurance: old.insurance,
+                    risk: RiskSnapshot::Unrecorded,
+                };
+                storage::set(env, &DataKey::Investment(investment_id), &investment);
+            }
+        }
+    }
+}
+
+/// Records are read from their old instance keys until moved, so this only
+/// relieves the instance entry; nothing is reshaped
+fn migrate_records_to_v6(env: &Env) {
+    for invoice_id in all_invoice_ids(env).iter() {
+        migrate_legacy(env, &DataKey::Invoice(invoice_id.clone()));
+        for bid_id in BidStorage::get_bids_for_invoice(env, &invoice_id).iter() {
+            migrate_legacy(env, &DataKey::Bid(bid_id));
+        }
+        for escrow_id in EscrowStorage::get_escrows_for_invoice(env, &invoice_id).iter() {
+            migrate_legacy(env, &DataKey::Escrow(escrow_id));
+        }
+    }
+    for audit_id in AuditStorage::get_all_audit_entries(env).iter() {
+        migrate_legacy(env, &DataKey::AuditEntry(audit_id));
+    }
+}
+
+/// Backups taken before v7 are hashed as they are now; archived backups are
+/// no longer listed and stay unhashed, so they no longer validate
+fn migrate_backup_hashes_to_v7(env: &Env) {
+    for backup_id in BackupStorage::get_all_backups(env).iter() {
+        if let Some(data) = BackupStorage::get_backup_data(env, &backup_id) {
+            let content_hash = BackupStorage::hash_backup_data(env, &data);
+            BackupStorage::set_content_hash(env, &backup_id, &content_hash);
+        }
+    }
+}
+
+/// Like v6, entries are read from their old keys until moved, so this only
+/// relieves the instance entry; nothing is reshaped
+fn migrate_keys_to_v8(env: &Env) {
+    for status in all_statuses().iter() {
+        migrate_legacy(env, &DataKey::StatusInvoices(status.clone()));
+    }
+    for invoice_id in all_invoice_ids(env).iter() {
+        if let Some(invoice) = InvoiceStorage::get_invoice(env, &invoice_id) {
+            migrate_legacy(env, &DataKey::BusinessInvoices(invoice.business));
+        }
+        migrate_legacy(env, &DataKey::BidList(invoice_id.clone()));
+        migrate_legacy(env, &DataKey::EscrowList(invoice_id.clone()));
+        migrate_legacy(env, &DataKey::InvestmentList(invoice_id.clone()));
+        migrate_legacy(env, &DataKey::AuditTrail(invoice_id.clone()));
+        for escrow_id in EscrowStorage::get_escrows_for_invoice(env, &invoice_id).iter() {
+            migrate_legacy(env, &DataKey::EscrowLedger(escrow_id));
+        }
+        for investment_id in InvestmentStorage::get_investments_for_invoice(env, &invoice_id).iter()
+        {
+            migrate_legacy(env, &DataKey::Investment(investment_id));
+        }
+    }
+    for backup_id in BackupStorage::get_all_backups(env).iter() {
+        migrate_legacy(env, &DataKey::Backup(backup_id.clone()));
+        migrate_legacy(env, &DataKey::BackupData(backup_id.clone()));
+        migrate_legacy(env, &DataKey::BackupHash(backup_id));
+    }
+}
+
+/// Invoice layout before v9, without a category or tags
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct InvoiceV8 {
+    pub id: BytesN<32>,
+    pub business: Address,
+    pub amount: i128,
+    pub currency: Address,
+    pub due_date: u64,
+    pub status: InvoiceStatus,
+    pub created_at: u64,
+    pub description: String,
+    pub funded_amount: i128,
+    pub funded_at: Option<u64>,
+    pub investor: Option<Address>,
+    pub investors: Vec<Address>,
+    pub settled_at: Option<u64>,
+    pub average_rating: Option<u32>,
+    pub total_ratings: u32,
+    pub ratings: Vec<InvoiceRating>,
+}
+
+impl InvoiceV8 {
+    fn into_v9(self, env: &Env) -> InvoiceV10 {
+        InvoiceV10 {
+            id: self.id,
+            business: self.business,
+            amount: self.amount,
+            currency: self.currency,
+            due_date: self.due_date,
+            status: self.status,
+            created_at: self.created_at,
+            description: self.description,
+            category: None,
+            tags: Vec::new(env),
+            funded_amount: self.funded_amount,
+            funded_at: self.funded_at,
+            investor: self.investor,
+            investors: self.investors,
+            settled_at: self.settled_at,
+            average_rating: self.average_rating,
+            total_ratings: self.total_ratings,
+            ratings: self.ratings,
+        }
+    }
+}
+
+/// Whether a stored record lacks a field, being still in a layout from before
+/// the field was added. Records written by newer code are left alone.
+fn lacks_field(env: &Env, raw: &Val, field: &Symbol) -> bool {
+    Map::<Symbol, Val>::try_from_val(env, raw)
+        .is_ok_and(|fields| !fields.contains_key(field.clone()))
+}
+
+/// Rewrite a record still lacking `field` with `convert`
+fn migrate_record<Old, New>(env: &Env, key: &DataKey, field: &Symbol, convert: fn(&Env, Old) -> New)
+where
+    Old: TryFromVal<Env, Val>,
+    New: IntoVal<Env, Val>,
+{
+    let raw: Option<Val> = storage::get(env, key);
+    if !raw.is_some_and(|raw| lacks_field(env, &raw, field)) {
+        return;
+    }
+    if let Some(old) = storage::get::<Old>(env, key) {
+        storage::set(env, key, &convert(env, old));
+    }
+}
+
+/// Ids of the invoices in a backup, whatever their layout
+fn backup_invoice_ids(env: &Env, backup_id: &BytesN<32>) -> Vec<BytesN<32>> {
+    let data: Vec<Map<Symbol, Val>> =
+        storage::get(env, &DataKey::BackupData(backup_id.clone())).unwrap_or_else(|| Vec::new(env));
+    let mut ids = Vec::new(env);
+    for fields in data.iter() {
+        let id = fields.get(symbol_short!("id"));
+        if let Some(id) = id.and_then(|id| BytesN::<32>::try_from_val(env, &id).ok()) {
+            ids.push_back(id);
+        }
+    }
+    ids
+}
+
+/// Convert every stored invoice still lacking `field` with `convert`: live
+/// invoices, backup data, and the staged invoices of a restore in progress.
+/// Backup data is converted and rehashed only when it still matches its
+/// recorded hash, so tampered backups keep failing validation.
+fn migrate_stored_invoices<Old, New>(env: &Env, field: Symbol, convert: fn(&Env, Old) -> New)
+where
+    Old: Clone + TryFromVal<Env, Val> + IntoVal<Env, Val>,
+    New: TryFromVal<Env, Val> + IntoVal<Env, Val>,
+{
+    for invoice_id in all_invoice_ids(env).iter() {
+        migrate_record(env, &DataKey::Invoice(invoice_id), &field, convert);
+    }
+    if let Some(backup_id) = BackupStorage::get_staged_backup(env) {
+        for invoice_id in backup_invoice_ids(env, &backup_id).iter() {
+            migrate_record(env, &DataKey::StagedInvoice(invoice_id), &field, convert);
+        }
+    }
+
+    for backup_id in BackupStorage::get_all_backups(env).iter() {
+        let key = DataKey::BackupData(backup_id.clone());
+        let Some(raw) = storage::get::<Vec<Val>>(env, &key) else {
+            continue;
+        };
+        if !raw
+            .first()
+            .is_some_and(|first| lacks_field(env, &first, &field))
+        {
+            continue;
+        }
+        let old_hash: BytesN<32> = env.crypto().sha256(&raw.to_xdr(env)).into();
+        if BackupStorage::get_content_hash(env, &backup_id) != Some(old_hash) {
+            continue;
+        }
+        let old: Vec<Old> = storage::get(env, &key).unwrap_or_else(|| Vec::new(env));
+        let mut invoices: Vec<New> = Vec::new(env);
+        for invoice in old.iter() {
+            invoices.push_back(convert(env, invoice));
+        }
+        storage::set(env, &key, &invoices);
+        let content_hash: BytesN<32> = env.crypto().sha256(&invoices.to_xdr(env)).into();
+        BackupStorage::set_content_hash(env, &backup_id, &content_hash);
+    }
+}
+
+/// Invoices from before v9 have no category or tags
+fn migrate_invoices_to_v9(env: &Env) {
+    migrate_stored_invoices(env, symbol_short!("tags"), |env, old: InvoiceV8| {
+        old.into_v9(env)
+    });
+}
+
+/// Investment layout before v10, without fee terms
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct InvestmentV9 {
+    pub investment_id: BytesN<32>,
+    pub invoice_id: BytesN<32>,
+    pub investor: Address,
+    pub amount: i128,
+    pub funded_at: u64,
+    pub status: InvestmentStatus,
+    pub insurance: InvestmentInsurance,
+    pub risk: RiskSnapshot,
+}
+
+/// Ids of the invoices whose investments may still be read: every live
+/// invoice and those archived by verified businesses
+fn invoices_with_investments(env: &Env) -> Vec<BytesN<32>> {
+    let mut ids = all_invoice_ids(env);
+    for business in BusinessVerificationStorage::get_verified_businesses(env).iter() {
+        ids.append(&ArchiveStorage::get_business_archive(env, &business));
+    }
+    ids
+}
+
+/// Fee terms were not recorded for investments made before v10, which
+/// settle under the current configuration
+fn migrate_investments_to_v10(env: &Env) {
+    let field = symbol_short!("fees");
+    for invoice_id in invoices_with_investments(env).iter() {
+        for investment_id in InvestmentStorage::get_investments_for_invoice(env, &invoice_id).iter()
+        {
+            let key = DataKey::Investment(investment_id);
+            migrate_record(env, &key, &field, |_, old: InvestmentV9| Investment {
+                investment_id: old.investment_id,
+                invoice_id: old.invoice_id,
+                investor: old.investor,
+                amount: old.amount,
+                funded_at: old.funded_at,
+                status: old.status,
+                insurance: old.insurance,
+                risk: old.risk,
+                fees: FeeSnapshot::Unrecorded,
+            });
+        }
+    }
+}
+
+/// Invoice layout before v11, without document hashes
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct InvoiceV10 {
+    pub id: BytesN<32>,
+    pub business: Address,
+    pub amount: i128,
+    pub currency: Address,
+    pub due_date: u64,
+    pub status: InvoiceStatus,
+    pub created_at: u64,
+    pub description: String,
+    pub category: Option<Symbol>,
+    pub tags: Vec<Symbol>,
+    pub funded_amount: i128,
+    pub funded_at: Option<u64>,
+    pub investor: Option<Address>,
+    pub investors: Vec<Address>,
+    pub settled_at: Option<u64>,
+    pub average_rating: Option<u32>,
+    pub total_ratings: u32,
+    pub ratings: Vec<InvoiceRating>,
+}
+
+impl InvoiceV10 {
+    fn into_v11(self, env: &Env) -> InvoiceV12 {
+        InvoiceV12 {
+            id: self.id,
+            business: self.business,
+            amount: self.amount,
+            currency: self.currency,
+            due_date: self.due_date,
+            status: self.status,
+            created_at: self.created_at,
+            description: self.description,
+            invoice_hash: None,
+            documents: Vec::new(env),
+            category: self.category,
+            tags: self.tags,
+            funded_amount: self.funded_amount,
+            funded_at: self.funded_at,
+            investor: self.investor,
+            investors: self.investors,
+            settled_at: self.settled_at,
+            average_rating: self.average_rating,
+            total_ratings: self.total_ratings,
+            ratings: self.ratings,
+        }
+    }
+}
+
+/// Archived invoice layout before v11, without document hashes
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct ArchivedInvoiceV10 {
+    pub id: BytesN<32>,
+    pub business: Address,
+    pub amount: i128,
+    pub currency: Address,
+    pub due_date: u64,
+    pub status: InvoiceStatus,
+    pub created_at: u64,
+    pub description: String,
+    pub funded_amount: i128,
+    pub funded_at: Option<u64>,
+    pub investors: Vec<Address>,
+    pub settled_at: Option<u64>,
+    pub average_rating: Option<u32>,
+    pub total_ratings: u32,
+    pub archived_at: u64,
+}
+
+impl ArchivedInvoiceV10 {
+    fn into_v11(self, env: &Env) -> ArchivedInvoice {
+        ArchivedInvoice {
+            id: self.id,
+            business: self.business,
+            amount: self.amount,
+            currency: self.currency,
+            due_date: self.due_date,
+            status: self.status,
+            created_at: self.created_at,
+            description: self.description,
+            invoice_hash: None,
+            documents: Vec::new(env),
+            funded_amount: self.funded_amount,
+            funded_at: self.funded_at,
+            investors: self.investors,
+            settled_at: self.settled_at,
+            average_rating: self.average_rating,
+            total_ratings: self.total_ratings,
+            archived_at: self.archived_at,
+        }
+    }
+}
+
+/// Invoices from before v11 have no document hashes. Archived invoices are
+/// found through the archives of verified businesses.
+fn migrate_invoices_to_v11(env: &Env) {
+    let field = symbol_short!("documents");
+    migrate_stored_invoices(env, field.clone(), |env, old: InvoiceV10| old.into_v11(env));
+    for business in BusinessVerificationStorage::get_verified_businesses(env).iter() {
+        for invoice_id in ArchiveStorage::get_business_archive(env, &business).iter() {
+            migrate_record(
+                env,
+                &DataKey::ArchivedInvoice(invoice_id),
+                &field,
+                |env, old: ArchivedInvoiceV10| old.into_v11(env),
+            );
+        }
+    }
+}
+
+/// Business verification layout before v12, without an expiry
+#[contracttype]
+pub(crate) struct BusinessVerificationV11 {
+    pub business: Address,
+    pub status: BusinessVerificationStatus,
+    pub verified_at: Option<u64>,
+    pub verified_by: Option<Address>,
+    pub kyc_data: String,
+    pub submitted_at: u64,
+    pub rejection_reason: Option<String>,
+}
+
+/// Verifications from before v12 never lapsed. Verified businesses get a
+/// full validity period from the migration rather than lapsing at once.
+fn migrate_verifications_to_v12(env: &Env) {
+    let field = Symbol::new(env, "expires_at");
+    let validity = BusinessVerificationStorage::get_kyc_validity(env);
+    let mut businesses = BusinessVerificationStorage::get_verified_businesses(env);
+    businesses.append(&BusinessVerificationStorage::get_pending_businesses(env));
+    businesses.append(&BusinessVerificationStorage::get_rejected_businesses(env));
+    for business in businesses.iter() {
+        let raw: Option<Val> = env.storage().instance().get(&business);
+        if !raw.is_some_and(|raw| lacks_field(env, &raw, &field)) {
+            continue;
+        }
+        let Some(old) = env
+            .storage()
+            .instance()
+            .get::<_, BusinessVerificationV11>(&business)
+        else {
+            continue;
+        };
+        let verified = matches!(old.status, BusinessVerificationStatus::Verified);
+        let verification = BusinessVerification {
+            business: old.business,
+            status: old.status,
+            verified_at: old.verified_at,
+            verified_by: old.verified_by,
+            expires_at: (verified && validity > 0)
+                .then(|| env.ledger().timestamp().saturating_add(validity)),
+            kyc_data: old.kyc_data,
+            submitted_at: old.submitted_at,
+            rejection_reason: old.rejection_reason,
+        };
+        env.storage().instance().set(&business, &verification);
+    }
+}
+
+/// Invoice layout before v13, without a due-date extension
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct InvoiceV12 {
+    pub id: BytesN<32>,
+    pub business: Address,
+    pub amount: i128,
+    pub currency: Address,
+    pub due_date: u64,
+    pub status: InvoiceStatus,
+    pub created_at: u64,
+    pub description: String,
+    pub invoice_hash: Option<BytesN<32>>,
+    pub documents: Vec<InvoiceDocument>,
+    pub category: Option<Symbol>,
+    pub tags: Vec<Symbol>,
+    pub funded_amount: i128,
+    pub funded_at: Option<u64>,
+    pub investor: Option<Address>,
+    pub investors: Vec<Address>,
+    pub settled_at: Option<u64>,
+    pub average_rating: Option<u32>,
+    pub total_ratings: u32,
+    pub ratings: Vec<InvoiceRating>,
+}
+
+impl InvoiceV12 {
+    fn into_v13(self, env: &Env) -> Invoice {
+        Invoice {
+            id: self.id,
+            business: self.business,
+            amount: self.amount,
+            currency: self.currency,
+            due_date: self.due_date,
+            extensions: Vec::new(env),
+            status: self.status,
+            created_at: self.created_at,
+            description: self.description,
+            invoice_hash: self.invoice_hash,
+            documents: self.documents,
+            category: self.category,
+            tags: self.tags,
+            funded_amount: self.funded_amount,
+            funded_at: self.funded_at,
+            investor: self.investor,
+            investors: self.investors,
+            settled_at: self.settled_at,
+            average_rating: self.average_rating,
+            total_ratings: self.total_ratings,
+            ratings: self.ratings,
+        }
+    }
+}
+
+/// Invoices from before v13 have had no due-date extension
+fn migrate_invoices_to_v13(env: &Env) {
+    let field = Symbol::new(env, "extensions");
+    migrate_stored_invoices(env, field, |env, old: InvoiceV12| old.into_v13(env));
+}
+
+/// Like v8, entries are read from their old keys until moved, so this only
+/// relieves the instance entry of the records it can still find; nothing is
+/// reshaped
+fn migrate_keys_to_v14(env: &Env) {
+    let mut currencies = ConfigStorage::get_supported_currencies(env);
+    for invoice_id in all_invoice_ids(env).iter() {
+        migrate_legacy(env, &DataKey::SettlementProgress(invoice_id.clone()));
+        migrate_legacy(env, &DataKey::AmountPaid(invoice_id.clone()));
+        migrate_legacy(env, &DataKey::Dispute(invoice_id.clone()));
+        migrate_legacy(env, &DataKey::Auction(invoice_id.clone()));
+        migrate_legacy(env, &DataKey::AuctionCommitments(invoice_id.clone()));
+        migrate_legacy(env, &DataKey::PriorityWindowEnd(invoice_id.clone()));
+        migrate_legacy(env, &DataKey::FundingDeadline(invoice_id.clone()));
+        for bid_id in BidStorage::get_bids_for_invoice(env, &invoice_id).iter() {
+            migrate_legacy(env, &DataKey::BidNegotiation(bid_id));
+        }
+        for investment_id in InvestmentStorage::get_investments_for_invoice(env, &invoice_id).iter()
+        {
+            migrate_legacy(env, &DataKey::InvestmentClaim(investment_id.clone()));
+            migrate_legacy(env, &DataKey::InsuranceRecovery(investment_id));
+        }
+        let Some(invoice) = InvoiceStorage::get_invoice(env, &invoice_id) else {
+            continue;
+        };
+        migrate_legacy(env, &DataKey::PriorityWindow(invoice.business.clone()));
+        migrate_legacy(
+            env,
+            &DataKey::ClaimableSettlement(invoice_id.clone(), invoice.business.clone()),
+        );
+        for investor in invoice.investors.iter() {
+            migrate_legacy(
+                env,
+                &DataKey::ClaimableSettlement(invoice_id.clone(), investor.clone()),
+            );
+            migrate_legacy(
+                env,
+                &DataKey::LastReturn(invoice.business.clone(), investor),
+            );
+        }
+        if !currencies.contains(&invoice.currency) {
+            currencies.push_back(invoice.currency);
+        }
+    }
+    for currency in currencies.iter() {
+        migrate_legacy(env, &DataKey::TreasuryBalance(currency));
+    }
+    for status in all_claim_statuses().iter() {
+        migrate_legacy(env, &DataKey::ClaimsByStatus(status.clone()));
+        for claim_id in InsuranceStorage::get_claims_by_status(env, status).iter() {
+            migrate_legacy(env, &DataKey::InsuranceClaim(claim_id));
+        }
+    }
+    migrate_legacy(env, &DataKey::ListedClaims);
+}
+
+/// Move the compliance, attestation, insurance, jurisdiction and backstop
+/// records to typed keys, splitting the lists of wash flags and of each
+/// business's attestations into one record per entry
+fn migrate_keys_to_v15(env: &Env) {
+    migrate_legacy(env, &DataKey::Blacklist);
+    for code in JurisdictionStorage::get_rule_codes(env).iter() {
+        migrate_legacy(env, &DataKey::JurisdictionRule(code));
+    }
+
+    let mut currencies = ConfigStorage::get_supported_currencies(env);
+    let mut investors = Vec::new(env);
+    for invoice_id in all_invoice_ids(env).iter() {
+        migrate_legacy(env, &DataKey::InsuranceEnabled(invoice_id.clone()));
+        let Some(invoice) = InvoiceStorage::get_invoice(env, &invoice_id) else {
+            continue;
+        };
+        for investor in invoice.investors.iter() {
+            if !investors.contains(&investor) {
+                investors.push_back(investor);
+            }
+        }
+        if !currencies.contains(&invoice.currency) {
+            currencies.push_back(invoice.currency);
+        }
+    }
+    for currency in currencies.iter() {
+        migrate_legacy(env, &DataKey::BackstopProviders(currency));
+    }
+    for status in [
+        InvestorVerificationStatus::Pending,
+        InvestorVerificationStatus::Verified,
+        InvestorVerificationStatus::Rejected,
+    ] {
+        investors.append(&InvestorVerificationStorage::get_investors_by_status(
+            env, status,
+        ));
+    }
+    for investor in investors.iter() {
+        migrate_legacy(env, &DataKey::Jurisdiction(investor));
+    }
+
+    let mut businesses = BusinessVerificationStorage::get_verified_businesses(env);
+    businesses.append(&BusinessVerificationStorage::get_pending_businesses(env));
+    businesses.append(&BusinessVerificationStorage::get_rejected_businesses(env));
+    for business in businesses.iter() {
+        migrate_legacy(env, &DataKey::Affiliates(business.clone()));
+        migrate_legacy(env, &DataKey::Jurisdiction(business.clone()));
+
+        let attest_key = (symbol_short!("attest"), business.clone());
+        let legacy_attestations: Option<Vec<FinancialAttestation>> =
+            env.storage().instance().get(&attest_key);
+        if let Some(attestations) = legacy_attestations {
+            for attestation in attestations.iter() {
+                AttestationStorage::add_attestation(env, &attestation);
+            }
+            env.storage().instance().remove(&attest_key);
+        }
+
+        let imported_key = (symbol_short!("att_ext"), business);
+        let legacy_imported: Option<Vec<ImportedAttestation>> =
+            env.storage().instance().get(&imported_key);
+        if let Some(imported) = legacy_imported {
+            for claim in imported.iter() {
+                AttestationStorage::add_imported(env, &claim);
+            }
+            env.storage().instance().remove(&imported_key);
+        }
+    }
+
+    let legacy_flags: Option<Vec<WashFlag>> =
+        env.storage().instance().get(&symbol_short!("wash_flg"));
+    if let Some(flags) = legacy_flags {
+        for flag in flags.iter() {
+            ComplianceStorage::add_wash_flag(env, &flag);
+        }
+        env.storage().instance().remove(&symbol_short!("wash_flg"));
+    }
+}
+
+/// Every insurance claim status, each with its own index of claims
+fn all_claim_statuses() -> [ClaimStatus; 6] {
+    [
+        ClaimStatus::Submitted,
+        ClaimStatus::UnderReview,
+        ClaimStatus::Approved,
+        ClaimStatus::Denied,
+        ClaimStatus::Appealed,
+        ClaimStatus::Paid,
+    ]
+}
@@ -1,7 +1,29 @@
-use soroban_sdk::{contracttype, symbol_short, vec, Address, Env, String, Symbol, Vec};
+use soroban_sdk::{
+    contracttype, symbol_short, vec, Address, BytesN, Env, String, Symbol, Vec,
+};
+use crate::compliance::check_not_blacklisted;
+use crate::config_history::record_config_change;
+use crate::config::{
+    check_description_length, check_kyc_data_length, check_rejection_reason_length,
+    require_supported_currency,
+};
+use crate::credit::check_credit_limit;
 use crate::errors::QuickLendXError;
+use crate::invoice::{Invoice, InvoiceStatus, InvoiceStorage};
+use crate::limits::{override_limit, LimitKind};
+use crate::oracle::to_reference;
+use crate::storage::{self, DataKey};
+use crate::events::{
+    emit_admin_initialized, emit_admin_transfer_accepted, emit_admin_transfer_started,
+};
+use crate::jurisdiction::check_upload;
+
+/// How long a business verification lasts unless the admin sets another
+/// validity period
+pub const DEFAULT_KYC_VALIDITY: u64 = 365 * 86_400;
 
 #[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub enum BusinessVerificationStatus {
     Pending,
     Verified,
@@ -9,16 +31,46 @@ pub enum BusinessVerificationStatus {
 }
 
 #[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct BusinessVerification {
     pub business: Address,
     pub status: BusinessVerificationStatus,
     pub verified_at: Option<u64>,
     pub verified_by: Option<Address>,
-    pub kyc_data: String, // Encrypted KYC data
+    pub expires_at: Option<u64>, // When the verification lapses, if ever
+    pub kyc_data: String,        // Encrypted KYC data
     pub submitted_at: u64,
     pub rejection_reason: Option<String>,
 }
 
+/// How thoroughly a business has been vetted, which bounds how much it may
+/// list. Businesses the admin has not placed in a tier are Basic.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum KYCTier {
+    Basic,
+    Standard,
+    Enhanced,
+}
+
+/// Caps on what a business in a tier may upload; 0 leaves a cap off
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TierLimits {
+    pub max_invoice_amount: i128,
+    pub max_active_invoices: u32, // Invoices pending, verified or funded at once
+}
+
+/// How many verifiers must approve an invoice of more than `threshold`
+/// before it is verified. A threshold of 0 lets one approval verify any
+/// invoice.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VerificationQuorum {
+    pub threshold: i128,
+    pub required: u32,
+}
+
 pub struct BusinessVerificationStorage;
 
 impl BusinessVerificationStorage {
@@ -27,6 +79,8 @@ impl BusinessVerificationStorage {
     const PENDING_BUSINESSES_KEY: &'static str = "pending_businesses";
     const REJECTED_BUSINESSES_KEY: &'static str = "rejected_businesses";
     const ADMIN_KEY: &'static str = "admin_address";
+    const PENDING_ADMIN_KEY: &'static str = "pending_admin";
+    const VERIFIERS_KEY: &'static str = "verifiers";
 
     pub fn store_verification(env: &Env, verification: &BusinessVerification) {
         env.storage()
@@ -73,6 +127,54 @@ impl BusinessVerificationStorage {
         Self::store_verification(env, verification);
     }
 
+    /// Get how long new and renewed verifications last, in seconds; 0 when
+    /// they do not lapse
+    pub fn get_kyc_validity(env: &Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("kyc_valid"))
+            .unwrap_or(DEFAULT_KYC_VALIDITY)
+    }
+
+    /// Get the tier a business has been placed in
+    pub fn get_kyc_tier(env: &Env, business: &Address) -> KYCTier {
+        storage::get(env, &DataKey::KYCTier(business.clone())).unwrap_or(KYCTier::Basic)
+    }
+
+    /// Get the caps on businesses in a tier; none apply until the admin sets
+    /// them
+    pub fn get_tier_limits(env: &Env, tier: KYCTier) -> TierLimits {
+        env.storage()
+            .instance()
+            .get(&(symbol_short!("tier_lim"), tier))
+            .unwrap_or(TierLimits {
+                max_invoice_amount: 0,
+                max_active_invoices: 0,
+            })
+    }
+
+    /// Get how many approvals high-value invoices need
+    pub fn get_verification_quorum(env: &Env) -> VerificationQuorum {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("vrf_quor"))
+            .unwrap_or(VerificationQuorum {
+                threshold: 0,
+                required: 1,
+            })
+    }
+
+    /// Get the verifiers who have approved an invoice, in order
+    pub fn get_invoice_approvals(env: &Env, invoice_id: &BytesN<32>) -> Vec<Address> {
+        storage::get(env, &DataKey::InvoiceApprovals(invoice_id.clone()))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    /// Forget the approvals given to an invoice, so it is verified afresh
+    pub fn clear_invoice_approvals(env: &Env, invoice_id: &BytesN<32>) {
+        storage::remove(env, &DataKey::InvoiceApprovals(invoice_id.clone()));
+    }
+
     pub fn is_business_verified(env: &Env, business: &Address) -> bool {
         if let Some(verification) = Self::get_verification(env, business) {
             matches!(verification.status, BusinessVerificationStatus::Verified)
@@ -173,6 +275,37 @@ impl BusinessVerificationStorage {
         env.storage().instance().get(&Self::ADMIN_KEY)
     }
 
+    pub fn get_pending_admin(env: &Env) -> Option<Address> {
+        env.storage().instance().get(&Self::PENDING_ADMIN_KEY)
+    }
+
+    pub fn set_pending_admin(env: &Env, admin: &Address) {
+        env.storage().instance().set(&Self::PENDING_ADMIN_KEY, admin);
+    }
+
+    pub fn clear_pending_admin(env: &Env) {
+        env.storage().instance().remove(&Self::PENDING_ADMIN_KEY);
+    }
+
+    /// Get the addresses the admin has allowed to verify businesses and
+    /// invoices
+    pub fn get_verifiers(env: &Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&Self::VERIFIERS_KEY)
+            .unwrap_or(vec![env])
+    }
+
+    fn set_verifiers(env: &Env, verifiers: &Vec<Address>) {
+        env.storage().instance().set(&Self::VERIFIERS_KEY, verifiers);
+    }
+
+    /// Whether an address may verify businesses and invoices. The admin
+    /// always may.
+    pub fn is_verifier(env: &Env, address: &Address) -> bool {
+        Self::is_admin(env, address) || Self::get_verifiers(env).contains(address)
+    }
+
     pub fn is_admin(env: &Env, address: &Address) -> bool {
         if let Some(admin) = Self::get_admin(env) {
             admin == *address
@@ -189,6 +322,7 @@ pub fn submit_kyc_application(
 ) -> Result<(), QuickLendXError> {
     // Only the business can submit their own KYC
     business.require_auth();
+    check_kyc_data_length(env, &kyc_data)?;
 
     // Check if business already has a verification record
     if let Some(existing_verification) =
@@ -199,7 +333,10 @@ pub fn submit_kyc_application(
                 return Err(QuickLendXError::KYCAlreadyPending);
             }
             BusinessVerificationStatus::Verified => {
-                return Err(QuickLendXError::KYCAlreadyVerified);
+                // Allow resubmission once the verification has lapsed
+                if !is_kyc_expired(env, &existing_verification) {
+                    return Err(QuickLendXError::KYCAlreadyVerified);
+                }
             }
             BusinessVerificationStatus::Rejected => {
                 // Allow resubmission if previously rejected
@@ -212,6 +349,7 @@ pub fn submit_kyc_application(
         status: BusinessVerificationStatus::Pending,
         verified_at: None,
         verified_by: None,
+        expires_at: None,
         kyc_data,
         submitted_at: env.ledger().timestamp(),
         rejection_reason: None,
@@ -224,14 +362,11 @@ pub fn submit_kyc_application(
 
 pub fn verify_business(
     env: &Env,
-    admin: &Address,
+    verifier: &Address,
     business: &Address,
 ) -> Result<(), QuickLendXError> {
-    // Only admin can verify businesses
-    admin.require_auth();
-    if !BusinessVerificationStorage::is_admin(env, admin) {
-        return Err(QuickLendXError::NotAdmin);
-    }
+    // Only the admin or a verifier can verify businesses
+    require_verifier(env, verifier)?;
 
     let mut verification = BusinessVerificationStorage::get_verification(env, business)
         .ok_or(QuickLendXError::KYCNotFound)?;
@@ -242,24 +377,209 @@ pub fn verify_business(
 
     verification.status = BusinessVerificationStatus::Verified;
     verification.verified_at = Some(env.ledger().timestamp());
-    verification.verified_by = Some(admin.clone());
+    verification.verified_by = Some(verifier.clone());
+    verification.expires_at = validity_end(env);
 
     BusinessVerificationStorage::update_verification(env, &verification);
-    emit_business_verified(env, business, admin);
+    emit_business_verified(env, business, verifier);
     Ok(())
 }
 
-pub fn reject_business(
+/// When a verification made now lapses, if it does
+fn validity_end(env: &Env) -> Option<u64> {
+    match BusinessVerificationStorage::get_kyc_validity(env) {
+        0 => None,
+        validity => Some(env.ledger().timestamp().saturating_add(validity)),
+    }
+}
+
+/// Whether a business's verification has lapsed
+pub fn is_kyc_expired(env: &Env, verification: &BusinessVerification) -> bool {
+    matches!(verification.status, BusinessVerificationStatus::Verified)
+        && verification
+            .expires_at
+            .is_some_and(|expires_at| env.ledger().timestamp() >= expires_at)
+}
+
+/// Renew a verified business's verification after a fresh review, whether
+/// or not it has lapsed (admin only). It lasts the current validity period
+/// from now.
+pub fn renew_kyc(env: &Env, admin: &Address, business: &Address) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    let mut verification = BusinessVerificationStorage::get_verification(env, business)
+        .ok_or(QuickLendXError::KYCNotFound)?;
+    if !matches!(verification.status, BusinessVerificationStatus::Verified) {
+        return Err(QuickLendXError::InvalidKYCStatus);
+    }
+
+    verification.verified_at = Some(env.ledger().timestamp());
+    verification.verified_by = Some(admin.clone());
+    verification.expires_at = validity_end(env);
+    BusinessVerificationStorage::update_verification(env, &verification);
+    emit_kyc_renewed(env, business, admin, verification.expires_at);
+    Ok(())
+}
+
+/// Set how long new and renewed verifications last, in seconds; 0 makes
+/// them permanent (admin only). Existing verifications keep their expiry.
+pub fn set_kyc_validity(env: &Env, admin: &Address, validity: u64) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    env.storage()
+        .instance()
+        .set(&symbol_short!("kyc_valid"), &validity);
+    record_config_change(env, "kyc_validity", validity, admin);
+    emit_kyc_validity_set(env, validity, admin);
+    Ok(())
+}
+
+/// Place a business in a tier, changing the caps on what it may upload next
+/// (admin only). The business must have applied for KYC.
+pub fn set_kyc_tier(
     env: &Env,
     admin: &Address,
     business: &Address,
-    reason: String,
+    tier: KYCTier,
 ) -> Result<(), QuickLendXError> {
-    // Only admin can reject businesses
-    admin.require_auth();
-    if !BusinessVerificationStorage::is_admin(env, admin) {
-        return Err(QuickLendXError::NotAdmin);
+    require_admin(env, admin)?;
+    if BusinessVerificationStorage::get_verification(env, business).is_none() {
+        return Err(QuickLendXError::KYCNotFound);
+    }
+    storage::set(env, &DataKey::KYCTier(business.clone()), &tier);
+    emit_kyc_tier_set(env, business, tier, admin);
+    Ok(())
+}
+
+/// Set the caps on businesses in a tier (admin only). Invoices already
+/// uploaded are not affected.
+pub fn set_tier_limits(
+    env: &Env,
+    admin: &Address,
+    tier: KYCTier,
+    limits: TierLimits,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    if limits.max_invoice_amount < 0 {
+        return Err(QuickLendXError::InvalidAmount);
+    }
+    env.storage()
+        .instance()
+        .set(&(symbol_short!("tier_lim"), tier), &limits);
+    let param = match tier {
+        KYCTier::Basic => "basic_tier_limits",
+        KYCTier::Standard => "standard_tier_limits",
+        KYCTier::Enhanced => "enhanced_tier_limits",
+    };
+    record_config_change(env, param, limits.clone(), admin);
+    emit_tier_limits_set(env, tier, &limits, admin);
+    Ok(())
+}
+
+/// Require invoices of more than `threshold` to be approved by `required`
+/// different verifiers, the admin included (admin only). Approvals already
+/// given still count.
+pub fn set_verification_quorum(
+    env: &Env,
+    admin: &Address,
+    quorum: VerificationQuorum,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    if quorum.threshold < 0 || quorum.required == 0 {
+        return Err(QuickLendXError::InvalidAmount);
     }
+    env.storage()
+        .instance()
+        .set(&symbol_short!("vrf_quor"), &quorum);
+    record_config_change(env, "verification_quorum", quorum.clone(), admin);
+    emit_verification_quorum_set(env, &quorum, admin);
+    Ok(())
+}
+
+/// Record a verifier's approval of a pending invoice, returning whether the
+/// invoice now has enough approvals to be verified
+pub fn approve_invoice(
+    env: &Env,
+    verifier: &Address,
+    invoice: &Invoice,
+) -> Result<bool, QuickLendXError> {
+    let quorum = BusinessVerificationStorage::get_verification_quorum(env);
+    if quorum.threshold == 0 || invoice.amount <= quorum.threshold {
+        return Ok(true);
+    }
+    let mut approvals = BusinessVerificationStorage::get_invoice_approvals(env, &invoice.id);
+    if approvals.contains(verifier) {
+        return Err(QuickLendXError::AlreadyApproved);
+    }
+    approvals.push_back(verifier.clone());
+    storage::set(
+        env,
+        &DataKey::InvoiceApprovals(invoice.id.clone()),
+        &approvals,
+    );
+    emit_invoice_approved(env, &invoice.id, verifier, approvals.len(), quorum.required);
+    Ok(approvals.len() >= quorum.required)
+}
+
+/// Reject an invoice amount above the cap of the business's tier, unless an
+/// approved limit request raises it for the invoice. The cap is in the
+/// reference currency once a price oracle is set.
+pub fn check_tier_amount(
+    env: &Env,
+    business: &Address,
+    currency: &Address,
+    amount: i128,
+    invoice_hash: &Option<BytesN<32>>,
+) -> Result<(), QuickLendXError> {
+    let tier = BusinessVerificationStorage::get_kyc_tier(env, business);
+    let limits = BusinessVerificationStorage::get_tier_limits(env, tier);
+    let max_amount = override_limit(env, business, invoice_hash, LimitKind::InvoiceAmount)
+        .unwrap_or(limits.max_invoice_amount);
+    if max_amount > 0 && to_reference(env, currency, amount)? > max_amount {
+        return Err(QuickLendXError::TierLimitExceeded);
+    }
+    Ok(())
+}
+
+/// Reject an upload of `amount` that would take the business past the caps
+/// of its tier, unless an approved limit request raises them for the invoice
+pub fn check_tier_limits(
+    env: &Env,
+    business: &Address,
+    currency: &Address,
+    amount: i128,
+    invoice_hash: &Option<BytesN<32>>,
+) -> Result<(), QuickLendXError> {
+    check_tier_amount(env, business, currency, amount, invoice_hash)?;
+    let tier = BusinessVerificationStorage::get_kyc_tier(env, business);
+    let limits = BusinessVerificationStorage::get_tier_limits(env, tier);
+    let max_active = override_limit(env, business, invoice_hash, LimitKind::ActiveInvoices)
+        .unwrap_or(limits.max_active_invoices as i128);
+    if max_active > 0 {
+        let active = InvoiceStorage::get_business_invoices(env, business)
+            .iter()
+            .filter_map(|invoice_id| InvoiceStorage::get_invoice(env, &invoice_id))
+            .filter(|invoice| {
+                matches!(
+                    invoice.status,
+                    InvoiceStatus::Pending | InvoiceStatus::Verified | InvoiceStatus::Funded
+                )
+            })
+            .count();
+        if active as i128 >= max_active {
+            return Err(QuickLendXError::TierLimitExceeded);
+        }
+    }
+    Ok(())
+}
+
+pub fn reject_business(
+    env: &Env,
+    verifier: &Address,
+    business: &Address,
+    reason: String,
+) -> Result<(), QuickLendXError> {
+    // Only the admin or a verifier can reject businesses
+    require_verifier(env, verifier)?;
+    check_rejection_reason_length(env, &reason)?;
 
     let mut verification = BusinessVerificationStorage::get_verification(env, business)
         .ok_or(QuickLendXError::KYCNotFound)?;
@@ -272,7 +592,7 @@ pub fn reject_business(
     verification.rejection_reason = Some(reason);
 
     BusinessVerificationStorage::update_verification(env, &verification);
-    emit_business_rejected(env, business, admin);
+    emit_business_rejected(env, business, verifier);
     Ok(())
 }
 
@@ -287,6 +607,99 @@ pub fn require_business_verification(env: &Env, business: &Address) -> Result<()
     if !BusinessVerificationStorage::is_business_verified(env, business) {
         return Err(QuickLendXError::BusinessNotVerified);
     }
+    let expired = BusinessVerificationStorage::get_verification(env, business)
+        .is_some_and(|verification| is_kyc_expired(env, &verification));
+    if expired {
+        return Err(QuickLendXError::KYCExpired);
+    }
+    Ok(())
+}
+
+/// Require `admin` to authorize the call and be the configured admin
+pub fn require_admin(env: &Env, admin: &Address) -> Result<(), QuickLendXError> {
+    admin.require_auth();
+    if !BusinessVerificationStorage::is_admin(env, admin) {
+        return Err(QuickLendXError::NotAdmin);
+    }
+    Ok(())
+}
+
+/// Require `verifier` to authorize the call and be the admin or one of the
+/// verifiers
+pub fn require_verifier(env: &Env, verifier: &Address) -> Result<(), QuickLendXError> {
+    verifier.require_auth();
+    if !BusinessVerificationStorage::is_verifier(env, verifier) {
+        return Err(QuickLendXError::NotVerifier);
+    }
+    Ok(())
+}
+
+/// Allow an address to verify and reject businesses and invoices (admin
+/// only). Adding an existing verifier does nothing.
+pub fn add_verifier(env: &Env, admin: &Address, verifier: &Address) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    let mut verifiers = BusinessVerificationStorage::get_verifiers(env);
+    if verifiers.contains(verifier) {
+        return Ok(());
+    }
+    verifiers.push_back(verifier.clone());
+    BusinessVerificationStorage::set_verifiers(env, &verifiers);
+    emit_verifier_added(env, verifier, admin);
+    Ok(())
+}
+
+/// Withdraw a verifier's role (admin only). Verifications it already made
+/// stand.
+pub fn remove_verifier(
+    env: &Env,
+    admin: &Address,
+    verifier: &Address,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    let mut verifiers = BusinessVerificationStorage::get_verifiers(env);
+    let index = verifiers
+        .first_index_of(verifier)
+        .ok_or(QuickLendXError::NotVerifier)?;
+    verifiers.remove(index);
+    BusinessVerificationStorage::set_verifiers(env, &verifiers);
+    emit_verifier_removed(env, verifier, admin);
+    Ok(())
+}
+
+/// Set the first admin (the admin being installed must authorize). Can only
+/// be called once; later changes go through `transfer_admin` and `accept_admin`.
+pub fn initialize_admin(env: &Env, admin: &Address) -> Result<(), QuickLendXError> {
+    admin.require_auth();
+    if BusinessVerificationStorage::get_admin(env).is_some() {
+        return Err(QuickLendXError::AlreadyInitialized);
+    }
+    BusinessVerificationStorage::set_admin(env, admin);
+    emit_admin_initialized(env, admin);
+    Ok(())
+}
+
+/// Nominate a new admin (current admin only). Takes effect once accepted.
+pub fn transfer_admin(
+    env: &Env,
+    admin: &Address,
+    new_admin: &Address,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+    BusinessVerificationStorage::set_pending_admin(env, new_admin);
+    emit_admin_transfer_started(env, admin, new_admin);
+    Ok(())
+}
+
+/// Accept a pending admin nomination (nominated address only)
+pub fn accept_admin(env: &Env, new_admin: &Address) -> Result<(), QuickLendXError> {
+    new_admin.require_auth();
+    if BusinessVerificationStorage::get_pending_admin(env).as_ref() != Some(new_admin) {
+        return Err(QuickLendXError::Unauthorized);
+    }
+    let previous_admin = BusinessVerificationStorage::get_admin(env);
+    BusinessVerificationStorage::set_admin(env, new_admin);
+    BusinessVerificationStorage::clear_pending_admin(env);
+    emit_admin_transfer_accepted(env, previous_admin, new_admin);
     Ok(())
 }
 
@@ -295,24 +708,146 @@ pub fn verify_invoice_data(
     env: &Env,
     business: &Address,
     amount: i128,
-    _currency: &Address,
+    currency: &Address,
     due_date: u64,
     description: &String,
 ) -> Result<(), QuickLendXError> {
     // First check if business is verified
     require_business_verification(env, business)?;
+    check_invoice_amount(amount)?;
+    check_invoice_currency(env, business, currency)?;
+    check_invoice_due_date(env, due_date)?;
+    check_invoice_description(env, description)
+}
+
+/// Every check an invoice upload must pass, with the input each one covers.
+/// Uploads stop at the first failure; `validate_invoice_input` reports all.
+fn invoice_upload_checks(
+    env: &Env,
+    business: &Address,
+    amount: i128,
+    currency: &Address,
+    due_date: u64,
+    description: &String,
+    invoice_hash: &Option<BytesN<32>>,
+) -> [(&'static str, Result<(), QuickLendXError>); 8] {
+    [
+        ("blacklist", check_not_blacklisted(env, business)),
+        ("business", require_business_verification(env, business)),
+        ("amount", check_invoice_amount(amount)),
+        ("currency", check_invoice_currency(env, business, currency)),
+        ("due_date", check_invoice_due_date(env, due_date)),
+        ("description", check_invoice_description(env, description)),
+        ("credit_limit", check_credit_limit(env, business, currency, amount, invoice_hash)),
+        ("tier_limits", check_tier_limits(env, business, currency, amount, invoice_hash)),
+    ]
+}
 
+/// Check an invoice upload, returning the first check it fails
+pub fn check_invoice_upload(
+    env: &Env,
+    business: &Address,
+    amount: i128,
+    currency: &Address,
+    due_date: u64,
+    description: &String,
+    invoice_hash: &Option<BytesN<32>>,
+) -> Result<(), QuickLendXError> {
+    let checks = invoice_upload_checks(
+        env,
+        business,
+        amount,
+        currency,
+        due_date,
+        description,
+        invoice_hash,
+    );
+    for (_, result) in checks {
+        result?;
+    }
+    Ok(())
+}
+
+fn check_invoice_amount(amount: i128) -> Result<(), QuickLendXError> {
     if amount <= 0 {
         return Err(QuickLendXError::InvalidAmount);
     }
-    let current_timestamp = env.ledger().timestamp();
-    if due_date <= current_timestamp {
+    Ok(())
+}
+
+fn check_invoice_currency(
+    env: &Env,
+    business: &Address,
+    currency: &Address,
+) -> Result<(), QuickLendXError> {
+    require_supported_currency(env, currency)?;
+    check_upload(env, business, currency)
+}
+
+fn check_invoice_due_date(env: &Env, due_date: u64) -> Result<(), QuickLendXError> {
+    if due_date <= env.ledger().timestamp() {
         return Err(QuickLendXError::InvoiceDueDateInvalid);
     }
+    Ok(())
+}
+
+fn check_invoice_description(env: &Env, description: &String) -> Result<(), QuickLendXError> {
     if description.len() == 0 {
         return Err(QuickLendXError::InvalidDescription);
     }
-    Ok(())
+    check_description_length(env, description)
+}
+
+/// Pass/fail result for a single invoice input field
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FieldValidation {
+    pub field: Symbol,
+    pub valid: bool,
+    pub error: Option<Symbol>, // Error code symbol when the field is invalid
+}
+
+/// Per-field results of validating an invoice upload without submitting it
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InvoiceInputValidation {
+    pub valid: bool,
+    pub fields: Vec<FieldValidation>,
+}
+
+/// Run every upload check on invoice input and report each one separately,
+/// instead of stopping at the first failure like `check_invoice_upload`
+pub fn validate_invoice_input(
+    env: &Env,
+    business: &Address,
+    amount: i128,
+    currency: &Address,
+    due_date: u64,
+    description: &String,
+) -> InvoiceInputValidation {
+    let results = invoice_upload_checks(
+        env,
+        business,
+        amount,
+        currency,
+        due_date,
+        description,
+        &None,
+    );
+
+    let mut validation = InvoiceInputValidation {
+        valid: true,
+        fields: Vec::new(env),
+    };
+    for (field, result) in results {
+        validation.valid &= result.is_ok();
+        validation.fields.push_back(FieldValidation {
+            field: Symbol::new(env, field),
+            valid: result.is_ok(),
+            error: result.err().map(Symbol::from),
+        });
+    }
+    validation
 }
 
 // Event emission functions (from main)
@@ -336,3 +871,65 @@ fn emit_business_rejected(env: &Env, business: &Address, admin: &Address) {
         (business.clone(), admin.clone(), env.ledger().timestamp()),
     );
 }
+
+fn emit_kyc_renewed(env: &Env, business: &Address, admin: &Address, expires_at: Option<u64>) {
+    env.events().publish(
+        (symbol_short!("kyc_renew"),),
+        (business.clone(), admin.clone(), expires_at),
+    );
+}
+
+fn emit_kyc_validity_set(env: &Env, validity: u64, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("kyc_valid"),),
+        (validity, admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+fn emit_kyc_tier_set(env: &Env, business: &Address, tier: KYCTier, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("kyc_tier"),),
+        (business.clone(), tier, admin.clone()),
+    );
+}
+
+fn emit_tier_limits_set(env: &Env, tier: KYCTier, limits: &TierLimits, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("tier_lim"),),
+        (tier, limits.clone(), admin.clone()),
+    );
+}
+
+fn emit_verifier_added(env: &Env, verifier: &Address, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("vrf_add"),),
+        (verifier.clone(), admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+fn emit_verifier_removed(env: &Env, verifier: &Address, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("vrf_rem"),),
+        (verifier.clone(), admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+fn emit_verification_quorum_set(env: &Env, quorum: &VerificationQuorum, admin: &Address) {
+    env.events().publish(
+        (symbol_short!("vrf_quor"),),
+        (quorum.clone(), admin.clone(), env.ledger().timestamp()),
+    );
+}
+
+fn emit_invoice_approved(
+    env: &Env,
+    invoice_id: &BytesN<32>,
+    verifier: &Address,
+    approvals: u32,
+    required: u32,
+) {
+    env.events().publish(
+        (symbol_short!("inv_appr"),),
+        (invoice_id.clone(), verifier.clone(), approvals, required),
+    );
+}
@@ -0,0 +1,117 @@
+use crate::audit::log_invoice_status_change;
+use crate::bid::{BidStatus, BidStorage};
+use crate::errors::QuickLendXError;
+use crate::events::{emit_escrow_refunded, emit_invoice_reconciled};
+use crate::investment::{InvestmentStatus, InvestmentStorage};
+use crate::invoice::{Invoice, InvoiceStatus, InvoiceStorage};
+use crate::maturity::release_funding;
+use crate::reverse_factoring::restore_credit_line;
+use crate::payments::{refund_escrow, EscrowKind, EscrowStatus, EscrowStorage};
+use crate::stop_loss::record_unwound;
+use crate::verification::require_admin;
+use soroban_sdk::{Address, BytesN, Env, Vec};
+
+/// A Funded invoice is backed when its funding escrows, held or released to
+/// the business, cover the funded amount. Missing or refunded escrows mean
+/// the funding never reached, or left, the protocol.
+fn is_escrow_backed(env: &Env, invoice: &Invoice) -> bool {
+    let mut backed_amount = 0i128;
+    for escrow in EscrowStorage::get_escrows_by_kind(env, &invoice.id, &EscrowKind::Funding).iter()
+    {
+        if escrow.status != EscrowStatus::Refunded {
+            backed_amount += escrow.amount;
+        }
+    }
+    backed_amount > 0 && backed_amount >= invoice.funded_amount
+}
+
+/// List Funded invoices whose escrow is missing or has been refunded
+pub fn get_unbacked_funded_invoices(env: &Env) -> Vec<BytesN<32>> {
+    let mut unbacked = Vec::new(env);
+    for invoice_id in InvoiceStorage::get_invoices_by_status(env, &InvoiceStatus::Funded).iter() {
+        if let Some(invoice) = InvoiceStorage::get_invoice(env, &invoice_id) {
+            if !is_escrow_backed(env, &invoice) {
+                unbacked.push_back(invoice_id);
+            }
+        }
+    }
+    unbacked
+}
+
+/// Return an unbacked Funded invoice to Verified (admin only).
+/// Funding escrows still held are refunded to their investors, and its
+/// investments and accepted bids are withdrawn so the invoice can be funded
+/// again from scratch. Invoices whose funding already reached the business
+/// cannot be reconciled.
+pub fn reconcile_funded_invoice(
+    env: &Env,
+    admin: &Address,
+    invoice_id: &BytesN<32>,
+) -> Result<(), QuickLendXError> {
+    require_admin(env, admin)?;
+
+    let mut invoice =
+        InvoiceStorage::get_invoice(env, invoice_id).ok_or(QuickLendXError::InvoiceNotFound)?;
+    if invoice.status != InvoiceStatus::Funded {
+        return Err(QuickLendXError::InvalidStatus);
+    }
+    if is_escrow_backed(env, &invoice) {
+        return Err(QuickLendXError::OperationNotAllowed);
+    }
+    let funding = EscrowStorage::get_escrows_by_kind(env, invoice_id, &EscrowKind::Funding);
+    if funding
+        .iter()
+        .any(|escrow| matches!(escrow.status, EscrowStatus::Released | EscrowStatus::Split))
+    {
+        return Err(QuickLendXError::OperationNotAllowed);
+    }
+
+    for escrow in funding.iter() {
+        if escrow.status == EscrowStatus::Held {
+            let escrow = refund_escrow(env, &escrow.escrow_id, admin)?;
+            emit_escrow_refunded(
+                env,
+                &escrow.escrow_id,
+                invoice_id,
+                &escrow.investor,
+                escrow.amount,
+                admin,
+            );
+        }
+    }
+
+    for investment_id in InvestmentStorage::get_investments_for_invoice(env, invoice_id).iter() {
+        if let Some(mut investment) = InvestmentStorage::get_investment(env, &investment_id) {
+            if investment.status == InvestmentStatus::Active {
+                investment.status = InvestmentStatus::Withdrawn;
+                InvestmentStorage::update_investment(env, &investment);
+                record_unwound(env, &investment);
+            }
+        }
+    }
+    for bid_id in BidStorage::get_bids_for_invoice(env, invoice_id).iter() {
+        if let Some(mut bid) = BidStorage::get_bid(env, &bid_id) {
+            if bid.status == BidStatus::Accepted {
+                bid.status = BidStatus::Withdrawn;
+                BidStorage::update_bid(env, &bid);
+            }
+        }
+    }
+
+    InvoiceStorage::remove_from_status_invoices(env, &InvoiceStatus::Funded, invoice_id);
+    release_funding(env, &invoice);
+    restore_credit_line(env, &invoice);
+    invoice.reset_funding(env);
+    InvoiceStorage::update_invoice(env, &invoice);
+    InvoiceStorage::add_to_status_invoices(env, &InvoiceStatus::Verified, invoice_id);
+
+    log_invoice_status_change(
+        env,
+        invoice_id.clone(),
+        admin.clone(),
+        InvoiceStatus::Funded,
+        InvoiceStatus::Verified,
+    );
+    emit_invoice_reconciled(env, invoice_id, admin);
+    Ok(())
+}